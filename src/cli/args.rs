@@ -1,5 +1,7 @@
 use clap::{Parser, Subcommand, ValueEnum};
 
+use crate::services::graph_export::ExportFormat;
+
 /// 存储方式配置
 #[derive(Debug, Clone, ValueEnum)]
 pub enum StorageMode {
@@ -17,6 +19,49 @@ impl Default for StorageMode {
     }
 }
 
+/// 日志输出格式
+#[derive(Debug, Clone, Copy, Default, ValueEnum)]
+pub enum LogFormat {
+    /// 人类可读格式
+    #[default]
+    Pretty,
+    /// 每行一个JSON对象
+    Json,
+}
+
+/// 报告输出格式
+#[derive(Debug, Clone, Copy, Default, ValueEnum)]
+pub enum ReportFormat {
+    /// 可读文本
+    #[default]
+    Text,
+    /// JSON格式
+    Json,
+    /// SARIF 2.1.0格式
+    Sarif,
+}
+
+/// 没有SARIF表示的报告的输出格式
+#[derive(Debug, Clone, Copy, Default, ValueEnum)]
+pub enum TextOrJson {
+    /// 可读文本
+    #[default]
+    Text,
+    /// JSON格式
+    Json,
+}
+
+/// 终端输出中位置链接对应的编辑器
+#[derive(Debug, Clone, Copy, ValueEnum)]
+pub enum EditorKind {
+    /// vscode://file/<path>:<line>
+    Vscode,
+    /// idea://open?file=<path>&line=<line>
+    Idea,
+    /// MacVim 的 mvim://open?url=file://<path>&line=<line>
+    Vim,
+}
+
 /// CodeGraph CLI - Analyze code dependencies and generate code graphs
 #[derive(Parser, Debug)]
 #[clap(author, version, about, long_about = None)]
@@ -29,6 +74,19 @@ pub struct Cli {
     #[clap(long, value_enum, default_value = "json")]
     pub storage_mode: StorageMode,
 
+    /// Log output format; the level is taken from RUST_LOG when set
+    #[clap(long, value_enum, default_value = "pretty", global = true)]
+    pub log_format: LogFormat,
+
+    /// Print function locations in text output as clickable editor links
+    #[clap(long, value_enum, global = true)]
+    pub editor: Option<EditorKind>,
+
+    /// External language analyzer to load (executable, or shared library with
+    /// `--features dynamic-analyzers`); repeatable
+    #[clap(long = "analyzer", value_parser, global = true)]
+    pub analyzers: Vec<String>,
+
     #[clap(subcommand)]
     pub command: Commands,
 }
@@ -43,6 +101,19 @@ pub enum Commands {
         /// Storage mode override for this command
         #[clap(long, value_enum)]
         storage_mode: Option<StorageMode>,
+
+        /// Server configuration file (TOML)
+        #[clap(long, value_parser)]
+        config: Option<String>,
+
+        /// Allowed CORS origin (repeatable); overrides `cors.allowed_origins` from the config
+        #[clap(long = "cors-origin", value_parser)]
+        cors_origins: Vec<String>,
+
+        /// Shell command run after every build or incremental update, with the
+        /// build summary as JSON on stdin (repeatable)
+        #[clap(long = "hook", value_parser)]
+        hooks: Vec<String>,
     },
     /// Vectorize code blocks and save to Qdrant
     Vectorize {
@@ -58,4 +129,243 @@ pub enum Commands {
         #[clap(long, value_parser, default_value = "http://localhost:6334")]
         qdrant_url: String,
     },
-}
\ No newline at end of file
+    /// Report structural smells: long functions, many parameters, deep nesting
+    Lint {
+        /// Path to the directory (or file) to lint
+        #[clap(long, value_parser, default_value = ".")]
+        path: String,
+
+        /// Maximum number of lines per function
+        #[clap(long, value_parser, default_value_t = 80)]
+        max_lines: usize,
+
+        /// Maximum number of parameters per function
+        #[clap(long, value_parser, default_value_t = 5)]
+        max_params: usize,
+
+        /// Maximum control-flow nesting depth
+        #[clap(long, value_parser, default_value_t = 4)]
+        max_nesting: usize,
+
+        /// Also build the call graph and report dead-code and cycle findings
+        #[clap(long, action)]
+        graph_rules: bool,
+
+        /// Baseline file of known findings; only findings missing from it are reported
+        #[clap(long, value_parser)]
+        baseline: Option<String>,
+
+        /// Write the current findings to the --baseline file instead of filtering by it
+        #[clap(long, action, requires = "baseline")]
+        write_baseline: bool,
+
+        /// Severity policy (YAML); defaults to codegraph-policy.yaml in the linted directory when present.
+        /// The command fails when a finding has error severity
+        #[clap(long, value_parser)]
+        policy: Option<String>,
+
+        /// Output format
+        #[clap(long, value_enum, default_value = "text")]
+        format: ReportFormat,
+    },
+    /// Suggest moving functions/modules based on cross-module call coupling
+    Refactor {
+        /// Path to the directory to analyze
+        #[clap(long, value_parser, default_value = ".")]
+        path: String,
+
+        /// Minimum number of calls with the target module
+        #[clap(long, value_parser, default_value_t = 2)]
+        min_calls: usize,
+
+        /// Minimum share of the function's coupling that goes to the target module
+        #[clap(long, value_parser, default_value_t = 0.6)]
+        min_ratio: f64,
+
+        /// Output format
+        #[clap(long, value_enum, default_value = "text")]
+        format: TextOrJson,
+    },
+    /// Find functions with many callers but no test coverage, from an lcov or Cobertura report
+    Coverage {
+        /// Coverage report (lcov tracefile or Cobertura XML)
+        #[clap(value_parser)]
+        report: String,
+
+        /// Path to the directory the report was produced for
+        #[clap(long, value_parser, default_value = ".")]
+        path: String,
+
+        /// Minimum number of distinct callers for an uncovered function to be reported
+        #[clap(long, value_parser, default_value_t = 3)]
+        min_fan_in: usize,
+
+        /// Output format
+        #[clap(long, value_enum, default_value = "text")]
+        format: TextOrJson,
+    },
+    /// List unresolved calls grouped by callee name, with candidate functions for each
+    UnresolvedCalls {
+        /// Path to the directory to analyze
+        #[clap(long, value_parser, default_value = ".")]
+        path: String,
+
+        /// Only report callee names with at least this many unresolved calls
+        #[clap(long, value_parser, default_value_t = 1)]
+        min_count: usize,
+
+        /// Maximum number of callee names to report
+        #[clap(long, value_parser)]
+        limit: Option<usize>,
+
+        /// Output format
+        #[clap(long, value_enum, default_value = "text")]
+        format: TextOrJson,
+    },
+    /// Export the call graph (json, dot, mermaid, d2, plantuml, gexf, gephi-stream, csv, ndjson, protobuf, obsidian, parquet)
+    Export {
+        /// Path to the directory to analyze
+        #[clap(long, value_parser, default_value = ".")]
+        path: String,
+
+        /// Output format
+        #[clap(long, value_enum, default_value = "gexf")]
+        format: ExportFormat,
+
+        /// Output file (a directory for csv, obsidian and parquet); prints to stdout when omitted
+        #[clap(long, short, value_parser)]
+        output: Option<String>,
+    },
+    /// Generate a static HTML report (module index, function pages, metrics dashboards)
+    Report {
+        /// Output directory for the site
+        #[clap(value_parser)]
+        dir: String,
+
+        /// Path to the directory to analyze
+        #[clap(long, value_parser, default_value = ".")]
+        path: String,
+    },
+    /// Summarize the local data directory: projects, snapshots, disk usage and vector collections
+    AuditStorage {
+        /// Storage directory; `.codegraph_db` in the current directory when omitted
+        #[clap(long, value_parser)]
+        dir: Option<String>,
+
+        /// Delete the projects matched by the retention rules
+        #[clap(long, action)]
+        prune: bool,
+
+        /// Retention rule: projects last built longer ago than this (e.g. "30d")
+        #[clap(long, value_parser)]
+        max_age: Option<String>,
+
+        /// Retention rule: keep only this many most recently built projects per storage root
+        #[clap(long, value_parser)]
+        keep_last: Option<usize>,
+
+        /// Retention rule: projects whose registered source directory no longer exists
+        #[clap(long, action)]
+        missing_source: bool,
+
+        /// Qdrant server to report vector collection sizes from
+        #[clap(long, value_parser)]
+        qdrant_url: Option<String>,
+
+        /// Output format
+        #[clap(long, value_enum, default_value = "text")]
+        format: TextOrJson,
+    },
+    /// Rewrite stored graphs and drop data left behind by deleted functions and files, reporting reclaimed space
+    CompactStorage {
+        /// Storage directory; `.codegraph_db` in the current directory when omitted
+        #[clap(long, value_parser)]
+        dir: Option<String>,
+
+        /// Also delete vectors whose source file no longer exists from this Qdrant collection
+        #[clap(long, value_parser, requires = "qdrant_url")]
+        collection: Option<String>,
+
+        /// Qdrant server URL
+        #[clap(long, value_parser)]
+        qdrant_url: Option<String>,
+
+        /// Output format
+        #[clap(long, value_enum, default_value = "text")]
+        format: TextOrJson,
+    },
+    /// Index a pull request by applying its diff to the stored graph of the base branch
+    PrIndex {
+        /// Path to the project directory; the head revision must be checked out there
+        #[clap(long, value_parser, default_value = ".")]
+        path: String,
+
+        /// Base branch or revision the pull request merges into
+        #[clap(long, value_parser, default_value = "main")]
+        base: String,
+
+        /// Head revision of the pull request
+        #[clap(long, value_parser, default_value = "HEAD")]
+        head: String,
+
+        /// Pull request number; taken from GITHUB_REF or CI_MERGE_REQUEST_IID when omitted
+        #[clap(long, value_parser)]
+        pr: Option<u64>,
+
+        /// Storage directory; `.codegraph_db` in the current directory when omitted
+        #[clap(long, value_parser)]
+        storage_dir: Option<String>,
+
+        /// Also vectorize the changed files into this Qdrant collection, tagged with the pull request number
+        #[clap(long, value_parser)]
+        collection: Option<String>,
+
+        /// Qdrant server URL
+        #[clap(long, value_parser, default_value = "http://localhost:6334")]
+        qdrant_url: String,
+    },
+    /// Generate a synthetic repository for scalability testing
+    #[clap(hide = true)]
+    GenerateRepo {
+        /// Output directory
+        #[clap(long, value_parser)]
+        path: String,
+
+        /// Number of source files
+        #[clap(long, value_parser, default_value_t = 100)]
+        files: usize,
+
+        /// Functions per file
+        #[clap(long, value_parser, default_value_t = 10)]
+        functions_per_file: usize,
+
+        /// Average number of calls made by each function
+        #[clap(long, value_parser, default_value_t = 2.0)]
+        calls_per_function: f64,
+
+        /// Languages to generate, assigned to files round-robin
+        #[clap(long, value_parser, value_delimiter = ',', default_value = "rust")]
+        languages: Vec<String>,
+
+        /// Random seed; the same arguments and seed produce the same repository
+        #[clap(long, value_parser, default_value_t = 42)]
+        seed: u64,
+    },
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sarif_only_for_commands_that_produce_it() {
+        let parse = |args: &[&str]| Cli::try_parse_from(["codegraph"].iter().chain(args));
+        assert!(parse(&["lint", "--format", "sarif"]).is_ok());
+        assert!(parse(&["refactor", "--format", "json"]).is_ok());
+        assert!(parse(&["refactor", "--format", "sarif"]).is_err());
+        assert!(parse(&["coverage", "lcov.info", "--format", "sarif"]).is_err());
+        assert!(parse(&["audit-storage", "--format", "sarif"]).is_err());
+        assert!(parse(&["unresolved-calls", "--format", "sarif"]).is_err());
+        assert!(parse(&["compact-storage", "--format", "sarif"]).is_err());
+    }
+}
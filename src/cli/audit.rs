@@ -0,0 +1,180 @@
+use std::path::PathBuf;
+
+use chrono::Utc;
+use qdrant_client::Qdrant;
+use qdrant_client::config::QdrantConfig;
+use serde::Serialize;
+use tracing::{info, warn};
+
+use super::args::TextOrJson;
+use crate::storage::{PersistenceManager, RetentionRules, StorageAudit};
+
+/// Qdrant 中的向量集合
+#[derive(Debug, Serialize)]
+pub struct VectorCollection {
+    pub name: String,
+    pub points: Option<u64>,
+    pub indexed_vectors: Option<u64>,
+    pub segments: u64,
+}
+
+#[derive(Serialize)]
+struct AuditReport<'a> {
+    #[serde(flatten)]
+    storage: &'a StorageAudit,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    vector_collections: Vec<VectorCollection>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pruned: Option<usize>,
+}
+
+pub async fn run_audit_storage(
+    dir: Option<String>,
+    rules: RetentionRules,
+    prune: bool,
+    qdrant_url: Option<String>,
+    format: TextOrJson,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let has_rules = rules.max_age.is_some() || rules.keep_last.is_some() || rules.missing_source;
+    if prune && !has_rules {
+        return Err("--prune needs a retention rule: --max-age, --keep-last or --missing-source".into());
+    }
+    let dir = dir.map(PathBuf::from).unwrap_or_else(PersistenceManager::default_base_dir);
+    if !dir.is_dir() {
+        return Err(format!("Storage directory {} does not exist", dir.display()).into());
+    }
+    info!("Auditing storage in {}", dir.display());
+
+    let audit = StorageAudit::collect(&dir, &rules, Utc::now())?;
+    // 向量只保存在 Qdrant 中，本地目录里没有
+    let vector_collections = match &qdrant_url {
+        Some(url) => vector_collections(url).await?,
+        None => vec![],
+    };
+    let pruned = if prune { Some(audit.prune()?) } else { None };
+
+    match format {
+        TextOrJson::Text => print_text(&audit, &vector_collections, qdrant_url.as_deref(), pruned, has_rules),
+        TextOrJson::Json => {
+            let report = AuditReport { storage: &audit, vector_collections, pruned };
+            println!("{}", serde_json::to_string_pretty(&report)?);
+        }
+    }
+    Ok(())
+}
+
+async fn vector_collections(url: &str) -> Result<Vec<VectorCollection>, Box<dyn std::error::Error>> {
+    let client = Qdrant::new(QdrantConfig::from_url(url))?;
+    let mut names: Vec<String> = client.list_collections().await?.collections.into_iter().map(|c| c.name).collect();
+    names.sort();
+    let mut collections = vec![];
+    for name in names {
+        match client.collection_info(name.as_str()).await {
+            Ok(response) => {
+                let info = response.result.unwrap_or_default();
+                collections.push(VectorCollection {
+                    name,
+                    points: info.points_count,
+                    indexed_vectors: info.indexed_vectors_count,
+                    segments: info.segments_count,
+                });
+            }
+            Err(e) => warn!("Failed to read Qdrant collection {}: {}", name, e),
+        }
+    }
+    Ok(collections)
+}
+
+fn print_text(
+    audit: &StorageAudit,
+    vector_collections: &[VectorCollection],
+    qdrant_url: Option<&str>,
+    pruned: Option<usize>,
+    has_rules: bool,
+) {
+    println!(
+        "Storage directory: {} ({}, {} project(s), {} snapshot(s))",
+        audit.path.display(),
+        human_bytes(audit.total_bytes),
+        audit.total_projects,
+        audit.total_snapshots
+    );
+    for root in &audit.roots {
+        let name = root.tenant.as_ref().map_or("default storage".to_string(), |tenant| format!("tenant {}", tenant));
+        println!(
+            "\n{} ({}; skeleton cache {} entries, {}; other {})",
+            name,
+            human_bytes(root.bytes),
+            root.skeleton_entries,
+            human_bytes(root.skeleton_bytes),
+            human_bytes(root.other_bytes)
+        );
+        if root.projects.is_empty() {
+            println!("  no projects");
+            continue;
+        }
+        println!("  {:<34} {:<17} {:<10} {:>6} {:>6} {:>10}", "PROJECT", "LAST BUILT", "COMMIT", "GRAPHS", "FILES", "SIZE");
+        for project in &root.projects {
+            let built = project.last_built.map_or("-".to_string(), |t| t.format("%Y-%m-%d %H:%M").to_string());
+            let commit = project.commit.as_deref().map_or("-", |c| &c[..c.len().min(10)]);
+            println!(
+                "  {:<34} {:<17} {:<10} {:>6} {:>6} {:>10}",
+                project.project_id,
+                built,
+                commit,
+                project.graph_files,
+                project.files,
+                human_bytes(project.bytes)
+            );
+            if let (Some(dir), Some(exists)) = (&project.project_dir, project.source_exists) {
+                println!("    source: {}{}", dir, if exists { "" } else { " (missing)" });
+            }
+            if let Some(reason) = &project.prune_reason {
+                println!("    {}: {}", if pruned.is_some() { "pruned" } else { "prunable" }, reason);
+            }
+        }
+    }
+
+    if let Some(url) = qdrant_url {
+        println!("\nVector collections ({}):", url);
+        if vector_collections.is_empty() {
+            println!("  none");
+        }
+        for collection in vector_collections {
+            let count = |n: Option<u64>| n.map_or("?".to_string(), |n| n.to_string());
+            println!(
+                "  {:<34} {} points, {} indexed vectors, {} segment(s)",
+                collection.name,
+                count(collection.points),
+                count(collection.indexed_vectors),
+                collection.segments
+            );
+        }
+    }
+
+    println!();
+    match pruned {
+        Some(pruned) => println!("Pruned {} project(s), freed {}", pruned, human_bytes(audit.prunable_bytes)),
+        None if audit.prunable_projects > 0 => println!(
+            "{} project(s) ({}) match the retention rules; run again with --prune to delete them",
+            audit.prunable_projects,
+            human_bytes(audit.prunable_bytes)
+        ),
+        None if has_rules => println!("No project matches the retention rules"),
+        None => {}
+    }
+}
+
+pub(crate) fn human_bytes(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["KB", "MB", "GB", "TB"];
+    if bytes < 1024 {
+        return format!("{} B", bytes);
+    }
+    let mut value = bytes as f64 / 1024.0;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    format!("{:.1} {}", value, UNITS[unit])
+}
@@ -0,0 +1,137 @@
+use std::path::{Path, PathBuf};
+
+use qdrant_client::Qdrant;
+use qdrant_client::config::QdrantConfig;
+use qdrant_client::qdrant::{DeletePointsBuilder, PointId, PointsIdsList, ScrollPointsBuilder};
+use serde::Serialize;
+use tracing::info;
+
+use super::args::TextOrJson;
+use super::audit::human_bytes;
+use crate::storage::{compact_storage, CompactionReport, PersistenceManager};
+
+/// 每次从 Qdrant 读取的向量数
+const SCROLL_BATCH: u32 = 256;
+
+/// 从向量集合中删除的向量
+#[derive(Debug, Serialize)]
+pub struct VectorCleanup {
+    pub collection: String,
+    pub scanned: usize,
+    pub deleted: usize,
+}
+
+#[derive(Serialize)]
+struct CompactReport<'a> {
+    #[serde(flatten)]
+    storage: &'a CompactionReport,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    vectors: Option<VectorCleanup>,
+}
+
+pub async fn run_compact_storage(
+    dir: Option<String>,
+    collection: Option<String>,
+    qdrant_url: Option<String>,
+    format: TextOrJson,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let dir = dir.map(PathBuf::from).unwrap_or_else(PersistenceManager::default_base_dir);
+    if !dir.is_dir() {
+        return Err(format!("Storage directory {} does not exist", dir.display()).into());
+    }
+    info!("Compacting storage in {}", dir.display());
+
+    let report = compact_storage(&dir)?;
+    let vectors = match (collection, qdrant_url) {
+        (Some(collection), Some(url)) => Some(drop_orphaned_vectors(&url, collection).await?),
+        _ => None,
+    };
+
+    match format {
+        TextOrJson::Text => print_text(&report, vectors.as_ref()),
+        TextOrJson::Json => {
+            let report = CompactReport { storage: &report, vectors };
+            println!("{}", serde_json::to_string_pretty(&report)?);
+        }
+    }
+    Ok(())
+}
+
+/// 删除 `file_path` 指向的源文件已不存在的向量
+async fn drop_orphaned_vectors(url: &str, collection: String) -> Result<VectorCleanup, Box<dyn std::error::Error>> {
+    let client = Qdrant::new(QdrantConfig::from_url(url))?;
+    let mut cleanup = VectorCleanup { collection, scanned: 0, deleted: 0 };
+    let mut offset: Option<PointId> = None;
+    loop {
+        let mut request = ScrollPointsBuilder::new(cleanup.collection.clone())
+            .limit(SCROLL_BATCH)
+            .with_payload(true)
+            .with_vectors(false);
+        if let Some(offset) = offset.take() {
+            request = request.offset(offset);
+        }
+        let response = client.scroll(request).await?;
+        cleanup.scanned += response.result.len();
+
+        let orphaned: Vec<PointId> = response
+            .result
+            .into_iter()
+            .filter(|point| {
+                point
+                    .payload
+                    .get("file_path")
+                    .and_then(|value| value.as_str())
+                    .is_some_and(|file| !Path::new(file).is_file())
+            })
+            .filter_map(|point| point.id)
+            .collect();
+        if !orphaned.is_empty() {
+            cleanup.deleted += orphaned.len();
+            client
+                .delete_points(
+                    DeletePointsBuilder::new(cleanup.collection.clone())
+                        .points(PointsIdsList { ids: orphaned })
+                        .wait(true),
+                )
+                .await?;
+        }
+
+        match response.next_page_offset {
+            Some(next) => offset = Some(next),
+            None => break,
+        }
+    }
+    Ok(cleanup)
+}
+
+fn print_text(report: &CompactionReport, vectors: Option<&VectorCleanup>) {
+    println!("Storage directory: {}", report.path.display());
+    for root in &report.roots {
+        let name = root.tenant.as_ref().map_or("default storage".to_string(), |tenant| format!("tenant {}", tenant));
+        println!(
+            "\n{} ({} orphaned skeleton(s), {})",
+            name,
+            root.orphaned_skeletons,
+            human_bytes(root.skeleton_bytes_reclaimed)
+        );
+        for project in &root.projects {
+            println!(
+                "  {:<34} {:>10} -> {:<10} index {:>4}, bodies {:>4}, annotations {:>4}, parse stats {:>4}",
+                project.project_id,
+                human_bytes(project.bytes_before),
+                human_bytes(project.bytes_after),
+                project.stale_index_entries,
+                project.orphaned_bodies,
+                project.orphaned_annotations,
+                project.orphaned_parse_stats
+            );
+        }
+    }
+    if let Some(vectors) = vectors {
+        println!(
+            "\nVector collection {}: deleted {} of {} vector(s)",
+            vectors.collection, vectors.deleted, vectors.scanned
+        );
+    }
+    println!("\nReclaimed {}", human_bytes(report.reclaimed_bytes));
+}
@@ -0,0 +1,33 @@
+use std::fs;
+use std::path::Path;
+use tracing::info;
+
+use super::args::{EditorKind, TextOrJson};
+use super::editor::format_location;
+use crate::codegraph::AnalyzeOptions;
+use crate::codegraph::parser::CodeParser;
+use crate::services::coverage::{analyze_coverage, CoverageData};
+
+pub fn run_coverage(
+    report: String,
+    path: String,
+    min_fan_in: usize,
+    format: TextOrJson,
+    editor: Option<EditorKind>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    info!("Matching coverage report {} against {}", report, path);
+
+    let content = fs::read_to_string(&report).map_err(|e| format!("Failed to read {}: {}", report, e))?;
+    let data = CoverageData::parse(&content, None)?;
+
+    // 需要完整的调用边来计算调用方数量，不复用增量缓存
+    let mut parser = CodeParser::with_options(AnalyzeOptions { force_rebuild: true, ..Default::default() });
+    let graph = parser.build_petgraph_code_graph(Path::new(&path))?;
+    let result = analyze_coverage(&graph, &data, min_fan_in);
+
+    match format {
+        TextOrJson::Text => print!("{}", result.to_text(|file, line| format_location(editor, file, line))),
+        TextOrJson::Json => println!("{}", serde_json::to_string_pretty(&result)?),
+    }
+    Ok(())
+}
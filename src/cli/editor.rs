@@ -0,0 +1,60 @@
+use std::path::{Path, PathBuf};
+
+use super::args::EditorKind;
+
+/// 格式化文件位置：指定编辑器时输出可点击的编辑器链接，否则输出 `file:line`
+pub fn format_location(editor: Option<EditorKind>, file: &Path, line: usize) -> String {
+    let Some(editor) = editor else {
+        return format!("{}:{}", file.display(), line);
+    };
+    let path = encode_path(&absolute(file).to_string_lossy().replace('\\', "/"));
+    // Windows 路径没有前导斜杠
+    let path = if path.starts_with('/') { path } else { format!("/{}", path) };
+    match editor {
+        EditorKind::Vscode => format!("vscode://file{}:{}", path, line),
+        EditorKind::Idea => format!("idea://open?file={}&line={}", path, line),
+        EditorKind::Vim => format!("mvim://open?url=file://{}&line={}", path, line),
+    }
+}
+
+fn absolute(file: &Path) -> PathBuf {
+    file.canonicalize()
+        .or_else(|_| std::env::current_dir().map(|cwd| cwd.join(file)))
+        .unwrap_or_else(|_| file.to_path_buf())
+}
+
+/// 对路径做百分号编码，保留 `/` 和URI中无需转义的字符
+fn encode_path(path: &str) -> String {
+    let mut encoded = String::with_capacity(path.len());
+    for byte in path.bytes() {
+        if byte.is_ascii_alphanumeric() || b"/-_.~:".contains(&byte) {
+            encoded.push(byte as char);
+        } else {
+            encoded.push_str(&format!("%{:02X}", byte));
+        }
+    }
+    encoded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_editor_links() {
+        let file = Path::new("/srv/my project/src/lib.rs");
+        assert_eq!(format_location(None, file, 7), "/srv/my project/src/lib.rs:7");
+        assert_eq!(
+            format_location(Some(EditorKind::Vscode), file, 7),
+            "vscode://file/srv/my%20project/src/lib.rs:7"
+        );
+        assert_eq!(
+            format_location(Some(EditorKind::Idea), file, 7),
+            "idea://open?file=/srv/my%20project/src/lib.rs&line=7"
+        );
+        assert_eq!(
+            format_location(Some(EditorKind::Vim), file, 7),
+            "mvim://open?url=file:///srv/my%20project/src/lib.rs&line=7"
+        );
+    }
+}
@@ -0,0 +1,60 @@
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use tracing::info;
+
+use crate::codegraph::parser::CodeParser;
+use crate::codegraph::types::PetCodeGraph;
+use crate::services::knowledge_base::write_knowledge_base;
+use crate::services::graph_export::{export_graph_bytes, node_metrics, to_edges_csv, to_nodes_csv, ExportFormat};
+
+pub fn run_export(path: String, format: ExportFormat, output: Option<String>) -> Result<(), Box<dyn std::error::Error>> {
+    info!("Exporting call graph for {} as {:?}", path, format);
+
+    let mut parser = CodeParser::new();
+    let graph = parser.build_petgraph_code_graph(Path::new(&path))?;
+
+    // CSV 导出为节点表和边表两个文件，输出参数为目录
+    if format == ExportFormat::Csv {
+        let dir = PathBuf::from(output.unwrap_or_else(|| ".".to_string()));
+        fs::create_dir_all(&dir)?;
+        fs::write(dir.join("nodes.csv"), to_nodes_csv(&graph, &node_metrics(&graph)))?;
+        fs::write(dir.join("edges.csv"), to_edges_csv(&graph))?;
+        info!("Wrote nodes.csv and edges.csv to {}", dir.display());
+        return Ok(());
+    }
+
+    if format == ExportFormat::Obsidian {
+        let dir = PathBuf::from(output.unwrap_or_else(|| "codegraph-vault".to_string()));
+        let summary = write_knowledge_base(&graph, Path::new(&path), &dir)?;
+        info!("Wrote {} module and {} class notes to {}", summary.modules, summary.classes, dir.display());
+        return Ok(());
+    }
+
+    if format == ExportFormat::Parquet {
+        return write_parquet(&graph, PathBuf::from(output.unwrap_or_else(|| ".".to_string())));
+    }
+
+    let content = export_graph_bytes(&graph, format)?;
+    match output {
+        Some(output) => {
+            fs::write(&output, content)?;
+            info!("Wrote {} functions to {}", graph.graph.node_count(), output);
+        }
+        None => std::io::stdout().write_all(&content)?,
+    }
+
+    Ok(())
+}
+
+#[cfg(feature = "parquet")]
+fn write_parquet(graph: &PetCodeGraph, dir: PathBuf) -> Result<(), Box<dyn std::error::Error>> {
+    let paths = crate::services::parquet_export::write_parquet_tables(graph, &dir)?;
+    info!("Wrote {} parquet tables to {}", paths.len(), dir.display());
+    Ok(())
+}
+
+#[cfg(not(feature = "parquet"))]
+fn write_parquet(_graph: &PetCodeGraph, _dir: PathBuf) -> Result<(), Box<dyn std::error::Error>> {
+    Err("Parquet export is not available in this build; rebuild with `--features parquet`".into())
+}
@@ -0,0 +1,16 @@
+use std::path::Path;
+use std::time::Instant;
+use tracing::info;
+
+use crate::services::synthetic::{generate_repository, SyntheticRepoConfig};
+
+pub fn run_generate_repo(path: String, config: SyntheticRepoConfig) -> Result<(), Box<dyn std::error::Error>> {
+    info!("Generating synthetic repository in {} ({} files, languages {:?})", path, config.files, config.languages);
+
+    let started = Instant::now();
+    let summary = generate_repository(Path::new(&path), &config)?;
+
+    println!("{}", serde_json::to_string_pretty(&summary)?);
+    info!("Generated {} files in {:?}", summary.files, started.elapsed());
+    Ok(())
+}
@@ -0,0 +1,59 @@
+use std::path::Path;
+use tracing::info;
+
+use super::args::{EditorKind, ReportFormat};
+use super::editor::format_location;
+use crate::services::lint::{LintConfig, LintService};
+use crate::services::policy::{Policy, DEFAULT_POLICY_FILE};
+use crate::services::suppression::Baseline;
+
+pub fn run_lint(
+    path: String,
+    config: LintConfig,
+    baseline: Option<String>,
+    write_baseline: bool,
+    policy: Option<String>,
+    format: ReportFormat,
+    editor: Option<EditorKind>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    info!("Linting {} (max_lines={}, max_params={}, max_nesting={}, graph_rules={})",
+        path, config.max_lines, config.max_params, config.max_nesting, config.graph_rules);
+
+    let root = Path::new(&path);
+    let service = LintService::new(config);
+    let mut report = service.lint_directory(root)?;
+
+    if let Some(baseline_path) = baseline {
+        let baseline_path = Path::new(&baseline_path);
+        if write_baseline {
+            Baseline::from_findings(&report.findings, root).save(baseline_path)?;
+            info!("Wrote {} finding(s) to baseline {}", report.findings.len(), baseline_path.display());
+            return Ok(());
+        }
+        let (findings, baselined) = Baseline::load(baseline_path)?.filter(report.findings, root);
+        report.findings = findings;
+        report.baselined = baselined;
+    }
+
+    let policy_path = policy
+        .map(|p| Path::new(&p).to_path_buf())
+        .or_else(|| Some(root.join(DEFAULT_POLICY_FILE)).filter(|p| root.is_dir() && p.is_file()));
+    if let Some(policy_path) = policy_path {
+        info!("Applying policy {}", policy_path.display());
+        let (findings, ignored) = Policy::load(&policy_path)?.apply(report.findings, root);
+        report.findings = findings;
+        report.ignored = ignored;
+    }
+
+    match format {
+        ReportFormat::Text => print!("{}", report.to_text_with(|file, line| format_location(editor, file, line))),
+        ReportFormat::Json => println!("{}", serde_json::to_string_pretty(&report)?),
+        ReportFormat::Sarif => println!("{}", serde_json::to_string_pretty(&report.to_sarif())?),
+    }
+
+    let errors = report.error_count();
+    if errors > 0 {
+        return Err(format!("{} finding(s) with error severity", errors).into());
+    }
+    Ok(())
+}
@@ -0,0 +1,37 @@
+use std::sync::OnceLock;
+
+use tracing_subscriber::{fmt, layer::SubscriberExt, reload, util::SubscriberInitExt, EnvFilter, Registry};
+
+use super::args::LogFormat;
+
+static FILTER_HANDLE: OnceLock<reload::Handle<EnvFilter, Registry>> = OnceLock::new();
+
+/// 初始化全局日志：级别优先取 `RUST_LOG`，否则按 verbose 选择 debug/info；输出到stderr
+pub fn init_logging(format: LogFormat, verbose: bool) -> Result<(), Box<dyn std::error::Error>> {
+    let default_level = if verbose { "debug" } else { "info" };
+    let filter = EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new(default_level));
+    let (filter, handle) = reload::Layer::new(filter);
+
+    let registry = tracing_subscriber::registry().with(filter);
+    match format {
+        LogFormat::Pretty => registry.with(fmt::layer().with_writer(std::io::stderr)).try_init()?,
+        LogFormat::Json => registry
+            .with(
+                fmt::layer()
+                    .json()
+                    .with_current_span(true)
+                    .with_span_list(true)
+                    .with_writer(std::io::stderr),
+            )
+            .try_init()?,
+    }
+    FILTER_HANDLE.set(handle).ok();
+    Ok(())
+}
+
+/// 运行时替换日志过滤规则（如 "debug" 或 "codegraph_cli=debug,info"）
+pub fn set_log_level(directives: &str) -> Result<(), String> {
+    let filter = EnvFilter::try_new(directives).map_err(|e| format!("Invalid log level '{}': {}", directives, e))?;
+    let handle = FILTER_HANDLE.get().ok_or_else(|| "Logging is not initialized".to_string())?;
+    handle.reload(filter).map_err(|e| format!("Failed to update log level: {}", e))
+}
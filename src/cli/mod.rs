@@ -2,8 +2,31 @@ pub mod args;
 pub mod runner;
 pub mod analyze;
 pub mod vectorize;
+pub mod lint;
+pub mod refactor;
+pub mod coverage;
+pub mod generate;
+pub mod export;
+pub mod report;
+pub mod editor;
+pub mod logging;
+pub mod audit;
+pub mod compact;
+pub mod pr_index;
+pub mod unresolved;
 
 pub use args::Cli;
 pub use runner::CodeGraphRunner;
 pub use analyze::run_analyze;
-pub use vectorize::run_vectorize;
\ No newline at end of file
+pub use vectorize::run_vectorize;
+pub use lint::run_lint;
+pub use refactor::run_refactor;
+pub use coverage::run_coverage;
+pub use generate::run_generate_repo;
+pub use export::run_export;
+pub use report::run_report;
+pub use logging::{init_logging, set_log_level};
+pub use audit::run_audit_storage;
+pub use compact::run_compact_storage;
+pub use pr_index::run_pr_index;
+pub use unresolved::run_unresolved_calls;
\ No newline at end of file
@@ -0,0 +1,57 @@
+use std::path::{Path, PathBuf};
+
+use qdrant_client::qdrant::Value;
+use tracing::info;
+
+use super::vectorize::VectorizeService;
+use crate::codegraph::parser::CodeParser;
+use crate::services::{index_pull_request, pull_request_from_env};
+use crate::storage::StorageManager;
+
+pub async fn run_pr_index(
+    storage: StorageManager,
+    path: String,
+    base: String,
+    head: String,
+    pr: Option<u64>,
+    collection: Option<String>,
+    qdrant_url: String,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let pull_request = pr
+        .or_else(pull_request_from_env)
+        .ok_or("No pull request number: pass --pr, or run in a CI job that sets GITHUB_REF or CI_MERGE_REQUEST_IID")?;
+    info!("Indexing pull request #{} of {} ({}..{})", pull_request, path, base, head);
+
+    let outcome = index_pull_request(&storage, &path, &base, &head, pull_request).map_err(|e| e.to_string())?;
+
+    // 只重新向量化变化的文件，向量带上 PR 编号
+    let mut vectors = None;
+    if let Some(collection) = collection {
+        let parser = CodeParser::new();
+        let files: Vec<PathBuf> = outcome
+            .changed_files
+            .iter()
+            .map(|file| Path::new(&path).join(file))
+            .filter(|file| file.is_file() && parser.is_supported_file(file))
+            .collect();
+        let service = VectorizeService::new(&qdrant_url, collection.clone())
+            .await?
+            .with_tag("pull_request", Value::from(pull_request as i64));
+        service.ensure_collection().await?;
+        vectors = Some((service.vectorize_files(&files).await?, collection));
+    }
+
+    let stats = outcome.graph.get_stats();
+    println!("project_id: {}", outcome.project_id);
+    println!("base project: {}", outcome.base_project_id);
+    println!(
+        "changed files: {} ({})",
+        outcome.changed_files.len(),
+        if outcome.warm { "applied to the base graph" } else { "no base graph, built from scratch" }
+    );
+    println!("functions: {}, resolved calls: {}", stats.total_functions, stats.resolved_calls);
+    if let Some((count, collection)) = vectors {
+        println!("vectors: {} written to {}", count, collection);
+    }
+    Ok(())
+}
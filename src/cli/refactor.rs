@@ -0,0 +1,43 @@
+use std::path::Path;
+use tracing::info;
+
+use super::args::{EditorKind, TextOrJson};
+use super::editor::format_location;
+use crate::codegraph::parser::CodeParser;
+use crate::services::refactor::{RefactorAdvisor, RefactorConfig, SuggestionKind};
+
+pub fn run_refactor(
+    path: String,
+    config: RefactorConfig,
+    format: TextOrJson,
+    editor: Option<EditorKind>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    info!("Analyzing module coupling for: {}", path);
+
+    let mut parser = CodeParser::new();
+    let graph = parser.build_petgraph_code_graph(Path::new(&path))?;
+    let suggestions = RefactorAdvisor::new(config).suggest(&graph);
+
+    match format {
+        TextOrJson::Text => {
+            for suggestion in &suggestions {
+                let action = match suggestion.kind {
+                    SuggestionKind::MoveFunction => "move function",
+                    SuggestionKind::MergeModule => "merge module",
+                };
+                let location = match (&suggestion.file_path, suggestion.line_start) {
+                    (Some(file), Some(line)) => format!("{}: ", format_location(editor, Path::new(file), line)),
+                    _ => String::new(),
+                };
+                println!("{}{} -> {} (score {:.2}): {}",
+                    location, action, suggestion.to_module, suggestion.score, suggestion.reason);
+            }
+            println!("\n{} suggestion(s)", suggestions.len());
+        }
+        TextOrJson::Json => {
+            println!("{}", serde_json::to_string_pretty(&suggestions)?);
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,22 @@
+use std::path::Path;
+use tracing::info;
+
+use crate::codegraph::parser::CodeParser;
+use crate::services::report::write_report;
+
+pub fn run_report(dir: String, path: String) -> Result<(), Box<dyn std::error::Error>> {
+    info!("Generating HTML report for {} in {}", path, dir);
+
+    let mut parser = CodeParser::new();
+    let graph = parser.build_petgraph_code_graph(Path::new(&path))?;
+    let summary = write_report(&graph, Path::new(&path), Path::new(&dir))?;
+
+    info!(
+        "Wrote {} pages ({} modules, {} functions); open {}",
+        summary.pages,
+        summary.modules,
+        summary.functions,
+        Path::new(&dir).join("index.html").display()
+    );
+    Ok(())
+}
@@ -1,8 +1,24 @@
-use tracing::{info, Level};
-use tracing_subscriber::FmtSubscriber;
+use std::path::PathBuf;
+
+use tracing::info;
 
 use super::args::{Cli, Commands};
 use super::vectorize::run_vectorize;
+use super::lint::run_lint;
+use super::refactor::run_refactor;
+use super::coverage::run_coverage;
+use super::generate::run_generate_repo;
+use super::export::run_export;
+use super::report::run_report;
+use super::audit::run_audit_storage;
+use super::compact::run_compact_storage;
+use super::pr_index::run_pr_index;
+use super::unresolved::run_unresolved_calls;
+use crate::services::lint::LintConfig;
+use crate::services::refactor::RefactorConfig;
+use crate::services::synthetic::SyntheticRepoConfig;
+use crate::storage::{RetentionRules, StorageManager};
+use crate::http::config::parse_interval;
 
 pub struct CodeGraphRunner;
 
@@ -12,14 +28,9 @@ impl CodeGraphRunner {
     }
 
     pub async fn run(cli: Cli) -> Result<(), Box<dyn std::error::Error>> {
-        // Initialize logging
-        let subscriber = FmtSubscriber::builder()
-            .with_max_level(if cli.verbose { Level::DEBUG } else { Level::INFO })
-            .finish();
-        tracing::subscriber::set_global_default(subscriber)?;
-
+        // Logging is initialized by the binary before dispatching here
         match cli.command {
-            Commands::Server { address: _, storage_mode: _ } => {
+            Commands::Server { .. } => {
                 info!("Starting server mode");
                 // TODO: 启动HTTP服务器
                 info!("Server mode not fully implemented yet");
@@ -28,6 +39,48 @@ impl CodeGraphRunner {
                 info!("Starting vectorize mode");
                 run_vectorize(path, collection, qdrant_url).await?;
             }
+            Commands::Lint { path, max_lines, max_params, max_nesting, graph_rules, baseline, write_baseline, policy, format } => {
+                let config = LintConfig { max_lines, max_params, max_nesting, graph_rules };
+                run_lint(path, config, baseline, write_baseline, policy, format, cli.editor)?;
+            }
+            Commands::Refactor { path, min_calls, min_ratio, format } => {
+                let config = RefactorConfig { min_calls, min_ratio };
+                run_refactor(path, config, format, cli.editor)?;
+            }
+            Commands::Coverage { report, path, min_fan_in, format } => {
+                run_coverage(report, path, min_fan_in, format, cli.editor)?;
+            }
+            Commands::UnresolvedCalls { path, min_count, limit, format } => {
+                run_unresolved_calls(path, min_count, limit, format, cli.editor)?;
+            }
+            Commands::Export { path, format, output } => {
+                run_export(path, format, output)?;
+            }
+            Commands::Report { dir, path } => {
+                run_report(dir, path)?;
+            }
+            Commands::AuditStorage { dir, prune, max_age, keep_last, missing_source, qdrant_url, format } => {
+                let rules = RetentionRules {
+                    max_age: max_age.as_deref().map(parse_interval).transpose()?,
+                    keep_last,
+                    missing_source,
+                };
+                run_audit_storage(dir, rules, prune, qdrant_url, format).await?;
+            }
+            Commands::CompactStorage { dir, collection, qdrant_url, format } => {
+                run_compact_storage(dir, collection, qdrant_url, format).await?;
+            }
+            Commands::PrIndex { path, base, head, pr, storage_dir, collection, qdrant_url } => {
+                let storage = match storage_dir {
+                    Some(dir) => StorageManager::with_base_dir(PathBuf::from(dir), cli.storage_mode),
+                    None => StorageManager::with_storage_mode(cli.storage_mode),
+                };
+                run_pr_index(storage, path, base, head, pr, collection, qdrant_url).await?;
+            }
+            Commands::GenerateRepo { path, files, functions_per_file, calls_per_function, languages, seed } => {
+                let config = SyntheticRepoConfig { files, functions_per_file, calls_per_function, languages, seed };
+                run_generate_repo(path, config)?;
+            }
         }
 
         Ok(())
@@ -0,0 +1,52 @@
+use std::path::Path;
+use tracing::info;
+
+use super::args::{EditorKind, TextOrJson};
+use super::editor::format_location;
+use crate::codegraph::AnalyzeOptions;
+use crate::codegraph::parser::CodeParser;
+use crate::services::unresolved::triage_unresolved_calls;
+
+pub fn run_unresolved_calls(
+    path: String,
+    min_count: usize,
+    limit: Option<usize>,
+    format: TextOrJson,
+    editor: Option<EditorKind>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    info!("Listing unresolved calls for: {}", path);
+
+    // 需要完整的调用边，不复用增量缓存
+    let mut parser = CodeParser::with_options(AnalyzeOptions { force_rebuild: true, ..Default::default() });
+    let graph = parser.build_petgraph_code_graph(Path::new(&path))?;
+    let mut report = triage_unresolved_calls(&graph);
+    report.groups.retain(|group| group.count >= min_count);
+    if let Some(limit) = limit {
+        report.groups.truncate(limit);
+    }
+
+    match format {
+        TextOrJson::Text => {
+            for group in &report.groups {
+                println!("{} ({} call(s))", group.callee_name, group.count);
+                for site in &group.sites {
+                    println!("  {}: in {}", format_location(editor, Path::new(&site.file_path), site.line_number), site.caller_name);
+                }
+                for candidate in &group.candidates {
+                    println!(
+                        "  candidate: {}::{} at {}",
+                        candidate.namespace,
+                        candidate.name,
+                        format_location(editor, Path::new(&candidate.file_path), candidate.line_start)
+                    );
+                }
+            }
+            println!(
+                "\n{} of {} call(s) unresolved",
+                report.unresolved_calls, report.total_calls
+            );
+        }
+        TextOrJson::Json => println!("{}", serde_json::to_string_pretty(&report)?),
+    }
+    Ok(())
+}
@@ -1,4 +1,4 @@
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::fs;
 use std::collections::HashMap;
 use qdrant_client::Qdrant;
@@ -17,6 +17,8 @@ pub struct VectorizeService {
     collection_name: String,
     embedding_client: reqwest::Client,
     embedding_url: String,
+    /// 附加到每个向量 payload 中的字段
+    tags: Vec<(String, Value)>,
 }
 
 impl VectorizeService {
@@ -31,9 +33,16 @@ impl VectorizeService {
             collection_name,
             embedding_client,
             embedding_url,
+            tags: Vec::new(),
         })
     }
 
+    /// 在每个向量的 payload 中附加一个字段（例如 PR 编号）
+    pub fn with_tag(mut self, key: &str, value: Value) -> Self {
+        self.tags.push((key.to_string(), value));
+        self
+    }
+
     /// 创建或获取集合
     pub async fn ensure_collection(&self) -> Result<(), Box<dyn std::error::Error>> {
         let collections = self.qdrant_client.list_collections().await?;
@@ -120,17 +129,25 @@ impl VectorizeService {
         info!("Starting vectorization of directory: {}", dir_path);
         
         let mut parser = CodeParser::new();
-        let mut ts_parser = TreeSitterParser::new();
         
         let path = Path::new(dir_path);
         let files = parser.scan_directory(path);
         
         info!("Found {} files to vectorize", files.len());
+        let total_vectors = self.vectorize_files(&files).await?;
+        
+        info!("Vectorization completed. Total vectors created: {}", total_vectors);
+        Ok(())
+    }
+
+    /// 向量化指定的文件，返回创建的向量数
+    pub async fn vectorize_files(&self, files: &[PathBuf]) -> Result<usize, Box<dyn std::error::Error>> {
+        let mut ts_parser = TreeSitterParser::new();
         let mut total_vectors = 0;
         
         for file_path in files {
             debug!("Processing file: {}", file_path.display());
-            match self.process_file(&file_path, &mut ts_parser).await {
+            match self.process_file(file_path, &mut ts_parser).await {
                 Ok(vectors) => {
                     total_vectors += vectors;
                     debug!("File {} processed successfully with {} vectors", file_path.display(), vectors);
@@ -141,8 +158,7 @@ impl VectorizeService {
             }
         }
         
-        info!("Vectorization completed. Total vectors created: {}", total_vectors);
-        Ok(())
+        Ok(total_vectors)
     }
 
     /// 处理单个文件
@@ -169,7 +185,7 @@ impl VectorizeService {
                     let symbol_info = symbol_ref.symbol_info_struct();
                     let code_block = symbol_info.get_content_from_file_blocked()
                         .unwrap_or_else(|e| {
-                            eprintln!("Warning: Failed to get content for {}: {}", symbol_ref.name(), e);
+                            tracing::warn!("Failed to get content for {}: {}", symbol_ref.name(), e);
                             symbol_ref.name().to_string()
                         });
                     
@@ -193,6 +209,9 @@ impl VectorizeService {
                     payload.insert("line_start", Value::from((symbol_ref.full_range().start_point.row + 1) as i64));
                     payload.insert("line_end", Value::from((symbol_ref.full_range().end_point.row + 1) as i64));
                     payload.insert("code_block", Value::from(code_block));
+                    for (key, value) in &self.tags {
+                        payload.insert(key.as_str(), value.clone());
+                    }
                     
                     let point = PointStruct::new(
                         point_id,
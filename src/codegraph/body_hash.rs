@@ -0,0 +1,75 @@
+/// 行注释的起始标记
+fn line_comment(language: &str) -> Option<&'static str> {
+    match language {
+        "python" | "bash" | "ruby" => Some("#"),
+        "lua" | "sql" => Some("--"),
+        "rust" | "go" | "cpp" | "java" | "kotlin" | "swift" | "scala" | "javascript" | "typescript" | "objc" | "proto" | "vue" | "svelte" | "html" => Some("//"),
+        _ => None,
+    }
+}
+
+/// 是否有 `/* */` 块注释
+fn has_block_comments(language: &str) -> bool {
+    !matches!(language, "python" | "bash" | "lua" | "ruby")
+}
+
+/// 规范化后的函数体：去掉注释和空白，只在相邻的两个单词之间保留一个空格。
+///
+/// 不区分字符串字面量，字符串中类似注释的内容也会被去掉；同样的源码总是得到同样的结果，
+/// 只改了格式或注释的副本仍然相同
+pub fn normalize_body(language: &str, body: &str) -> String {
+    let mut code = body.to_string();
+    if has_block_comments(language) {
+        let mut stripped = String::with_capacity(code.len());
+        let mut rest = code.as_str();
+        while let Some(start) = rest.find("/*") {
+            stripped.push_str(&rest[..start]);
+            stripped.push(' ');
+            rest = rest[start + 2..].find("*/").map_or("", |end| &rest[start + 2 + end + 2..]);
+        }
+        stripped.push_str(rest);
+        code = stripped;
+    }
+    let marker = line_comment(language);
+    let mut normalized = String::with_capacity(code.len());
+    let tokens = code
+        .lines()
+        .map(|line| marker.and_then(|marker| line.find(marker)).map_or(line, |at| &line[..at]))
+        .flat_map(str::split_whitespace);
+    for token in tokens {
+        // 只有两个单词字符之间需要保留空格
+        if normalized.ends_with(is_word_char) && token.starts_with(is_word_char) {
+            normalized.push(' ');
+        }
+        normalized.push_str(token);
+    }
+    normalized
+}
+
+fn is_word_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_'
+}
+
+/// 函数体的哈希（规范化后内容的MD5），函数体为空时为 `None`
+pub fn body_hash(language: &str, body: &str) -> Option<String> {
+    let normalized = normalize_body(language, body);
+    (!normalized.is_empty()).then(|| format!("{:x}", md5::compute(normalized.as_bytes())))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_formatting_and_comments_do_not_change_hash() {
+        let original = "fn total(items: &[u32]) -> u32 {\n    // 求和\n    items.iter().sum()\n}\n";
+        let reformatted = "fn total(items: &[u32]) -> u32 {\n\titems.iter() /* 迭代 */ .sum()\n}";
+        assert_eq!(normalize_body("rust", original), "fn total(items:&[u32])->u32{items.iter().sum()}");
+        assert_eq!(body_hash("rust", original), body_hash("rust", reformatted));
+        assert_ne!(body_hash("rust", original), body_hash("rust", "fn total(items: &[u32]) -> u32 {\n    items.iter().product()\n}"));
+
+        let python = "def total(items):\n    # sum them\n    return sum(items)\n";
+        assert_eq!(body_hash("python", python), body_hash("python", "def total(items):\n    return  sum(items)"));
+        assert_eq!(body_hash("python", "  # only a comment\n"), None);
+    }
+}
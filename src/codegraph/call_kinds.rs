@@ -0,0 +1,156 @@
+use std::ops::Range;
+
+use crate::codegraph::treesitter::structs::SymbolType;
+use crate::codegraph::treesitter::{AstSymbolInstance, AstSymbolInstanceArc};
+use crate::codegraph::types::CallKind;
+
+/// 参数中的调用作为任务派生出去的函数
+fn spawners(language: &str) -> &'static [&'static str] {
+    match language {
+        "rust" => &["spawn", "spawn_blocking", "spawn_local"],
+        "python" => &["create_task", "ensure_future", "run_coroutine_threadsafe", "run_in_executor", "start_soon"],
+        "javascript" | "typescript" => &["then", "catch", "finally"],
+        _ => &[],
+    }
+}
+
+/// 文件中各调用点的调用方式。
+///
+/// 按调用点周围的源码判断：Rust 中紧跟 `.await`、Python/JavaScript/TypeScript 中前面是 `await`
+/// 的调用为 `Await`；位于派生函数（`tokio::spawn`、`asyncio.create_task`、Promise 的 `then` 等）
+/// 参数中的调用，以及结果接着 `.then(` 的调用为 `Spawn`；其余为 `Sync`。
+#[derive(Debug, Default)]
+pub struct CallKinds {
+    language: String,
+    /// 派生函数调用的参数范围（字节）
+    spawned: Vec<Range<usize>>,
+    code: String,
+}
+
+impl CallKinds {
+    pub fn new(language: &str, symbols: &[AstSymbolInstanceArc], code: String) -> Self {
+        let names = spawners(language);
+        let mut spawned = Vec::new();
+        if !names.is_empty() {
+            for symbol in symbols {
+                let guard = symbol.read();
+                if guard.symbol_type() != SymbolType::FunctionCall || !names.contains(&guard.name()) {
+                    continue;
+                }
+                let range = guard.full_range();
+                // 方法调用的范围包含接收者，参数从调用名后的括号开始
+                let Some(text) = code.get(range.start_byte..range.end_byte) else { continue };
+                if let Some(offset) = text.find(&format!("{}(", guard.name())) {
+                    spawned.push(range.start_byte + offset + guard.name().len()..range.end_byte);
+                }
+            }
+        }
+        Self { language: language.to_string(), spawned, code }
+    }
+
+    pub fn kind(&self, call: &dyn AstSymbolInstance) -> CallKind {
+        let range = call.full_range();
+        if self.spawned.iter().any(|args| args.contains(&range.start_byte)) {
+            return CallKind::Spawn;
+        }
+        let before = self.code.get(..range.start_byte).unwrap_or_default().trim_end();
+        let after = self.code.get(range.end_byte..).unwrap_or_default().trim_start();
+        match self.language.as_str() {
+            "rust" if keyword_prefix(after, ".await") => CallKind::Await,
+            "python" | "javascript" | "typescript" if keyword_suffix(before, "await") => CallKind::Await,
+            "javascript" | "typescript" if ["then", "catch", "finally"].iter().any(|name| chained(after, name)) => CallKind::Spawn,
+            _ => CallKind::Sync,
+        }
+    }
+}
+
+fn is_ident_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_'
+}
+
+fn keyword_prefix(text: &str, keyword: &str) -> bool {
+    text.strip_prefix(keyword).is_some_and(|rest| !rest.starts_with(is_ident_char))
+}
+
+fn keyword_suffix(text: &str, keyword: &str) -> bool {
+    text.strip_suffix(keyword).is_some_and(|rest| !rest.ends_with(is_ident_char) && !rest.ends_with('.'))
+}
+
+/// `text` 以 `.name(` 开头（允许换行缩进）
+fn chained(text: &str, name: &str) -> bool {
+    text.strip_prefix('.')
+        .and_then(|rest| rest.trim_start().strip_prefix(name))
+        .is_some_and(|rest| rest.trim_start().starts_with('('))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::codegraph::treesitter::TreeSitterParser;
+    use std::io::Write;
+
+    fn kinds(extension: &str, code: &str) -> Vec<(String, CallKind)> {
+        let mut file = tempfile::Builder::new().suffix(extension).tempfile().unwrap();
+        file.write_all(code.as_bytes()).unwrap();
+        let language = match extension {
+            ".rs" => "rust",
+            ".py" => "python",
+            _ => "typescript",
+        };
+        let symbols = TreeSitterParser::new().parse_file(&file.path().to_path_buf()).unwrap();
+        let call_kinds = CallKinds::new(language, &symbols, code.to_string());
+        let mut kinds: Vec<(String, CallKind)> = symbols
+            .iter()
+            .filter(|symbol| symbol.read().symbol_type() == SymbolType::FunctionCall)
+            .map(|symbol| {
+                let guard = symbol.read();
+                (guard.name().to_string(), call_kinds.kind(guard.as_ref()))
+            })
+            .collect();
+        kinds.sort_by(|a, b| a.0.cmp(&b.0));
+        kinds
+    }
+
+    #[test]
+    fn test_rust_call_kinds() {
+        let code = "async fn run() {\n    load().await;\n    tokio::spawn(worker());\n    log();\n}\n";
+        assert_eq!(
+            kinds(".rs", code),
+            vec![
+                ("load".to_string(), CallKind::Await),
+                ("log".to_string(), CallKind::Sync),
+                ("spawn".to_string(), CallKind::Sync),
+                ("worker".to_string(), CallKind::Spawn),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_python_call_kinds() {
+        let code = "async def run():\n    await load()\n    asyncio.create_task(worker())\n    log()\n";
+        assert_eq!(
+            kinds(".py", code),
+            vec![
+                ("create_task".to_string(), CallKind::Sync),
+                ("load".to_string(), CallKind::Await),
+                ("log".to_string(), CallKind::Sync),
+                ("worker".to_string(), CallKind::Spawn),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_typescript_call_kinds() {
+        let code = "async function run() {\n    await load();\n    fetchData()\n        .then((r) => handle(r));\n    log();\n}\n";
+        assert_eq!(
+            kinds(".ts", code),
+            vec![
+                ("fetchData".to_string(), CallKind::Spawn),
+                ("handle".to_string(), CallKind::Spawn),
+                ("load".to_string(), CallKind::Await),
+                ("log".to_string(), CallKind::Sync),
+                ("then".to_string(), CallKind::Sync),
+            ]
+        );
+    }
+}
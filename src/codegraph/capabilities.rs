@@ -0,0 +1,184 @@
+use std::path::Path;
+
+use serde::Serialize;
+
+use crate::codegraph::treesitter::parsers::get_tree_sitter_language;
+use crate::codegraph::treesitter::{AnalyzerRegistry, LanguageId};
+use crate::storage::build_manifest::GrammarInfo;
+
+/// 各语言分析器实现的提取功能
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct Features {
+    /// 类、结构体、接口等类型声明
+    pub classes: bool,
+    /// 函数调用边
+    pub calls: bool,
+    /// 导入语句，用于跨文件解析调用
+    pub imports: bool,
+    /// `/query_code_skeleton` 的文件骨架
+    pub skeletons: bool,
+}
+
+impl Features {
+    const ALL: Self = Self { classes: true, calls: true, imports: true, skeletons: true };
+    const NONE: Self = Self { classes: false, calls: false, imports: false, skeletons: false };
+}
+
+/// 解析所用的 tree-sitter 语法，与构建清单中记录的相同
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Grammar {
+    /// 语法对应的语言，例如 Objective-C 使用 `cpp` 语法
+    pub name: String,
+    #[serde(flatten)]
+    pub info: GrammarInfo,
+}
+
+/// 一种语言的分析能力
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct LanguageCapabilities {
+    /// 与 `FunctionInfo.language` 相同的语言名
+    pub language: String,
+    /// 不带点的扩展名
+    pub extensions: Vec<String>,
+    /// `tree-sitter`：内置语法；`scanner`：不使用语法的内置扫描器；`external`：外部注册的分析器
+    pub analyzer: &'static str,
+    /// 当前构建能否分析该语言，能识别但没有打包语法的语言为 false
+    pub available: bool,
+    /// 管理员可以通过 `/admin/analyzers` 暂停分析某种语言
+    pub enabled: bool,
+    pub features: Features,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub grammars: Vec<Grammar>,
+}
+
+/// 内置语言：语言名、扩展名、使用的语法和提取功能，没有任何功能的语言不可用
+const BUILTIN_LANGUAGES: &[(&str, &[&str], Option<LanguageId>, Features)] = &[
+    ("rust", &["rs"], Some(LanguageId::Rust), Features::ALL),
+    ("python", &["py", "py3", "pyx", "ipynb"], Some(LanguageId::Python), Features::ALL),
+    ("javascript", &["js", "jsx"], Some(LanguageId::JavaScript), Features::ALL),
+    ("typescript", &["ts"], Some(LanguageId::TypeScript), Features::ALL),
+    ("typescript", &["tsx"], Some(LanguageId::TypeScriptReact), Features::ALL),
+    ("java", &["java"], Some(LanguageId::Java), Features::ALL),
+    (
+        "cpp",
+        &["cpp", "cc", "cxx", "c++", "c", "h", "hpp", "hxx", "hh", "inl", "inc", "tpp", "tpl"],
+        Some(LanguageId::Cpp),
+        Features::ALL,
+    ),
+    ("go", &["go"], Some(LanguageId::Go), Features::ALL),
+    ("kotlin", &["kt", "kts"], Some(LanguageId::Kotlin), Features::ALL),
+    ("ruby", &["rb", "rake"], Some(LanguageId::Ruby), Features::ALL),
+    ("swift", &["swift"], Some(LanguageId::Swift), Features::ALL),
+    ("scala", &["scala", "sc"], Some(LanguageId::Scala), Features::ALL),
+    // Objective-C 改写为等行数的 C++ 后用 C++ 语法解析
+    ("objc", &["m", "mm"], Some(LanguageId::Cpp), Features::ALL),
+    ("vue", &["vue"], Some(LanguageId::TypeScript), Features::ALL),
+    ("svelte", &["svelte"], Some(LanguageId::TypeScript), Features::ALL),
+    // HTML 页面只分析 `<script>` 块，不生成骨架
+    (
+        "html",
+        &["html", "htm"],
+        Some(LanguageId::JavaScript),
+        Features { skeletons: false, ..Features::ALL },
+    ),
+    ("lua", &["lua"], None, Features { classes: false, ..Features::ALL }),
+    ("bash", &["sh", "bash"], None, Features { classes: false, ..Features::ALL }),
+    ("sql", &["sql"], None, Features { classes: false, imports: false, ..Features::ALL }),
+    ("proto", &["proto"], None, Features { calls: false, ..Features::ALL }),
+];
+
+/// 内置语言和已注册的外部分析器的能力；外部分析器处理的扩展名不再列在内置语言下
+pub fn capabilities() -> Vec<LanguageCapabilities> {
+    let external = AnalyzerRegistry::registered();
+    let claimed = |extension: &str| external.iter().any(|manifest| manifest.extensions.iter().any(|e| e.eq_ignore_ascii_case(extension)));
+
+    let mut languages: Vec<LanguageCapabilities> = Vec::new();
+    for (language, extensions, grammar, features) in BUILTIN_LANGUAGES {
+        let extensions: Vec<String> = extensions.iter().filter(|e| !claimed(e)).map(|e| e.to_string()).collect();
+        if extensions.is_empty() {
+            continue;
+        }
+        let available = *features != Features::NONE;
+        let grammar = grammar.and_then(|grammar_id| {
+            Some(Grammar { name: grammar_id.to_string(), info: GrammarInfo::of(&get_tree_sitter_language(grammar_id)?) })
+        });
+        // `.ts` 和 `.tsx` 用不同的语法，但对外是同一种语言
+        if let Some(existing) = languages.iter_mut().find(|l| l.language == *language) {
+            existing.extensions.extend(extensions);
+            existing.grammars.extend(grammar);
+            continue;
+        }
+        languages.push(LanguageCapabilities {
+            language: language.to_string(),
+            extensions,
+            analyzer: if grammar.is_some() { "tree-sitter" } else { "scanner" },
+            available,
+            enabled: AnalyzerRegistry::is_enabled(language),
+            features: *features,
+            grammars: grammar.into_iter().collect(),
+        });
+    }
+    languages.extend(external.into_iter().map(|manifest| LanguageCapabilities {
+        enabled: AnalyzerRegistry::is_enabled(&manifest.name),
+        language: manifest.name,
+        extensions: manifest.extensions,
+        analyzer: "external",
+        available: true,
+        features: Features::ALL,
+        grammars: Vec::new(),
+    }));
+    languages
+}
+
+/// 文件所属语言的名字，与构建中的 `FunctionInfo.language` 相同；外部分析器优先
+pub fn language_for_path(path: &Path) -> Option<String> {
+    if let Some(language) = AnalyzerRegistry::language_for_path(path) {
+        return Some(language);
+    }
+    if matches!(path.file_name().and_then(|n| n.to_str()), Some("Gemfile" | "Rakefile")) {
+        return Some("ruby".to_string());
+    }
+    let extension = path.extension().and_then(|e| e.to_str())?.to_lowercase();
+    BUILTIN_LANGUAGES
+        .iter()
+        .find(|(_, extensions, _, _)| extensions.contains(&extension.as_str()))
+        .map(|(language, _, _, _)| language.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::codegraph::parser::CodeParser;
+
+    #[test]
+    fn test_lists_builtin_languages() {
+        let languages = capabilities();
+        let language = |name: &str| languages.iter().find(|l| l.language == name).unwrap();
+
+        let typescript = language("typescript");
+        assert_eq!(typescript.extensions, vec!["ts", "tsx"]);
+        let grammars: Vec<&str> = typescript.grammars.iter().map(|g| g.name.as_str()).collect();
+        assert_eq!(grammars, vec!["typescript", "typescriptreact"]);
+        assert!(typescript.grammars[0].info.abi_version > 0);
+        assert_eq!(language("objc").grammars[0].name, "cpp");
+
+        let sql = language("sql");
+        assert_eq!((sql.analyzer, sql.features.calls, sql.features.imports), ("scanner", true, false));
+        let kotlin = language("kotlin");
+        assert_eq!((kotlin.analyzer, kotlin.features), ("tree-sitter", Features::ALL));
+        let ruby = language("ruby");
+        assert_eq!((ruby.analyzer, ruby.features), ("tree-sitter", Features::ALL));
+        let swift = language("swift");
+        assert_eq!((swift.analyzer, swift.features), ("tree-sitter", Features::ALL));
+        let scala = language("scala");
+        assert_eq!((scala.analyzer, scala.features), ("tree-sitter", Features::ALL));
+
+        // 列出的扩展名都会被构建扫描
+        let parser = CodeParser::new();
+        for capability in languages.iter().filter(|l| l.available) {
+            for extension in &capability.extensions {
+                assert!(parser.is_supported_file(Path::new(&format!("file.{}", extension))), "{}", extension);
+            }
+        }
+    }
+}
@@ -0,0 +1,193 @@
+use std::collections::HashMap;
+use std::path::Path;
+
+use uuid::Uuid;
+
+use crate::codegraph::treesitter::structs::SymbolType;
+use crate::codegraph::treesitter::AstSymbolInstanceArc;
+
+/// 一个文件中的匿名函数（闭包、lambda、匿名函数表达式）。
+///
+/// 解析器把匿名函数作为没有名字的函数声明输出，图中按外层函数命名为 `外层::<lambda@行号>`；
+/// 文件顶层的匿名函数以文件名（不含扩展名）作为外层，嵌套的匿名函数以外层匿名函数的名字作为前缀。
+/// 同一外层中同一行有多个匿名函数时，名字带上列号（从1开始）：`外层::<lambda@行号:列号>`。
+#[derive(Debug, Default)]
+pub struct Closures {
+    /// 匿名函数符号的 guid -> 图中的名字
+    names: HashMap<Uuid, String>,
+    /// 匿名函数符号的 guid -> 外层函数符号的 guid
+    parents: HashMap<Uuid, Uuid>,
+}
+
+struct Node {
+    function: bool,
+    name: String,
+    parent: Option<Uuid>,
+    line: usize,
+    column: usize,
+}
+
+impl Closures {
+    pub fn new(symbols: &[AstSymbolInstanceArc], file_path: &Path) -> Self {
+        let nodes: HashMap<Uuid, Node> = symbols
+            .iter()
+            .map(|symbol| {
+                let symbol = symbol.read();
+                let node = Node {
+                    function: symbol.symbol_type() == SymbolType::FunctionDeclaration,
+                    name: symbol.name().to_string(),
+                    parent: *symbol.parent_guid(),
+                    line: symbol.full_range().start_point.row + 1,
+                    column: symbol.full_range().start_point.column + 1,
+                };
+                (*symbol.guid(), node)
+            })
+            .collect();
+        let module = file_path.file_stem().map(|stem| stem.to_string_lossy().to_string()).unwrap_or_default();
+
+        // 同一外层、同一行的匿名函数个数
+        let mut per_line: HashMap<(Option<Uuid>, usize), usize> = HashMap::new();
+        for (guid, node) in &nodes {
+            if node.function && node.name.is_empty() {
+                *per_line.entry((enclosing_function(&nodes, guid), node.line)).or_default() += 1;
+            }
+        }
+
+        let mut closures = Self::default();
+        for (guid, node) in &nodes {
+            if node.function && node.name.is_empty() {
+                closures.resolve(&nodes, &per_line, guid, &module);
+            }
+        }
+        closures
+    }
+
+    /// 匿名函数在图中的名字，具名函数返回 `None`
+    pub fn name(&self, guid: &Uuid) -> Option<&str> {
+        self.names.get(guid).map(String::as_str)
+    }
+
+    /// 匿名函数的外层函数，文件顶层的匿名函数没有外层函数
+    pub fn parent(&self, guid: &Uuid) -> Option<Uuid> {
+        self.parents.get(guid).copied()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.names.is_empty()
+    }
+
+    fn resolve(
+        &mut self,
+        nodes: &HashMap<Uuid, Node>,
+        per_line: &HashMap<(Option<Uuid>, usize), usize>,
+        guid: &Uuid,
+        module: &str,
+    ) -> String {
+        let node = &nodes[guid];
+        if !node.name.is_empty() {
+            return node.name.clone();
+        }
+        if let Some(name) = self.names.get(guid) {
+            return name.clone();
+        }
+        let parent = enclosing_function(nodes, guid);
+        let prefix = match parent {
+            Some(parent) => self.resolve(nodes, per_line, &parent, module),
+            None => module.to_string(),
+        };
+        let name = if per_line.get(&(parent, node.line)).is_some_and(|count| *count > 1) {
+            format!("{}::<lambda@{}:{}>", prefix, node.line, node.column)
+        } else {
+            format!("{}::<lambda@{}>", prefix, node.line)
+        };
+        if let Some(parent) = parent {
+            self.parents.insert(*guid, parent);
+        }
+        self.names.insert(*guid, name.clone());
+        name
+    }
+}
+
+/// 沿 `parent_guid` 向上找到的第一个函数声明
+fn enclosing_function(nodes: &HashMap<Uuid, Node>, guid: &Uuid) -> Option<Uuid> {
+    let mut current = nodes.get(guid)?.parent;
+    // 父链不会比符号数更长，防止错误的父链形成环
+    for _ in 0..nodes.len() {
+        let parent = current?;
+        let node = nodes.get(&parent)?;
+        if node.function {
+            return Some(parent);
+        }
+        current = node.parent;
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::codegraph::treesitter::TreeSitterParser;
+    use std::path::PathBuf;
+
+    fn closure_names(file_name: &str, code: &str) -> Vec<(String, Option<String>)> {
+        let dir = tempfile::tempdir().unwrap();
+        let path: PathBuf = dir.path().join(file_name);
+        std::fs::write(&path, code).unwrap();
+        let symbols = TreeSitterParser::new().parse_file(&path).unwrap();
+        let closures = Closures::new(&symbols, &path);
+        let named: HashMap<Uuid, String> = symbols
+            .iter()
+            .map(|symbol| {
+                let symbol = symbol.read();
+                (*symbol.guid(), symbol.name().to_string())
+            })
+            .collect();
+        let mut names: Vec<(String, Option<String>)> = symbols
+            .iter()
+            .filter_map(|symbol| {
+                let guid = *symbol.read().guid();
+                let name = closures.name(&guid)?.to_string();
+                let parent = closures.parent(&guid).map(|parent| closures.name(&parent).map_or_else(|| named[&parent].clone(), str::to_string));
+                Some((name, parent))
+            })
+            .collect();
+        names.sort();
+        names
+    }
+
+    #[test]
+    fn test_closures_named_after_enclosing_function() {
+        let code = "fn run(items: Vec<i32>) {\n    items.iter().for_each(|x| {\n        let f = |y: i32| y + 1;\n        f(*x);\n    });\n}\n";
+        assert_eq!(
+            closure_names("jobs.rs", code),
+            vec![
+                ("run::<lambda@2>".to_string(), Some("run".to_string())),
+                ("run::<lambda@2>::<lambda@3>".to_string(), Some("run::<lambda@2>".to_string())),
+            ]
+        );
+        // 文件顶层的匿名函数以文件名作为外层
+        assert_eq!(closure_names("server.js", "app.get('/', () => {});\n"), vec![("server::<lambda@1>".to_string(), None)]);
+    }
+
+    #[test]
+    fn test_closures_on_one_line_get_columns() {
+        let code = "fn run(items: Vec<i32>) -> Vec<i32> {\n    items.into_iter().map(|x| x + 1).filter(|x| *x > 2).collect()\n}\n";
+        assert_eq!(
+            closure_names("jobs.rs", code),
+            vec![
+                ("run::<lambda@2:27>".to_string(), Some("run".to_string())),
+                ("run::<lambda@2:45>".to_string(), Some("run".to_string())),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_broken_closure_parameters() {
+        // 错误恢复得到的残缺参数仍然产生匿名函数，解析器不崩溃
+        for parameters in ["|: i32, y|", "|mut: i32|", "|x: i32, : u8|", "|#[a] : u8|"] {
+            let code = format!("fn run() {{\n    let f = {} 1;\n}}\n", parameters);
+            let names = closure_names("broken.rs", &code);
+            assert!(names.iter().all(|(name, _)| name.starts_with("run::<lambda@2")), "{}: {:?}", parameters, names);
+        }
+    }
+}
@@ -0,0 +1,217 @@
+use tree_sitter::{Node, Parser};
+
+use crate::codegraph::types::FunctionInfo;
+
+/// 被装饰的声明是函数还是类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DecoratedKind {
+    Function,
+    Class,
+}
+
+/// 一个 Python 装饰器、Java 注解或 TypeScript 装饰器及其装饰的声明
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Decoration {
+    /// 写出的名字，不含 `@` 和参数，例如 `app.route`、`Scheduled`、`Get`
+    pub name: String,
+    /// 括号中参数的原文，没有括号时为 `None`
+    pub arguments: Option<String>,
+    /// 装饰器所在行（从1开始）
+    pub line: usize,
+    pub target: DecoratedKind,
+    pub target_name: String,
+    /// 被装饰声明的名字所在行，落在图中对应函数或类的行范围内
+    pub target_line: usize,
+}
+
+impl Decoration {
+    /// 图中被装饰的函数：同名且行范围包含声明名字的最内层函数
+    pub fn find_function<'a>(&self, functions: impl IntoIterator<Item = &'a FunctionInfo>) -> Option<&'a FunctionInfo> {
+        if self.target != DecoratedKind::Function {
+            return None;
+        }
+        functions
+            .into_iter()
+            .filter(|f| f.name == self.target_name && f.line_start <= self.target_line && self.target_line <= f.line_end)
+            .min_by_key(|f| f.line_end - f.line_start)
+    }
+
+    /// 框架在运行时调用被装饰函数时的入口类型，例如 `http`、`scheduled`；其他装饰器返回 `None`
+    pub fn entry_point_kind(&self, language: &str) -> Option<&'static str> {
+        if self.target != DecoratedKind::Function {
+            return None;
+        }
+        let short = self.name.rsplit('.').next().unwrap_or(&self.name);
+        let (_, _, kind, qualified) = FRAMEWORK_DECORATORS
+            .iter()
+            .find(|(lang, names, _, _)| *lang == language && names.contains(&short))?;
+        // `@app.get` 是路由，单独的 `@get` 不一定是
+        (!qualified || self.name.contains('.')).then_some(*kind)
+    }
+}
+
+/// 由框架调用被装饰函数的装饰器：语言、名字的最后一段、入口类型、是否必须写出接收者（`app.`、`router.`）
+const FRAMEWORK_DECORATORS: &[(&str, &[&str], &str, bool)] = &[
+    // Flask、FastAPI、Starlette 等
+    ("python", &["route", "get", "post", "put", "delete", "patch", "head", "options", "websocket", "api_route"], "http", true),
+    // Celery
+    ("python", &["task", "shared_task", "periodic_task"], "task", false),
+    // Spring
+    ("java", &["RequestMapping", "GetMapping", "PostMapping", "PutMapping", "DeleteMapping", "PatchMapping"], "http", false),
+    ("java", &["Scheduled"], "scheduled", false),
+    ("java", &["EventListener", "KafkaListener", "RabbitListener", "JmsListener", "SqsListener"], "message", false),
+    // NestJS
+    ("typescript", &["Get", "Post", "Put", "Delete", "Patch", "Head", "Options", "All"], "http", false),
+    ("typescript", &["Cron", "Interval", "Timeout"], "scheduled", false),
+    ("typescript", &["OnEvent", "EventPattern", "MessagePattern", "Process"], "message", false),
+];
+
+fn language_of(language: &str) -> Option<tree_sitter::Language> {
+    match language {
+        "python" => Some(tree_sitter_python::LANGUAGE.into()),
+        "java" => Some(tree_sitter_java::LANGUAGE.into()),
+        "typescript" => Some(tree_sitter_typescript::LANGUAGE_TYPESCRIPT.into()),
+        _ => None,
+    }
+}
+
+/// 扫描 Python、Java 和 TypeScript 文件中装饰函数、方法和类型的装饰器（注解），其余语言返回空。
+/// 参数、字段上的注解不记录
+pub fn scan_decorators(language: &str, code: &str) -> Vec<Decoration> {
+    let Some(grammar) = language_of(language) else {
+        return Vec::new();
+    };
+    let mut parser = Parser::new();
+    if parser.set_language(&grammar).is_err() {
+        return Vec::new();
+    }
+    let Some(tree) = parser.parse(code, None) else {
+        return Vec::new();
+    };
+
+    let mut decorations = Vec::new();
+    let mut stack = vec![tree.root_node()];
+    while let Some(node) = stack.pop() {
+        if let Some(decoration) = decoration(node, code) {
+            decorations.push(decoration);
+        }
+        let mut cursor = node.walk();
+        stack.extend(node.named_children(&mut cursor));
+    }
+    decorations.sort_by_key(|d| (d.target_line, d.line));
+    decorations
+}
+
+fn text<'a>(node: Node, code: &'a str) -> &'a str {
+    code.get(node.byte_range()).unwrap_or_default()
+}
+
+fn decoration(node: Node, code: &str) -> Option<Decoration> {
+    let (name, arguments, target) = match node.kind() {
+        // Python `decorator`、TypeScript `decorator`：`@name`、`@name(..)`
+        "decorator" => {
+            let expression = node.named_child(0)?;
+            let (callee, arguments) = match expression.kind() {
+                "call" | "call_expression" => (expression.child_by_field_name("function")?, expression.child_by_field_name("arguments")),
+                _ => (expression, None),
+            };
+            (text(callee, code), arguments, decorated(node)?)
+        }
+        // Java 注解在声明的 modifiers 中
+        "marker_annotation" | "annotation" => {
+            let modifiers = node.parent().filter(|parent| parent.kind() == "modifiers")?;
+            (text(node.child_by_field_name("name")?, code), node.child_by_field_name("arguments"), modifiers.parent()?)
+        }
+        _ => return None,
+    };
+    let kind = match target.kind() {
+        "function_definition" | "method_definition" | "method_declaration" | "constructor_declaration" | "function_declaration" => {
+            DecoratedKind::Function
+        }
+        "class_definition" | "class_declaration" | "abstract_class_declaration" | "interface_declaration" | "enum_declaration"
+        | "record_declaration" => DecoratedKind::Class,
+        _ => return None,
+    };
+    let target_name = target.child_by_field_name("name")?;
+    let arguments = arguments.map(|arguments| {
+        let arguments = text(arguments, code).trim();
+        let inner = arguments.strip_prefix('(').and_then(|a| a.strip_suffix(')')).unwrap_or(arguments);
+        inner.split_whitespace().collect::<Vec<_>>().join(" ")
+    });
+    Some(Decoration {
+        name: name.split_whitespace().collect(),
+        arguments,
+        line: node.start_position().row + 1,
+        target: kind,
+        target_name: text(target_name, code).to_string(),
+        target_line: target_name.start_position().row + 1,
+    })
+}
+
+/// 装饰器所装饰的声明
+fn decorated(decorator: Node) -> Option<Node> {
+    let parent = decorator.parent()?;
+    match parent.kind() {
+        "decorated_definition" => parent.child_by_field_name("definition"),
+        "export_statement" => parent.child_by_field_name("declaration"),
+        // TypeScript 类成员的装饰器写在成员之前，是类体的子节点
+        "class_body" => {
+            let mut sibling = decorator.next_named_sibling();
+            while let Some(node) = sibling.filter(|node| node.kind() == "decorator") {
+                sibling = node.next_named_sibling();
+            }
+            sibling
+        }
+        _ => Some(parent),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn summary(language: &str, code: &str) -> Vec<(String, Option<String>, String, Option<&'static str>)> {
+        scan_decorators(language, code)
+            .into_iter()
+            .map(|d| {
+                let kind = d.entry_point_kind(language);
+                (d.name, d.arguments, d.target_name, kind)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_scan_decorators() {
+        let python = "@app.route('/users', methods=['GET'])\n@login_required\ndef users():\n    pass\n\n@dataclass\nclass User:\n    @get\n    def name(self):\n        pass\n";
+        assert_eq!(
+            summary("python", python),
+            vec![
+                ("app.route".into(), Some("'/users', methods=['GET']".into()), "users".into(), Some("http")),
+                ("login_required".into(), None, "users".into(), None),
+                ("dataclass".into(), None, "User".into(), None),
+                ("get".into(), None, "name".into(), None),
+            ]
+        );
+
+        let java = "@RestController\nclass Jobs {\n  @Scheduled(fixedRate = 1000)\n  @Override\n  public void run(@RequestParam String id) {}\n}\n";
+        assert_eq!(
+            summary("java", java),
+            vec![
+                ("RestController".into(), None, "Jobs".into(), None),
+                ("Scheduled".into(), Some("fixedRate = 1000".into()), "run".into(), Some("scheduled")),
+                ("Override".into(), None, "run".into(), None),
+            ]
+        );
+
+        let typescript = "@Controller('users')\nexport class Users {\n  @Get(':id')\n  @UseGuards(AuthGuard)\n  find(id: string) {}\n}\n";
+        assert_eq!(
+            summary("typescript", typescript),
+            vec![
+                ("Controller".into(), Some("'users'".into()), "Users".into(), None),
+                ("Get".into(), Some("':id'".into()), "find".into(), Some("http")),
+                ("UseGuards".into(), Some("AuthGuard".into()), "find".into(), None),
+            ]
+        );
+        assert!(scan_decorators("rust", "#[test]\nfn f() {}\n").is_empty());
+    }
+}
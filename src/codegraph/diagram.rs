@@ -0,0 +1,290 @@
+use std::collections::{BTreeMap, HashMap};
+use std::path::PathBuf;
+
+use uuid::Uuid;
+
+use super::types::{CallRelation, FunctionInfo, FunctionKind};
+
+/// 按语言着色（填充色）
+pub(crate) fn language_color(language: &str) -> &'static str {
+    match language {
+        "rust" => "#dea584",
+        "python" => "#ffe873",
+        "javascript" => "#f7df1e",
+        "typescript" => "#9cc3e6",
+        "java" => "#f89820",
+        "cpp" => "#f34b7d",
+        "go" => "#7fd5ea",
+        "kotlin" => "#a97bff",
+        "ruby" => "#cc342d",
+        "swift" => "#f05138",
+        "scala" => "#c22d40",
+        "objc" => "#438eff",
+        "lua" => "#000080",
+        "bash" => "#89e051",
+        "sql" => "#e38c00",
+        "proto" => "#60a0b0",
+        "vue" => "#41b883",
+        "svelte" => "#ff3e00",
+        "html" => "#e34c26",
+        _ => "#e0e0e0",
+    }
+}
+
+/// 同一文件的函数放在一个分组中，分组按路径排序以保证输出稳定
+fn group_by_file<'a>(functions: &[&'a FunctionInfo]) -> BTreeMap<&'a PathBuf, Vec<&'a FunctionInfo>> {
+    let mut files: BTreeMap<&PathBuf, Vec<&FunctionInfo>> = BTreeMap::new();
+    for function in functions {
+        files.entry(&function.file_path).or_default().push(function);
+    }
+    for group in files.values_mut() {
+        group.sort_by(|a, b| a.line_start.cmp(&b.line_start).then_with(|| a.name.cmp(&b.name)));
+    }
+    files
+}
+
+fn location(function: &FunctionInfo) -> String {
+    format!("{}:{}", function.file_path.display(), function.line_start)
+}
+
+/// DOT和D2的双引号字符串使用相同的转义规则
+fn quote_escape(text: &str) -> String {
+    text.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+fn mermaid_escape(text: &str) -> String {
+    text.replace('"', "#quot;")
+}
+
+/// 生成DOT：每个文件一个cluster，节点带 `file:line` 提示并按语言着色，未解析的调用用虚线
+pub fn render_dot(functions: &[&FunctionInfo], relations: &[&CallRelation]) -> String {
+    let mut dot = String::from("digraph CodeGraph {\n");
+    dot.push_str("    rankdir=TB;\n");
+    dot.push_str("    node [shape=box, style=\"rounded,filled\"];\n\n");
+
+    let mut node_ids: HashMap<Uuid, String> = HashMap::new();
+    for (cluster, (file_path, group)) in group_by_file(functions).into_iter().enumerate() {
+        dot.push_str(&format!("    subgraph cluster_{} {{\n", cluster));
+        dot.push_str(&format!("        label=\"{}\";\n", quote_escape(&file_path.display().to_string())));
+        dot.push_str("        style=dashed;\n");
+        for function in group {
+            let node_id = format!("n{}", function.id.simple());
+            let style = if function.is_unresolved() { ", style=\"rounded,dashed\"" } else { "" };
+            let namespace = if function.namespace.is_empty() || function.is_unresolved() {
+                String::new()
+            } else {
+                format!("\\n{}", quote_escape(&function.namespace))
+            };
+            dot.push_str(&format!(
+                "        {} [label=\"{}{}\", tooltip=\"{}\", fillcolor=\"{}\"{}];\n",
+                node_id,
+                quote_escape(&function.name),
+                namespace,
+                quote_escape(&location(function)),
+                language_color(&function.language),
+                style
+            ));
+            node_ids.insert(function.id, node_id);
+        }
+        dot.push_str("    }\n");
+    }
+    dot.push('\n');
+
+    for relation in relations {
+        let (Some(caller), Some(callee)) = (node_ids.get(&relation.caller_id), node_ids.get(&relation.callee_id)) else {
+            continue;
+        };
+        let tooltip = format!("{}:{}", relation.caller_file.display(), relation.line_number);
+        let style = if relation.is_resolved { "" } else { ", style=dashed, color=gray40" };
+        dot.push_str(&format!("    {} -> {} [tooltip=\"{}\"{}];\n", caller, callee, quote_escape(&tooltip), style));
+    }
+
+    dot.push_str("}\n");
+    dot
+}
+
+/// 生成Mermaid流程图：每个文件一个subgraph，节点按语言着色并带 `file:line` 提示，未解析的调用用虚线
+pub fn render_mermaid(functions: &[&FunctionInfo], relations: &[&CallRelation]) -> String {
+    let mut mermaid = String::from("flowchart TB\n");
+    let mut node_ids: HashMap<Uuid, String> = HashMap::new();
+    let mut classes: BTreeMap<String, Vec<String>> = BTreeMap::new();
+    let mut tooltips = Vec::new();
+
+    for (index, (file_path, group)) in group_by_file(functions).into_iter().enumerate() {
+        mermaid.push_str(&format!(
+            "    subgraph file{}[\"{}\"]\n",
+            index,
+            mermaid_escape(&file_path.display().to_string())
+        ));
+        for function in group {
+            let node_id = format!("n{}", node_ids.len());
+            mermaid.push_str(&format!("        {}[\"{}\"]\n", node_id, mermaid_escape(&function.name)));
+            let class = if function.is_unresolved() {
+                FunctionKind::Unresolved.as_str().to_string()
+            } else {
+                format!("lang_{}", function.language)
+            };
+            classes.entry(class).or_default().push(node_id.clone());
+            tooltips.push(format!(
+                "    click {} \"{}\" \"{}\"\n",
+                node_id,
+                mermaid_escape(&function.file_path.display().to_string()),
+                mermaid_escape(&location(function))
+            ));
+            node_ids.insert(function.id, node_id);
+        }
+        mermaid.push_str("    end\n");
+    }
+
+    for relation in relations {
+        let (Some(caller), Some(callee)) = (node_ids.get(&relation.caller_id), node_ids.get(&relation.callee_id)) else {
+            continue;
+        };
+        let arrow = if relation.is_resolved { "-->" } else { "-.->" };
+        mermaid.push_str(&format!("    {} {} {}\n", caller, arrow, callee));
+    }
+
+    for line in tooltips {
+        mermaid.push_str(&line);
+    }
+    for (class, nodes) in &classes {
+        let style = match class.strip_prefix("lang_") {
+            Some(language) => format!("fill:{},stroke:#555", language_color(language)),
+            None => "fill:#f5f5f5,stroke:#999,stroke-dasharray:4 3".to_string(),
+        };
+        mermaid.push_str(&format!("    classDef {} {}\n", class, style));
+        mermaid.push_str(&format!("    class {} {}\n", nodes.join(","), class));
+    }
+    mermaid
+}
+
+/// 生成D2：每个文件一个容器，节点按语言着色并带 `file:line` 提示，未解析的调用用虚线
+pub fn render_d2(functions: &[&FunctionInfo], relations: &[&CallRelation]) -> String {
+    let mut d2 = String::from("direction: down\n\n");
+    let mut node_ids: HashMap<Uuid, String> = HashMap::new();
+
+    for (index, (file_path, group)) in group_by_file(functions).into_iter().enumerate() {
+        let container = format!("file{}", index);
+        d2.push_str(&format!("{}: \"{}\" {{\n", container, quote_escape(&file_path.display().to_string())));
+        for function in group {
+            let node_id = format!("n{}", node_ids.len());
+            d2.push_str(&format!("  {}: \"{}\" {{\n", node_id, quote_escape(&function.name)));
+            d2.push_str(&format!("    tooltip: \"{}\"\n", quote_escape(&location(function))));
+            if function.is_unresolved() {
+                d2.push_str("    style.stroke-dash: 3\n");
+            } else {
+                d2.push_str(&format!("    style.fill: \"{}\"\n", language_color(&function.language)));
+            }
+            d2.push_str("  }\n");
+            node_ids.insert(function.id, format!("{}.{}", container, node_id));
+        }
+        d2.push_str("}\n");
+    }
+    d2.push('\n');
+
+    for relation in relations {
+        let (Some(caller), Some(callee)) = (node_ids.get(&relation.caller_id), node_ids.get(&relation.callee_id)) else {
+            continue;
+        };
+        if relation.is_resolved {
+            d2.push_str(&format!("{} -> {}\n", caller, callee));
+        } else {
+            d2.push_str(&format!("{} -> {}: {{style.stroke-dash: 3}}\n", caller, callee));
+        }
+    }
+    d2
+}
+
+fn plantuml_escape(text: &str) -> String {
+    text.replace('"', "'")
+}
+
+/// 生成PlantUML：每个文件一个package，节点按语言着色并带 `file:line` 提示，未解析的调用用虚线
+pub fn render_plantuml(functions: &[&FunctionInfo], relations: &[&CallRelation]) -> String {
+    let mut uml = String::from("@startuml\nskinparam rectangle {\n  RoundCorner 10\n}\n\n");
+    let mut node_ids: HashMap<Uuid, String> = HashMap::new();
+
+    for (file_path, group) in group_by_file(functions) {
+        uml.push_str(&format!("package \"{}\" {{\n", plantuml_escape(&file_path.display().to_string())));
+        for function in group {
+            let node_id = format!("n{}", node_ids.len());
+            let style = if function.is_unresolved() {
+                "#f5f5f5;line.dashed".to_string()
+            } else {
+                language_color(&function.language).to_string()
+            };
+            uml.push_str(&format!(
+                "  rectangle \"{}\" as {} {} [[{{{}}}]]\n",
+                plantuml_escape(&function.name),
+                node_id,
+                style,
+                plantuml_escape(&location(function))
+            ));
+            node_ids.insert(function.id, node_id);
+        }
+        uml.push_str("}\n");
+    }
+    uml.push('\n');
+
+    for relation in relations {
+        let (Some(caller), Some(callee)) = (node_ids.get(&relation.caller_id), node_ids.get(&relation.callee_id)) else {
+            continue;
+        };
+        let arrow = if relation.is_resolved { "-->" } else { "..>" };
+        uml.push_str(&format!("{} {} {}\n", caller, arrow, callee));
+    }
+    uml.push_str("@enduml\n");
+    uml
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn function(name: &str, file: &str, line: usize, namespace: &str) -> FunctionInfo {
+        FunctionInfo {
+            id: Uuid::new_v4(),
+            name: name.to_string(),
+            file_path: PathBuf::from(file),
+            line_start: line,
+            line_end: line + 2,
+            namespace: namespace.to_string(),
+            language: "rust".to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_diagrams_cluster_by_file_and_style_unresolved_calls() {
+        let main = function("main", "src/main.rs", 3, "");
+        let parse = function("parse", "src/parser.rs", 10, "parser");
+        let missing = FunctionInfo { kind: FunctionKind::Unresolved, ..function("missing", "src/main.rs", 0, "unresolved") };
+        let relations = [CallRelation::between(&main, &parse), CallRelation::between(&main, &missing)];
+        let functions = [&main, &parse, &missing];
+        let relations: Vec<_> = relations.iter().collect();
+
+        let dot = render_dot(&functions, &relations);
+        assert_eq!(dot.matches("subgraph cluster_").count(), 2);
+        assert!(dot.contains("label=\"src/parser.rs\""));
+        assert!(dot.contains(&format!("n{} [label=\"parse\\nparser\", tooltip=\"src/parser.rs:10\", fillcolor=\"#dea584\"]", parse.id.simple())));
+        assert!(dot.contains(&format!("n{} -> n{} [tooltip=\"src/main.rs:4\", style=dashed", main.id.simple(), missing.id.simple())));
+
+        let mermaid = render_mermaid(&functions, &relations);
+        assert!(mermaid.starts_with("flowchart TB\n    subgraph file0[\"src/main.rs\"]\n"));
+        assert!(mermaid.contains("    n1 -.-> n0\n") && mermaid.contains("    n1 --> n2\n"));
+        assert!(mermaid.contains("    click n2 \"src/parser.rs\" \"src/parser.rs:10\"\n"));
+        assert!(mermaid.contains("    class n1,n2 lang_rust\n"));
+        assert!(mermaid.contains("    class n0 unresolved\n"));
+
+        let d2 = render_d2(&functions, &relations);
+        assert!(d2.contains("file0: \"src/main.rs\" {\n  n0: \"missing\" {\n    tooltip: \"src/main.rs:0\"\n    style.stroke-dash: 3\n"));
+        assert!(d2.contains("file1: \"src/parser.rs\" {\n  n2: \"parse\" {\n    tooltip: \"src/parser.rs:10\"\n    style.fill: \"#dea584\"\n"));
+        assert!(d2.contains("\nfile0.n1 -> file1.n2\n") && d2.contains("\nfile0.n1 -> file0.n0: {style.stroke-dash: 3}\n"));
+
+        let uml = render_plantuml(&functions, &relations);
+        assert!(uml.starts_with("@startuml\n") && uml.ends_with("@enduml\n"));
+        assert!(uml.contains("package \"src/parser.rs\" {\n  rectangle \"parse\" as n2 #dea584 [[{src/parser.rs:10}]]\n}\n"));
+        assert!(uml.contains("  rectangle \"missing\" as n0 #f5f5f5;line.dashed [[{src/main.rs:0}]]\n"));
+        assert!(uml.contains("\nn1 --> n2\n") && uml.contains("\nn1 ..> n0\n"));
+    }
+}
@@ -0,0 +1,556 @@
+use std::collections::{BTreeMap, HashSet};
+use std::fs;
+use std::path::PathBuf;
+
+use serde::{Deserialize, Serialize};
+use tree_sitter::{Node, Parser, Tree};
+use uuid::Uuid;
+
+use crate::codegraph::decorators::{scan_decorators, Decoration};
+use crate::codegraph::types::{FunctionInfo, PetCodeGraph};
+
+/// 由 Web、定时任务或消息框架调用的函数。
+///
+/// 框架在运行时调用这些函数，项目代码中通常没有调用者，因此它们是调用图的根：
+/// 死代码检查不报告它们，层级视图默认从它们展开。构建时检测并随图保存。
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EntryPoint {
+    pub function_id: Uuid,
+    /// `axum`、`actix`、`rocket`、`spring`、`flask`、`fastapi`、`express`、`celery` 或 `nestjs`
+    pub framework: String,
+    /// `http`、`scheduled`、`task` 或 `message`
+    pub kind: String,
+    /// HTTP 方法（大写），不限方法或不是 HTTP 入口时为 `None`
+    pub method: Option<String>,
+    /// 路由路径，只有写成字符串字面量时才能得到
+    pub route: Option<String>,
+    /// 注册入口的装饰器、注解或调用，例如 `app.route`、`GetMapping`、`router.get`、`get`
+    pub decorator: String,
+    /// 装饰器、注解参数的原文
+    pub arguments: Option<String>,
+    /// 注册所在的文件和行（从1开始）
+    pub file_path: PathBuf,
+    pub line: usize,
+}
+
+/// 注册的处理函数在源码中的写法
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Handler {
+    /// 按名字引用的函数，取路径的最后一段
+    Named(String),
+    /// 直接写在注册处的匿名函数，记录其起始行和列（都从1开始）
+    Closure(usize, usize),
+    /// 被装饰的函数
+    Decorated(Decoration),
+}
+
+/// 在一个文件中找到的入口注册，尚未对应到图中的函数
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Registration {
+    handler: Handler,
+    framework: &'static str,
+    kind: &'static str,
+    method: Option<String>,
+    route: Option<String>,
+    decorator: String,
+    arguments: Option<String>,
+    line: usize,
+}
+
+const HTTP_METHODS: &[&str] = &["GET", "POST", "PUT", "DELETE", "PATCH", "HEAD", "OPTIONS"];
+
+/// axum 的 `MethodRouter` 构造函数和方法
+const AXUM_ROUTERS: &[&str] = &["get", "post", "put", "delete", "patch", "head", "options", "trace", "any"];
+
+/// actix-web（以及 Rocket）的路由属性宏
+const ROUTE_ATTRIBUTES: &[&str] = &["get", "post", "put", "delete", "patch", "head", "options", "trace", "route"];
+
+/// Express 的 `app.get('/path', handler)` 一类注册方法
+const EXPRESS_METHODS: &[&str] = &["get", "post", "put", "delete", "patch", "head", "options", "all"];
+
+/// 找出图中所有由框架调用的函数，按注册所在的文件和行排序。
+///
+/// 注册方式从图中函数所在的源文件重新读取；读不到的文件跳过。
+/// 按名字引用的处理函数优先在注册所在文件中查找，其次在同一语言的其他文件中按名字查找。
+pub fn detect_entry_points(graph: &PetCodeGraph) -> Vec<EntryPoint> {
+    let mut files: BTreeMap<&PathBuf, Vec<&FunctionInfo>> = BTreeMap::new();
+    for function in graph.get_all_functions() {
+        if !function.is_unresolved() {
+            files.entry(&function.file_path).or_default().push(function);
+        }
+    }
+
+    let mut entry_points = Vec::new();
+    let mut seen = HashSet::new();
+    for (file_path, functions) in files {
+        let language = functions[0].language.as_str();
+        if !matches!(language, "rust" | "python" | "java" | "javascript" | "typescript") {
+            continue;
+        }
+        let Ok(code) = fs::read_to_string(file_path) else {
+            continue;
+        };
+        for registration in registrations(language, &code) {
+            let Some(function) = resolve(graph, &registration.handler, language, &functions) else {
+                continue;
+            };
+            if !seen.insert((function.id, registration.line)) {
+                continue;
+            }
+            entry_points.push(EntryPoint {
+                function_id: function.id,
+                framework: registration.framework.to_string(),
+                kind: registration.kind.to_string(),
+                method: registration.method,
+                route: registration.route,
+                decorator: registration.decorator,
+                arguments: registration.arguments,
+                file_path: file_path.clone(),
+                line: registration.line,
+            });
+        }
+    }
+    entry_points.sort_by(|a, b| (&a.file_path, a.line).cmp(&(&b.file_path, b.line)));
+    entry_points
+}
+
+fn resolve<'a>(
+    graph: &'a PetCodeGraph,
+    handler: &Handler,
+    language: &str,
+    functions: &[&'a FunctionInfo],
+) -> Option<&'a FunctionInfo> {
+    match handler {
+        Handler::Decorated(decoration) => decoration.find_function(functions.iter().copied()),
+        Handler::Closure(line, column) => {
+            // 同一行有多个匿名函数时名字带列号
+            let suffixes = [format!("<lambda@{}>", line), format!("<lambda@{}:{}>", line, column)];
+            functions.iter().copied().find(|f| f.line_start == *line && suffixes.iter().any(|suffix| f.name.ends_with(suffix)))
+        }
+        Handler::Named(name) => functions.iter().copied().find(|f| f.name == *name).or_else(|| {
+            graph
+                .find_functions_by_name(name)
+                .into_iter()
+                .filter(|f| f.language == language && !f.is_unresolved())
+                .min_by(|a, b| (&a.file_path, a.line_start).cmp(&(&b.file_path, b.line_start)))
+        }),
+    }
+}
+
+fn registrations(language: &str, code: &str) -> Vec<Registration> {
+    match language {
+        "python" | "java" => decorated(language, code),
+        "typescript" => {
+            let mut registrations = decorated(language, code);
+            if let Some(tree) = parse(tree_sitter_typescript::LANGUAGE_TYPESCRIPT.into(), code) {
+                registrations.extend(express(&tree, code));
+            }
+            registrations
+        }
+        "javascript" => parse(tree_sitter_javascript::LANGUAGE.into(), code)
+            .map(|tree| express(&tree, code))
+            .unwrap_or_default(),
+        "rust" => parse(tree_sitter_rust::LANGUAGE.into(), code)
+            .map(|tree| rust_routes(&tree, code))
+            .unwrap_or_default(),
+        _ => Vec::new(),
+    }
+}
+
+fn parse(grammar: tree_sitter::Language, code: &str) -> Option<Tree> {
+    let mut parser = Parser::new();
+    parser.set_language(&grammar).ok()?;
+    parser.parse(code, None)
+}
+
+fn text<'a>(node: Node, code: &'a str) -> &'a str {
+    code.get(node.byte_range()).unwrap_or_default()
+}
+
+/// 先序遍历语法树的所有命名节点
+fn named_nodes(tree: &Tree) -> Vec<Node<'_>> {
+    let mut nodes = Vec::new();
+    let mut stack = vec![tree.root_node()];
+    while let Some(node) = stack.pop() {
+        nodes.push(node);
+        let mut cursor = node.walk();
+        let children: Vec<_> = node.named_children(&mut cursor).collect();
+        stack.extend(children.into_iter().rev());
+    }
+    nodes
+}
+
+/// 文本中第一个引号括起的字符串
+fn first_string_literal(text: &str) -> Option<String> {
+    let start = text.find(['"', '\'', '`'])?;
+    let quote = text[start..].chars().next()?;
+    let rest = &text[start + 1..];
+    Some(rest[..rest.find(quote)?].to_string())
+}
+
+/// 由方法名得到 HTTP 方法：`get`、`Get`、`GetMapping` -> `GET`；`route`、`RequestMapping`、`all` 不限方法
+fn http_method(name: &str) -> Option<String> {
+    let verb = name.strip_suffix("Mapping").unwrap_or(name).to_ascii_uppercase();
+    HTTP_METHODS.contains(&verb.as_str()).then_some(verb)
+}
+
+/// Python、Java、TypeScript 中由框架装饰器注册的入口
+fn decorated(language: &str, code: &str) -> Vec<Registration> {
+    scan_decorators(language, code)
+        .into_iter()
+        .filter_map(|decoration| {
+            let kind = decoration.entry_point_kind(language)?;
+            let short = decoration.name.rsplit('.').next().unwrap_or(&decoration.name);
+            let framework = match (language, kind) {
+                // Flask 用 `@app.route`，FastAPI 用 `@app.get` 等按方法命名的装饰器
+                ("python", "http") if short == "route" => "flask",
+                ("python", "http") => "fastapi",
+                ("python", _) => "celery",
+                ("java", _) => "spring",
+                _ => "nestjs",
+            };
+            let (method, route) = if kind == "http" {
+                (http_method(short), decoration.arguments.as_deref().and_then(first_string_literal))
+            } else {
+                (None, None)
+            };
+            Some(Registration {
+                framework,
+                kind,
+                method,
+                route,
+                decorator: decoration.name.clone(),
+                arguments: decoration.arguments.clone(),
+                line: decoration.line,
+                handler: Handler::Decorated(decoration),
+            })
+        })
+        .collect()
+}
+
+/// JavaScript、TypeScript 中的 `app.get('/path', ..handlers)`：以 `/` 开头的路径之后的每个函数参数都是处理函数（包括中间件）
+fn express(tree: &Tree, code: &str) -> Vec<Registration> {
+    let mut registrations = Vec::new();
+    for node in named_nodes(tree) {
+        if node.kind() != "call_expression" {
+            continue;
+        }
+        let Some(callee) = node.child_by_field_name("function").filter(|f| f.kind() == "member_expression") else {
+            continue;
+        };
+        let Some(property) = callee.child_by_field_name("property") else {
+            continue;
+        };
+        let verb = text(property, code);
+        if !EXPRESS_METHODS.contains(&verb) {
+            continue;
+        }
+        let Some(arguments) = node.child_by_field_name("arguments") else {
+            continue;
+        };
+        let mut cursor = arguments.walk();
+        let arguments: Vec<_> = arguments.named_children(&mut cursor).collect();
+        let Some(route) = arguments.first().filter(|a| a.kind() == "string").and_then(|a| first_string_literal(text(*a, code)))
+        else {
+            continue;
+        };
+        if !route.starts_with('/') {
+            continue;
+        }
+        for argument in &arguments[1..] {
+            let handler = match argument.kind() {
+                "identifier" => Handler::Named(text(*argument, code).to_string()),
+                "member_expression" => match argument.child_by_field_name("property") {
+                    Some(property) => Handler::Named(text(property, code).to_string()),
+                    None => continue,
+                },
+                "arrow_function" | "function_expression" | "function" => {
+                    Handler::Closure(argument.start_position().row + 1, argument.start_position().column + 1)
+                }
+                _ => continue,
+            };
+            registrations.push(Registration {
+                handler,
+                framework: "express",
+                kind: "http",
+                method: http_method(verb),
+                route: Some(route.clone()),
+                decorator: text(callee, code).split_whitespace().collect(),
+                arguments: None,
+                line: argument.start_position().row + 1,
+            });
+        }
+    }
+    registrations
+}
+
+/// Rust 中的 axum `.route("/path", get(handler).post(other))`、actix-web `.route("/path", web::get().to(handler))`
+/// 以及 actix-web 和 Rocket 的 `#[get("/path")]` 属性
+fn rust_routes(tree: &Tree, code: &str) -> Vec<Registration> {
+    let mut registrations = Vec::new();
+    for node in named_nodes(tree) {
+        match node.kind() {
+            "function_item" => registrations.extend(route_attribute(node, code)),
+            "call_expression" => {
+                let Some(callee) = node.child_by_field_name("function").filter(|f| f.kind() == "field_expression") else {
+                    continue;
+                };
+                if callee.child_by_field_name("field").is_none_or(|field| text(field, code) != "route") {
+                    continue;
+                }
+                let Some(arguments) = node.child_by_field_name("arguments") else {
+                    continue;
+                };
+                let mut cursor = arguments.walk();
+                let arguments: Vec<_> = arguments.named_children(&mut cursor).collect();
+                let route = arguments
+                    .first()
+                    .filter(|a| a.kind() == "string_literal")
+                    .and_then(|a| first_string_literal(text(*a, code)));
+                let routers = if route.is_some() { &arguments[1..] } else { &arguments[..] };
+                for router in routers {
+                    for (framework, verb, decorator, handler) in method_router(*router, code) {
+                        registrations.push(Registration {
+                            framework,
+                            kind: "http",
+                            method: verb.as_deref().and_then(http_method),
+                            route: route.clone(),
+                            decorator,
+                            arguments: None,
+                            line: handler.1,
+                            handler: handler.0,
+                        });
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+    // 链式调用中最外层的 `.route` 最先遍历到，按行排回书写顺序
+    registrations.sort_by_key(|registration| registration.line);
+    registrations
+}
+
+type RouterHandler = (&'static str, Option<String>, String, (Handler, usize));
+
+/// 方法路由表达式中的处理函数：`get(a).post(b)`、`axum::routing::get(a)`、`web::get().to(a)`
+fn method_router(node: Node, code: &str) -> Vec<RouterHandler> {
+    let mut handlers = Vec::new();
+    if node.kind() != "call_expression" {
+        return handlers;
+    }
+    let (Some(function), Some(arguments)) = (node.child_by_field_name("function"), node.child_by_field_name("arguments")) else {
+        return handlers;
+    };
+    let handler = arguments.named_child(0).and_then(|argument| rust_handler(argument, code));
+    match function.kind() {
+        "identifier" | "scoped_identifier" => {
+            let verb = last_segment(function, code);
+            if let Some(handler) = handler.filter(|_| AXUM_ROUTERS.contains(&verb.as_str())) {
+                handlers.push(("axum", Some(verb), text(function, code).to_string(), handler));
+            }
+        }
+        "field_expression" => {
+            let (Some(receiver), Some(field)) = (function.child_by_field_name("value"), function.child_by_field_name("field")) else {
+                return handlers;
+            };
+            let field = text(field, code);
+            if field == "to" {
+                // actix-web：`web::get().to(handler)`，`web::route().to(handler)` 不限方法
+                let verb = receiver.child_by_field_name("function").map(|f| last_segment(f, code));
+                if let Some(handler) = handler {
+                    let decorator = text(function, code).split_whitespace().collect();
+                    handlers.push(("actix", verb, decorator, handler));
+                }
+                return handlers;
+            }
+            handlers.extend(method_router(receiver, code));
+            if let Some(handler) = handler.filter(|_| AXUM_ROUTERS.contains(&field)) {
+                handlers.push(("axum", Some(field.to_string()), field.to_string(), handler));
+            }
+        }
+        _ => {}
+    }
+    handlers
+}
+
+/// 处理函数参数及其所在行：函数路径取最后一段，闭包记录起始位置
+fn rust_handler(node: Node, code: &str) -> Option<(Handler, usize)> {
+    let line = node.start_position().row + 1;
+    match node.kind() {
+        "identifier" | "scoped_identifier" => Some((Handler::Named(last_segment(node, code)), line)),
+        "closure_expression" => Some((Handler::Closure(line, node.start_position().column + 1), line)),
+        _ => None,
+    }
+}
+
+fn last_segment(node: Node, code: &str) -> String {
+    match node.kind() {
+        "scoped_identifier" => node.child_by_field_name("name").map(|name| text(name, code)).unwrap_or_default().to_string(),
+        _ => text(node, code).to_string(),
+    }
+}
+
+/// 函数前的 `#[get("/path")]`、`#[route("/path", method = "GET")]` 属性
+fn route_attribute(function: Node, code: &str) -> Option<Registration> {
+    let name = text(function.child_by_field_name("name")?, code).to_string();
+    let mut sibling = function.prev_named_sibling();
+    while let Some(node) = sibling {
+        sibling = node.prev_named_sibling();
+        match node.kind() {
+            "attribute_item" => {}
+            "line_comment" | "block_comment" => continue,
+            _ => break,
+        }
+        let Some(attribute) = node.named_child(0).filter(|a| a.kind() == "attribute") else {
+            continue;
+        };
+        let Some(path) = attribute.named_child(0) else {
+            continue;
+        };
+        let verb = last_segment(path, code);
+        let Some(arguments) = attribute.child_by_field_name("arguments") else {
+            continue;
+        };
+        let arguments = text(arguments, code);
+        let Some(route) = first_string_literal(arguments).filter(|_| ROUTE_ATTRIBUTES.contains(&verb.as_str())) else {
+            continue;
+        };
+        let inner = arguments.strip_prefix('(').and_then(|a| a.strip_suffix(')')).unwrap_or(arguments);
+        let framework = if code.contains("rocket") { "rocket" } else { "actix" };
+        return Some(Registration {
+            handler: Handler::Named(name),
+            framework,
+            kind: "http",
+            method: http_method(&verb),
+            route: Some(route),
+            decorator: text(path, code).to_string(),
+            arguments: Some(inner.split_whitespace().collect::<Vec<_>>().join(" ")),
+            line: node.start_position().row + 1,
+        });
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn summary(language: &str, code: &str) -> Vec<(&'static str, Option<String>, Option<String>, Handler)> {
+        registrations(language, code)
+            .into_iter()
+            .map(|r| {
+                let handler = match r.handler {
+                    Handler::Decorated(decoration) => Handler::Named(decoration.target_name),
+                    handler => handler,
+                };
+                (r.framework, r.method, r.route, handler)
+            })
+            .collect()
+    }
+
+    fn named(name: &str) -> Handler {
+        Handler::Named(name.to_string())
+    }
+
+    #[test]
+    fn test_detect_entry_points() {
+        use crate::codegraph::parser::CodeParser;
+        use crate::codegraph::AnalyzeOptions;
+        use tempfile::TempDir;
+
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        fs::write(
+            root.join("main.rs"),
+            "mod handlers;\n\nfn app() -> Router {\n    Router::new()\n        .route(\"/users\", get(handlers::list_users))\n        .route(\"/health\", get(|| async { \"ok\" }))\n}\n",
+        )
+        .unwrap();
+        fs::write(root.join("handlers.rs"), "pub async fn list_users() -> String {\n    load()\n}\n\nfn load() -> String {\n    String::new()\n}\n").unwrap();
+        fs::write(root.join("server.js"), "function index(req, res) {\n  res.send('hi');\n}\n\napp.get('/', index);\napp.post('/items', (req, res) => {\n  res.send(req.body);\n});\n").unwrap();
+        let options = AnalyzeOptions {
+            storage_dir: Some(root.join(".codegraph_db")),
+            force_rebuild: true,
+            ..Default::default()
+        };
+        let graph = CodeParser::with_options(options).build_petgraph_code_graph(root).unwrap();
+
+        let detected = detect_entry_points(&graph);
+        let entry_points: Vec<_> = detected
+            .iter()
+            .map(|entry| {
+                let function = graph.get_function_by_id(&entry.function_id).unwrap();
+                let file = function.file_path.file_name().unwrap().to_string_lossy().to_string();
+                (function.name.clone(), file, entry.framework.as_str(), entry.method.clone(), entry.route.clone())
+            })
+            .collect();
+        assert_eq!(
+            entry_points,
+            vec![
+                ("list_users".to_string(), "handlers.rs".to_string(), "axum", Some("GET".to_string()), Some("/users".to_string())),
+                ("app::<lambda@6>".to_string(), "main.rs".to_string(), "axum", Some("GET".to_string()), Some("/health".to_string())),
+                ("index".to_string(), "server.js".to_string(), "express", Some("GET".to_string()), Some("/".to_string())),
+                ("server::<lambda@6>".to_string(), "server.js".to_string(), "express", Some("POST".to_string()), Some("/items".to_string())),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_rust_routes() {
+        let axum = "fn app() -> Router {\n    Router::new()\n        .route(\"/users\", get(list_users).post(handlers::create_user))\n        .route(\"/health\", axum::routing::get(|| async { \"ok\" }))\n}\n";
+        assert_eq!(
+            summary("rust", axum),
+            vec![
+                ("axum", Some("GET".into()), Some("/users".into()), named("list_users")),
+                ("axum", Some("POST".into()), Some("/users".into()), named("create_user")),
+                ("axum", Some("GET".into()), Some("/health".into()), Handler::Closure(4, 46)),
+            ]
+        );
+
+        let actix = "#[get(\"/users/{id}\")]\n// fetch one user\nasync fn user() -> impl Responder { \"\" }\n\nfn config(cfg: &mut web::ServiceConfig) {\n    cfg.route(\"/users\", web::post().to(create));\n}\n";
+        assert_eq!(
+            summary("rust", actix),
+            vec![
+                ("actix", Some("GET".into()), Some("/users/{id}".into()), named("user")),
+                ("actix", Some("POST".into()), Some("/users".into()), named("create")),
+            ]
+        );
+        // 不是路由的属性和方法调用
+        assert!(summary("rust", "#[test]\nfn get() {}\nfn f(m: Map) { m.route(\"/x\", 1); }\n").is_empty());
+    }
+
+    #[test]
+    fn test_express_routes() {
+        let js = "const app = express();\napp.get('/users', auth, listUsers);\nrouter.post('/users', (req, res) => res.send(create(req)));\nmap.get('key');\ncache.get('/path');\n";
+        assert_eq!(
+            summary("javascript", js),
+            vec![
+                ("express", Some("GET".into()), Some("/users".into()), named("auth")),
+                ("express", Some("GET".into()), Some("/users".into()), named("listUsers")),
+                ("express", Some("POST".into()), Some("/users".into()), Handler::Closure(3, 23)),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_decorated_routes() {
+        let python = "@app.route('/users')\ndef users():\n    pass\n\n@router.get('/items/{id}')\nasync def item(id):\n    pass\n\n@shared_task\ndef cleanup():\n    pass\n";
+        assert_eq!(
+            summary("python", python),
+            vec![
+                ("flask", None, Some("/users".into()), named("users")),
+                ("fastapi", Some("GET".into()), Some("/items/{id}".into()), named("item")),
+                ("celery", None, None, named("cleanup")),
+            ]
+        );
+
+        let java = "@RestController\nclass Users {\n  @RequestMapping(\"/users\")\n  List<User> all() { return null; }\n  @PostMapping(value = \"/users\")\n  void add() {}\n}\n";
+        assert_eq!(
+            summary("java", java),
+            vec![
+                ("spring", None, Some("/users".into()), named("all")),
+                ("spring", Some("POST".into()), Some("/users".into()), named("add")),
+            ]
+        );
+    }
+}
@@ -0,0 +1,715 @@
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::codegraph::manifest::{version_satisfies, PackageEcosystem, RepositoryPackages};
+use crate::codegraph::reexports::ModuleImports;
+use crate::codegraph::repository::RepositoryManager;
+use crate::codegraph::types::FunctionInfo;
+
+/// 联邦查询的类型
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FederatedQueryKind {
+    /// 按名称查找函数定义
+    #[default]
+    Definitions,
+    /// 查找调用该函数的函数
+    Callers,
+    /// 查找该函数调用的函数
+    Callees,
+}
+
+/// 已登记仓库的概要
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FederatedRepository {
+    pub name: String,
+    pub path: String,
+    pub total_functions: usize,
+    pub resolved_calls: usize,
+    /// 仓库发布的包名
+    pub packages: Vec<String>,
+}
+
+/// 一条联邦查询结果，带来源仓库
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FederatedHit {
+    pub repository: String,
+    pub repository_path: String,
+    pub function: FunctionInfo,
+    /// 调用者/被调用者查询中被匹配的函数，定义查询时为空
+    pub target: Option<FunctionInfo>,
+    /// 调用发生的行号
+    pub line_number: Option<usize>,
+    /// 跨仓库调用经由的包，仓库内调用为空
+    pub via_package: Option<String>,
+}
+
+/// 仓库之间的包依赖：`from_repository` 依赖 `to_repository` 发布的包
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PackageLink {
+    pub from_repository: String,
+    pub to_repository: String,
+    pub ecosystem: PackageEcosystem,
+    pub package: String,
+    pub version_req: Option<String>,
+    pub version: Option<String>,
+}
+
+/// 跨仓库调用边
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CrossRepoEdge {
+    pub from_repository: String,
+    pub caller: FunctionInfo,
+    pub to_repository: String,
+    pub callee: FunctionInfo,
+    pub package: String,
+    pub line_number: usize,
+}
+
+/// 影响分析中受影响的函数
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImpactedFunction {
+    pub repository: String,
+    pub function: FunctionInfo,
+    /// 距被修改函数的调用层数
+    pub depth: usize,
+    /// 经由跨仓库边到达时的包名
+    pub via_package: Option<String>,
+}
+
+/// 重复函数组中的一个函数
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DuplicateFunction {
+    pub repository: String,
+    pub repository_path: String,
+    pub function: FunctionInfo,
+}
+
+/// 函数体（规范化后）完全相同、出现在多个仓库中的一组函数
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DuplicateGroup {
+    pub body_hash: String,
+    /// 函数体行数（取组内最大值）
+    pub lines: usize,
+    /// 涉及的仓库（按名称排序）
+    pub repositories: Vec<String>,
+    pub functions: Vec<DuplicateFunction>,
+}
+
+struct FederatedEntry {
+    repository: Arc<RepositoryManager>,
+    packages: RepositoryPackages,
+}
+
+/// 多仓库联邦：按名称登记多个仓库，在所有仓库上执行同一查询并汇总结果；
+/// 仓库A依赖仓库B发布的包时，A中对B函数的调用成为跨仓库边
+#[derive(Default)]
+pub struct RepositoryFederation {
+    repositories: RwLock<BTreeMap<String, FederatedEntry>>,
+    // 跨仓库边缓存，登记或移除仓库时失效
+    cross_edges: RwLock<Option<Arc<Vec<CrossRepoEdge>>>>,
+}
+
+impl RepositoryFederation {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 登记仓库并扫描其清单文件，同名仓库会被替换；返回是否替换了已有仓库
+    pub fn register(&self, name: &str, repository: RepositoryManager) -> bool {
+        let packages = RepositoryPackages::scan(repository.get_repository_path());
+        self.register_with_packages(name, repository, packages)
+    }
+
+    /// 使用已知的包信息登记仓库
+    pub fn register_with_packages(&self, name: &str, repository: RepositoryManager, packages: RepositoryPackages) -> bool {
+        let entry = FederatedEntry { repository: Arc::new(repository), packages };
+        let replaced = self.repositories.write().insert(name.to_string(), entry).is_some();
+        *self.cross_edges.write() = None;
+        replaced
+    }
+
+    /// 移除仓库
+    pub fn unregister(&self, name: &str) -> bool {
+        let removed = self.repositories.write().remove(name).is_some();
+        if removed {
+            *self.cross_edges.write() = None;
+        }
+        removed
+    }
+
+    pub fn get(&self, name: &str) -> Option<Arc<RepositoryManager>> {
+        self.repositories.read().get(name).map(|e| e.repository.clone())
+    }
+
+    /// 已登记仓库列表（按名称排序）
+    pub fn list(&self) -> Vec<FederatedRepository> {
+        self.repositories
+            .read()
+            .iter()
+            .map(|(name, entry)| {
+                let stats = entry.repository.get_repository_stats();
+                FederatedRepository {
+                    name: name.clone(),
+                    path: entry.repository.get_repository_path().display().to_string(),
+                    total_functions: stats.total_functions,
+                    resolved_calls: stats.resolved_calls,
+                    packages: entry.packages.published.iter().map(|p| p.name.clone()).collect(),
+                }
+            })
+            .collect()
+    }
+
+    /// 按包名（及版本要求）匹配出的仓库间依赖
+    pub fn package_links(&self) -> Vec<PackageLink> {
+        let repositories = self.repositories.read();
+        let mut links = Vec::new();
+        let mut seen = HashSet::new();
+        for (from, entry) in repositories.iter() {
+            for dependency in &entry.packages.dependencies {
+                for (to, target) in repositories.iter().filter(|(to, _)| *to != from) {
+                    let Some(published) = target.packages.find_published(dependency.ecosystem, &dependency.name) else {
+                        continue;
+                    };
+                    // 版本不满足时依赖的是该包的其他发布版本，而不是这个仓库中的源码
+                    let compatible = match (&dependency.version_req, &published.version) {
+                        (Some(req), Some(version)) => version_satisfies(req, version),
+                        _ => true,
+                    };
+                    if compatible && seen.insert((from.clone(), to.clone(), published.name.clone())) {
+                        links.push(PackageLink {
+                            from_repository: from.clone(),
+                            to_repository: to.clone(),
+                            ecosystem: dependency.ecosystem,
+                            package: published.name.clone(),
+                            version_req: dependency.version_req.clone(),
+                            version: published.version.clone(),
+                        });
+                    }
+                }
+            }
+        }
+        links
+    }
+
+    /// 跨仓库调用边（结果会缓存到仓库集合变化为止）
+    pub fn cross_repo_edges(&self) -> Arc<Vec<CrossRepoEdge>> {
+        if let Some(edges) = self.cross_edges.read().as_ref() {
+            return edges.clone();
+        }
+        let edges = Arc::new(self.compute_cross_repo_edges());
+        *self.cross_edges.write() = Some(edges.clone());
+        edges
+    }
+
+    /// 由依赖方未解析的调用得到跨仓库边：按调用所在文件的导入确认被调用的名字来自该包，
+    /// 文件没有导入这个名字时（如全限定调用）按名字匹配；依赖方自身定义的同名函数优先
+    fn compute_cross_repo_edges(&self) -> Vec<CrossRepoEdge> {
+        let links = self.package_links();
+        let repositories = self.repositories.read();
+
+        let mut edges = Vec::new();
+        let mut seen = HashSet::new();
+        let mut file_imports: HashMap<PathBuf, ModuleImports> = HashMap::new();
+        for link in links {
+            let (Some(from), Some(to)) = (repositories.get(&link.from_repository), repositories.get(&link.to_repository))
+            else {
+                continue;
+            };
+            let from_graph = from.repository.get_call_graph();
+            let from_graph = from_graph.read();
+            let to_graph = to.repository.get_call_graph();
+            let to_graph = to_graph.read();
+
+            let local: HashSet<&str> = from_graph
+                .get_all_functions()
+                .into_iter()
+                .filter(|f| !f.is_unresolved())
+                .map(|f| f.name.as_str())
+                .collect();
+            let mut exported: HashMap<&str, Vec<&FunctionInfo>> = HashMap::new();
+            for function in to_graph.get_all_functions() {
+                if !function.is_unresolved() && function.name != "main" && !local.contains(function.name.as_str()) {
+                    exported.entry(function.name.as_str()).or_default().push(function);
+                }
+            }
+            if exported.is_empty() {
+                continue;
+            }
+
+            for relation in from_graph.get_all_call_relations() {
+                if relation.is_resolved {
+                    continue;
+                }
+                let (Some(caller), Some(placeholder)) = (
+                    from_graph.get_function_by_id(&relation.caller_id),
+                    from_graph.get_function_by_id(&relation.callee_id),
+                ) else {
+                    continue;
+                };
+                if caller.is_unresolved() || !placeholder.is_unresolved() {
+                    continue;
+                }
+                let (receiver, name) = split_call_name(&relation.callee_name);
+                let imports = file_imports.entry(caller.file_path.clone()).or_insert_with(|| {
+                    std::fs::read_to_string(&caller.file_path)
+                        .map(|code| ModuleImports::scan(&caller.language, &caller.file_path, &code))
+                        .unwrap_or_default()
+                });
+                let targets = imports.call_targets(&caller.language, receiver, name);
+                let package = link.package.as_str();
+                let candidates: Vec<&FunctionInfo> = if targets.is_empty() {
+                    exported.get(name).into_iter().flatten().copied().collect()
+                } else {
+                    targets
+                        .iter()
+                        .flat_map(|(module, original)| {
+                            exported
+                                .get(original.as_str())
+                                .into_iter()
+                                .flatten()
+                                .copied()
+                                .filter(move |callee| imported_from(module, package, callee))
+                        })
+                        .collect()
+                };
+                for callee in candidates {
+                    if seen.insert((caller.id, callee.id)) {
+                        edges.push(CrossRepoEdge {
+                            from_repository: link.from_repository.clone(),
+                            caller: caller.clone(),
+                            to_repository: link.to_repository.clone(),
+                            callee: callee.clone(),
+                            package: link.package.clone(),
+                            line_number: relation.line_number,
+                        });
+                    }
+                }
+            }
+        }
+        edges
+    }
+
+    /// 在指定仓库（为空表示全部）中按函数名查询，返回 (被查询的仓库, 结果)；
+    /// 调用者/被调用者查询包含跨仓库边
+    pub fn search(
+        &self,
+        function_name: &str,
+        kind: FederatedQueryKind,
+        only: &[String],
+    ) -> (Vec<String>, Vec<FederatedHit>) {
+        let cross_edges = match kind {
+            FederatedQueryKind::Definitions => Arc::new(Vec::new()),
+            _ => self.cross_repo_edges(),
+        };
+        let repositories = self.repositories.read();
+        let path_of = |name: &str| {
+            repositories
+                .get(name)
+                .map(|e| e.repository.get_repository_path().display().to_string())
+                .unwrap_or_default()
+        };
+
+        let mut searched = Vec::new();
+        let mut hits = Vec::new();
+        for (name, entry) in repositories.iter().filter(|(name, _)| only.is_empty() || only.contains(name)) {
+            let repository_path = path_of(name);
+            let graph = entry.repository.get_call_graph();
+            let graph = graph.read();
+
+            for target in graph.find_functions_by_name(function_name) {
+                let related = match kind {
+                    FederatedQueryKind::Definitions => {
+                        // 未解析的占位节点不是定义
+                        if !target.is_unresolved() {
+                            hits.push(FederatedHit {
+                                repository: name.clone(),
+                                repository_path: repository_path.clone(),
+                                function: target.clone(),
+                                target: None,
+                                line_number: None,
+                                via_package: None,
+                            });
+                        }
+                        continue;
+                    }
+                    FederatedQueryKind::Callers => graph.get_callers(&target.id),
+                    FederatedQueryKind::Callees => graph.get_callees(&target.id),
+                };
+                for (function, relation) in related {
+                    hits.push(FederatedHit {
+                        repository: name.clone(),
+                        repository_path: repository_path.clone(),
+                        function: function.clone(),
+                        target: Some(target.clone()),
+                        line_number: Some(relation.line_number),
+                        via_package: None,
+                    });
+                }
+
+                for edge in cross_edges.iter() {
+                    let (repository, function) = match kind {
+                        FederatedQueryKind::Callers if edge.to_repository == *name && edge.callee.id == target.id => {
+                            (&edge.from_repository, &edge.caller)
+                        }
+                        FederatedQueryKind::Callees if edge.from_repository == *name && edge.caller.id == target.id => {
+                            (&edge.to_repository, &edge.callee)
+                        }
+                        _ => continue,
+                    };
+                    hits.push(FederatedHit {
+                        repository: repository.clone(),
+                        repository_path: path_of(repository),
+                        function: function.clone(),
+                        target: Some(target.clone()),
+                        line_number: Some(edge.line_number),
+                        via_package: Some(edge.package.clone()),
+                    });
+                }
+            }
+            searched.push(name.clone());
+        }
+        (searched, hits)
+    }
+
+    /// 在指定仓库（为空表示全部）中查找函数体相同、且分布在至少两个仓库中的函数，
+    /// 返回 (被查询的仓库, 重复组)。短于 `min_lines` 行的函数、未解析的占位节点和没有函数体哈希的节点不参与；
+    /// 重复组按行数与仓库数的乘积从大到小排序，最值得抽取为共享库的排在前面
+    pub fn duplicates(&self, only: &[String], min_lines: usize) -> (Vec<String>, Vec<DuplicateGroup>) {
+        let repositories = self.repositories.read();
+        let mut searched = Vec::new();
+        let mut by_hash: HashMap<String, Vec<DuplicateFunction>> = HashMap::new();
+        for (name, entry) in repositories.iter().filter(|(name, _)| only.is_empty() || only.contains(name)) {
+            let repository_path = entry.repository.get_repository_path().display().to_string();
+            let graph = entry.repository.get_call_graph();
+            let graph = graph.read();
+            for function in graph.get_all_functions() {
+                let Some(hash) = &function.body_hash else { continue };
+                if function.is_unresolved() || function.line_end + 1 < function.line_start + min_lines {
+                    continue;
+                }
+                by_hash.entry(hash.clone()).or_default().push(DuplicateFunction {
+                    repository: name.clone(),
+                    repository_path: repository_path.clone(),
+                    function: function.clone(),
+                });
+            }
+            searched.push(name.clone());
+        }
+
+        let mut groups: Vec<DuplicateGroup> = by_hash
+            .into_iter()
+            .filter_map(|(body_hash, mut functions)| {
+                let mut repositories: Vec<String> = functions.iter().map(|f| f.repository.clone()).collect();
+                repositories.sort();
+                repositories.dedup();
+                if repositories.len() < 2 {
+                    return None;
+                }
+                functions.sort_by(|a, b| {
+                    (&a.repository, &a.function.file_path, a.function.line_start)
+                        .cmp(&(&b.repository, &b.function.file_path, b.function.line_start))
+                });
+                let lines = functions.iter().map(|f| f.function.line_end + 1 - f.function.line_start).max().unwrap_or(0);
+                Some(DuplicateGroup { body_hash, lines, repositories, functions })
+            })
+            .collect();
+        groups.sort_by(|a, b| {
+            (b.lines * b.repositories.len(), b.functions.len())
+                .cmp(&(a.lines * a.repositories.len(), a.functions.len()))
+                .then_with(|| a.body_hash.cmp(&b.body_hash))
+        });
+        (searched, groups)
+    }
+
+    /// 跨仓库影响分析：修改 `repository` 中的函数后，`max_depth` 层内所有（直接或间接）调用者
+    ///
+    /// 最多返回 `max_results` 个调用者，达到上限即停止遍历；调用方需要判断是否超出上限时多要一个
+    pub fn impact(
+        &self,
+        repository: &str,
+        function_name: &str,
+        max_depth: usize,
+        max_results: usize,
+    ) -> Result<Vec<ImpactedFunction>, String> {
+        let cross_edges = self.cross_repo_edges();
+        let repositories = self.repositories.read();
+        let entry = repositories
+            .get(repository)
+            .ok_or_else(|| format!("repository '{}' is not registered", repository))?;
+
+        let mut queue: VecDeque<(String, Uuid, usize)> = VecDeque::new();
+        let mut visited: HashSet<(String, Uuid)> = HashSet::new();
+        {
+            let graph = entry.repository.get_call_graph();
+            let graph = graph.read();
+            for function in graph.find_functions_by_name(function_name) {
+                if !function.is_unresolved() && visited.insert((repository.to_string(), function.id)) {
+                    queue.push_back((repository.to_string(), function.id, 0));
+                }
+            }
+        }
+        if queue.is_empty() {
+            return Err(format!("function '{}' not found in repository '{}'", function_name, repository));
+        }
+
+        let mut impacted = Vec::new();
+        while let Some((repo, id, depth)) = queue.pop_front() {
+            if depth >= max_depth {
+                continue;
+            }
+            let mut next: Vec<(String, FunctionInfo, Option<String>)> = Vec::new();
+            if let Some(entry) = repositories.get(&repo) {
+                let graph = entry.repository.get_call_graph();
+                let graph = graph.read();
+                next.extend(graph.get_callers(&id).into_iter().map(|(f, _)| (repo.clone(), f.clone(), None)));
+            }
+            next.extend(
+                cross_edges
+                    .iter()
+                    .filter(|e| e.to_repository == repo && e.callee.id == id)
+                    .map(|e| (e.from_repository.clone(), e.caller.clone(), Some(e.package.clone()))),
+            );
+
+            for (caller_repo, caller, via_package) in next {
+                if !visited.insert((caller_repo.clone(), caller.id)) {
+                    continue;
+                }
+                if impacted.len() >= max_results {
+                    return Ok(impacted);
+                }
+                queue.push_back((caller_repo.clone(), caller.id, depth + 1));
+                impacted.push(ImpactedFunction {
+                    repository: caller_repo,
+                    function: caller,
+                    depth: depth + 1,
+                    via_package,
+                });
+            }
+        }
+        Ok(impacted)
+    }
+}
+
+/// 把未解析调用的名字拆成 (接收者, 名字)，如 `auth::authorize` -> (`auth`, `authorize`)
+fn split_call_name(callee_name: &str) -> (Option<&str>, &str) {
+    match callee_name.rsplit_once("::").or_else(|| callee_name.rsplit_once('.')) {
+        Some((receiver, name)) => (Some(receiver), name),
+        None => (None, callee_name),
+    }
+}
+
+/// 导入语句中的模块是否来自该包（包名中的 `-` 在 Rust/Python 中写作 `_`），
+/// 或者就是被调用函数所在的命名空间（如 Java 的包名）
+fn imported_from(module: &str, package: &str, callee: &FunctionInfo) -> bool {
+    let within = |module: &str, root: &str| {
+        module
+            .strip_prefix(root)
+            .is_some_and(|rest| rest.is_empty() || rest.starts_with("::") || rest.starts_with(['.', '/']))
+    };
+    let in_package = within(module, package) || within(module, &package.replace('-', "_"));
+    in_package
+        || (!callee.namespace.is_empty()
+            && module.strip_suffix(callee.namespace.as_str()).is_some_and(|rest| {
+                rest.is_empty() || rest.ends_with("::") || rest.ends_with(['.', '/'])
+            }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::codegraph::manifest::{PackageDependency, PackageInfo};
+    use crate::codegraph::types::{FunctionKind, PetCodeGraph};
+    use std::path::PathBuf;
+
+    fn function(name: &str, namespace: &str, file: &str, line: usize) -> FunctionInfo {
+        FunctionInfo {
+            id: Uuid::new_v4(),
+            name: name.to_string(),
+            file_path: PathBuf::from(file),
+            line_start: line,
+            line_end: line + 2,
+            namespace: namespace.to_string(),
+            language: "rust".to_string(),
+            ..Default::default()
+        }
+    }
+
+    fn hashed(name: &str, line_end: usize, body: &str) -> FunctionInfo {
+        FunctionInfo {
+            line_end,
+            body_hash: crate::codegraph::body_hash::body_hash("rust", body),
+            ..function(name, "crate", "src/lib.rs", 1)
+        }
+    }
+
+    fn placeholder(name: &str, file: &str, line: usize) -> FunctionInfo {
+        FunctionInfo { kind: FunctionKind::Unresolved, ..function(name, "unresolved", file, line) }
+    }
+
+    fn repository(path: &str, caller: &str, callee: &str, resolved: bool) -> RepositoryManager {
+        let mut graph = PetCodeGraph::new();
+        let caller = function(caller, "crate", "src/lib.rs", 1);
+        let callee = if resolved { function(callee, "crate", "src/lib.rs", 4) } else { placeholder(callee, "src/lib.rs", 4) };
+        graph.add_function(caller.clone());
+        graph.add_function(callee.clone());
+        graph.add_call(&caller, &callee);
+        RepositoryManager::with_call_graph(PathBuf::from(path), graph)
+    }
+
+    #[test]
+    fn test_federated_callers_keep_provenance() {
+        let federation = RepositoryFederation::new();
+        assert!(!federation.register("billing", repository("/srv/billing", "charge", "authorize", true)));
+        assert!(!federation.register("checkout", repository("/srv/checkout", "submit", "authorize", false)));
+
+        let (searched, hits) = federation.search("authorize", FederatedQueryKind::Callers, &[]);
+        assert_eq!(searched, vec!["billing", "checkout"]);
+        let mut callers: Vec<(&str, &str)> = hits
+            .iter()
+            .map(|h| (h.repository.as_str(), h.function.name.as_str()))
+            .collect();
+        callers.sort();
+        assert_eq!(callers, vec![("billing", "charge"), ("checkout", "submit")]);
+
+        // 占位节点不计为定义
+        let (_, hits) = federation.search("authorize", FederatedQueryKind::Definitions, &[]);
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].repository_path, "/srv/billing");
+
+        let (searched, _) = federation.search("authorize", FederatedQueryKind::Callers, &["checkout".to_string()]);
+        assert_eq!(searched, vec!["checkout"]);
+        assert!(federation.unregister("checkout"));
+        assert_eq!(federation.list().len(), 1);
+    }
+
+    #[test]
+    fn test_cross_repo_edges_follow_package_dependencies() {
+        let dir = std::env::temp_dir().join(format!("codegraph-federation-{}", Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let lib_file = dir.join("auth.rs");
+        let app_file = dir.join("app.rs");
+        std::fs::write(&lib_file, "fn authorize() {\n    check_token();\n}\nfn check_token() {}\n").unwrap();
+        std::fs::write(
+            &app_file,
+            "use acme_auth::authorize;\nuse session::check_token;\n\nfn submit() {\n    authorize();\n    check_token();\n}\nfn handler() {\n    submit();\n}\n",
+        )
+        .unwrap();
+
+        let mut lib_graph = PetCodeGraph::new();
+        let authorize = function("authorize", "auth", lib_file.to_str().unwrap(), 1);
+        lib_graph.add_function(authorize.clone());
+        lib_graph.add_function(function("check_token", "auth", lib_file.to_str().unwrap(), 4));
+
+        let mut app_graph = PetCodeGraph::new();
+        let submit = function("submit", "app", app_file.to_str().unwrap(), 4);
+        let handler = function("handler", "app", app_file.to_str().unwrap(), 8);
+        app_graph.add_function(submit.clone());
+        app_graph.add_function(handler.clone());
+        app_graph.add_call(&handler, &submit);
+        // 解析器为没有在本仓库中找到定义的调用留下的占位节点
+        for name in ["authorize", "check_token"] {
+            let placeholder = placeholder(name, app_file.to_str().unwrap(), 5);
+            app_graph.add_function(placeholder.clone());
+            app_graph.add_call(&submit, &placeholder);
+        }
+
+        let published = |name: &str, version: &str| RepositoryPackages {
+            published: vec![PackageInfo {
+                ecosystem: PackageEcosystem::Cargo,
+                name: name.to_string(),
+                version: Some(version.to_string()),
+                manifest: PathBuf::from("Cargo.toml"),
+            }],
+            dependencies: Vec::new(),
+        };
+        let mut app_packages = published("checkout", "0.1.0");
+        app_packages.dependencies.push(PackageDependency {
+            ecosystem: PackageEcosystem::Cargo,
+            name: "acme_auth".to_string(),
+            version_req: Some("1.2".to_string()),
+            manifest: PathBuf::from("Cargo.toml"),
+        });
+
+        let federation = RepositoryFederation::new();
+        federation.register_with_packages("auth", RepositoryManager::with_call_graph(dir.clone(), lib_graph), published("acme-auth", "1.4.0"));
+        federation.register_with_packages("checkout", RepositoryManager::with_call_graph(dir.clone(), app_graph), app_packages);
+
+        // check_token 导入自其他模块，同名也不连到 acme-auth
+        let edges = federation.cross_repo_edges();
+        assert_eq!(edges.len(), 1);
+        assert_eq!((edges[0].caller.name.as_str(), edges[0].callee.name.as_str()), ("submit", "authorize"));
+        assert_eq!(edges[0].package, "acme-auth");
+        assert_eq!(edges[0].line_number, 5);
+
+        let (_, hits) = federation.search("authorize", FederatedQueryKind::Callers, &["auth".to_string()]);
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].repository, "checkout");
+        assert_eq!(hits[0].via_package.as_deref(), Some("acme-auth"));
+
+        let impacted = federation.impact("auth", "authorize", 5, usize::MAX).unwrap();
+        let names: Vec<(&str, usize)> = impacted.iter().map(|i| (i.function.name.as_str(), i.depth)).collect();
+        assert_eq!(names, vec![("submit", 1), ("handler", 2)]);
+        // 结果不超过上限，达到上限后停止遍历
+        let limited = federation.impact("auth", "authorize", 5, 1).unwrap();
+        assert_eq!(limited.iter().map(|i| i.function.name.as_str()).collect::<Vec<_>>(), vec!["submit"]);
+        assert!(federation.impact("auth", "authorize", 5, 0).unwrap().is_empty());
+        assert_eq!(federation.impact("auth", "authorize", 5, 2).unwrap().len(), 2);
+
+        // 依赖的是不兼容版本时没有跨仓库边
+        federation.register_with_packages(
+            "auth",
+            RepositoryManager::with_call_graph(dir.clone(), PetCodeGraph::new()),
+            published("acme-auth", "2.0.0"),
+        );
+        assert!(federation.package_links().is_empty());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_duplicates_span_repositories() {
+        let retry = "fn retry(op: impl Fn() -> bool) -> bool {\n    for _ in 0..3 {\n        if op() { return true; }\n    }\n    false\n}";
+        let retry_copy = "fn retry(op: impl Fn() -> bool) -> bool {\n    // copied from billing\n    for _ in 0..3 { if op() { return true; } }\n    false\n}";
+        let graph = |functions: Vec<FunctionInfo>| {
+            let mut graph = PetCodeGraph::new();
+            for function in functions {
+                graph.add_function(function);
+            }
+            graph
+        };
+        let federation = RepositoryFederation::new();
+        federation.register("billing", RepositoryManager::with_call_graph(PathBuf::from("/srv/billing"), graph(vec![
+            hashed("retry", 6, retry),
+            hashed("ok", 1, "fn ok() -> bool { true }"),
+        ])));
+        federation.register("checkout", RepositoryManager::with_call_graph(PathBuf::from("/srv/checkout"), graph(vec![
+            hashed("retry", 5, retry_copy),
+            hashed("ok", 1, "fn ok() -> bool { true }"),
+        ])));
+        // 仓库内部的重复不算
+        federation.register("search", RepositoryManager::with_call_graph(PathBuf::from("/srv/search"), graph(vec![
+            hashed("index", 5, "fn index() {\n    a();\n    b();\n    c();\n}"),
+            hashed("index", 5, "fn index() {\n    a();\n    b();\n    c();\n}"),
+        ])));
+
+        let (searched, groups) = federation.duplicates(&[], 5);
+        assert_eq!(searched, vec!["billing", "checkout", "search"]);
+        assert_eq!(groups.len(), 1);
+        assert_eq!(groups[0].repositories, vec!["billing", "checkout"]);
+        assert_eq!(groups[0].lines, 6);
+        let functions: Vec<(&str, &str)> = groups[0].functions.iter().map(|f| (f.repository.as_str(), f.function.name.as_str())).collect();
+        assert_eq!(functions, vec![("billing", "retry"), ("checkout", "retry")]);
+
+        // 降低行数下限后单行的重复函数也被列出，排在后面
+        let (_, groups) = federation.duplicates(&[], 1);
+        let names: Vec<&str> = groups.iter().map(|g| g.functions[0].function.name.as_str()).collect();
+        assert_eq!(names, vec!["retry", "ok"]);
+        let (_, groups) = federation.duplicates(&["billing".to_string(), "search".to_string()], 1);
+        assert!(groups.is_empty());
+    }
+}
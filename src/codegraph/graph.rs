@@ -2,6 +2,7 @@ use std::collections::{HashMap, HashSet};
 use std::path::PathBuf;
 use uuid::Uuid;
 use crate::codegraph::types::{FunctionInfo, CallRelation, GraphRelation, CodeGraphStats};
+use crate::codegraph::diagram::{render_d2, render_dot, render_mermaid, render_plantuml};
 
 /// 代码图核心结构
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
@@ -155,27 +156,30 @@ impl CodeGraph {
 
     /// 导出为DOT格式
     pub fn to_dot(&self) -> String {
-        let mut dot = String::from("digraph CodeGraph {\n");
-        dot.push_str("    rankdir=TB;\n");
-        dot.push_str("    node [shape=box];\n\n");
-        
-        // 添加节点
-        for function in self.functions.values() {
-            let node_id = function.name.replace(" ", "_").replace("-", "_");
-            let label = format!("{}\\n{}", function.name, function.file_path.display());
-            dot.push_str(&format!("    {} [label=\"{}\"];\n", node_id, label));
-        }
-        
-        // 添加边
-        for relation in &self.call_relations {
-            let caller_name = relation.caller_name.replace(" ", "_").replace("-", "_");
-            let callee_name = relation.callee_name.replace(" ", "_").replace("-", "_");
-            let style = if relation.is_resolved { "" } else { " [style=dashed]" };
-            dot.push_str(&format!("    {} -> {}{};\n", caller_name, callee_name, style));
-        }
-        
-        dot.push_str("}\n");
-        dot
+        let functions: Vec<&FunctionInfo> = self.functions.values().collect();
+        let relations: Vec<&CallRelation> = self.call_relations.iter().collect();
+        render_dot(&functions, &relations)
+    }
+
+    /// 导出为Mermaid流程图
+    pub fn to_mermaid(&self) -> String {
+        let functions: Vec<&FunctionInfo> = self.functions.values().collect();
+        let relations: Vec<&CallRelation> = self.call_relations.iter().collect();
+        render_mermaid(&functions, &relations)
+    }
+
+    /// 导出为D2
+    pub fn to_d2(&self) -> String {
+        let functions: Vec<&FunctionInfo> = self.functions.values().collect();
+        let relations: Vec<&CallRelation> = self.call_relations.iter().collect();
+        render_d2(&functions, &relations)
+    }
+
+    /// 导出为PlantUML
+    pub fn to_plantuml(&self) -> String {
+        let functions: Vec<&FunctionInfo> = self.functions.values().collect();
+        let relations: Vec<&CallRelation> = self.call_relations.iter().collect();
+        render_plantuml(&functions, &relations)
     }
 
     /// 导出为JSON格式
@@ -0,0 +1,184 @@
+use std::collections::{BTreeMap, HashMap};
+use std::path::Path;
+
+use crate::codegraph::types::FunctionInfo;
+
+/// 生成的桩代码文件名后缀（protoc / grpc 插件的默认输出）
+const GENERATED_SUFFIXES: &[&str] = &[
+    ".pb.go",
+    "_grpc.pb.go",
+    "_pb2.py",
+    "_pb2_grpc.py",
+    "Grpc.java",
+    "_pb.js",
+    "_grpc_pb.js",
+    "_pb.d.ts",
+    "_pb.ts",
+    "_grpc_pb.d.ts",
+    "ServiceClientPb.ts",
+];
+
+/// `.proto` 中定义的 RPC，按服务分组。
+///
+/// 各语言的生成代码调用 RPC 时只出现方法名（`client.GetUser(...)`、`stub.getUser(...)`），
+/// 因此仅当文件中出现该服务的客户端桩类型时才把同名调用连到 RPC 节点；
+/// 出现服务端基类/接口的文件中，同名函数作为 RPC 的实现，由 RPC 节点指向它。
+#[derive(Debug, Default)]
+pub struct RpcIndex {
+    /// 服务名 -> (方法名, RPC 函数)
+    services: BTreeMap<String, Vec<(String, FunctionInfo)>>,
+}
+
+impl RpcIndex {
+    /// 从图中全部函数里收集 proto 分析器产生的 `Service.Method` 函数
+    pub fn new<'a>(functions: impl IntoIterator<Item = &'a FunctionInfo>) -> Self {
+        let mut services: BTreeMap<String, Vec<(String, FunctionInfo)>> = BTreeMap::new();
+        for function in functions {
+            if function.language != "proto" {
+                continue;
+            }
+            if let Some((service, method)) = function.name.split_once('.') {
+                services.entry(service.to_string()).or_default().push((method.to_string(), function.clone()));
+            }
+        }
+        Self { services }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.services.is_empty()
+    }
+
+    /// 文件中桩调用的写法 -> RPC 函数；生成的桩代码本身不参与
+    pub fn client_calls(&self, file_path: &Path, language: &str, content: &str) -> HashMap<String, FunctionInfo> {
+        let mut calls = HashMap::new();
+        if is_generated_stub(file_path, content) {
+            return calls;
+        }
+        for (service, rpcs) in &self.services {
+            if !client_markers(service).iter().any(|marker| content.contains(marker.as_str())) {
+                continue;
+            }
+            for (method, rpc) in rpcs {
+                for spelling in method_spellings(language, method) {
+                    calls.entry(spelling).or_insert_with(|| rpc.clone());
+                }
+            }
+        }
+        calls
+    }
+
+    /// 文件中实现 RPC 的函数：(RPC 函数, 实现函数)
+    pub fn server_impls<'f>(
+        &self,
+        file_path: &Path,
+        language: &str,
+        content: &str,
+        functions: &'f [FunctionInfo],
+    ) -> Vec<(FunctionInfo, &'f FunctionInfo)> {
+        let mut impls = vec![];
+        if language == "proto" || is_generated_stub(file_path, content) {
+            return impls;
+        }
+        for (service, rpcs) in &self.services {
+            if !server_markers(service).iter().any(|marker| content.contains(marker.as_str())) {
+                continue;
+            }
+            for (method, rpc) in rpcs {
+                let spellings = method_spellings(language, method);
+                for function in functions {
+                    if spellings.iter().any(|spelling| spelling == short_name(&function.name)) {
+                        impls.push((rpc.clone(), function));
+                    }
+                }
+            }
+        }
+        impls
+    }
+
+    /// 调用名对应的 RPC；`calls` 来自 [`RpcIndex::client_calls`]
+    pub fn resolve<'c>(calls: &'c HashMap<String, FunctionInfo>, call_name: &str) -> Option<&'c FunctionInfo> {
+        if calls.is_empty() {
+            return None;
+        }
+        calls.get(short_name(call_name))
+    }
+}
+
+/// 是否为 protoc 生成的文件（桩代码中的同名方法是转发而非业务调用）
+pub fn is_generated_stub(file_path: &Path, content: &str) -> bool {
+    let name = file_path.file_name().and_then(|n| n.to_str()).unwrap_or("");
+    if GENERATED_SUFFIXES.iter().any(|suffix| name.ends_with(suffix)) {
+        return true;
+    }
+    let header: String = content.lines().take(10).collect::<Vec<_>>().join("\n");
+    header.contains("DO NOT EDIT") && (header.contains("protoc") || header.contains("protocol buffer"))
+}
+
+/// `client.GetUser`、`Stub::GetUser` 中的方法名
+fn short_name(name: &str) -> &str {
+    name.rsplit(['.', ':']).next().unwrap_or(name)
+}
+
+fn client_markers(service: &str) -> Vec<String> {
+    vec![
+        format!("{}Client", service),      // Go、grpc-js、grpc-web
+        format!("{}Stub", service),        // Python
+        format!("{}Grpc.new", service),    // Java: newBlockingStub / newStub / newFutureStub
+    ]
+}
+
+fn server_markers(service: &str) -> Vec<String> {
+    vec![
+        format!("{}Server", service),      // Go: Unimplemented...Server / Register...Server，grpc-js: I...Server
+        format!("{}Servicer", service),    // Python
+        format!("{}ImplBase", service),    // Java
+        format!("{}Service", service),     // grpc-js: server.addService(...Service, ...)
+    ]
+}
+
+/// 生成代码中方法名的写法：Go、Python 保持原样，Java 为小驼峰，JS/TS 两种都有
+fn method_spellings(language: &str, method: &str) -> Vec<String> {
+    let mut chars = method.chars();
+    let lower_camel = chars
+        .next()
+        .map(|first| first.to_lowercase().chain(chars).collect::<String>())
+        .unwrap_or_default();
+    match language {
+        "java" => vec![lower_camel],
+        "javascript" | "typescript" => vec![lower_camel, method.to_string()],
+        _ => vec![method.to_string()],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn function(name: &str, language: &str, file: &str) -> FunctionInfo {
+        FunctionInfo {
+            id: uuid::Uuid::new_v4(),
+            name: name.to_string(),
+            file_path: PathBuf::from(file),
+            line_start: 1,
+            line_end: 1,
+            namespace: "global".to_string(),
+            language: language.to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_stub_calls_need_client_marker() {
+        let rpcs = [function("UserService.GetUser", "proto", "api/user.proto")];
+        let index = RpcIndex::new(&rpcs);
+
+        let java = "var stub = UserServiceGrpc.newBlockingStub(channel);\nstub.getUser(request);";
+        let calls = index.client_calls(Path::new("App.java"), "java", java);
+        assert_eq!(RpcIndex::resolve(&calls, "stub.getUser").map(|f| f.name.as_str()), Some("UserService.GetUser"));
+        assert!(RpcIndex::resolve(&calls, "GetUser").is_none());
+
+        assert!(index.client_calls(Path::new("repo.go"), "go", "db.GetUser(id)").is_empty());
+        assert!(index.client_calls(Path::new("user_grpc.pb.go"), "go", "UserServiceClient").is_empty());
+    }
+}
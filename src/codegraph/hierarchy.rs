@@ -0,0 +1,274 @@
+use tree_sitter::{Node, Parser};
+
+use crate::codegraph::types::ClassType;
+
+/// 类型中声明的方法
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DeclaredMethod {
+    pub name: String,
+    pub line_start: usize,
+    pub line_end: usize,
+    /// 只有声明没有实现：接口方法、抽象方法、C++ 纯虚函数
+    pub is_abstract: bool,
+}
+
+/// 文件中的一个类、接口或结构体声明及其直接的父类型
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TypeDeclaration {
+    pub name: String,
+    pub class_type: ClassType,
+    pub line_start: usize,
+    pub line_end: usize,
+    /// 继承的类（接口声明中为继承的接口）
+    pub extends: Vec<String>,
+    /// 实现的接口
+    pub implements: Vec<String>,
+    pub methods: Vec<DeclaredMethod>,
+}
+
+impl TypeDeclaration {
+    /// 所有直接父类型
+    pub fn supertypes(&self) -> impl Iterator<Item = &String> {
+        self.extends.iter().chain(&self.implements)
+    }
+}
+
+fn language_of(language: &str) -> Option<tree_sitter::Language> {
+    match language {
+        "java" => Some(tree_sitter_java::LANGUAGE.into()),
+        "typescript" => Some(tree_sitter_typescript::LANGUAGE_TYPESCRIPT.into()),
+        "cpp" => Some(tree_sitter_cpp::LANGUAGE.into()),
+        _ => None,
+    }
+}
+
+/// 扫描 Java、TypeScript 和 C++ 文件中的类型声明，其余语言返回空。
+///
+/// 父类型只记录名字（去掉泛型参数和命名空间限定）。C++ 中只含纯虚函数的类视为接口，
+/// 在类外定义的成员函数不计入该类
+pub fn scan_types(language: &str, code: &str) -> Vec<TypeDeclaration> {
+    let Some(grammar) = language_of(language) else {
+        return Vec::new();
+    };
+    let mut parser = Parser::new();
+    if parser.set_language(&grammar).is_err() {
+        return Vec::new();
+    }
+    let Some(tree) = parser.parse(code, None) else {
+        return Vec::new();
+    };
+
+    let mut types = Vec::new();
+    let mut stack = vec![tree.root_node()];
+    while let Some(node) = stack.pop() {
+        if let Some(declaration) = type_declaration(language, node, code) {
+            types.push(declaration);
+        }
+        let mut cursor = node.walk();
+        stack.extend(node.named_children(&mut cursor));
+    }
+    types.sort_by_key(|t| t.line_start);
+    types
+}
+
+fn text<'a>(node: Node, code: &'a str) -> &'a str {
+    code.get(node.byte_range()).unwrap_or_default()
+}
+
+fn type_declaration(language: &str, node: Node, code: &str) -> Option<TypeDeclaration> {
+    let kind = node.kind();
+    let is_type = match language {
+        "java" => matches!(kind, "class_declaration" | "interface_declaration" | "enum_declaration" | "record_declaration"),
+        "typescript" => matches!(kind, "class_declaration" | "abstract_class_declaration" | "interface_declaration"),
+        _ => matches!(kind, "class_specifier" | "struct_specifier"),
+    };
+    // C++ 中没有类体的是前置声明或类型引用
+    let body = node.child_by_field_name("body");
+    if !is_type || body.is_none() {
+        return None;
+    }
+    let name = text(node.child_by_field_name("name")?, code).to_string();
+
+    let mut extends = Vec::new();
+    let mut implements = Vec::new();
+    let mut cursor = node.walk();
+    for child in node.children(&mut cursor) {
+        match child.kind() {
+            // Java
+            "superclass" | "extends_interfaces" => extends.extend(type_names(child, code)),
+            "super_interfaces" => implements.extend(type_names(child, code)),
+            // TypeScript
+            "class_heritage" => {
+                let mut inner = child.walk();
+                for clause in child.named_children(&mut inner) {
+                    match clause.kind() {
+                        "extends_clause" => extends.extend(clause.child_by_field_name("value").and_then(|v| type_name(v, code))),
+                        "implements_clause" => implements.extend(type_names(clause, code)),
+                        _ => {}
+                    }
+                }
+            }
+            "extends_type_clause" => extends.extend(type_names(child, code)),
+            // C++
+            "base_class_clause" => extends.extend(type_names(child, code)),
+            _ => {}
+        }
+    }
+
+    let mut methods = Vec::new();
+    collect_methods(body?, code, &mut methods);
+    let class_type = match kind {
+        "interface_declaration" => ClassType::Interface,
+        "enum_declaration" => ClassType::Enum,
+        "class_specifier" | "struct_specifier"
+            if methods.iter().any(|m| m.is_abstract) && methods.iter().all(|m| m.is_abstract) =>
+        {
+            ClassType::Interface
+        }
+        "struct_specifier" => ClassType::Struct,
+        _ => ClassType::Class,
+    };
+    Some(TypeDeclaration {
+        name,
+        class_type,
+        line_start: node.start_position().row + 1,
+        line_end: node.end_position().row + 1,
+        extends,
+        implements,
+        methods,
+    })
+}
+
+/// 节点下各个直接的类型名（跳过 `public`、`virtual` 等修饰）
+fn type_names(node: Node, code: &str) -> Vec<String> {
+    let mut names = Vec::new();
+    let mut cursor = node.walk();
+    for child in node.named_children(&mut cursor) {
+        if child.kind() == "type_list" {
+            names.extend(type_names(child, code));
+        } else if let Some(name) = type_name(child, code) {
+            names.push(name);
+        }
+    }
+    names
+}
+
+/// 去掉泛型参数和命名空间限定后的类型名
+fn type_name(node: Node, code: &str) -> Option<String> {
+    match node.kind() {
+        "type_identifier" | "identifier" => Some(text(node, code).to_string()),
+        "generic_type" | "template_type" => {
+            let name = node.child_by_field_name("name").or_else(|| node.named_child(0))?;
+            type_name(name, code)
+        }
+        "qualified_identifier" | "nested_type_identifier" | "scoped_type_identifier" => {
+            let name = node.child_by_field_name("name").or_else(|| node.named_child(node.named_child_count().checked_sub(1)?))?;
+            type_name(name, code)
+        }
+        "member_expression" => node.child_by_field_name("property").map(|p| text(p, code).to_string()),
+        _ => None,
+    }
+}
+
+fn collect_methods(body: Node, code: &str, methods: &mut Vec<DeclaredMethod>) {
+    let mut cursor = body.walk();
+    for member in body.named_children(&mut cursor) {
+        let (name, is_abstract) = match member.kind() {
+            // Java 枚举中的方法位于枚举常量之后的声明部分
+            "enum_body_declarations" => {
+                collect_methods(member, code, methods);
+                continue;
+            }
+            "method_declaration" => (member.child_by_field_name("name"), member.child_by_field_name("body").is_none()),
+            "method_definition" => (member.child_by_field_name("name"), false),
+            "abstract_method_signature" | "method_signature" => (member.child_by_field_name("name"), true),
+            "function_definition" => (cpp_method_name(member), false),
+            // 只声明不定义的成员函数中只有纯虚函数（`= 0`）是抽象的，其余在类外定义
+            "field_declaration" => match member.child_by_field_name("default_value") {
+                Some(value) if text(value, code) == "0" => (cpp_method_name(member), true),
+                _ => continue,
+            },
+            _ => continue,
+        };
+        let Some(name) = name else { continue };
+        methods.push(DeclaredMethod {
+            name: text(name, code).to_string(),
+            line_start: member.start_position().row + 1,
+            line_end: member.end_position().row + 1,
+            is_abstract,
+        });
+    }
+}
+
+/// C++ 成员函数名；析构函数、运算符等不参与覆盖匹配
+fn cpp_method_name(member: Node) -> Option<Node> {
+    let declarator = member.child_by_field_name("declarator")?;
+    if declarator.kind() != "function_declarator" {
+        return None;
+    }
+    declarator.child_by_field_name("declarator").filter(|name| name.kind() == "field_identifier")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    type Summary = (String, String, Vec<String>, Vec<String>, Vec<(String, bool)>);
+
+    fn summary(types: &[TypeDeclaration]) -> Vec<Summary> {
+        types
+            .iter()
+            .map(|t| {
+                (
+                    t.name.clone(),
+                    format!("{:?}", t.class_type),
+                    t.extends.clone(),
+                    t.implements.clone(),
+                    t.methods.iter().map(|m| (m.name.clone(), m.is_abstract)).collect(),
+                )
+            })
+            .collect()
+    }
+
+    fn owned(items: &[&str]) -> Vec<String> {
+        items.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn test_java_types() {
+        let code = "interface Shape extends Named<String> {\n    double area();\n    default String label() { return \"\"; }\n}\nabstract class Base implements Shape {\n    public abstract double area();\n}\nclass Circle extends Base implements Shape, java.lang.Comparable<Circle> {\n    public double area() { return 1.0; }\n}\n";
+        assert_eq!(
+            summary(&scan_types("java", code)),
+            vec![
+                ("Shape".to_string(), "Interface".to_string(), owned(&["Named"]), vec![], vec![("area".to_string(), true), ("label".to_string(), false)]),
+                ("Base".to_string(), "Class".to_string(), vec![], owned(&["Shape"]), vec![("area".to_string(), true)]),
+                ("Circle".to_string(), "Class".to_string(), owned(&["Base"]), owned(&["Shape", "Comparable"]), vec![("area".to_string(), false)]),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_typescript_types() {
+        let code = "export interface Shape extends Named<string> {\n    area(): number;\n    name?: string;\n}\nexport default abstract class Poly<T extends X> extends Base<T> implements Shape, ns.Sized {\n  abstract area(): number;\n  size(): number { return 0; }\n}\n";
+        assert_eq!(
+            summary(&scan_types("typescript", code)),
+            vec![
+                ("Shape".to_string(), "Interface".to_string(), owned(&["Named"]), vec![], vec![("area".to_string(), true)]),
+                ("Poly".to_string(), "Class".to_string(), owned(&["Base"]), owned(&["Shape", "Sized"]), vec![("area".to_string(), true), ("size".to_string(), false)]),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_cpp_types() {
+        let code = "namespace geo {\nclass Shape {\npublic:\n    virtual double area() const = 0;\n    virtual ~Shape() = default;\n};\n}\nclass Circle final : public geo::Shape {\npublic:\n    double area() const override { return 1.0; }\n};\ntemplate <typename T>\nstruct Rect : public Shape, private Sized<T> {\n    double area() const override;\n};\nstruct Point;\n";
+        assert_eq!(
+            summary(&scan_types("cpp", code)),
+            vec![
+                ("Shape".to_string(), "Interface".to_string(), vec![], vec![], vec![("area".to_string(), true)]),
+                ("Circle".to_string(), "Class".to_string(), owned(&["Shape"]), vec![], vec![("area".to_string(), false)]),
+                ("Rect".to_string(), "Struct".to_string(), owned(&["Shape", "Sized"]), vec![], vec![]),
+            ]
+        );
+    }
+}
@@ -0,0 +1,359 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+/// 扫描清单文件时的最大目录深度（覆盖 workspace 成员和 monorepo 子包）
+const MAX_MANIFEST_DEPTH: usize = 4;
+
+/// 包所属的生态
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PackageEcosystem {
+    Cargo,
+    Npm,
+    Python,
+    Go,
+}
+
+/// 仓库发布的包
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PackageInfo {
+    pub ecosystem: PackageEcosystem,
+    pub name: String,
+    pub version: Option<String>,
+    pub manifest: PathBuf,
+}
+
+/// 仓库声明的依赖
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PackageDependency {
+    pub ecosystem: PackageEcosystem,
+    pub name: String,
+    /// 版本要求，未声明或为路径/git依赖时为空
+    pub version_req: Option<String>,
+    pub manifest: PathBuf,
+}
+
+/// 从清单文件（Cargo.toml、package.json、pyproject.toml、requirements.txt、go.mod）收集的包信息
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RepositoryPackages {
+    pub published: Vec<PackageInfo>,
+    pub dependencies: Vec<PackageDependency>,
+}
+
+impl RepositoryPackages {
+    /// 扫描仓库目录下的清单文件，无法解析的文件被忽略
+    pub fn scan(root: &Path) -> Self {
+        let mut packages = Self::default();
+        packages.scan_dir(root, 0);
+        packages
+    }
+
+    fn scan_dir(&mut self, dir: &Path, depth: usize) {
+        let Ok(entries) = fs::read_dir(dir) else {
+            return;
+        };
+        let mut entries: Vec<PathBuf> = entries.flatten().map(|e| e.path()).collect();
+        entries.sort();
+        for path in entries {
+            let Some(name) = path.file_name().and_then(|n| n.to_str()) else {
+                continue;
+            };
+            if path.is_dir() {
+                let ignored = name.starts_with('.')
+                    || matches!(name, "target" | "node_modules" | "__pycache__" | "vendor" | "dist" | "build");
+                if !ignored && depth < MAX_MANIFEST_DEPTH {
+                    self.scan_dir(&path, depth + 1);
+                }
+                continue;
+            }
+            let Ok(content) = fs::read_to_string(&path) else {
+                continue;
+            };
+            match name {
+                "Cargo.toml" => self.parse_cargo(&path, &content),
+                "package.json" => self.parse_package_json(&path, &content),
+                "pyproject.toml" => self.parse_pyproject(&path, &content),
+                "requirements.txt" => self.parse_requirements(&path, &content),
+                "go.mod" => self.parse_go_mod(&path, &content),
+                _ => {}
+            }
+        }
+    }
+
+    /// 仓库是否发布了指定的包
+    pub fn find_published(&self, ecosystem: PackageEcosystem, name: &str) -> Option<&PackageInfo> {
+        let name = normalize_package_name(ecosystem, name);
+        self.published
+            .iter()
+            .find(|p| p.ecosystem == ecosystem && normalize_package_name(ecosystem, &p.name) == name)
+    }
+
+    fn publish(&mut self, ecosystem: PackageEcosystem, name: &str, version: Option<String>, manifest: &Path) {
+        self.published.push(PackageInfo {
+            ecosystem,
+            name: name.to_string(),
+            version,
+            manifest: manifest.to_path_buf(),
+        });
+    }
+
+    fn depend(&mut self, ecosystem: PackageEcosystem, name: &str, version_req: Option<String>, manifest: &Path) {
+        self.dependencies.push(PackageDependency {
+            ecosystem,
+            name: name.to_string(),
+            version_req: version_req.filter(|v| !v.trim().is_empty()),
+            manifest: manifest.to_path_buf(),
+        });
+    }
+
+    fn parse_cargo(&mut self, path: &Path, content: &str) {
+        let Ok(manifest) = content.parse::<toml::Table>() else {
+            return;
+        };
+        if let Some(package) = manifest.get("package").and_then(|p| p.as_table()) {
+            if let Some(name) = package.get("name").and_then(|n| n.as_str()) {
+                let version = package.get("version").and_then(|v| v.as_str()).map(str::to_string);
+                self.publish(PackageEcosystem::Cargo, name, version, path);
+            }
+        }
+
+        let workspace_deps = manifest
+            .get("workspace")
+            .and_then(|w| w.get("dependencies"))
+            .and_then(|d| d.as_table());
+        let sections = ["dependencies", "dev-dependencies", "build-dependencies"]
+            .iter()
+            .filter_map(|section| manifest.get(*section).and_then(|d| d.as_table()))
+            .chain(workspace_deps);
+        let mut found = Vec::new();
+        for table in sections {
+            for (key, spec) in table {
+                // `foo = { package = "real-name", version = "1" }` 依赖的是 real-name
+                let name = spec.get("package").and_then(|p| p.as_str()).unwrap_or(key);
+                let version = match spec {
+                    toml::Value::String(v) => Some(v.clone()),
+                    _ => spec.get("version").and_then(|v| v.as_str()).map(str::to_string),
+                };
+                found.push((name.to_string(), version));
+            }
+        }
+        for (name, version) in found {
+            self.depend(PackageEcosystem::Cargo, &name, version, path);
+        }
+    }
+
+    fn parse_package_json(&mut self, path: &Path, content: &str) {
+        let Ok(manifest) = serde_json::from_str::<serde_json::Value>(content) else {
+            return;
+        };
+        if let Some(name) = manifest.get("name").and_then(|n| n.as_str()) {
+            let version = manifest.get("version").and_then(|v| v.as_str()).map(str::to_string);
+            self.publish(PackageEcosystem::Npm, name, version, path);
+        }
+        for section in ["dependencies", "devDependencies", "peerDependencies", "optionalDependencies"] {
+            let Some(deps) = manifest.get(section).and_then(|d| d.as_object()) else {
+                continue;
+            };
+            for (name, version) in deps {
+                self.depend(PackageEcosystem::Npm, name, version.as_str().map(str::to_string), path);
+            }
+        }
+    }
+
+    fn parse_pyproject(&mut self, path: &Path, content: &str) {
+        let Ok(manifest) = content.parse::<toml::Table>() else {
+            return;
+        };
+        let project = manifest.get("project").and_then(|p| p.as_table());
+        let poetry = manifest
+            .get("tool")
+            .and_then(|t| t.get("poetry"))
+            .and_then(|p| p.as_table());
+
+        for table in project.into_iter().chain(poetry) {
+            if let Some(name) = table.get("name").and_then(|n| n.as_str()) {
+                let version = table.get("version").and_then(|v| v.as_str()).map(str::to_string);
+                self.publish(PackageEcosystem::Python, name, version, path);
+                break;
+            }
+        }
+
+        // PEP 621: dependencies = ["requests>=2", ...]
+        if let Some(deps) = project.and_then(|p| p.get("dependencies")).and_then(|d| d.as_array()) {
+            for requirement in deps.iter().filter_map(|d| d.as_str()) {
+                if let Some((name, version)) = parse_python_requirement(requirement) {
+                    self.depend(PackageEcosystem::Python, &name, version, path);
+                }
+            }
+        }
+        // Poetry: [tool.poetry.dependencies] requests = "^2.0"
+        if let Some(deps) = poetry.and_then(|p| p.get("dependencies")).and_then(|d| d.as_table()) {
+            for (name, spec) in deps {
+                if name == "python" {
+                    continue;
+                }
+                let version = match spec {
+                    toml::Value::String(v) => Some(v.clone()),
+                    _ => spec.get("version").and_then(|v| v.as_str()).map(str::to_string),
+                };
+                self.depend(PackageEcosystem::Python, name, version, path);
+            }
+        }
+    }
+
+    fn parse_requirements(&mut self, path: &Path, content: &str) {
+        for line in content.lines() {
+            let line = line.split('#').next().unwrap_or("").trim();
+            if line.is_empty() || line.starts_with('-') {
+                continue;
+            }
+            if let Some((name, version)) = parse_python_requirement(line) {
+                self.depend(PackageEcosystem::Python, &name, version, path);
+            }
+        }
+    }
+
+    fn parse_go_mod(&mut self, path: &Path, content: &str) {
+        let mut in_require = false;
+        for line in content.lines() {
+            let line = line.split("//").next().unwrap_or("").trim();
+            if let Some(module) = line.strip_prefix("module ") {
+                self.publish(PackageEcosystem::Go, module.trim(), None, path);
+            } else if line.starts_with("require (") || line == "require(" {
+                in_require = true;
+            } else if in_require && line == ")" {
+                in_require = false;
+            } else {
+                let requirement = if in_require { Some(line) } else { line.strip_prefix("require ") };
+                let mut parts = requirement.unwrap_or("").split_whitespace();
+                if let Some(module) = parts.next() {
+                    self.depend(PackageEcosystem::Go, module, parts.next().map(str::to_string), path);
+                }
+            }
+        }
+    }
+}
+
+/// 解析 `name[extra]>=1.0; marker` 形式的 Python 依赖
+fn parse_python_requirement(requirement: &str) -> Option<(String, Option<String>)> {
+    let requirement = requirement.split(';').next()?.trim();
+    let end = requirement
+        .find(|c: char| !(c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.')))
+        .unwrap_or(requirement.len());
+    let name = &requirement[..end];
+    if name.is_empty() {
+        return None;
+    }
+    let rest = requirement[end..].trim_start();
+    let rest = match rest.strip_prefix('[') {
+        Some(extras) => extras.split_once(']').map(|(_, r)| r).unwrap_or(""),
+        None => rest,
+    };
+    let version = rest.trim();
+    Some((name.to_string(), (!version.is_empty()).then(|| version.to_string())))
+}
+
+/// 规范化包名：Python 不区分大小写且 `-`/`_`/`.` 等价，crates.io 中 `-`/`_` 等价
+pub fn normalize_package_name(ecosystem: PackageEcosystem, name: &str) -> String {
+    match ecosystem {
+        PackageEcosystem::Python => name.to_lowercase().replace(['_', '.'], "-"),
+        PackageEcosystem::Cargo => name.replace('_', "-"),
+        PackageEcosystem::Npm | PackageEcosystem::Go => name.to_string(),
+    }
+}
+
+fn parse_version(version: &str) -> Option<Vec<u64>> {
+    let version = version.trim().trim_start_matches('v');
+    let core = version.split(['-', '+']).next()?;
+    let parts: Vec<u64> = core
+        .split('.')
+        .map_while(|p| p.trim().parse::<u64>().ok())
+        .collect();
+    (!parts.is_empty()).then_some(parts)
+}
+
+/// 粗略判断版本是否满足依赖要求：支持 `^`、`~`、`~=`、`=`/`==`、`>=` 和裸版本号（按 `^` 处理）；
+/// 无法识别的要求（`*`、路径、git、组合区间等）视为满足
+pub fn version_satisfies(version_req: &str, version: &str) -> bool {
+    let Some(version) = parse_version(version) else {
+        return true;
+    };
+    // 组合要求只检查第一段
+    let requirement = version_req.split([',', '|']).next().unwrap_or("").trim();
+    let (operator, rest) = match requirement.find(|c: char| c.is_ascii_digit() || c == 'v') {
+        Some(index) => requirement.split_at(index),
+        None => return true,
+    };
+    let Some(required) = parse_version(rest) else {
+        return true;
+    };
+    let at = |v: &[u64], i: usize| v.get(i).copied().unwrap_or(0);
+    let same_prefix = |len: usize| (0..len).all(|i| at(&version, i) == at(&required, i));
+    let not_older = (0..3).map(|i| at(&version, i).cmp(&at(&required, i))).find(|o| o.is_ne()).is_none_or(|o| o.is_gt());
+
+    match operator.trim() {
+        "=" | "==" => same_prefix(required.len()),
+        ">=" | ">" => not_older,
+        "<" | "<=" | "!=" => true,
+        "~" => same_prefix(required.len().min(2)) && not_older,
+        "~=" => same_prefix(required.len().saturating_sub(1).max(1)) && not_older,
+        "^" | "" => {
+            // 0.x 的不兼容边界在次版本号
+            let significant = required.iter().position(|&p| p != 0).map_or(required.len(), |i| i + 1);
+            same_prefix(significant.min(required.len())) && not_older
+        }
+        _ => true,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_scan_manifests() {
+        let dir = std::env::temp_dir().join(format!("codegraph-manifest-{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(dir.join("web")).unwrap();
+        fs::write(
+            dir.join("Cargo.toml"),
+            "[package]\nname = \"billing_core\"\nversion = \"1.4.0\"\n\n[dependencies]\nserde = \"1\"\nauth = { package = \"acme-auth\", version = \"0.3\" }\n",
+        )
+        .unwrap();
+        fs::write(
+            dir.join("web/package.json"),
+            r#"{"name": "@acme/web", "version": "2.0.0", "dependencies": {"@acme/ui": "^3.1.0"}}"#,
+        )
+        .unwrap();
+        fs::write(dir.join("requirements.txt"), "Acme_Client[async]>=1.2 ; python_version > '3.8'\n-e .\n").unwrap();
+
+        let packages = RepositoryPackages::scan(&dir);
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert!(packages.find_published(PackageEcosystem::Cargo, "billing-core").is_some());
+        assert!(packages.find_published(PackageEcosystem::Npm, "@acme/web").is_some());
+        let deps: Vec<(&str, Option<&str>)> = packages
+            .dependencies
+            .iter()
+            .map(|d| (d.name.as_str(), d.version_req.as_deref()))
+            .collect();
+        assert!(deps.contains(&("acme-auth", Some("0.3"))));
+        assert!(deps.contains(&("@acme/ui", Some("^3.1.0"))));
+        assert!(deps.contains(&("Acme_Client", Some(">=1.2"))));
+    }
+
+    #[test]
+    fn test_version_satisfies() {
+        assert!(version_satisfies("1", "1.4.0"));
+        assert!(version_satisfies("^1.2", "1.4.0"));
+        assert!(!version_satisfies("^2", "1.4.0"));
+        assert!(!version_satisfies("0.3", "0.4.1"));
+        assert!(version_satisfies("~1.4.0", "1.4.9"));
+        assert!(!version_satisfies("~1.4.0", "1.5.0"));
+        assert!(version_satisfies(">=1.2", "1.4.0"));
+        assert!(!version_satisfies("==1.3", "1.4.0"));
+        assert!(version_satisfies("*", "1.4.0"));
+        assert!(version_satisfies("v1.2.0", "v1.9.3"));
+    }
+}
@@ -3,12 +3,40 @@ pub mod parser;
 pub mod types;
 pub mod treesitter;
 pub mod repository;
+pub mod options;
+pub mod federation;
+pub mod manifest;
+pub mod sourcemap;
+pub mod diagram;
+pub mod proto;
+pub mod grpc;
+pub mod call_kinds;
+pub mod rust_methods;
+pub mod reexports;
+pub mod rust_macros;
+pub mod body_hash;
+pub mod hierarchy;
+pub mod tsconfig;
+pub mod overloads;
+pub mod overrides;
+pub mod signatures;
+pub mod closures;
+pub mod decorators;
+pub mod entrypoints;
+pub mod capabilities;
 
 pub use graph::CodeGraph;
 pub use types::{
-    CallRelation, FunctionInfo, GraphNode, GraphRelation, PetCodeGraph,
+    CallRelation, FunctionInfo, FunctionParameter, GraphNode, GraphRelation, GraphViolation, PetCodeGraph,
     ClassInfo, ClassType, EntityNode, EntityEdge, EntityEdgeType, EntityGraph,
-    FileMetadata, FileIndex, SnippetIndex, SnippetInfo
+    FileMetadata, FileIndex, FileParseStats, SnippetIndex, SnippetInfo
 };
-pub use treesitter::TreeSitterParser;
-pub use repository::{RepositoryManager, RepositoryStats, SearchResult};
\ No newline at end of file
+pub use treesitter::{AnalyzerRegistry, LanguageAnalyzer, NotebookCells, TreeSitterParser};
+pub use repository::{RepositoryManager, RepositoryStats, SearchResult};
+pub use options::AnalyzeOptions;
+pub use federation::{
+    CrossRepoEdge, DuplicateFunction, DuplicateGroup, FederatedHit, FederatedQueryKind, FederatedRepository, ImpactedFunction, PackageLink,
+    RepositoryFederation,
+};
+pub use manifest::{PackageEcosystem, RepositoryPackages};
+pub use sourcemap::{OriginalLocation, SourceMap};
\ No newline at end of file
@@ -0,0 +1,117 @@
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::codegraph::treesitter::AnalyzerRegistry;
+
+/// 可用于限定构建范围的语言名称（与 `FunctionInfo.language` 一致）
+pub const SUPPORTED_LANGUAGES: &[&str] = &["rust", "python", "javascript", "typescript", "java", "cpp", "go", "kotlin", "ruby", "swift", "scala", "objc", "lua", "bash", "sql", "proto", "vue", "svelte", "html"];
+
+/// 扫描时总是跳过的目录名；以 `.` 开头的目录也会跳过
+pub const IGNORED_DIRECTORIES: &[&str] = &["target", "node_modules", "__pycache__"];
+
+/// 分析选项，控制一次构建的范围
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct AnalyzeOptions {
+    /// 仅分析这些语言，为空表示全部
+    #[serde(default)]
+    pub languages: Vec<String>,
+    /// 排除的路径：glob模式（相对项目根目录），或不含通配符的目录/文件前缀
+    #[serde(default)]
+    pub exclude_paths: Vec<String>,
+    /// 忽略已保存的图和文件哈希，强制全量构建
+    #[serde(default)]
+    pub force_rebuild: bool,
+    /// 增量构建读写已保存图和文件哈希的目录，默认为 `.codegraph_db`
+    #[serde(skip)]
+    pub storage_dir: Option<PathBuf>,
+    /// 构建和增量更新后校验图的一致性，发现问题时报错（调试构建中总是校验，但只记录日志）
+    #[serde(default)]
+    pub strict: bool,
+    /// C/C++ 宏定义作为函数分析：宏调用解析到宏，宏体中的调用作为宏的调用
+    #[serde(default)]
+    pub expand_macros: bool,
+    /// 将函数源码压缩后随图一起保存，片段查询返回被分析版本的代码而不是读取工作区
+    #[serde(default)]
+    pub embed_sources: bool,
+    /// 文件无法解析时按函数名猜测调用（main/entry 调用同文件的其他函数，测试函数调用被测函数）；
+    /// 猜出的边会污染图，默认关闭，解析失败记录在解析统计中
+    #[serde(default)]
+    pub heuristic_fallback: bool,
+    /// 为框架装饰器（`@app.route`、`@Scheduled`、`@Get` 等）注册的函数添加来自框架节点的调用边，
+    /// 这些函数不再显示为没有调用者
+    #[serde(default)]
+    pub framework_entry_points: bool,
+}
+
+impl AnalyzeOptions {
+    /// 规范化语言名称（支持常见别名），未知语言返回错误
+    pub fn normalize_language(language: &str) -> Result<String, String> {
+        let normalized = match language.trim().to_lowercase().as_str() {
+            "rs" => "rust".to_string(),
+            "py" => "python".to_string(),
+            "js" | "jsx" => "javascript".to_string(),
+            "ts" | "tsx" => "typescript".to_string(),
+            "c" | "c++" | "cc" | "cxx" => "cpp".to_string(),
+            "golang" => "go".to_string(),
+            "objective-c" | "objective-c++" | "objc++" | "m" | "mm" => "objc".to_string(),
+            "sh" | "shell" | "shellscript" => "bash".to_string(),
+            "tsql" | "t-sql" | "plsql" | "pl/sql" | "plpgsql" | "mysql" => "sql".to_string(),
+            "protobuf" | "grpc" => "proto".to_string(),
+            "htm" => "html".to_string(),
+            other => other.to_string(),
+        };
+        if SUPPORTED_LANGUAGES.contains(&normalized.as_str()) || AnalyzerRegistry::is_registered_language(&normalized) {
+            Ok(normalized)
+        } else {
+            Err(format!(
+                "Unsupported language '{}', expected one of: {}",
+                language,
+                SUPPORTED_LANGUAGES.join(", ")
+            ))
+        }
+    }
+
+    /// 设置语言过滤（会做规范化）
+    pub fn with_languages(mut self, languages: &[String]) -> Result<Self, String> {
+        self.languages = languages
+            .iter()
+            .map(|l| Self::normalize_language(l))
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(self)
+    }
+
+    /// 是否限定了构建范围（此时不能复用全量构建的增量结果）
+    pub fn is_scoped(&self) -> bool {
+        !self.languages.is_empty() || !self.exclude_paths.is_empty()
+    }
+
+    /// 语言是否在构建范围内
+    pub fn allows_language(&self, language: &str) -> bool {
+        self.languages.is_empty() || self.languages.iter().any(|l| l == language)
+    }
+
+    /// 路径是否被排除，`root` 为项目根目录
+    pub fn is_excluded(&self, root: &Path, path: &Path) -> bool {
+        if self.exclude_paths.is_empty() {
+            return false;
+        }
+        let relative = path.strip_prefix(root).unwrap_or(path);
+        self.exclude_paths.iter().any(|pattern| path_matches(pattern, relative))
+    }
+}
+
+/// 相对路径是否匹配模式：含通配符时按glob匹配，否则按目录/文件前缀或路径中的某一段匹配
+pub fn path_matches(pattern: &str, relative: &Path) -> bool {
+    let relative_str = relative.to_string_lossy().replace('\\', "/");
+    let pattern = pattern.trim_start_matches("./").trim_end_matches('/');
+    if pattern.contains(['*', '?', '[']) {
+        glob::Pattern::new(pattern)
+            .map(|p| p.matches(&relative_str))
+            .unwrap_or(false)
+    } else {
+        relative_str == pattern
+            || relative_str.starts_with(&format!("{}/", pattern))
+            || relative.components().any(|c| c.as_os_str() == pattern)
+    }
+}
@@ -0,0 +1,428 @@
+use regex::Regex;
+
+/// 按实参个数和类型区分同名函数的语言
+pub const OVERLOADED_LANGUAGES: &[&str] = &["java", "cpp", "typescript", "javascript"];
+
+/// 实参或形参的类型，字面量只能确定大类
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum ArgType {
+    Integer,
+    Float,
+    Text,
+    Boolean,
+    Char,
+    /// 其他具名类型，取最后一段（`com.acme.Order`、`acme::Order` 为 `Order`）
+    Named(String),
+}
+
+/// 一个形参：声明的类型（未写出或为泛型参数、`any`、`Object` 时为空）和是否可省略（有默认值或为可选参数）
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Parameter {
+    pub type_: Option<ArgType>,
+    pub optional: bool,
+}
+
+/// 函数声明的形参列表
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct Parameters {
+    pub params: Vec<Parameter>,
+    /// 最后一个形参接收任意多个实参（`String... args`、`...rest`、C/C++ 的 `...`）
+    pub variadic: bool,
+}
+
+impl Parameters {
+    /// 函数声明源码中 `name` 后括号里的形参
+    pub fn parse(language: &str, declaration: &str, name: &str) -> Option<Self> {
+        let items = list_after(declaration, name, true)?;
+        let mut parameters = Parameters::default();
+        for item in items {
+            let item = item.trim();
+            // `f(void)` 和 `f()` 一样没有参数
+            if item.is_empty() || (language == "cpp" && item == "void") {
+                continue;
+            }
+            if item.contains("...") {
+                parameters.variadic = true;
+            }
+            let (declared, default) = match top_level_find(item, '=') {
+                Some(at) => (item[..at].trim(), true),
+                None => (item, false),
+            };
+            let (optional, type_) = match language {
+                "typescript" | "javascript" => {
+                    let (name, type_) = match top_level_find(declared, ':') {
+                        Some(at) => (declared[..at].trim(), Some(declared[at + 1..].trim())),
+                        None => (declared, None),
+                    };
+                    (default || name.ends_with('?'), type_.and_then(param_type))
+                }
+                _ => (default, param_type(strip_parameter_name(declared))),
+            };
+            parameters.params.push(Parameter { type_, optional });
+        }
+        Some(parameters)
+    }
+
+    /// 能否以 `arity` 个实参调用
+    pub fn accepts(&self, arity: usize) -> bool {
+        let required = self.params.iter().filter(|p| !p.optional).count() - usize::from(self.variadic && self.params.last().is_some_and(|p| !p.optional));
+        required <= arity && (self.variadic || arity <= self.params.len())
+    }
+
+    /// 第 `index` 个实参对应的形参，可变参数之后的实参都对应最后一个形参
+    fn at(&self, index: usize) -> Option<&Parameter> {
+        self.params.get(index).or_else(|| self.params.last().filter(|_| self.variadic))
+    }
+}
+
+/// 调用点的实参，类型可由字面量、`new T(..)` 或前面声明的变量和参数确定时记录类型
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CallArguments {
+    pub types: Vec<Option<ArgType>>,
+}
+
+impl CallArguments {
+    /// `call` 为调用表达式的源码（从接收者或调用名开始）；`before` 为调用之前的源码，
+    /// 在其中查找作为实参的变量最近一次声明的类型
+    pub fn parse(language: &str, call: &str, name: &str, before: &str) -> Option<Self> {
+        let items = list_after(call, name, false)?;
+        let types = items
+            .iter()
+            .map(|item| item.trim())
+            .filter(|item| !item.is_empty())
+            .map(|item| literal_type(language, item).or_else(|| variable_type(language, item, before)))
+            .collect();
+        Some(Self { types })
+    }
+}
+
+/// 从同名候选函数中按实参选择被调用的重载，`None` 表示候选函数的形参未知。
+///
+/// 先保留能以该实参个数调用的候选（都不能时保留全部），再按实参与形参类型的匹配程度打分：
+/// 类型相同得2分，可隐式转换（整数传给浮点数、字符传给整数）得1分，一方未知或具名类型不同
+/// （可能是子类型）得0分，其余扣1分。返回得分最高的第一个候选和置信度：只有一种形参列表时
+/// 不是重载，置信度为 `None`；否则为得分最高的不同形参列表数的倒数，实参个数与所有候选都不符时减半
+pub fn pick(candidates: &[Option<&Parameters>], call: &CallArguments) -> Option<(usize, Option<f32>)> {
+    if candidates.is_empty() {
+        return None;
+    }
+    let arity = call.types.len();
+    let mut fits: Vec<usize> = (0..candidates.len()).filter(|&i| candidates[i].is_none_or(|p| p.accepts(arity))).collect();
+    let penalty = if fits.is_empty() {
+        fits = (0..candidates.len()).collect();
+        0.5
+    } else {
+        1.0
+    };
+    let score = |parameters: Option<&Parameters>| -> i32 {
+        let Some(parameters) = parameters else { return 0 };
+        call.types
+            .iter()
+            .enumerate()
+            .map(|(i, arg)| match (arg, parameters.at(i).and_then(|p| p.type_.as_ref())) {
+                (Some(arg), Some(param)) => type_score(arg, param),
+                _ => 0,
+            })
+            .sum()
+    };
+    let scores: Vec<(usize, i32)> = fits.iter().map(|&i| (i, score(candidates[i]))).collect();
+    let best = scores.iter().map(|(_, s)| *s).max()?;
+    let winners: Vec<usize> = scores.iter().filter(|(_, s)| *s == best).map(|(i, _)| *i).collect();
+
+    let distinct = |indices: &mut dyn Iterator<Item = usize>| {
+        let mut seen: Vec<Option<&Parameters>> = Vec::new();
+        for i in indices {
+            // 形参未知的候选各算一种
+            if candidates[i].is_none() || !seen.contains(&candidates[i]) {
+                seen.push(candidates[i]);
+            }
+        }
+        seen.len()
+    };
+    let confidence = (distinct(&mut (0..candidates.len())) > 1).then(|| penalty / distinct(&mut winners.iter().copied()) as f32);
+    Some((winners[0], confidence))
+}
+
+fn type_score(arg: &ArgType, param: &ArgType) -> i32 {
+    match (arg, param) {
+        (a, p) if a == p => 2,
+        (ArgType::Integer, ArgType::Float) | (ArgType::Char, ArgType::Integer) => 1,
+        (ArgType::Named(_), ArgType::Named(_)) => 0,
+        _ => -1,
+    }
+}
+
+/// `text` 中第一个 `name` 后（可跟 `<..>` 模板实参）括号里用顶层逗号分隔的各项；
+/// `angles` 为真时 `<>` 也作为括号（形参中的泛型类型），实参中 `<` 可能是比较运算符
+fn list_after<'a>(text: &'a str, name: &str, angles: bool) -> Option<Vec<&'a str>> {
+    let (open, close) = parenthesis_after(text, name, angles)?;
+    Some(split_top_level(&text[open + 1..close], angles))
+}
+
+/// `text` 中第一个 `name` 后（可跟 `<..>` 模板实参）的括号的左右括号位置
+pub(crate) fn parenthesis_after(text: &str, name: &str, angles: bool) -> Option<(usize, usize)> {
+    if name.is_empty() {
+        return None;
+    }
+    let bytes = text.as_bytes();
+    let is_ident = |b: u8| b.is_ascii_alphanumeric() || b == b'_' || b == b'$';
+    let mut from = 0;
+    let open = loop {
+        let at = from + text.get(from..)?.find(name)?;
+        from = at + name.len();
+        if at > 0 && is_ident(bytes[at - 1]) || bytes.get(from).copied().is_some_and(is_ident) {
+            continue;
+        }
+        let mut i = skip_spaces(bytes, from);
+        if bytes.get(i) == Some(&b'<') {
+            i = skip_spaces(bytes, close_of(bytes, i, true)? + 1);
+        }
+        if bytes.get(i) == Some(&b'(') {
+            break i;
+        }
+    };
+    Some((open, close_of(bytes, open, angles)?))
+}
+
+/// 用顶层逗号分隔的各项，只有空白时为空
+pub(crate) fn split_top_level(inner: &str, angles: bool) -> Vec<&str> {
+    let mut items = Vec::new();
+    let mut start = 0;
+    for at in top_level_positions(inner, ',', angles) {
+        items.push(&inner[start..at]);
+        start = at + 1;
+    }
+    items.push(&inner[start..]);
+    if items.len() == 1 && items[0].trim().is_empty() {
+        items.clear();
+    }
+    items
+}
+
+fn skip_spaces(bytes: &[u8], mut i: usize) -> usize {
+    while bytes.get(i).is_some_and(|b| b.is_ascii_whitespace()) {
+        i += 1;
+    }
+    i
+}
+
+/// 与 `open` 处的括号配对的右括号位置，跳过字符串
+pub(crate) fn close_of(bytes: &[u8], open: usize, angles: bool) -> Option<usize> {
+    let mut depth = 0usize;
+    let mut quote = None;
+    let mut i = open;
+    while i < bytes.len() {
+        let b = bytes[i];
+        match quote {
+            Some(q) => {
+                if b == b'\\' {
+                    i += 1;
+                } else if b == q {
+                    quote = None;
+                }
+            }
+            None => match b {
+                b'"' | b'\'' | b'`' => quote = Some(b),
+                b'(' | b'[' | b'{' => depth += 1,
+                b'<' if angles => depth += 1,
+                b')' | b']' | b'}' => {
+                    depth = depth.checked_sub(1)?;
+                    if depth == 0 {
+                        return Some(i);
+                    }
+                }
+                // `->`、`=>` 中的 `>` 不是括号
+                b'>' if angles && !matches!(bytes.get(i.wrapping_sub(1)), Some(b'-' | b'=')) => {
+                    depth = depth.checked_sub(1)?;
+                    if depth == 0 {
+                        return Some(i);
+                    }
+                }
+                _ => {}
+            },
+        }
+        i += 1;
+    }
+    None
+}
+
+/// `text` 中不在括号和字符串内的 `target` 的位置
+pub(crate) fn top_level_positions(text: &str, target: char, angles: bool) -> Vec<usize> {
+    let bytes = text.as_bytes();
+    let mut positions = Vec::new();
+    let mut depth = 0i32;
+    let mut quote = None;
+    let mut i = 0;
+    while i < bytes.len() {
+        let b = bytes[i];
+        match quote {
+            Some(q) => {
+                if b == b'\\' {
+                    i += 1;
+                } else if b == q {
+                    quote = None;
+                }
+            }
+            None => match b {
+                b'"' | b'\'' | b'`' => quote = Some(b),
+                b'(' | b'[' | b'{' => depth += 1,
+                b')' | b']' | b'}' => depth -= 1,
+                b'<' if angles => depth += 1,
+                b'>' if angles && !matches!(bytes.get(i.wrapping_sub(1)), Some(b'-' | b'=')) => depth -= 1,
+                // `=>`、`==` 不是默认值
+                b'=' if matches!(bytes.get(i + 1), Some(b'>' | b'=')) => i += 1,
+                _ if depth == 0 && b == target as u8 => positions.push(i),
+                _ => {}
+            },
+        }
+        i += 1;
+    }
+    positions
+}
+
+pub(crate) fn top_level_find(text: &str, target: char) -> Option<usize> {
+    top_level_positions(text, target, true).first().copied()
+}
+
+/// Java/C/C++ 形参去掉参数名、注解和修饰符后的类型：`final List<String> names` 为 `List<String>`
+pub(crate) fn strip_parameter_name(declared: &str) -> &str {
+    let declared = declared.trim();
+    let end = declared.trim_end_matches(|c: char| c == ']' || c == '[' || c.is_whitespace()).len();
+    let declared = &declared[..end];
+    // 只有一个词时是省略了参数名的类型
+    let Some(split) = declared.rfind(|c: char| !(c.is_alphanumeric() || c == '_')) else { return declared };
+    let (type_, name) = declared.split_at(split + 1);
+    if name.is_empty() || type_.trim().is_empty() {
+        declared
+    } else {
+        type_.trim()
+    }
+}
+
+/// 形参类型文本对应的类型，泛型参数、`any`、`Object`、`auto` 等可接受任意实参的类型为 `None`
+fn param_type(text: &str) -> Option<ArgType> {
+    let pointer = text.contains('*');
+    let mut type_ = text.to_string();
+    for modifier in ["const ", "final ", "volatile ", "unsigned ", "signed ", "struct ", "readonly "] {
+        type_ = type_.replace(modifier, "");
+    }
+    // 注解 `@NonNull String`
+    let type_ = type_.split_whitespace().filter(|word| !word.starts_with('@')).collect::<Vec<_>>().join(" ");
+    let type_ = type_.split('<').next().unwrap_or_default();
+    let type_ = type_.trim_end_matches(['&', '*', '[', ']', '.', ' ']);
+    let last = type_.rsplit(['.', ':']).next().unwrap_or_default().trim();
+    match last {
+        "char" | "wchar_t" if pointer => Some(ArgType::Text),
+        "int" | "long" | "short" | "byte" | "Integer" | "Long" | "Short" | "Byte" | "BigInteger" | "size_t" | "ssize_t" | "ptrdiff_t"
+        | "int8_t" | "int16_t" | "int32_t" | "int64_t" | "uint8_t" | "uint16_t" | "uint32_t" | "uint64_t" | "bigint" => Some(ArgType::Integer),
+        "float" | "double" | "Float" | "Double" | "BigDecimal" | "number" => Some(ArgType::Float),
+        "String" | "string" | "wstring" | "string_view" | "CharSequence" => Some(ArgType::Text),
+        "bool" | "boolean" | "Boolean" => Some(ArgType::Boolean),
+        "char" | "Character" | "wchar_t" => Some(ArgType::Char),
+        "" | "any" | "unknown" | "Object" | "object" | "auto" | "void" => None,
+        // 单个大写字母是泛型参数
+        name if name.len() == 1 && name.starts_with(char::is_uppercase) => None,
+        name if name.starts_with(|c: char| c.is_alphabetic() || c == '_') => Some(ArgType::Named(name.to_string())),
+        _ => None,
+    }
+}
+
+/// 字面量和 `new T(..)` 实参的类型
+fn literal_type(language: &str, arg: &str) -> Option<ArgType> {
+    let integer = Regex::new(r"^-?(0[xX][0-9a-fA-F_]+|0[bB][01_]+|\d[\d_]*)[lLuUn]*$").unwrap();
+    let float = Regex::new(r"^-?(\d[\d_]*\.\d*|\.\d+)([eE][-+]?\d+)?[fFdD]?$|^-?\d+([eE][-+]?\d+)?[fFdD]$").unwrap();
+    let new = Regex::new(r"^new\s+([\w.:$]+)").unwrap();
+    match arg {
+        "true" | "false" => return Some(ArgType::Boolean),
+        _ if integer.is_match(arg) => return Some(ArgType::Integer),
+        _ if float.is_match(arg) => return Some(ArgType::Float),
+        _ => {}
+    }
+    if arg.starts_with('"') || arg.starts_with('`') || arg.starts_with("L\"") || arg.starts_with("u8\"") {
+        return Some(ArgType::Text);
+    }
+    if arg.starts_with('\'') {
+        // JavaScript/TypeScript 的单引号也是字符串
+        return Some(if matches!(language, "typescript" | "javascript") { ArgType::Text } else { ArgType::Char });
+    }
+    new.captures(arg).and_then(|c| param_type(&c[1]))
+}
+
+/// 变量实参在 `before` 中最近一次声明（局部变量或调用者的参数）的类型
+fn variable_type(language: &str, arg: &str, before: &str) -> Option<ArgType> {
+    if !arg.chars().all(|c| c.is_alphanumeric() || c == '_' || c == '$') || arg.starts_with(char::is_numeric) {
+        return None;
+    }
+    let name = regex::escape(arg);
+    match language {
+        "typescript" | "javascript" => {
+            let annotated = Regex::new(&format!(r"(?:^|[^\w$.]){}\s*\??\s*:\s*([\w.$]+)", name)).ok()?;
+            let initialized = Regex::new(&format!(r"\b(?:let|const|var)\s+{}\s*=\s*([^;,\n]+)", name)).ok()?;
+            last_declaration(before, &[(&annotated, false), (&initialized, true)], language)
+        }
+        _ => {
+            let declared = Regex::new(&format!(r"([A-Za-z_][\w.:$]*(?:<[^;(){{}}]*>)?(?:\s*\[\s*\])*)\s*[&*]*\s+[&*]*{}\s*[=;,)\[:]", name)).ok()?;
+            let inferred = Regex::new(&format!(r"\b(?:var|auto)\s+{}\s*=\s*([^;,\n]+)", name)).ok()?;
+            last_declaration(before, &[(&declared, false), (&inferred, true)], language)
+        }
+    }
+}
+
+/// 各模式在 `before` 中最后一次匹配得到的类型；`initializer` 为真时捕获的是初始值而非类型
+fn last_declaration(before: &str, patterns: &[(&Regex, bool)], language: &str) -> Option<ArgType> {
+    let keywords = ["return", "new", "else", "case", "throw", "delete", "typeof", "instanceof", "in", "of", "var", "auto", "let", "const"];
+    patterns
+        .iter()
+        .filter_map(|(pattern, initializer)| {
+            let captures = pattern.captures_iter(before).filter(|c| !keywords.contains(&&c[1])).last()?;
+            let position = captures.get(0)?.end();
+            let type_ = if *initializer { literal_type(language, captures[1].trim()) } else { param_type(&captures[1]) };
+            Some((position, type_))
+        })
+        .max_by_key(|(position, _)| *position)
+        .and_then(|(_, type_)| type_)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parameters() {
+        let java = Parameters::parse("java", "public static int add(final int a, @NonNull List<String> names, String... rest) {", "add").unwrap();
+        assert_eq!(java.params.iter().map(|p| p.type_.clone()).collect::<Vec<_>>(), vec![Some(ArgType::Integer), Some(ArgType::Named("List".to_string())), Some(ArgType::Text)]);
+        assert!(java.variadic && java.accepts(2) && java.accepts(5) && !java.accepts(1));
+
+        let cpp = Parameters::parse("cpp", "std::string Formatter::format(const char* text, double width = 1.0)", "format").unwrap();
+        assert_eq!(cpp.params, vec![Parameter { type_: Some(ArgType::Text), optional: false }, Parameter { type_: Some(ArgType::Float), optional: true }]);
+        assert!(cpp.accepts(1) && cpp.accepts(2) && !cpp.accepts(3));
+        assert!(Parameters::parse("cpp", "void reset(void)", "reset").unwrap().params.is_empty());
+
+        let ts = Parameters::parse("typescript", "render(node: Node, depth?: number, ...rest: string[]): void", "render").unwrap();
+        assert_eq!(ts.params.iter().map(|p| p.optional).collect::<Vec<_>>(), vec![false, true, false]);
+        assert!(ts.accepts(1) && ts.accepts(4) && !ts.accepts(0));
+    }
+
+    #[test]
+    fn test_pick_overload() {
+        let before = "void run(String label) {\n    long count = 3;\n    Order order = new Order();\n";
+        let call = CallArguments::parse("java", "log.write(count, \"x\", order)", "write", before).unwrap();
+        assert_eq!(call.types, vec![Some(ArgType::Integer), Some(ArgType::Text), Some(ArgType::Named("Order".to_string()))]);
+        let call = CallArguments::parse("java", "write(label, max(a, b))", "write", before).unwrap();
+        assert_eq!(call.types, vec![Some(ArgType::Text), None]);
+
+        let one = Parameters::parse("java", "void write(String s)", "write").unwrap();
+        let text = Parameters::parse("java", "void write(String s, String t)", "write").unwrap();
+        let number = Parameters::parse("java", "void write(int n, String t)", "write").unwrap();
+        let candidates = [Some(&one), Some(&text), Some(&number)];
+        // 实参个数排除第一个，类型选出第三个
+        let call = CallArguments::parse("java", "write(7, \"x\")", "write", "").unwrap();
+        assert_eq!(pick(&candidates, &call), Some((2, Some(1.0))));
+        // 类型未知时两个候选同分
+        let call = CallArguments::parse("java", "write(a, b)", "write", "").unwrap();
+        assert_eq!(pick(&candidates, &call), Some((1, Some(0.5))));
+        // 同一种形参列表（头文件中的声明和实现）不是重载
+        assert_eq!(pick(&[Some(&one), Some(&one)], &call), Some((0, None)));
+        assert_eq!(pick(&[], &call), None);
+    }
+}
@@ -0,0 +1,177 @@
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+use tracing::warn;
+
+use crate::codegraph::types::FunctionInfo;
+use crate::storage::annotations::FunctionIndex;
+
+/// 项目根目录下的手工调用解析文件名
+pub const OVERRIDES_FILE: &str = "codegraph-overrides.toml";
+
+/// 一个或多个目标函数，写法与注解文件的节点键相同：
+/// `src/handlers.py::on_create`、`handlers.on_create` 或只写函数名
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum OverrideTargets {
+    One(String),
+    Many(Vec<String>),
+}
+
+impl OverrideTargets {
+    fn keys(&self) -> &[String] {
+        match self {
+            Self::One(key) => std::slice::from_ref(key),
+            Self::Many(keys) => keys,
+        }
+    }
+}
+
+/// 某个调用处的动态分派，`callee` 为空时匹配该行的所有调用
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SiteOverride {
+    /// 调用者所在文件的路径后缀
+    pub file: String,
+    pub line: usize,
+    #[serde(default)]
+    pub callee: Option<String>,
+    #[serde(alias = "target")]
+    pub targets: OverrideTargets,
+}
+
+/// `codegraph-overrides.toml`：把未解析的被调用名或动态分派的调用处映射到具体函数
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct CallOverrides {
+    /// 调用处写出的被调用名 -> 目标函数
+    #[serde(default)]
+    pub calls: BTreeMap<String, OverrideTargets>,
+    #[serde(default, rename = "site")]
+    pub sites: Vec<SiteOverride>,
+}
+
+impl CallOverrides {
+    /// 读取项目根目录下的覆盖文件，不存在时返回 `None`
+    pub fn find(root: &Path) -> Result<Option<Self>, String> {
+        let path = root.join(OVERRIDES_FILE);
+        if path.is_file() { Self::load(&path).map(Some) } else { Ok(None) }
+    }
+
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let content = fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read overrides file {}: {}", path.display(), e))?;
+        toml::from_str(&content).map_err(|e| format!("Invalid overrides file {}: {}", path.display(), e))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.calls.is_empty() && self.sites.is_empty()
+    }
+
+    /// 在图中的函数里查找各条覆盖的目标，找不到的目标记录警告后忽略
+    pub fn resolve<'a>(&self, functions: impl IntoIterator<Item = &'a FunctionInfo>) -> ResolvedOverrides {
+        let index = FunctionIndex::from_functions(functions);
+        let lookup = |targets: &OverrideTargets| -> Vec<FunctionInfo> {
+            let mut resolved: Vec<FunctionInfo> = Vec::new();
+            for key in targets.keys() {
+                let matched = index.lookup(key);
+                if matched.is_empty() {
+                    warn!("Call override target {} does not match any function", key);
+                }
+                for function in matched {
+                    if !resolved.iter().any(|f| f.id == function.id) {
+                        resolved.push(function.clone());
+                    }
+                }
+            }
+            resolved
+        };
+
+        let calls = self
+            .calls
+            .iter()
+            .map(|(name, targets)| (name.clone(), lookup(targets)))
+            .filter(|(_, targets)| !targets.is_empty())
+            .collect();
+        let sites = self
+            .sites
+            .iter()
+            .map(|site| (site.clone(), lookup(&site.targets)))
+            .filter(|(_, targets)| !targets.is_empty())
+            .collect();
+        ResolvedOverrides { calls, sites }
+    }
+}
+
+/// 已在图中找到目标函数的覆盖
+#[derive(Debug, Clone, Default)]
+pub struct ResolvedOverrides {
+    calls: HashMap<String, Vec<FunctionInfo>>,
+    sites: Vec<(SiteOverride, Vec<FunctionInfo>)>,
+}
+
+impl ResolvedOverrides {
+    /// 调用处的目标函数，调用处覆盖优先于按名称的覆盖
+    pub fn targets(&self, file: &Path, line: usize, call_name: &str) -> Option<&[FunctionInfo]> {
+        self.sites
+            .iter()
+            .find(|(site, _)| {
+                site.line == line
+                    && file.ends_with(&site.file)
+                    && site.callee.as_deref().is_none_or(|callee| callee == call_name)
+            })
+            .map(|(_, targets)| targets.as_slice())
+            .or_else(|| self.calls.get(call_name).map(Vec::as_slice))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.calls.is_empty() && self.sites.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+    use uuid::Uuid;
+
+    fn function(name: &str, namespace: &str, file: &str) -> FunctionInfo {
+        FunctionInfo {
+            id: Uuid::new_v4(),
+            name: name.to_string(),
+            file_path: PathBuf::from(file),
+            line_start: 1,
+            line_end: 5,
+            namespace: namespace.to_string(),
+            language: "python".to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_resolves_names_and_sites() {
+        let overrides: CallOverrides = toml::from_str(
+            r#"
+            [calls]
+            "handlers[kind]" = "src/handlers.py::on_create"
+            "bus.emit" = ["on_create", "audit.record", "missing"]
+
+            [[site]]
+            file = "src/app.py"
+            line = 12
+            target = "audit.record"
+            "#,
+        )
+        .unwrap();
+        let on_create = function("on_create", "handlers", "/repo/src/handlers.py");
+        let record = function("record", "audit", "/repo/src/audit.py");
+        let resolved = overrides.resolve([&on_create, &record]);
+
+        let ids = |targets: Option<&[FunctionInfo]>| targets.unwrap_or_default().iter().map(|f| f.id).collect::<Vec<_>>();
+        let app = Path::new("/repo/src/app.py");
+        assert_eq!(ids(resolved.targets(app, 3, "handlers[kind]")), vec![on_create.id]);
+        assert_eq!(ids(resolved.targets(app, 3, "bus.emit")), vec![on_create.id, record.id]);
+        assert_eq!(ids(resolved.targets(app, 12, "handlers[kind]")), vec![record.id]);
+        assert!(resolved.targets(app, 3, "other").is_none());
+    }
+}
@@ -1,15 +1,33 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::path::{Path, PathBuf};
 use std::fs;
 use uuid::Uuid;
 use tracing::{info, warn, debug};
 
 use crate::codegraph::types::{
-    FunctionInfo, CallRelation, PetCodeGraph, EntityGraph, ClassInfo, ClassType,
-    FileIndex, SnippetIndex
+    FunctionInfo, CallRelation, CallKind, PetCodeGraph, FunctionKind, EntityGraph,
+    EntityEdge, EntityEdgeType, ClassInfo, ClassType, FileIndex, FileParseStats, SnippetIndex
 };
 use crate::codegraph::graph::CodeGraph;
-use crate::codegraph::treesitter::TreeSitterParser;
+use crate::codegraph::treesitter::{AnalyzerRegistry, NotebookCells, TreeSitterParser};
+use crate::codegraph::options::{AnalyzeOptions, IGNORED_DIRECTORIES};
+use crate::codegraph::sourcemap::{utf16_column, SourceMap};
+use crate::codegraph::grpc::RpcIndex;
+use crate::codegraph::call_kinds::CallKinds;
+use crate::codegraph::reexports::{ModuleImport, ModuleImports};
+use crate::codegraph::tsconfig::TsConfig;
+use crate::codegraph::rust_macros::{MacroDefinition, RustMacros};
+use crate::codegraph::rust_methods::{impl_owners, ReceiverTypes};
+use crate::codegraph::body_hash::body_hash;
+use crate::codegraph::hierarchy::{scan_types, TypeDeclaration};
+use crate::codegraph::overloads::{self, CallArguments, Parameters, OVERLOADED_LANGUAGES};
+use crate::codegraph::overrides::{CallOverrides, ResolvedOverrides, OVERRIDES_FILE};
+use crate::codegraph::signatures::{self, Signature};
+use crate::codegraph::closures::Closures;
+use crate::codegraph::decorators::{scan_decorators, DecoratedKind, Decoration};
+
+/// 函数在生成文件中的范围：((起始行, 起始列), (结束行, 结束列))，行号从0开始
+type GeneratedRange = ((usize, usize), (usize, usize));
 
 /// 代码解析器，负责解析源代码文件并提取函数调用关系
 pub struct CodeParser {
@@ -23,6 +41,40 @@ pub struct CodeParser {
     file_index: FileIndex,
     /// 代码片段索引
     snippet_index: SnippetIndex,
+    /// 分析选项
+    options: AnalyzeOptions,
+    /// 带 source map 的生成文件（JS/TS 构建产物）-> source map
+    source_maps: HashMap<PathBuf, SourceMap>,
+    /// 函数ID -> 原始源码位置 (文件, 起始行, 结束行)，调用分析完成后写回图中
+    original_locations: HashMap<Uuid, (PathBuf, usize, usize)>,
+    /// 函数ID -> 在生成文件中的范围，压缩代码只有一行时按列确定调用者
+    generated_ranges: HashMap<Uuid, GeneratedRange>,
+    /// Jupyter notebook -> 代码单元，符号范围指向拼接后的脚本
+    notebooks: HashMap<PathBuf, NotebookCells>,
+    /// 函数ID -> 在 notebook 拼接脚本中的行范围（函数本身记录的是单元内的行号）
+    notebook_ranges: HashMap<Uuid, (usize, usize)>,
+    /// Rust 方法的函数ID -> 所在 impl 或 trait 块的类型名
+    method_owners: HashMap<Uuid, String>,
+    /// (类型名, 方法名) -> Rust 方法，按接收者类型解析 `obj.method()`
+    rust_methods: HashMap<(String, String), FunctionInfo>,
+    /// Rust 文件 -> (命名空间, 文件中的声明宏定义和宏调用)
+    rust_macros: HashMap<PathBuf, (String, RustMacros)>,
+    /// 文件 -> (命名空间, 文件中的导入和再导出语句)
+    module_imports: HashMap<PathBuf, (String, ModuleImports)>,
+    /// 再导出节点的函数ID -> 被再导出的函数ID
+    reexport_targets: HashMap<Uuid, Uuid>,
+    /// 本次解析过的文件 -> 解析耗时、语法树节点数和 ERROR 节点数
+    parse_stats: HashMap<PathBuf, FileParseStats>,
+    /// 目录 -> 对其生效的 tsconfig.json/jsconfig.json，用于解析路径别名
+    tsconfigs: HashMap<PathBuf, Option<TsConfig>>,
+    /// Java 文件 -> (包名, 文件中的类型声明)，按包名、导入和继承关系解析 `Foo.bar()`、`this.helper()`
+    java_types: HashMap<PathBuf, (String, Vec<TypeDeclaration>)>,
+    /// Java、C++、TypeScript/JavaScript 函数ID -> 形参列表，按实参选择同名函数的重载
+    function_parameters: HashMap<Uuid, Parameters>,
+    /// 匿名函数的函数ID -> 外层函数的函数ID，调用分析时添加外层函数到匿名函数的调用边
+    closure_parents: HashMap<Uuid, Uuid>,
+    /// 项目根目录下 `codegraph-overrides.toml` 中的手工调用解析，调用分析前在图中查找目标
+    call_overrides: Option<ResolvedOverrides>,
 }
 
 impl CodeParser {
@@ -33,37 +85,106 @@ impl CodeParser {
             ts_parser: TreeSitterParser::new(),
             file_index: FileIndex::default(),
             snippet_index: SnippetIndex::default(),
+            options: AnalyzeOptions::default(),
+            source_maps: HashMap::new(),
+            original_locations: HashMap::new(),
+            generated_ranges: HashMap::new(),
+            notebooks: HashMap::new(),
+            notebook_ranges: HashMap::new(),
+            method_owners: HashMap::new(),
+            rust_methods: HashMap::new(),
+            rust_macros: HashMap::new(),
+            module_imports: HashMap::new(),
+            reexport_targets: HashMap::new(),
+            parse_stats: HashMap::new(),
+            tsconfigs: HashMap::new(),
+            java_types: HashMap::new(),
+            function_parameters: HashMap::new(),
+            closure_parents: HashMap::new(),
+            call_overrides: None,
+        }
+    }
+
+    /// 使用指定的分析选项创建解析器
+    pub fn with_options(options: AnalyzeOptions) -> Self {
+        Self {
+            ts_parser: TreeSitterParser::new().with_macro_expansion(options.expand_macros),
+            options,
+            ..Self::new()
         }
     }
 
+    /// 获取分析选项
+    pub fn options(&self) -> &AnalyzeOptions {
+        &self.options
+    }
+
+    /// 本次构建中解析过的文件的解析统计，增量构建跳过的文件不在其中
+    pub fn parse_stats(&self) -> &HashMap<PathBuf, FileParseStats> {
+        &self.parse_stats
+    }
+
     /// 扫描目录下的所有支持的文件
     pub fn scan_directory(&mut self, dir: &Path) -> Vec<PathBuf> {
         let mut files = Vec::new();
-        self._scan_directory_recursive(dir, &mut files);
+        self._scan_directory_recursive(dir, dir, &mut files);
         files
     }
 
-    fn _scan_directory_recursive(&self, dir: &Path, files: &mut Vec<PathBuf>) {
+    fn _scan_directory_recursive(&self, root: &Path, dir: &Path, files: &mut Vec<PathBuf>) {
         if let Ok(entries) = fs::read_dir(dir) {
             for entry in entries.flatten() {
                 let path = entry.path();
+                if self.options.is_excluded(root, &path) {
+                    continue;
+                }
                 if path.is_dir() {
                     // 跳过常见的忽略目录
                     if let Some(name) = path.file_name().and_then(|n| n.to_str()) {
-                        if name.starts_with('.') || name == "target" || name == "node_modules" || name == "__pycache__" {
+                        if name.starts_with('.') || IGNORED_DIRECTORIES.contains(&name) {
                             continue;
                         }
                     }
-                    self._scan_directory_recursive(&path, files);
-                } else if self.is_supported_file(&path) {
+                    self._scan_directory_recursive(root, &path, files);
+                } else if self.is_supported_file(&path) && self._analyzes_language(&path) {
                     files.push(path);
                 }
             }
         }
     }
 
+    /// 目录扫描会收录的文件：未被排除、不在隐藏或忽略的目录中、语言受支持且在分析范围内
+    fn _is_indexed_file(&self, root: &Path, path: &Path) -> bool {
+        let relative = path.strip_prefix(root).unwrap_or(path);
+        let in_ignored_dir = relative.parent().is_some_and(|parent| {
+            parent.components().any(|c| match c {
+                std::path::Component::Normal(name) => {
+                    name.to_str().is_some_and(|name| name.starts_with('.') || IGNORED_DIRECTORIES.contains(&name))
+                }
+                _ => false,
+            })
+        });
+        !in_ignored_dir
+            && !self.options.is_excluded(root, path)
+            && self.is_supported_file(path)
+            && self._analyzes_language(path)
+    }
+
+    /// 文件的语言在分析范围内，且没有被管理员暂停
+    fn _analyzes_language(&self, path: &Path) -> bool {
+        let language = self._detect_language(path);
+        self.options.allows_language(&language) && AnalyzerRegistry::is_enabled(&language)
+    }
+
     /// 判断文件是否为支持的源代码文件
-    fn is_supported_file(&self, path: &Path) -> bool {
+    pub fn is_supported_file(&self, path: &Path) -> bool {
+        if AnalyzerRegistry::for_path(path).is_some() {
+            return true;
+        }
+        // 没有扩展名的Ruby构建文件
+        if matches!(path.file_name().and_then(|n| n.to_str()), Some("Gemfile" | "Rakefile")) {
+            return true;
+        }
         if let Some(ext) = path.extension().and_then(|e| e.to_str()) {
             matches!(ext.to_lowercase().as_str(),
                 "cpp" | "cc" | "cxx" | "c++" | "c" | "h" | "hpp" | "hxx" | "hh" |
@@ -74,14 +195,263 @@ impl CodeParser {
                 "rs" |
                 "ts" |
                 "tsx" |
-                "go"
+                "go" |
+                "kt" | "kts" |
+                "rb" | "rake" |
+                "swift" |
+                "scala" | "sc" |
+                "m" | "mm" |
+                "lua" |
+                "sh" | "bash" |
+                "sql" |
+                "proto" |
+                "vue" | "svelte" |
+                "html" | "htm" |
+                "ipynb"
             )
         } else {
             false
         }
     }
 
-    /// 增量更新单个文件
+    /// 由目录中 Java、TypeScript 和 C++ 文件的类型声明构建实体图：类、接口和它们的方法，
+    /// 类到方法的 `Contains` 边，类到父类型的 `Inherits`/`Implements` 边（父类型是接口时为 `Implements`），
+    /// 以及方法到各个父类型中同名方法的 `Overrides` 边。
+    ///
+    /// 方法节点尽量取 `call_graph` 中的同一个函数（ID 相同），调用图中没有的方法新建节点，
+    /// 只有声明的方法种类为 [`FunctionKind::Abstract`]。父类型按名字在同一语言的类型中查找
+    pub fn build_entity_graph(&mut self, dir: &Path, call_graph: &PetCodeGraph) -> EntityGraph {
+        let mut entity_graph = EntityGraph::new();
+        let mut supertypes: Vec<(Uuid, String, Vec<String>)> = Vec::new();
+        let mut decorated: Vec<(PathBuf, String, Vec<Decoration>)> = Vec::new();
+        for file_path in self.scan_directory(dir) {
+            let language = self._detect_language(&file_path);
+            if !matches!(language.as_str(), "java" | "typescript" | "cpp" | "python") {
+                continue;
+            }
+            let Ok(code) = fs::read_to_string(&file_path) else { continue };
+            let decorations = scan_decorators(&language, &code);
+            if !decorations.is_empty() {
+                decorated.push((file_path.clone(), language.clone(), decorations));
+            }
+            let types = scan_types(&language, &code);
+            if types.is_empty() {
+                continue;
+            }
+            let namespace = self._extract_namespace_from_content(&code, &file_path);
+            let functions = call_graph.find_functions_by_file(&file_path);
+
+            for declaration in types {
+                // C++ 的同名类可能出现在多个文件中，实体图里的 ID 不能重复
+                let class_id = Some(ClassInfo::stable_id(&language, &file_path, &namespace, &declaration.name))
+                    .filter(|id| entity_graph.get_entity_by_id(id).is_none())
+                    .unwrap_or_else(Uuid::new_v4);
+                let mut methods = Vec::new();
+                for method in &declaration.methods {
+                    let existing = functions.iter().find(|f| {
+                        f.name == method.name
+                            && f.line_start <= method.line_end
+                            && method.line_start <= f.line_end
+                            && entity_graph.get_node_index(&f.id).is_none()
+                    });
+                    let mut function = existing.map(|f| (*f).clone()).unwrap_or_else(|| FunctionInfo {
+                        id: Uuid::new_v4(),
+                        name: method.name.clone(),
+                        file_path: file_path.clone(),
+                        line_start: method.line_start,
+                        line_end: method.line_end,
+                        namespace: namespace.clone(),
+                        language: language.clone(),
+                        signature: Some(method.name.clone()),
+                        cell: None,
+                        body_hash: None,
+                        partial: false,
+                        return_type: None,
+                        parameters: Vec::new(),
+                        parent_class_id: Some(class_id),
+                        kind: FunctionKind::Function,
+                    });
+                    if method.is_abstract {
+                        function.kind = FunctionKind::Abstract;
+                    }
+                    methods.push(function);
+                }
+
+                let is_interface = declaration.class_type == ClassType::Interface;
+                let class = ClassInfo {
+                    id: class_id,
+                    name: declaration.name.clone(),
+                    file_path: file_path.clone(),
+                    line_start: declaration.line_start,
+                    line_end: declaration.line_end,
+                    namespace: namespace.clone(),
+                    language: language.clone(),
+                    class_type: declaration.class_type.clone(),
+                    parent_class: if is_interface { None } else { declaration.extends.first().cloned() },
+                    implemented_interfaces: if is_interface { declaration.extends.clone() } else { declaration.implements.clone() },
+                    member_functions: methods.iter().map(|f| f.id).collect(),
+                    member_variables: vec![],
+                };
+                supertypes.push((class_id, language.clone(), declaration.supertypes().cloned().collect()));
+                entity_graph.add_class(class);
+                for function in methods {
+                    let function_id = function.id;
+                    entity_graph.add_function(function);
+                    let _ = entity_graph.add_edge(EntityEdge {
+                        source: class_id,
+                        target: function_id,
+                        edge_type: EntityEdgeType::Contains,
+                        metadata: None,
+                    });
+                }
+            }
+        }
+
+        // 继承和实现
+        for (class_id, language, names) in &supertypes {
+            let is_interface = entity_graph.get_class_by_id(class_id).is_some_and(|c| c.class_type == ClassType::Interface);
+            let mut edges = Vec::new();
+            for name in names {
+                for parent in entity_graph.find_classes_by_name(name) {
+                    if parent.id == *class_id || parent.language != *language {
+                        continue;
+                    }
+                    let edge_type = if !is_interface && parent.class_type == ClassType::Interface {
+                        EntityEdgeType::Implements
+                    } else {
+                        EntityEdgeType::Inherits
+                    };
+                    edges.push(EntityEdge { source: *class_id, target: parent.id, edge_type, metadata: None });
+                }
+            }
+            for edge in edges {
+                let _ = entity_graph.add_edge(edge);
+            }
+        }
+
+        // 方法覆盖父类型（直接或间接）中的同名方法
+        let mut overrides = Vec::new();
+        for (class_id, _, _) in &supertypes {
+            let ancestors = entity_graph.get_supertypes(class_id);
+            for method in entity_graph.get_class_members(class_id) {
+                for ancestor in &ancestors {
+                    for overridden in entity_graph.get_class_members(&ancestor.id).into_iter().filter(|f| f.name == method.name) {
+                        overrides.push(EntityEdge {
+                            source: method.id,
+                            target: overridden.id,
+                            edge_type: EntityEdgeType::Overrides,
+                            metadata: None,
+                        });
+                    }
+                }
+            }
+        }
+        for edge in overrides {
+            let _ = entity_graph.add_edge(edge);
+        }
+
+        // 装饰器（注解）
+        let mut external_decorators: HashMap<(String, String), Uuid> = HashMap::new();
+        for (file_path, language, decorations) in decorated {
+            let functions = call_graph.find_functions_by_file(&file_path);
+            for decoration in decorations {
+                let source = match decoration.target {
+                    DecoratedKind::Function => {
+                        // Java、TypeScript 方法可能已作为类成员加入实体图
+                        let member = decoration.find_function(entity_graph.get_all_functions().into_iter().filter(|f| f.file_path == file_path)).map(|f| f.id);
+                        member.or_else(|| {
+                            let function = decoration.find_function(functions.iter().copied())?;
+                            if entity_graph.get_node_index(&function.id).is_none() {
+                                entity_graph.add_function(function.clone());
+                            }
+                            Some(function.id)
+                        })
+                    }
+                    DecoratedKind::Class => entity_graph
+                        .find_classes_by_file(&file_path)
+                        .into_iter()
+                        .find(|c| c.name == decoration.target_name && c.line_start <= decoration.target_line && decoration.target_line <= c.line_end)
+                        .map(|c| c.id),
+                };
+                let Some(source) = source else { continue };
+                let entry_point = decoration.entry_point_kind(&language);
+                let target = self._decorator_entity(&decoration, &language, &file_path, entry_point.is_some(), call_graph, &mut entity_graph, &mut external_decorators);
+                let mut metadata = serde_json::json!({
+                    "decorator": decoration.name,
+                    "arguments": decoration.arguments,
+                    "line": decoration.line,
+                });
+                if let Some(kind) = entry_point {
+                    metadata["entry_point"] = kind.into();
+                }
+                let _ = entity_graph.add_edge(EntityEdge { source, target, edge_type: EntityEdgeType::DecoratedBy, metadata: Some(metadata) });
+            }
+        }
+
+        entity_graph.update_stats();
+        entity_graph
+    }
+
+    /// 装饰器对应的实体：项目中定义的同名装饰器函数或注解类型，否则为每种外部装饰器新建一个
+    /// 命名空间为 `decorator` 的函数节点。框架入口装饰器不按名字查找项目函数，`@app.get` 不是项目中的 `get`
+    #[allow(clippy::too_many_arguments)]
+    fn _decorator_entity(
+        &self,
+        decoration: &Decoration,
+        language: &str,
+        file_path: &Path,
+        framework: bool,
+        call_graph: &PetCodeGraph,
+        entity_graph: &mut EntityGraph,
+        external: &mut HashMap<(String, String), Uuid>,
+    ) -> Uuid {
+        let short = decoration.name.rsplit('.').next().unwrap_or(&decoration.name);
+        let defined = (!framework)
+            .then(|| {
+                call_graph
+                    .find_functions_by_name(short)
+                    .into_iter()
+                    .filter(|f| f.language == language && !f.is_unresolved() && f.namespace != "framework")
+                    .min_by_key(|f| f.file_path != file_path)
+            })
+            .flatten();
+        if let Some(function) = defined {
+            if entity_graph.get_node_index(&function.id).is_none() {
+                entity_graph.add_function(function.clone());
+            }
+            return function.id;
+        }
+        if let Some(class) = entity_graph.find_classes_by_name(short).into_iter().find(|c| c.language == language) {
+            return class.id;
+        }
+        *external.entry((language.to_string(), decoration.name.clone())).or_insert_with(|| {
+            let function = FunctionInfo {
+                id: Uuid::new_v4(),
+                name: decoration.name.clone(),
+                file_path: file_path.to_path_buf(),
+                line_start: decoration.line,
+                line_end: decoration.line,
+                namespace: "decorator".to_string(),
+                language: language.to_string(),
+                signature: Some(format!("@{}", decoration.name)),
+                cell: None,
+                body_hash: None,
+                partial: false,
+                return_type: None,
+                parameters: Vec::new(),
+                parent_class_id: None,
+                kind: FunctionKind::Function,
+            };
+            let id = function.id;
+            entity_graph.add_function(function);
+            id
+        })
+    }
+
+    /// 增量更新单个文件。
+    ///
+    /// 调用图按 [`Self::apply_changed_files`] 更新：其余文件调入该文件的调用按名称重新连接，
+    /// 而不是随旧函数一起删除
     pub fn refresh_file(
         &mut self,
         file_path: &PathBuf,
@@ -89,41 +459,41 @@ impl CodeParser {
         call_graph: &mut PetCodeGraph,
     ) -> Result<(), String> {
         info!("Refreshing file: {}", file_path.display());
+        let root = file_path.parent().unwrap_or(Path::new("")).to_path_buf();
 
         // 检查文件是否存在
         if !file_path.exists() {
             // 文件被删除，清理相关索引
-            self._remove_file_entities(file_path, entity_graph, call_graph);
-            return Ok(());
+            self._remove_file_entities(file_path, entity_graph);
+            self.apply_changed_files(call_graph, &root, std::slice::from_ref(file_path))?;
+            call_graph.update_stats();
+            return call_graph.check_invariants(self.options.strict, "removing a file");
         }
 
-        // 解析文件，提取新的实体和函数
-        let (classes, functions) = self._extract_entities_from_file(file_path)?;
+        // 解析文件，提取新的类
+        let (classes, _) = self._extract_entities_from_file(file_path)?;
 
-        // 移除旧的实体和函数
-        self._remove_file_entities(file_path, entity_graph, call_graph);
+        // 移除旧的实体，重新解析文件中的函数和调用
+        self._remove_file_entities(file_path, entity_graph);
+        self.apply_changed_files(call_graph, &root, std::slice::from_ref(file_path))?;
 
         // 添加到图中
         let class_ids: Vec<Uuid> = classes.iter().map(|c| c.id).collect();
-        let function_ids: Vec<Uuid> = functions.iter().map(|f| f.id).collect();
+        let function_ids: Vec<Uuid> = call_graph.find_functions_by_file(file_path).iter().map(|f| f.id).collect();
 
         for class in classes {
             entity_graph.add_class(class);
         }
 
-        for function in functions {
-            call_graph.add_function(function);
-        }
-
-        // 分析调用关系
-        self._analyze_file_calls(file_path, &function_ids, call_graph)?;
-
         // 更新索引
         self.file_index.rebuild_for_file(file_path, class_ids.clone(), function_ids.clone());
 
         // 更新代码片段索引
         self._update_snippet_index(file_path, &class_ids, &function_ids, entity_graph)?;
 
+        call_graph.update_stats();
+        call_graph.check_invariants(self.options.strict, "refreshing a file")?;
+
         info!("Successfully refreshed file: {}", file_path.display());
         Ok(())
     }
@@ -139,6 +509,9 @@ impl CodeParser {
 
         let language = self._detect_language(file_path);
         let namespace = self._extract_namespace(file_path);
+        let file_content = fs::read_to_string(file_path).unwrap_or_default();
+        let owners = impl_owners(&symbols);
+        let closures = Closures::new(&symbols, file_path);
 
         for symbol in symbols {
             let symbol_guard = symbol.read();
@@ -146,21 +519,30 @@ impl CodeParser {
 
             match symbol_ref.symbol_type() {
                 crate::codegraph::treesitter::structs::SymbolType::FunctionDeclaration => {
+                    let name = closures.name(symbol_ref.guid()).unwrap_or(symbol_ref.name()).to_string();
                     let function = FunctionInfo {
                         id: Uuid::new_v4(),
-                        name: symbol_ref.name().to_string(),
+                        name: name.clone(),
                         file_path: file_path.clone(),
                         line_start: symbol_ref.full_range().start_point.row + 1,
                         line_end: symbol_ref.full_range().end_point.row + 1,
                         namespace: namespace.clone(),
                         language: language.clone(),
-                        signature: Some(symbol_ref.name().to_string()),
+                        signature: Some(name),
+                        cell: None,
+                        body_hash: None,
+                        partial: false,
+                        return_type: None,
+                        parameters: Vec::new(),
+                        parent_class_id: method_owner(&owners, symbol_ref, &language, &file_content)
+                            .map(|owner| ClassInfo::stable_id(&language, file_path, &namespace, &owner)),
+                        kind: FunctionKind::Function,
                     };
                     functions.push(function);
                 },
                 crate::codegraph::treesitter::structs::SymbolType::StructDeclaration => {
                     let class = ClassInfo {
-                        id: Uuid::new_v4(),
+                        id: ClassInfo::stable_id(&language, file_path, &namespace, symbol_ref.name()),
                         name: symbol_ref.name().to_string(),
                         file_path: file_path.clone(),
                         line_start: symbol_ref.full_range().start_point.row + 1,
@@ -179,68 +561,11 @@ impl CodeParser {
             }
         }
 
-        Ok((classes, functions))
-    }
-
-    /// 分析文件的函数调用
-    fn _analyze_file_calls(
-        &self,
-        file_path: &PathBuf,
-        function_ids: &[Uuid],
-        call_graph: &mut PetCodeGraph,
-    ) -> Result<(), String> {
-        let symbols = self.ts_parser.parse_file(file_path)
-            .map_err(|e| format!("Failed to parse file for call analysis: {:?}", e))?;
-
-        for symbol in symbols {
-            let symbol_guard = symbol.read();
-            let symbol_ref = symbol_guard.as_ref();
-
-            if symbol_ref.symbol_type() == crate::codegraph::treesitter::structs::SymbolType::FunctionCall {
-                let call_name = symbol_ref.name();
-                let call_line = symbol_ref.full_range().start_point.row + 1;
-
-                // 查找调用者函数
-                if let Some(caller_id) = self._find_caller_function(file_path, call_line, function_ids) {
-                    // 查找被调用函数（先在本文件，再全局）
-                    if let Some(callee_id) = self._find_callee_function(call_name, function_ids, call_graph) {
-                        let relation = CallRelation {
-                            caller_id: *caller_id,
-                            callee_id,
-                            caller_name: "".to_string(), // 会在add_call_relation中填充
-                            callee_name: call_name.to_string(),
-                            caller_file: file_path.clone(),
-                            callee_file: file_path.clone(),
-                            line_number: call_line,
-                            is_resolved: true,
-                        };
-                        if let Err(e) = call_graph.add_call_relation(relation) {
-                            warn!("Failed to add call relation: {}", e);
-                        }
-                    } else {
-                        // 未解析的调用
-                        self._handle_unresolved_call(caller_id, call_name, file_path, call_line, call_graph);
-                    }
-                }
-            }
+        for class in &mut classes {
+            class.member_functions = functions.iter().filter(|f| f.parent_class_id == Some(class.id)).map(|f| f.id).collect();
         }
 
-        Ok(())
-    }
-
-    /// 查找调用者函数
-    fn _find_caller_function<'a>(&self, file_path: &PathBuf, call_line: usize, function_ids: &'a [Uuid]) -> Option<&'a Uuid> {
-        // 根据行号范围查找包含调用行的函数
-        for function_id in function_ids {
-            if let Some(function) = self._get_function_by_id(function_id) {
-                if function.file_path == *file_path && 
-                   call_line >= function.line_start && 
-                   call_line <= function.line_end {
-                    return Some(function_id);
-                }
-            }
-        }
-        None
+        Ok((classes, functions))
     }
 
     /// 根据ID获取函数信息
@@ -255,75 +580,16 @@ impl CodeParser {
         None
     }
 
-
-
-    /// 查找被调用函数
-    fn _find_callee_function(&self, call_name: &str, function_ids: &[Uuid], call_graph: &PetCodeGraph) -> Option<Uuid> {
-        // 先在本文件查找
-        for &func_id in function_ids {
-            if let Some(func) = call_graph.get_function_by_id(&func_id) {
-                if func.name == call_name {
-                    return Some(func_id);
-                }
-            }
-        }
-
-        // 再全局查找
-        let global_functions = call_graph.find_functions_by_name(call_name);
-        global_functions.first().map(|f| f.id)
-    }
-
-    /// 处理未解析的调用
-    fn _handle_unresolved_call(
-        &self,
-        caller_id: &Uuid,
-        call_name: &str,
-        file_path: &PathBuf,
-        call_line: usize,
-        call_graph: &mut PetCodeGraph,
-    ) {
-        // 创建未解析的调用关系
-        let relation = CallRelation {
-            caller_id: *caller_id,
-            callee_id: Uuid::new_v4(), // 临时ID
-            caller_name: "".to_string(),
-            callee_name: call_name.to_string(),
-            caller_file: file_path.clone(),
-            callee_file: file_path.clone(),
-            line_number: call_line,
-            is_resolved: false,
-        };
-
-        if let Err(e) = call_graph.add_call_relation(relation) {
-            warn!("Failed to add unresolved call relation: {}", e);
-        }
-    }
-
     /// 移除文件相关的所有实体
-    fn _remove_file_entities(
-        &mut self,
-        file_path: &PathBuf,
-        entity_graph: &mut EntityGraph,
-        call_graph: &mut PetCodeGraph,
-    ) {
+    fn _remove_file_entities(&mut self, file_path: &PathBuf, entity_graph: &mut EntityGraph) {
         // 获取文件的所有实体ID
         let entity_ids = self.file_index.get_all_entity_ids(file_path);
-        let function_ids = self.file_index.get_all_function_ids(file_path);
-        let _class_ids = self.file_index.get_all_class_ids(file_path);
 
-        // 从图中移除
+        // 从图中移除；调用图中的函数由 apply_changed_files 删除
         for entity_id in entity_ids {
             entity_graph.remove_entity(&entity_id);
         }
 
-        for function_id in function_ids {
-            if let Some(node_index) = call_graph.get_node_index(&function_id) {
-                call_graph.graph.remove_node(node_index);
-                call_graph.function_to_node.remove(&function_id);
-                call_graph.node_to_function.remove(&node_index);
-            }
-        }
-
         // 清理索引
         self.file_index.remove_file(file_path);
         self.snippet_index.clear_file_cache(file_path);
@@ -376,15 +642,33 @@ impl CodeParser {
 
     /// 检测文件语言
     fn _detect_language(&self, file_path: &Path) -> String {
+        if let Some(language) = AnalyzerRegistry::language_for_path(file_path) {
+            return language;
+        }
+        if matches!(file_path.file_name().and_then(|n| n.to_str()), Some("Gemfile" | "Rakefile")) {
+            return "ruby".to_string();
+        }
         if let Some(ext) = file_path.extension().and_then(|e| e.to_str()) {
             match ext.to_lowercase().as_str() {
                 "rs" => "rust".to_string(),
-                "py" | "py3" | "pyx" => "python".to_string(),
+                "py" | "py3" | "pyx" | "ipynb" => "python".to_string(),
                 "js" | "jsx" => "javascript".to_string(),
                 "ts" | "tsx" => "typescript".to_string(),
                 "java" => "java".to_string(),
                 "cpp" | "cc" | "cxx" | "c++" | "c" | "h" | "hpp" | "hxx" | "hh" => "cpp".to_string(),
                 "go" => "go".to_string(),
+                "kt" | "kts" => "kotlin".to_string(),
+                "rb" | "rake" => "ruby".to_string(),
+                "swift" => "swift".to_string(),
+                "scala" | "sc" => "scala".to_string(),
+                "m" | "mm" => "objc".to_string(),
+                "lua" => "lua".to_string(),
+                "sh" | "bash" => "bash".to_string(),
+                "sql" => "sql".to_string(),
+                "proto" => "proto".to_string(),
+                "vue" => "vue".to_string(),
+                "svelte" => "svelte".to_string(),
+                "html" | "htm" => "html".to_string(),
                 _ => "unknown".to_string(),
             }
         } else {
@@ -401,7 +685,7 @@ impl CodeParser {
         "global".to_string()
     }
 
-    /// 解析单个文件（完整实现，支持多语言）
+    /// 解析单个文件（完整实现，支持多语言），失败原因记录在解析统计中
     pub fn parse_file(&mut self, file_path: &PathBuf) -> Result<(), String> {
         info!("Parsing file: {}", file_path.display());
         
@@ -410,23 +694,70 @@ impl CodeParser {
             return Err(format!("File does not exist: {}", file_path.display()));
         }
 
+        let started = std::time::Instant::now();
+        let result = self._parse_file(file_path);
+        if let Err(e) = &result {
+            let stats = FileParseStats {
+                language: self._detect_language(file_path),
+                duration_ms: started.elapsed().as_secs_f64() * 1000.0,
+                node_count: None,
+                error_nodes: None,
+                parse_error: Some(e.clone()),
+            };
+            self.parse_stats.insert(file_path.clone(), stats);
+        }
+        result
+    }
+
+    fn _parse_file(&mut self, file_path: &PathBuf) -> Result<(), String> {
         // 使用TreeSitter解析器解析文件
-        let symbols = self.ts_parser.parse_file(file_path)
+        let started = std::time::Instant::now();
+        let (symbols, tree_stats) = self.ts_parser.parse_file_with_stats(file_path)
             .map_err(|e| format!("Failed to parse file {}: {:?}", file_path.display(), e))?;
         info!("TreeSitter parsing completed, found {} symbols", symbols.len());
-        
-
+        let stats = FileParseStats {
+            language: self._detect_language(file_path),
+            duration_ms: started.elapsed().as_secs_f64() * 1000.0,
+            node_count: tree_stats.as_ref().map(|stats| stats.node_count),
+            error_nodes: tree_stats.as_ref().map(|stats| stats.error_nodes),
+            parse_error: None,
+        };
+        if stats.is_suspect() {
+            warn!(
+                "{} has {} ERROR nodes out of {}, extracted symbols may be incomplete",
+                file_path.display(),
+                stats.error_nodes.unwrap_or_default(),
+                stats.node_count.unwrap_or_default()
+            );
+        }
+        self.parse_stats.insert(file_path.clone(), stats);
+        let error_lines = tree_stats.map(|stats| stats.error_lines).unwrap_or_default();
 
         // 读取文件内容用于代码片段提取
         let file_content = fs::read_to_string(file_path)
             .map_err(|e| format!("Failed to read file {}: {}", file_path.display(), e))?;
+        // notebook 按拼接后的脚本分析，与解析器看到的内容一致
+        let notebook = if file_path.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("ipynb")) {
+            Some(NotebookCells::parse(&file_content).map_err(|e| format!("Failed to read notebook {}: {}", file_path.display(), e))?)
+        } else {
+            None
+        };
+        let file_content = notebook.as_ref().map_or(file_content, NotebookCells::script);
 
         let language = self._detect_language(file_path);
         let namespace = self._extract_namespace_from_content(&file_content, file_path);
+        let source_map = match language.as_str() {
+            "javascript" | "typescript" => SourceMap::for_generated_file(file_path),
+            _ => None,
+        };
         
         let mut functions = Vec::new();
         let mut classes = Vec::new();
         let mut function_calls = Vec::new();
+        let owners = impl_owners(&symbols);
+        let closures = Closures::new(&symbols, file_path);
+        // 函数符号的 guid -> 函数ID
+        let mut function_ids = HashMap::new();
 
         // 分析每个AST符号
         for symbol in symbols {
@@ -439,7 +770,32 @@ impl CodeParser {
             match symbol_ref.symbol_type() {
                 crate::codegraph::treesitter::structs::SymbolType::FunctionDeclaration => {
                     // 提取函数信息
-                    let function = self._extract_function_info(symbol_ref, file_path, &namespace, &language);
+                    let mut function = self._extract_function_info(symbol_ref, closures.name(symbol_ref.guid()), file_path, &namespace, &language, &file_content);
+                    function_ids.insert(*symbol_ref.guid(), function.id);
+                    // 语法错误区域之外的函数照常提取，与错误区域重叠的标记为不完整
+                    function.partial = symbol_ref.is_error()
+                        || error_lines.iter().any(|(start, end)| *start <= function.line_end && function.line_start <= *end);
+                    let range = symbol_ref.full_range();
+                    function.body_hash = file_content.get(range.start_byte..range.end_byte).and_then(|body| body_hash(&language, body));
+                    if let Some(map) = &source_map {
+                        self._record_original_location(map, &file_content, symbol_ref, &function);
+                    }
+                    if OVERLOADED_LANGUAGES.contains(&language.as_str()) {
+                        let header_end = symbol_ref.declaration_range().end_byte.max(range.start_byte);
+                        let header = file_content
+                            .get(range.start_byte..header_end)
+                            .filter(|header| !header.is_empty())
+                            .or_else(|| file_content.get(range.start_byte..range.end_byte));
+                        if let Some(parameters) = header.and_then(|header| Parameters::parse(&language, header, &function.name)) {
+                            self.function_parameters.insert(function.id, parameters);
+                        }
+                    }
+                    let owner = method_owner(&owners, symbol_ref, &language, &file_content);
+                    function.parent_class_id = owner.as_deref().map(|owner| ClassInfo::stable_id(&language, file_path, &namespace, owner));
+                    if let Some(owner) = owner.filter(|_| language == "rust") {
+                        self.method_owners.insert(function.id, owner.clone());
+                        self.rust_methods.entry((owner, function.name.clone())).or_insert_with(|| function.clone());
+                    }
                     functions.push(function);
                 },
                 crate::codegraph::treesitter::structs::SymbolType::StructDeclaration => {
@@ -455,6 +811,65 @@ impl CodeParser {
                 _ => {}
             }
         }
+        for class in &mut classes {
+            class.member_functions = functions.iter().filter(|f| f.parent_class_id == Some(class.id)).map(|f| f.id).collect();
+        }
+        for old in self.file_functions.get(file_path).into_iter().flatten() {
+            self.closure_parents.remove(&old.id);
+        }
+        for (guid, function_id) in &function_ids {
+            if let Some(parent_id) = closures.parent(guid).and_then(|parent| function_ids.get(&parent)) {
+                self.closure_parents.insert(*function_id, *parent_id);
+            }
+        }
+
+        // Rust 声明宏作为 `name!` 函数，宏调用生成的函数在全部文件解析完之后展开
+        if language == "rust" {
+            let macros = RustMacros::scan(&file_content);
+            for definition in &macros.definitions {
+                functions.push(FunctionInfo {
+                    id: Uuid::new_v4(),
+                    name: format!("{}!", definition.name),
+                    file_path: file_path.clone(),
+                    line_start: definition.line_start,
+                    line_end: definition.line_end,
+                    namespace: namespace.clone(),
+                    language: language.clone(),
+                    signature: Some(format!("macro_rules! {}", definition.name)),
+                    cell: None,
+                    body_hash: None,
+                    partial: false,
+                    return_type: None,
+                    parameters: Vec::new(),
+                    parent_class_id: None,
+                    kind: FunctionKind::Macro,
+                });
+            }
+            if macros.is_empty() {
+                self.rust_macros.remove(file_path);
+            } else {
+                self.rust_macros.insert(file_path.clone(), (namespace.clone(), macros));
+            }
+        }
+
+        // 导入和再导出语句，再导出节点在全部文件解析完之后创建
+        let mut module_imports = ModuleImports::scan(&language, file_path, &file_content);
+        if matches!(language.as_str(), "javascript" | "typescript" | "vue" | "svelte") {
+            if let Some(config) = self._tsconfig_for(file_path) {
+                module_imports.resolve_modules(|module| config.resolve(module));
+            }
+        }
+        if module_imports.is_empty() {
+            self.module_imports.remove(file_path);
+        } else {
+            self.module_imports.insert(file_path.clone(), (namespace.clone(), module_imports));
+        }
+        if language == "java" {
+            self.java_types.insert(file_path.clone(), (namespace.clone(), scan_types(&language, &file_content)));
+        }
+
+        // notebook 中的行号换算为所在代码单元内的行号，代码片段取自单元源码
+        let cell_snippets = notebook.as_ref().map(|cells| self._locate_notebook_cells(cells, &mut functions, &mut classes));
 
         // 注册函数到全局注册表
         for function in &functions {
@@ -463,9 +878,19 @@ impl CodeParser {
         
         // 保存文件函数映射
         self.file_functions.insert(file_path.clone(), functions.clone());
+        if let Some(map) = source_map {
+            self.source_maps.insert(file_path.clone(), map);
+        }
 
         // 更新代码片段索引
-        self._update_snippet_index_with_content(file_path, &functions, &classes, &file_content)?;
+        if let Some(cell_snippets) = cell_snippets {
+            for (source, cell_functions, cell_classes) in cell_snippets {
+                self._update_snippet_index_with_content(file_path, &cell_functions, &cell_classes, &source)?;
+            }
+            self.notebooks.insert(file_path.clone(), notebook.unwrap_or_default());
+        } else {
+            self._update_snippet_index_with_content(file_path, &functions, &classes, &file_content)?;
+        }
 
         info!("Successfully parsed file: {} ({} functions, {} classes, {} calls)", 
               file_path.display(), functions.len(), classes.len(), function_calls.len());
@@ -477,16 +902,23 @@ impl CodeParser {
     fn _extract_function_info(
         &self,
         symbol: &dyn crate::codegraph::treesitter::ast_instance_structs::AstSymbolInstance,
+        closure_name: Option<&str>,
         file_path: &PathBuf,
         namespace: &str,
         language: &str,
+        file_content: &str,
     ) -> FunctionInfo {
-        let name = symbol.name().to_string();
+        // 匿名函数使用 `外层::<lambda@行号>` 作为名字
+        let name = closure_name.unwrap_or(symbol.name()).to_string();
         let line_start = symbol.full_range().start_point.row + 1;
         let line_end = symbol.full_range().end_point.row + 1;
         
-        // 尝试提取函数签名
-        let signature = self._extract_function_signature(symbol);
+        // 从声明源码提取签名、返回类型和形参
+        let range = symbol.full_range();
+        let signature = file_content
+            .get(range.start_byte..range.end_byte)
+            .and_then(|source| signatures::extract(language, source, &name))
+            .unwrap_or_else(|| Signature { text: name.clone(), ..Default::default() });
 
         FunctionInfo {
             id: Uuid::new_v4(),
@@ -496,8 +928,95 @@ impl CodeParser {
             line_end,
             namespace: namespace.to_string(),
             language: language.to_string(),
-            signature,
+            signature: Some(signature.text),
+            cell: None,
+            body_hash: None,
+            partial: false,
+            return_type: signature.return_type,
+            parameters: signature.parameters,
+            parent_class_id: None,
+            kind: FunctionKind::Function,
+        }
+    }
+
+    /// 把 notebook 中函数和类的行号换算为代码单元内的行号，并按单元分组供代码片段索引使用。
+    /// 函数在拼接脚本中的范围保留下来，调用分析仍按脚本行号查找调用者。
+    fn _locate_notebook_cells(
+        &mut self,
+        cells: &NotebookCells,
+        functions: &mut [FunctionInfo],
+        classes: &mut [ClassInfo],
+    ) -> Vec<(String, Vec<FunctionInfo>, Vec<ClassInfo>)> {
+        let mut by_cell: std::collections::BTreeMap<usize, (Vec<FunctionInfo>, Vec<ClassInfo>)> = Default::default();
+        for function in functions.iter_mut() {
+            self.notebook_ranges.insert(function.id, (function.line_start, function.line_end));
+            if let Some((cell, line)) = cells.locate(function.line_start) {
+                function.line_end = line + (function.line_end - function.line_start);
+                function.line_start = line;
+                function.cell = Some(cell);
+                by_cell.entry(cell).or_default().0.push(function.clone());
+            }
         }
+        for class in classes.iter_mut() {
+            if let Some((cell, line)) = cells.locate(class.line_start) {
+                class.line_end = line + (class.line_end - class.line_start);
+                class.line_start = line;
+                by_cell.entry(cell).or_default().1.push(class.clone());
+            }
+        }
+        by_cell
+            .into_iter()
+            .filter_map(|(cell, (functions, classes))| Some((cells.cell_source(cell)?.to_string(), functions, classes)))
+            .collect()
+    }
+
+    /// notebook 拼接脚本中的行号换算为所在代码单元内的行号，其他文件原样返回
+    fn _notebook_line(&self, file_path: &Path, line: usize) -> usize {
+        self.notebooks.get(file_path).and_then(|cells| cells.locate(line)).map_or(line, |(_, line)| line)
+    }
+
+    /// 通过 source map 记录构建产物中函数在原始源码中的位置
+    fn _record_original_location(
+        &mut self,
+        map: &SourceMap,
+        code: &str,
+        symbol: &dyn crate::codegraph::treesitter::ast_instance_structs::AstSymbolInstance,
+        function: &FunctionInfo,
+    ) {
+        let range = symbol.full_range();
+        self.generated_ranges.insert(
+            function.id,
+            (
+                (range.start_point.row, range.start_point.column),
+                (range.end_point.row, range.end_point.column),
+            ),
+        );
+        let start_column = utf16_column(code, range.start_byte, range.start_point.column);
+        let Some(start) = map.lookup(range.start_point.row + 1, start_column) else {
+            return;
+        };
+        // 结束位置映射到其他文件或更靠前的行时，只保留起始行
+        let end_column = utf16_column(code, range.end_byte, range.end_point.column);
+        let line_end = map
+            .lookup(range.end_point.row + 1, end_column.saturating_sub(1))
+            .filter(|end| end.source == start.source && end.line >= start.line)
+            .map_or(start.line, |end| end.line);
+        self.original_locations.insert(function.id, (start.source, start.line, line_end));
+    }
+
+    /// 把构建产物中的函数及其调用行号改写为 source map 还原的原始位置
+    fn _apply_original_locations(&mut self, code_graph: &mut PetCodeGraph) {
+        self.generated_ranges.clear();
+        if self.original_locations.is_empty() {
+            return;
+        }
+        let mut relocated = 0;
+        for (function_id, (file_path, line_start, line_end)) in self.original_locations.drain() {
+            if code_graph.relocate_function(&function_id, file_path, line_start, line_end) {
+                relocated += 1;
+            }
+        }
+        info!("Mapped {} functions from generated files to their original sources", relocated);
     }
 
     /// 从AST符号提取类信息
@@ -515,13 +1034,13 @@ impl CodeParser {
 
         // 根据语言确定类类型
         let class_type = match language {
-            "rust" => ClassType::Struct,
-            "cpp" | "java" | "typescript" | "javascript" => ClassType::Class,
+            "rust" | "go" => ClassType::Struct,
+            "cpp" | "objc" | "java" | "typescript" | "javascript" => ClassType::Class,
             _ => ClassType::Class,
         };
 
         ClassInfo {
-            id: Uuid::new_v4(),
+            id: ClassInfo::stable_id(language, file_path, namespace, &name),
             name,
             file_path: file_path.clone(),
             line_start,
@@ -549,24 +1068,6 @@ impl CodeParser {
         (call_name, line_number)
     }
 
-    /// 提取函数签名
-    fn _extract_function_signature(&self, symbol: &dyn crate::codegraph::treesitter::ast_instance_structs::AstSymbolInstance) -> Option<String> {
-        // 使用声明范围来获取函数签名
-        let decl_range = symbol.declaration_range();
-        let full_range = symbol.full_range();
-        
-        // 尝试从声明范围提取签名
-        if decl_range.start_point.row != full_range.start_point.row || 
-           decl_range.end_point.row != full_range.end_point.row {
-            // 如果声明范围与完整范围不同，说明有更精确的签名信息
-            let signature = format!("{}()", symbol.name());
-            return Some(signature);
-        }
-        
-        // 否则返回函数名作为签名
-        Some(symbol.name().to_string())
-    }
-
     fn _extract_namespace_from_content(&self, content: &str, file_path: &PathBuf) -> String {
         let language = self._detect_language(file_path);
         
@@ -604,8 +1105,8 @@ impl CodeParser {
                 }
                 "default".to_string()
             },
-            "cpp" => {
-                // 查找namespace声明
+            "cpp" | "objc" => {
+                // 查找namespace声明（Objective-C++ 中可能出现）
                 for line in content.lines() {
                     if line.trim().starts_with("namespace ") {
                         if let Some(name) = line.trim().split_whitespace().nth(1) {
@@ -615,6 +1116,37 @@ impl CodeParser {
                 }
                 "global".to_string()
             },
+            "go" | "kotlin" | "scala" => {
+                // 查找package声明
+                for line in content.lines() {
+                    if line.trim().starts_with("package ") {
+                        if let Some(package) = line.split_whitespace().nth(1) {
+                            return package.trim_end_matches(';').to_string();
+                        }
+                    }
+                }
+                "global".to_string()
+            },
+            "lua" => {
+                // 模块名取自文件名，init.lua 取所在目录名，与 require 的最后一段一致
+                let module = file_path.with_extension("");
+                crate::codegraph::treesitter::parsers::lua::module_tail(&module.to_string_lossy()).to_string()
+            },
+            "bash" => {
+                // 脚本没有模块声明，以文件名（不含扩展名）作为命名空间
+                file_path.file_stem().and_then(|s| s.to_str()).unwrap_or("global").to_string()
+            },
+            "sql" => {
+                // Oracle 包体名，否则取第一个存储过程的 schema（如 dbo）
+                crate::codegraph::treesitter::parsers::sql::file_namespace(content).unwrap_or_else(|| "global".to_string())
+            },
+            "proto" => {
+                crate::codegraph::treesitter::parsers::proto::file_namespace(content).unwrap_or_else(|| "global".to_string())
+            },
+            "vue" | "svelte" | "html" => {
+                // 组件和页面以文件名（不含扩展名）命名
+                file_path.file_stem().and_then(|s| s.to_str()).unwrap_or("global").to_string()
+            },
             _ => "global".to_string(),
         }
     }
@@ -688,8 +1220,11 @@ impl CodeParser {
 
     /// 构建完整的代码图（增量构建）
     pub fn build_code_graph(&mut self, dir: &Path) -> Result<CodeGraph, String> {
-        // 1. 尝试从本地数据库加载现有的图
-        let mut code_graph = self._load_existing_code_graph(dir)?;
+        // 1. 尝试从本地数据库加载现有的图（强制重建、限定了构建范围或调用覆盖文件变化时不复用）
+        let mut file_hashes = self._load_file_hashes(dir)?;
+        let (overrides, overrides_changed) = self._load_call_overrides(dir, &mut file_hashes)?;
+        let incremental = !self.options.force_rebuild && !self.options.is_scoped() && !overrides_changed;
+        let mut code_graph = if incremental { self._load_existing_code_graph(dir)? } else { None };
         let has_existing_data = code_graph.is_some();
         
         if let Some(ref mut existing_graph) = code_graph {
@@ -705,15 +1240,13 @@ impl CodeParser {
         let files = self.scan_directory(dir);
         info!("Found {} files to process", files.len());
         
-        // 3. 加载文件哈希值（如果存在）
-        let mut file_hashes = self._load_file_hashes(dir)?;
-        
-        // 4. 逐个处理文件，检查是否需要重新解析
+        // 3. 逐个处理文件，检查是否需要重新解析
         let mut processed_files = 0;
         let mut skipped_files = 0;
         
         for file_path in files {
-            if self._should_skip_file(&file_path, &mut file_hashes)? {
+            let unchanged = self._should_skip_file(&file_path, &mut file_hashes)?;
+            if incremental && unchanged {
                 skipped_files += 1;
                 continue;
             }
@@ -726,8 +1259,10 @@ impl CodeParser {
         }
         
         info!("File processing completed: {} processed, {} skipped", processed_files, skipped_files);
+        self._expand_rust_macros();
+        self._expand_reexports();
         
-        // 5. 如果这是增量构建，需要合并新解析的函数
+        // 4. 如果这是增量构建，需要合并新解析的函数
         if has_existing_data {
             if !self.file_functions.is_empty() {
                 self._merge_new_functions_to_code_graph(&mut code_graph);
@@ -742,13 +1277,14 @@ impl CodeParser {
             }
         }
         
-        // 6. 分析调用关系
+        // 5. 分析调用关系，调用覆盖的目标可能位于未变化的文件中
+        self.call_overrides = overrides.map(|overrides| overrides.resolve(code_graph.functions.values()));
         self._analyze_call_relations(&mut code_graph);
         
-        // 7. 更新统计信息
+        // 6. 更新统计信息
         code_graph.update_stats();
         
-        // 8. 保存新的文件哈希值
+        // 7. 保存新的文件哈希值
         self._save_file_hashes(dir, &file_hashes)?;
         
         Ok(code_graph)
@@ -756,8 +1292,11 @@ impl CodeParser {
 
     /// 构建基于petgraph的代码图（增量构建）
     pub fn build_petgraph_code_graph(&mut self, dir: &Path) -> Result<PetCodeGraph, String> {
-        // 1. 尝试从本地数据库加载现有的图
-        let mut code_graph = self._load_existing_graph(dir)?;
+        // 1. 尝试从本地数据库加载现有的图（强制重建、限定了构建范围或调用覆盖文件变化时不复用）
+        let mut file_hashes = self._load_file_hashes(dir)?;
+        let (overrides, overrides_changed) = self._load_call_overrides(dir, &mut file_hashes)?;
+        let incremental = !self.options.force_rebuild && !self.options.is_scoped() && !overrides_changed;
+        let mut code_graph = if incremental { self._load_existing_graph(dir)? } else { None };
         let has_existing_data = code_graph.is_some();
         
         if let Some(ref mut existing_graph) = code_graph {
@@ -773,15 +1312,13 @@ impl CodeParser {
         let files = self.scan_directory(dir);
         info!("Found {} files to process", files.len());
         
-        // 3. 加载文件哈希值（如果存在）
-        let mut file_hashes = self._load_file_hashes(dir)?;
-        
-        // 4. 逐个处理文件，检查是否需要重新解析
+        // 3. 逐个处理文件，检查是否需要重新解析
         let mut processed_files = 0;
         let mut skipped_files = 0;
         
         for file_path in files {
-            if self._should_skip_file(&file_path, &mut file_hashes)? {
+            let unchanged = self._should_skip_file(&file_path, &mut file_hashes)?;
+            if incremental && unchanged {
                 skipped_files += 1;
                 continue;
             }
@@ -794,8 +1331,10 @@ impl CodeParser {
         }
         
         info!("File processing completed: {} processed, {} skipped", processed_files, skipped_files);
+        self._expand_rust_macros();
+        self._expand_reexports();
         
-        // 5. 如果这是增量构建，需要合并新解析的函数
+        // 4. 如果这是增量构建，需要合并新解析的函数
         if has_existing_data {
             self._merge_new_functions(&mut code_graph);
         } else {
@@ -807,40 +1346,166 @@ impl CodeParser {
             }
         }
         
-        // 6. 分析调用关系
+        // 5. 分析调用关系，之后把构建产物中的函数映射回原始源码
+        self.call_overrides = overrides.map(|overrides| overrides.resolve(code_graph.get_all_functions()));
         self._analyze_petgraph_call_relations(&mut code_graph);
+        self._apply_original_locations(&mut code_graph);
         
-        // 7. 更新统计信息
+        // 6. 更新统计信息并校验图的一致性
         code_graph.update_stats();
+        code_graph.check_invariants(self.options.strict, "building the graph")?;
         
-        // 8. 保存新的文件哈希值
+        // 7. 保存新的文件哈希值
         self._save_file_hashes(dir, &file_hashes)?;
         
         Ok(code_graph)
     }
 
-    /// 尝试从本地数据库加载现有的CodeGraph
-    fn _load_existing_code_graph(&self, dir: &Path) -> Result<Option<CodeGraph>, String> {
-        use crate::storage::PersistenceManager;
-        use md5;
-        
-        let persistence = PersistenceManager::new();
-        
-        // 尝试多种方式的项目ID
-        let project_ids = vec![
-            // 1. 使用目录名（原始方式）
-            dir.file_name()
-                .and_then(|n| n.to_str())
-                .unwrap_or("default")
-                .to_string(),
-            // 2. 使用目录路径的MD5哈希（HTTP接口方式）
-            format!("{:x}", md5::compute(dir.to_string_lossy().as_bytes())),
-        ];
-        
-        for project_id in project_ids {
-            info!("Attempting to load existing CodeGraph for project ID: {}", project_id);
-            
-            match persistence.load_graph(&project_id) {
+    /// 把变化的文件应用到已有的图上（例如基线分支的图），不重新解析其余文件。
+    ///
+    /// 变化文件中原有的函数连同调用边一起删除，仍存在的文件重新解析并分析调用；
+    /// 其余文件中调用这些函数的边按名称重新连接，找不到时改为未解析调用，
+    /// 原本未解析而现在能在变化文件中找到的调用也会连上
+    pub fn apply_changed_files(
+        &mut self,
+        code_graph: &mut PetCodeGraph,
+        root: &Path,
+        changed_files: &[PathBuf],
+    ) -> Result<(), String> {
+        // 1. 删除变化文件中的函数，先记下其余文件调入这些函数的调用
+        let mut inbound = Vec::new();
+        for file_path in changed_files {
+            for function in code_graph.find_functions_by_file(file_path) {
+                for (caller, relation) in code_graph.get_callers(&function.id) {
+                    if !changed_files.contains(&caller.file_path) {
+                        inbound.push((caller.clone(), relation.clone()));
+                    }
+                }
+            }
+        }
+        for file_path in changed_files {
+            code_graph.remove_file(file_path);
+        }
+
+        // 2. 重新解析仍存在的文件；之前构建留下的解析结果不再使用，避免重复加入其余文件的函数
+        self.file_functions.clear();
+        self.rust_macros.clear();
+        self.module_imports.clear();
+        self.java_types.clear();
+        self.rust_methods.retain(|_, method| !changed_files.contains(&method.file_path));
+        for file_path in changed_files {
+            if !file_path.is_file() || !self._is_indexed_file(root, file_path) {
+                continue;
+            }
+            if let Err(e) = self.parse_file(file_path) {
+                warn!("Failed to parse {}: {}", file_path.display(), e);
+            }
+        }
+        self._expand_rust_macros();
+        self._expand_reexports();
+        let mut added_names = std::collections::HashSet::new();
+        for functions in self.file_functions.values() {
+            for function in functions {
+                added_names.insert(function.name.clone());
+                code_graph.add_function(function.clone());
+            }
+        }
+        self.call_overrides = CallOverrides::find(root)?.map(|overrides| overrides.resolve(code_graph.get_all_functions()));
+        self._analyze_petgraph_call_relations(code_graph);
+
+        // 3. 其余文件调入变化文件的调用按名称重新连接
+        for (caller, relation) in inbound {
+            let callee = code_graph
+                .find_functions_by_name(&relation.callee_name)
+                .into_iter()
+                .filter(|f| !f.is_unresolved())
+                .min_by_key(|f| f.file_path != relation.callee_file)
+                .cloned();
+            match callee {
+                Some(callee) => {
+                    let relation = CallRelation { callee_id: callee.id, callee_file: callee.file_path, ..relation };
+                    if let Err(e) = code_graph.add_call_relation(relation) {
+                        warn!("Failed to reconnect call relation: {}", e);
+                    }
+                }
+                None => {
+                    let relation = CallRelation { callee_file: caller.file_path.clone(), ..relation };
+                    self._create_unresolved_call_relation(&caller, relation, code_graph)
+                }
+            }
+        }
+
+        // 4. 其余文件中的未解析调用，名称与新增函数相同时改为已解析
+        let placeholders: Vec<FunctionInfo> = code_graph
+            .get_all_functions()
+            .into_iter()
+            .filter(|f| f.is_unresolved() && added_names.contains(&f.name) && !changed_files.contains(&f.file_path))
+            .cloned()
+            .collect();
+        for placeholder in placeholders {
+            let Some(callee) = code_graph
+                .find_functions_by_name(&placeholder.name)
+                .into_iter()
+                .find(|f| !f.is_unresolved())
+                .cloned()
+            else {
+                continue;
+            };
+            let callers: Vec<CallRelation> = code_graph
+                .get_callers(&placeholder.id)
+                .into_iter()
+                .map(|(_, relation)| relation.clone())
+                .collect();
+            code_graph.remove_function(&placeholder.id);
+            for relation in callers {
+                let relation = CallRelation {
+                    callee_id: callee.id,
+                    callee_file: callee.file_path.clone(),
+                    is_resolved: true,
+                    ..relation
+                };
+                if let Err(e) = code_graph.add_call_relation(relation) {
+                    warn!("Failed to resolve call relation: {}", e);
+                }
+            }
+        }
+
+        self._apply_original_locations(code_graph);
+        code_graph.update_stats();
+        code_graph.check_invariants(self.options.strict, "applying changed files")
+    }
+
+    /// 增量构建使用的本地数据库
+    fn _persistence(&self) -> crate::storage::PersistenceManager {
+        use crate::storage::PersistenceManager;
+
+        match &self.options.storage_dir {
+            Some(dir) => PersistenceManager::with_base_dir(dir.clone(), crate::cli::args::StorageMode::Json),
+            None => PersistenceManager::new(),
+        }
+    }
+
+    /// 尝试从本地数据库加载现有的CodeGraph
+    fn _load_existing_code_graph(&self, dir: &Path) -> Result<Option<CodeGraph>, String> {
+        use md5;
+        
+        let persistence = self._persistence();
+        
+        // 尝试多种方式的项目ID
+        let project_ids = vec![
+            // 1. 使用目录名（原始方式）
+            dir.file_name()
+                .and_then(|n| n.to_str())
+                .unwrap_or("default")
+                .to_string(),
+            // 2. 使用目录路径的MD5哈希（HTTP接口方式）
+            format!("{:x}", md5::compute(dir.to_string_lossy().as_bytes())),
+        ];
+        
+        for project_id in project_ids {
+            info!("Attempting to load existing CodeGraph for project ID: {}", project_id);
+            
+            match persistence.load_graph(&project_id) {
                 Ok(Some(pet_graph)) => {
                     info!("Found existing PetCodeGraph with {} functions for project ID: {}", 
                           pet_graph.graph.node_count(), project_id);
@@ -901,14 +1566,13 @@ impl CodeParser {
 
     /// 尝试从本地数据库加载现有的图
     fn _load_existing_graph(&self, dir: &Path) -> Result<Option<PetCodeGraph>, String> {
-        use crate::storage::PersistenceManager;
         
         // 使用项目路径作为项目ID
         let project_id = dir.file_name()
             .and_then(|n| n.to_str())
             .unwrap_or("default");
         
-        let persistence = PersistenceManager::new();
+        let persistence = self._persistence();
         match persistence.load_graph(project_id) {
             Ok(Some(graph)) => Ok(Some(graph)),
             Ok(None) => Ok(None),
@@ -921,10 +1585,9 @@ impl CodeParser {
 
     /// 加载文件哈希值
     fn _load_file_hashes(&self, dir: &Path) -> Result<HashMap<String, String>, String> {
-        use crate::storage::PersistenceManager;
         use md5;
         
-        let persistence = PersistenceManager::new();
+        let persistence = self._persistence();
         
         // 尝试多种方式的项目ID
         let project_ids = vec![
@@ -958,10 +1621,9 @@ impl CodeParser {
 
     /// 保存文件哈希值
     fn _save_file_hashes(&self, dir: &Path, hashes: &HashMap<String, String>) -> Result<(), String> {
-        use crate::storage::PersistenceManager;
         use md5;
         
-        let persistence = PersistenceManager::new();
+        let persistence = self._persistence();
         
         // 使用目录路径的MD5哈希作为项目ID（与HTTP接口保持一致）
         let project_id = format!("{:x}", md5::compute(dir.to_string_lossy().as_bytes()));
@@ -1003,6 +1665,32 @@ impl CodeParser {
         Ok(false)
     }
 
+    /// 调用覆盖文件为该调用处指定的目标函数
+    fn _override_targets(&self, file_path: &Path, line: usize, call_name: &str) -> Option<&[FunctionInfo]> {
+        self.call_overrides.as_ref()?.targets(file_path, line, call_name)
+    }
+
+    /// 读取项目根目录下的调用覆盖文件。覆盖文件的哈希与源文件哈希一起保存（文件不存在时为空），
+    /// 返回值的第二项表示它是否与上次构建不同
+    fn _load_call_overrides(
+        &self,
+        dir: &Path,
+        file_hashes: &mut HashMap<String, String>,
+    ) -> Result<(Option<CallOverrides>, bool), String> {
+        let path = dir.join(OVERRIDES_FILE);
+        let current_hash = match fs::read(&path) {
+            Ok(content) => format!("{:x}", md5::compute(&content)),
+            Err(_) => String::new(),
+        };
+        let key = path.to_string_lossy().to_string();
+        let changed = file_hashes.get(&key).map_or("", String::as_str) != current_hash;
+        if changed {
+            info!("{} changed, analyzing all files", OVERRIDES_FILE);
+            file_hashes.insert(key, current_hash);
+        }
+        Ok((CallOverrides::find(dir)?, changed))
+    }
+
     /// 合并新解析的函数到现有图中
     fn _merge_new_functions(&self, code_graph: &mut PetCodeGraph) {
         for (_file_path, functions) in &self.file_functions {
@@ -1023,10 +1711,16 @@ impl CodeParser {
 
     /// 分析调用关系 
     fn _analyze_call_relations(&self, code_graph: &mut CodeGraph) {
+        // 图中已包含未变化文件的函数，增量构建时也能找到全部 RPC 定义
+        let rpc_index = RpcIndex::new(code_graph.functions.values());
         // 使用TreeSitter解析器分析每个文件的调用关系
         for (file_path, functions) in &self.file_functions {
             if let Ok(symbols) = self.ts_parser.parse_file(file_path) {
-                self._analyze_file_call_relations(&symbols, functions, code_graph);
+                let (rpc_calls, rpc_impls) = self._grpc_links(&rpc_index, file_path, functions);
+                for relation in rpc_impls {
+                    code_graph.add_call_relation(relation);
+                }
+                self._analyze_file_call_relations(&symbols, functions, &rpc_calls, code_graph);
             } else {
                 warn!("Failed to parse file for call analysis: {}", file_path.display());
             }
@@ -1038,6 +1732,7 @@ impl CodeParser {
         &self, 
         symbols: &[crate::codegraph::treesitter::AstSymbolInstanceArc], 
         functions: &[FunctionInfo], 
+        rpc_calls: &HashMap<String, FunctionInfo>,
         code_graph: &mut CodeGraph
     ) {
         // 分析每个AST符号
@@ -1050,6 +1745,51 @@ impl CodeParser {
                 let call_name = symbol_ref.name();
                 let call_file = symbol_ref.file_path();
                 let call_line = symbol_ref.full_range().start_point.row + 1;
+                let reported_line = self._notebook_line(call_file, call_line);
+                // 0. 调用覆盖文件中指定的目标优先于分析结果
+                if let Some(targets) = self._override_targets(call_file, reported_line, call_name) {
+                    if let Some(caller_idx) = self._find_caller_function_by_line(call_file, call_line, functions) {
+                        let caller = &functions[caller_idx];
+                        for callee in targets {
+                            code_graph.add_call_relation(CallRelation {
+                                caller_id: caller.id,
+                                callee_id: callee.id,
+                                caller_name: caller.name.clone(),
+                                callee_name: callee.name.clone(),
+                                caller_file: caller.file_path.clone(),
+                                callee_file: callee.file_path.clone(),
+                                line_number: reported_line,
+                                is_resolved: true,
+                                observed: false,
+                                template_args: None,
+                                call_kind: CallKind::Sync,
+                                confidence: None,
+                            });
+                        }
+                        continue;
+                    }
+                }
+                // gRPC 桩调用连到 .proto 中的 RPC 定义
+                if let Some(rpc) = RpcIndex::resolve(rpc_calls, call_name) {
+                    if let Some(caller_idx) = self._find_caller_function_by_line(call_file, call_line, functions) {
+                        let caller = &functions[caller_idx];
+                        code_graph.add_call_relation(CallRelation {
+                            caller_id: caller.id,
+                            callee_id: rpc.id,
+                            caller_name: caller.name.clone(),
+                            callee_name: rpc.name.clone(),
+                            caller_file: caller.file_path.clone(),
+                            callee_file: rpc.file_path.clone(),
+                            line_number: reported_line,
+                            is_resolved: true,
+                            observed: false,
+                            template_args: None,
+                            call_kind: CallKind::Sync,
+                            confidence: None,
+                        });
+                        continue;
+                    }
+                }
                 // 1. 先在本文件查找被调用函数
                 if let Some(callee_idx) = self._find_function_by_name_in_list(call_name, functions) {
                     // 查找调用者函数（通过分析调用位置）
@@ -1063,8 +1803,12 @@ impl CodeParser {
                             callee_name: callee.name.clone(),
                             caller_file: caller.file_path.clone(),
                             callee_file: callee.file_path.clone(),
-                            line_number: call_line,
+                            line_number: reported_line,
                             is_resolved: true,
+                            observed: false,
+                            template_args: None,
+                            call_kind: CallKind::Sync,
+                            confidence: None,
                         };
                         code_graph.add_call_relation(relation);
                         continue;
@@ -1082,8 +1826,12 @@ impl CodeParser {
                             callee_name: callee.name.clone(),
                             caller_file: caller.file_path.clone(),
                             callee_file: callee.file_path.clone(),
-                            line_number: call_line,
+                            line_number: reported_line,
                             is_resolved: true,
+                            observed: false,
+                            template_args: None,
+                            call_kind: CallKind::Sync,
+                            confidence: None,
                         };
                         code_graph.add_call_relation(relation);
                         continue;
@@ -1095,6 +1843,480 @@ impl CodeParser {
         }
     }
 
+    /// 文件中的 gRPC 桩调用（调用写法 -> RPC），以及 RPC 到文件中服务端实现的调用关系
+    fn _grpc_links(
+        &self,
+        rpc_index: &RpcIndex,
+        file_path: &Path,
+        functions: &[FunctionInfo],
+    ) -> (HashMap<String, FunctionInfo>, Vec<CallRelation>) {
+        if rpc_index.is_empty() {
+            return (HashMap::new(), vec![]);
+        }
+        let Ok(content) = fs::read_to_string(file_path) else {
+            return (HashMap::new(), vec![]);
+        };
+        let language = self._detect_language(file_path);
+        let impls = rpc_index
+            .server_impls(file_path, &language, &content, functions)
+            .into_iter()
+            .map(|(rpc, implementation)| CallRelation {
+                caller_id: rpc.id,
+                callee_id: implementation.id,
+                caller_name: rpc.name.clone(),
+                callee_name: implementation.name.clone(),
+                caller_file: rpc.file_path.clone(),
+                callee_file: implementation.file_path.clone(),
+                line_number: implementation.line_start,
+                is_resolved: true,
+                observed: false,
+                template_args: None,
+                call_kind: CallKind::Sync,
+                confidence: None,
+            })
+            .collect();
+        (rpc_index.client_calls(file_path, &language, &content), impls)
+    }
+
+    /// 把 Rust 宏调用生成的函数加入调用所在的文件，范围为整个宏调用。
+    /// 增量构建时只能展开本次解析过的文件中定义的宏
+    fn _expand_rust_macros(&mut self) {
+        let mut generated = Vec::new();
+        for (file_path, (namespace, macros)) in &self.rust_macros {
+            for invocation in &macros.invocations {
+                let Some(definition) = self._rust_macro_definition(file_path, &invocation.name) else {
+                    continue;
+                };
+                for name in definition.generated_functions(invocation) {
+                    generated.push(FunctionInfo {
+                        id: Uuid::new_v4(),
+                        signature: Some(format!("fn {}", name)),
+                        name,
+                        file_path: file_path.clone(),
+                        line_start: invocation.line_start,
+                        line_end: invocation.line_end,
+                        namespace: namespace.clone(),
+                        language: "rust".to_string(),
+                        cell: None,
+                        body_hash: None,
+                        partial: false,
+                        return_type: None,
+                        parameters: Vec::new(),
+                        parent_class_id: None,
+                        kind: FunctionKind::Generated,
+                    });
+                }
+            }
+        }
+        for function in generated {
+            let functions = self.file_functions.entry(function.file_path.clone()).or_default();
+            if functions.iter().any(|f| f.name == function.name && f.line_start == function.line_start) {
+                continue;
+            }
+            functions.push(function.clone());
+            self.function_registry.insert(function.name.clone(), function);
+        }
+    }
+
+    /// 为再导出语句在门面文件中创建节点：具名再导出每个名字一个节点，通配再导出为来源模块中的每个函数创建节点。
+    /// 来源模块须是本次解析过的文件；门面的门面按轮次展开
+    fn _expand_reexports(&mut self) {
+        loop {
+            let mut generated = Vec::new();
+            for (file_path, (namespace, imports)) in &self.module_imports {
+                let language = self._detect_language(file_path);
+                let existing = self.file_functions.get(file_path);
+                for reexport in &imports.reexports {
+                    for (target, name) in self._reexport_sources(&language, file_path, imports, reexport) {
+                        let known = existing.is_some_and(|functions| functions.iter().any(|f| f.is_reexport() && f.name == name));
+                        if known || generated.iter().any(|(f, _): &(FunctionInfo, Uuid)| f.file_path == *file_path && f.name == name) {
+                            continue;
+                        }
+                        let signature = reexport_statement(&language, reexport, &target.name, &name);
+                        generated.push((
+                            FunctionInfo {
+                                id: Uuid::new_v4(),
+                                name,
+                                file_path: file_path.clone(),
+                                line_start: reexport.line,
+                                line_end: reexport.line,
+                                namespace: namespace.clone(),
+                                language: language.clone(),
+                                signature: Some(signature),
+                                cell: None,
+                                body_hash: None,
+                                partial: false,
+                                return_type: None,
+                                parameters: Vec::new(),
+                                parent_class_id: None,
+                                kind: FunctionKind::Reexport,
+                            },
+                            target.id,
+                        ));
+                    }
+                }
+            }
+            if generated.is_empty() {
+                break;
+            }
+            for (function, target) in generated {
+                self.reexport_targets.insert(function.id, target);
+                self.file_functions.entry(function.file_path.clone()).or_default().push(function);
+            }
+        }
+    }
+
+    /// 再导出语句指向的函数及其在门面中的名字
+    fn _reexport_sources(
+        &self,
+        language: &str,
+        file_path: &Path,
+        imports: &ModuleImports,
+        reexport: &ModuleImport,
+    ) -> Vec<(FunctionInfo, String)> {
+        let mut sources = Vec::new();
+        for (source_file, functions) in &self.file_functions {
+            if source_file == file_path || !imports.resolves_to(language, file_path, &reexport.module, source_file) {
+                continue;
+            }
+            for function in functions {
+                let name = if reexport.is_glob() {
+                    // 通配不导出宏和 Python 的私有名字
+                    let private = language == "python" && function.name.starts_with('_');
+                    if private || function.name.ends_with('!') {
+                        continue;
+                    }
+                    Some(function.name.clone())
+                } else {
+                    reexport.names.iter().find(|(_, original)| *original == function.name).map(|(alias, _)| alias.clone())
+                };
+                if let Some(name) = name {
+                    if !sources.iter().any(|(_, existing)| *existing == name) {
+                        sources.push((function.clone(), name));
+                    }
+                }
+            }
+        }
+        sources
+    }
+
+    /// 再导出节点 -> 被再导出的函数（可能是另一个门面中的再导出节点）
+    fn _reexport_links(&self, functions: &[FunctionInfo], code_graph: &PetCodeGraph) -> Vec<CallRelation> {
+        functions
+            .iter()
+            .filter_map(|function| {
+                let target = code_graph.get_function_by_id(self.reexport_targets.get(&function.id)?)?;
+                Some(CallRelation {
+                    caller_id: function.id,
+                    callee_id: target.id,
+                    caller_name: function.name.clone(),
+                    callee_name: target.name.clone(),
+                    caller_file: function.file_path.clone(),
+                    callee_file: target.file_path.clone(),
+                    line_number: function.line_start,
+                    is_resolved: true,
+                    observed: false,
+                    template_args: None,
+                    call_kind: CallKind::Sync,
+                    confidence: None,
+                })
+            })
+            .collect()
+    }
+
+    /// 外层函数到其中定义的匿名函数的边，位于匿名函数定义的行；回调在何时被调用无法静态确定
+    fn _closure_links(&self, functions: &[FunctionInfo]) -> Vec<CallRelation> {
+        functions
+            .iter()
+            .filter_map(|closure| {
+                let parent_id = self.closure_parents.get(&closure.id)?;
+                let parent = functions.iter().find(|function| function.id == *parent_id)?;
+                Some(CallRelation {
+                    caller_id: parent.id,
+                    callee_id: closure.id,
+                    caller_name: parent.name.clone(),
+                    callee_name: closure.name.clone(),
+                    caller_file: parent.file_path.clone(),
+                    callee_file: closure.file_path.clone(),
+                    line_number: closure.line_start,
+                    is_resolved: true,
+                    observed: false,
+                    template_args: None,
+                    call_kind: CallKind::Sync,
+                    confidence: None,
+                })
+            })
+            .collect()
+    }
+
+    /// 框架装饰器（`@app.route`、`@Scheduled`、`@Get` 等）注册的入口：文件中每种装饰器一个命名空间为 `framework`
+    /// 的节点，在装饰器所在行调用被装饰的函数
+    fn _entry_point_links(&self, file_path: &PathBuf, functions: &[FunctionInfo], code_graph: &mut PetCodeGraph) -> Vec<CallRelation> {
+        let language = self._detect_language(file_path);
+        let Ok(code) = fs::read_to_string(file_path) else { return Vec::new() };
+        let mut frameworks: HashMap<String, FunctionInfo> = HashMap::new();
+        let mut relations = Vec::new();
+        for decoration in scan_decorators(&language, &code) {
+            let Some(kind) = decoration.entry_point_kind(&language) else { continue };
+            let Some(handler) = decoration.find_function(functions) else { continue };
+            let framework = frameworks.entry(decoration.name.clone()).or_insert_with(|| {
+                let framework = FunctionInfo {
+                    id: Uuid::new_v4(),
+                    name: format!("@{}", decoration.name),
+                    file_path: file_path.clone(),
+                    line_start: decoration.line,
+                    line_end: decoration.line,
+                    namespace: "framework".to_string(),
+                    language: language.clone(),
+                    signature: Some(format!("@{} ({} entry point)", decoration.name, kind)),
+                    cell: None,
+                    body_hash: None,
+                    partial: false,
+                    return_type: None,
+                    parameters: Vec::new(),
+                    parent_class_id: None,
+                    kind: FunctionKind::Function,
+                };
+                code_graph.add_function(framework.clone());
+                framework
+            });
+            relations.push(CallRelation {
+                caller_id: framework.id,
+                callee_id: handler.id,
+                caller_name: framework.name.clone(),
+                callee_name: handler.name.clone(),
+                caller_file: file_path.clone(),
+                callee_file: handler.file_path.clone(),
+                line_number: decoration.line,
+                is_resolved: true,
+                observed: false,
+                template_args: None,
+                call_kind: CallKind::Sync,
+                confidence: None,
+            });
+        }
+        relations
+    }
+
+    /// 文件从门面模块导入了 `call_name` 时，门面中对应的再导出节点
+    fn _imported_reexport(&self, file_path: &Path, call_name: &str) -> Option<FunctionInfo> {
+        let (_, imports) = self.module_imports.get(file_path)?;
+        let language = self._detect_language(file_path);
+        let short_name = call_name.rsplit(['.', ':']).next().unwrap_or(call_name);
+        imports.imports.iter().find_map(|import| {
+            let name = if import.is_glob() {
+                short_name
+            } else {
+                import.names.iter().find(|(alias, _)| alias == call_name || alias == short_name)?.1.as_str()
+            };
+            self.file_functions
+                .iter()
+                .filter(|(facade, _)| imports.resolves_to(&language, file_path, &import.module, facade))
+                .find_map(|(_, functions)| functions.iter().find(|f| f.is_reexport() && f.name == name))
+                .cloned()
+        })
+    }
+
+    /// 调用经导入语句指向的函数：`z.foo()` 中 `z` 为 `import x as z`（或 `import * as z from 'x'`）时
+    /// 连到模块 `x` 中的 `foo`，`w()` 中 `w` 为 `from x import y as w`（或 `import { y as w } from 'x'`）时
+    /// 连到模块 `x` 中的 `y`
+    fn _imported_function(&self, file_path: &Path, language: &str, receiver: Option<&str>, call_name: &str) -> Option<FunctionInfo> {
+        let (_, imports) = self.module_imports.get(file_path)?;
+        imports.call_targets(language, receiver, call_name).into_iter().find_map(|(module, name)| {
+            self.file_functions
+                .iter()
+                .filter(|(source, _)| source.as_path() != file_path && imports.resolves_to(language, file_path, &module, source))
+                .find_map(|(_, functions)| functions.iter().find(|f| f.name == name))
+                .cloned()
+        })
+    }
+
+    /// Java 调用 `receiver.name()` 指向的方法：接收者为空、`this` 或 `super` 时从调用者所在的类查找，
+    /// 接收者为类名（`Foo`、`a.b.Foo`）时从该类查找，找不到时沿父类和接口向上查找（CHA）。
+    /// 没有接收者且类层次中没有该方法时按 `import static` 查找
+    fn _java_method(
+        &self,
+        file_path: &Path,
+        caller: &FunctionInfo,
+        receiver: Option<&str>,
+        call_name: &str,
+        arguments: Option<&CallArguments>,
+    ) -> Option<(FunctionInfo, Option<f32>)> {
+        let (_, types) = self.java_types.get(file_path)?;
+        let enclosing = || {
+            types
+                .iter()
+                .filter(|t| t.line_start <= caller.line_start && caller.line_end <= t.line_end)
+                .min_by_key(|t| t.line_end - t.line_start)
+        };
+        let mut visited = HashSet::new();
+        match receiver {
+            None | Some("this") => {
+                let found = enclosing().and_then(|t| self._java_lookup_method(file_path, t, call_name, arguments, &mut visited));
+                if found.is_some() || receiver.is_some() {
+                    return found;
+                }
+                let (_, imports) = self.module_imports.get(file_path)?;
+                imports.call_targets("java", None, call_name).into_iter().find_map(|(class, name)| {
+                    let (path, declaration) = self._java_resolve_type(file_path, &class)?;
+                    self._java_lookup_method(path, declaration, &name, arguments, &mut visited)
+                })
+            }
+            Some("super") => enclosing()?.supertypes().find_map(|supertype| {
+                let (path, declaration) = self._java_resolve_type(file_path, supertype)?;
+                self._java_lookup_method(path, declaration, call_name, arguments, &mut visited)
+            }),
+            // 小写开头的接收者是变量或字段，类型未知
+            Some(class) if class.rsplit('.').next().is_some_and(|name| name.starts_with(char::is_uppercase)) => {
+                let (path, declaration) = self._java_resolve_type(file_path, class)?;
+                self._java_lookup_method(path, declaration, call_name, arguments, &mut visited)
+            }
+            Some(_) => None,
+        }
+    }
+
+    /// 在类型及其父类型中查找方法，返回最近的类型中有实现的同名方法，有多个重载时按实参选择
+    fn _java_lookup_method(
+        &self,
+        file_path: &Path,
+        declaration: &TypeDeclaration,
+        call_name: &str,
+        arguments: Option<&CallArguments>,
+        visited: &mut HashSet<(PathBuf, usize)>,
+    ) -> Option<(FunctionInfo, Option<f32>)> {
+        if !visited.insert((file_path.to_path_buf(), declaration.line_start)) {
+            return None;
+        }
+        let functions = self.file_functions.get(file_path);
+        let found: Vec<&FunctionInfo> = declaration
+            .methods
+            .iter()
+            .filter(|method| method.name == call_name && !method.is_abstract)
+            .filter_map(|method| {
+                functions?
+                    .iter()
+                    .find(|f| f.name == method.name && f.line_start <= method.line_end && method.line_start <= f.line_end)
+            })
+            .collect();
+        let picked = match arguments {
+            Some(arguments) => self._pick_overload(&found, arguments),
+            None => found.first().map(|function| ((*function).clone(), None)),
+        };
+        if picked.is_some() {
+            return picked;
+        }
+        declaration.supertypes().find_map(|supertype| {
+            let (path, parent) = self._java_resolve_type(file_path, supertype)?;
+            self._java_lookup_method(path, parent, call_name, arguments, visited)
+        })
+    }
+
+    /// `file_path` 中写的类型名指向的类型声明：全限定名按包名查找；简单名依次在本文件、
+    /// 单类型导入、同一个包和按需导入（`import a.b.*`）的包中查找
+    fn _java_resolve_type(&self, file_path: &Path, name: &str) -> Option<(&PathBuf, &TypeDeclaration)> {
+        let in_package = |package: &str, simple: &str| {
+            self.java_types.iter().find_map(|(path, (namespace, types))| {
+                (namespace == package).then(|| types.iter().find(|t| t.name == simple).map(|t| (path, t)))?
+            })
+        };
+        if let Some((package, simple)) = name.rsplit_once('.') {
+            return in_package(package, simple);
+        }
+        let (own_path, (package, types)) = self.java_types.get_key_value(file_path)?;
+        if let Some(declaration) = types.iter().find(|t| t.name == name) {
+            return Some((own_path, declaration));
+        }
+        let imports = self.module_imports.get(file_path).map(|(_, imports)| &imports.imports);
+        let single = imports
+            .into_iter()
+            .flatten()
+            .filter(|import| import.names.iter().any(|(alias, _)| alias == name))
+            .find_map(|import| in_package(&import.module, name));
+        single.or_else(|| in_package(package, name)).or_else(|| {
+            imports
+                .into_iter()
+                .flatten()
+                .filter(|import| import.is_glob())
+                .find_map(|import| in_package(&import.module, name))
+        })
+    }
+
+    /// 文件所在目录生效的 tsconfig.json/jsconfig.json，按目录缓存
+    fn _tsconfig_for(&mut self, file_path: &Path) -> Option<&TsConfig> {
+        let dir = file_path.parent()?.to_path_buf();
+        self.tsconfigs.entry(dir.clone()).or_insert_with(|| TsConfig::find(&dir)).as_ref()
+    }
+
+    /// 按名称查找 `macro_rules!` 定义，优先使用同一文件中的定义
+    fn _rust_macro_definition(&self, file_path: &Path, name: &str) -> Option<&MacroDefinition> {
+        self.rust_macros
+            .get(file_path)
+            .and_then(|(_, macros)| macros.definition(name))
+            .or_else(|| self.rust_macros.values().find_map(|(_, macros)| macros.definition(name)))
+    }
+
+    /// Rust 宏的调用边：调用处所在函数 -> `name!`，宏生成的函数 -> 生成它的宏，宏 -> 转写器中调用的函数。
+    /// 项目中没有定义的宏（`println!` 等）不记录
+    fn _rust_macro_links(
+        &self,
+        file_path: &PathBuf,
+        functions: &[FunctionInfo],
+        code_graph: &PetCodeGraph,
+    ) -> Vec<CallRelation> {
+        let Some((_, macros)) = self.rust_macros.get(file_path) else {
+            return vec![];
+        };
+        let relation = |caller: &FunctionInfo, callee: &FunctionInfo, line_number: usize| CallRelation {
+            caller_id: caller.id,
+            callee_id: callee.id,
+            caller_name: caller.name.clone(),
+            callee_name: callee.name.clone(),
+            caller_file: caller.file_path.clone(),
+            callee_file: callee.file_path.clone(),
+            line_number,
+            is_resolved: true,
+            observed: false,
+            template_args: None,
+            call_kind: CallKind::Sync,
+            confidence: None,
+        };
+        let mut relations = Vec::new();
+        for invocation in &macros.invocations {
+            let macro_name = format!("{}!", invocation.name);
+            let Some(callee) = self._resolve_callee_function(&macro_name, file_path, functions, code_graph) else {
+                continue;
+            };
+            let generated = self
+                ._rust_macro_definition(file_path, &invocation.name)
+                .map(|definition| definition.generated_functions(invocation))
+                .unwrap_or_default();
+            if generated.is_empty() {
+                if let Some(caller_idx) = self._find_caller_function_by_line(file_path, invocation.line_start, functions) {
+                    relations.push(relation(&functions[caller_idx], &callee, invocation.line_start));
+                }
+            } else {
+                relations.extend(
+                    functions
+                        .iter()
+                        .filter(|f| f.line_start == invocation.line_start && generated.contains(&f.name))
+                        .map(|caller| relation(caller, &callee, invocation.line_start)),
+                );
+            }
+        }
+        for definition in &macros.definitions {
+            let macro_name = format!("{}!", definition.name);
+            let Some(caller) = functions.iter().find(|f| f.name == macro_name && f.line_start == definition.line_start) else {
+                continue;
+            };
+            for (name, line) in &definition.calls {
+                if let Some(callee) = self._resolve_callee_function(name, file_path, functions, code_graph) {
+                    relations.push(relation(caller, &callee, *line));
+                }
+            }
+        }
+        relations
+    }
+
     /// 查找调用者函数（按行号）
     fn _find_caller_function_by_line(
         &self,
@@ -1102,15 +2324,30 @@ impl CodeParser {
         call_line: usize,
         functions: &[FunctionInfo]
     ) -> Option<usize> {
-        // 查找包含调用行的函数
-        for (idx, function) in functions.iter().enumerate() {
-            if function.file_path == *file_path && 
-               call_line >= function.line_start && 
-               call_line <= function.line_end {
-                return Some(idx);
-            }
-        }
-        None 
+        // 查找包含调用行的最内层函数（嵌套函数、整个脚本作为函数时外层范围也包含调用行）
+        functions
+            .iter()
+            .enumerate()
+            .filter(|(_, function)| {
+                // notebook 中的函数按拼接脚本中的范围匹配
+                let (start, end) = self.notebook_ranges.get(&function.id).copied().unwrap_or((function.line_start, function.line_end));
+                function.file_path == *file_path && call_line >= start && call_line <= end
+            })
+            .min_by_key(|(_, function)| function.line_end - function.line_start)
+            .map(|(idx, _)| idx)
+    }
+
+    /// 按 (行, 列) 查找包含调用位置的最内层函数（用于带 source map 的生成文件）
+    fn _find_caller_function_by_position(&self, position: (usize, usize), functions: &[FunctionInfo]) -> Option<usize> {
+        functions
+            .iter()
+            .enumerate()
+            .filter_map(|(idx, function)| {
+                let (start, end) = self.generated_ranges.get(&function.id)?;
+                (*start <= position && position <= *end).then_some((idx, *start))
+            })
+            .max_by_key(|(_, start)| *start)
+            .map(|(idx, _)| idx)
     }
 
     /// 在函数列表中根据名称查找函数
@@ -1143,8 +2380,12 @@ impl CodeParser {
                 callee_name: call_name.to_string(),
                 caller_file: caller.file_path.clone(),
                 callee_file: call_file.clone(),
-                line_number: call_line,
+                line_number: self._notebook_line(call_file, call_line),
                 is_resolved: false,
+                observed: false,
+                template_args: None,
+                call_kind: CallKind::Sync,
+                confidence: None,
             };
             code_graph.add_call_relation(relation);
         }
@@ -1166,7 +2407,8 @@ impl CodeParser {
     fn _find_function_by_name_global(&self, name: &str) -> Option<FunctionInfo> {
         for (_file_path, functions) in &self.file_functions {
             for function in functions {
-                if function.name == name {
+                // 再导出节点只在经门面模块导入时使用
+                if function.name == name && !function.is_reexport() {
                     return Some(function.clone());
                 }
             }
@@ -1181,6 +2423,9 @@ impl CodeParser {
         let mut total_calls = 0;
         let mut resolved_calls = 0;
         let mut unresolved_calls = 0;
+        let mut qualified_calls = 0;
+        // 图中已包含未变化文件的函数，增量构建时也能找到全部 RPC 定义
+        let rpc_index = RpcIndex::new(code_graph.get_all_functions());
         
         // 遍历每个文件的函数
         for (file_path, functions) in &self.file_functions {
@@ -1191,26 +2436,58 @@ impl CodeParser {
             // 使用TreeSitter解析器分析文件中的函数调用
             match self.ts_parser.parse_file(file_path) {
                 Ok(symbols) => {
+                    let (rpc_calls, rpc_impls) = self._grpc_links(&rpc_index, file_path, functions);
+                    for relation in rpc_impls {
+                        if let Err(e) = code_graph.add_call_relation(relation) {
+                            warn!("Failed to add gRPC implementation relation: {}", e);
+                        }
+                    }
+                    for relation in self._rust_macro_links(file_path, functions, code_graph) {
+                        if let Err(e) = code_graph.add_call_relation(relation) {
+                            warn!("Failed to add macro call relation: {}", e);
+                        }
+                    }
+                    for relation in self._reexport_links(functions, code_graph) {
+                        if let Err(e) = code_graph.add_call_relation(relation) {
+                            warn!("Failed to add re-export relation: {}", e);
+                        }
+                    }
+                    for relation in self._closure_links(functions) {
+                        if let Err(e) = code_graph.add_call_relation(relation) {
+                            warn!("Failed to add closure relation: {}", e);
+                        }
+                    }
+                    if self.options.framework_entry_points {
+                        for relation in self._entry_point_links(file_path, functions, code_graph) {
+                            if let Err(e) = code_graph.add_call_relation(relation) {
+                                warn!("Failed to add entry point relation: {}", e);
+                            }
+                        }
+                    }
                     let file_calls = self._analyze_file_calls_for_petgraph(
                         &symbols, 
                         functions, 
+                        &rpc_calls,
                         code_graph,
                         file_path
                     );
                     total_calls += file_calls.total;
                     resolved_calls += file_calls.resolved;
                     unresolved_calls += file_calls.unresolved;
+                    qualified_calls += file_calls.qualified;
                 },
                 Err(e) => {
                     warn!("Failed to parse file {} for call analysis: {:?}", file_path.display(), e);
-                    // 即使解析失败，也尝试基于函数名的简单分析
-                    self._fallback_call_analysis(functions, code_graph);
+                    // 启用时按函数名猜测调用，猜出的边标记为未解析
+                    if self.options.heuristic_fallback {
+                        self._fallback_call_analysis(functions, code_graph);
+                    }
                 }
             }
         }
         
-        info!("Call analysis completed: {} total calls, {} resolved, {} unresolved", 
-              total_calls, resolved_calls, unresolved_calls);
+        info!("Call analysis completed: {} total calls, {} resolved ({} via Java packages and imports), {} unresolved", 
+              total_calls, resolved_calls, qualified_calls, unresolved_calls);
     }
     
     /// 分析单个文件的函数调用（用于petgraph）
@@ -1218,13 +2495,47 @@ impl CodeParser {
         &self,
         symbols: &[crate::codegraph::treesitter::AstSymbolInstanceArc],
         functions: &[FunctionInfo],
+        rpc_calls: &HashMap<String, FunctionInfo>,
         code_graph: &mut PetCodeGraph,
         file_path: &PathBuf,
     ) -> CallAnalysisStats {
         let mut stats = CallAnalysisStats::default();
+        // 生成文件需要原文把字节列号换算为 source map 的 UTF-16 列号
+        let generated = self
+            .source_maps
+            .get(file_path)
+            .and_then(|map| Some((map, fs::read_to_string(file_path).ok()?)));
+        let language = self._detect_language(file_path);
+        let call_kinds = ["rust", "python", "javascript", "typescript"]
+            .contains(&language.as_str())
+            .then(|| Some(CallKinds::new(&language, symbols, fs::read_to_string(file_path).ok()?)))
+            .flatten()
+            .unwrap_or_default();
+        let receivers = (!self.rust_methods.is_empty() && language == "rust")
+            .then(|| Some(ReceiverTypes::new(symbols, fs::read_to_string(file_path).ok()?)))
+            .flatten();
+        // 调用处写出的接收者，如 `z.foo()` 中的 `z`
+        let import_source = (["python", "javascript", "typescript"].contains(&language.as_str())
+            && self.module_imports.contains_key(file_path.as_path()))
+            .then(|| fs::read_to_string(file_path).ok())
+            .flatten();
+        let java_source = (language == "java" && self.java_types.contains_key(file_path.as_path()))
+            .then(|| fs::read_to_string(file_path).ok())
+            .flatten();
+        // 按实参个数和类型选择重载
+        let overload_source = OVERLOADED_LANGUAGES
+            .contains(&language.as_str())
+            .then(|| fs::read_to_string(file_path).ok())
+            .flatten();
         
         // 分析每个AST符号
         for symbol in symbols {
+            // C++ 模板调用写出的模板实参
+            let template_args = symbol
+                .write()
+                .as_any_mut()
+                .downcast_ref::<crate::codegraph::treesitter::ast_instance_structs::FunctionCall>()
+                .and_then(|call| template_args_text(&call.template_types));
             let symbol_guard = symbol.read();
             let symbol_ref = symbol_guard.as_ref();
             
@@ -1233,18 +2544,88 @@ impl CodeParser {
                 stats.total += 1;
                 let call_name = symbol_ref.name();
                 let call_line = symbol_ref.full_range().start_point.row + 1;
+                // 构建产物中的调用行号报告为原始源码中的行号
+                let reported_line = generated
+                    .as_ref()
+                    .and_then(|(map, code)| {
+                        let range = symbol_ref.full_range();
+                        map.lookup(call_line, utf16_column(code, range.start_byte, range.start_point.column))
+                    })
+                    .map_or_else(|| self._notebook_line(file_path, call_line), |location| location.line);
                 
                 // 查找调用者函数（通过分析调用位置）
-                if let Some(caller_idx) = self._find_caller_function_by_line(file_path, call_line, functions) {
+                let caller_idx = if self.source_maps.contains_key(file_path) {
+                    let start = symbol_ref.full_range().start_point;
+                    self._find_caller_function_by_position((start.row, start.column), functions)
+                } else {
+                    self._find_caller_function_by_line(file_path, call_line, functions)
+                };
+                if let Some(caller_idx) = caller_idx {
                     let caller = &functions[caller_idx];
                     
-                    // 尝试解析被调用函数
-                    if let Some(callee_info) = self._resolve_callee_function(
-                        call_name, 
-                        file_path, 
-                        functions, 
-                        code_graph
-                    ) {
+                    // 调用覆盖文件中指定的目标优先于分析结果
+                    if let Some(targets) = self._override_targets(file_path, reported_line, call_name) {
+                        for callee_info in targets {
+                            let relation = CallRelation {
+                                caller_id: caller.id,
+                                callee_id: callee_info.id,
+                                caller_name: caller.name.clone(),
+                                callee_name: callee_info.name.clone(),
+                                caller_file: caller.file_path.clone(),
+                                callee_file: callee_info.file_path.clone(),
+                                line_number: reported_line,
+                                is_resolved: true,
+                                observed: false,
+                                template_args: template_args.clone(),
+                                call_kind: call_kinds.kind(symbol_ref),
+                                confidence: None,
+                            };
+                            if let Err(e) = code_graph.add_call_relation(relation) {
+                                warn!("Failed to add overridden call relation: {}", e);
+                            }
+                        }
+                        stats.resolved += 1;
+                        continue;
+                    }
+                    
+                    // 尝试解析被调用函数，gRPC 桩调用优先连到 RPC 定义
+                    let rpc = RpcIndex::resolve(rpc_calls, call_name).cloned();
+                    // Rust 方法调用的接收者类型已知时连到该类型 impl 块中的方法
+                    let method = receivers.as_ref().and_then(|receivers| {
+                        let owner = receivers.receiver_type(
+                            symbol_ref,
+                            (caller.line_start, caller.line_end),
+                            self.method_owners.get(&caller.id).map(String::as_str),
+                        )?;
+                        self.rust_methods.get(&(owner, call_name.to_string())).cloned()
+                    });
+                    // 经门面模块导入的名字连到门面中的再导出节点
+                    let reexport = || self._imported_reexport(file_path, call_name);
+                    // 经导入的模块或别名调用的函数优先于同名函数
+                    let imported = || {
+                        let code = import_source.as_deref()?;
+                        let receiver = call_receiver(code, symbol_ref.full_range().start_byte, call_name);
+                        self._imported_function(file_path, &language, receiver, call_name)
+                    };
+                    let arguments = overload_source.as_deref().and_then(|code| {
+                        let range = symbol_ref.full_range();
+                        CallArguments::parse(&language, code.get(range.start_byte..range.end_byte)?, call_name, code.get(..range.start_byte)?)
+                    });
+                    // Java 按包名、导入和类层次解析
+                    let java = java_source.as_deref().and_then(|code| {
+                        let receiver = call_receiver(code, symbol_ref.full_range().start_byte, call_name);
+                        self._java_method(file_path, caller, receiver, call_name, arguments.as_ref())
+                    });
+                    if java.is_some() {
+                        stats.qualified += 1;
+                    }
+                    let resolved = rpc
+                        .or(method)
+                        .map(|callee| (callee, None))
+                        .or(java)
+                        .or_else(|| reexport().or_else(imported).map(|callee| (callee, None)))
+                        .or_else(|| self._resolve_overloaded_callee(call_name, file_path, functions, code_graph, arguments.as_ref()));
+                    if let Some((callee_info, confidence)) = resolved {
                         // 创建已解析的调用关系
                         let relation = CallRelation {
                             caller_id: caller.id,
@@ -1253,8 +2634,12 @@ impl CodeParser {
                             callee_name: callee_info.name.clone(),
                             caller_file: caller.file_path.clone(),
                             callee_file: callee_info.file_path.clone(),
-                            line_number: call_line,
+                            line_number: reported_line,
                             is_resolved: true,
+                            observed: false,
+                            template_args: template_args.clone(),
+                            call_kind: call_kinds.kind(symbol_ref),
+                            confidence,
                         };
                         
                         if let Err(e) = code_graph.add_call_relation(relation) {
@@ -1264,13 +2649,21 @@ impl CodeParser {
                         }
                     } else {
                         // 创建未解析的调用关系
-                        self._create_unresolved_call_relation(
-                            caller, 
-                            call_name, 
-                            file_path, 
-                            call_line, 
-                            code_graph
-                        );
+                        let relation = CallRelation {
+                            caller_id: caller.id,
+                            callee_id: Uuid::nil(),
+                            caller_name: caller.name.clone(),
+                            callee_name: call_name.to_string(),
+                            caller_file: caller.file_path.clone(),
+                            callee_file: file_path.clone(),
+                            line_number: reported_line,
+                            is_resolved: false,
+                            observed: false,
+                            template_args,
+                            call_kind: call_kinds.kind(symbol_ref),
+                            confidence: None,
+                        };
+                        self._create_unresolved_call_relation(caller, relation, code_graph);
                         stats.unresolved += 1;
                     }
                 }
@@ -1314,6 +2707,40 @@ impl CodeParser {
         None
     }
     
+    /// 解析被调用函数，本文件或其他文件中有多个同名函数时按实参选择重载，同时返回选择的置信度
+    fn _resolve_overloaded_callee(
+        &self,
+        call_name: &str,
+        current_file: &PathBuf,
+        current_functions: &[FunctionInfo],
+        code_graph: &PetCodeGraph,
+        arguments: Option<&CallArguments>,
+    ) -> Option<(FunctionInfo, Option<f32>)> {
+        if let Some(arguments) = arguments {
+            let local: Vec<&FunctionInfo> = current_functions.iter().filter(|function| function.name == call_name).collect();
+            let candidates = if local.is_empty() {
+                self.file_functions
+                    .values()
+                    .flatten()
+                    .filter(|function| function.name == call_name && !function.is_reexport())
+                    .collect()
+            } else {
+                local
+            };
+            if let Some(picked) = self._pick_overload(&candidates, arguments) {
+                return Some(picked);
+            }
+        }
+        self._resolve_callee_function(call_name, current_file, current_functions, code_graph).map(|callee| (callee, None))
+    }
+
+    /// 按形参列表从同名候选函数中选出与实参最匹配的一个
+    fn _pick_overload(&self, candidates: &[&FunctionInfo], arguments: &CallArguments) -> Option<(FunctionInfo, Option<f32>)> {
+        let parameters: Vec<Option<&Parameters>> = candidates.iter().map(|function| self.function_parameters.get(&function.id)).collect();
+        let (index, confidence) = overloads::pick(&parameters, arguments)?;
+        Some((candidates[index].clone(), confidence))
+    }
+
     /// 解析限定函数名（如 Class.method, module.function）
     fn _resolve_qualified_function_name(
         &self,
@@ -1325,39 +2752,42 @@ impl CodeParser {
             let (prefix, method_name) = qualified_name.split_at(dot_pos);
             let method_name = &method_name[1..]; // 去掉点号
             
-            // 查找匹配的方法
+            // 查找匹配的方法，优先选择位于指定类/模块中的函数
             let candidates = code_graph.find_functions_by_name(method_name);
-            for func in candidates {
-                // 检查函数是否在指定的类/模块中
-                if func.namespace.contains(prefix) || func.name == method_name {
-                    return Some(func.clone());
-                }
+            let matched = candidates.iter().find(|func| func.namespace.contains(prefix)).or(candidates.first());
+            if let Some(func) = matched {
+                return Some((*func).clone());
             }
         }
         
         None
     }
     
-    /// 创建未解析的调用关系
+    /// 创建未解析的调用关系：为被调用名称创建占位节点，并把 `relation` 连到该节点
     fn _create_unresolved_call_relation(
         &self,
         caller: &FunctionInfo,
-        call_name: &str,
-        file_path: &PathBuf,
-        call_line: usize,
+        relation: CallRelation,
         code_graph: &mut PetCodeGraph,
     ) {
         // 为未解析的调用创建一个临时函数节点
         let temp_callee_id = Uuid::new_v4();
         let temp_callee = FunctionInfo {
             id: temp_callee_id,
-            name: call_name.to_string(),
-            file_path: file_path.clone(),
-            line_start: call_line,
-            line_end: call_line,
+            name: relation.callee_name.clone(),
+            file_path: relation.callee_file.clone(),
+            line_start: relation.line_number,
+            line_end: relation.line_number,
             namespace: "unresolved".to_string(),
             language: caller.language.clone(),
-            signature: Some(format!("unresolved_call_{}", call_name)),
+            signature: Some(format!("unresolved_call_{}", relation.callee_name)),
+            cell: None,
+            body_hash: None,
+            partial: false,
+            return_type: None,
+            parameters: Vec::new(),
+            parent_class_id: None,
+            kind: FunctionKind::Unresolved,
         };
         
         // 添加到代码图
@@ -1365,14 +2795,9 @@ impl CodeParser {
         
         // 创建未解析的调用关系
         let relation = CallRelation {
-            caller_id: caller.id,
             callee_id: temp_callee_id,
-            caller_name: caller.name.clone(),
-            callee_name: call_name.to_string(),
-            caller_file: caller.file_path.clone(),
-            callee_file: file_path.clone(),
-            line_number: call_line,
             is_resolved: false,
+            ..relation
         };
         
         if let Err(e) = code_graph.add_call_relation(relation) {
@@ -1420,6 +2845,10 @@ impl CodeParser {
                     callee_file: other_func.file_path.clone(),
                     line_number: main_function.line_start,
                     is_resolved: false, // 启发式调用标记为未解析
+                    observed: false,
+                    template_args: None,
+                    call_kind: CallKind::Sync,
+                    confidence: None,
                 };
                 
                 if let Err(e) = code_graph.add_call_relation(relation) {
@@ -1454,6 +2883,10 @@ impl CodeParser {
                         callee_file: other_func.file_path.clone(),
                         line_number: test_function.line_start,
                         is_resolved: false, // 启发式调用标记为未解析
+                        observed: false,
+                        template_args: None,
+                        call_kind: CallKind::Sync,
+                        confidence: None,
                     };
                     
                     if let Err(e) = code_graph.add_call_relation(relation) {
@@ -1471,6 +2904,8 @@ struct CallAnalysisStats {
     total: usize,
     resolved: usize,
     unresolved: usize,
+    /// 按 Java 包名、导入和类层次解析的调用
+    qualified: usize,
 }
 
 impl Default for CodeParser {
@@ -1479,7 +2914,60 @@ impl Default for CodeParser {
     }
 }
 
-#[cfg(test)]
+/// 方法所属类型的名字：直接所在的类体或 impl 块（`owners` 由 [`impl_owners`] 得到），
+/// 其次是声明中写出的 Go 接收者或 C++ 类外定义的限定名；自由函数为 `None`
+pub(crate) fn method_owner(
+    owners: &HashMap<Uuid, String>,
+    symbol: &dyn crate::codegraph::treesitter::ast_instance_structs::AstSymbolInstance,
+    language: &str,
+    file_content: &str,
+) -> Option<String> {
+    if let Some(owner) = owners.get(symbol.guid()) {
+        return Some(owner.clone());
+    }
+    if !matches!(language, "go" | "cpp" | "objc") {
+        return None;
+    }
+    let range = symbol.full_range();
+    let signature = signatures::extract(language, file_content.get(range.start_byte..range.end_byte)?, symbol.name())?;
+    signatures::declared_owner(language, &signature.text, symbol.name())
+}
+
+/// 模板实参列表的文本形式，例如 `int, Box<int>`；没有模板实参时为 None
+/// 单个名字的再导出语句，作为再导出节点的签名
+/// 调用处名字前写出的接收者：从调用起点到 `.name` 之前的文本，没有接收者时为 `None`
+fn call_receiver<'a>(code: &'a str, start: usize, call_name: &str) -> Option<&'a str> {
+    let rest = code.get(start..)?;
+    let end = rest.find(['(', '<', '\n']).unwrap_or(rest.len());
+    let receiver = rest[..end].trim_end().strip_suffix(call_name)?.strip_suffix('.')?;
+    (!receiver.is_empty()).then_some(receiver)
+}
+
+fn reexport_statement(language: &str, reexport: &ModuleImport, original: &str, name: &str) -> String {
+    let alias = if original == name { String::new() } else { format!(" as {}", name) };
+    match (language, reexport.is_glob()) {
+        ("rust", true) => format!("pub use {}::*", reexport.module),
+        ("rust", false) => format!("pub use {}::{}{}", reexport.module, original, alias),
+        ("python", true) => format!("from {} import *", reexport.module),
+        ("python", false) => format!("from {} import {}{}", reexport.module, original, alias),
+        (_, true) => format!("export * from '{}'", reexport.module),
+        (_, false) => format!("export {{ {}{} }} from '{}'", original, alias, reexport.module),
+    }
+}
+
+fn template_args_text(types: &[crate::codegraph::treesitter::ast_instance_structs::TypeDef]) -> Option<String> {
+    fn type_text(type_: &crate::codegraph::treesitter::ast_instance_structs::TypeDef) -> String {
+        let name = type_.name.clone().unwrap_or_default();
+        if type_.nested_types.is_empty() {
+            return name;
+        }
+        let nested: Vec<String> = type_.nested_types.iter().map(type_text).collect();
+        format!("{}<{}>", name, nested.join(", "))
+    }
+    (!types.is_empty()).then(|| types.iter().map(type_text).collect::<Vec<_>>().join(", "))
+}
+
+#[cfg(test)]
 mod tests {
     use super::*;
     use std::path::PathBuf;
@@ -1624,6 +3112,7 @@ if __name__ == "__main__":
             namespace: "global".to_string(),
             language: "rust".to_string(),
             signature: Some("fn main()".to_string()),
+            ..Default::default()
         };
         
         let func2 = FunctionInfo {
@@ -1635,6 +3124,7 @@ if __name__ == "__main__":
             namespace: "global".to_string(),
             language: "rust".to_string(),
             signature: Some("fn calculate()".to_string()),
+            ..Default::default()
         };
         
         // 添加到代码图
@@ -1677,6 +3167,7 @@ if __name__ == "__main__":
             namespace: "Calculator".to_string(),
             language: "rust".to_string(),
             signature: Some("fn process()".to_string()),
+            ..Default::default()
         };
         
         code_graph.add_function(method.clone());
@@ -1747,4 +3238,1328 @@ pub fn new_function() {
         // 清理
         temp_dir.close().unwrap();
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_strict_refresh_keeps_graph_consistent() {
+        let temp_dir = tempdir().unwrap();
+        let a = temp_dir.path().join("a.rs");
+        let b = temp_dir.path().join("b.rs");
+        fs::write(&a, "pub fn first() {\n    second();\n}\n\npub fn second() {}\n").unwrap();
+        fs::write(&b, "pub fn third() {\n    first();\n}\n").unwrap();
+
+        let options = AnalyzeOptions {
+            storage_dir: Some(temp_dir.path().join(".codegraph_db")),
+            force_rebuild: true,
+            strict: true,
+            ..Default::default()
+        };
+        let mut parser = CodeParser::with_options(options);
+        let mut call_graph = parser.build_petgraph_code_graph(temp_dir.path()).unwrap();
+        let mut entity_graph = EntityGraph::new();
+        assert!(call_graph.validate().is_empty());
+
+        // 先刷新 a.rs：删除的节点不在末尾，petgraph 会移动其他节点
+        fs::write(&a, "pub fn first() {}\n").unwrap();
+        parser.refresh_file(&a, &mut entity_graph, &mut call_graph).unwrap();
+        fs::remove_file(&b).unwrap();
+        parser.refresh_file(&b, &mut entity_graph, &mut call_graph).unwrap();
+
+        assert!(call_graph.validate().is_empty(), "{:?}", call_graph.validate());
+        assert!(call_graph.find_functions_by_name("third").is_empty());
+        assert_eq!(call_graph.find_functions_by_name("first").len(), 1);
+    }
+
+    #[test]
+    fn test_call_overrides_resolve_dynamic_calls() {
+        let temp_dir = tempdir().unwrap();
+        fs::create_dir_all(temp_dir.path().join("plugins")).unwrap();
+        fs::write(temp_dir.path().join("plugins/csv.py"), "def read_csv(path):\n    return []\n").unwrap();
+        fs::write(temp_dir.path().join("plugins/json.py"), "def read_json(path):\n    return {}\n").unwrap();
+        fs::write(
+            temp_dir.path().join("app.py"),
+            "def run(kind, path):\n    load_plugin(kind)\n    readers[kind](path)\n",
+        )
+        .unwrap();
+        fs::write(
+            temp_dir.path().join(OVERRIDES_FILE),
+            "[calls]\nload_plugin = \"plugins/csv.py::read_csv\"\n\n[[site]]\nfile = \"app.py\"\nline = 3\ntargets = [\"read_csv\", \"json.read_json\"]\n",
+        )
+        .unwrap();
+
+        let options = AnalyzeOptions {
+            storage_dir: Some(temp_dir.path().join(".codegraph_db")),
+            force_rebuild: true,
+            ..Default::default()
+        };
+        let graph = CodeParser::with_options(options).build_petgraph_code_graph(temp_dir.path()).unwrap();
+        let run = graph.find_functions_by_name("run")[0].id;
+        let mut callees: Vec<(String, usize)> = graph
+            .get_callees(&run)
+            .into_iter()
+            .filter(|(_, relation)| relation.is_resolved)
+            .map(|(callee, relation)| (callee.name.clone(), relation.line_number))
+            .collect();
+        callees.sort();
+        assert_eq!(
+            callees,
+            vec![("read_csv".to_string(), 2), ("read_csv".to_string(), 3), ("read_json".to_string(), 3)]
+        );
+    }
+
+    #[test]
+    fn test_methods_link_to_their_class() {
+        let temp_dir = tempdir().unwrap();
+        fs::write(
+            temp_dir.path().join("shapes.py"),
+            "class Square:\n    def area(self):\n        def unit():\n            return 1\n        return unit()\n\ndef total(shapes):\n    return 0\n",
+        )
+        .unwrap();
+        fs::write(temp_dir.path().join("point.go"), "package geo\n\ntype Point struct{ X int }\n").unwrap();
+        fs::write(temp_dir.path().join("methods.go"), "package geo\n\nfunc (p *Point) Norm() int { return p.X }\n\nfunc Origin() Point { return Point{} }\n").unwrap();
+        fs::write(temp_dir.path().join("lib.rs"), "pub struct Stack;\n\nimpl Stack {\n    pub fn push(&self) {}\n}\n\npub fn new() {}\n").unwrap();
+
+        let options = AnalyzeOptions {
+            storage_dir: Some(temp_dir.path().join(".codegraph_db")),
+            force_rebuild: true,
+            ..Default::default()
+        };
+        let graph = CodeParser::with_options(options).build_petgraph_code_graph(temp_dir.path()).unwrap();
+        let parent = |name: &str| graph.find_functions_by_name(name)[0].parent_class_id;
+        let file = |name: &str| temp_dir.path().join(name);
+
+        assert_eq!(parent("area"), Some(ClassInfo::stable_id("python", &file("shapes.py"), "global", "Square")));
+        assert_eq!(parent("unit"), None);
+        assert_eq!(parent("total"), None);
+        // Go 的方法与接收者类型在同一个包的不同文件中
+        assert_eq!(parent("Norm"), Some(ClassInfo::stable_id("go", &file("point.go"), "", "Point")));
+        assert_eq!(parent("Origin"), None);
+        assert_eq!(parent("push"), Some(ClassInfo::stable_id("rust", &file("lib.rs"), "crate", "Stack")));
+        assert_eq!(parent("new"), None);
+
+        let classes = crate::services::graph_export::extract_classes(&graph);
+        let square = classes.iter().find(|class| class.name == "Square").unwrap();
+        assert_eq!(Some(square.id), parent("area"));
+        assert_eq!(square.member_functions, vec![graph.find_functions_by_name("area")[0].id]);
+    }
+
+    #[test]
+    fn test_go_project_call_graph() {
+        let temp_dir = tempdir().unwrap();
+        fs::create_dir_all(temp_dir.path().join("shapes")).unwrap();
+        fs::write(
+            temp_dir.path().join("shapes/shapes.go"),
+            "package shapes\n\ntype Shape interface {\n\tArea() float64\n}\n\ntype Circle struct {\n\tR float64\n}\n\nfunc (c *Circle) Area() float64 {\n\treturn square(c.R) * 3.14\n}\n\nfunc square(x float64) float64 {\n\treturn x * x\n}\n",
+        )
+        .unwrap();
+        fs::write(
+            temp_dir.path().join("main.go"),
+            "package main\n\nimport \"example.com/demo/shapes\"\n\nfunc main() {\n\tc := &shapes.Circle{R: 2}\n\tprintln(c.Area())\n}\n",
+        )
+        .unwrap();
+
+        let options = AnalyzeOptions {
+            storage_dir: Some(temp_dir.path().join(".codegraph_db")),
+            force_rebuild: true,
+            ..Default::default()
+        };
+        let mut parser = CodeParser::with_options(options);
+        let graph = parser.build_petgraph_code_graph(temp_dir.path()).unwrap();
+
+        // 命名空间取自package声明，方法调用按名称解析到接收者方法
+        let area = graph.find_functions_by_name("Area")[0];
+        assert_eq!(area.namespace, "shapes");
+        assert_eq!(area.language, "go");
+        let main = graph.find_functions_by_name("main")[0];
+        assert_eq!(main.namespace, "main");
+        let callees: Vec<_> = graph.get_callees(&main.id).into_iter().map(|(f, _)| f.name.clone()).collect();
+        assert!(callees.contains(&"Area".to_string()));
+        let area_callees: Vec<_> = graph.get_callees(&area.id).into_iter().map(|(f, _)| f.name.clone()).collect();
+        assert_eq!(area_callees, vec!["square".to_string()]);
+    }
+
+    #[test]
+    fn test_objc_project_call_graph() {
+        let temp_dir = tempdir().unwrap();
+        fs::write(
+            temp_dir.path().join("Engine.m"),
+            "#import \"Engine.h\"\n\n@implementation Engine\n- (void)startWithPower:(int)power\n                  mode:(NSString *)mode {\n    [self ignite];\n}\n\n- (void)ignite {\n}\n@end\n",
+        )
+        .unwrap();
+        fs::write(
+            temp_dir.path().join("Car.mm"),
+            "#import \"Engine.h\"\n\n@interface Car : NSObject\n- (void)drive;\n@end\n\n@implementation Car\n- (void)drive {\n    Engine *engine = [[Engine alloc] init];\n    [engine startWithPower:3 mode:@\"eco\"];\n}\n@end\n",
+        )
+        .unwrap();
+
+        let options = AnalyzeOptions {
+            storage_dir: Some(temp_dir.path().join(".codegraph_db")),
+            force_rebuild: true,
+            ..Default::default()
+        };
+        let mut parser = CodeParser::with_options(options);
+        let graph = parser.build_petgraph_code_graph(temp_dir.path()).unwrap();
+
+        // 方法名由选择子各部分以下划线连接，行号指向原始文件
+        let start = graph.find_functions_by_name("startWithPower_mode")[0];
+        assert_eq!(start.language, "objc");
+        assert_eq!(start.line_start, 4);
+        let drive = graph.find_functions_by_name("drive")[0];
+        assert_eq!(drive.line_start, 8);
+        let callees: Vec<_> = graph.get_callees(&drive.id).into_iter().map(|(f, _)| f.name.clone()).collect();
+        assert!(callees.contains(&"startWithPower_mode".to_string()));
+        let start_callees: Vec<_> = graph.get_callees(&start.id).into_iter().map(|(f, _)| f.name.clone()).collect();
+        assert_eq!(start_callees, vec!["ignite".to_string()]);
+    }
+
+    #[test]
+    fn test_lua_project_call_graph() {
+        let temp_dir = tempdir().unwrap();
+        fs::create_dir_all(temp_dir.path().join("game/ai")).unwrap();
+        fs::write(
+            temp_dir.path().join("game/utils.lua"),
+            "local M = {}\n\nfunction M.clamp(x, lo, hi)\n  return math.max(lo, math.min(x, hi))\nend\n\nreturn M\n",
+        )
+        .unwrap();
+        fs::write(
+            temp_dir.path().join("game/ai/init.lua"),
+            "local M = {}\n\nfunction M.clamp(x)\n  return x\nend\n\nreturn M\n",
+        )
+        .unwrap();
+        fs::write(
+            temp_dir.path().join("game/enemy.lua"),
+            "local utils = require(\"game.utils\")\n\nlocal function update(e)\n  e.hp = utils.clamp(e.hp, 0, 100)\n  native_log(e.hp)\nend\n",
+        )
+        .unwrap();
+        fs::write(temp_dir.path().join("bindings.cpp"), "int native_log(int value) {\n    return value;\n}\n").unwrap();
+
+        let options = AnalyzeOptions {
+            storage_dir: Some(temp_dir.path().join(".codegraph_db")),
+            force_rebuild: true,
+            ..Default::default()
+        };
+        let mut parser = CodeParser::with_options(options);
+        let graph = parser.build_petgraph_code_graph(temp_dir.path()).unwrap();
+
+        // module.function 调用按 require 的模块解析；Lua 与 C++ 函数在同一张图中
+        let update = graph.find_functions_by_name("update")[0];
+        assert_eq!(update.language, "lua");
+        assert_eq!(update.namespace, "enemy");
+        let callees: Vec<_> = graph.get_callees(&update.id).into_iter().map(|(f, _)| f.clone()).collect();
+        let clamp = callees.iter().find(|f| f.name == "clamp").unwrap();
+        assert_eq!(clamp.namespace, "utils");
+        assert!(clamp.file_path.ends_with("game/utils.lua"));
+        assert!(callees.iter().any(|f| f.name == "native_log" && f.language == "cpp"));
+        let ai_clamp = graph.find_functions_by_name("clamp").into_iter().find(|f| f.namespace == "ai");
+        assert!(ai_clamp.is_some());
+    }
+
+    #[test]
+    fn test_kotlin_project_call_graph() {
+        let temp_dir = tempdir().unwrap();
+        fs::write(
+            temp_dir.path().join("Repository.kt"),
+            "package com.example.data\n\nclass Repository {\n    fun find(id: Int): String = \"user$id\"\n}\n",
+        )
+        .unwrap();
+        fs::write(
+            temp_dir.path().join("Service.kt"),
+            "package com.example.app\n\nimport com.example.data.Repository\n\nclass UserService(private val repo: Repository) {\n    fun load(id: Int): String {\n        return repo.find(id)\n    }\n}\n",
+        )
+        .unwrap();
+
+        let options = AnalyzeOptions {
+            storage_dir: Some(temp_dir.path().join(".codegraph_db")),
+            force_rebuild: true,
+            ..Default::default()
+        };
+        let mut parser = CodeParser::with_options(options);
+        let graph = parser.build_petgraph_code_graph(temp_dir.path()).unwrap();
+
+        let load = graph.find_functions_by_name("load")[0];
+        assert_eq!((load.language.as_str(), load.namespace.as_str()), ("kotlin", "com.example.app"));
+        assert_eq!(load.return_type.as_deref(), Some("String"));
+        let callees: Vec<_> = graph.get_callees(&load.id).into_iter().map(|(f, _)| f.clone()).collect();
+        let find = callees.iter().find(|f| f.name == "find").unwrap();
+        assert!(find.file_path.ends_with("Repository.kt"));
+        assert_eq!(find.namespace, "com.example.data");
+    }
+
+    #[test]
+    fn test_ruby_project_call_graph() {
+        let temp_dir = tempdir().unwrap();
+        fs::create_dir_all(temp_dir.path().join("lib")).unwrap();
+        fs::write(
+            temp_dir.path().join("lib/invoice.rb"),
+            "class Invoice\n  def initialize(items)\n    @items = items\n  end\n\n  def total\n    @items.sum\n  end\nend\n",
+        )
+        .unwrap();
+        fs::write(
+            temp_dir.path().join("billing.rb"),
+            "require_relative 'lib/invoice'\n\ndef checkout(items)\n  invoice = Invoice.new(items)\n  invoice.total\nend\n",
+        )
+        .unwrap();
+
+        let options = AnalyzeOptions {
+            storage_dir: Some(temp_dir.path().join(".codegraph_db")),
+            force_rebuild: true,
+            ..Default::default()
+        };
+        let mut parser = CodeParser::with_options(options);
+        let graph = parser.build_petgraph_code_graph(temp_dir.path()).unwrap();
+
+        let checkout = graph.find_functions_by_name("checkout")[0];
+        assert_eq!(checkout.language, "ruby");
+        let callees: Vec<_> = graph.get_callees(&checkout.id).into_iter().map(|(f, _)| f.clone()).collect();
+        let total = callees.iter().find(|f| f.name == "total").unwrap();
+        assert!(total.file_path.ends_with("lib/invoice.rb"));
+        assert!(parser.is_supported_file(&temp_dir.path().join("Rakefile")));
+    }
+
+    #[test]
+    fn test_swift_project_call_graph() {
+        let temp_dir = tempdir().unwrap();
+        fs::write(
+            temp_dir.path().join("Store.swift"),
+            "class Store {\n    func clear() {}\n}\n\nextension Store {\n    func reload() -> Int {\n        return 0\n    }\n}\n",
+        )
+        .unwrap();
+        fs::write(
+            temp_dir.path().join("App.swift"),
+            "import Foundation\n\nfunc refresh(store: Store) -> Int {\n    store.clear()\n    return store.reload()\n}\n",
+        )
+        .unwrap();
+
+        let options = AnalyzeOptions {
+            storage_dir: Some(temp_dir.path().join(".codegraph_db")),
+            force_rebuild: true,
+            ..Default::default()
+        };
+        let mut parser = CodeParser::with_options(options);
+        let graph = parser.build_petgraph_code_graph(temp_dir.path()).unwrap();
+
+        let refresh = graph.find_functions_by_name("refresh")[0];
+        assert_eq!((refresh.language.as_str(), refresh.return_type.as_deref()), ("swift", Some("Int")));
+        let mut callees: Vec<_> = graph.get_callees(&refresh.id).into_iter().map(|(f, _)| f.name.clone()).collect();
+        callees.sort();
+        assert_eq!(callees, vec!["clear", "reload"]);
+        let reload = graph.find_functions_by_name("reload")[0];
+        assert!(reload.file_path.ends_with("Store.swift"));
+    }
+
+    #[test]
+    fn test_scala_project_call_graph() {
+        let temp_dir = tempdir().unwrap();
+        fs::write(
+            temp_dir.path().join("Cart.scala"),
+            "package com.example.shop\n\nclass Cart(items: List[Int]) {\n  def total: Int = items.sum\n}\n",
+        )
+        .unwrap();
+        fs::write(
+            temp_dir.path().join("Main.scala"),
+            "package com.example.app\n\nimport com.example.shop.Cart\n\nobject Main {\n  def report(cart: Cart): Int = cart.total\n}\n",
+        )
+        .unwrap();
+
+        let options = AnalyzeOptions {
+            storage_dir: Some(temp_dir.path().join(".codegraph_db")),
+            force_rebuild: true,
+            ..Default::default()
+        };
+        let mut parser = CodeParser::with_options(options);
+        let graph = parser.build_petgraph_code_graph(temp_dir.path()).unwrap();
+
+        // `cart.total` calls the parameterless method without parentheses
+        let report = graph.find_functions_by_name("report")[0];
+        assert_eq!((report.namespace.as_str(), report.return_type.as_deref()), ("com.example.app", Some("Int")));
+        let callees: Vec<_> = graph.get_callees(&report.id).into_iter().map(|(f, _)| f.clone()).collect();
+        let total = callees.iter().find(|f| f.name == "total").unwrap();
+        assert!(total.file_path.ends_with("Cart.scala"));
+        assert_eq!(total.namespace, "com.example.shop");
+    }
+
+    #[test]
+    fn test_bash_script_dependency_graph() {
+        let temp_dir = tempdir().unwrap();
+        fs::create_dir_all(temp_dir.path().join("scripts/lib")).unwrap();
+        fs::write(
+            temp_dir.path().join("scripts/lib/common.sh"),
+            "log() {\n  echo \"[$(date)] $*\"\n}\n",
+        )
+        .unwrap();
+        fs::write(temp_dir.path().join("scripts/build.sh"), "#!/bin/sh\nmake all\n").unwrap();
+        fs::write(
+            temp_dir.path().join("scripts/release.sh"),
+            "#!/usr/bin/env bash\nsource \"$(dirname \"$0\")/lib/common.sh\"\n\nrelease() {\n  log releasing\n  ./scripts/build.sh\n}\n\nrelease\n",
+        )
+        .unwrap();
+
+        let options = AnalyzeOptions {
+            storage_dir: Some(temp_dir.path().join(".codegraph_db")),
+            force_rebuild: true,
+            ..Default::default()
+        };
+        let mut parser = CodeParser::with_options(options);
+        let graph = parser.build_petgraph_code_graph(temp_dir.path()).unwrap();
+
+        // 脚本本身是一个函数：顶层命令属于脚本，函数体内的命令属于该函数
+        let script = graph.find_functions_by_name("release.sh")[0];
+        assert_eq!((script.language.as_str(), script.namespace.as_str()), ("bash", "release"));
+        let script_callees: Vec<_> = graph.get_callees(&script.id).into_iter().map(|(f, _)| f.name.clone()).collect();
+        assert!(script_callees.contains(&"common.sh".to_string()));
+        assert!(script_callees.contains(&"release".to_string()));
+
+        let release = graph.find_functions_by_name("release")[0];
+        let callees: Vec<_> = graph.get_callees(&release.id).into_iter().map(|(f, r)| (f.clone(), r.is_resolved)).collect();
+        let build = callees.iter().find(|(f, _)| f.name == "build.sh").unwrap();
+        assert!(build.1 && build.0.file_path.ends_with("scripts/build.sh"));
+        let log = callees.iter().find(|(f, _)| f.name == "log").unwrap();
+        assert!(log.1 && log.0.file_path.ends_with("scripts/lib/common.sh"));
+    }
+
+    #[test]
+    fn test_sql_procedure_call_chain() {
+        let temp_dir = tempdir().unwrap();
+        fs::create_dir_all(temp_dir.path().join("db/procs")).unwrap();
+        fs::write(
+            temp_dir.path().join("db/procs/orders.sql"),
+            "CREATE PROCEDURE dbo.usp_PlaceOrder @CustomerId INT\nAS\nBEGIN\n    EXEC dbo.usp_ReserveStock @CustomerId;\n    EXEC usp_Audit 'order';\nEND\nGO\n",
+        )
+        .unwrap();
+        fs::write(
+            temp_dir.path().join("db/procs/stock.sql"),
+            "CREATE PROCEDURE dbo.usp_ReserveStock @CustomerId INT\nAS\nBEGIN\n    UPDATE Stock SET Reserved = 1 WHERE CustomerId = @CustomerId;\n    EXEC usp_Audit 'stock';\nEND\nGO\n\nCREATE PROCEDURE audit.usp_Audit @Kind NVARCHAR(20)\nAS\n    INSERT INTO AuditLog (Kind) VALUES (@Kind);\nGO\n",
+        )
+        .unwrap();
+
+        let options = AnalyzeOptions {
+            storage_dir: Some(temp_dir.path().join(".codegraph_db")),
+            force_rebuild: true,
+            ..Default::default()
+        };
+        let mut parser = CodeParser::with_options(options);
+        let graph = parser.build_petgraph_code_graph(temp_dir.path()).unwrap();
+
+        // 存储过程之间的 EXEC 调用跨文件解析，形成调用链
+        let place = graph.find_functions_by_name("usp_placeorder")[0];
+        assert_eq!((place.language.as_str(), place.namespace.as_str()), ("sql", "dbo"));
+        let callees: Vec<_> = graph.get_callees(&place.id).into_iter().map(|(f, r)| (f.clone(), r.is_resolved)).collect();
+        let reserve = callees.iter().find(|(f, _)| f.name == "usp_reservestock").unwrap();
+        assert!(reserve.1 && reserve.0.file_path.ends_with("db/procs/stock.sql"));
+        assert!(callees.iter().any(|(f, resolved)| f.name == "usp_audit" && *resolved));
+
+        let reserve_callees: Vec<_> = graph.get_callees(&reserve.0.id).into_iter().map(|(f, _)| f.name.clone()).collect();
+        assert_eq!(reserve_callees, vec!["usp_audit".to_string()]);
+    }
+
+    #[test]
+    fn test_notebook_functions_carry_cell() {
+        let temp_dir = tempdir().unwrap();
+        let notebook = serde_json::json!({
+            "metadata": {"kernelspec": {"language": "python"}},
+            "nbformat": 4,
+            "cells": [
+                {"cell_type": "markdown", "source": ["# Cleaning\n"]},
+                {"cell_type": "code", "source": ["%load_ext autoreload\n", "def clean(df):\n", "    return df.dropna()\n"]},
+                {"cell_type": "code", "source": ["import helpers\n", "\n", "def load(path):\n", "    !ls data\n", "    df = helpers.read(path)\n", "    return clean(df)\n"]}
+            ]
+        });
+        fs::write(temp_dir.path().join("analysis.ipynb"), notebook.to_string()).unwrap();
+        fs::write(temp_dir.path().join("helpers.py"), "def read(path):\n    return open(path)\n").unwrap();
+
+        let options = AnalyzeOptions {
+            storage_dir: Some(temp_dir.path().join(".codegraph_db")),
+            force_rebuild: true,
+            ..Default::default()
+        };
+        let mut parser = CodeParser::with_options(options);
+        let graph = parser.build_petgraph_code_graph(temp_dir.path()).unwrap();
+
+        // 行号相对所在代码单元，单元序号包含 markdown 单元
+        let load = graph.find_functions_by_name("load")[0];
+        assert_eq!((load.language.as_str(), load.cell, load.line_start, load.line_end), ("python", Some(2), 3, 6));
+        let clean = graph.find_functions_by_name("clean")[0];
+        assert_eq!((clean.cell, clean.line_start, clean.line_end), (Some(1), 2, 3));
+
+        // 跨单元和跨文件的调用都能解析，调用行号同样相对代码单元
+        let callees = graph.get_callees(&load.id);
+        let to_clean = callees.iter().find(|(f, _)| f.name == "clean").unwrap();
+        assert!(to_clean.1.is_resolved && to_clean.1.line_number == 6);
+        assert!(callees.iter().any(|(f, r)| f.name == "read" && r.is_resolved && f.file_path.ends_with("helpers.py")));
+        assert!(callees.iter().all(|(f, _)| f.name != "ls"));
+    }
+
+    #[test]
+    fn test_rust_trait_method_calls() {
+        let temp_dir = tempdir().unwrap();
+        fs::create_dir_all(temp_dir.path().join("src")).unwrap();
+        fs::write(
+            temp_dir.path().join("src/shapes.rs"),
+            "pub trait Shape {\n    fn area(&self) -> f64;\n}\n\npub struct Circle { r: f64 }\npub struct Square { s: f64 }\n\nimpl Shape for Circle {\n    fn area(&self) -> f64 { 3.14 * self.r * self.r }\n}\n\nimpl Shape for Square {\n    fn area(&self) -> f64 { self.s * self.s }\n}\n\nimpl Square {\n    pub fn new(s: f64) -> Self { Square { s } }\n    pub fn doubled(&self) -> f64 { self.area() * 2.0 }\n    pub fn inner(&self, c: Circle) -> f64 { self.c.area() }\n}\n",
+        )
+        .unwrap();
+        fs::write(
+            temp_dir.path().join("src/main.rs"),
+            "use crate::shapes::{Circle, Shape, Square};\n\nfn total(c: &Circle) -> f64 {\n    let sq = Square::new(2.0);\n    let other: Square = make();\n    c.area() + sq.area() + other.area()\n}\n",
+        )
+        .unwrap();
+        let options = AnalyzeOptions {
+            storage_dir: Some(temp_dir.path().join(".codegraph_db")),
+            force_rebuild: true,
+            ..Default::default()
+        };
+        let graph = CodeParser::with_options(options).build_petgraph_code_graph(temp_dir.path()).unwrap();
+
+        // 各 impl 块中的 area 按起始行区分：Circle 第9行，Square 第13行
+        let area_lines = |caller: &str| {
+            let caller = graph.find_functions_by_name(caller)[0];
+            let mut lines: Vec<usize> = graph
+                .get_callees(&caller.id)
+                .into_iter()
+                .filter(|(f, r)| f.name == "area" && r.is_resolved)
+                .map(|(f, _)| f.line_start)
+                .collect();
+            lines.sort();
+            lines
+        };
+        assert_eq!(area_lines("total"), vec![9, 13, 13]);
+        assert_eq!(area_lines("doubled"), vec![13]);
+        // `self.c` 是字段，类型未知时按名称解析，不会当作 self 或参数 `c`
+        assert!(!area_lines("inner").iter().any(|line| *line == 9 || *line == 13));
+    }
+
+    #[test]
+    fn test_rust_macro_generated_functions() {
+        let temp_dir = tempdir().unwrap();
+        fs::create_dir_all(temp_dir.path().join("src")).unwrap();
+        fs::write(
+            temp_dir.path().join("src/macros.rs"),
+            "macro_rules! getter {\n    ($name:ident, $value:expr) => {\n        pub fn $name() -> i32 { checked($value) }\n    };\n}\n\nmacro_rules! handlers {\n    ($($name:ident),* $(,)?) => {\n        $( fn $name() { log_call(stringify!($name)); } )*\n    };\n}\n\nfn checked(v: i32) -> i32 { v }\nfn log_call(_name: &str) {}\n",
+        )
+        .unwrap();
+        fs::write(
+            temp_dir.path().join("src/main.rs"),
+            "getter!(answer, 42);\nhandlers!(on_open, on_close);\n\nfn main() {\n    let x = answer();\n    on_open();\n    handlers!();\n    println!(\"{}\", x);\n}\n",
+        )
+        .unwrap();
+        let options = AnalyzeOptions {
+            storage_dir: Some(temp_dir.path().join(".codegraph_db")),
+            force_rebuild: true,
+            ..Default::default()
+        };
+        let graph = CodeParser::with_options(options).build_petgraph_code_graph(temp_dir.path()).unwrap();
+
+        let callees = |caller: &str| {
+            let caller = graph.find_functions_by_name(caller)[0];
+            let mut names: Vec<(String, usize)> = graph
+                .get_callees(&caller.id)
+                .into_iter()
+                .map(|(f, r)| (f.name.clone(), r.line_number))
+                .collect();
+            names.sort();
+            names
+        };
+        // 宏调用生成的函数位于调用处，按普通函数解析
+        let answer = graph.find_functions_by_name("answer")[0];
+        assert!(answer.file_path.ends_with("src/main.rs"));
+        assert_eq!((answer.line_start, answer.signature.as_deref(), answer.kind), (1, Some("fn answer"), FunctionKind::Generated));
+        assert_eq!(graph.find_functions_by_name("on_close").len(), 1);
+        let getter = graph.find_functions_by_name("getter!")[0];
+        assert_eq!((getter.signature.as_deref(), getter.kind), (Some("macro_rules! getter"), FunctionKind::Macro));
+        // 函数体中的宏调用连到宏，标准库宏不产生节点
+        assert_eq!(
+            callees("main"),
+            vec![("answer".to_string(), 5), ("handlers!".to_string(), 7), ("on_open".to_string(), 6)]
+        );
+        assert!(graph.find_functions_by_name("println!").is_empty());
+        // 生成的函数指向生成它的宏，宏指向转写器中调用的函数
+        assert_eq!(callees("answer"), vec![("getter!".to_string(), 1)]);
+        assert_eq!(callees("on_close"), vec![("handlers!".to_string(), 2)]);
+        assert_eq!(callees("getter!"), vec![("checked".to_string(), 3)]);
+        assert_eq!(callees("handlers!"), vec![("log_call".to_string(), 9)]);
+    }
+
+    #[test]
+    fn test_async_call_kinds() {
+        let temp_dir = tempdir().unwrap();
+        fs::write(
+            temp_dir.path().join("jobs.py"),
+            "import asyncio\n\nasync def fetch():\n    pass\n\nasync def refresh():\n    pass\n\ndef log():\n    pass\n\nasync def run():\n    await fetch()\n    asyncio.create_task(refresh())\n    log()\n",
+        )
+        .unwrap();
+        let options = AnalyzeOptions {
+            storage_dir: Some(temp_dir.path().join(".codegraph_db")),
+            force_rebuild: true,
+            ..Default::default()
+        };
+        let graph = CodeParser::with_options(options).build_petgraph_code_graph(temp_dir.path()).unwrap();
+
+        let run = graph.find_functions_by_name("run")[0];
+        let mut kinds: Vec<(String, CallKind, bool)> = graph
+            .get_callees(&run.id)
+            .into_iter()
+            .map(|(f, r)| (f.name.clone(), r.call_kind, r.is_resolved))
+            .collect();
+        kinds.sort_by(|a, b| a.0.cmp(&b.0));
+        // 派生函数本身是普通调用，未解析的调用也带有调用方式
+        assert_eq!(
+            kinds,
+            vec![
+                ("create_task".to_string(), CallKind::Sync, false),
+                ("fetch".to_string(), CallKind::Await, true),
+                ("log".to_string(), CallKind::Sync, true),
+                ("refresh".to_string(), CallKind::Spawn, true),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_closures_become_lambda_nodes() {
+        let temp_dir = tempdir().unwrap();
+        let files = [
+            ("jobs.rs", "fn helper(x: i32) -> i32 {\n    x\n}\n\nfn run(items: Vec<i32>) -> Vec<i32> {\n    items.into_iter().map(|x| {\n        helper(x)\n    }).collect()\n}\n"),
+            ("jobs.py", "def helper(x):\n    return x\n\ndef run(items):\n    return sorted(items, key=lambda x:\n        helper(x))\n"),
+            ("server.js", "function helper() {}\n\nfunction run(app) {\n  app.get('/', function () {\n    helper();\n  });\n}\n"),
+            ("handler.ts", "function helper(): void {}\n\nexport const handler = (x: number) => {\n  [x].forEach((y) => {\n    helper();\n  });\n};\n"),
+        ];
+        for (name, code) in files {
+            fs::write(temp_dir.path().join(name), code).unwrap();
+        }
+        let options = AnalyzeOptions {
+            storage_dir: Some(temp_dir.path().join(".codegraph_db")),
+            force_rebuild: true,
+            ..Default::default()
+        };
+        let graph = CodeParser::with_options(options).build_petgraph_code_graph(temp_dir.path()).unwrap();
+
+        // 外层函数调用匿名函数，匿名函数中的调用从匿名函数出发
+        for (parent, closure, language) in [
+            ("run", "run::<lambda@6>", "rust"),
+            ("run", "run::<lambda@5>", "python"),
+            ("run", "run::<lambda@4>", "javascript"),
+            ("handler", "handler::<lambda@4>", "typescript"),
+        ] {
+            let parent = graph.get_all_functions().into_iter().find(|f| f.name == parent && f.language == language).unwrap();
+            let lambda = graph.find_functions_by_name(closure).into_iter().find(|f| f.language == language).unwrap();
+            let callers: Vec<&str> = graph.get_callers(&lambda.id).into_iter().map(|(f, _)| f.name.as_str()).collect();
+            assert_eq!(callers, vec![parent.name.as_str()], "{}", closure);
+            let callees: Vec<&str> = graph.get_callees(&lambda.id).into_iter().filter(|(_, r)| r.is_resolved).map(|(f, _)| f.name.as_str()).collect();
+            assert_eq!(callees, vec!["helper"], "{}", closure);
+            assert!(graph.get_callees(&parent.id).iter().all(|(f, _)| f.name != "helper"), "{}", closure);
+        }
+        assert!(graph.get_all_functions().iter().all(|f| !f.name.is_empty()));
+    }
+
+    #[test]
+    fn test_entity_graph_links_implementations() {
+        let temp_dir = tempdir().unwrap();
+        let root = temp_dir.path();
+        fs::write(root.join("Shape.java"), "interface Shape {\n    double area();\n}\nabstract class Base implements Shape {\n    public abstract double area();\n}\nclass Circle extends Base {\n    public double area() { return 1.0; }\n}\nclass Square implements Shape {\n    public double area() { return 2.0; }\n    public double side() { return 1.0; }\n}\n").unwrap();
+        fs::write(root.join("shape.ts"), "export interface Shape {\n    area(): number;\n}\nexport class Hexagon implements Shape {\n    area(): number { return 6; }\n}\n").unwrap();
+        fs::write(root.join("shape.cpp"), "class Shape {\npublic:\n    virtual double area() const = 0;\n};\nclass Circle : public Shape {\npublic:\n    double area() const override { return 1.0; }\n};\n").unwrap();
+        let options = AnalyzeOptions {
+            storage_dir: Some(root.join(".codegraph_db")),
+            force_rebuild: true,
+            ..Default::default()
+        };
+        let mut parser = CodeParser::with_options(options);
+        let call_graph = parser.build_petgraph_code_graph(root).unwrap();
+        let entity_graph = parser.build_entity_graph(root, &call_graph);
+
+        let implementations = |language: &str| -> Vec<(String, usize)> {
+            entity_graph
+                .find_implementations("Shape", "area")
+                .into_iter()
+                .filter(|(class, _)| class.language == language)
+                .map(|(class, function)| (class.name.clone(), function.line_start))
+                .collect()
+        };
+        // 抽象类中的抽象方法不是实现；经抽象类间接实现接口的类也算
+        assert_eq!(implementations("java"), vec![("Circle".to_string(), 8), ("Square".to_string(), 11)]);
+        assert_eq!(implementations("typescript"), vec![("Hexagon".to_string(), 5)]);
+        assert_eq!(implementations("cpp"), vec![("Circle".to_string(), 7)]);
+
+        // 有实现的方法与调用图中的函数是同一个节点
+        let (_, hexagon_area) = entity_graph.find_implementations("Shape", "area").into_iter().find(|(c, _)| c.name == "Hexagon").unwrap();
+        assert!(call_graph.get_function_by_id(&hexagon_area.id).is_some());
+
+        let square = entity_graph.find_classes_by_name("Square")[0];
+        assert_eq!(square.implemented_interfaces, vec!["Shape"]);
+        let circle = entity_graph.find_classes_by_name("Circle").into_iter().find(|c| c.language == "java").unwrap();
+        assert_eq!(circle.parent_class.as_deref(), Some("Base"));
+        let supertypes: Vec<&str> = entity_graph.get_supertypes(&circle.id).iter().map(|c| c.name.as_str()).collect();
+        assert_eq!(supertypes, vec!["Base", "Shape"]);
+        assert!(entity_graph.find_implementations("Base", "side").is_empty());
+    }
+
+    #[test]
+    fn test_decorators_link_entry_points() {
+        use petgraph::visit::EdgeRef;
+        use crate::codegraph::types::EntityNode;
+        let temp_dir = tempdir().unwrap();
+        let root = temp_dir.path();
+        fs::write(root.join("app.py"), "def login_required(f):\n    return f\n\n@app.route('/users')\n@login_required\ndef users():\n    pass\n").unwrap();
+        fs::write(root.join("Jobs.java"), "@Component\nclass Jobs {\n    @Scheduled(fixedRate = 1000)\n    public void run() {}\n}\n").unwrap();
+        fs::write(root.join("users.ts"), "@Controller('users')\nexport class Users {\n  @Get(':id')\n  find(id: string) { return id; }\n}\n").unwrap();
+        let options = AnalyzeOptions {
+            storage_dir: Some(root.join(".codegraph_db")),
+            force_rebuild: true,
+            framework_entry_points: true,
+            ..Default::default()
+        };
+        let mut parser = CodeParser::with_options(options);
+        let call_graph = parser.build_petgraph_code_graph(root).unwrap();
+        let entity_graph = parser.build_entity_graph(root, &call_graph);
+
+        let entry_points: Vec<(&str, &str, &str)> = entity_graph
+            .entry_points()
+            .into_iter()
+            .map(|(function, edge)| {
+                let metadata = edge.metadata.as_ref().unwrap();
+                (function.name.as_str(), metadata["decorator"].as_str().unwrap(), metadata["entry_point"].as_str().unwrap())
+            })
+            .collect();
+        assert_eq!(entry_points, vec![("run", "Scheduled", "scheduled"), ("users", "app.route", "http"), ("find", "Get", "http")]);
+
+        // 项目中定义的装饰器连到其函数，外部装饰器和类上的注解各有一个节点
+        let decorators = |name: &str| -> Vec<String> {
+            let function = entity_graph.get_all_functions().into_iter().find(|f| f.name == name).unwrap();
+            let node = entity_graph.get_node_index(&function.id).unwrap();
+            let mut names: Vec<String> = entity_graph
+                .graph
+                .edges(node)
+                .filter(|edge| matches!(edge.weight().edge_type, EntityEdgeType::DecoratedBy))
+                .map(|edge| match &entity_graph.graph[edge.target()] {
+                    EntityNode::Function(f) => format!("{} {}", f.namespace, f.name),
+                    EntityNode::Class(c) => c.name.clone(),
+                })
+                .collect();
+            names.sort();
+            names
+        };
+        let login_required = call_graph.find_functions_by_name("login_required")[0];
+        assert_eq!(decorators("users"), vec!["decorator app.route".to_string(), format!("{} login_required", login_required.namespace)]);
+        let users_class = entity_graph.find_classes_by_name("Users")[0];
+        assert_eq!(entity_graph.graph.edges(entity_graph.get_node_index(&users_class.id).unwrap()).count(), 2);
+
+        // 框架节点调用被注册的函数
+        let route = call_graph.find_functions_by_name("@app.route")[0];
+        assert_eq!(route.namespace, "framework");
+        let callees: Vec<&str> = call_graph.get_callees(&route.id).iter().map(|(f, _)| f.name.as_str()).collect();
+        assert_eq!(callees, vec!["users"]);
+        let run = call_graph.find_functions_by_name("run")[0];
+        let callers: Vec<&str> = call_graph.get_callers(&run.id).iter().map(|(f, _)| f.name.as_str()).collect();
+        assert_eq!(callers, vec!["@Scheduled"]);
+    }
+
+    #[test]
+    fn test_functions_carry_normalized_body_hash() {
+        let temp_dir = tempdir().unwrap();
+        let root = temp_dir.path();
+        fs::write(root.join("a.py"), "def slugify(text):\n    # lower-case first\n    return text.lower().replace(' ', '-')\n\ndef other():\n    return 1\n").unwrap();
+        fs::write(root.join("b.py"), "def slugify(text):\n    return text.lower().replace(' ',  '-')\n").unwrap();
+        let options = AnalyzeOptions {
+            storage_dir: Some(root.join(".codegraph_db")),
+            force_rebuild: true,
+            ..Default::default()
+        };
+        let graph = CodeParser::with_options(options).build_petgraph_code_graph(root).unwrap();
+
+        let hash = |name: &str, file: &str| {
+            graph.find_functions_by_name(name).into_iter().find(|f| f.file_path.ends_with(file)).unwrap().body_hash.clone()
+        };
+        assert!(hash("slugify", "a.py").is_some());
+        assert_eq!(hash("slugify", "a.py"), hash("slugify", "b.py"));
+        assert_ne!(hash("slugify", "a.py"), hash("other", "a.py"));
+    }
+
+    #[test]
+    fn test_reexports_through_facade_modules() {
+        let temp_dir = tempdir().unwrap();
+        let root = temp_dir.path();
+        fs::create_dir_all(root.join("web")).unwrap();
+        fs::create_dir_all(root.join("pkg")).unwrap();
+        fs::create_dir_all(root.join("src")).unwrap();
+        fs::write(root.join("web/impl.ts"), "export function render() {}\n").unwrap();
+        fs::write(root.join("web/index.ts"), "export * from './impl';\n").unwrap();
+        fs::write(root.join("web/app.ts"), "import { render } from './index';\n\nfunction start() {\n    render();\n}\n").unwrap();
+        fs::write(root.join("pkg/core.py"), "def load():\n    pass\n").unwrap();
+        fs::write(root.join("pkg/__init__.py"), "from .core import load\n").unwrap();
+        fs::write(root.join("main.py"), "from pkg import load\n\ndef boot():\n    load()\n").unwrap();
+        fs::write(root.join("src/inner.rs"), "pub fn helper() {}\n").unwrap();
+        fs::write(root.join("src/api.rs"), "pub use crate::inner::helper as assist;\n").unwrap();
+        fs::write(root.join("src/main.rs"), "use crate::api::assist;\n\nfn main() {\n    assist();\n}\n").unwrap();
+        let options = AnalyzeOptions {
+            storage_dir: Some(root.join(".codegraph_db")),
+            force_rebuild: true,
+            ..Default::default()
+        };
+        let graph = CodeParser::with_options(options).build_petgraph_code_graph(root).unwrap();
+
+        let definition = |name: &str| graph.find_functions_by_name(name).into_iter().find(|f| !f.is_reexport()).unwrap();
+        for (name, facade, signature, user) in [
+            ("render", "web/index.ts", "export * from './impl'", "start"),
+            ("load", "pkg/__init__.py", "from .core import load", "boot"),
+            ("helper", "src/api.rs", "pub use crate::inner::helper as assist", "main"),
+        ] {
+            let function = definition(name);
+            // 被再导出的函数只被门面中的再导出节点直接调用，使用者经门面连到它
+            let callers = graph.get_callers(&function.id);
+            assert_eq!(callers.len(), 1, "{}", name);
+            let (node, _) = callers[0];
+            assert!(node.file_path.ends_with(facade));
+            assert_eq!((node.signature.as_deref(), node.kind), (Some(signature), FunctionKind::Reexport));
+            let users: Vec<&str> = graph.get_users(&function.id).into_iter().map(|(f, _)| f.name.as_str()).collect();
+            assert_eq!(users, vec![user]);
+        }
+        assert_eq!(graph.validate(), vec![]);
+    }
+
+    #[test]
+    fn test_python_calls_resolve_through_imports() {
+        let temp_dir = tempdir().unwrap();
+        let root = temp_dir.path();
+        fs::create_dir_all(root.join("pkg")).unwrap();
+        fs::write(root.join("pkg/core.py"), "def load():\n    return 1\n").unwrap();
+        fs::write(root.join("pkg/legacy.py"), "def load():\n    return 2\n\ndef parse():\n    return 3\n").unwrap();
+        fs::write(root.join("tools.py"), "def parse():\n    return 4\n").unwrap();
+        fs::write(
+            root.join("app.py"),
+            "import pkg.core as core\nfrom pkg import legacy\nfrom pkg.legacy import parse as read\n\ndef run():\n    total = 0\n    core.load()\n    legacy.load()\n    read()\n",
+        )
+        .unwrap();
+        let options = AnalyzeOptions {
+            storage_dir: Some(root.join(".codegraph_db")),
+            force_rebuild: true,
+            ..Default::default()
+        };
+        let graph = CodeParser::with_options(options).build_petgraph_code_graph(root).unwrap();
+
+        let run = graph.find_functions_by_name("run")[0];
+        let mut calls: Vec<(usize, String, String)> = graph
+            .get_callees(&run.id)
+            .into_iter()
+            .map(|(f, r)| {
+                let file = f.file_path.strip_prefix(root).unwrap_or(&f.file_path).display().to_string();
+                (r.line_number, f.name.clone(), file)
+            })
+            .collect();
+        calls.sort();
+        // 同名函数按导入的模块区分
+        assert_eq!(
+            calls,
+            vec![
+                (7, "load".to_string(), "pkg/core.py".to_string()),
+                (8, "load".to_string(), "pkg/legacy.py".to_string()),
+                (9, "parse".to_string(), "pkg/legacy.py".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_typescript_path_aliases_resolve_calls() {
+        let temp_dir = tempdir().unwrap();
+        let root = temp_dir.path();
+        fs::create_dir_all(root.join("src/app")).unwrap();
+        fs::create_dir_all(root.join("src/legacy")).unwrap();
+        fs::write(
+            root.join("tsconfig.json"),
+            "{\n  // aliases\n  \"compilerOptions\": {\n    \"baseUrl\": \".\",\n    \"paths\": { \"@app/*\": [\"src/app/*\"], \"@legacy/*\": [\"src/legacy/*\"] },\n  },\n}\n",
+        )
+        .unwrap();
+        fs::write(root.join("src/app/utils.ts"), "export function helper(): number {\n    return 1;\n}\n").unwrap();
+        fs::write(root.join("src/legacy/utils.ts"), "export function helper(): number {\n    return 2;\n}\n").unwrap();
+        fs::write(
+            root.join("src/main.ts"),
+            "import { helper as run } from '@app/utils';\nimport * as legacy from '@legacy/utils';\n\nfunction start() {\n    const total = 0;\n    run();\n    legacy.helper();\n}\n",
+        )
+        .unwrap();
+        let options = AnalyzeOptions {
+            storage_dir: Some(root.join(".codegraph_db")),
+            force_rebuild: true,
+            ..Default::default()
+        };
+        let graph = CodeParser::with_options(options).build_petgraph_code_graph(root).unwrap();
+
+        let start = graph.find_functions_by_name("start")[0];
+        let mut calls: Vec<(usize, String, bool)> = graph
+            .get_callees(&start.id)
+            .into_iter()
+            .map(|(f, r)| {
+                let file = f.file_path.strip_prefix(root).unwrap_or(&f.file_path).display().to_string();
+                (r.line_number, file, r.is_resolved)
+            })
+            .collect();
+        calls.sort();
+        assert_eq!(
+            calls,
+            vec![(6, "src/app/utils.ts".to_string(), true), (7, "src/legacy/utils.ts".to_string(), true)]
+        );
+    }
+
+    #[test]
+    fn test_java_calls_resolve_through_package_imports_and_hierarchy() {
+        let temp_dir = tempdir().unwrap();
+        let root = temp_dir.path();
+        for package in ["util", "legacy", "app"] {
+            fs::create_dir_all(root.join("com/acme").join(package)).unwrap();
+        }
+        for package in ["util", "legacy"] {
+            fs::write(
+                root.join(format!("com/acme/{}/Strings.java", package)),
+                format!("package com.acme.{};\n\npublic class Strings {{\n    public static String format(String s) {{\n        return s;\n    }}\n}}\n", package),
+            )
+            .unwrap();
+        }
+        fs::write(
+            root.join("com/acme/app/Base.java"),
+            "package com.acme.app;\n\npublic class Base {\n    protected int helper() {\n        return 1;\n    }\n}\n",
+        )
+        .unwrap();
+        fs::write(
+            root.join("com/acme/app/Other.java"),
+            "package com.acme.app;\n\nclass Other {\n    int helper() {\n        return 2;\n    }\n}\n",
+        )
+        .unwrap();
+        fs::write(
+            root.join("com/acme/app/Service.java"),
+            "package com.acme.app;\n\nimport com.acme.util.Strings;\n\npublic class Service extends Base {\n    public void run() {\n        int total = 0;\n        Strings.format(\"a\");\n        this.helper();\n    }\n}\n",
+        )
+        .unwrap();
+        let options = AnalyzeOptions {
+            storage_dir: Some(root.join(".codegraph_db")),
+            force_rebuild: true,
+            ..Default::default()
+        };
+        let graph = CodeParser::with_options(options).build_petgraph_code_graph(root).unwrap();
+
+        let run = graph.find_functions_by_name("run")[0];
+        let mut calls: Vec<(String, String, bool)> = graph
+            .get_callees(&run.id)
+            .into_iter()
+            .map(|(f, r)| {
+                let file = f.file_path.strip_prefix(root).unwrap_or(&f.file_path).display().to_string();
+                (f.name.clone(), file, r.is_resolved)
+            })
+            .collect();
+        calls.sort();
+        assert_eq!(
+            calls,
+            vec![
+                ("format".to_string(), "com/acme/util/Strings.java".to_string(), true),
+                ("helper".to_string(), "com/acme/app/Base.java".to_string(), true),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_overloads_resolved_by_arity_and_argument_types() {
+        let temp_dir = tempdir().unwrap();
+        let root = temp_dir.path();
+        fs::write(
+            root.join("Printer.java"),
+            "public class Printer {\n    void print(String s) {}\n    void print(int n) {}\n    void print(String s, int width) {}\n\n    void run(long count, Object any) {\n        print(\"a\");\n        print(count);\n        print(\"b\", 2);\n        print(any);\n    }\n}\n",
+        )
+        .unwrap();
+        fs::write(
+            root.join("shapes.cpp"),
+            "double area(double r) { return r * r; }\ndouble area(double w, double h) { return w * h; }\n\nvoid draw() {\n    area(2.0, 3.0);\n}\n",
+        )
+        .unwrap();
+        let options = AnalyzeOptions {
+            storage_dir: Some(root.join(".codegraph_db")),
+            force_rebuild: true,
+            ..Default::default()
+        };
+        let graph = CodeParser::with_options(options).build_petgraph_code_graph(root).unwrap();
+
+        let callees = |name: &str| {
+            let caller = graph.find_functions_by_name(name)[0];
+            let mut calls: Vec<(usize, usize, Option<f32>)> = graph
+                .get_callees(&caller.id)
+                .into_iter()
+                .map(|(f, r)| (r.line_number, f.line_start, r.confidence))
+                .collect();
+            calls.sort_by_key(|call| call.0);
+            calls
+        };
+        // 按类型、按个数选出唯一的重载；`Object` 实参类型未知，两个单参数重载各占一半
+        assert_eq!(callees("run"), vec![(7, 2, Some(1.0)), (8, 3, Some(1.0)), (9, 4, Some(1.0)), (10, 2, Some(0.5))]);
+        assert_eq!(callees("draw"), vec![(5, 2, Some(1.0))]);
+    }
+
+    #[test]
+    fn test_parse_stats_flag_files_with_syntax_errors() {
+        let temp_dir = tempdir().unwrap();
+        let root = temp_dir.path();
+        fs::write(root.join("good.py"), "def load(path):\n    return open(path).read()\n").unwrap();
+        fs::write(root.join("broken.py"), "def load(path:\n    return ))) open(path\n\ndef save(:\n    pass\n").unwrap();
+        fs::write(root.join("report.sql"), "CREATE FUNCTION total() RETURNS int AS $$ SELECT 1 $$ LANGUAGE sql;\n").unwrap();
+        fs::write(root.join("latin1.py"), b"def caf\xe9():\n    pass\n").unwrap();
+        let options = AnalyzeOptions {
+            storage_dir: Some(root.join(".codegraph_db")),
+            force_rebuild: true,
+            ..Default::default()
+        };
+        let mut parser = CodeParser::with_options(options);
+        parser.build_petgraph_code_graph(root).unwrap();
+
+        let stats = |name: &str| parser.parse_stats()[&root.join(name)].clone();
+        let good = stats("good.py");
+        assert_eq!((good.language.as_str(), good.error_nodes), ("python", Some(0)));
+        assert!(good.node_count.unwrap() > 10 && !good.is_suspect());
+        let broken = stats("broken.py");
+        assert!(broken.error_nodes.unwrap() > 0 && broken.is_suspect());
+        // SQL 不使用 tree-sitter 语法，只有耗时
+        let sql = stats("report.sql");
+        assert_eq!((sql.node_count, sql.error_ratio(), sql.is_suspect()), (None, None, false));
+        // 无法读取的文件记录失败原因
+        let failed = stats("latin1.py");
+        assert!(failed.parse_error.is_some() && failed.is_suspect());
+        assert!(good.parse_error.is_none());
+    }
+
+    #[test]
+    fn test_syntax_errors_keep_functions_outside_error_regions() {
+        let temp_dir = tempdir().unwrap();
+        let root = temp_dir.path();
+        fs::write(
+            root.join("lib.rs"),
+            "fn good_a() -> i32 {\n    1\n}\n\nfn broken(x: i32 {\n    let y = ;\n}\n\nfn good_b() -> i32 {\n    good_a()\n}\n",
+        )
+        .unwrap();
+        // C++ 的错误恢复把后面的函数并入 ERROR 节点，需要重新拼出函数定义
+        fs::write(
+            root.join("main.cpp"),
+            "int first() {\n  return 1;\n}\n\nint broken(int x {\n  int y = ;\n}\n\nint second() {\n  return first();\n}\n",
+        )
+        .unwrap();
+        let options = AnalyzeOptions {
+            storage_dir: Some(root.join(".codegraph_db")),
+            force_rebuild: true,
+            ..Default::default()
+        };
+        let graph = CodeParser::with_options(options).build_petgraph_code_graph(root).unwrap();
+
+        let mut functions: Vec<(String, usize, bool)> = graph
+            .get_all_functions()
+            .into_iter()
+            .filter(|f| !f.is_unresolved())
+            .map(|f| (f.name.clone(), f.line_start, f.partial))
+            .collect();
+        functions.sort();
+        assert_eq!(
+            functions,
+            vec![
+                ("broken".to_string(), 5, true),
+                ("first".to_string(), 1, false),
+                ("good_a".to_string(), 1, false),
+                ("good_b".to_string(), 9, false),
+                ("second".to_string(), 9, true),
+            ]
+        );
+        for (caller, callee) in [("good_b", "good_a"), ("second", "first")] {
+            let caller = graph.find_functions_by_name(caller)[0];
+            let callees: Vec<&str> = graph.get_callees(&caller.id).into_iter().map(|(f, _)| f.name.as_str()).collect();
+            assert_eq!(callees, vec![callee]);
+        }
+    }
+
+    #[test]
+    fn test_cpp_template_calls() {
+        let temp_dir = tempdir().unwrap();
+        fs::write(
+            temp_dir.path().join("main.cpp"),
+            "template <typename T>\nT clamp_value(T v, T lo, T hi) {\n    return v < lo ? lo : v;\n}\n\ntemplate <typename T>\nclass Box {\npublic:\n    T get() { return value; }\n    static Box make(T v) { return Box{v}; }\n    template <typename U> U as() { return static_cast<U>(value); }\n    T value;\n};\n\nint run() {\n    int a = clamp_value<int>(1, 2, 3);\n    double b = clamp_value<double>(1.0, 2.0, 3.0);\n    int c = clamp_value(4, 5, 6);\n    Box<int> box = Box<int>::make(1);\n    box.get();\n    box.template as<long>();\n    auto p = std::make_unique<Box<int>>();\n    return a;\n}\n",
+        )
+        .unwrap();
+        let options = AnalyzeOptions {
+            storage_dir: Some(temp_dir.path().join(".codegraph_db")),
+            force_rebuild: true,
+            ..Default::default()
+        };
+        let graph = CodeParser::with_options(options).build_petgraph_code_graph(temp_dir.path()).unwrap();
+
+        let run = graph.find_functions_by_name("run")[0];
+        let mut calls: Vec<(usize, String, bool, Option<String>)> = graph
+            .get_callees(&run.id)
+            .into_iter()
+            .map(|(f, r)| (r.line_number, f.name.clone(), r.is_resolved, r.template_args.clone()))
+            .collect();
+        calls.sort();
+        // 每个实例化各有一条边，模板实参记录在边上
+        assert_eq!(
+            calls,
+            vec![
+                (16, "clamp_value".to_string(), true, Some("int".to_string())),
+                (17, "clamp_value".to_string(), true, Some("double".to_string())),
+                (18, "clamp_value".to_string(), true, None),
+                (19, "make".to_string(), true, Some("int".to_string())),
+                (20, "get".to_string(), true, None),
+                (21, "as".to_string(), true, Some("long".to_string())),
+                (22, "make_unique".to_string(), false, Some("Box<int>".to_string())),
+            ]
+        );
+        assert!(graph.find_functions_by_name("").is_empty());
+    }
+
+    #[test]
+    fn test_c_macros_as_functions() {
+        let temp_dir = tempdir().unwrap();
+        fs::write(
+            temp_dir.path().join("log.h"),
+            "void log_write(int level, const char *msg);\n\n#define LOG_ERROR(msg) \\\n    log_write(3, msg)\n#define CALL(f, arg) f(arg)\n#define log_warn log_write\n#define MAX_RETRIES 3\n",
+        )
+        .unwrap();
+        fs::write(
+            temp_dir.path().join("main.c"),
+            "#include \"log.h\"\n#define CALL(f, arg) f(arg)\n\nvoid cleanup(int code) {\n}\n\nint main(void) {\n    LOG_ERROR(\"boom\");\n    CALL(cleanup, 1);\n    return 0;\n}\n",
+        )
+        .unwrap();
+
+        let build = |expand_macros: bool| {
+            let options = AnalyzeOptions {
+                storage_dir: Some(temp_dir.path().join(".codegraph_db")),
+                force_rebuild: true,
+                expand_macros,
+                ..Default::default()
+            };
+            CodeParser::with_options(options).build_petgraph_code_graph(temp_dir.path()).unwrap()
+        };
+
+        let graph = build(false);
+        assert!(graph.find_functions_by_name("LOG_ERROR").iter().all(|f| f.is_unresolved()));
+
+        let graph = build(true);
+        // 宏定义是函数，宏调用解析到宏，宏体中的调用是宏的调用
+        let log_error = graph.find_functions_by_name("LOG_ERROR").into_iter().find(|f| !f.is_unresolved()).unwrap();
+        assert_eq!((log_error.line_start, log_error.line_end), (3, 4));
+        let main = graph.find_functions_by_name("main")[0];
+        let callees = graph.get_callees(&main.id);
+        assert!(callees.iter().any(|(f, r)| f.id == log_error.id && r.is_resolved && r.line_number == 8));
+        assert!(graph.get_callees(&log_error.id).iter().any(|(f, r)| f.name == "log_write" && r.line_number == 4));
+        // 宏体调用的参数在调用处作为调用
+        assert!(callees.iter().any(|(f, r)| f.name == "cleanup" && r.is_resolved && r.line_number == 9));
+        assert!(graph.find_functions_by_name("log_warn").iter().any(|f| !f.is_unresolved()));
+        assert!(graph.find_functions_by_name("MAX_RETRIES").is_empty());
+    }
+
+    #[test]
+    fn test_single_file_component_scripts() {
+        let temp_dir = tempdir().unwrap();
+        fs::write(
+            temp_dir.path().join("UserForm.vue"),
+            "<template>\n  <form @submit=\"save\"></form>\n</template>\n\n<script lang=\"ts\">\nimport { post } from './api'\n\nexport default {\n  methods: {\n    save(): void {\n      post(this.user)\n    },\n  },\n}\n</script>\n",
+        )
+        .unwrap();
+        fs::write(temp_dir.path().join("api.ts"), "export function post(body: object) {\n  return fetch('/users', { body })\n}\n").unwrap();
+        fs::write(
+            temp_dir.path().join("Counter.svelte"),
+            "<script>\n  let count = 0\n  function increment() {\n    count = bump(count)\n  }\n  function bump(n) {\n    return n + 1\n  }\n</script>\n\n<button on:click={increment}>{count}</button>\n",
+        )
+        .unwrap();
+
+        let options = AnalyzeOptions {
+            storage_dir: Some(temp_dir.path().join(".codegraph_db")),
+            force_rebuild: true,
+            ..Default::default()
+        };
+        let mut parser = CodeParser::with_options(options);
+        let graph = parser.build_petgraph_code_graph(temp_dir.path()).unwrap();
+
+        // 行号是组件文件中的行号
+        let save = graph.find_functions_by_name("save")[0];
+        assert_eq!((save.language.as_str(), save.namespace.as_str(), save.line_start, save.line_end), ("vue", "UserForm", 10, 12));
+        let callees = graph.get_callees(&save.id);
+        assert!(callees.iter().any(|(f, r)| f.name == "post" && r.is_resolved && r.line_number == 11 && f.file_path.ends_with("api.ts")));
+
+        let increment = graph.find_functions_by_name("increment")[0];
+        assert_eq!((increment.language.as_str(), increment.line_start), ("svelte", 3));
+        assert!(graph.get_callees(&increment.id).iter().any(|(f, r)| f.name == "bump" && r.line_number == 4));
+    }
+
+    #[test]
+    fn test_embedded_language_calls() {
+        let temp_dir = tempdir().unwrap();
+        fs::write(temp_dir.path().join("orders.py"), "def refresh(db):\n    db.execute(\"CALL refresh_totals()\")\n").unwrap();
+        fs::write(temp_dir.path().join("totals.sql"), "CREATE PROCEDURE refresh_totals()\nBEGIN\n  UPDATE totals SET n = 0;\nEND;\n").unwrap();
+        fs::write(
+            temp_dir.path().join("lib.rs"),
+            "/// ```\n/// assert_eq!(add(1, 2), 3);\n/// ```\npub fn add(a: i32, b: i32) -> i32 {\n    assert!(valid(a));\n    a + b\n}\n\nfn valid(a: i32) -> bool {\n    a > 0\n}\n",
+        )
+        .unwrap();
+        fs::write(
+            temp_dir.path().join("index.html"),
+            "<html>\n<body>\n<script>\nfunction start() {\n  render()\n}\nfunction render() {}\n</script>\n</body>\n</html>\n",
+        )
+        .unwrap();
+
+        let options = AnalyzeOptions {
+            storage_dir: Some(temp_dir.path().join(".codegraph_db")),
+            force_rebuild: true,
+            ..Default::default()
+        };
+        let mut parser = CodeParser::with_options(options);
+        let graph = parser.build_petgraph_code_graph(temp_dir.path()).unwrap();
+
+        let refresh = graph.find_functions_by_name("refresh")[0];
+        assert!(graph.get_callees(&refresh.id).iter().any(|(f, r)| f.name == "refresh_totals" && f.language == "sql" && r.line_number == 2));
+
+        // 宏参数中的调用属于所在函数，文档测试是单独的函数
+        let add = graph.find_functions_by_name("add")[0];
+        assert!(graph.get_callees(&add.id).iter().any(|(f, r)| f.name == "valid" && r.line_number == 5));
+        let doctest = graph.find_functions_by_name("add::doctest")[0];
+        assert_eq!((doctest.line_start, doctest.line_end), (1, 3));
+        assert!(graph.get_callees(&doctest.id).iter().any(|(f, _)| f.id == add.id));
+
+        let start = graph.find_functions_by_name("start")[0];
+        assert_eq!((start.language.as_str(), start.namespace.as_str(), start.line_start), ("html", "index", 4));
+        assert!(graph.get_callees(&start.id).iter().any(|(f, r)| f.name == "render" && r.line_number == 5));
+    }
+
+    #[test]
+    fn test_grpc_stub_calls_link_services() {
+        let temp_dir = tempdir().unwrap();
+        fs::create_dir_all(temp_dir.path().join("api")).unwrap();
+        fs::create_dir_all(temp_dir.path().join("orders")).unwrap();
+        fs::create_dir_all(temp_dir.path().join("users")).unwrap();
+        fs::write(
+            temp_dir.path().join("api/user.proto"),
+            "syntax = \"proto3\";\npackage acme.user;\n\nservice UserService {\n  rpc GetUser(GetUserRequest) returns (User);\n}\n",
+        )
+        .unwrap();
+        fs::write(
+            temp_dir.path().join("orders/client.go"),
+            "package orders\n\nfunc PlaceOrder(conn Conn) {\n\tclient := pb.NewUserServiceClient(conn)\n\tclient.GetUser(ctx, req)\n}\n",
+        )
+        .unwrap();
+        fs::write(
+            temp_dir.path().join("users/server.go"),
+            "package users\n\ntype server struct {\n\tpb.UnimplementedUserServiceServer\n}\n\nfunc (s *server) GetUser(ctx Context, req *Request) (*User, error) {\n\treturn loadUser(req)\n}\n\nfunc loadUser(req *Request) (*User, error) {\n\treturn nil, nil\n}\n",
+        )
+        .unwrap();
+
+        let options = AnalyzeOptions {
+            storage_dir: Some(temp_dir.path().join(".codegraph_db")),
+            force_rebuild: true,
+            ..Default::default()
+        };
+        let mut parser = CodeParser::with_options(options);
+        let graph = parser.build_petgraph_code_graph(temp_dir.path()).unwrap();
+
+        // 客户端 -> RPC 定义 -> 服务端实现 -> 实现内部的调用
+        let rpc = graph.find_functions_by_name("UserService.GetUser")[0];
+        assert_eq!((rpc.language.as_str(), rpc.namespace.as_str()), ("proto", "acme.user"));
+        let callers: Vec<_> = graph.get_callers(&rpc.id).into_iter().map(|(f, r)| (f.name.clone(), r.is_resolved)).collect();
+        assert_eq!(callers, vec![("PlaceOrder".to_string(), true)]);
+        let implementation = graph.get_callees(&rpc.id).into_iter().map(|(f, _)| f.clone()).collect::<Vec<_>>();
+        assert_eq!(implementation.len(), 1);
+        assert!(implementation[0].file_path.ends_with("users/server.go"));
+        let downstream: Vec<_> = graph.get_callees(&implementation[0].id).into_iter().map(|(f, _)| f.name.clone()).collect();
+        assert_eq!(downstream, vec!["loadUser".to_string()]);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_command_analyzer_plugin() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let temp_dir = tempdir().unwrap();
+        // 一个以可执行文件形式提供的分析器：`def`/`end` 定义函数，`call` 调用函数
+        let plugin = temp_dir.path().join("toy-analyzer");
+        fs::write(
+            &plugin,
+            r#"#!/bin/sh
+if [ "$1" = manifest ]; then echo '{"name": "toy", "extensions": ["toy"]}'; exit 0; fi
+awk 'BEGIN { printf "{\"symbols\": ["; n = 0 }
+$1 == "def" { d++; start[d] = NR; name[d] = $2 }
+$1 == "end" { printf "%s{\"kind\": \"function\", \"name\": \"%s\", \"line\": %d, \"end_line\": %d}", (n++ ? "," : ""), name[d], start[d], NR; d-- }
+$1 == "call" { printf "%s{\"kind\": \"call\", \"name\": \"%s\", \"line\": %d}", (n++ ? "," : ""), $2, NR }
+END { print "]}" }'
+"#,
+        )
+        .unwrap();
+        fs::set_permissions(&plugin, fs::Permissions::from_mode(0o755)).unwrap();
+        let project = temp_dir.path().join("project");
+        fs::create_dir_all(&project).unwrap();
+        fs::write(project.join("main.toy"), "def main\n  call helper\nend\n").unwrap();
+        fs::write(project.join("util.toy"), "def helper\n  call missing\nend\n").unwrap();
+
+        let manifest = AnalyzerRegistry::register_dynamic(&plugin).unwrap();
+        assert_eq!(manifest.name, "toy");
+        let options = AnalyzeOptions {
+            storage_dir: Some(temp_dir.path().join(".codegraph_db")),
+            force_rebuild: true,
+            ..Default::default()
+        }
+        .with_languages(&["toy".to_string()])
+        .unwrap();
+        let mut parser = CodeParser::with_options(options);
+        let graph = parser.build_petgraph_code_graph(&project).unwrap();
+        AnalyzerRegistry::unregister("toy");
+
+        let main = graph.find_functions_by_name("main")[0];
+        assert_eq!(main.language, "toy");
+        let callees: Vec<_> = graph.get_callees(&main.id).into_iter().map(|(f, r)| (f.clone(), r.is_resolved)).collect();
+        assert_eq!(callees.len(), 1);
+        assert!(callees[0].1 && callees[0].0.name == "helper" && callees[0].0.file_path.ends_with("util.toy"));
+    }
+
+    #[test]
+    fn test_scan_directory_with_analyze_options() {
+        let temp_dir = tempdir().unwrap();
+        fs::create_dir_all(temp_dir.path().join("src")).unwrap();
+        fs::create_dir_all(temp_dir.path().join("vendor/lib")).unwrap();
+        fs::write(temp_dir.path().join("src/main.rs"), "fn main() {}").unwrap();
+        fs::write(temp_dir.path().join("src/tool.py"), "def tool():\n    pass\n").unwrap();
+        fs::write(temp_dir.path().join("vendor/lib/dep.rs"), "fn dep() {}").unwrap();
+
+        let options = AnalyzeOptions {
+            exclude_paths: vec!["vendor".to_string()],
+            ..Default::default()
+        }
+        .with_languages(&["rs".to_string()])
+        .unwrap();
+        let mut parser = CodeParser::with_options(options);
+        let files = parser.scan_directory(temp_dir.path());
+
+        assert_eq!(files, vec![temp_dir.path().join("src/main.rs")]);
+        assert!(AnalyzeOptions::default().with_languages(&["cobol".to_string()]).is_err());
+    }
+
+    #[test]
+    fn test_generated_js_mapped_to_original_sources() {
+        let temp_dir = tempdir().unwrap();
+        let dist = temp_dir.path().join("dist");
+        fs::create_dir_all(&dist).unwrap();
+        fs::write(
+            dist.join("bundle.js"),
+            "function add(a,b){return a+b}function main(){return add(1,2)}\n//# sourceMappingURL=bundle.js.map\n",
+        )
+        .unwrap();
+        fs::write(
+            dist.join("bundle.js.map"),
+            r#"{"version":3,"sources":["../src/math.ts","../src/app.ts"],"names":[],"mappings":"AAAA,kBACE,WCGF,uBACS"}"#,
+        )
+        .unwrap();
+
+        let options = AnalyzeOptions {
+            storage_dir: Some(temp_dir.path().join(".codegraph_db")),
+            ..Default::default()
+        };
+        let mut parser = CodeParser::with_options(options);
+        let graph = parser.build_petgraph_code_graph(temp_dir.path()).unwrap();
+
+        let add = graph.find_functions_by_name("add")[0];
+        assert_eq!(add.file_path, temp_dir.path().join("src/math.ts"));
+        assert_eq!((add.line_start, add.line_end), (1, 2));
+        let main = graph.find_functions_by_name("main")[0];
+        assert_eq!(main.file_path, temp_dir.path().join("src/app.ts"));
+        assert_eq!(main.line_start, 5);
+        assert!(graph.find_functions_by_file(&dist.join("bundle.js")).is_empty());
+
+        let calls = graph.get_callees(&main.id);
+        assert_eq!(calls.len(), 1);
+        assert_eq!(calls[0].1.line_number, 6);
+        assert_eq!(calls[0].1.caller_file, temp_dir.path().join("src/app.ts"));
+    }
+}
@@ -0,0 +1,404 @@
+// `proto/codegraph.proto` 的 prost 消息定义，字段编号需与 .proto 文件保持一致
+
+use std::path::PathBuf;
+
+use prost::Message;
+use uuid::Uuid;
+
+use super::types;
+
+/// 函数
+#[derive(Clone, PartialEq, Message)]
+pub struct FunctionInfo {
+    #[prost(string, tag = "1")]
+    pub id: String,
+    #[prost(string, tag = "2")]
+    pub name: String,
+    #[prost(string, tag = "3")]
+    pub file_path: String,
+    #[prost(uint64, tag = "4")]
+    pub line_start: u64,
+    #[prost(uint64, tag = "5")]
+    pub line_end: u64,
+    #[prost(string, tag = "6")]
+    pub namespace: String,
+    #[prost(string, tag = "7")]
+    pub language: String,
+    #[prost(string, optional, tag = "8")]
+    pub signature: Option<String>,
+    #[prost(uint64, optional, tag = "9")]
+    pub cell: Option<u64>,
+    #[prost(string, optional, tag = "10")]
+    pub body_hash: Option<String>,
+    #[prost(bool, tag = "11")]
+    pub partial: bool,
+    #[prost(string, optional, tag = "12")]
+    pub return_type: Option<String>,
+    #[prost(message, repeated, tag = "13")]
+    pub parameters: Vec<FunctionParameter>,
+    #[prost(string, optional, tag = "14")]
+    pub parent_class_id: Option<String>,
+    #[prost(enumeration = "FunctionKind", tag = "15")]
+    pub kind: i32,
+}
+
+/// 函数种类，未知值按普通函数处理
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, prost::Enumeration)]
+#[repr(i32)]
+pub enum FunctionKind {
+    Function = 0,
+    Unresolved = 1,
+    Macro = 2,
+    Generated = 3,
+    Reexport = 4,
+    Abstract = 5,
+}
+
+/// 函数的形参
+#[derive(Clone, PartialEq, Message)]
+pub struct FunctionParameter {
+    #[prost(string, tag = "1")]
+    pub name: String,
+    #[prost(string, optional, tag = "2")]
+    pub r#type: Option<String>,
+}
+
+/// 类类型
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, prost::Enumeration)]
+#[repr(i32)]
+pub enum ClassType {
+    Unspecified = 0,
+    Class = 1,
+    Struct = 2,
+    Interface = 3,
+    Trait = 4,
+    Enum = 5,
+}
+
+/// 类/结构体
+#[derive(Clone, PartialEq, Message)]
+pub struct ClassInfo {
+    #[prost(string, tag = "1")]
+    pub id: String,
+    #[prost(string, tag = "2")]
+    pub name: String,
+    #[prost(string, tag = "3")]
+    pub file_path: String,
+    #[prost(uint64, tag = "4")]
+    pub line_start: u64,
+    #[prost(uint64, tag = "5")]
+    pub line_end: u64,
+    #[prost(string, tag = "6")]
+    pub namespace: String,
+    #[prost(string, tag = "7")]
+    pub language: String,
+    #[prost(enumeration = "ClassType", tag = "8")]
+    pub class_type: i32,
+    #[prost(string, optional, tag = "9")]
+    pub parent_class: Option<String>,
+    #[prost(string, repeated, tag = "10")]
+    pub implemented_interfaces: Vec<String>,
+    #[prost(string, repeated, tag = "11")]
+    pub member_functions: Vec<String>,
+    #[prost(string, repeated, tag = "12")]
+    pub member_variables: Vec<String>,
+}
+
+/// 调用关系
+#[derive(Clone, PartialEq, Message)]
+pub struct CallRelation {
+    #[prost(string, tag = "1")]
+    pub caller_id: String,
+    #[prost(string, tag = "2")]
+    pub callee_id: String,
+    #[prost(string, tag = "3")]
+    pub caller_name: String,
+    #[prost(string, tag = "4")]
+    pub callee_name: String,
+    #[prost(string, tag = "5")]
+    pub caller_file: String,
+    #[prost(string, tag = "6")]
+    pub callee_file: String,
+    #[prost(uint64, tag = "7")]
+    pub line_number: u64,
+    #[prost(bool, tag = "8")]
+    pub is_resolved: bool,
+    #[prost(bool, tag = "9")]
+    pub observed: bool,
+    /// C++ 模板调用的模板实参，非模板调用为空串
+    #[prost(string, tag = "10")]
+    pub template_args: String,
+    /// 调用方式：sync、await 或 spawn
+    #[prost(string, tag = "11")]
+    pub call_kind: String,
+    /// 从同名重载中选出被调用函数的置信度，没有重载时为空
+    #[prost(float, optional, tag = "12")]
+    pub confidence: Option<f32>,
+}
+
+/// 完整的导出图
+#[derive(Clone, PartialEq, Message)]
+pub struct CodeGraph {
+    #[prost(message, repeated, tag = "1")]
+    pub functions: Vec<FunctionInfo>,
+    #[prost(message, repeated, tag = "2")]
+    pub classes: Vec<ClassInfo>,
+    #[prost(message, repeated, tag = "3")]
+    pub calls: Vec<CallRelation>,
+}
+
+fn parse_id(value: &str) -> Result<Uuid, String> {
+    Uuid::parse_str(value).map_err(|e| format!("Invalid id '{}': {}", value, e))
+}
+
+impl From<&types::FunctionInfo> for FunctionInfo {
+    fn from(function: &types::FunctionInfo) -> Self {
+        Self {
+            id: function.id.to_string(),
+            name: function.name.clone(),
+            file_path: function.file_path.display().to_string(),
+            line_start: function.line_start as u64,
+            line_end: function.line_end as u64,
+            namespace: function.namespace.clone(),
+            language: function.language.clone(),
+            signature: function.signature.clone(),
+            cell: function.cell.map(|cell| cell as u64),
+            body_hash: function.body_hash.clone(),
+            partial: function.partial,
+            return_type: function.return_type.clone(),
+            parameters: function
+                .parameters
+                .iter()
+                .map(|parameter| FunctionParameter { name: parameter.name.clone(), r#type: parameter.type_.clone() })
+                .collect(),
+            parent_class_id: function.parent_class_id.map(|id| id.to_string()),
+            kind: FunctionKind::from(function.kind) as i32,
+        }
+    }
+}
+
+impl TryFrom<FunctionInfo> for types::FunctionInfo {
+    type Error = String;
+
+    fn try_from(function: FunctionInfo) -> Result<Self, Self::Error> {
+        Ok(Self {
+            id: parse_id(&function.id)?,
+            name: function.name,
+            file_path: PathBuf::from(function.file_path),
+            line_start: function.line_start as usize,
+            line_end: function.line_end as usize,
+            namespace: function.namespace,
+            language: function.language,
+            signature: function.signature,
+            cell: function.cell.map(|cell| cell as usize),
+            body_hash: function.body_hash,
+            partial: function.partial,
+            return_type: function.return_type,
+            parameters: function
+                .parameters
+                .into_iter()
+                .map(|parameter| types::FunctionParameter { name: parameter.name, type_: parameter.r#type })
+                .collect(),
+            parent_class_id: function.parent_class_id.as_deref().map(parse_id).transpose()?,
+            kind: match FunctionKind::try_from(function.kind) {
+                Ok(FunctionKind::Unresolved) => types::FunctionKind::Unresolved,
+                Ok(FunctionKind::Macro) => types::FunctionKind::Macro,
+                Ok(FunctionKind::Generated) => types::FunctionKind::Generated,
+                Ok(FunctionKind::Reexport) => types::FunctionKind::Reexport,
+                Ok(FunctionKind::Abstract) => types::FunctionKind::Abstract,
+                _ => types::FunctionKind::Function,
+            },
+        })
+    }
+}
+
+impl From<types::FunctionKind> for FunctionKind {
+    fn from(kind: types::FunctionKind) -> Self {
+        match kind {
+            types::FunctionKind::Function => FunctionKind::Function,
+            types::FunctionKind::Unresolved => FunctionKind::Unresolved,
+            types::FunctionKind::Macro => FunctionKind::Macro,
+            types::FunctionKind::Generated => FunctionKind::Generated,
+            types::FunctionKind::Reexport => FunctionKind::Reexport,
+            types::FunctionKind::Abstract => FunctionKind::Abstract,
+        }
+    }
+}
+
+impl From<&types::ClassType> for ClassType {
+    fn from(class_type: &types::ClassType) -> Self {
+        match class_type {
+            types::ClassType::Class => ClassType::Class,
+            types::ClassType::Struct => ClassType::Struct,
+            types::ClassType::Interface => ClassType::Interface,
+            types::ClassType::Trait => ClassType::Trait,
+            types::ClassType::Enum => ClassType::Enum,
+        }
+    }
+}
+
+impl From<&types::ClassInfo> for ClassInfo {
+    fn from(class: &types::ClassInfo) -> Self {
+        Self {
+            id: class.id.to_string(),
+            name: class.name.clone(),
+            file_path: class.file_path.display().to_string(),
+            line_start: class.line_start as u64,
+            line_end: class.line_end as u64,
+            namespace: class.namespace.clone(),
+            language: class.language.clone(),
+            class_type: ClassType::from(&class.class_type) as i32,
+            parent_class: class.parent_class.clone(),
+            implemented_interfaces: class.implemented_interfaces.clone(),
+            member_functions: class.member_functions.iter().map(Uuid::to_string).collect(),
+            member_variables: class.member_variables.clone(),
+        }
+    }
+}
+
+impl TryFrom<ClassInfo> for types::ClassInfo {
+    type Error = String;
+
+    fn try_from(class: ClassInfo) -> Result<Self, Self::Error> {
+        let class_type = match ClassType::try_from(class.class_type) {
+            Ok(ClassType::Struct) => types::ClassType::Struct,
+            Ok(ClassType::Interface) => types::ClassType::Interface,
+            Ok(ClassType::Trait) => types::ClassType::Trait,
+            Ok(ClassType::Enum) => types::ClassType::Enum,
+            // 未知或未指定的类型按普通类处理
+            _ => types::ClassType::Class,
+        };
+        Ok(Self {
+            id: parse_id(&class.id)?,
+            name: class.name,
+            file_path: PathBuf::from(class.file_path),
+            line_start: class.line_start as usize,
+            line_end: class.line_end as usize,
+            namespace: class.namespace,
+            language: class.language,
+            class_type,
+            parent_class: class.parent_class,
+            implemented_interfaces: class.implemented_interfaces,
+            member_functions: class.member_functions.iter().map(|id| parse_id(id)).collect::<Result<_, _>>()?,
+            member_variables: class.member_variables,
+        })
+    }
+}
+
+impl From<&types::CallRelation> for CallRelation {
+    fn from(relation: &types::CallRelation) -> Self {
+        Self {
+            caller_id: relation.caller_id.to_string(),
+            callee_id: relation.callee_id.to_string(),
+            caller_name: relation.caller_name.clone(),
+            callee_name: relation.callee_name.clone(),
+            caller_file: relation.caller_file.display().to_string(),
+            callee_file: relation.callee_file.display().to_string(),
+            line_number: relation.line_number as u64,
+            is_resolved: relation.is_resolved,
+            observed: relation.observed,
+            template_args: relation.template_args.clone().unwrap_or_default(),
+            call_kind: relation.call_kind.as_str().to_string(),
+            confidence: relation.confidence,
+        }
+    }
+}
+
+impl TryFrom<CallRelation> for types::CallRelation {
+    type Error = String;
+
+    fn try_from(relation: CallRelation) -> Result<Self, Self::Error> {
+        Ok(Self {
+            caller_id: parse_id(&relation.caller_id)?,
+            callee_id: parse_id(&relation.callee_id)?,
+            caller_name: relation.caller_name,
+            callee_name: relation.callee_name,
+            caller_file: PathBuf::from(relation.caller_file),
+            callee_file: PathBuf::from(relation.callee_file),
+            line_number: relation.line_number as usize,
+            is_resolved: relation.is_resolved,
+            observed: relation.observed,
+            template_args: Some(relation.template_args).filter(|args| !args.is_empty()),
+            // 旧版本导出的图没有该字段
+            call_kind: if relation.call_kind.is_empty() { Default::default() } else { relation.call_kind.parse()? },
+            confidence: relation.confidence,
+        })
+    }
+}
+
+impl CodeGraph {
+    /// 从调用图和（可选的）类列表构建，函数按文件和行号排序，调用按源码位置排序
+    pub fn from_graph(graph: &types::PetCodeGraph, classes: &[types::ClassInfo]) -> Self {
+        let mut functions = graph.get_all_functions();
+        functions.sort_by(|a, b| (&a.file_path, a.line_start, &a.name).cmp(&(&b.file_path, b.line_start, &b.name)));
+        let mut calls: Vec<&types::CallRelation> = graph.graph.edge_weights().collect();
+        calls.sort_by(|a, b| {
+            (&a.caller_file, a.line_number, &a.caller_name, &a.callee_name)
+                .cmp(&(&b.caller_file, b.line_number, &b.caller_name, &b.callee_name))
+        });
+        Self {
+            functions: functions.into_iter().map(FunctionInfo::from).collect(),
+            classes: classes.iter().map(ClassInfo::from).collect(),
+            calls: calls.into_iter().map(CallRelation::from).collect(),
+        }
+    }
+
+    /// 还原为调用图；类不属于调用图，单独返回
+    pub fn into_graph(self) -> Result<(types::PetCodeGraph, Vec<types::ClassInfo>), String> {
+        let mut graph = types::PetCodeGraph::new();
+        for function in self.functions {
+            graph.add_function(function.try_into()?);
+        }
+        for call in self.calls {
+            graph.add_call_relation(call.try_into()?)?;
+        }
+        graph.update_stats();
+        let classes = self.classes.into_iter().map(types::ClassInfo::try_from).collect::<Result<_, _>>()?;
+        Ok((graph, classes))
+    }
+
+    /// 解码protobuf二进制
+    pub fn decode_bytes(bytes: &[u8]) -> Result<Self, String> {
+        Self::decode(bytes).map_err(|e| format!("Failed to decode protobuf graph: {}", e))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::codegraph::parser::CodeParser;
+    use crate::codegraph::AnalyzeOptions;
+    use crate::services::graph_export::to_protobuf;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_protobuf_round_trip() {
+        let temp_dir = TempDir::new().unwrap();
+        std::fs::write(
+            temp_dir.path().join("shapes.rs"),
+            "pub struct Circle {\n    r: f64,\n}\n\npub fn area(c: &Circle) -> f64 {\n    square(c.r) * 3.14\n}\n\npub fn square(x: f64) -> f64 {\n    x * x\n}\n",
+        )
+        .unwrap();
+        let options = AnalyzeOptions {
+            storage_dir: Some(temp_dir.path().join(".codegraph_db")),
+            force_rebuild: true,
+            ..Default::default()
+        };
+        let graph = CodeParser::with_options(options).build_petgraph_code_graph(temp_dir.path()).unwrap();
+
+        let message = CodeGraph::decode_bytes(&to_protobuf(&graph)).unwrap();
+        assert_eq!(message.classes.len(), 1);
+        assert_eq!(message.classes[0].class_type, ClassType::Struct as i32);
+
+        let (decoded, classes) = message.into_graph().unwrap();
+        assert_eq!(classes[0].name, "Circle");
+        assert_eq!(decoded.graph.node_count(), graph.graph.node_count());
+        assert_eq!(decoded.graph.edge_count(), graph.graph.edge_count());
+        let area = graph.find_functions_by_name("area")[0];
+        let callees: Vec<_> = decoded.get_callees(&area.id).into_iter().map(|(f, _)| f.name.clone()).collect();
+        assert_eq!(callees, vec!["square".to_string()]);
+        assert!(decoded.validate().is_empty());
+
+        assert!(CodeGraph::decode_bytes(b"\xff\xff").is_err());
+    }
+}
@@ -0,0 +1,422 @@
+use std::collections::HashMap;
+use std::path::{Component, Path, PathBuf};
+
+use regex::Regex;
+
+/// 一条导入或再导出语句中来自同一模块的名字
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ModuleImport {
+    /// 按源码原样记录的模块，如 `crate::inner`、`./impl`、`.impl`
+    pub module: String,
+    /// (本模块中的名字, 原模块中的名字)；为空表示通配导入 `*`
+    pub names: Vec<(String, String)>,
+    /// 语句所在的行（从1开始）
+    pub line: usize,
+}
+
+impl ModuleImport {
+    pub fn is_glob(&self) -> bool {
+        self.names.is_empty()
+    }
+}
+
+/// Python 中整体导入的模块：`import x`、`import x.y as z`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ModuleAlias {
+    /// 本模块中引用该模块的名字；没有 `as` 时为完整的模块名
+    pub alias: String,
+    pub module: String,
+    pub line: usize,
+}
+
+/// 文件中的导入和再导出语句。
+///
+/// 再导出为 Rust 的 `pub use`、TypeScript/JavaScript 的 `export * from` 和 `export { .. } from`、
+/// Python 的 `from x import *` 以及 `__init__.py` 中的 `from x import a`；其余导入语句记为普通导入。
+/// Java 的 `import a.b.Foo;` 记为模块 `a.b` 中的名字 `Foo`，`import static a.b.Foo.bar;` 记为模块 `a.b.Foo` 中的 `bar`
+#[derive(Debug, Clone, Default)]
+pub struct ModuleImports {
+    pub reexports: Vec<ModuleImport>,
+    pub imports: Vec<ModuleImport>,
+    pub module_aliases: Vec<ModuleAlias>,
+    /// 经路径别名（tsconfig 的 `paths`、`baseUrl`）解析的模块 -> 模块路径（绝对路径，不含扩展名）
+    pub resolved_modules: HashMap<String, PathBuf>,
+}
+
+impl ModuleImports {
+    pub fn scan(language: &str, file_path: &Path, code: &str) -> Self {
+        let mut imports = Self::default();
+        match language {
+            "rust" => imports.scan_rust(code),
+            "python" => imports.scan_python(file_path, code),
+            "javascript" | "typescript" | "vue" | "svelte" => imports.scan_js(code),
+            "java" => imports.scan_java(code),
+            _ => {}
+        }
+        imports
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.reexports.is_empty() && self.imports.is_empty() && self.module_aliases.is_empty()
+    }
+
+    /// 用 `resolve` 解析各语句中的非相对路径模块，解析结果供 [`ModuleImports::resolves_to`] 使用
+    pub fn resolve_modules(&mut self, resolve: impl Fn(&str) -> Option<PathBuf>) {
+        let modules = self
+            .reexports
+            .iter()
+            .chain(&self.imports)
+            .map(|import| &import.module)
+            .chain(self.module_aliases.iter().map(|alias| &alias.module));
+        let resolved: HashMap<String, PathBuf> =
+            modules.filter_map(|module| Some((module.clone(), resolve(module)?))).collect();
+        self.resolved_modules = resolved;
+    }
+
+    /// `from` 文件中写的 `module` 是否指向 `file`，优先使用路径别名的解析结果
+    pub fn resolves_to(&self, language: &str, from: &Path, module: &str, file: &Path) -> bool {
+        match self.resolved_modules.get(module) {
+            Some(base) => js_module_matches(language, base, file),
+            None => module_resolves_to(language, from, module, file),
+        }
+    }
+
+    /// `receiver.name` 形式的调用可能指向的 (模块, 名字)。
+    ///
+    /// 接收者可以是整体导入的模块（Python 的 `import x as z`、JavaScript/TypeScript 的
+    /// `import * as z from 'x'`），也可以是 Python 中 `from pkg import mod` 导入的子模块；
+    /// 没有接收者时按 `from x import y as w`、`import { y as w } from 'x'` 中的别名查找，
+    /// Python 还包括通配导入
+    pub fn call_targets(&self, language: &str, receiver: Option<&str>, name: &str) -> Vec<(String, String)> {
+        let python = language == "python";
+        let from_imports = self.imports.iter().chain(&self.reexports);
+        let Some(receiver) = receiver else {
+            return from_imports
+                .filter_map(|import| {
+                    let original = if import.is_glob() {
+                        if !python {
+                            return None;
+                        }
+                        name
+                    } else {
+                        import.names.iter().find(|(alias, _)| alias == name)?.1.as_str()
+                    };
+                    Some((import.module.clone(), original.to_string()))
+                })
+                .collect();
+        };
+        let aliased = self
+            .module_aliases
+            .iter()
+            .filter(|alias| alias.alias == receiver)
+            .map(|alias| (alias.module.clone(), name.to_string()));
+        let submodules = from_imports.filter(|_| python).flat_map(|import| {
+            import.names.iter().filter(|(alias, _)| alias == receiver).map(|(_, original)| {
+                let separator = if import.module.ends_with('.') { "" } else { "." };
+                (format!("{}{}{}", import.module, separator, original), name.to_string())
+            })
+        });
+        aliased.chain(submodules).collect()
+    }
+
+    fn scan_rust(&mut self, code: &str) {
+        let statement = Regex::new(r"(?m)^[ \t]*(pub(?:\s*\([^)]*\))?\s+)?use\s+([^;]+);").unwrap();
+        for captures in statement.captures_iter(code) {
+            let line = line_of(code, captures.get(0).unwrap().start());
+            let tree: String = captures[2].split_whitespace().collect::<Vec<_>>().join(" ");
+            let mut items = Vec::new();
+            rust_use_items(tree.trim_start_matches("::"), "", &mut items);
+            let target = if captures.get(1).is_some() { &mut self.reexports } else { &mut self.imports };
+            for (module, item) in items {
+                let names = match item {
+                    None => Vec::new(),
+                    Some((alias, name)) if alias != "_" && name != "self" => vec![(alias, name)],
+                    Some(_) => continue,
+                };
+                target.push(ModuleImport { module, names, line });
+            }
+        }
+    }
+
+    fn scan_python(&mut self, file_path: &Path, code: &str) {
+        let statement = Regex::new(r"(?m)^[ \t]*from[ \t]+([\w.]+)[ \t]+import[ \t]+(\([^)]*\)|[^\n#;]+)").unwrap();
+        let import_modules = Regex::new(r"(?m)^[ \t]*import[ \t]+([^\n#;]+)").unwrap();
+        let package = file_path.file_stem().is_some_and(|stem| stem == "__init__");
+        for captures in import_modules.captures_iter(code) {
+            let line = line_of(code, captures.get(0).unwrap().start());
+            for (alias, module) in aliased_names(captures[1].trim_end_matches('\\'), " as ") {
+                self.module_aliases.push(ModuleAlias { alias, module, line });
+            }
+        }
+        for captures in statement.captures_iter(code) {
+            let line = line_of(code, captures.get(0).unwrap().start());
+            let names = aliased_names(captures[2].trim_matches(|c: char| c == '(' || c == ')' || c.is_whitespace()), " as ");
+            let import = ModuleImport { module: captures[1].to_string(), names, line };
+            if import.is_glob() || package {
+                self.reexports.push(import);
+            } else {
+                self.imports.push(import);
+            }
+        }
+    }
+
+    fn scan_js(&mut self, code: &str) {
+        let export_all = Regex::new(r#"(?m)^[ \t]*export\s*\*\s*from\s*['"]([^'"]+)['"]"#).unwrap();
+        let export_named = Regex::new(r#"(?m)^[ \t]*export\s+(?:type\s+)?\{([^}]*)\}\s*from\s*['"]([^'"]+)['"]"#).unwrap();
+        let import_named = Regex::new(r#"(?m)^[ \t]*import\s+(?:type\s+)?(?:\w+\s*,\s*)?\{([^}]*)\}\s*from\s*['"]([^'"]+)['"]"#).unwrap();
+        let import_all = Regex::new(r#"(?m)^[ \t]*import\s*\*\s*as\s+(\w+)\s+from\s*['"]([^'"]+)['"]"#).unwrap();
+        for captures in export_all.captures_iter(code) {
+            let line = line_of(code, captures.get(0).unwrap().start());
+            self.reexports.push(ModuleImport { module: captures[1].to_string(), names: Vec::new(), line });
+        }
+        for captures in import_all.captures_iter(code) {
+            let line = line_of(code, captures.get(0).unwrap().start());
+            self.imports.push(ModuleImport { module: captures[2].to_string(), names: Vec::new(), line });
+            self.module_aliases.push(ModuleAlias { alias: captures[1].to_string(), module: captures[2].to_string(), line });
+        }
+        for (pattern, target) in [(&export_named, &mut self.reexports), (&import_named, &mut self.imports)] {
+            for captures in pattern.captures_iter(code) {
+                let line = line_of(code, captures.get(0).unwrap().start());
+                let names = aliased_names(&captures[1], " as ");
+                if !names.is_empty() {
+                    target.push(ModuleImport { module: captures[2].to_string(), names, line });
+                }
+            }
+        }
+    }
+
+    fn scan_java(&mut self, code: &str) {
+        let statement = Regex::new(r"(?m)^[ \t]*import\s+(?:static\s+)?([\w.]+)(\.\*)?\s*;").unwrap();
+        for captures in statement.captures_iter(code) {
+            let line = line_of(code, captures.get(0).unwrap().start());
+            let path = &captures[1];
+            let import = if captures.get(2).is_some() {
+                ModuleImport { module: path.to_string(), names: Vec::new(), line }
+            } else {
+                let Some((module, name)) = path.rsplit_once('.') else { continue };
+                ModuleImport { module: module.to_string(), names: vec![(name.to_string(), name.to_string())], line }
+            };
+            self.imports.push(import);
+        }
+    }
+}
+
+/// 展开 Rust 的 use 树：`a::b::{c, d as e, f::*}` -> (模块, Some((别名, 名字)))，通配为 (模块, None)
+fn rust_use_items(tree: &str, prefix: &str, items: &mut Vec<(String, Option<(String, String)>)>) {
+    let join = |module: &str| match (prefix.is_empty(), module.is_empty()) {
+        (true, _) => module.to_string(),
+        (false, true) => prefix.to_string(),
+        (false, false) => format!("{}::{}", prefix, module),
+    };
+    if let Some(open) = tree.find('{') {
+        let module = tree[..open].trim().trim_end_matches("::");
+        let inner = tree[open + 1..].trim_end();
+        let inner = inner.strip_suffix('}').unwrap_or(inner);
+        for item in split_top_level(inner) {
+            rust_use_items(item.trim(), &join(module), items);
+        }
+        return;
+    }
+    let (path, alias) = match tree.split_once(" as ") {
+        Some((path, alias)) => (path.trim(), Some(alias.trim())),
+        None => (tree.trim(), None),
+    };
+    if path.is_empty() {
+        return;
+    }
+    let (module, name) = path.rsplit_once("::").unwrap_or(("", path));
+    if name == "*" {
+        items.push((join(module), None));
+    } else {
+        items.push((join(module), Some((alias.unwrap_or(name).to_string(), name.to_string()))));
+    }
+}
+
+/// 按顶层逗号拆分，跳过嵌套的花括号
+fn split_top_level(text: &str) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let (mut depth, mut start) = (0usize, 0usize);
+    for (i, c) in text.char_indices() {
+        match c {
+            '{' => depth += 1,
+            '}' => depth = depth.saturating_sub(1),
+            ',' if depth == 0 => {
+                parts.push(&text[start..i]);
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    parts.push(&text[start..]);
+    parts.into_iter().filter(|part| !part.trim().is_empty()).collect()
+}
+
+/// `a, b as c` -> [(a, a), (c, b)]；`*` 为空列表
+fn aliased_names(list: &str, separator: &str) -> Vec<(String, String)> {
+    list.split(',')
+        .map(|item| item.split_whitespace().collect::<Vec<_>>().join(" "))
+        .map(|item| item.trim_start_matches("type ").to_string())
+        .filter(|item| !item.is_empty() && item != "*")
+        .map(|item| match item.split_once(separator) {
+            Some((name, alias)) => (alias.trim().to_string(), name.trim().to_string()),
+            None => (item.clone(), item),
+        })
+        .collect()
+}
+
+fn line_of(code: &str, offset: usize) -> usize {
+    code[..offset].matches('\n').count() + 1
+}
+
+/// 去掉路径中的 `.` 和 `..`
+pub(crate) fn normalize(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                normalized.pop();
+            }
+            other => normalized.push(other),
+        }
+    }
+    normalized
+}
+
+/// 文件作为模块时的路径：去掉扩展名，`mod.rs`、`__init__.py`、`index.ts` 等取所在目录
+fn module_path(language: &str, file: &Path) -> PathBuf {
+    let stem = file.file_stem().and_then(|stem| stem.to_str()).unwrap_or_default();
+    let directory_module = match language {
+        "rust" => stem == "mod",
+        "python" => stem == "__init__",
+        _ => stem == "index",
+    };
+    if directory_module {
+        file.parent().map(Path::to_path_buf).unwrap_or_default()
+    } else {
+        file.with_extension("")
+    }
+}
+
+/// `from` 文件中写的 `module` 是否指向 `file`
+pub fn module_resolves_to(language: &str, from: &Path, module: &str, file: &Path) -> bool {
+    let target = module_path(language, file);
+    match language {
+        "rust" => {
+            let segments: Vec<&str> = module
+                .split("::")
+                .filter(|segment| !segment.is_empty() && !matches!(*segment, "crate" | "self" | "super"))
+                .collect();
+            if segments.is_empty() {
+                // `crate::name` 指向 crate 根文件
+                let stem = file.file_stem().and_then(|stem| stem.to_str()).unwrap_or_default();
+                return module.starts_with("crate") && matches!(stem, "lib" | "main");
+            }
+            target.ends_with(segments.iter().collect::<PathBuf>())
+        }
+        "python" => {
+            let dots = module.chars().take_while(|c| *c == '.').count();
+            let rest: PathBuf = module[dots..].split('.').filter(|segment| !segment.is_empty()).collect();
+            if dots == 0 {
+                return !rest.as_os_str().is_empty() && target.ends_with(&rest);
+            }
+            let mut base = from.parent().map(Path::to_path_buf).unwrap_or_default();
+            for _ in 1..dots {
+                base.pop();
+            }
+            normalize(&base.join(rest)) == normalize(&target)
+        }
+        _ => {
+            if !module.starts_with('.') {
+                return false;
+            }
+            js_module_matches(language, &normalize(&from.parent().unwrap_or(Path::new("")).join(module)), file)
+        }
+    }
+}
+
+/// JavaScript/TypeScript 的模块路径 `base` 是否指向 `file`：
+/// `api` 可以指向 `api.ts` 或 `api/index.ts`，`api/index` 也指向后者
+fn js_module_matches(language: &str, base: &Path, file: &Path) -> bool {
+    [normalize(&module_path(language, file)), normalize(&file.with_extension(""))]
+        .iter()
+        .any(|target| target == base || *target == base.with_extension(""))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn named(module: &str, names: &[(&str, &str)], line: usize) -> ModuleImport {
+        let names = names.iter().map(|(alias, name)| (alias.to_string(), name.to_string())).collect();
+        ModuleImport { module: module.to_string(), names, line }
+    }
+
+    #[test]
+    fn test_scan_rust() {
+        let code = "use std::fmt;\npub use crate::inner::{helper, nested::{run as start}, glob::*};\npub(crate) use self::util::clamp;\n";
+        let imports = ModuleImports::scan("rust", Path::new("src/lib.rs"), code);
+        assert_eq!(imports.imports, vec![named("std", &[("fmt", "fmt")], 1)]);
+        assert_eq!(
+            imports.reexports,
+            vec![
+                named("crate::inner", &[("helper", "helper")], 2),
+                named("crate::inner::nested", &[("start", "run")], 2),
+                named("crate::inner::glob", &[], 2),
+                named("self::util", &[("clamp", "clamp")], 3),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_scan_python_and_js() {
+        let init = ModuleImports::scan("python", Path::new("pkg/__init__.py"), "from .core import load, save as store\n");
+        assert_eq!(init.reexports, vec![named(".core", &[("load", "load"), ("store", "save")], 1)]);
+        let module = ModuleImports::scan("python", Path::new("app.py"), "from pkg import load\nfrom .helpers import *\n");
+        assert_eq!(module.imports, vec![named("pkg", &[("load", "load")], 1)]);
+        assert_eq!(module.reexports, vec![named(".helpers", &[], 2)]);
+
+        let code = "import os, numpy as np\nimport pkg.core as core\nfrom pkg import util\nfrom .helpers import parse as p\n";
+        let module = ModuleImports::scan("python", Path::new("app.py"), code);
+        let aliases: Vec<_> = module.module_aliases.iter().map(|a| (a.alias.as_str(), a.module.as_str(), a.line)).collect();
+        assert_eq!(aliases, vec![("os", "os", 1), ("np", "numpy", 1), ("core", "pkg.core", 2)]);
+        let targets = |receiver, name| module.call_targets("python", receiver, name);
+        assert_eq!(targets(Some("core"), "load"), vec![("pkg.core".to_string(), "load".to_string())]);
+        assert_eq!(targets(Some("util"), "clean"), vec![("pkg.util".to_string(), "clean".to_string())]);
+        assert_eq!(targets(None, "p"), vec![(".helpers".to_string(), "parse".to_string())]);
+        assert!(targets(None, "load").is_empty());
+        assert!(targets(Some("self"), "load").is_empty());
+
+        let code = "export * from './impl';\nexport { a as b } from './other';\nimport { b } from './index';\n";
+        let index = ModuleImports::scan("typescript", Path::new("src/index.ts"), code);
+        assert_eq!(index.reexports, vec![named("./impl", &[], 1), named("./other", &[("b", "a")], 2)]);
+        assert_eq!(index.imports, vec![named("./index", &[("b", "b")], 3)]);
+    }
+
+    #[test]
+    fn test_scan_java() {
+        let code = "package com.acme.app;\n\nimport com.acme.util.Strings;\nimport com.acme.model.*;\nimport static com.acme.util.Checks.notNull;\n";
+        let imports = ModuleImports::scan("java", Path::new("App.java"), code);
+        assert_eq!(
+            imports.imports,
+            vec![
+                named("com.acme.util", &[("Strings", "Strings")], 3),
+                named("com.acme.model", &[], 4),
+                named("com.acme.util.Checks", &[("notNull", "notNull")], 5),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_module_resolves_to() {
+        assert!(module_resolves_to("rust", Path::new("/p/src/lib.rs"), "crate::inner", Path::new("/p/src/inner.rs")));
+        assert!(module_resolves_to("rust", Path::new("/p/src/lib.rs"), "crate::inner", Path::new("/p/src/inner/mod.rs")));
+        assert!(module_resolves_to("rust", Path::new("/p/src/main.rs"), "crate", Path::new("/p/src/lib.rs")));
+        assert!(!module_resolves_to("rust", Path::new("/p/src/lib.rs"), "crate::inner", Path::new("/p/src/outer.rs")));
+        assert!(module_resolves_to("python", Path::new("/p/pkg/__init__.py"), ".core", Path::new("/p/pkg/core.py")));
+        assert!(module_resolves_to("python", Path::new("/p/app.py"), "pkg", Path::new("/p/pkg/__init__.py")));
+        assert!(module_resolves_to("typescript", Path::new("/p/src/app.ts"), "./api", Path::new("/p/src/api/index.ts")));
+        assert!(module_resolves_to("typescript", Path::new("/p/src/api/index.ts"), "../impl", Path::new("/p/src/impl.ts")));
+        assert!(!module_resolves_to("typescript", Path::new("/p/src/app.ts"), "lodash", Path::new("/p/src/lodash.ts")));
+    }
+}
@@ -43,6 +43,14 @@ impl RepositoryManager {
         }
     }
 
+    /// 使用已构建的调用图创建仓库管理器（不重新解析源码）
+    pub fn with_call_graph(repository_path: PathBuf, mut call_graph: PetCodeGraph) -> Self {
+        call_graph.update_stats();
+        let manager = Self::new(repository_path);
+        *manager.call_graph.write() = call_graph;
+        manager
+    }
+
     /// 初始化仓库分析
     pub fn initialize(&mut self) -> Result<(), String> {
         info!("Initializing repository analysis for: {}", self.repository_path.display());
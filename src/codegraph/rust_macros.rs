@@ -0,0 +1,383 @@
+use std::collections::HashMap;
+
+use tree_sitter::{Node, Parser};
+
+/// `macro_rules!` 匹配器中的一个元素
+#[derive(Debug, Clone)]
+enum Pattern {
+    /// 需要原样出现的记号
+    Token(String),
+    /// 括号分组，匹配调用中的一个分组
+    Group(Vec<Pattern>),
+    /// `$name:kind`
+    Binding { name: String, kind: String },
+    /// `$( ... ) 分隔符 *`
+    Repetition { inner: Vec<Pattern>, separator: Option<String> },
+}
+
+/// 宏调用参数中的一个记号
+#[derive(Debug, Clone)]
+enum Token {
+    Leaf { text: String, identifier: bool },
+    Group(Vec<Token>),
+}
+
+/// 转写器中 `fn` 之后的函数名
+#[derive(Debug, Clone)]
+enum Generated {
+    Literal(String),
+    /// 来自元变量；`repeated` 表示位于 `$( ... )*` 中，每次重复生成一个函数
+    Fragment { name: String, repeated: bool },
+}
+
+#[derive(Debug, Clone)]
+struct MacroRule {
+    matcher: Vec<Pattern>,
+    generated: Vec<Generated>,
+}
+
+/// 文件中的一个 `macro_rules!` 定义
+#[derive(Debug, Clone)]
+pub struct MacroDefinition {
+    pub name: String,
+    pub line_start: usize,
+    pub line_end: usize,
+    /// 转写器中出现的函数调用 (函数名, 行号)
+    pub calls: Vec<(String, usize)>,
+    rules: Vec<MacroRule>,
+}
+
+/// 文件中的一个宏调用 `name!(...)`
+#[derive(Debug, Clone)]
+pub struct MacroInvocation {
+    pub name: String,
+    pub line_start: usize,
+    pub line_end: usize,
+    tokens: Vec<Token>,
+}
+
+/// Rust 文件中的声明宏定义和宏调用。
+///
+/// 解析器不展开宏，这里只按 `macro_rules!` 的规则做简单匹配：找到第一条能匹配调用参数的规则，
+/// 转写器里 `fn 名字` / `fn $元变量` 即为该次调用生成的函数。
+#[derive(Debug, Clone, Default)]
+pub struct RustMacros {
+    pub definitions: Vec<MacroDefinition>,
+    pub invocations: Vec<MacroInvocation>,
+}
+
+impl RustMacros {
+    pub fn scan(code: &str) -> Self {
+        let mut parser = Parser::new();
+        if parser.set_language(&tree_sitter_rust::LANGUAGE.into()).is_err() {
+            return Self::default();
+        }
+        let Some(tree) = parser.parse(code, None) else {
+            return Self::default();
+        };
+        let mut macros = Self::default();
+        let mut stack = vec![tree.root_node()];
+        while let Some(node) = stack.pop() {
+            match node.kind() {
+                "macro_definition" => {
+                    if let Some(definition) = definition(&node, code) {
+                        macros.definitions.push(definition);
+                    }
+                    continue;
+                }
+                "macro_invocation" => {
+                    if let Some(invocation) = invocation(&node, code) {
+                        macros.invocations.push(invocation);
+                    }
+                }
+                _ => {}
+            }
+            let mut cursor = node.walk();
+            stack.extend(node.children(&mut cursor));
+        }
+        macros.definitions.sort_by_key(|definition| definition.line_start);
+        macros.invocations.sort_by_key(|invocation| invocation.line_start);
+        macros
+    }
+
+    pub fn definition(&self, name: &str) -> Option<&MacroDefinition> {
+        self.definitions.iter().find(|definition| definition.name == name)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.definitions.is_empty() && self.invocations.is_empty()
+    }
+}
+
+impl MacroDefinition {
+    /// 该宏的一次调用生成的函数名（按转写器中出现的顺序，去重）
+    pub fn generated_functions(&self, invocation: &MacroInvocation) -> Vec<String> {
+        let Some((rule, bindings)) = self.rules.iter().find_map(|rule| {
+            let mut bindings = HashMap::new();
+            let matched = match_sequence(&rule.matcher, &invocation.tokens, None, &mut bindings)?;
+            (matched == invocation.tokens.len()).then_some((rule, bindings))
+        }) else {
+            return vec![];
+        };
+        let mut names: Vec<String> = Vec::new();
+        for generated in &rule.generated {
+            let values: Vec<String> = match generated {
+                Generated::Literal(name) => vec![name.clone()],
+                Generated::Fragment { name, repeated: true } => bindings.get(name).cloned().unwrap_or_default(),
+                Generated::Fragment { name, repeated: false } => {
+                    bindings.get(name).and_then(|values| values.first().cloned()).into_iter().collect()
+                }
+            };
+            for value in values {
+                if !names.contains(&value) {
+                    names.push(value);
+                }
+            }
+        }
+        names
+    }
+}
+
+fn definition(node: &Node, code: &str) -> Option<MacroDefinition> {
+    let name = node.child_by_field_name("name")?.utf8_text(code.as_bytes()).ok()?.to_string();
+    let mut rules = Vec::new();
+    let mut calls = Vec::new();
+    let mut cursor = node.walk();
+    for rule in node.children(&mut cursor).filter(|child| child.kind() == "macro_rule") {
+        let (Some(left), Some(right)) = (rule.child_by_field_name("left"), rule.child_by_field_name("right")) else {
+            continue;
+        };
+        let mut leaves = Vec::new();
+        transcriber_leaves(&right, false, &mut leaves);
+        let mut generated = Vec::new();
+        for window in leaves.windows(3) {
+            let [(previous, _), (current, repeated), (next, _)] = window else { continue };
+            let text = current.utf8_text(code.as_bytes()).unwrap_or_default();
+            match current.kind() {
+                "identifier" if previous.kind() == "fn" => generated.push(Generated::Literal(text.to_string())),
+                "metavariable" if previous.kind() == "fn" => generated.push(Generated::Fragment {
+                    name: text.trim_start_matches('$').to_string(),
+                    repeated: *repeated,
+                }),
+                // `helper(...)`、`path::helper(...)`：标识符后紧跟圆括号分组
+                "identifier" if next.kind() == "token_tree" && next.utf8_text(code.as_bytes()).is_ok_and(|t| t.starts_with('(')) => {
+                    calls.push((text.to_string(), current.start_position().row + 1));
+                }
+                _ => {}
+            }
+        }
+        rules.push(MacroRule { matcher: patterns(&left, code), generated });
+    }
+    Some(MacroDefinition {
+        name,
+        line_start: node.start_position().row + 1,
+        line_end: node.end_position().row + 1,
+        calls,
+        rules,
+    })
+}
+
+fn invocation<'a>(node: &Node<'a>, code: &str) -> Option<MacroInvocation> {
+    let path = node.child_by_field_name("macro")?;
+    let name = match path.kind() {
+        "scoped_identifier" => path.child_by_field_name("name")?,
+        _ => path,
+    };
+    let mut cursor = node.walk();
+    let arguments = node.children(&mut cursor).find(|child| child.kind() == "token_tree")?;
+    Some(MacroInvocation {
+        name: name.utf8_text(code.as_bytes()).ok()?.to_string(),
+        line_start: node.start_position().row + 1,
+        line_end: node.end_position().row + 1,
+        tokens: tokens(&arguments, code),
+    })
+}
+
+/// 分组内的子节点（去掉首尾的括号）
+fn inner_children<'a>(node: &Node<'a>) -> Vec<Node<'a>> {
+    let mut cursor = node.walk();
+    let children: Vec<Node<'a>> = node.children(&mut cursor).collect();
+    match children.len() {
+        0..=2 => vec![],
+        len => children[1..len - 1].to_vec(),
+    }
+}
+
+fn tokens(node: &Node, code: &str) -> Vec<Token> {
+    inner_children(node)
+        .iter()
+        .map(|child| match child.kind() {
+            "token_tree" => Token::Group(tokens(child, code)),
+            kind => Token::Leaf {
+                text: child.utf8_text(code.as_bytes()).unwrap_or_default().to_string(),
+                identifier: kind == "identifier",
+            },
+        })
+        .collect()
+}
+
+fn patterns(node: &Node, code: &str) -> Vec<Pattern> {
+    pattern_list(&inner_children(node), code)
+}
+
+fn pattern_list(nodes: &[Node], code: &str) -> Vec<Pattern> {
+    let text = |node: &Node| node.utf8_text(code.as_bytes()).unwrap_or_default().to_string();
+    nodes
+        .iter()
+        .filter_map(|node| {
+            Some(match node.kind() {
+                "token_tree_pattern" => Pattern::Group(patterns(node, code)),
+                "token_binding_pattern" => Pattern::Binding {
+                    name: text(&node.child_by_field_name("name")?).trim_start_matches('$').to_string(),
+                    kind: text(&node.child_by_field_name("type")?),
+                },
+                "token_repetition_pattern" => {
+                    // `$ ( 元素... ) 分隔符? 重复符`
+                    let mut cursor = node.walk();
+                    let children: Vec<Node> = node.children(&mut cursor).collect();
+                    let close = children.iter().rposition(|child| child.kind() == ")")?;
+                    Pattern::Repetition {
+                        inner: pattern_list(children.get(2..close)?, code),
+                        // 分隔符不是语法树中的节点，取 `)` 与重复符之间的源码
+                        separator: children
+                            .last()
+                            .and_then(|op| code.get(children[close].end_byte()..op.start_byte()))
+                            .map(str::trim)
+                            .filter(|separator| !separator.is_empty())
+                            .map(str::to_string),
+                    }
+                }
+                _ => Pattern::Token(text(node)),
+            })
+        })
+        .collect()
+}
+
+/// 转写器中的叶子记号，标记是否位于 `$( ... )` 重复中
+fn transcriber_leaves<'a>(node: &Node<'a>, repeated: bool, leaves: &mut Vec<(Node<'a>, bool)>) {
+    let mut cursor = node.walk();
+    for child in node.children(&mut cursor) {
+        match child.kind() {
+            // 分组本身也作为叶子保留，用来判断标识符后是否紧跟调用参数
+            "token_tree" => {
+                leaves.push((child, repeated));
+                transcriber_leaves(&child, repeated, leaves);
+            }
+            "token_repetition" => transcriber_leaves(&child, true, leaves),
+            _ => leaves.push((child, repeated)),
+        }
+    }
+}
+
+fn token_text(token: &Token) -> String {
+    match token {
+        Token::Leaf { text, .. } => text.clone(),
+        Token::Group(tokens) => tokens.iter().map(token_text).collect::<Vec<_>>().join(" "),
+    }
+}
+
+/// 从 `tokens` 开头匹配 `patterns`，返回消耗的记号数；`follow` 为模式之后应出现的记号
+fn match_sequence(
+    patterns: &[Pattern],
+    tokens: &[Token],
+    follow: Option<&str>,
+    bindings: &mut HashMap<String, Vec<String>>,
+) -> Option<usize> {
+    let mut position = 0;
+    for (index, pattern) in patterns.iter().enumerate() {
+        // 非标识符片段一直吞到下一个字面记号为止
+        let stop = patterns[index + 1..]
+            .iter()
+            .find_map(|pattern| match pattern {
+                Pattern::Token(text) => Some(text.as_str()),
+                Pattern::Repetition { separator: Some(separator), .. } => Some(separator.as_str()),
+                _ => None,
+            })
+            .or(follow);
+        match pattern {
+            Pattern::Token(text) => match tokens.get(position)? {
+                Token::Leaf { text: token, .. } if token == text => position += 1,
+                _ => return None,
+            },
+            Pattern::Group(inner) => match tokens.get(position)? {
+                Token::Group(group) => {
+                    (match_sequence(inner, group, None, bindings)? == group.len()).then_some(())?;
+                    position += 1;
+                }
+                _ => return None,
+            },
+            Pattern::Binding { name, kind } => {
+                let consumed = match kind.as_str() {
+                    "ident" | "lifetime" | "tt" | "literal" => {
+                        let token = tokens.get(position)?;
+                        if kind == "ident" && !matches!(token, Token::Leaf { identifier: true, .. }) {
+                            return None;
+                        }
+                        1
+                    }
+                    _ => {
+                        let rest = &tokens[position.min(tokens.len())..];
+                        let length = rest
+                            .iter()
+                            .position(|token| matches!((token, stop), (Token::Leaf { text, .. }, Some(stop)) if text == stop))
+                            .unwrap_or(rest.len());
+                        if length == 0 {
+                            return None;
+                        }
+                        length
+                    }
+                };
+                let text = tokens[position..position + consumed].iter().map(token_text).collect::<Vec<_>>().join(" ");
+                bindings.entry(name.clone()).or_default().push(text);
+                position += consumed;
+            }
+            Pattern::Repetition { inner, separator } => {
+                if inner.is_empty() {
+                    continue;
+                }
+                let inner_follow = separator.as_deref().or(stop);
+                while position < tokens.len() {
+                    let mut attempt = bindings.clone();
+                    let Some(consumed) = match_sequence(inner, &tokens[position..], inner_follow, &mut attempt) else { break };
+                    if consumed == 0 {
+                        break;
+                    }
+                    *bindings = attempt;
+                    position += consumed;
+                    match (separator, tokens.get(position)) {
+                        (Some(separator), Some(Token::Leaf { text, .. })) if text == separator => position += 1,
+                        (Some(_), _) => break,
+                        (None, _) => {}
+                    }
+                }
+            }
+        }
+    }
+    Some(position)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_generated_functions() {
+        let code = "macro_rules! getter {\n    ($name:ident, $value:expr) => {\n        pub fn $name() -> i32 { checked($value) }\n    };\n}\n\nmacro_rules! handlers {\n    ($($name:ident),* $(,)?) => {\n        $( fn $name() { log_call(stringify!($name)); } )*\n        fn handler_count() -> usize { 0 }\n    };\n}\n\ngetter!(answer, 40 + 2);\nhandlers!(on_open, on_close,);\n\nfn main() {\n    println!(\"{}\", answer());\n}\n";
+        let macros = RustMacros::scan(code);
+        let names: Vec<&str> = macros.definitions.iter().map(|d| d.name.as_str()).collect();
+        assert_eq!(names, vec!["getter", "handlers"]);
+        let invoked: Vec<(&str, usize)> = macros.invocations.iter().map(|i| (i.name.as_str(), i.line_start)).collect();
+        assert_eq!(invoked, vec![("getter", 14), ("handlers", 15), ("println", 18)]);
+
+        assert_eq!(macros.definitions[0].generated_functions(&macros.invocations[0]), vec!["answer"]);
+        assert_eq!(
+            macros.definitions[1].generated_functions(&macros.invocations[1]),
+            vec!["on_open", "on_close", "handler_count"]
+        );
+        // 参数不匹配任何规则时不生成函数
+        assert!(macros.definitions[0].generated_functions(&macros.invocations[2]).is_empty());
+
+        assert_eq!(macros.definitions[0].calls, vec![("checked".to_string(), 3)]);
+        assert_eq!(macros.definitions[1].calls, vec![("log_call".to_string(), 9)]);
+    }
+}
@@ -0,0 +1,129 @@
+use std::collections::HashMap;
+use std::sync::LazyLock;
+
+use regex::Regex;
+use uuid::Uuid;
+
+use crate::codegraph::treesitter::structs::SymbolType;
+use crate::codegraph::treesitter::{AstSymbolInstance, AstSymbolInstanceArc, FunctionDeclaration, TypeDef, VariableDefinition};
+
+/// 关联函数构造的值 `Type::new(..)`、`Type::<T>::with(..)`，捕获类型名
+static CONSTRUCTOR: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^([A-Z][A-Za-z0-9_]*)(?:::<.*?>)?::[a-z_][A-Za-z0-9_]*\s*\(").unwrap());
+
+/// 一个已知类型的局部名字：带类型标注的参数或 let 绑定
+#[derive(Debug, Clone)]
+struct Binding {
+    name: String,
+    type_name: String,
+    /// 绑定出现的行（从1开始），参数为函数的起始行
+    line: usize,
+}
+
+/// Rust 文件中方法调用接收者的类型。
+///
+/// 只处理能在本文件内直接确定的情况：`self`（所在 impl 块的类型）、带类型标注的参数和 let 绑定、
+/// 结构体字面量以及 `Type::new(..)` 这类关联函数构造的变量。
+#[derive(Debug, Default)]
+pub struct ReceiverTypes {
+    /// 变量使用的 guid -> 变量名
+    usages: HashMap<Uuid, String>,
+    bindings: Vec<Binding>,
+    code: String,
+}
+
+impl ReceiverTypes {
+    pub fn new(symbols: &[AstSymbolInstanceArc], code: String) -> Self {
+        let mut receivers = Self::default();
+        for symbol in symbols {
+            let mut guard = symbol.write();
+            let line = guard.full_range().start_point.row + 1;
+            match guard.symbol_type() {
+                SymbolType::VariableUsage => {
+                    // 字段使用 `a.b` 的范围是整个表达式，只有单独的变量名才能作为接收者
+                    let range = guard.full_range();
+                    if code.get(range.start_byte..range.end_byte) == Some(guard.name()) {
+                        receivers.usages.insert(*guard.guid(), guard.name().to_string());
+                    }
+                }
+                SymbolType::VariableDefinition => {
+                    let name = guard.name().to_string();
+                    let Some(variable) = guard.as_any_mut().downcast_ref::<VariableDefinition>() else { continue };
+                    let type_name = type_name(&variable.type_).or_else(|| {
+                        let value = variable.type_.inference_info.as_deref()?;
+                        Some(CONSTRUCTOR.captures(value)?[1].to_string())
+                    });
+                    if let Some(type_name) = type_name {
+                        receivers.bindings.push(Binding { name, type_name, line });
+                    }
+                }
+                SymbolType::FunctionDeclaration => {
+                    let Some(function) = guard.as_any_mut().downcast_ref::<FunctionDeclaration>() else { continue };
+                    for arg in &function.args {
+                        if let Some(type_name) = arg.type_.as_ref().and_then(type_name) {
+                            receivers.bindings.push(Binding { name: arg.name.clone(), type_name, line });
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+        receivers.code = code;
+        receivers
+    }
+
+    /// 方法调用接收者的类型；`caller_range` 为调用者函数的行范围，`caller_owner` 为调用者所在 impl 块的类型
+    pub fn receiver_type(
+        &self,
+        call: &dyn AstSymbolInstance,
+        caller_range: (usize, usize),
+        caller_owner: Option<&str>,
+    ) -> Option<String> {
+        // 解析器不为 `self` 生成变量使用，按调用的源码判断
+        let text = self.code.get(call.full_range().start_byte..call.full_range().end_byte)?;
+        let on_self = text
+            .strip_prefix("self")
+            .and_then(|rest| rest.trim_start().strip_prefix('.'))
+            .and_then(|rest| rest.trim_start().strip_prefix(call.name()))
+            .is_some_and(|rest| rest.trim_start().starts_with(['(', ':']));
+        if on_self {
+            return caller_owner.map(str::to_string);
+        }
+        let receiver = self.usages.get(call.fields().caller_guid.as_ref()?)?;
+        let call_line = call.full_range().start_point.row + 1;
+        // 取调用之前、同一函数内最近的同名绑定，后面的 let 遮蔽前面的
+        self.bindings
+            .iter()
+            .filter(|binding| binding.name == *receiver)
+            .filter(|binding| binding.line >= caller_range.0 && binding.line <= call_line.min(caller_range.1))
+            .max_by_key(|binding| binding.line)
+            .map(|binding| binding.type_name.clone())
+    }
+}
+
+/// 函数声明 guid -> 所在 impl 或 trait 块的类型名
+pub fn impl_owners(symbols: &[AstSymbolInstanceArc]) -> HashMap<Uuid, String> {
+    let blocks: HashMap<Uuid, String> = symbols
+        .iter()
+        .filter_map(|symbol| {
+            let guard = symbol.read();
+            (guard.symbol_type() == SymbolType::StructDeclaration).then(|| (*guard.guid(), guard.name().to_string()))
+        })
+        .collect();
+    symbols
+        .iter()
+        .filter_map(|symbol| {
+            let guard = symbol.read();
+            if guard.symbol_type() != SymbolType::FunctionDeclaration {
+                return None;
+            }
+            let owner = blocks.get(guard.parent_guid().as_ref()?)?;
+            Some((*guard.guid(), owner.clone()))
+        })
+        .collect()
+}
+
+/// `Self` 和基本类型无法用来查找 impl 块
+fn type_name(type_: &TypeDef) -> Option<String> {
+    type_.name.clone().filter(|name| !type_.is_pod && name != "Self" && name.starts_with(|c: char| c.is_ascii_uppercase()))
+}
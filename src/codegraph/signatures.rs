@@ -0,0 +1,528 @@
+use regex::Regex;
+
+use crate::codegraph::overloads::{close_of, parenthesis_after, split_top_level, strip_parameter_name, top_level_find};
+use crate::codegraph::types::FunctionParameter;
+
+/// 返回类型写在函数名之前的语言
+const C_LIKE_LANGUAGES: &[&str] = &["java", "cpp", "objc"];
+
+/// 写在返回类型之前的修饰符
+const MODIFIERS: &[&str] = &[
+    "public", "private", "protected", "internal", "static", "final", "abstract", "synchronized", "native", "default", "strictfp",
+    "inline", "virtual", "explicit", "extern", "constexpr", "consteval", "friend", "override", "async", "export", "template",
+];
+
+/// 从函数声明源码提取的签名
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Signature {
+    /// 函数体之前的声明部分，空白压缩为单个空格
+    pub text: String,
+    pub return_type: Option<String>,
+    pub parameters: Vec<FunctionParameter>,
+}
+
+/// 从函数的完整源码（tree-sitter 的完整范围）中提取声明、返回类型和形参；
+/// 找不到形参列表时（Bash 脚本节点等）只返回第一行
+pub fn extract(language: &str, source: &str, name: &str) -> Option<Signature> {
+    if language == "objc" && source.trim_start().starts_with(['-', '+']) {
+        return objc_method(source);
+    }
+    let masked = mask(language, source);
+    let angles = matches!(language, "rust" | "java" | "cpp" | "typescript" | "kotlin" | "swift");
+    // 限定名（`UserService.GetUser`、`dbo.usp_audit`）在声明中只写最后一段；
+    // Bash 的函数名本身可以含 `.` 和 `::`（`util::log`），脚本节点的名字是文件名
+    let bash = language == "bash";
+    let short = if bash { name } else { name.rsplit(['.', ':']).next().unwrap_or(name) };
+    let span = parenthesis_after(&masked, short, angles).or_else(|| {
+        // `const f = (a) => ..`、`f = function(a) ..`：名字之后的第一个括号；
+        // Bash 的 `function f {` 没有括号，之后的括号是命令替换 `$(..)`
+        if bash {
+            return None;
+        }
+        let at = masked.find(short)? + short.len();
+        let open = at + masked[at..].find('(')?;
+        Some((open, close_of(masked.as_bytes(), open, angles)?))
+    });
+    let Some((open, close)) = span else {
+        let line = source.lines().map(str::trim).find(|line| !line.is_empty())?;
+        let text = collapse(line.split('{').next().unwrap_or(line));
+        return (!text.is_empty()).then(|| Signature { text, ..Default::default() });
+    };
+
+    let name_at = masked[..open].rfind(short).unwrap_or(open);
+    let start = header_start(language, source, name_at);
+    let end = header_end(language, &masked, close + 1);
+    let prefix = &source[start..name_at];
+    let tail = &source[close + 1..end];
+    Some(Signature {
+        text: collapse(&source[start..end]),
+        return_type: return_type(language, prefix, tail),
+        parameters: parameters(language, &source[open + 1..close], &masked[open + 1..close], angles),
+    })
+}
+
+/// Objective-C 方法 `- (NSString *)name:(int)a with:(id)b`：返回类型和形参类型写在括号里，
+/// 形参名跟在类型之后
+fn objc_method(source: &str) -> Option<Signature> {
+    let header = source.trim_start();
+    let header = &header[..header.find(['{', ';']).unwrap_or(header.len())];
+    let text = collapse(header);
+    // `at` 处的括号中的类型和括号之后的位置
+    let typed = |at: usize| -> Option<(String, usize)> {
+        let rest = &header[at..];
+        let open = at + rest.len() - rest.trim_start().len();
+        if !header[open..].starts_with('(') {
+            return None;
+        }
+        let close = close_of(header.as_bytes(), open, false)?;
+        Some((collapse(&header[open + 1..close]), close + 1))
+    };
+    let return_type = typed(1).map(|(type_, _)| type_);
+    let mut parameters = Vec::new();
+    let mut rest = 1;
+    while let Some(colon) = header[rest..].find(':').map(|at| rest + at) {
+        let (type_, end) = typed(colon + 1).map_or((None, colon + 1), |(type_, end)| (Some(type_), end));
+        let name = header[end..].trim_start();
+        let name = &name[..name.find(|c: char| !(c.is_alphanumeric() || c == '_')).unwrap_or(name.len())];
+        parameters.push(FunctionParameter { name: name.to_string(), type_ });
+        rest = end;
+    }
+    Some(Signature { text, return_type: return_type.filter(|type_| !type_.is_empty()), parameters })
+}
+
+/// 在类型的声明之外定义的方法所属的类型名：Go 方法的接收者类型、C++ 类外定义的 `Class::method`。
+/// `text` 是 [`extract`] 得到的声明
+pub fn declared_owner(language: &str, text: &str, name: &str) -> Option<String> {
+    let owner = match language {
+        "go" => {
+            let receiver = text.strip_prefix("func")?.trim_start().strip_prefix('(')?;
+            let receiver = &receiver[..receiver.find([')', '['])?];
+            receiver.split_whitespace().last()?.trim_start_matches('*')
+        }
+        "cpp" | "objc" => {
+            let short = name.rsplit("::").next().unwrap_or(name);
+            let qualified = format!("::{}", short);
+            let at = text.match_indices(&qualified).map(|(at, _)| at).find(|&at| {
+                !text[at + qualified.len()..].starts_with(|c: char| c.is_alphanumeric() || c == '_')
+            })?;
+            // `Buffer<T>::name` 中的模板实参
+            let mut before = &text[..at];
+            if before.ends_with('>') {
+                let mut depth = 0;
+                let open = before.char_indices().rev().find(|&(_, c)| {
+                    depth += match c {
+                        '>' => 1,
+                        '<' => -1,
+                        _ => 0,
+                    };
+                    depth == 0
+                })?;
+                before = &before[..open.0];
+            }
+            let start = before.rfind(|c: char| !(c.is_alphanumeric() || c == '_')).map_or(0, |at| at + 1);
+            &before[start..]
+        }
+        _ => return None,
+    };
+    (!owner.is_empty()).then(|| owner.to_string())
+}
+
+/// 用于查找的文本，与源码逐字节对应：Rust 生命周期 `'a` 中的单引号不是字符字面量，换成其他字符以免被当作字符串；
+/// SQL 标识符不区分大小写，解析器记录的函数名是小写的
+fn mask(language: &str, source: &str) -> String {
+    match language {
+        "rust" => source.replace('\'', "_"),
+        "sql" => source.to_ascii_lowercase(),
+        _ => source.to_string(),
+    }
+}
+
+/// 声明开始的位置：函数名所在行的行首；C 系语言中返回类型或模板头单独占一行时包含上一行
+fn header_start(language: &str, source: &str, name_at: usize) -> usize {
+    let line_start = source[..name_at].rfind('\n').map_or(0, |at| at + 1);
+    if !C_LIKE_LANGUAGES.contains(&language) || line_start == 0 {
+        return line_start;
+    }
+    let previous_start = source[..line_start - 1].rfind('\n').map_or(0, |at| at + 1);
+    let previous = source[previous_start..line_start].trim();
+    let is_type_line = !previous.is_empty()
+        && !previous.ends_with([';', '{', '}', ')'])
+        && !previous.starts_with(['@', '#', '/', '*']);
+    if is_type_line { previous_start } else { line_start }
+}
+
+/// 声明结束（函数体开始）的位置
+fn header_end(language: &str, masked: &str, from: usize) -> usize {
+    let rest = &masked[from..];
+    let found = match language {
+        "python" => top_level_find(rest, ':'),
+        "sql" => Regex::new(r"(?i)\b(AS|IS|BEGIN|LANGUAGE)\b").ok().and_then(|re| re.find(rest)).map(|m| m.start()),
+        "lua" | "ruby" => rest.find('\n'),
+        _ => {
+            // Kotlin 和 Scala 的表达式函数体 `fun f(x: Int) = x * 2`
+            let expression_body = matches!(language, "kotlin" | "scala");
+            let bytes = rest.as_bytes();
+            let mut depth = 0i32;
+            (0..bytes.len()).find(|&i| match bytes[i] {
+                b'(' | b'[' => {
+                    depth += 1;
+                    false
+                }
+                b')' | b']' => {
+                    depth -= 1;
+                    false
+                }
+                b'{' | b';' => depth <= 0,
+                b'=' => depth <= 0 && (expression_body || bytes.get(i + 1) == Some(&b'>')),
+                _ => false,
+            })
+        }
+    };
+    from + found.unwrap_or(rest.len())
+}
+
+fn return_type(language: &str, prefix: &str, tail: &str) -> Option<String> {
+    let tail = collapse(tail);
+    let tail = tail.as_str();
+    let returned = match language {
+        "rust" => tail.split_once("->").map(|(_, type_)| type_.split(" where ").next().unwrap_or(type_)),
+        "python" => tail.split_once("->").map(|(_, type_)| type_),
+        "swift" => tail.split_once("->").map(|(_, type_)| type_.split(" where ").next().unwrap_or(type_)),
+        "typescript" | "javascript" | "vue" | "svelte" | "html" => tail.strip_prefix(':'),
+        "kotlin" => tail.strip_prefix(':').map(|type_| type_.split(" where ").next().unwrap_or(type_)),
+        "scala" => {
+            // 柯里化的其余形参列表 `(implicit ec: ExecutionContext)`
+            let mut rest = tail;
+            while rest.starts_with('(') {
+                rest = rest[close_of(rest.as_bytes(), 0, false)? + 1..].trim_start();
+            }
+            rest.strip_prefix(':')
+        }
+        "go" => Some(tail),
+        "sql" => Regex::new(r"(?is)^RETURNS\s+(.+)$").ok()?.captures(tail).and_then(|c| c.get(1)).map(|m| m.as_str()),
+        "proto" => Regex::new(r"(?i)^returns\s*\((.+)\)").ok()?.captures(tail).and_then(|c| c.get(1)).map(|m| m.as_str()),
+        "cpp" if tail.contains("->") => tail.split_once("->").map(|(_, type_)| type_),
+        _ if C_LIKE_LANGUAGES.contains(&language) => Some(c_return_type(prefix)?),
+        _ => None,
+    };
+    returned.map(collapse).filter(|type_| !type_.is_empty())
+}
+
+/// 函数名之前去掉修饰符、注解、模板头和类名限定后剩下的返回类型：
+/// `public static <T> List<T>` 为 `List<T>`，`int Point::` 为 `int`
+fn c_return_type(prefix: &str) -> Option<&str> {
+    let mut rest = prefix.trim();
+    loop {
+        let before = rest;
+        if let Some(word) = rest.split_whitespace().next() {
+            if MODIFIERS.contains(&word) || word.starts_with('@') {
+                rest = rest[word.len()..].trim_start();
+                // 注解的参数 `@SuppressWarnings("x")`
+                if word.starts_with('@') && !word.contains('(') && rest.starts_with('(') {
+                    rest = rest[close_of(rest.as_bytes(), 0, false)? + 1..].trim_start();
+                }
+            }
+        }
+        // 模板头和泛型方法的类型参数
+        if rest.starts_with('<') {
+            rest = rest[close_of(rest.as_bytes(), 0, true)? + 1..].trim_start();
+        }
+        if rest == before {
+            break;
+        }
+    }
+    // 类名限定 `Point::`、析构函数的 `~`
+    let mut rest = rest.trim_end().trim_end_matches('~');
+    while let Some(stripped) = rest.strip_suffix("::") {
+        let qualifier = stripped.rfind(|c: char| !(c.is_alphanumeric() || c == '_')).map_or(0, |at| at + 1);
+        rest = stripped[..qualifier].trim_end();
+    }
+    Some(rest)
+}
+
+/// `inner` 为括号内的源码，`masked_inner` 为同一范围的掩码文本（用于拆分）
+fn parameters(language: &str, inner: &str, masked_inner: &str, angles: bool) -> Vec<FunctionParameter> {
+    let items: Vec<&str> = split_top_level(masked_inner, angles)
+        .into_iter()
+        .map(|item| {
+            let offset = item.as_ptr() as usize - masked_inner.as_ptr() as usize;
+            inner[offset..offset + item.len()].trim()
+        })
+        .filter(|item| !item.is_empty())
+        .collect();
+    if language == "go" {
+        return go_parameters(&items);
+    }
+    items.into_iter().filter_map(|item| parameter(language, item)).collect()
+}
+
+fn parameter(language: &str, item: &str) -> Option<FunctionParameter> {
+    let declared = match top_level_find(item, '=') {
+        Some(at) => item[..at].trim(),
+        None => item,
+    };
+    let (name, type_) = match language {
+        "rust" => match declared.trim_start_matches("mut ").trim() {
+            "self" => ("self".to_string(), Some("Self".to_string())),
+            "&self" => ("self".to_string(), Some("&Self".to_string())),
+            "&mut self" => ("self".to_string(), Some("&mut Self".to_string())),
+            declared => name_colon_type(declared.trim_start_matches("mut ")),
+        },
+        "python" => {
+            // `/` 和 `*` 只分隔仅位置参数和仅关键字参数
+            if declared == "/" || declared == "*" {
+                return None;
+            }
+            name_colon_type(declared)
+        }
+        "typescript" | "javascript" | "vue" | "svelte" | "html" => {
+            let declared = ["public ", "private ", "protected ", "readonly "]
+                .iter()
+                .fold(declared, |declared, modifier| declared.strip_prefix(modifier).unwrap_or(declared).trim_start());
+            let (name, type_) = name_colon_type(declared);
+            (name.trim_end_matches('?').to_string(), type_)
+        }
+        "kotlin" => {
+            let declared = ["vararg ", "val ", "var ", "noinline ", "crossinline "]
+                .iter()
+                .fold(declared, |declared, modifier| declared.strip_prefix(modifier).unwrap_or(declared).trim_start());
+            name_colon_type(declared)
+        }
+        "swift" => {
+            // `_ s: String`、`from s: String`：冒号前的最后一个词是形参名，前面是实参标签
+            let (label, type_) = name_colon_type(declared);
+            (label.rsplit(' ').next().unwrap_or_default().to_string(), type_)
+        }
+        "scala" => {
+            let declared = ["implicit ", "using ", "val ", "var "]
+                .iter()
+                .fold(declared, |declared, modifier| declared.strip_prefix(modifier).unwrap_or(declared).trim_start());
+            name_colon_type(declared)
+        }
+        "lua" | "bash" => (collapse(declared), None),
+        // `*rest`、`key:`、`&block`
+        "ruby" => {
+            let name = declared.split(':').next().unwrap_or(declared);
+            (collapse(name.trim_start_matches(['*', '&'])), None)
+        }
+        "sql" => {
+            let declared = Regex::new(r"(?is)^(IN\s+|OUT\s+|INOUT\s+)?(.*?)(\s+DEFAULT\s+.*)?$")
+                .ok()
+                .and_then(|re| re.captures(declared)?.get(2).map(|m| m.as_str()))
+                .unwrap_or(declared);
+            match declared.split_once(char::is_whitespace) {
+                Some((name, type_)) => (name.to_string(), Some(collapse(type_))),
+                None => (declared.to_string(), None),
+            }
+        }
+        "proto" => (String::new(), Some(collapse(declared))),
+        _ => {
+            // C、C++、Java、Objective-C：类型在前，参数名在后
+            if declared == "void" || declared.contains("...") && !declared.contains(char::is_alphanumeric) {
+                return (declared != "void").then(|| FunctionParameter { name: declared.to_string(), type_: None });
+            }
+            let declared = declared
+                .split_whitespace()
+                .filter(|word| !word.starts_with('@') && *word != "final")
+                .collect::<Vec<_>>()
+                .join(" ");
+            let type_ = strip_parameter_name(&declared);
+            let name = declared[type_.len()..].trim().trim_end_matches(['[', ']']).to_string();
+            (name, Some(collapse(type_)))
+        }
+    };
+    (!name.is_empty() || type_.is_some()).then(|| FunctionParameter { name, type_: type_.filter(|t| !t.is_empty()) })
+}
+
+/// `name: Type`，没有类型时只有名字
+fn name_colon_type(declared: &str) -> (String, Option<String>) {
+    match top_level_find(declared, ':') {
+        Some(at) => (collapse(&declared[..at]), Some(collapse(&declared[at + 1..]))),
+        None => (collapse(declared), None),
+    }
+}
+
+/// Go 的形参要么都有名字要么都没有，`a, b int` 中的 `a` 沿用后面的类型
+fn go_parameters(items: &[&str]) -> Vec<FunctionParameter> {
+    let named = items.iter().any(|item| item.split_whitespace().nth(1).is_some());
+    if !named {
+        return items.iter().map(|item| FunctionParameter { name: String::new(), type_: Some(collapse(item)) }).collect();
+    }
+    let mut parameters: Vec<FunctionParameter> = Vec::new();
+    let mut pending = 0;
+    for item in items {
+        match item.split_once(char::is_whitespace) {
+            Some((name, type_)) => {
+                let type_ = collapse(type_);
+                let start = parameters.len() - pending;
+                for parameter in &mut parameters[start..] {
+                    parameter.type_ = Some(type_.clone());
+                }
+                pending = 0;
+                parameters.push(FunctionParameter { name: name.to_string(), type_: Some(type_) });
+            }
+            None => {
+                pending += 1;
+                parameters.push(FunctionParameter { name: item.to_string(), type_: None });
+            }
+        }
+    }
+    parameters
+}
+
+fn collapse(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn summary(signature: &Signature) -> (String, Option<String>, Vec<String>) {
+        let parameters = signature
+            .parameters
+            .iter()
+            .map(|p| match &p.type_ {
+                Some(type_) => format!("{}: {}", p.name, type_),
+                None => p.name.clone(),
+            })
+            .collect();
+        (signature.text.clone(), signature.return_type.clone(), parameters)
+    }
+
+    fn check(language: &str, source: &str, name: &str, text: &str, returns: Option<&str>, parameters: &[&str]) {
+        let signature = extract(language, source, name).unwrap();
+        assert_eq!(
+            summary(&signature),
+            (text.to_string(), returns.map(str::to_string), parameters.iter().map(|p| p.to_string()).collect()),
+            "{} {}",
+            language,
+            name
+        );
+    }
+
+    #[test]
+    fn test_declared_owner() {
+        assert_eq!(declared_owner("go", "func (s *Server) Handle(w http.ResponseWriter)", "Handle").as_deref(), Some("Server"));
+        assert_eq!(declared_owner("go", "func (s *Stack[K, V]) Push(v V)", "Push").as_deref(), Some("Stack"));
+        assert_eq!(declared_owner("go", "func Free()", "Free"), None);
+        assert_eq!(declared_owner("cpp", "static const char *ns::Buffer<T>::name(int)", "name").as_deref(), Some("Buffer"));
+        assert_eq!(declared_owner("cpp", "Buffer::~Buffer()", "~Buffer").as_deref(), Some("Buffer"));
+        assert_eq!(declared_owner("cpp", "void free(int)", "free"), None);
+    }
+
+    #[test]
+    fn test_extracts_signatures_per_language() {
+        check(
+            "rust",
+            "pub fn longest<'a>(&self, items: &'a [String],\n    limit: usize) -> Option<&'a str>\nwhere\n    Self: Sized,\n{\n    None\n}",
+            "longest",
+            "pub fn longest<'a>(&self, items: &'a [String], limit: usize) -> Option<&'a str> where Self: Sized,",
+            Some("Option<&'a str>"),
+            &["self: &Self", "items: &'a [String]", "limit: usize"],
+        );
+        check(
+            "python",
+            "@cache\ndef load(path: str, *, retries: int = 3, **kwargs) -> Dict[str, Any]:\n    return {}\n",
+            "load",
+            "def load(path: str, *, retries: int = 3, **kwargs) -> Dict[str, Any]",
+            Some("Dict[str, Any]"),
+            &["path: str", "retries: int", "**kwargs"],
+        );
+        check(
+            "typescript",
+            "export async function fetchUser(id: string, opts?: Options): Promise<User> {\n  return api(id);\n}",
+            "fetchUser",
+            "export async function fetchUser(id: string, opts?: Options): Promise<User>",
+            Some("Promise<User>"),
+            &["id: string", "opts: Options"],
+        );
+        check("javascript", "const handler = async (req, res) => {\n  res.end();\n}", "handler", "const handler = async (req, res)", None, &["req", "res"]);
+        check(
+            "java",
+            "@Override\npublic static <T extends Comparable<T>> List<T> sorted(final Collection<T> items, int limit) {\n  return null;\n}",
+            "sorted",
+            "public static <T extends Comparable<T>> List<T> sorted(final Collection<T> items, int limit)",
+            Some("List<T>"),
+            &["items: Collection<T>", "limit: int"],
+        );
+        check(
+            "cpp",
+            "template <typename T>\nstatic const char *Buffer::name(const std::vector<T> &values, int, ...) {\n  return 0;\n}",
+            "name",
+            "template <typename T> static const char *Buffer::name(const std::vector<T> &values, int, ...)",
+            Some("const char *"),
+            &["values: const std::vector<T> &", ": int", "..."],
+        );
+        check(
+            "go",
+            "func (s *Server) Handle(w http.ResponseWriter, a, b int) (int, error) {\n\treturn 0, nil\n}",
+            "Handle",
+            "func (s *Server) Handle(w http.ResponseWriter, a, b int) (int, error)",
+            Some("(int, error)"),
+            &["w: http.ResponseWriter", "a: int", "b: int"],
+        );
+        check(
+            "sql",
+            "CREATE FUNCTION dbo.total(p_id INT, IN p_name VARCHAR(20) DEFAULT 'x')\nRETURNS int AS $$ SELECT 1 $$ LANGUAGE sql;",
+            "total",
+            "CREATE FUNCTION dbo.total(p_id INT, IN p_name VARCHAR(20) DEFAULT 'x') RETURNS int",
+            Some("int"),
+            &["p_id: INT", "p_name: VARCHAR(20)"],
+        );
+        check(
+            "proto",
+            "rpc GetUser(GetUserRequest) returns (GetUserResponse);",
+            "UserService.GetUser",
+            "rpc GetUser(GetUserRequest) returns (GetUserResponse)",
+            Some("GetUserResponse"),
+            &[": GetUserRequest"],
+        );
+        check(
+            "kotlin",
+            "override fun <T> load(vararg ids: Int, mapper: (Int) -> T, limit: Int = 10): Map<Int, T> = ids.associateWith(mapper)",
+            "load",
+            "override fun <T> load(vararg ids: Int, mapper: (Int) -> T, limit: Int = 10): Map<Int, T>",
+            Some("Map<Int, T>"),
+            &["ids: Int", "mapper: (Int) -> T", "limit: Int"],
+        );
+        check(
+            "swift",
+            "public func format(_ s: String, from base: Int = 10) throws -> [String: Int] {\n    [:]\n}",
+            "format",
+            "public func format(_ s: String, from base: Int = 10) throws -> [String: Int]",
+            Some("[String: Int]"),
+            &["s: String", "base: Int"],
+        );
+        check(
+            "scala",
+            "override def load[T](ids: Seq[Int], limit: Int = 10)(implicit ec: ExecutionContext): Future[Map[Int, T]] = Future(Map.empty)",
+            "load",
+            "override def load[T](ids: Seq[Int], limit: Int = 10)(implicit ec: ExecutionContext): Future[Map[Int, T]]",
+            Some("Future[Map[Int, T]]"),
+            &["ids: Seq[Int]", "limit: Int"],
+        );
+        check("lua", "function M.clamp(x, lo, hi)\n  return x\nend", "clamp", "function M.clamp(x, lo, hi)", None, &["x", "lo", "hi"]);
+        check(
+            "ruby",
+            "def build(items, limit = 10, *rest, key:, retries: 3, &block)\n  new(items)\nend",
+            "build",
+            "def build(items, limit = 10, *rest, key:, retries: 3, &block)",
+            None,
+            &["items", "limit", "rest", "key", "retries", "block"],
+        );
+        check(
+            "objc",
+            "- (NSString *)tableView:(UITableView *)view titleAt:(NSInteger)row {\n    return nil;\n}",
+            "tableView_titleAt",
+            "- (NSString *)tableView:(UITableView *)view titleAt:(NSInteger)row",
+            Some("NSString *"),
+            &["view: UITableView *", "row: NSInteger"],
+        );
+        check("objc", "+ (instancetype)sharedCar {\n    return nil;\n}", "sharedCar", "+ (instancetype)sharedCar", Some("instancetype"), &[]);
+        check("bash", "#!/bin/bash\necho deploying\n", "deploy.sh", "#!/bin/bash", None, &[]);
+        check("bash", "#!/usr/bin/env bash\nsquare() { echo $(( $1 * $1 )); }\n", "shapes.sh", "#!/usr/bin/env bash", None, &[]);
+        check("bash", "function distance {\n  local dx=$(square \"$1\")\n}", "distance", "function distance", None, &[]);
+        check("bash", "util::log() {\n  echo \"$@\"\n}", "util::log", "util::log()", None, &[]);
+    }
+}
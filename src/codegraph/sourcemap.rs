@@ -0,0 +1,257 @@
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+
+use serde::Deserialize;
+
+/// source map 还原出的原始位置（行号从1开始，列号从0开始）
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OriginalLocation {
+    pub source: PathBuf,
+    pub line: usize,
+    pub column: usize,
+    pub name: Option<String>,
+}
+
+/// 一个映射段：生成代码中的列 -> 原始源码位置
+#[derive(Debug, Clone, Copy)]
+struct Segment {
+    generated_column: usize,
+    source: usize,
+    original_line: usize,
+    original_column: usize,
+    name: Option<usize>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RawSourceMap {
+    version: u32,
+    #[serde(default)]
+    source_root: Option<String>,
+    sources: Vec<Option<String>>,
+    #[serde(default)]
+    names: Vec<String>,
+    mappings: String,
+}
+
+/// 解析后的 Source Map v3（不支持索引映射 `sections`）
+#[derive(Debug, Clone)]
+pub struct SourceMap {
+    sources: Vec<PathBuf>,
+    names: Vec<String>,
+    /// 按生成代码的行（从0开始）组织的映射段，每行内按列排序
+    lines: Vec<Vec<Segment>>,
+}
+
+impl SourceMap {
+    /// 解析 source map，相对的 `sources` 以 `map_dir` 为基准解析为路径
+    pub fn parse(content: &str, map_dir: &Path) -> Result<Self, String> {
+        let raw: RawSourceMap =
+            serde_json::from_str(content).map_err(|e| format!("Invalid source map: {}", e))?;
+        if raw.version != 3 {
+            return Err(format!("Unsupported source map version {}", raw.version));
+        }
+
+        let root = raw.source_root.as_deref().map(strip_scheme).unwrap_or_default();
+        let sources = raw
+            .sources
+            .iter()
+            .map(|source| {
+                let source = strip_scheme(source.as_deref().unwrap_or_default());
+                let path = Path::new(&root).join(source);
+                if path.is_absolute() {
+                    normalize(&path)
+                } else {
+                    normalize(&map_dir.join(path))
+                }
+            })
+            .collect();
+
+        Ok(Self {
+            sources,
+            names: raw.names,
+            lines: decode_mappings(&raw.mappings)?,
+        })
+    }
+
+    /// 查找生成文件对应的 source map：优先同目录的 `<file>.map`，其次文件末尾的 `sourceMappingURL` 注释
+    pub fn for_generated_file(path: &Path) -> Option<Self> {
+        let mut adjacent = path.as_os_str().to_owned();
+        adjacent.push(".map");
+        let map_path = PathBuf::from(adjacent);
+        let map_path = if map_path.is_file() {
+            map_path
+        } else {
+            let content = fs::read_to_string(path).ok()?;
+            let url = content
+                .lines()
+                .rev()
+                .take(5)
+                .find_map(|line| line.trim().strip_prefix("//# sourceMappingURL="))?
+                .trim();
+            // 内联的 data: URL 和远程地址不读取
+            if url.contains(':') {
+                return None;
+            }
+            path.parent()?.join(url)
+        };
+
+        let content = fs::read_to_string(&map_path).ok()?;
+        match Self::parse(&content, map_path.parent()?) {
+            Ok(map) => Some(map),
+            Err(e) => {
+                tracing::warn!("Ignoring source map {}: {}", map_path.display(), e);
+                None
+            }
+        }
+    }
+
+    /// 查找生成代码位置（行号从1开始，列号从0开始）对应的原始位置
+    pub fn lookup(&self, line: usize, column: usize) -> Option<OriginalLocation> {
+        let segments = self.lines.get(line.checked_sub(1)?)?;
+        // 取该行中列号不大于目标列的最后一个映射段；目标在首个映射段之前时使用首个映射段
+        let index = segments.partition_point(|s| s.generated_column <= column);
+        let segment = segments.get(index.saturating_sub(1))?;
+        Some(OriginalLocation {
+            source: self.sources.get(segment.source)?.clone(),
+            line: segment.original_line + 1,
+            column: segment.original_column,
+            name: segment.name.and_then(|i| self.names.get(i).cloned()),
+        })
+    }
+}
+
+/// source map 的列号以 UTF-16 码元计，tree-sitter 以字节计；`byte_offset` 为该位置在 `code` 中的字节偏移
+pub fn utf16_column(code: &str, byte_offset: usize, byte_column: usize) -> usize {
+    code.get(byte_offset.saturating_sub(byte_column)..byte_offset)
+        .map_or(byte_column, |prefix| prefix.encode_utf16().count())
+}
+
+/// 去掉打包工具添加的 `webpack://<namespace>/` 之类的前缀
+fn strip_scheme(source: &str) -> String {
+    match source.split_once("://") {
+        Some((_, rest)) if !source.starts_with("file://") => {
+            rest.split_once('/').map(|(_, path)| path).unwrap_or(rest).to_string()
+        }
+        Some((_, rest)) => rest.to_string(),
+        None => source.to_string(),
+    }
+}
+
+/// 词法上规范化路径（处理 `.` 和 `..`，不访问文件系统）
+fn normalize(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                if !normalized.pop() {
+                    normalized.push("..");
+                }
+            }
+            other => normalized.push(other.as_os_str()),
+        }
+    }
+    normalized
+}
+
+/// 解码 `mappings` 字段（逗号分隔段、分号分隔行，字段为相对前一段的 Base64 VLQ 增量）
+fn decode_mappings(mappings: &str) -> Result<Vec<Vec<Segment>>, String> {
+    let mut lines = Vec::new();
+    let (mut source, mut original_line, mut original_column, mut name) = (0i64, 0i64, 0i64, 0i64);
+
+    for line in mappings.split(';') {
+        let mut segments = Vec::new();
+        let mut generated_column = 0i64;
+        for segment in line.split(',').filter(|s| !s.is_empty()) {
+            let fields = decode_vlq(segment)?;
+            generated_column += fields[0];
+            // 只有生成列的段没有对应的原始位置
+            if fields.len() < 4 {
+                continue;
+            }
+            source += fields[1];
+            original_line += fields[2];
+            original_column += fields[3];
+            if let Some(delta) = fields.get(4) {
+                name += delta;
+            }
+            if generated_column < 0 || source < 0 || original_line < 0 || original_column < 0 {
+                return Err(format!("Negative position in mapping segment '{}'", segment));
+            }
+            segments.push(Segment {
+                generated_column: generated_column as usize,
+                source: source as usize,
+                original_line: original_line as usize,
+                original_column: original_column as usize,
+                name: (fields.len() >= 5 && name >= 0).then_some(name as usize),
+            });
+        }
+        segments.sort_by_key(|s| s.generated_column);
+        lines.push(segments);
+    }
+    Ok(lines)
+}
+
+fn decode_vlq(segment: &str) -> Result<Vec<i64>, String> {
+    let mut values = Vec::new();
+    let (mut value, mut shift) = (0i64, 0u32);
+    for ch in segment.bytes() {
+        let digit = match ch {
+            b'A'..=b'Z' => ch - b'A',
+            b'a'..=b'z' => ch - b'a' + 26,
+            b'0'..=b'9' => ch - b'0' + 52,
+            b'+' => 62,
+            b'/' => 63,
+            _ => return Err(format!("Invalid base64 character '{}' in mappings", ch as char)),
+        } as i64;
+        if shift > 60 {
+            return Err(format!("VLQ value too large in segment '{}'", segment));
+        }
+        value += (digit & 0x1f) << shift;
+        if digit & 0x20 != 0 {
+            shift += 5;
+            continue;
+        }
+        values.push(if value & 1 == 1 { -(value >> 1) } else { value >> 1 });
+        value = 0;
+        shift = 0;
+    }
+    if shift != 0 || values.is_empty() {
+        return Err(format!("Truncated VLQ segment '{}'", segment));
+    }
+    Ok(values)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lookup_minified_bundle() {
+        // 单行压缩代码：第0列 -> src/math.ts:1:0 (add)，第20列 -> src/app.ts:3:2 (main)
+        let map = r#"{
+            "version": 3,
+            "sources": ["webpack://demo/./src/math.ts", "../src/app.ts"],
+            "names": ["add", "main"],
+            "mappings": "AAAAA,oBCEEC"
+        }"#;
+        let map = SourceMap::parse(map, Path::new("/project/dist")).unwrap();
+
+        let add = map.lookup(1, 9).unwrap();
+        assert_eq!(add.source, PathBuf::from("/project/dist/src/math.ts"));
+        assert_eq!((add.line, add.column, add.name.as_deref()), (1, 0, Some("add")));
+
+        let main = map.lookup(1, 25).unwrap();
+        assert_eq!(main.source, PathBuf::from("/project/src/app.ts"));
+        assert_eq!((main.line, main.column, main.name.as_deref()), (3, 2, Some("main")));
+
+        assert!(map.lookup(2, 0).is_none());
+
+        // "é" 占2字节、1个UTF-16码元，"🎉" 占4字节、2个UTF-16码元
+        let code = "const s=\"é🎉\";function f(){}";
+        let offset = code.find("function").unwrap();
+        assert_eq!(utf16_column(code, offset, offset), 14);
+        assert_eq!(decode_vlq("2HQD").unwrap(), vec![123, 8, -1]);
+    }
+}
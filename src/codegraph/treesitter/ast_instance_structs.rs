@@ -154,7 +154,13 @@ pub struct SymbolInformation {
 
 impl SymbolInformation {
     pub fn get_content(&self, content: &String) -> io::Result<String> {
-        let lines: Vec<&str> = content.split("\n").collect();
+        // Rows count "\n" only, so CRLF files keep their row numbers; drop the "\r" and a leading BOM from the text
+        let lines: Vec<&str> = content
+            .strip_prefix('\u{feff}')
+            .unwrap_or(content)
+            .split('\n')
+            .map(|line| line.strip_suffix('\r').unwrap_or(line))
+            .collect();
         let mut end_row = self.full_range.end_point.row + 1;
         let raw_content = content.get(self.full_range.start_byte..self.full_range.end_byte);
         if raw_content.is_none() {
@@ -187,7 +193,7 @@ impl SymbolInformation {
         if content.is_none() {
             return Err(io::Error::other("Incorrect declaration range"));
         }
-        Ok(content.unwrap().to_string())
+        Ok(content.unwrap().trim_start_matches('\u{feff}').replace("\r\n", "\n"))
     }
 
     pub async fn get_declaration_content_from_file(&self) -> io::Result<String> {
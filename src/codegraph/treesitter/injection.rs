@@ -0,0 +1,541 @@
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
+
+use regex::Regex;
+use tree_sitter::{Node, Parser};
+use uuid::Uuid;
+
+use crate::codegraph::treesitter::ast_instance_structs::AstSymbolInstanceArc;
+use crate::codegraph::treesitter::language_id::LanguageId;
+use crate::codegraph::treesitter::parsers::sfc::{blank_outside, script_blocks};
+use crate::codegraph::treesitter::parsers::{get_ast_parser, get_tree_sitter_language};
+use crate::codegraph::treesitter::structs::SymbolType;
+
+/// Name given to the functions wrapped around embedded regions; one short
+/// enough to fit over the shortest header it replaces
+const WRAPPER: &str = "fn d(";
+
+/// How a region of another language is embedded in the host file
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InjectionKind {
+    /// JavaScript or TypeScript in the `<script>` blocks of an HTML page
+    ScriptBlock,
+    /// A SQL statement in a string literal: `cursor.execute("CALL refresh_totals()")`
+    SqlString,
+    /// The arguments of Rust macro invocations, parsed as expressions: `assert_eq!(total(), 3)`
+    MacroArguments,
+    /// A Rust code block in a `///` or `//!` doc comment
+    Doctest,
+}
+
+/// A function the injection wraps around embedded regions so that they parse
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Wrapper {
+    /// Start byte of the wrapper, also the start of the function it stands for
+    start: usize,
+    /// Name the wrapper is kept under; `None` drops it and moves its symbols to the host function
+    name: Option<String>,
+    /// 0-based row of the host function the kept wrapper belongs to
+    host_row: Option<usize>,
+}
+
+/// Code of another language embedded in a host file.
+///
+/// `code` is the host file with every byte outside the embedded regions
+/// replaced by a space (line breaks kept), so the symbols parsed from it carry
+/// the byte offsets, rows and columns of the host file unchanged. The few
+/// characters needed for the regions to parse on their own, such as a function
+/// wrapped around them or statement separators, are written over blanked bytes.
+#[derive(Debug, Clone)]
+pub struct Injection {
+    pub kind: InjectionKind,
+    pub language: LanguageId,
+    pub code: String,
+    wrappers: Vec<Wrapper>,
+}
+
+/// Finds the embedded regions of a host file
+pub fn detect(host: LanguageId, code: &str) -> Vec<Injection> {
+    let mut injections = Vec::new();
+    if host == LanguageId::Html {
+        injections.extend(html_scripts(code));
+    }
+    injections.extend(sql_strings(host, code));
+    if host == LanguageId::Rust {
+        // doctests come first so that the macro arguments in them find their function
+        let doctests = doctests(code);
+        let doctest_macros = doctests.as_ref().and_then(|doctests| macro_arguments(&doctests.code));
+        injections.extend(doctests);
+        injections.extend(macro_arguments(code));
+        injections.extend(doctest_macros);
+    }
+    injections
+}
+
+/// Parses the embedded regions of a host file with the analyzer of their language.
+///
+/// Top-level symbols of a region become children of the innermost host function
+/// around it, so a call in a SQL string or a macro argument is a call made by
+/// that function. A doctest becomes a function named `<item>::doctest`, nested in
+/// the function it documents.
+pub fn parse_injections(host: LanguageId, code: &str, path: &PathBuf, host_symbols: &[AstSymbolInstanceArc]) -> Vec<AstSymbolInstanceArc> {
+    let mut host_functions: Vec<(Uuid, usize, usize, usize)> = host_symbols
+        .iter()
+        .filter_map(|symbol| {
+            let symbol = symbol.read();
+            (symbol.symbol_type() == SymbolType::FunctionDeclaration).then(|| {
+                let range = symbol.full_range();
+                (*symbol.guid(), range.start_byte, range.end_byte, range.start_point.row)
+            })
+        })
+        .collect();
+
+    let mut symbols = Vec::new();
+    for injection in detect(host, code) {
+        let Ok(mut parser) = get_ast_parser(injection.language) else { continue };
+        let parsed = parser.parse(&injection.code, path);
+
+        // wrappers to drop, and kept wrappers with the host function they belong to
+        let mut dropped = HashSet::new();
+        let mut kept: HashMap<Uuid, Option<Uuid>> = HashMap::new();
+        for symbol in &parsed {
+            let mut symbol = symbol.write();
+            if symbol.symbol_type() != SymbolType::FunctionDeclaration {
+                continue;
+            }
+            let start = symbol.full_range().start_byte;
+            let Some(wrapper) = injection.wrappers.iter().find(|w| w.start == start) else { continue };
+            let guid = *symbol.guid();
+            match &wrapper.name {
+                None => {
+                    dropped.insert(guid);
+                }
+                Some(name) => {
+                    symbol.fields_mut().name = name.clone();
+                    let host = wrapper
+                        .host_row
+                        .and_then(|row| host_functions.iter().find(|(.., start_row)| *start_row == row))
+                        .map(|(guid, ..)| *guid);
+                    kept.insert(guid, host);
+                }
+            }
+        }
+        let guids: HashSet<Uuid> = parsed.iter().map(|s| *s.read().guid()).collect();
+
+        for symbol in parsed {
+            let guid = *symbol.read().guid();
+            if dropped.contains(&guid) {
+                continue;
+            }
+            {
+                let mut symbol = symbol.write();
+                let parent = symbol.parent_guid().filter(|p| guids.contains(p) && !dropped.contains(p));
+                let parent = match kept.get(&guid) {
+                    Some(host) => *host,
+                    None => parent.or_else(|| enclosing(&host_functions, symbol.full_range().start_byte)),
+                };
+                let fields = symbol.fields_mut();
+                fields.parent_guid = parent;
+                fields.childs_guid.retain(|child| !dropped.contains(child));
+                // the regions of later injections may lie in a doctest
+                if kept.contains_key(&guid) {
+                    let range = symbol.full_range();
+                    host_functions.push((guid, range.start_byte, range.end_byte, range.start_point.row));
+                }
+            }
+            symbols.push(symbol);
+        }
+    }
+
+    // children moved from dropped wrappers to host functions
+    let by_guid: HashMap<Uuid, &AstSymbolInstanceArc> = host_symbols.iter().chain(&symbols).map(|s| (*s.read().guid(), s)).collect();
+    for symbol in &symbols {
+        let (guid, parent) = {
+            let symbol = symbol.read();
+            (*symbol.guid(), *symbol.parent_guid())
+        };
+        if let Some(parent) = parent.and_then(|p| by_guid.get(&p)) {
+            let mut parent = parent.write();
+            if !parent.childs_guid().contains(&guid) {
+                parent.fields_mut().childs_guid.push(guid);
+            }
+        }
+    }
+    symbols
+}
+
+/// Innermost function whose byte range contains `byte`
+fn enclosing(functions: &[(Uuid, usize, usize, usize)], byte: usize) -> Option<Uuid> {
+    functions
+        .iter()
+        .filter(|(_, start, end, _)| *start <= byte && byte < *end)
+        .min_by_key(|(_, start, end, _)| end - start)
+        .map(|(guid, ..)| *guid)
+}
+
+/// The host file with every byte replaced by a space, line breaks excepted
+fn blank(code: &str) -> Vec<u8> {
+    code.bytes().map(|b| if b == b'\n' || b == b'\r' { b } else { b' ' }).collect()
+}
+
+fn keep(bytes: &mut [u8], code: &str, start: usize, end: usize) {
+    bytes[start..end].copy_from_slice(&code.as_bytes()[start..end]);
+}
+
+fn blank_range(bytes: &mut [u8], start: usize, end: usize) {
+    for byte in &mut bytes[start..end] {
+        if *byte != b'\n' && *byte != b'\r' {
+            *byte = b' ';
+        }
+    }
+}
+
+/// Writes `text` at `at` when it only covers blanked bytes on one line
+fn write_over(bytes: &mut [u8], at: usize, text: &str) -> bool {
+    let Some(target) = bytes.get_mut(at..at + text.len()) else { return false };
+    if target.iter().any(|b| *b != b' ') {
+        return false;
+    }
+    target.copy_from_slice(text.as_bytes());
+    true
+}
+
+fn parse_tree(language: LanguageId, code: &str) -> Option<tree_sitter::Tree> {
+    let mut parser = Parser::new();
+    parser.set_language(&get_tree_sitter_language(language)?).ok()?;
+    parser.parse(code, None)
+}
+
+/// The `<script>` blocks of an HTML page, parsed as TypeScript when any block
+/// is marked `lang="ts"` and as JavaScript otherwise
+fn html_scripts(code: &str) -> Option<Injection> {
+    let blocks = script_blocks(code);
+    if blocks.is_empty() {
+        return None;
+    }
+    let language = if blocks.iter().any(|block| block.typescript) { LanguageId::TypeScript } else { LanguageId::JavaScript };
+    Some(Injection { kind: InjectionKind::ScriptBlock, language, code: blank_outside(code, &blocks), wrappers: Vec::new() })
+}
+
+/// Nodes of the given kinds, not descending into them
+fn collect<'a>(root: Node<'a>, kinds: &[&str], found: &mut Vec<Node<'a>>) {
+    let mut stack = vec![root];
+    while let Some(node) = stack.pop() {
+        if kinds.contains(&node.kind()) {
+            found.push(node);
+            continue;
+        }
+        let mut cursor = node.walk();
+        stack.extend(node.children(&mut cursor));
+    }
+}
+
+/// Byte range of a string literal between its quotes; `None` for an empty string
+fn string_content(node: Node, code: &str) -> Option<(usize, usize)> {
+    let text = code.get(node.byte_range())?;
+    let open = text.find(['"', '\'', '`'])?;
+    let quote = text[open..].chars().next()?;
+    let run = text[open..].chars().take_while(|c| *c == quote).count().min(3);
+    let run = if run == 2 { 1 } else { run };
+    let close = text.rfind(quote)? + 1 - run;
+    let (start, end) = (node.start_byte() + open + run, node.start_byte() + close);
+    (start < end).then_some((start, end))
+}
+
+/// SQL statements embedded in the string literals of a Python, JavaScript,
+/// TypeScript, Java, Go or Rust file.
+///
+/// A string is SQL when it starts like a statement: `SELECT .. FROM`,
+/// `INSERT INTO`, `UPDATE .. SET`, `DELETE FROM`, `WITH .. AS (`, `CALL`, `EXEC`,
+/// `MERGE INTO` or `CREATE` of a routine, table or view. Interpolations
+/// (`{user_id}`, `${table}`) and escape sequences are blanked, and each string is
+/// a separate statement.
+fn sql_strings(host: LanguageId, code: &str) -> Option<Injection> {
+    let statement = Regex::new(
+        r"(?is)^\s*(select\s.*\sfrom\s|insert\s+into\s|update\s+\S+\s+set\s|delete\s+from\s|with\s+(recursive\s+)?\w+(\s*\([^)]*\))?\s+as\s*\(|call\s+[\w.]+\s*\(|exec(ute)?\s+[\w.]+|merge\s+into\s|create\s+(or\s+replace\s+)?(procedure|function|trigger|table|view|index)\s)",
+    )
+    .unwrap();
+    let kinds: &[&str] = match host {
+        LanguageId::Python => &["string"],
+        LanguageId::JavaScript | LanguageId::TypeScript | LanguageId::TypeScriptReact => &["string", "template_string"],
+        LanguageId::Java => &["string_literal", "text_block"],
+        LanguageId::Go => &["interpreted_string_literal", "raw_string_literal"],
+        LanguageId::Rust => &["string_literal", "raw_string_literal"],
+        _ => return None,
+    };
+    // cheap check before parsing the file a second time
+    let keywords = Regex::new(r"(?i)\b(select|insert|update|delete|with|call|exec|execute|merge|create)\s").unwrap();
+    if !keywords.is_match(code) {
+        return None;
+    }
+    let tree = parse_tree(host, code)?;
+    let mut strings = Vec::new();
+    collect(tree.root_node(), kinds, &mut strings);
+
+    let mut bytes = blank(code);
+    let mut found = false;
+    for string in strings {
+        let Some((start, end)) = string_content(string, code) else { continue };
+        if !statement.is_match(&code[start..end]) {
+            continue;
+        }
+        keep(&mut bytes, code, start, end);
+        // the opening quote ends the previous statement
+        bytes[start - 1] = b';';
+        let mut parts = Vec::new();
+        collect(string, &["interpolation", "template_substitution", "escape_sequence"], &mut parts);
+        for part in parts.iter().filter(|part| part.id() != string.id()) {
+            blank_range(&mut bytes, part.start_byte().max(start), part.end_byte().min(end));
+        }
+        found = true;
+    }
+    found.then(|| Injection {
+        kind: InjectionKind::SqlString,
+        language: LanguageId::Sql,
+        code: String::from_utf8(bytes).unwrap_or_default(),
+        wrappers: Vec::new(),
+    })
+}
+
+/// The arguments of the macro invocations in Rust function bodies.
+///
+/// tree-sitter keeps macro arguments as an opaque token tree, so calls such as
+/// `assert_eq!(total(), 3)` or `vec![build()]` are otherwise missed. Each token
+/// tree is kept as an expression statement (`(total(), 3);`, brackets replaced by
+/// parentheses) in a copy of the function whose header is replaced by a wrapper;
+/// names of macros invoked inside the arguments are blanked so that their
+/// arguments are expressions too.
+fn macro_arguments(code: &str) -> Option<Injection> {
+    if !code.contains('!') {
+        return None;
+    }
+    let nested_macro = Regex::new(r"(?:\w+::)*\w+\s*!").unwrap();
+    let tree = parse_tree(LanguageId::Rust, code)?;
+    let mut functions = Vec::new();
+    collect(tree.root_node(), &["function_item"], &mut functions);
+
+    let mut bytes = blank(code);
+    let mut wrappers = Vec::new();
+    let mut pending = functions;
+    while let Some(function) = pending.pop() {
+        let Some(body) = function.child_by_field_name("body") else { continue };
+        let mut invocations = Vec::new();
+        let mut stack = vec![body];
+        while let Some(node) = stack.pop() {
+            match node.kind() {
+                "macro_invocation" => invocations.push(node),
+                // nested functions get their own wrapper
+                "function_item" => pending.push(node),
+                _ => {
+                    let mut cursor = node.walk();
+                    stack.extend(node.children(&mut cursor));
+                }
+            }
+        }
+        let arguments: Vec<Node> = invocations
+            .iter()
+            .filter_map(|invocation| {
+                let mut cursor = invocation.walk();
+                let last = invocation.children(&mut cursor).last();
+                last.filter(|node| node.kind() == "token_tree")
+            })
+            .collect();
+        if arguments.is_empty() || !wrap(&mut bytes, function.start_byte(), body.start_byte()) {
+            continue;
+        }
+        wrappers.push(Wrapper { start: function.start_byte(), name: None, host_row: None });
+        bytes[body.start_byte()] = b'{';
+        bytes[body.end_byte() - 1] = b'}';
+        for tree in arguments {
+            let (start, end) = (tree.start_byte(), tree.end_byte());
+            keep(&mut bytes, code, start, end);
+            bytes[start - 1] = b';';
+            // `vec![a(), b()]` is kept as a tuple: calls in array expressions are not extracted
+            if bytes[start] == b'[' {
+                bytes[start] = b'(';
+                bytes[end - 1] = b')';
+            }
+            for nested in nested_macro.find_iter(&code[start..end]) {
+                blank_range(&mut bytes, start + nested.start(), start + nested.end());
+            }
+        }
+    }
+    (!wrappers.is_empty()).then(|| Injection {
+        kind: InjectionKind::MacroArguments,
+        language: LanguageId::Rust,
+        code: String::from_utf8(bytes).unwrap_or_default(),
+        wrappers,
+    })
+}
+
+/// Writes a wrapper function header `fn d()` over the blanked header that
+/// spans from `start` to the body at `body`; `false` when it does not fit
+fn wrap(bytes: &mut [u8], start: usize, body: usize) -> bool {
+    // `)` goes on the last blanked byte before the body that is not a line break
+    let Some(close) = (start + WRAPPER.len()..body).rev().find(|i| bytes[*i] == b' ') else { return false };
+    if !write_over(bytes, start, WRAPPER) {
+        return false;
+    }
+    bytes[close] = b')';
+    true
+}
+
+/// Rust code blocks in doc comments.
+///
+/// Each block is parsed as the body of a function, placed on its opening fence
+/// and named after the documented item (`add::doctest`); `//!` blocks are named
+/// after the module (`self::doctest`). `#` hides a line in the rendered docs but
+/// not from the compiler, so hidden lines are kept. Blocks tagged with another
+/// language (```` ```text ````, ```` ```sh ````) are skipped.
+fn doctests(code: &str) -> Option<Injection> {
+    if !code.contains("```") {
+        return None;
+    }
+    let item = Regex::new(
+        r#"^\s*(?:pub(?:\s*\([^)]*\))?\s+)?(?:(?:const|async|unsafe|default)\s+|extern\s+"[^"]*"\s+)*(?:fn|struct|enum|trait|mod|type|const|static|union|impl)\s+(\w+)"#,
+    )
+    .unwrap();
+    let rustdoc_attributes = ["rust", "ignore", "should_panic", "no_run", "compile_fail", "test_harness", "edition2015", "edition2018", "edition2021", "edition2024"];
+
+    let lines: Vec<(usize, &str)> = code
+        .split_inclusive('\n')
+        .scan(0, |offset, line| {
+            let start = *offset;
+            *offset += line.len();
+            Some((start, line))
+        })
+        .collect();
+    // `///` or `//!` marker: (offset of the text after it, inner doc)
+    let doc = |line: &str| -> Option<(usize, bool)> {
+        let indent = line.len() - line.trim_start().len();
+        let rest = &line[indent..];
+        if rest.starts_with("////") {
+            return None;
+        }
+        let inner = rest.starts_with("//!");
+        (rest.starts_with("///") || inner).then_some((indent + 3, inner))
+    };
+
+    let mut bytes = blank(code);
+    let mut wrappers = Vec::new();
+    let mut i = 0;
+    while i < lines.len() {
+        let (offset, line) = lines[i];
+        let Some((text_at, inner)) = doc(line) else {
+            i += 1;
+            continue;
+        };
+        let text = line[text_at..].trim();
+        let Some(info) = text.strip_prefix("```") else {
+            i += 1;
+            continue;
+        };
+        let is_rust = info.split([',', ' ']).filter(|a| !a.is_empty()).all(|a| rustdoc_attributes.contains(&a));
+        let Some(close) = (i + 1..lines.len()).find(|&j| doc(lines[j].1).is_none_or(|(at, _)| lines[j].1[at..].trim().starts_with("```"))) else {
+            break;
+        };
+        let closed = doc(lines[close].1).is_some();
+        if !is_rust || !closed {
+            i = close + 1;
+            continue;
+        }
+        // the documented item follows the doc comment, after any attributes
+        let host = if inner {
+            Some(("self".to_string(), None))
+        } else {
+            lines[close + 1..]
+                .iter()
+                .enumerate()
+                .find(|(_, (_, l))| doc(l).is_none() && !l.trim().is_empty() && !l.trim_start().starts_with("#["))
+                .and_then(|(k, (_, l))| item.captures(l).map(|c| (c[1].to_string(), Some(close + 1 + k))))
+        };
+        let fence_start = offset;
+        let closing = lines[close].0 + lines[close].1.trim_end().len() - 1;
+        if let Some((name, host_row)) = host.filter(|_| line.trim_end().len() > WRAPPER.len() && write_over(&mut bytes, fence_start, WRAPPER)) {
+            bytes[fence_start + WRAPPER.len()] = b')';
+            bytes[fence_start + WRAPPER.len() + 1] = b'{';
+            bytes[closing] = b'}';
+            for &(start, body) in &lines[i + 1..close] {
+                let Some((at, _)) = doc(body) else { continue };
+                let mut from = start + at;
+                // hidden line: `/// # let x = 1;`
+                let rest = &body[at..];
+                let trimmed = rest.trim_start();
+                if trimmed.starts_with("# ") || trimmed.trim_end() == "#" {
+                    from += rest.len() - trimmed.len() + 1;
+                }
+                keep(&mut bytes, code, from, start + body.trim_end_matches(['\n', '\r']).len());
+            }
+            wrappers.push(Wrapper { start: fence_start, name: Some(format!("{}::doctest", name)), host_row });
+        }
+        i = close + 1;
+    }
+    (!wrappers.is_empty()).then(|| Injection {
+        kind: InjectionKind::Doctest,
+        language: LanguageId::Rust,
+        code: String::from_utf8(bytes).unwrap_or_default(),
+        wrappers,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn calls(symbols: &[AstSymbolInstanceArc]) -> Vec<(String, usize)> {
+        let mut calls: Vec<(String, usize)> = symbols
+            .iter()
+            .map(|s| s.read())
+            .filter(|s| s.symbol_type() == SymbolType::FunctionCall)
+            .map(|s| (s.name().to_string(), s.full_range().start_point.row + 1))
+            .collect();
+        calls.sort();
+        calls
+    }
+
+    #[test]
+    fn test_injections_keep_host_offsets() {
+        let code = "def load(db, user_id):\n    db.execute(f\"SELECT order_total({user_id}) FROM orders\")\n    db.execute(\"CALL refresh_totals()\")\n    return \"select a file\"\n";
+        let injections = detect(LanguageId::Python, code);
+        assert_eq!(injections.len(), 1);
+        let sql = &injections[0];
+        assert_eq!((sql.kind, sql.language), (InjectionKind::SqlString, LanguageId::Sql));
+        assert_eq!(sql.code.len(), code.len());
+        assert_eq!(sql.code.lines().nth(1).unwrap(), "                ;SELECT order_total(         ) FROM orders  ");
+        assert!(sql.code.lines().nth(3).unwrap().trim().is_empty());
+
+        let rust = "/// Adds two numbers.\n///\n/// ```\n/// # use demo::add;\n/// assert_eq!(add(1, 2), 3);\n/// ```\n#[inline]\npub fn add(a: i32, b: i32) -> i32 {\n    debug_assert!(check(a));\n    a + b\n}\n";
+        let injections = detect(LanguageId::Rust, rust);
+        let kinds: Vec<InjectionKind> = injections.iter().map(|i| i.kind).collect();
+        assert_eq!(kinds, vec![InjectionKind::Doctest, InjectionKind::MacroArguments, InjectionKind::MacroArguments]);
+        let doctest = &injections[0];
+        assert_eq!(doctest.code.len(), rust.len());
+        assert_eq!(doctest.code.lines().nth(2).unwrap(), "fn d(){");
+        assert_eq!(doctest.code.lines().nth(3).unwrap(), "      use demo::add;");
+        assert_eq!(doctest.code.lines().nth(4).unwrap(), "    assert_eq!(add(1, 2), 3);");
+        assert_eq!(doctest.code.lines().nth(5).unwrap().trim(), "}");
+    }
+
+    #[test]
+    fn test_parse_injections_attach_to_host_function() {
+        let path = PathBuf::from("lib.rs");
+        let code = "/// ```\n/// let total = add(1, 2);\n/// ```\npub fn add(a: i32, b: i32) -> i32 {\n    assert!(valid(a), \"{}\", describe(b));\n    let v = vec![square(a), format!(\"{}\", label(b))];\n    a + b\n}\n";
+        let mut parser = get_ast_parser(LanguageId::Rust).unwrap();
+        let host = parser.parse(code, &path);
+        let add = host.iter().find(|s| s.read().name() == "add").map(|s| *s.read().guid()).unwrap();
+
+        let injected = parse_injections(LanguageId::Rust, code, &path, &host);
+        assert_eq!(
+            calls(&injected),
+            vec![("add".to_string(), 2), ("describe".to_string(), 5), ("label".to_string(), 6), ("square".to_string(), 6), ("valid".to_string(), 5)]
+        );
+        let doctest = injected.iter().map(|s| s.read()).find(|s| s.symbol_type() == SymbolType::FunctionDeclaration).unwrap();
+        assert_eq!(doctest.name(), "add::doctest");
+        assert_eq!(*doctest.parent_guid(), Some(add));
+        assert_eq!((doctest.full_range().start_point.row, doctest.full_range().end_point.row), (0, 2));
+        // calls in macro arguments belong to the host function
+        let valid = injected.iter().map(|s| s.read()).find(|s| s.name() == "valid").unwrap();
+        assert_eq!(*valid.parent_guid(), Some(add));
+        assert!(!injected.iter().any(|s| s.read().name() == "d"));
+    }
+}
@@ -24,8 +24,9 @@ pub enum LanguageId {
     Lua,
     Ocaml,
     Php,
+    Proto,
     // Markdown,
-    // ObjectiveC,
+    ObjectiveC,
     Python,
     R,
     Ruby,
@@ -33,11 +34,12 @@ pub enum LanguageId {
     Scala,
     // Solidity,
     Sql,
+    Svelte,
     Swift,
     // Toml,
     TypeScript,
     TypeScriptReact,
-    // Vue,
+    Vue,
     Unknown,
 }
 
@@ -69,8 +71,9 @@ impl fmt::Display for LanguageId {
             Self::Lua => write!(f, "lua"),
             Self::Ocaml => write!(f, "ocaml"),
             Self::Php => write!(f, "php"),
+            Self::Proto => write!(f, "proto"),
             // Self::Markdown => write!(f, "markdown"),
-            // Self::ObjectiveC => write!(f, "objective-c"),
+            Self::ObjectiveC => write!(f, "objective-c"),
             Self::Python => write!(f, "python"),
             Self::R => write!(f, "r"),
             Self::Ruby => write!(f, "ruby"),
@@ -78,11 +81,12 @@ impl fmt::Display for LanguageId {
             Self::Scala => write!(f, "scala"),
             // Self::Solidity => write!(f, "solidity"),
             Self::Sql => write!(f, "sql"),
+            Self::Svelte => write!(f, "svelte"),
             Self::Swift => write!(f, "swift"),
             // Self::Toml => write!(f, "toml"),
             Self::TypeScript => write!(f, "typescript"),
             Self::TypeScriptReact => write!(f, "typescriptreact"),
-            // Self::Vue => write!(f, "vue"),
+            Self::Vue => write!(f, "vue"),
             Self::Unknown => write!(f, "unknown"),
         }
     }
@@ -101,23 +105,27 @@ impl From<&str> for LanguageId {
             // "erlang" => Self::Erlang,
             "go" => Self::Go,
             "html" => Self::Html,
+            "kotlin" => Self::Kotlin,
             "java" => Self::Java,
             "javascript" => Self::JavaScript,
             // "json" => Self::Json,
             "lua" => Self::Lua,
             // "markdown" => Self::Markdown,
-            // "objective-c" => Self::ObjectiveC,
+            "objective-c" => Self::ObjectiveC,
             "python" => Self::Python,
             "r" => Self::R,
+            "proto" => Self::Proto,
             "ruby" => Self::Ruby,
             "rust" => Self::Rust,
             "scala" => Self::Scala,
             "shellscript" => Self::Bash,
+            "sql" => Self::Sql,
+            "svelte" => Self::Svelte,
             "swift" => Self::Swift,
             // "toml" => Self::Toml,
             "typescript" => Self::TypeScript,
             "typescriptreact" => Self::TypeScriptReact,
-            // "vue" => Self::Vue,
+            "vue" => Self::Vue,
             _ => Self::Unknown,
         }
     }
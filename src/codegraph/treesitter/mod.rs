@@ -4,36 +4,80 @@ pub mod structs;
 pub mod ast_instance_structs;
 pub mod skeletonizer;
 pub mod file_ast_markup;
+pub mod injection;
 
 use std::path::PathBuf;
-use crate::codegraph::treesitter::parsers::{get_ast_parser_by_filename, ParserError};
+use crate::codegraph::capabilities::language_for_path;
+use crate::codegraph::treesitter::parsers::cpp::CppParser;
+use crate::codegraph::treesitter::parsers::notebook::NotebookParser;
+use crate::codegraph::treesitter::parsers::{get_ast_parser_by_filename, get_language_id_by_filename, AstLanguageParser, ParserError};
+pub use crate::codegraph::treesitter::parsers::TreeStats;
 
 pub use language_id::LanguageId;
+pub use parsers::external::{AnalyzerManifest, AnalyzerRegistry, ExternalSymbol, ExternalSymbolKind, LanguageAnalyzer};
+pub use parsers::notebook::NotebookCells;
 pub use structs::*;
 pub use ast_instance_structs::*;
 pub use skeletonizer::*;
 pub use file_ast_markup::*;
 
 /// TreeSitter解析器的主要接口
-pub struct TreeSitterParser;
+#[derive(Debug, Clone, Default)]
+pub struct TreeSitterParser {
+    /// C/C++ 文件中的宏定义作为函数分析
+    expand_macros: bool,
+}
 
 impl TreeSitterParser {
     /// 创建新的TreeSitter解析器实例
     pub fn new() -> Self {
-        TreeSitterParser
+        Self::default()
+    }
+
+    /// 设置是否把 C/C++ 宏定义作为函数分析（宏调用可解析到宏，调用链穿过宏体）
+    pub fn with_macro_expansion(mut self, expand_macros: bool) -> Self {
+        self.expand_macros = expand_macros;
+        self
     }
 
     /// 解析文件并返回AST符号实例
     pub fn parse_file(&self, file_path: &PathBuf) -> Result<Vec<AstSymbolInstanceArc>, ParserError> {
-        let (mut parser, _language_id) = get_ast_parser_by_filename(file_path)?;
+        self.parse_file_with_stats(file_path).map(|(symbols, _)| symbols)
+    }
+
+    /// 解析文件，同时返回语法树的节点数和 ERROR 节点数（不使用 tree-sitter 语法的解析器为 `None`）
+    pub fn parse_file_with_stats(&self, file_path: &PathBuf) -> Result<(Vec<AstSymbolInstanceArc>, Option<TreeStats>), ParserError> {
+        // notebook 的符号范围指向拼接后的脚本而非 JSON 文件本身，
+        // 只在构建图时解析，不提供给按字节范围改写源码的服务（重命名、lint 等）
+        let is_notebook = file_path.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("ipynb"));
+        // 管理员暂停了该语言的分析（例如其语法在某些文件上崩溃）
+        if let Some(language) = language_for_path(file_path).filter(|language| !AnalyzerRegistry::is_enabled(language)) {
+            return Err(ParserError { message: format!("The {} analyzer is disabled", language) });
+        }
+        let language = get_language_id_by_filename(file_path).filter(|_| AnalyzerRegistry::for_path(file_path).is_none());
         // 读取文件内容
         let code = std::fs::read_to_string(file_path)
             .map_err(|e| ParserError {
                 message: format!("Failed to read file {}: {}", file_path.display(), e)
             })?;
-        
-        // 解析文件内容
-        let symbols = parser.parse(&code, file_path);
-        Ok(symbols)
+        // HTML 页面本身不产生符号，只分析其中的 `<script>` 块
+        if language == Some(LanguageId::Html) {
+            return Ok((injection::parse_injections(LanguageId::Html, &code, file_path, &[]), None));
+        }
+        let mut parser: Box<dyn AstLanguageParser> = if is_notebook {
+            Box::new(NotebookParser::new()?)
+        } else if self.expand_macros && language == Some(LanguageId::Cpp) {
+            Box::new(CppParser::with_macro_expansion()?)
+        } else {
+            get_ast_parser_by_filename(file_path)?.0
+        };
+
+        // 解析文件内容，嵌入的其他语言代码（SQL 字符串、宏参数、文档测试）的符号挂在所在的函数下
+        let mut symbols = parser.parse(&code, file_path);
+        if let Some(language) = language.filter(|_| !is_notebook) {
+            let injected = injection::parse_injections(language, &code, file_path, &symbols);
+            symbols.extend(injected);
+        }
+        Ok((symbols, parser.tree_stats()))
     }
 } 
\ No newline at end of file
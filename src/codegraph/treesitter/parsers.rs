@@ -6,6 +6,7 @@ use tracing::error;
 
 use crate::codegraph::treesitter::ast_instance_structs::AstSymbolInstanceArc;
 use crate::codegraph::treesitter::language_id::LanguageId;
+use crate::codegraph::treesitter::parsers::external::{AnalyzerRegistry, ExternalParser};
 
 
 pub(crate) mod python;
@@ -18,6 +19,18 @@ pub(crate) mod cpp;
 pub(crate) mod ts;
 mod js;
 pub(crate) mod go;
+pub(crate) mod objc;
+pub(crate) mod lua;
+pub(crate) mod bash;
+pub(crate) mod sql;
+pub(crate) mod proto;
+pub(crate) mod notebook;
+pub(crate) mod sfc;
+mod kotlin;
+mod ruby;
+mod scala;
+mod swift;
+pub mod external;
 
 
 #[derive(Debug, PartialEq, Eq)]
@@ -33,8 +46,52 @@ impl Display for ParserError {
 
 impl Error for ParserError {}
 
+/// Size of a tree-sitter tree and how much of it failed to parse
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TreeStats {
+    pub node_count: usize,
+    /// `ERROR` nodes inserted where the grammar could not match the source
+    pub error_nodes: usize,
+    /// 1-based line ranges of `ERROR` and `MISSING` nodes
+    pub error_lines: Vec<(usize, usize)>,
+}
+
+impl TreeStats {
+    pub fn of(tree: &tree_sitter::Tree) -> Self {
+        let mut stats = TreeStats::default();
+        let mut cursor = tree.walk();
+        loop {
+            let node = cursor.node();
+            stats.node_count += 1;
+            if node.is_error() {
+                stats.error_nodes += 1;
+            }
+            if node.is_error() || node.is_missing() {
+                stats.error_lines.push((node.start_position().row + 1, node.end_position().row + 1));
+            }
+            if cursor.goto_first_child() || cursor.goto_next_sibling() {
+                continue;
+            }
+            loop {
+                if !cursor.goto_parent() {
+                    return stats;
+                }
+                if cursor.goto_next_sibling() {
+                    break;
+                }
+            }
+        }
+    }
+}
+
 pub trait AstLanguageParser: Send {
     fn parse(&mut self, code: &str, path: &PathBuf) -> Vec<AstSymbolInstanceArc>;
+
+    /// Statistics of the tree built by the last `parse`; `None` for parsers
+    /// that do not use a tree-sitter grammar
+    fn tree_stats(&self) -> Option<TreeStats> {
+        None
+    }
 }
 
 fn internal_error<E: Display>(err: E) -> ParserError {
@@ -79,6 +136,46 @@ pub(crate) fn get_ast_parser(language_id: LanguageId) -> Result<Box<dyn AstLangu
             let parser = go::GoParser::new()?;
             Ok(Box::new(parser))
         }
+        LanguageId::ObjectiveC => {
+            let parser = objc::ObjCParser::new()?;
+            Ok(Box::new(parser))
+        }
+        LanguageId::Lua => {
+            let parser = lua::LuaParser::new()?;
+            Ok(Box::new(parser))
+        }
+        LanguageId::Bash => {
+            let parser = bash::BashAnalyzer::new()?;
+            Ok(Box::new(parser))
+        }
+        LanguageId::Sql => {
+            let parser = sql::SqlAnalyzer::new()?;
+            Ok(Box::new(parser))
+        }
+        LanguageId::Proto => {
+            let parser = proto::ProtoAnalyzer::new()?;
+            Ok(Box::new(parser))
+        }
+        LanguageId::Vue | LanguageId::Svelte => {
+            let parser = sfc::SfcParser::new()?;
+            Ok(Box::new(parser))
+        }
+        LanguageId::Kotlin => {
+            let parser = kotlin::KotlinAnalyzer::new()?;
+            Ok(Box::new(parser))
+        }
+        LanguageId::Ruby => {
+            let parser = ruby::RubyAnalyzer::new()?;
+            Ok(Box::new(parser))
+        }
+        LanguageId::Swift => {
+            let parser = swift::SwiftAnalyzer::new()?;
+            Ok(Box::new(parser))
+        }
+        LanguageId::Scala => {
+            let parser = scala::ScalaAnalyzer::new()?;
+            Ok(Box::new(parser))
+        }
         other => Err(ParserError {
             message: "Unsupported language id: ".to_string() + &other.to_string()
         }),
@@ -86,7 +183,31 @@ pub(crate) fn get_ast_parser(language_id: LanguageId) -> Result<Box<dyn AstLangu
 }
 
 
+/// 返回语言对应的tree-sitter语法，用于需要直接遍历语法树的分析
+pub(crate) fn get_tree_sitter_language(language_id: LanguageId) -> Option<tree_sitter::Language> {
+    match language_id {
+        LanguageId::Rust => Some(tree_sitter_rust::LANGUAGE.into()),
+        LanguageId::Python => Some(tree_sitter_python::LANGUAGE.into()),
+        LanguageId::Java => Some(tree_sitter_java::LANGUAGE.into()),
+        LanguageId::Cpp => Some(tree_sitter_cpp::LANGUAGE.into()),
+        LanguageId::TypeScript => Some(tree_sitter_typescript::LANGUAGE_TYPESCRIPT.into()),
+        LanguageId::TypeScriptReact => Some(tree_sitter_typescript::LANGUAGE_TSX.into()),
+        LanguageId::JavaScript => Some(tree_sitter_javascript::LANGUAGE.into()),
+        LanguageId::Go => Some(tree_sitter_go::LANGUAGE.into()),
+        LanguageId::Kotlin => Some(tree_sitter_kotlin_ng::LANGUAGE.into()),
+        LanguageId::Ruby => Some(tree_sitter_ruby::LANGUAGE.into()),
+        LanguageId::Swift => Some(tree_sitter_swift::LANGUAGE.into()),
+        LanguageId::Scala => Some(tree_sitter_scala::LANGUAGE.into()),
+        _ => None,
+    }
+}
+
+
 pub fn get_ast_parser_by_filename(filename: &PathBuf) -> Result<(Box<dyn AstLanguageParser + 'static>, LanguageId), ParserError> {
+    // 外部注册的分析器优先于内置解析器
+    if let Some(analyzer) = AnalyzerRegistry::for_path(filename) {
+        return Ok((Box::new(ExternalParser::new(analyzer)), LanguageId::Unknown));
+    }
     let suffix = filename.extension().and_then(|e| e.to_str()).unwrap_or("").to_lowercase();
     let maybe_language_id = get_language_id_by_filename(filename);
     match maybe_language_id {
@@ -99,6 +220,11 @@ pub fn get_ast_parser_by_filename(filename: &PathBuf) -> Result<(Box<dyn AstLang
 }
 
 pub fn get_language_id_by_filename(filename: &PathBuf) -> Option<LanguageId> {
+    // 没有扩展名的Ruby构建文件
+    let file_name = filename.file_name().and_then(|n| n.to_str()).unwrap_or("");
+    if matches!(file_name, "Gemfile" | "Rakefile") {
+        return Some(LanguageId::Ruby);
+    }
     let suffix = filename.extension().and_then(|e| e.to_str()).unwrap_or("").to_lowercase();
     match suffix.as_str() {
         "cpp" | "cc" | "cxx" | "c++" | "c" | "h" | "hpp" | "hxx" | "hh" => Some(LanguageId::Cpp),
@@ -110,6 +236,18 @@ pub fn get_language_id_by_filename(filename: &PathBuf) -> Option<LanguageId> {
         "ts" => Some(LanguageId::TypeScript),
         "tsx" => Some(LanguageId::TypeScriptReact),
         "go" => Some(LanguageId::Go),
+        "m" | "mm" => Some(LanguageId::ObjectiveC),
+        "lua" => Some(LanguageId::Lua),
+        "sh" | "bash" => Some(LanguageId::Bash),
+        "sql" => Some(LanguageId::Sql),
+        "proto" => Some(LanguageId::Proto),
+        "vue" => Some(LanguageId::Vue),
+        "svelte" => Some(LanguageId::Svelte),
+        "html" | "htm" => Some(LanguageId::Html),
+        "kt" | "kts" => Some(LanguageId::Kotlin),
+        "rb" | "rake" => Some(LanguageId::Ruby),
+        "swift" => Some(LanguageId::Swift),
+        "scala" | "sc" => Some(LanguageId::Scala),
         _ => None
     }
 }
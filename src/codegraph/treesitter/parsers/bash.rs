@@ -0,0 +1,645 @@
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use parking_lot::RwLock;
+use tree_sitter::{Point, Range};
+use uuid::Uuid;
+
+use crate::codegraph::treesitter::ast_instance_structs::{AstSymbolFields, AstSymbolInstanceArc, CommentDefinition, FunctionCall, FunctionDeclaration, ImportDeclaration, ImportType};
+use crate::codegraph::treesitter::language_id::LanguageId;
+use crate::codegraph::treesitter::parsers::{AstLanguageParser, ParserError};
+use crate::codegraph::treesitter::parsers::utils::get_guid;
+
+/// Shell script analyzer.
+///
+/// No shell grammar is bundled, so scripts are scanned directly: quoting, command
+/// substitutions, heredocs and comments are skipped or recursed into, and the
+/// first word of every simple command is a call. The script itself is a function
+/// named after its file (`deploy.sh`) that owns the top-level commands, so running
+/// another script (`./build.sh`, `bash scripts/test.sh`) or sourcing it
+/// (`source lib.sh`, `. lib.sh`) is a call to that script's node. Shell builtins and
+/// common text utilities are not recorded.
+pub(crate) struct BashAnalyzer;
+
+impl BashAnalyzer {
+    pub fn new() -> Result<Self, ParserError> {
+        Ok(Self)
+    }
+}
+
+impl AstLanguageParser for BashAnalyzer {
+    fn parse(&mut self, code: &str, path: &PathBuf) -> Vec<AstSymbolInstanceArc> {
+        Scanner::new(code, path).scan()
+    }
+}
+
+/// Words that start a compound command and keep the parser at command position
+static OPENING_KEYWORDS: [&str; 11] = ["if", "then", "else", "elif", "do", "while", "until", "!", "time", "fi", "done"];
+
+/// Commands whose remaining words are not commands themselves
+static IGNORED_COMMANDS: [&str; 5] = ["for", "select", "case", "[[", "coproc"];
+
+/// Prefixes that run the next word as the command
+static COMMAND_PREFIXES: [&str; 7] = ["sudo", "exec", "command", "builtin", "nohup", "env", "xargs"];
+
+/// Interpreters whose first non-option argument is a script
+static SHELLS: [&str; 5] = ["bash", "sh", "zsh", "ksh", "dash"];
+
+/// Builtins and ubiquitous utilities, left out so the graph shows project functions and tools
+static SHELL_BUILTINS: [&str; 73] = [
+    ":", "[", "]]", "alias", "awk", "basename", "break", "cat", "cd", "chmod", "chown", "continue", "cp", "cut",
+    "date", "declare", "dirname", "dirs", "echo", "eval", "exit", "export", "false", "getopts", "grep", "hash",
+    "head", "jobs", "kill", "let", "ln", "local", "ls", "mapfile", "mkdir", "mktemp", "mv", "popd", "printf",
+    "pushd", "pwd", "read", "readarray", "readlink", "readonly", "realpath", "return", "rm", "rmdir", "sed",
+    "set", "shift", "shopt", "sleep", "sort", "source", "tail", "tee", "test", "touch", "tr", "trap", "true",
+    "type", "typeset", "ulimit", "umask", "unalias", "uniq", "unset", "wait", "wc", "which",
+];
+
+/// A word of a simple command with quotes removed
+struct Word {
+    text: String,
+    start: usize,
+    end: usize,
+    /// The word contained quotes, escapes or expansions and cannot be a keyword
+    quoted: bool,
+}
+
+enum Block {
+    /// Index into `Scanner::functions`
+    Function(usize),
+    Other,
+}
+
+struct Scanner<'a> {
+    code: &'a str,
+    b: &'a [u8],
+    i: usize,
+    path: &'a PathBuf,
+    line_starts: Vec<usize>,
+    root_guid: Uuid,
+    /// The first entry is the script itself
+    functions: Vec<FunctionDeclaration>,
+    calls: Vec<FunctionCall>,
+    imports: Vec<ImportDeclaration>,
+    comments: Vec<(usize, usize)>,
+    blocks: Vec<Block>,
+    /// A function header whose body has not started yet
+    pending_function: Option<usize>,
+    /// Heredoc delimiters whose bodies start at the next newline, with the `<<-` flag
+    heredocs: Vec<(String, bool)>,
+    /// One entry per open `case`; true while a pattern is expected
+    cases: Vec<bool>,
+}
+
+/// Last path segment of a script reference: `"$DIR"/lib/util.sh` -> `util.sh`
+fn script_name(word: &str) -> Option<&str> {
+    let name = word.rsplit('/').next()?;
+    (!name.is_empty() && !name.contains(['$', '*', '?'])).then_some(name)
+}
+
+fn is_script_path(word: &str) -> bool {
+    word.contains('/') || word.ends_with(".sh") || word.ends_with(".bash")
+}
+
+fn is_assignment(word: &Word) -> bool {
+    let Some(eq) = word.text.find('=') else {
+        return false;
+    };
+    let name = word.text[..eq].trim_end_matches('+');
+    let name = name.split_once('[').map_or(name, |(head, _)| head);
+    !name.is_empty()
+        && !name.starts_with(|c: char| c.is_ascii_digit())
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+fn is_command_name(word: &str) -> bool {
+    word.starts_with(|c: char| c.is_alphabetic() || c == '_')
+        && word.chars().all(|c| c.is_alphanumeric() || matches!(c, '_' | '-' | '.' | ':'))
+}
+
+impl<'a> Scanner<'a> {
+    fn new(code: &'a str, path: &'a PathBuf) -> Self {
+        let line_starts = std::iter::once(0)
+            .chain(code.bytes().enumerate().filter(|(_, c)| *c == b'\n').map(|(i, _)| i + 1))
+            .collect();
+        Self {
+            code,
+            b: code.as_bytes(),
+            i: 0,
+            path,
+            line_starts,
+            root_guid: get_guid(),
+            functions: vec![],
+            calls: vec![],
+            imports: vec![],
+            comments: vec![],
+            blocks: vec![],
+            pending_function: None,
+            heredocs: vec![],
+            cases: vec![],
+        }
+    }
+
+    fn point(&self, byte: usize) -> Point {
+        let row = self.line_starts.partition_point(|&start| start <= byte) - 1;
+        Point { row, column: byte - self.line_starts[row] }
+    }
+
+    fn range(&self, start: usize, end: usize) -> Range {
+        Range { start_byte: start, end_byte: end, start_point: self.point(start), end_point: self.point(end) }
+    }
+
+    fn fields(&self, name: &str, start: usize, end: usize, parent: Option<Uuid>) -> AstSymbolFields {
+        let mut fields = AstSymbolFields::from_data(LanguageId::Bash, self.path.clone(), false);
+        fields.guid = get_guid();
+        fields.name = name.to_string();
+        fields.parent_guid = Some(parent.unwrap_or(self.root_guid));
+        fields.full_range = self.range(start, end);
+        fields.declaration_range = fields.full_range;
+        fields.definition_range = fields.full_range;
+        fields
+    }
+
+    fn peek(&self, offset: usize) -> Option<u8> {
+        self.b.get(self.i + offset).copied()
+    }
+
+    /// Innermost function, the script itself at top level
+    fn enclosing(&self) -> Uuid {
+        let index = self.blocks.iter().rev().find_map(|b| match b {
+            Block::Function(index) => Some(*index),
+            Block::Other => None,
+        });
+        self.functions[index.unwrap_or(0)].ast_fields.guid
+    }
+
+    fn scan(mut self) -> Vec<AstSymbolInstanceArc> {
+        let script = self.path.file_name().and_then(|n| n.to_str()).unwrap_or("script").to_string();
+        let mut declaration = FunctionDeclaration {
+            ast_fields: self.fields(&script, 0, self.code.trim_end().len(), None),
+            ..Default::default()
+        };
+        // Only the first line is the "signature" of a script
+        let first_line_end = self.code.find('\n').unwrap_or(self.code.len());
+        declaration.ast_fields.declaration_range = self.range(0, first_line_end);
+        self.functions.push(declaration);
+
+        self.list(None);
+
+        let comment_ranges: Vec<Range> = self.comments.iter().map(|&(start, end)| self.range(start, end)).collect();
+        let mut symbols: Vec<AstSymbolInstanceArc> = vec![];
+        let mut children: HashMap<Uuid, Vec<Uuid>> = HashMap::new();
+        for fields in self.calls.iter().map(|c| &c.ast_fields).chain(self.functions.iter().skip(1).map(|f| &f.ast_fields)) {
+            if let Some(parent) = fields.parent_guid {
+                children.entry(parent).or_default().push(fields.guid);
+            }
+        }
+        for mut function in self.functions {
+            function.ast_fields.childs_guid = children.remove(&function.ast_fields.guid).unwrap_or_default();
+            symbols.push(Arc::new(RwLock::new(Box::new(function))));
+        }
+        for call in self.calls {
+            symbols.push(Arc::new(RwLock::new(Box::new(call))));
+        }
+        for import in self.imports {
+            symbols.push(Arc::new(RwLock::new(Box::new(import))));
+        }
+        for range in comment_ranges {
+            let mut ast_fields = AstSymbolFields::from_data(LanguageId::Bash, self.path.clone(), false);
+            ast_fields.guid = get_guid();
+            ast_fields.parent_guid = Some(self.root_guid);
+            ast_fields.full_range = range;
+            let comment = CommentDefinition { ast_fields };
+            symbols.push(Arc::new(RwLock::new(Box::new(comment))));
+        }
+        symbols
+    }
+
+    /// Scans commands until the `close` byte (`)` of `$(...)`/subshells, a backquote)
+    /// or the end of the input
+    fn list(&mut self, close: Option<u8>) {
+        let mut words: Vec<Word> = vec![];
+        loop {
+            if words.is_empty() && self.cases.last() == Some(&true) && self.case_pattern() {
+                continue;
+            }
+            let Some(c) = self.peek(0) else {
+                break;
+            };
+            match c {
+                b'\n' => {
+                    self.finish(&mut words);
+                    self.i += 1;
+                    self.skip_heredocs();
+                }
+                b' ' | b'\t' | b'\r' => self.i += 1,
+                b'\\' if self.peek(1) == Some(b'\n') => self.i += 2,
+                b'#' => {
+                    let start = self.i;
+                    self.i = self.b[start..].iter().position(|&c| c == b'\n').map_or(self.b.len(), |p| start + p);
+                    self.comments.push((start, self.i));
+                }
+                b';' | b'&' | b'|' => {
+                    self.finish(&mut words);
+                    let case_end = c == b';' && matches!(self.peek(1), Some(b';' | b'&'));
+                    self.i += 1;
+                    while matches!(self.peek(0), Some(b';' | b'&' | b'|')) {
+                        self.i += 1;
+                    }
+                    if case_end {
+                        if let Some(expect_pattern) = self.cases.last_mut() {
+                            *expect_pattern = true;
+                        }
+                    }
+                }
+                b'(' => self.open_paren(&mut words),
+                b')' => {
+                    self.i += 1;
+                    if close == Some(b')') {
+                        self.finish(&mut words);
+                        return;
+                    }
+                }
+                b'`' if close == Some(b'`') => {
+                    self.i += 1;
+                    self.finish(&mut words);
+                    return;
+                }
+                b'<' | b'>' => self.redirection(),
+                _ => {
+                    let word = self.word(close);
+                    if words.is_empty() && !word.quoted && self.keyword(&word) {
+                        continue;
+                    }
+                    words.push(word);
+                }
+            }
+        }
+        self.finish(&mut words);
+    }
+
+    /// Handles reserved words at command position; returns false for ordinary words
+    fn keyword(&mut self, word: &Word) -> bool {
+        match word.text.as_str() {
+            text if OPENING_KEYWORDS.contains(&text) => true,
+            "esac" => {
+                self.cases.pop();
+                true
+            }
+            "{" => {
+                let block = match self.pending_function.take() {
+                    Some(index) => Block::Function(index),
+                    None => Block::Other,
+                };
+                self.blocks.push(block);
+                true
+            }
+            "}" => {
+                if let Some(Block::Function(index)) = self.blocks.pop() {
+                    self.close_function(index, word.end);
+                }
+                true
+            }
+            "function" => {
+                self.skip_blanks();
+                let name = self.word(None);
+                self.skip_blanks();
+                if self.peek(0) == Some(b'(') {
+                    self.skip_balanced(b'(', b')');
+                }
+                if !name.text.is_empty() {
+                    self.declare_function(&name.text, word.start);
+                }
+                true
+            }
+            _ => false,
+        }
+    }
+
+    fn declare_function(&mut self, name: &str, start: usize) {
+        let parent = self.enclosing();
+        let declaration = FunctionDeclaration {
+            ast_fields: self.fields(name, start, self.i, Some(parent)),
+            ..Default::default()
+        };
+        self.functions.push(declaration);
+        self.pending_function = Some(self.functions.len() - 1);
+    }
+
+    fn close_function(&mut self, index: usize, end: usize) {
+        let start = self.functions[index].ast_fields.full_range.start_byte;
+        let body_start = self.functions[index].ast_fields.declaration_range.end_byte;
+        self.functions[index].ast_fields.full_range = self.range(start, end);
+        self.functions[index].ast_fields.definition_range = self.range(body_start, end);
+    }
+
+    /// `name()` declares a function, `arr=(...)` is an array, `(...)` at command
+    /// position is a subshell (or the body of a pending function)
+    fn open_paren(&mut self, words: &mut Vec<Word>) {
+        if words.is_empty() {
+            self.i += 1;
+            let function = self.pending_function.take();
+            if let Some(index) = function {
+                self.blocks.push(Block::Function(index));
+            }
+            self.list(Some(b')'));
+            if let Some(index) = function {
+                self.blocks.pop();
+                self.close_function(index, self.i);
+            }
+            return;
+        }
+        let empty_parens = self.code[self.i + 1..].trim_start_matches([' ', '\t']).starts_with(')');
+        if words.len() == 1 && !words[0].quoted && empty_parens {
+            let word = words.pop().unwrap_or_else(|| unreachable!());
+            self.skip_balanced(b'(', b')');
+            self.declare_function(&word.text, word.start);
+        } else {
+            self.skip_balanced(b'(', b')');
+        }
+    }
+
+    /// Consumes a case pattern up to its `)`; returns false when not at a pattern
+    fn case_pattern(&mut self) -> bool {
+        while matches!(self.peek(0), Some(b' ' | b'\t' | b'\r' | b'\n')) {
+            self.i += 1;
+        }
+        if self.code[self.i..].starts_with("esac") && !self.b.get(self.i + 4).is_some_and(|c| c.is_ascii_alphanumeric() || *c == b'_') {
+            return false;
+        }
+        if self.peek(0).is_none() || self.peek(0) == Some(b'#') {
+            return false;
+        }
+        if self.peek(0) == Some(b'(') {
+            self.i += 1;
+        }
+        while let Some(c) = self.peek(0) {
+            match c {
+                b')' => {
+                    self.i += 1;
+                    break;
+                }
+                b'\'' | b'"' => {
+                    self.word(None);
+                }
+                b'\\' => self.i = (self.i + 2).min(self.b.len()),
+                _ => self.i += 1,
+            }
+        }
+        if let Some(expect_pattern) = self.cases.last_mut() {
+            *expect_pattern = false;
+        }
+        true
+    }
+
+    fn skip_blanks(&mut self) {
+        while matches!(self.peek(0), Some(b' ' | b'\t')) {
+            self.i += 1;
+        }
+    }
+
+    /// Skips a balanced `open ... close` group starting at the current `open`
+    fn skip_balanced(&mut self, open: u8, close: u8) {
+        let mut depth = 0;
+        while let Some(c) = self.peek(0) {
+            match c {
+                b'\\' => {
+                    self.i += 2;
+                    continue;
+                }
+                b'\'' | b'"' => {
+                    self.word(None);
+                    continue;
+                }
+                _ if c == open => depth += 1,
+                _ if c == close => {
+                    depth -= 1;
+                    if depth == 0 {
+                        self.i += 1;
+                        return;
+                    }
+                }
+                _ => {}
+            }
+            self.i += 1;
+        }
+        self.i = self.i.min(self.b.len());
+    }
+
+    /// Redirections: heredocs register their delimiter, `<(...)`/`>(...)` are
+    /// scanned as commands and other targets are skipped
+    fn redirection(&mut self) {
+        if self.code[self.i..].starts_with("<<<") {
+            self.i += 3;
+        } else if self.code[self.i..].starts_with("<<") {
+            self.i += 2;
+            let strip_tabs = self.peek(0) == Some(b'-');
+            if strip_tabs {
+                self.i += 1;
+            }
+            self.skip_blanks();
+            let delimiter = self.word(None);
+            self.heredocs.push((delimiter.text, strip_tabs));
+            return;
+        } else if matches!(self.peek(1), Some(b'(')) {
+            self.i += 2;
+            self.list(Some(b')'));
+            return;
+        } else {
+            self.i += 1;
+            while matches!(self.peek(0), Some(b'<' | b'>' | b'&' | b'|')) {
+                self.i += 1;
+            }
+        }
+        self.skip_blanks();
+        if !matches!(self.peek(0), None | Some(b'\n' | b';' | b'|' | b'&' | b')')) {
+            self.word(None);
+        }
+    }
+
+    /// Skips the bodies of heredocs opened on the line that just ended
+    fn skip_heredocs(&mut self) {
+        for (delimiter, strip_tabs) in std::mem::take(&mut self.heredocs) {
+            while self.i < self.b.len() {
+                let end = self.b[self.i..].iter().position(|&c| c == b'\n').map_or(self.b.len(), |p| self.i + p);
+                let line = &self.code[self.i..end];
+                let line = if strip_tabs { line.trim_start_matches('\t') } else { line };
+                self.i = (end + 1).min(self.b.len());
+                if line.trim_end_matches('\r') == delimiter {
+                    break;
+                }
+            }
+        }
+    }
+
+    /// Reads one word, recursing into command substitutions
+    fn word(&mut self, close: Option<u8>) -> Word {
+        let start = self.i;
+        let mut text = String::new();
+        let mut quoted = false;
+        while let Some(c) = self.peek(0) {
+            match c {
+                b' ' | b'\t' | b'\n' | b'\r' | b';' | b'&' | b'|' | b'<' | b'>' | b'(' | b')' => break,
+                b'`' if close == Some(b'`') => break,
+                b'`' => {
+                    quoted = true;
+                    self.i += 1;
+                    self.list(Some(b'`'));
+                    text.push_str("$()");
+                }
+                b'\\' => {
+                    quoted = true;
+                    self.i += 1;
+                    if let Some(escaped) = self.code[self.i..].chars().next() {
+                        text.push(escaped);
+                        self.i += escaped.len_utf8();
+                    }
+                }
+                b'\'' => {
+                    quoted = true;
+                    let end = self.b[self.i + 1..].iter().position(|&c| c == b'\'').map_or(self.b.len(), |p| self.i + 1 + p);
+                    text.push_str(&self.code[self.i + 1..end]);
+                    self.i = (end + 1).min(self.b.len());
+                }
+                b'"' => {
+                    quoted = true;
+                    self.i += 1;
+                    self.double_quoted(&mut text);
+                }
+                b'$' => {
+                    quoted = true;
+                    self.expansion(&mut text);
+                }
+                _ => {
+                    let ch = self.code[self.i..].chars().next().unwrap_or(' ');
+                    text.push(ch);
+                    self.i += ch.len_utf8();
+                }
+            }
+        }
+        Word { text, start, end: self.i, quoted }
+    }
+
+    fn double_quoted(&mut self, text: &mut String) {
+        while let Some(c) = self.peek(0) {
+            match c {
+                b'"' => {
+                    self.i += 1;
+                    return;
+                }
+                b'\\' => {
+                    self.i += 1;
+                    if let Some(escaped) = self.code[self.i..].chars().next() {
+                        text.push(escaped);
+                        self.i += escaped.len_utf8();
+                    }
+                }
+                b'$' => self.expansion(text),
+                b'`' => {
+                    self.i += 1;
+                    self.list(Some(b'`'));
+                    text.push_str("$()");
+                }
+                _ => {
+                    let ch = self.code[self.i..].chars().next().unwrap_or(' ');
+                    text.push(ch);
+                    self.i += ch.len_utf8();
+                }
+            }
+        }
+    }
+
+    /// `$(...)` is scanned for commands, `$((...))` and `${...}` are skipped
+    fn expansion(&mut self, text: &mut String) {
+        match (self.peek(1), self.peek(2)) {
+            (Some(b'('), Some(b'(')) => {
+                self.i += 1;
+                self.skip_balanced(b'(', b')');
+                text.push_str("$(())");
+            }
+            (Some(b'('), _) => {
+                self.i += 2;
+                self.list(Some(b')'));
+                text.push_str("$()");
+            }
+            (Some(b'{'), _) => {
+                self.i += 1;
+                self.skip_balanced(b'{', b'}');
+                text.push_str("${}");
+            }
+            _ => {
+                text.push('$');
+                self.i += 1;
+            }
+        }
+    }
+
+    /// Records the call made by a complete simple command
+    fn finish(&mut self, words: &mut Vec<Word>) {
+        let command: Vec<Word> = std::mem::take(words);
+        let mut k = 0;
+        while k < command.len() && (is_assignment(&command[k]) || COMMAND_PREFIXES.contains(&command[k].text.as_str())) {
+            k += 1;
+            // options of the prefix (`sudo -u deploy`, `env -i`)
+            while k < command.len() && command[k].text.starts_with('-') {
+                k += if matches!(command[k].text.as_str(), "-u" | "-g" | "-C") { 2 } else { 1 };
+            }
+        }
+        let Some(first) = command.get(k) else {
+            return;
+        };
+        let name = first.text.as_str();
+        if IGNORED_COMMANDS.contains(&name) {
+            if name == "case" {
+                self.cases.push(true);
+            }
+            return;
+        }
+        let end = command.last().map_or(first.end, |w| w.end);
+        let parent = self.enclosing();
+
+        if name == "source" || name == "." {
+            let Some(target) = command.get(k + 1) else {
+                return;
+            };
+            let Some(script) = script_name(&target.text) else {
+                return;
+            };
+            let import = ImportDeclaration {
+                ast_fields: self.fields(script, first.start, end, None),
+                path_components: target.text.split('/').filter(|s| !s.is_empty()).map(str::to_string).collect(),
+                import_type: ImportType::UserModule,
+                ..Default::default()
+            };
+            self.imports.push(import);
+            self.record_call(script, first.start, end, parent);
+            return;
+        }
+
+        let target = if SHELLS.contains(&name) {
+            match command[k + 1..].iter().find(|w| !w.text.starts_with('-')) {
+                Some(script) if is_script_path(&script.text) => script_name(&script.text),
+                _ => None,
+            }
+        } else if is_script_path(name) {
+            script_name(name)
+        } else if is_command_name(name) && !SHELL_BUILTINS.contains(&name) {
+            Some(name)
+        } else {
+            None
+        };
+        if let Some(target) = target {
+            let target = target.to_string();
+            self.record_call(&target, first.start, end, parent);
+        }
+    }
+
+    fn record_call(&mut self, name: &str, start: usize, end: usize, parent: Uuid) {
+        let call = FunctionCall {
+            ast_fields: self.fields(name, start, end, Some(parent)),
+            ..Default::default()
+        };
+        self.calls.push(call);
+    }
+}
@@ -1,4 +1,4 @@
-use std::collections::{HashMap, VecDeque};
+use std::collections::{HashMap, HashSet, VecDeque};
 use std::path::PathBuf;
 use std::string::ToString;
 use std::sync::Arc;
@@ -6,12 +6,12 @@ use itertools::Itertools;
 use parking_lot::RwLock;
 
 use similar::DiffableStr;
-use tree_sitter::{Node, Parser, Range};
+use tree_sitter::{Node, Parser, Point, Range};
 use uuid::Uuid;
 
 use crate::codegraph::treesitter::ast_instance_structs::{AstSymbolFields, AstSymbolInstanceArc, ClassFieldDeclaration, CommentDefinition, FunctionArg, FunctionCall, FunctionDeclaration, ImportDeclaration, ImportType, StructDeclaration, TypeDef, VariableDefinition, VariableUsage};
 use crate::codegraph::treesitter::language_id::LanguageId;
-use crate::codegraph::treesitter::parsers::{AstLanguageParser, internal_error, ParserError};
+use crate::codegraph::treesitter::parsers::{AstLanguageParser, internal_error, ParserError, TreeStats};
 use crate::codegraph::treesitter::parsers::utils::{CandidateInfo, get_guid};
 use crate::codegraph::treesitter::skeletonizer::SkeletonFormatter;
 use crate::codegraph::treesitter::ast_instance_structs::SymbolInformation;
@@ -19,8 +19,21 @@ use crate::codegraph::treesitter::structs::SymbolType;
 
 pub(crate) struct CppParser {
     pub parser: Parser,
+    tree_stats: Option<TreeStats>,
+    expand_macros: bool,
+    /// Calls found in the body of each macro of the file being parsed, by the
+    /// start byte of its `#define`
+    macro_calls: HashMap<usize, Vec<(String, Range)>>,
+    /// Function-like macros of the file -> positions of the parameters the body calls
+    macro_call_params: HashMap<String, Vec<usize>>,
+    /// Ids of the `compound_statement` nodes inside `ERROR` subtrees that were
+    /// recovered as function bodies
+    recovered_bodies: HashSet<usize>,
 }
 
+/// Wraps a macro body so that tree-sitter parses it as statements
+const MACRO_BODY_PREFIX: &str = "void __codegraph_macro__() {\n";
+
 
 static CPP_KEYWORDS: [&str; 92] = [
     "alignas", "alignof", "and", "and_eq", "asm", "auto", "bitand", "bitor",
@@ -98,13 +111,182 @@ pub fn parse_type(parent: &Node, code: &str) -> Option<TypeDef> {
     None
 }
 
+/// Types of a `template_argument_list`; arguments that are not plain types (`std::string`, `3`)
+/// are kept as written
+fn template_arguments(arguments: &Node, code: &str) -> Vec<TypeDef> {
+    let mut types = vec![];
+    for i in 0..arguments.named_child_count() {
+        let argument = arguments.named_child(i).unwrap();
+        types.push(parse_type(&argument, code).unwrap_or_else(|| TypeDef {
+            name: Some(code.slice(argument.byte_range()).to_string()),
+            ..Default::default()
+        }));
+    }
+    types
+}
+
 impl CppParser {
     pub fn new() -> Result<CppParser, ParserError> {
         let mut parser = Parser::new();
         parser
             .set_language(&tree_sitter_cpp::LANGUAGE.into())
             .map_err(internal_error)?;
-        Ok(CppParser { parser })
+        Ok(CppParser {
+            parser,
+            tree_stats: None,
+            expand_macros: false,
+            macro_calls: HashMap::new(),
+            macro_call_params: HashMap::new(),
+            recovered_bodies: HashSet::new(),
+        })
+    }
+
+    /// A parser that also analyzes the macros defined in a file.
+    ///
+    /// Every function-like `#define`, and every object-like one whose body calls
+    /// something or just names a function (`#define log_err log_write`), becomes a
+    /// function spanning the definition, and the calls in its body become its calls.
+    /// An invocation such as `CHECK(x)` then resolves to the macro instead of being a
+    /// dangling call, and the call graph continues through the macro body. When the
+    /// body calls one of the macro's parameters (`#define CALL(f, x) f(x)`), the
+    /// argument passed at each invocation in the same file is recorded as a call too.
+    /// Macros are not expanded into each other and conditional compilation is ignored.
+    pub fn with_macro_expansion() -> Result<CppParser, ParserError> {
+        Ok(CppParser { expand_macros: true, ..CppParser::new()? })
+    }
+
+    /// Collects the calls in the bodies of all macros defined in the tree
+    fn collect_macros(&mut self, root: &Node, code: &str) {
+        self.macro_calls.clear();
+        self.macro_call_params.clear();
+        let mut stack = vec![*root];
+        while let Some(node) = stack.pop() {
+            match node.kind() {
+                "preproc_function_def" | "preproc_def" => {
+                    let Some(value) = node.child_by_field_name("value") else { continue };
+                    let calls = self.macro_body_calls(&value, code);
+                    if let (Some(name), Some(parameters)) = (node.child_by_field_name("name"), node.child_by_field_name("parameters")) {
+                        let parameters = macro_parameters(&parameters, code);
+                        let called: Vec<usize> = parameters
+                            .iter()
+                            .enumerate()
+                            .filter(|(_, parameter)| calls.iter().any(|(call, _)| call == *parameter))
+                            .map(|(i, _)| i)
+                            .collect();
+                        if !called.is_empty() {
+                            self.macro_call_params.insert(code.slice(name.byte_range()).to_string(), called);
+                        }
+                    }
+                    self.macro_calls.insert(node.start_byte(), calls);
+                }
+                _ => {
+                    for i in (0..node.child_count()).rev() {
+                        stack.push(node.child(i).unwrap());
+                    }
+                }
+            }
+        }
+    }
+
+    /// Calls in a macro body, with their ranges in the file
+    fn macro_body_calls(&mut self, value: &Node, code: &str) -> Vec<(String, Range)> {
+        let body = code.slice(value.byte_range());
+        let wrapped = format!("{}{}\n;}}", MACRO_BODY_PREFIX, body);
+        let Some(tree) = self.parser.parse(&wrapped, None) else { return vec![] };
+        let origin = value.start_position();
+        let to_file = |point: Point| Point {
+            row: origin.row + point.row - 1,
+            column: if point.row == 1 { origin.column + point.column } else { point.column },
+        };
+
+        let mut calls = vec![];
+        let mut stack = vec![tree.root_node()];
+        while let Some(node) = stack.pop() {
+            if node.kind() == "call_expression" && node.start_byte() >= MACRO_BODY_PREFIX.len() {
+                let name = node.child_by_field_name("function").and_then(|function| match function.kind() {
+                    "identifier" => Some(function),
+                    "field_expression" => function.child_by_field_name("field"),
+                    _ => None,
+                });
+                if let Some(name) = name {
+                    let range = Range {
+                        start_byte: value.start_byte() + node.start_byte() - MACRO_BODY_PREFIX.len(),
+                        end_byte: (value.start_byte() + node.end_byte() - MACRO_BODY_PREFIX.len()).min(value.end_byte()),
+                        start_point: to_file(node.start_position()),
+                        end_point: to_file(node.end_position()),
+                    };
+                    calls.push((wrapped[name.byte_range()].to_string(), range));
+                }
+            }
+            for i in (0..node.child_count()).rev() {
+                stack.push(node.child(i).unwrap());
+            }
+        }
+        calls
+    }
+
+    fn parse_macro_definition<'a>(&mut self, info: &CandidateInfo<'a>, code: &str) -> Vec<AstSymbolInstanceArc> {
+        let mut symbols: Vec<AstSymbolInstanceArc> = Default::default();
+        let (Some(name), Some(value)) = (info.node.child_by_field_name("name"), info.node.child_by_field_name("value")) else {
+            return symbols;
+        };
+        let mut calls = self.macro_calls.get(&info.node.start_byte()).cloned().unwrap_or_default();
+        let parameters = info.node.child_by_field_name("parameters");
+        if parameters.is_none() && calls.is_empty() {
+            // an object-like macro is only a function when it aliases one
+            let body = code.slice(value.byte_range()).trim();
+            if body.is_empty() || !body.chars().all(|c| c == '_' || c.is_ascii_alphanumeric()) || body.starts_with(|c: char| c.is_ascii_digit()) {
+                return symbols;
+            }
+            calls.push((body.to_string(), value.range()));
+        }
+
+        let mut decl = FunctionDeclaration::default();
+        decl.ast_fields.language = info.ast_fields.language;
+        decl.ast_fields.file_path = info.ast_fields.file_path.clone();
+        decl.ast_fields.is_error = info.ast_fields.is_error;
+        decl.ast_fields.name = code.slice(name.byte_range()).to_string();
+        // the node includes the line break ending the directive
+        decl.ast_fields.full_range = Range {
+            start_byte: info.node.start_byte(),
+            end_byte: value.end_byte(),
+            start_point: info.node.start_position(),
+            end_point: value.end_position(),
+        };
+        let declared = parameters.unwrap_or(name);
+        decl.ast_fields.declaration_range = Range {
+            start_byte: info.node.start_byte(),
+            end_byte: declared.end_byte(),
+            start_point: info.node.start_position(),
+            end_point: declared.end_position(),
+        };
+        decl.ast_fields.definition_range = value.range();
+        decl.ast_fields.parent_guid = Some(info.parent_guid);
+        decl.ast_fields.guid = get_guid();
+        let parameters = parameters.map(|p| macro_parameters(&p, code)).unwrap_or_default();
+        decl.args = parameters
+            .iter()
+            .map(|parameter| FunctionArg { name: parameter.clone(), type_: None })
+            .collect();
+
+        for (callee, range) in calls {
+            // calls of a parameter are recorded at the invocations instead
+            if parameters.contains(&callee) {
+                continue;
+            }
+            let mut call = FunctionCall::default();
+            call.ast_fields.language = info.ast_fields.language;
+            call.ast_fields.file_path = info.ast_fields.file_path.clone();
+            call.ast_fields.is_error = info.ast_fields.is_error;
+            call.ast_fields.name = callee;
+            call.ast_fields.full_range = range;
+            call.ast_fields.parent_guid = Some(decl.ast_fields.guid);
+            call.ast_fields.guid = get_guid();
+            call.ast_fields.caller_guid = Some(get_guid());
+            symbols.push(Arc::new(RwLock::new(Box::new(call))));
+        }
+        symbols.push(Arc::new(RwLock::new(Box::new(decl))));
+        symbols
     }
 
     pub fn parse_struct_declaration<'a>(
@@ -584,6 +766,71 @@ impl CppParser {
         symbols
     }
 
+    /// Function definitions that error recovery split apart.
+    ///
+    /// After a broken definition, tree-sitter often folds the following
+    /// definitions into the `ERROR` node: `int next() { .. }` becomes a
+    /// declaration with a `function_declarator`, followed by a separate
+    /// `compound_statement`, possibly one level of `ERROR` apart. Each such pair
+    /// is turned back into a function whose body is parsed as usual.
+    fn recover_function_definitions<'a>(&mut self, info: &CandidateInfo<'a>, code: &str, candidates: &mut VecDeque<CandidateInfo<'a>>) -> Vec<AstSymbolInstanceArc> {
+        fn flatten<'a>(node: Node<'a>, items: &mut Vec<Node<'a>>) {
+            for i in 0..node.child_count() {
+                let child = node.child(i).unwrap();
+                if child.kind() == "ERROR" {
+                    flatten(child, items);
+                } else {
+                    items.push(child);
+                }
+            }
+        }
+        let mut items = Vec::new();
+        flatten(info.node, &mut items);
+
+        let mut symbols: Vec<AstSymbolInstanceArc> = Default::default();
+        for pair in items.windows(2) {
+            let (head, body) = (pair[0], pair[1]);
+            let Some(declarator) = head.child_by_field_name("declarator") else { continue };
+            if declarator.kind() != "function_declarator" || body.kind() != "compound_statement" {
+                continue;
+            }
+            let mut decl = FunctionDeclaration::default();
+            decl.ast_fields.language = info.ast_fields.language;
+            decl.ast_fields.file_path = info.ast_fields.file_path.clone();
+            decl.ast_fields.is_error = true;
+            decl.ast_fields.full_range = Range {
+                start_byte: head.start_byte(),
+                end_byte: body.end_byte(),
+                start_point: head.start_position(),
+                end_point: body.end_position(),
+            };
+            decl.ast_fields.declaration_range = head.range();
+            decl.ast_fields.definition_range = body.range();
+            decl.ast_fields.parent_guid = Some(info.parent_guid);
+            decl.ast_fields.guid = get_guid();
+            if let Some(name) = declarator.child_by_field_name("declarator") {
+                let (symbols_l, types_l, name_l, namespace_l) =
+                    self.parse_declaration(&name, code, &decl.ast_fields.file_path,
+                                           &decl.ast_fields.guid, true, candidates);
+                symbols.extend(symbols_l);
+                decl.ast_fields.name = name_l;
+                decl.ast_fields.namespace = namespace_l;
+                decl.template_types = types_l;
+            }
+            if let Some(return_type) = head.child_by_field_name("type") {
+                decl.return_type = parse_type(&return_type, code);
+            }
+            self.recovered_bodies.insert(body.id());
+            candidates.push_back(CandidateInfo {
+                ast_fields: decl.ast_fields.clone(),
+                node: body,
+                parent_guid: decl.ast_fields.guid,
+            });
+            symbols.push(Arc::new(RwLock::new(Box::new(decl))));
+        }
+        symbols
+    }
+
     pub fn parse_call_expression<'a>(&mut self, info: &CandidateInfo<'a>, code: &str, candidates: &mut VecDeque<CandidateInfo<'a>>) -> Vec<AstSymbolInstanceArc> {
         let mut symbols: Vec<AstSymbolInstanceArc> = Default::default();
         let mut decl = FunctionCall::default();
@@ -604,12 +851,12 @@ impl CppParser {
             symbols.extend(self.find_error_usages(&function, code, &info.ast_fields.file_path,
                                                   &info.parent_guid));
             match function.kind() {
-                "identifier" => {
-                    decl.ast_fields.name = code.slice(function.byte_range()).to_string();
+                "identifier" | "template_function" | "qualified_identifier" => {
+                    self.parse_call_target(&function, code, &mut decl);
                 }
                 "field_expression" => {
                     if let Some(field) =  function.child_by_field_name("field") {
-                        decl.ast_fields.name = code.slice(field.byte_range()).to_string();
+                        self.parse_call_target(&field, code, &mut decl);
                     }
                     if let Some(argument) = function.child_by_field_name("argument") {
                         candidates.push_back(CandidateInfo {
@@ -629,6 +876,22 @@ impl CppParser {
             }
         }
         if let Some(arguments) = info.node.child_by_field_name("arguments") {
+            if let Some(positions) = self.macro_call_params.get(&decl.ast_fields.name) {
+                // `CALL(handler, x)` calls `handler` when the macro body calls that parameter
+                for &position in positions {
+                    let Some(argument) = arguments.named_child(position).filter(|a| a.kind() == "identifier") else { continue };
+                    let mut call = FunctionCall::default();
+                    call.ast_fields.language = info.ast_fields.language;
+                    call.ast_fields.file_path = info.ast_fields.file_path.clone();
+                    call.ast_fields.is_error = info.ast_fields.is_error;
+                    call.ast_fields.name = code.slice(argument.byte_range()).to_string();
+                    call.ast_fields.full_range = argument.range();
+                    call.ast_fields.parent_guid = Some(info.parent_guid);
+                    call.ast_fields.guid = get_guid();
+                    call.ast_fields.caller_guid = Some(get_guid());
+                    symbols.push(Arc::new(RwLock::new(Box::new(call))));
+                }
+            }
             symbols.extend(self.find_error_usages(&arguments, code, &info.ast_fields.file_path,
                                                   &info.parent_guid));
             let mut new_ast_fields = info.ast_fields.clone();
@@ -647,6 +910,42 @@ impl CppParser {
         symbols
     }
 
+    /// Sets the called name of `foo`, `foo<int>`, `ns::Box<int>::make` or `obj.template as<long>`,
+    /// collecting the template arguments of the function and of its class scope into `template_types`
+    fn parse_call_target(&self, node: &Node, code: &str, decl: &mut FunctionCall) {
+        match node.kind() {
+            "template_function" | "template_method" => {
+                if let Some(name) = node.child_by_field_name("name") {
+                    self.parse_call_target(&name, code, decl);
+                }
+                if let Some(arguments) = node.child_by_field_name("arguments") {
+                    decl.template_types.extend(template_arguments(&arguments, code));
+                }
+            }
+            "qualified_identifier" => {
+                if let Some(scope) = node.child_by_field_name("scope") {
+                    if scope.kind() == "template_type" {
+                        if let Some(arguments) = scope.child_by_field_name("arguments") {
+                            decl.template_types.extend(template_arguments(&arguments, code));
+                        }
+                    }
+                }
+                if let Some(name) = node.child_by_field_name("name") {
+                    self.parse_call_target(&name, code, decl);
+                }
+            }
+            "dependent_name" => {
+                // `obj.template as<long>()`
+                if let Some(name) = node.named_child(0) {
+                    self.parse_call_target(&name, code, decl);
+                }
+            }
+            _ => {
+                decl.ast_fields.name = code.slice(node.byte_range()).to_string();
+            }
+        }
+    }
+
     fn find_error_usages(&mut self, parent: &Node, code: &str, path: &PathBuf, parent_guid: &Uuid) -> Vec<AstSymbolInstanceArc> {
         let mut symbols: Vec<AstSymbolInstanceArc> = Default::default();
         for i in 0..parent.child_count() {
@@ -723,6 +1022,9 @@ impl CppParser {
             "function_definition" => {
                 symbols.extend(self.parse_function_declaration(info, code, candidates));
             }
+            "preproc_function_def" | "preproc_def" if self.expand_macros => {
+                symbols.extend(self.parse_macro_definition(info, code));
+            }
             "call_expression" => {
                 symbols.extend(self.parse_call_expression(info, code, candidates));
             }
@@ -828,11 +1130,17 @@ impl CppParser {
                 }
             }
             "ERROR" => {
+                if info.node.parent().is_none_or(|parent| parent.kind() != "ERROR") {
+                    symbols.extend(self.recover_function_definitions(info, code, candidates));
+                }
                 let mut ast = info.ast_fields.clone();
                 ast.is_error = true;
 
                 for i in 0..info.node.child_count() {
                     let child = info.node.child(i).unwrap();
+                    if self.recovered_bodies.contains(&child.id()) {
+                        continue;
+                    }
                     candidates.push_back(CandidateInfo {
                         ast_fields: ast.clone(),
                         node: child,
@@ -860,6 +1168,9 @@ impl CppParser {
         ast_fields.file_path = path.clone();
         ast_fields.is_error = false;
         ast_fields.language = LanguageId::Cpp;
+        if self.expand_macros {
+            self.collect_macros(parent, code);
+        }
 
         let mut candidates = VecDeque::from(vec![CandidateInfo {
             ast_fields,
@@ -897,9 +1208,26 @@ impl CppParser {
 impl AstLanguageParser for CppParser {
     fn parse(&mut self, code: &str, path: &PathBuf) -> Vec<AstSymbolInstanceArc> {
         let tree = self.parser.parse(code, None).unwrap();
+        self.tree_stats = Some(TreeStats::of(&tree));
+        self.recovered_bodies.clear();
         let symbols = self.parse_(&tree.root_node(), code, path);
         symbols
     }
+
+    fn tree_stats(&self) -> Option<TreeStats> {
+        self.tree_stats.clone()
+    }
+}
+
+/// Parameter names of a function-like macro; `...` is `__VA_ARGS__`
+fn macro_parameters(parameters: &Node, code: &str) -> Vec<String> {
+    let mut names = vec![];
+    for i in 0..parameters.named_child_count() {
+        let parameter = parameters.named_child(i).unwrap();
+        let text = code.slice(parameter.byte_range());
+        names.push(if text == "..." { "__VA_ARGS__".to_string() } else { text.to_string() });
+    }
+    names
 }
 
 pub struct CppSkeletonFormatter;
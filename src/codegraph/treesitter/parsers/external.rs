@@ -0,0 +1,527 @@
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::Arc;
+
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use tracing::{info, warn};
+use tree_sitter::{Point, Range};
+use uuid::Uuid;
+
+use crate::codegraph::treesitter::ast_instance_structs::{AstSymbolFields, AstSymbolInstanceArc, FunctionCall, FunctionDeclaration, ImportDeclaration, ImportType, StructDeclaration};
+use crate::codegraph::treesitter::language_id::LanguageId;
+use crate::codegraph::treesitter::parsers::AstLanguageParser;
+use crate::codegraph::treesitter::parsers::utils::get_guid;
+
+/// Language name and file extensions an external analyzer handles
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AnalyzerManifest {
+    /// Reported as `FunctionInfo.language` and accepted by `--languages`
+    pub name: String,
+    /// Extensions without the dot, matched case-insensitively
+    pub extensions: Vec<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ExternalSymbolKind {
+    Function,
+    Class,
+    Call,
+    Import,
+}
+
+/// One symbol reported by an external analyzer.
+///
+/// Only lines are needed: calls are attributed to the innermost function whose
+/// lines contain them, as for the bundled analyzers.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ExternalSymbol {
+    pub kind: ExternalSymbolKind,
+    /// Function or class name, called name, or imported module path
+    pub name: String,
+    /// 1-based first line
+    pub line: usize,
+    /// 1-based last line; `line` when omitted
+    #[serde(default)]
+    pub end_line: Option<usize>,
+}
+
+/// What an analyzer plugin returns for one file
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct AnalysisOutput {
+    pub symbols: Vec<ExternalSymbol>,
+}
+
+/// An analyzer for a language that has no bundled parser.
+///
+/// Implement it in-process and pass it to [`AnalyzerRegistry::register`], or
+/// build it as a shared library or executable and load it with
+/// [`AnalyzerRegistry::register_dynamic`].
+pub trait LanguageAnalyzer: Send + Sync {
+    fn manifest(&self) -> &AnalyzerManifest;
+    fn analyze(&self, path: &Path, code: &str) -> Result<Vec<ExternalSymbol>, String>;
+}
+
+static ANALYZERS: RwLock<Vec<Arc<dyn LanguageAnalyzer>>> = parking_lot::const_rwlock(Vec::new());
+/// Files that analyzers loaded with [`AnalyzerRegistry::register_dynamic`] came from, by name
+static SOURCES: RwLock<BTreeMap<String, PathBuf>> = parking_lot::const_rwlock(BTreeMap::new());
+/// Languages, bundled or external, whose files are currently not analyzed
+static DISABLED: RwLock<BTreeSet<String>> = parking_lot::const_rwlock(BTreeSet::new());
+
+/// Process-wide set of external analyzers, consulted before the bundled parsers
+pub struct AnalyzerRegistry;
+
+impl AnalyzerRegistry {
+    /// Add an analyzer; one registered earlier under the same name is replaced
+    pub fn register(analyzer: Arc<dyn LanguageAnalyzer>) -> Result<(), String> {
+        let manifest = analyzer.manifest();
+        if manifest.name.trim().is_empty() {
+            return Err("analyzer name must not be empty".to_string());
+        }
+        if manifest.name != manifest.name.to_lowercase() {
+            return Err(format!("analyzer name '{}' must be lowercase", manifest.name));
+        }
+        if manifest.extensions.iter().all(|e| e.trim_start_matches('.').is_empty()) {
+            return Err(format!("analyzer '{}' declares no file extensions", manifest.name));
+        }
+        let mut analyzers = ANALYZERS.write();
+        analyzers.retain(|a| a.manifest().name != manifest.name);
+        info!("Registered analyzer '{}' for .{}", manifest.name, manifest.extensions.join(", ."));
+        analyzers.push(analyzer);
+        Ok(())
+    }
+
+    /// Load an analyzer from a shared library (`.so`, `.dylib`, `.dll`) or an executable.
+    ///
+    /// Shared libraries export the C functions described in the README and need the
+    /// `dynamic-analyzers` feature. Any other file is run as a command: `<path> manifest`
+    /// prints the manifest and `<path> analyze <file>` reads the source on stdin and
+    /// prints `{"symbols": [...]}`.
+    pub fn register_dynamic(path: impl AsRef<Path>) -> Result<AnalyzerManifest, String> {
+        let path = path.as_ref();
+        let extension = path.extension().and_then(|e| e.to_str()).unwrap_or("").to_lowercase();
+        let analyzer: Arc<dyn LanguageAnalyzer> = match extension.as_str() {
+            "so" | "dylib" | "dll" => Arc::new(load_library(path)?),
+            "wasm" => {
+                return Err(format!(
+                    "{}: no WebAssembly runtime is bundled; wrap the module in an executable (e.g. a script running `wasmtime {}`) and load that",
+                    path.display(),
+                    path.display()
+                ))
+            }
+            _ => Arc::new(CommandAnalyzer::new(path)?),
+        };
+        let manifest = analyzer.manifest().clone();
+        Self::register(analyzer)?;
+        SOURCES.write().insert(manifest.name.clone(), path.to_path_buf());
+        Ok(manifest)
+    }
+
+    /// Load every analyzer registered with [`Self::register_dynamic`] again from its file,
+    /// picking up a rebuilt library or an edited script; stops at the first one that fails
+    pub fn reload_dynamic() -> Result<Vec<AnalyzerManifest>, String> {
+        let sources: Vec<(String, PathBuf)> = SOURCES.read().iter().map(|(name, path)| (name.clone(), path.clone())).collect();
+        let mut reloaded = Vec::new();
+        for (name, path) in sources {
+            let manifest = Self::register_dynamic(&path).map_err(|e| format!("Failed to reload analyzer '{}': {}", name, e))?;
+            if manifest.name != name {
+                // The file now declares another language; the old registration would otherwise linger
+                Self::unregister(&name);
+            }
+            reloaded.push(manifest);
+        }
+        Ok(reloaded)
+    }
+
+    /// Remove the analyzer registered under `name`
+    pub fn unregister(name: &str) -> bool {
+        let mut analyzers = ANALYZERS.write();
+        let before = analyzers.len();
+        analyzers.retain(|a| a.manifest().name != name);
+        SOURCES.write().remove(name);
+        analyzers.len() != before
+    }
+
+    /// Stop or resume analyzing files of `language` (a bundled language or an external
+    /// analyzer's name). Builds skip the files of a disabled language and other callers
+    /// get a parse error for them.
+    pub fn set_enabled(language: &str, enabled: bool) {
+        let mut disabled = DISABLED.write();
+        let changed = if enabled { disabled.remove(language) } else { disabled.insert(language.to_string()) };
+        if changed {
+            info!("{} the {} analyzer", if enabled { "Enabled" } else { "Disabled" }, language);
+        }
+    }
+
+    pub fn is_enabled(language: &str) -> bool {
+        !DISABLED.read().contains(language)
+    }
+
+    pub fn disabled() -> Vec<String> {
+        DISABLED.read().iter().cloned().collect()
+    }
+
+    pub fn registered() -> Vec<AnalyzerManifest> {
+        ANALYZERS.read().iter().map(|a| a.manifest().clone()).collect()
+    }
+
+    /// The analyzer for `path`'s extension; the most recently registered one wins
+    pub fn for_path(path: &Path) -> Option<Arc<dyn LanguageAnalyzer>> {
+        let extension = path.extension().and_then(|e| e.to_str())?.to_lowercase();
+        ANALYZERS
+            .read()
+            .iter()
+            .rev()
+            .find(|a| a.manifest().extensions.iter().any(|e| e.trim_start_matches('.').eq_ignore_ascii_case(&extension)))
+            .cloned()
+    }
+
+    /// Language name of the analyzer for `path`, if one is registered
+    pub fn language_for_path(path: &Path) -> Option<String> {
+        Self::for_path(path).map(|a| a.manifest().name.clone())
+    }
+
+    pub fn is_registered_language(name: &str) -> bool {
+        ANALYZERS.read().iter().any(|a| a.manifest().name == name)
+    }
+}
+
+/// Adapts a [`LanguageAnalyzer`] to the parser interface used by the graph builder
+pub(crate) struct ExternalParser {
+    analyzer: Arc<dyn LanguageAnalyzer>,
+}
+
+impl ExternalParser {
+    pub fn new(analyzer: Arc<dyn LanguageAnalyzer>) -> Self {
+        Self { analyzer }
+    }
+}
+
+impl AstLanguageParser for ExternalParser {
+    fn parse(&mut self, code: &str, path: &PathBuf) -> Vec<AstSymbolInstanceArc> {
+        match self.analyzer.analyze(path, code) {
+            Ok(symbols) => to_ast_symbols(&symbols, code, path),
+            Err(e) => {
+                warn!("Analyzer '{}' failed on {}: {}", self.analyzer.manifest().name, path.display(), e);
+                vec![]
+            }
+        }
+    }
+}
+
+/// Byte range covering whole lines `first..=last` (1-based, clamped to the file)
+fn line_range(line_starts: &[usize], code: &str, first: usize, last: usize) -> Range {
+    let row = |line: usize| line.clamp(1, line_starts.len()) - 1;
+    let (start_row, end_row) = (row(first), row(last.max(first)));
+    let start_byte = line_starts[start_row];
+    let end_byte = line_starts.get(end_row + 1).map_or(code.len(), |next| next - 1).max(start_byte);
+    Range {
+        start_byte,
+        end_byte,
+        start_point: Point { row: start_row, column: 0 },
+        end_point: Point { row: end_row, column: end_byte - line_starts[end_row] },
+    }
+}
+
+fn to_ast_symbols(symbols: &[ExternalSymbol], code: &str, path: &Path) -> Vec<AstSymbolInstanceArc> {
+    let line_starts: Vec<usize> = std::iter::once(0)
+        .chain(code.bytes().enumerate().filter(|(_, c)| *c == b'\n').map(|(i, _)| i + 1))
+        .collect();
+    let root_guid = get_guid();
+    let guids: Vec<Uuid> = symbols.iter().map(|_| get_guid()).collect();
+
+    // Parent of each symbol: the narrowest function or class whose lines contain it
+    let span = |s: &ExternalSymbol| (s.line, s.end_line.unwrap_or(s.line).max(s.line));
+    let parents: Vec<Option<usize>> = symbols
+        .iter()
+        .enumerate()
+        .map(|(i, symbol)| {
+            let (first, last) = span(symbol);
+            symbols
+                .iter()
+                .enumerate()
+                .filter(|&(j, s)| j != i && matches!(s.kind, ExternalSymbolKind::Function | ExternalSymbolKind::Class))
+                .filter(|&(j, s)| {
+                    let (start, end) = span(s);
+                    start <= first && last <= end && ((start, end) != (first, last) || j < i)
+                })
+                .min_by_key(|&(_, s)| span(s).1 - span(s).0)
+                .map(|(j, _)| j)
+        })
+        .collect();
+    let mut children: HashMap<usize, Vec<Uuid>> = HashMap::new();
+    for (i, parent) in parents.iter().enumerate() {
+        if let Some(parent) = parent {
+            children.entry(*parent).or_default().push(guids[i]);
+        }
+    }
+
+    let mut result: Vec<AstSymbolInstanceArc> = vec![];
+    for (i, symbol) in symbols.iter().enumerate() {
+        let (first, last) = span(symbol);
+        let mut fields = AstSymbolFields::from_data(LanguageId::Unknown, path.to_path_buf(), false);
+        fields.guid = guids[i];
+        fields.name = symbol.name.clone();
+        fields.parent_guid = Some(parents[i].map_or(root_guid, |p| guids[p]));
+        fields.childs_guid = children.remove(&i).unwrap_or_default();
+        fields.full_range = line_range(&line_starts, code, first, last);
+        fields.declaration_range = line_range(&line_starts, code, first, first);
+        fields.definition_range = fields.full_range;
+        match symbol.kind {
+            ExternalSymbolKind::Function => {
+                let declaration = FunctionDeclaration { ast_fields: fields, ..Default::default() };
+                result.push(Arc::new(RwLock::new(Box::new(declaration))));
+            }
+            ExternalSymbolKind::Class => {
+                let declaration = StructDeclaration { ast_fields: fields, ..Default::default() };
+                result.push(Arc::new(RwLock::new(Box::new(declaration))));
+            }
+            ExternalSymbolKind::Call => {
+                let call = FunctionCall { ast_fields: fields, ..Default::default() };
+                result.push(Arc::new(RwLock::new(Box::new(call))));
+            }
+            ExternalSymbolKind::Import => {
+                let import = ImportDeclaration {
+                    path_components: symbol.name.split('/').filter(|s| !s.is_empty()).map(str::to_string).collect(),
+                    import_type: ImportType::UserModule,
+                    ast_fields: fields,
+                    ..Default::default()
+                };
+                result.push(Arc::new(RwLock::new(Box::new(import))));
+            }
+        }
+    }
+    result
+}
+
+/// Analyzer run as a separate process for every file
+struct CommandAnalyzer {
+    program: PathBuf,
+    manifest: AnalyzerManifest,
+}
+
+impl CommandAnalyzer {
+    fn new(program: &Path) -> Result<Self, String> {
+        let output = Command::new(program)
+            .arg("manifest")
+            .stdin(Stdio::null())
+            .output()
+            .map_err(|e| format!("Failed to run analyzer {}: {}", program.display(), e))?;
+        if !output.status.success() {
+            return Err(format!(
+                "Analyzer {} exited with {} on `manifest`: {}",
+                program.display(),
+                output.status,
+                String::from_utf8_lossy(&output.stderr).trim()
+            ));
+        }
+        let manifest = serde_json::from_slice(&output.stdout)
+            .map_err(|e| format!("Invalid manifest from analyzer {}: {}", program.display(), e))?;
+        Ok(Self { program: program.to_path_buf(), manifest })
+    }
+}
+
+impl LanguageAnalyzer for CommandAnalyzer {
+    fn manifest(&self) -> &AnalyzerManifest {
+        &self.manifest
+    }
+
+    fn analyze(&self, path: &Path, code: &str) -> Result<Vec<ExternalSymbol>, String> {
+        let mut child = Command::new(&self.program)
+            .arg("analyze")
+            .arg(path)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|e| format!("Failed to run analyzer: {}", e))?;
+        // Written from another thread so a plugin that prints before reading all input cannot deadlock
+        let mut stdin = child.stdin.take().ok_or("analyzer stdin unavailable")?;
+        let input = code.to_string();
+        let writer = std::thread::spawn(move || stdin.write_all(input.as_bytes()));
+        let output = child.wait_with_output().map_err(|e| format!("Analyzer failed: {}", e))?;
+        let _ = writer.join();
+        if !output.status.success() {
+            return Err(format!("exited with {}: {}", output.status, String::from_utf8_lossy(&output.stderr).trim()));
+        }
+        serde_json::from_slice::<AnalysisOutput>(&output.stdout)
+            .map(|output| output.symbols)
+            .map_err(|e| format!("invalid analyzer output: {}", e))
+    }
+}
+
+#[cfg(feature = "dynamic-analyzers")]
+fn load_library(path: &Path) -> Result<dylib::LibraryAnalyzer, String> {
+    dylib::LibraryAnalyzer::load(path)
+}
+
+#[cfg(not(feature = "dynamic-analyzers"))]
+fn load_library(path: &Path) -> Result<CommandAnalyzer, String> {
+    Err(format!(
+        "{}: loading shared-library analyzers requires building with `--features dynamic-analyzers`",
+        path.display()
+    ))
+}
+
+/// Shared-library analyzers, exchanging JSON over a C ABI:
+///
+/// ```c
+/// const char *codegraph_analyzer_manifest(void);
+/// char *codegraph_analyzer_analyze(const char *path, const char *code, size_t code_len);
+/// void codegraph_analyzer_free(char *output);
+/// ```
+#[cfg(feature = "dynamic-analyzers")]
+mod dylib {
+    use std::ffi::{c_char, CStr, CString};
+    use std::path::Path;
+
+    use libloading::Library;
+
+    use super::{AnalysisOutput, AnalyzerManifest, ExternalSymbol, LanguageAnalyzer};
+
+    type ManifestFn = unsafe extern "C" fn() -> *const c_char;
+    type AnalyzeFn = unsafe extern "C" fn(*const c_char, *const c_char, usize) -> *mut c_char;
+    type FreeFn = unsafe extern "C" fn(*mut c_char);
+
+    pub(super) struct LibraryAnalyzer {
+        manifest: AnalyzerManifest,
+        analyze: AnalyzeFn,
+        free: FreeFn,
+        // Keeps the function pointers above valid
+        _library: Library,
+    }
+
+    impl LibraryAnalyzer {
+        pub(super) fn load(path: &Path) -> Result<Self, String> {
+            let error = |e: libloading::Error| format!("{}: {}", path.display(), e);
+            // SAFETY: loading runs the library's initializers; the user chose to trust it
+            let library = unsafe { Library::new(path) }.map_err(error)?;
+            // SAFETY: the signatures match the documented plugin ABI
+            let (manifest, analyze, free) = unsafe {
+                let manifest = *library.get::<ManifestFn>(b"codegraph_analyzer_manifest\0").map_err(error)?;
+                let analyze = *library.get::<AnalyzeFn>(b"codegraph_analyzer_analyze\0").map_err(error)?;
+                let free = *library.get::<FreeFn>(b"codegraph_analyzer_free\0").map_err(error)?;
+                (manifest, analyze, free)
+            };
+            // SAFETY: the plugin returns a static NUL-terminated string
+            let manifest = unsafe {
+                let raw = manifest();
+                if raw.is_null() {
+                    return Err(format!("{}: codegraph_analyzer_manifest returned null", path.display()));
+                }
+                CStr::from_ptr(raw).to_bytes().to_vec()
+            };
+            let manifest = serde_json::from_slice(&manifest)
+                .map_err(|e| format!("Invalid manifest from analyzer {}: {}", path.display(), e))?;
+            Ok(Self { manifest, analyze, free, _library: library })
+        }
+    }
+
+    impl LanguageAnalyzer for LibraryAnalyzer {
+        fn manifest(&self) -> &AnalyzerManifest {
+            &self.manifest
+        }
+
+        fn analyze(&self, path: &Path, code: &str) -> Result<Vec<ExternalSymbol>, String> {
+            let path = CString::new(path.to_string_lossy().as_bytes()).map_err(|e| e.to_string())?;
+            // SAFETY: inputs outlive the call; the output is released with the plugin's own free
+            let output = unsafe {
+                let raw = (self.analyze)(path.as_ptr(), code.as_ptr() as *const c_char, code.len());
+                if raw.is_null() {
+                    return Err("codegraph_analyzer_analyze returned null".to_string());
+                }
+                let bytes = CStr::from_ptr(raw).to_bytes().to_vec();
+                (self.free)(raw);
+                bytes
+            };
+            serde_json::from_slice::<AnalysisOutput>(&output)
+                .map(|output| output.symbols)
+                .map_err(|e| format!("invalid analyzer output: {}", e))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::codegraph::treesitter::structs::SymbolType;
+
+    struct FixedAnalyzer {
+        manifest: AnalyzerManifest,
+        symbols: Vec<ExternalSymbol>,
+    }
+
+    impl LanguageAnalyzer for FixedAnalyzer {
+        fn manifest(&self) -> &AnalyzerManifest {
+            &self.manifest
+        }
+
+        fn analyze(&self, _path: &Path, _code: &str) -> Result<Vec<ExternalSymbol>, String> {
+            Ok(self.symbols.clone())
+        }
+    }
+
+    fn symbol(kind: ExternalSymbolKind, name: &str, line: usize, end_line: Option<usize>) -> ExternalSymbol {
+        ExternalSymbol { kind, name: name.to_string(), line, end_line }
+    }
+
+    #[test]
+    fn test_external_symbols_nest_by_lines() {
+        let code = "mod pkg\n  fn outer\n    call inner\n    fn inner\n    end\n  end\nend\n";
+        let symbols = vec![
+            symbol(ExternalSymbolKind::Class, "pkg", 1, Some(7)),
+            symbol(ExternalSymbolKind::Function, "outer", 2, Some(6)),
+            symbol(ExternalSymbolKind::Call, "inner", 3, None),
+            symbol(ExternalSymbolKind::Function, "inner", 4, Some(5)),
+        ];
+        let ast = to_ast_symbols(&symbols, code, &PathBuf::from("a.toy"));
+        let fields: Vec<AstSymbolFields> = ast.iter().map(|s| s.read().fields().clone()).collect();
+        assert_eq!(ast[0].read().symbol_type(), SymbolType::StructDeclaration);
+        assert_eq!(ast[2].read().symbol_type(), SymbolType::FunctionCall);
+        assert_eq!(fields[1].parent_guid, Some(fields[0].guid));
+        assert_eq!(fields[2].parent_guid, Some(fields[1].guid));
+        assert_eq!(fields[3].parent_guid, Some(fields[1].guid));
+        assert_eq!(fields[1].childs_guid, vec![fields[2].guid, fields[3].guid]);
+        assert_eq!((fields[1].full_range.start_point.row, fields[1].full_range.end_point.row), (1, 5));
+        assert_eq!(&code[fields[2].full_range.start_byte..fields[2].full_range.end_byte], "    call inner");
+    }
+
+    #[test]
+    fn test_registry_matches_extensions() {
+        let analyzer = FixedAnalyzer {
+            manifest: AnalyzerManifest { name: "registry-test".to_string(), extensions: vec![".RegTest".to_string()] },
+            symbols: vec![],
+        };
+        AnalyzerRegistry::register(Arc::new(analyzer)).unwrap();
+        assert_eq!(AnalyzerRegistry::language_for_path(Path::new("x/y.regtest")).as_deref(), Some("registry-test"));
+        assert!(AnalyzerRegistry::is_registered_language("registry-test"));
+        assert!(AnalyzerRegistry::unregister("registry-test"));
+        assert!(AnalyzerRegistry::for_path(Path::new("x/y.regtest")).is_none());
+
+        assert!(AnalyzerRegistry::register_dynamic("plugin.wasm").unwrap_err().contains("WebAssembly"));
+    }
+
+    #[test]
+    fn test_disabled_language_is_not_parsed() {
+        let analyzer = FixedAnalyzer {
+            manifest: AnalyzerManifest { name: "disable-test".to_string(), extensions: vec!["distest".to_string()] },
+            symbols: vec![symbol(ExternalSymbolKind::Function, "main", 1, Some(1))],
+        };
+        AnalyzerRegistry::register(Arc::new(analyzer)).unwrap();
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("a.distest");
+        std::fs::write(&path, "fn main\n").unwrap();
+        let parser = crate::codegraph::treesitter::TreeSitterParser::new();
+
+        AnalyzerRegistry::set_enabled("disable-test", false);
+        assert_eq!(AnalyzerRegistry::disabled(), vec!["disable-test".to_string()]);
+        assert!(parser.parse_file(&path).unwrap_err().message.contains("disabled"));
+
+        AnalyzerRegistry::set_enabled("disable-test", true);
+        assert_eq!(parser.parse_file(&path).unwrap().len(), 1);
+        AnalyzerRegistry::unregister("disable-test");
+    }
+}
@@ -10,7 +10,7 @@ use tracing::debug;
 
 use crate::codegraph::treesitter::ast_instance_structs::{AstSymbolFields, AstSymbolInstanceArc, ClassFieldDeclaration, CommentDefinition, FunctionArg, FunctionDeclaration, ImportDeclaration, ImportType, StructDeclaration, TypeDef, FunctionCall};
 use crate::codegraph::treesitter::language_id::LanguageId;
-use crate::codegraph::treesitter::parsers::{AstLanguageParser, internal_error, ParserError};
+use crate::codegraph::treesitter::parsers::{AstLanguageParser, internal_error, ParserError, TreeStats};
 use crate::codegraph::treesitter::parsers::utils::{CandidateInfo, get_children_guids, get_guid};
 use crate::codegraph::treesitter::skeletonizer::SkeletonFormatter;
 use crate::codegraph::treesitter::ast_instance_structs::SymbolInformation;
@@ -18,6 +18,7 @@ use crate::codegraph::treesitter::structs::SymbolType;
 
 pub(crate) struct GoParser {
     pub parser: Parser,
+    tree_stats: Option<TreeStats>,
 }
 
 pub struct GoSkeletonFormatter;
@@ -28,7 +29,7 @@ impl GoParser {
         parser
             .set_language(&tree_sitter_go::LANGUAGE.into())
             .map_err(internal_error)?;
-        Ok(GoParser { parser })
+        Ok(GoParser { parser, tree_stats: None })
     }
 
     pub fn parse_struct_declaration<'a>(&mut self, info: &CandidateInfo<'a>, code: &str, candidates: &mut VecDeque<CandidateInfo<'a>>) -> Vec<AstSymbolInstanceArc> {
@@ -51,7 +52,7 @@ impl GoParser {
         }
         
         if full_range == info.node.range() {
-            debug!("anonymous struct: {}", code.slice(info.node.byte_range()).to_string());
+            debug!("anonymous {}: {}", info.node.kind(), code.slice(info.node.byte_range()).to_string());
             return symbols;
         }
         decl.ast_fields.full_range = full_range;
@@ -571,7 +572,8 @@ impl GoParser {
         let kind = info.node.kind();
         
         match kind {
-            "struct_type" => {
+            "struct_type" | "interface_type" => {
+                // Interfaces are type declarations too, as in the Java and TS parsers
                 symbols.extend(self.parse_struct_declaration(info, code, candidates));
             }
             "function_declaration" => {
@@ -734,8 +736,13 @@ impl GoParser {
 impl AstLanguageParser for GoParser {
     fn parse(&mut self, code: &str, path: &PathBuf) -> Vec<AstSymbolInstanceArc> {
         let tree = self.parser.parse(code, None).unwrap();
+        self.tree_stats = Some(TreeStats::of(&tree));
         self.parse_(&tree.root_node(), code, path)
     }
+
+    fn tree_stats(&self) -> Option<TreeStats> {
+        self.tree_stats.clone()
+    }
 }
 
 impl SkeletonFormatter for GoSkeletonFormatter {
@@ -13,11 +13,12 @@ use uuid::Uuid;
 
 use crate::codegraph::treesitter::ast_instance_structs::{AstSymbolFields, AstSymbolInstanceArc, ClassFieldDeclaration, CommentDefinition, FunctionArg, FunctionCall, FunctionDeclaration, ImportDeclaration, ImportType, StructDeclaration, TypeDef, VariableDefinition, VariableUsage};
 use crate::codegraph::treesitter::language_id::LanguageId;
-use crate::codegraph::treesitter::parsers::{AstLanguageParser, internal_error, ParserError};
+use crate::codegraph::treesitter::parsers::{AstLanguageParser, internal_error, ParserError, TreeStats};
 use crate::codegraph::treesitter::parsers::utils::{CandidateInfo, get_guid};
 
 pub(crate) struct JavaParser {
     pub parser: Parser,
+    tree_stats: Option<TreeStats>,
 }
 
 static JAVA_KEYWORDS: [&str; 50] = [
@@ -221,7 +222,7 @@ impl JavaParser {
         parser
             .set_language(&tree_sitter_java::LANGUAGE.into())
             .map_err(internal_error)?;
-        Ok(JavaParser { parser })
+        Ok(JavaParser { parser, tree_stats: None })
     }
 
     pub fn parse_struct_declaration<'a>(
@@ -795,7 +796,12 @@ impl JavaParser {
 impl AstLanguageParser for JavaParser {
     fn parse(&mut self, code: &str, path: &PathBuf) -> Vec<AstSymbolInstanceArc> {
         let tree = self.parser.parse(code, None).unwrap();
+        self.tree_stats = Some(TreeStats::of(&tree));
         let symbols = self.parse_(&tree.root_node(), code, path);
         symbols
     }
+
+    fn tree_stats(&self) -> Option<TreeStats> {
+        self.tree_stats.clone()
+    }
 }
@@ -10,11 +10,12 @@ use uuid::Uuid;
 
 use crate::codegraph::treesitter::ast_instance_structs::{AstSymbolFields, AstSymbolInstanceArc, ClassFieldDeclaration, CommentDefinition, FunctionArg, FunctionCall, FunctionDeclaration, ImportDeclaration, ImportType, StructDeclaration, TypeDef, VariableDefinition, VariableUsage};
 use crate::codegraph::treesitter::language_id::LanguageId;
-use crate::codegraph::treesitter::parsers::{AstLanguageParser, internal_error, ParserError};
+use crate::codegraph::treesitter::parsers::{AstLanguageParser, internal_error, ParserError, TreeStats};
 use crate::codegraph::treesitter::parsers::utils::{CandidateInfo, get_guid};
 
 pub(crate) struct JSParser {
     pub parser: Parser,
+    tree_stats: Option<TreeStats>,
 }
 
 static LAMBDA_KINDS: [&str; 2] = ["function_expression", "arrow_function"];
@@ -143,7 +144,7 @@ impl JSParser {
         parser
             .set_language(&tree_sitter_javascript::LANGUAGE.into())
             .map_err(internal_error)?;
-        Ok(Self { parser })
+        Ok(Self { parser, tree_stats: None })
     }
 
     pub fn parse_struct_declaration<'a>(
@@ -319,8 +320,6 @@ impl JSParser {
             decl.ast_fields.name = code.slice(name.byte_range()).to_string();
         } else if let Some(name) = name_from_var {
             decl.ast_fields.name = name.clone();
-        } else {
-            decl.ast_fields.name = format!("lambda-{}", decl.ast_fields.guid);
         }
 
         if let Some(parameters) = info.node.child_by_field_name("parameters") {
@@ -554,7 +553,7 @@ impl JSParser {
                     symbols.extend(self.parse_variable_definition(info, code, candidates));
                 }
             }
-            "method_definition" | "function_declaration" => {
+            "method_definition" | "function_declaration" | "function_expression" | "arrow_function" => {
                 symbols.extend(self.parse_function_declaration(info, code, candidates, None));
             }
             "call_expression" => {
@@ -789,9 +788,14 @@ impl JSParser {
 impl AstLanguageParser for JSParser {
     fn parse(&mut self, code: &str, path: &PathBuf) -> Vec<AstSymbolInstanceArc> {
         let tree = self.parser.parse(code, None).unwrap();
+        self.tree_stats = Some(TreeStats::of(&tree));
         let symbols = self.parse_(&tree.root_node(), code, path);
         symbols
     }
+
+    fn tree_stats(&self) -> Option<TreeStats> {
+        self.tree_stats.clone()
+    }
 }
 
 
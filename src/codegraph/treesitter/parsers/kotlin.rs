@@ -0,0 +1,251 @@
+use std::collections::{HashMap, VecDeque};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use parking_lot::RwLock;
+use tree_sitter::{Node, Parser, Range};
+use uuid::Uuid;
+
+use crate::codegraph::treesitter::ast_instance_structs::{AstSymbolFields, AstSymbolInstanceArc, CommentDefinition, FunctionArg, FunctionCall, FunctionDeclaration, ImportDeclaration, ImportType, StructDeclaration, TypeDef};
+use crate::codegraph::treesitter::language_id::LanguageId;
+use crate::codegraph::treesitter::parsers::{AstLanguageParser, internal_error, ParserError, TreeStats};
+use crate::codegraph::treesitter::parsers::utils::{CandidateInfo, get_guid};
+
+/// Kotlin analyzer.
+///
+/// Classes, interfaces, enum classes and objects are type declarations whose
+/// member functions become methods; a companion object's functions belong to
+/// the enclosing class. Every `call_expression` is a call named after its last
+/// segment (`repo.find(id)` is `find`), so constructor calls (`UserService()`)
+/// link to the class like Java's `new UserService()`, and calls resolve against
+/// Java functions of the same project.
+pub(crate) struct KotlinAnalyzer {
+    parser: Parser,
+    tree_stats: Option<TreeStats>,
+}
+
+/// Packages of the standard library and the platform
+static SYSTEM_PACKAGES: [&str; 5] = ["kotlin", "kotlinx", "java", "javax", "android"];
+
+impl KotlinAnalyzer {
+    pub fn new() -> Result<Self, ParserError> {
+        let mut parser = Parser::new();
+        parser
+            .set_language(&tree_sitter_kotlin_ng::LANGUAGE.into())
+            .map_err(internal_error)?;
+        Ok(Self { parser, tree_stats: None })
+    }
+}
+
+impl AstLanguageParser for KotlinAnalyzer {
+    fn parse(&mut self, code: &str, path: &PathBuf) -> Vec<AstSymbolInstanceArc> {
+        let tree = self.parser.parse(code, None).unwrap();
+        self.tree_stats = Some(TreeStats::of(&tree));
+        parse_tree(&tree.root_node(), code, path)
+    }
+
+    fn tree_stats(&self) -> Option<TreeStats> {
+        self.tree_stats.clone()
+    }
+}
+
+fn text<'a>(node: &Node, code: &'a str) -> &'a str {
+    code.get(node.byte_range()).unwrap_or_default()
+}
+
+fn child_of_kind<'a>(node: &Node<'a>, kind: &str) -> Option<Node<'a>> {
+    let mut cursor = node.walk();
+    let found = node.children(&mut cursor).find(|child| child.kind() == kind);
+    found
+}
+
+fn new_fields(info: &CandidateInfo, node: &Node) -> AstSymbolFields {
+    let mut fields = AstSymbolFields::from_fields(&info.ast_fields);
+    fields.guid = get_guid();
+    fields.parent_guid = Some(info.parent_guid);
+    fields.full_range = node.range();
+    fields.declaration_range = node.range();
+    fields.definition_range = node.range();
+    fields
+}
+
+/// Range from the start of the declaration to the start of its body
+fn header_range(full: &Range, body: &Node) -> Range {
+    Range {
+        start_byte: full.start_byte,
+        end_byte: body.start_byte(),
+        start_point: full.start_point,
+        end_point: body.start_position(),
+    }
+}
+
+/// `Base<T>` and `pkg.Base` name the type `Base`
+fn user_type(node: &Node, code: &str) -> Option<TypeDef> {
+    let node = if node.kind() == "user_type" { *node } else { child_of_kind(node, "user_type")? };
+    let mut cursor = node.walk();
+    let name = node.children(&mut cursor).filter(|child| child.kind() == "identifier").last()?;
+    Some(TypeDef { name: Some(text(&name, code).to_string()), ..Default::default() })
+}
+
+fn push_children<'a>(node: &Node<'a>, ast_fields: &AstSymbolFields, parent_guid: Uuid, candidates: &mut VecDeque<CandidateInfo<'a>>) {
+    let mut cursor = node.walk();
+    for child in node.children(&mut cursor) {
+        candidates.push_back(CandidateInfo { ast_fields: ast_fields.clone(), node: child, parent_guid });
+    }
+}
+
+fn parse_class<'a>(info: &CandidateInfo<'a>, code: &str, candidates: &mut VecDeque<CandidateInfo<'a>>) -> AstSymbolInstanceArc {
+    let mut decl = StructDeclaration { ast_fields: new_fields(info, &info.node), ..Default::default() };
+    if let Some(name) = info.node.child_by_field_name("name") {
+        decl.ast_fields.name = text(&name, code).to_string();
+    }
+    if let Some(specifiers) = child_of_kind(&info.node, "delegation_specifiers") {
+        let mut cursor = specifiers.walk();
+        for specifier in specifiers.children(&mut cursor).filter(|child| child.kind() == "delegation_specifier") {
+            // The superclass constructor call `Base()` is a call too
+            if let Some(invocation) = child_of_kind(&specifier, "constructor_invocation") {
+                candidates.push_back(CandidateInfo { ast_fields: info.ast_fields.clone(), node: invocation, parent_guid: info.parent_guid });
+            }
+            decl.inherited_types.extend(user_type(&specifier, code).or_else(|| {
+                child_of_kind(&specifier, "constructor_invocation").and_then(|invocation| user_type(&invocation, code))
+            }));
+        }
+    }
+    let body = child_of_kind(&info.node, "class_body").or_else(|| child_of_kind(&info.node, "enum_class_body"));
+    if let Some(body) = body {
+        decl.ast_fields.declaration_range = header_range(&decl.ast_fields.full_range, &body);
+        decl.ast_fields.definition_range = body.range();
+        push_children(&body, &info.ast_fields, decl.ast_fields.guid, candidates);
+    }
+    Arc::new(RwLock::new(Box::new(decl)))
+}
+
+fn parse_function<'a>(info: &CandidateInfo<'a>, code: &str, candidates: &mut VecDeque<CandidateInfo<'a>>) -> AstSymbolInstanceArc {
+    let mut decl = FunctionDeclaration { ast_fields: new_fields(info, &info.node), ..Default::default() };
+    if let Some(name) = info.node.child_by_field_name("name") {
+        decl.ast_fields.name = text(&name, code).to_string();
+    }
+    if let Some(parameters) = child_of_kind(&info.node, "function_value_parameters") {
+        let mut cursor = parameters.walk();
+        for parameter in parameters.children(&mut cursor).filter(|child| child.kind() == "parameter") {
+            let mut arg = FunctionArg::default();
+            if let Some(name) = child_of_kind(&parameter, "identifier") {
+                arg.name = text(&name, code).to_string();
+            }
+            arg.type_ = user_type(&parameter, code);
+            decl.args.push(arg);
+        }
+    }
+    // Interface and abstract methods have no body and keep the full range as their declaration
+    if let Some(body) = child_of_kind(&info.node, "function_body") {
+        decl.ast_fields.declaration_range = header_range(&decl.ast_fields.full_range, &body);
+        decl.ast_fields.definition_range = body.range();
+        push_children(&body, &info.ast_fields, decl.ast_fields.guid, candidates);
+    }
+    Arc::new(RwLock::new(Box::new(decl)))
+}
+
+fn parse_call<'a>(info: &CandidateInfo<'a>, code: &str, candidates: &mut VecDeque<CandidateInfo<'a>>) -> Option<AstSymbolInstanceArc> {
+    let mut decl = FunctionCall { ast_fields: new_fields(info, &info.node), ..Default::default() };
+    if let Some(caller_guid) = info.ast_fields.caller_guid {
+        decl.ast_fields.guid = caller_guid;
+    }
+    decl.ast_fields.caller_guid = Some(get_guid());
+
+    let mut arguments_fields = info.ast_fields.clone();
+    arguments_fields.caller_guid = None;
+    let mut cursor = info.node.walk();
+    let children: Vec<Node> = info.node.named_children(&mut cursor).collect();
+    let (callee, rest) = children.split_first()?;
+    match callee.kind() {
+        "identifier" => decl.ast_fields.name = text(callee, code).to_string(),
+        "user_type" => decl.ast_fields.name = user_type(callee, code)?.name?,
+        "navigation_expression" => {
+            let mut cursor = callee.walk();
+            let parts: Vec<Node> = callee.named_children(&mut cursor).collect();
+            let (name, receiver) = parts.split_last()?;
+            decl.ast_fields.name = text(name, code).to_string();
+            // Calls in the receiver are chained to this call through `caller_guid`
+            let mut receiver_fields = info.ast_fields.clone();
+            receiver_fields.caller_guid = decl.ast_fields.caller_guid;
+            for node in receiver {
+                candidates.push_back(CandidateInfo { ast_fields: receiver_fields.clone(), node: *node, parent_guid: info.parent_guid });
+            }
+        }
+        _ => candidates.push_back(CandidateInfo { ast_fields: arguments_fields.clone(), node: *callee, parent_guid: info.parent_guid }),
+    }
+    for node in rest {
+        candidates.push_back(CandidateInfo { ast_fields: arguments_fields.clone(), node: *node, parent_guid: info.parent_guid });
+    }
+    if decl.ast_fields.name.is_empty() {
+        return None;
+    }
+    Some(Arc::new(RwLock::new(Box::new(decl))))
+}
+
+fn parse_import(info: &CandidateInfo, code: &str) -> Option<AstSymbolInstanceArc> {
+    let path = child_of_kind(&info.node, "qualified_identifier")?;
+    let mut def = ImportDeclaration { ast_fields: new_fields(info, &info.node), ..Default::default() };
+    def.path_components = text(&path, code).split('.').map(|part| part.trim().to_string()).collect();
+    // `import kotlin.math.max as maximum`
+    if let Some(alias) = child_of_kind(&info.node, "identifier") {
+        def.alias = Some(text(&alias, code).to_string());
+    }
+    if def.path_components.first().is_some_and(|first| SYSTEM_PACKAGES.contains(&first.as_str())) {
+        def.import_type = ImportType::System;
+    }
+    def.ast_fields.name = def.alias.clone().or_else(|| def.path_components.last().cloned()).unwrap_or_default();
+    Some(Arc::new(RwLock::new(Box::new(def))))
+}
+
+fn parse_usages<'a>(info: &CandidateInfo<'a>, code: &str, candidates: &mut VecDeque<CandidateInfo<'a>>) -> Vec<AstSymbolInstanceArc> {
+    let mut symbols: Vec<AstSymbolInstanceArc> = vec![];
+    match info.node.kind() {
+        "class_declaration" | "object_declaration" => symbols.push(parse_class(info, code, candidates)),
+        // Functions of a companion object belong to the enclosing class
+        "companion_object" => {
+            if let Some(body) = child_of_kind(&info.node, "class_body") {
+                push_children(&body, &info.ast_fields, info.parent_guid, candidates);
+            }
+        }
+        "function_declaration" => symbols.push(parse_function(info, code, candidates)),
+        "call_expression" | "constructor_invocation" => symbols.extend(parse_call(info, code, candidates)),
+        "import" => symbols.extend(parse_import(info, code)),
+        "line_comment" | "block_comment" => {
+            let def = CommentDefinition { ast_fields: new_fields(info, &info.node) };
+            symbols.push(Arc::new(RwLock::new(Box::new(def))));
+        }
+        "package_header" => {}
+        "ERROR" => {
+            let mut ast_fields = info.ast_fields.clone();
+            ast_fields.is_error = true;
+            push_children(&info.node, &ast_fields, info.parent_guid, candidates);
+        }
+        _ => push_children(&info.node, &info.ast_fields, info.parent_guid, candidates),
+    }
+    symbols
+}
+
+fn parse_tree(root: &Node, code: &str, path: &Path) -> Vec<AstSymbolInstanceArc> {
+    let mut symbols: Vec<AstSymbolInstanceArc> = vec![];
+    let mut candidates = VecDeque::from(vec![CandidateInfo {
+        ast_fields: AstSymbolFields::from_data(LanguageId::Kotlin, path.to_path_buf(), false),
+        node: *root,
+        parent_guid: get_guid(),
+    }]);
+    while let Some(candidate) = candidates.pop_front() {
+        symbols.extend(parse_usages(&candidate, code, &mut candidates));
+    }
+
+    let guid_to_symbol: HashMap<Uuid, AstSymbolInstanceArc> = symbols.iter().map(|s| (*s.read().guid(), s.clone())).collect();
+    for symbol in &symbols {
+        let (guid, parent_guid) = {
+            let symbol = symbol.read();
+            (*symbol.guid(), *symbol.parent_guid())
+        };
+        if let Some(parent) = parent_guid.and_then(|parent_guid| guid_to_symbol.get(&parent_guid)) {
+            parent.write().fields_mut().childs_guid.push(guid);
+        }
+    }
+    symbols
+}
@@ -0,0 +1,427 @@
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use parking_lot::RwLock;
+use tree_sitter::{Point, Range};
+use uuid::Uuid;
+
+use crate::codegraph::treesitter::ast_instance_structs::{AstSymbolFields, AstSymbolInstanceArc, CommentDefinition, FunctionArg, FunctionCall, FunctionDeclaration, ImportDeclaration, ImportType};
+use crate::codegraph::treesitter::language_id::LanguageId;
+use crate::codegraph::treesitter::parsers::{AstLanguageParser, ParserError};
+use crate::codegraph::treesitter::parsers::utils::get_guid;
+
+/// Lua parser.
+///
+/// No Lua grammar is bundled, and Lua's syntax is small enough to scan directly:
+/// the source is tokenized, `function ... end` blocks are matched through the
+/// block keywords, and calls are recognized as a name path followed by `(`, a
+/// string or a table constructor. Calls through a `require`d module
+/// (`local utils = require("game.utils")` then `utils.clamp(x)`) are named
+/// `utils.clamp`, so they resolve against functions of the `utils` module.
+pub(crate) struct LuaParser;
+
+impl LuaParser {
+    pub fn new() -> Result<Self, ParserError> {
+        Ok(Self)
+    }
+}
+
+impl AstLanguageParser for LuaParser {
+    fn parse(&mut self, code: &str, path: &PathBuf) -> Vec<AstSymbolInstanceArc> {
+        Scanner::new(code, path).scan()
+    }
+}
+
+static LUA_KEYWORDS: [&str; 22] = [
+    "and", "break", "do", "else", "elseif", "end", "false", "for", "function", "goto", "if", "in",
+    "local", "nil", "not", "or", "repeat", "return", "then", "true", "until", "while",
+];
+
+/// Standard library tables; calls through them keep the table name so they do not
+/// resolve to project functions that happen to share the field name
+static LUA_STDLIB: [&str; 10] = [
+    "string", "table", "math", "os", "io", "coroutine", "debug", "utf8", "package", "bit32",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum TokenKind {
+    Name,
+    Keyword,
+    Str,
+    Number,
+    Symbol,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Token<'a> {
+    kind: TokenKind,
+    text: &'a str,
+    start: usize,
+    end: usize,
+}
+
+impl Token<'_> {
+    fn is(&self, kind: TokenKind, text: &str) -> bool {
+        self.kind == kind && self.text == text
+    }
+}
+
+/// Level of a long bracket opener (`[[` is 0, `[==[` is 2) starting at `i`
+fn long_bracket(b: &[u8], i: usize) -> Option<usize> {
+    if b.get(i) != Some(&b'[') {
+        return None;
+    }
+    let level = b[i + 1..].iter().take_while(|&&c| c == b'=').count();
+    (b.get(i + 1 + level) == Some(&b'[')).then_some(level)
+}
+
+/// Index right after the long bracket closer of the given level, searching from `i`
+fn long_bracket_end(b: &[u8], i: usize, level: usize) -> usize {
+    let mut closer = vec![b']'];
+    closer.extend(std::iter::repeat_n(b'=', level));
+    closer.push(b']');
+    b[i..].windows(closer.len()).position(|w| w == closer.as_slice()).map_or(b.len(), |p| i + p + closer.len())
+}
+
+/// Splits Lua source into tokens, returning comment spans separately
+fn tokenize(code: &str) -> (Vec<Token<'_>>, Vec<(usize, usize)>) {
+    const SYMBOLS: [&str; 10] = ["...", "..", "==", "~=", "<=", ">=", "::", "//", "<<", ">>"];
+    let b = code.as_bytes();
+    let mut tokens = Vec::new();
+    let mut comments = Vec::new();
+    let mut i = 0;
+    while i < b.len() {
+        let c = b[i];
+        let start = i;
+        let kind = if c.is_ascii_whitespace() {
+            i += 1;
+            continue;
+        } else if code[i..].starts_with("--") {
+            i = match long_bracket(b, i + 2) {
+                Some(level) => long_bracket_end(b, i + 2, level),
+                None => b[i..].iter().position(|&c| c == b'\n').map_or(b.len(), |p| i + p),
+            };
+            comments.push((start, i));
+            continue;
+        } else if let Some(level) = long_bracket(b, i) {
+            i = long_bracket_end(b, i, level);
+            TokenKind::Str
+        } else if c == b'"' || c == b'\'' {
+            i += 1;
+            while i < b.len() && b[i] != c && b[i] != b'\n' {
+                i += if b[i] == b'\\' { 2 } else { 1 };
+            }
+            i = (i + 1).min(b.len());
+            TokenKind::Str
+        } else if c.is_ascii_digit() || (c == b'.' && b.get(i + 1).is_some_and(u8::is_ascii_digit)) {
+            while i < b.len() {
+                let d = b[i];
+                let exponent_sign = (d == b'+' || d == b'-') && matches!(b[i - 1], b'e' | b'E' | b'p' | b'P');
+                if d.is_ascii_alphanumeric() || d == b'.' || exponent_sign {
+                    i += 1;
+                } else {
+                    break;
+                }
+            }
+            TokenKind::Number
+        } else if c.is_ascii_alphabetic() || c == b'_' {
+            while i < b.len() && (b[i].is_ascii_alphanumeric() || b[i] == b'_') {
+                i += 1;
+            }
+            if LUA_KEYWORDS.contains(&&code[start..i]) { TokenKind::Keyword } else { TokenKind::Name }
+        } else {
+            i += SYMBOLS.iter().find(|s| code[i..].starts_with(**s)).map_or_else(
+                || code[i..].chars().next().map_or(1, char::len_utf8),
+                |s| s.len(),
+            );
+            TokenKind::Symbol
+        };
+        tokens.push(Token { kind, text: &code[start..i], start, end: i });
+    }
+    (tokens, comments)
+}
+
+/// Last segment of a module path: `game.utils` -> `utils`, `game/utils/init` -> `utils`
+pub(crate) fn module_tail(module: &str) -> &str {
+    let mut segments = module.rsplit(['.', '/']).filter(|s| !s.is_empty());
+    match segments.next() {
+        Some("init") => segments.next().unwrap_or("init"),
+        Some(last) => last,
+        None => module,
+    }
+}
+
+enum Block {
+    /// Index into `Scanner::functions`, `None` for anonymous functions
+    Function(Option<usize>),
+    Other,
+}
+
+struct Scanner<'a> {
+    code: &'a str,
+    path: &'a PathBuf,
+    line_starts: Vec<usize>,
+    root_guid: Uuid,
+    functions: Vec<FunctionDeclaration>,
+    calls: Vec<FunctionCall>,
+    imports: Vec<ImportDeclaration>,
+    /// local alias -> module name of `local x = require("...")`
+    aliases: HashMap<&'a str, &'a str>,
+}
+
+impl<'a> Scanner<'a> {
+    fn new(code: &'a str, path: &'a PathBuf) -> Self {
+        let line_starts = std::iter::once(0)
+            .chain(code.bytes().enumerate().filter(|(_, c)| *c == b'\n').map(|(i, _)| i + 1))
+            .collect();
+        Self {
+            code,
+            path,
+            line_starts,
+            root_guid: get_guid(),
+            functions: vec![],
+            calls: vec![],
+            imports: vec![],
+            aliases: HashMap::new(),
+        }
+    }
+
+    fn point(&self, byte: usize) -> Point {
+        let row = self.line_starts.partition_point(|&start| start <= byte) - 1;
+        Point { row, column: byte - self.line_starts[row] }
+    }
+
+    fn range(&self, start: usize, end: usize) -> Range {
+        Range { start_byte: start, end_byte: end, start_point: self.point(start), end_point: self.point(end) }
+    }
+
+    fn fields(&self, name: &str, start: usize, end: usize, parent: Option<Uuid>) -> AstSymbolFields {
+        let mut fields = AstSymbolFields::from_data(LanguageId::Lua, self.path.clone(), false);
+        fields.guid = get_guid();
+        fields.name = name.to_string();
+        fields.parent_guid = Some(parent.unwrap_or(self.root_guid));
+        fields.full_range = self.range(start, end);
+        fields.declaration_range = fields.full_range;
+        fields.definition_range = fields.full_range;
+        fields
+    }
+
+    fn scan(mut self) -> Vec<AstSymbolInstanceArc> {
+        let (tokens, comments) = tokenize(self.code);
+        let mut blocks: Vec<Block> = vec![];
+
+        for (i, token) in tokens.iter().enumerate() {
+            let enclosing = blocks.iter().rev().find_map(|b| match b {
+                Block::Function(Some(index)) => Some(self.functions[*index].ast_fields.guid),
+                _ => None,
+            });
+            match (token.kind, token.text) {
+                (TokenKind::Keyword, "function") => {
+                    let index = self.function_declaration(&tokens, i, enclosing);
+                    blocks.push(Block::Function(index));
+                }
+                (TokenKind::Keyword, "do" | "if" | "repeat") => blocks.push(Block::Other),
+                (TokenKind::Keyword, "end" | "until") => {
+                    if let Some(Block::Function(Some(index))) = blocks.pop() {
+                        let start = self.functions[index].ast_fields.full_range.start_byte;
+                        let body_start = self.functions[index].ast_fields.declaration_range.end_byte;
+                        self.functions[index].ast_fields.full_range = self.range(start, token.end);
+                        self.functions[index].ast_fields.definition_range = self.range(body_start, token.end);
+                    }
+                }
+                (TokenKind::Str, _) | (TokenKind::Symbol, "(" | "{") => self.call(&tokens, i, enclosing),
+                _ => {}
+            }
+        }
+
+        let comment_ranges: Vec<Range> = comments.iter().map(|&(start, end)| self.range(start, end)).collect();
+        let mut symbols: Vec<AstSymbolInstanceArc> = vec![];
+        let mut children: HashMap<Uuid, Vec<Uuid>> = HashMap::new();
+        for fields in self.calls.iter().map(|c| &c.ast_fields).chain(self.functions.iter().map(|f| &f.ast_fields)) {
+            if let Some(parent) = fields.parent_guid {
+                children.entry(parent).or_default().push(fields.guid);
+            }
+        }
+        for mut function in self.functions {
+            function.ast_fields.childs_guid = children.remove(&function.ast_fields.guid).unwrap_or_default();
+            symbols.push(Arc::new(RwLock::new(Box::new(function))));
+        }
+        for call in self.calls {
+            symbols.push(Arc::new(RwLock::new(Box::new(call))));
+        }
+        for import in self.imports {
+            symbols.push(Arc::new(RwLock::new(Box::new(import))));
+        }
+        for range in comment_ranges {
+            let mut ast_fields = AstSymbolFields::from_data(LanguageId::Lua, self.path.clone(), false);
+            ast_fields.guid = get_guid();
+            ast_fields.parent_guid = Some(self.root_guid);
+            ast_fields.full_range = range;
+            let comment = CommentDefinition { ast_fields };
+            symbols.push(Arc::new(RwLock::new(Box::new(comment))));
+        }
+        symbols
+    }
+
+    /// Start index of the `a.b:c` name path ending at token `last`
+    fn path_start(tokens: &[Token], last: usize) -> usize {
+        let mut start = last;
+        while start >= 2
+            && (tokens[start - 1].is(TokenKind::Symbol, ".") || tokens[start - 1].is(TokenKind::Symbol, ":"))
+            && tokens[start - 2].kind == TokenKind::Name
+        {
+            start -= 2;
+        }
+        start
+    }
+
+    /// `function a.b:name(...)`, `local function name(...)` and `name = function(...)`;
+    /// returns the index of the recorded declaration, `None` for anonymous functions
+    fn function_declaration(&mut self, tokens: &[Token<'a>], i: usize, parent: Option<Uuid>) -> Option<usize> {
+        let (name, mut start, mut params) = if tokens.get(i + 1).is_some_and(|t| t.kind == TokenKind::Name) {
+            let mut j = i + 1;
+            while tokens.get(j + 2).is_some_and(|t| t.kind == TokenKind::Name)
+                && (tokens[j + 1].is(TokenKind::Symbol, ".") || tokens[j + 1].is(TokenKind::Symbol, ":"))
+            {
+                j += 2;
+            }
+            (tokens[j].text, i, j + 1)
+        } else if i >= 2 && tokens[i - 1].is(TokenKind::Symbol, "=") && tokens[i - 2].kind == TokenKind::Name {
+            (tokens[i - 2].text, Self::path_start(tokens, i - 2), i + 1)
+        } else {
+            return None;
+        };
+        if start > 0 && tokens[start - 1].is(TokenKind::Keyword, "local") {
+            start -= 1;
+        }
+
+        let mut args = vec![];
+        let mut header_end = tokens[params.saturating_sub(1).max(i)].end;
+        if tokens.get(params).is_some_and(|t| t.is(TokenKind::Symbol, "(")) {
+            params += 1;
+            while let Some(token) = tokens.get(params) {
+                header_end = token.end;
+                if token.is(TokenKind::Symbol, ")") {
+                    break;
+                }
+                if token.kind == TokenKind::Name || token.text == "..." {
+                    args.push(FunctionArg { name: token.text.to_string(), type_: None });
+                }
+                params += 1;
+            }
+        }
+
+        let start_byte = tokens[start].start;
+        let declaration = FunctionDeclaration {
+            ast_fields: self.fields(name, start_byte, header_end, parent),
+            args,
+            ..Default::default()
+        };
+        self.functions.push(declaration);
+        Some(self.functions.len() - 1)
+    }
+
+    /// A name path followed by `(`, a string or `{` is a call; `require` calls are imports
+    fn call(&mut self, tokens: &[Token<'a>], i: usize, parent: Option<Uuid>) {
+        if i == 0 || tokens[i - 1].kind != TokenKind::Name {
+            return;
+        }
+        let last = i - 1;
+        let start = Self::path_start(tokens, last);
+        if start > 0 && tokens[start - 1].is(TokenKind::Keyword, "function") {
+            return;
+        }
+        let segments: Vec<&str> = (start..=last).step_by(2).map(|k| tokens[k].text).collect();
+        let method_call = last > start && tokens[last - 1].text == ":";
+
+        if segments == ["require"] {
+            self.require(tokens, i, start);
+            return;
+        }
+        let name = match segments.as_slice() {
+            [table, field] if !method_call => {
+                if let Some(module) = self.aliases.get(table) {
+                    format!("{}.{}", module_tail(module), field)
+                } else if LUA_STDLIB.contains(table) {
+                    format!("{}.{}", table, field)
+                } else {
+                    field.to_string()
+                }
+            }
+            _ => segments[segments.len() - 1].to_string(),
+        };
+        let end = Self::arguments_end(tokens, i);
+        let call = FunctionCall {
+            ast_fields: self.fields(&name, tokens[start].start, end, parent),
+            ..Default::default()
+        };
+        self.calls.push(call);
+    }
+
+    fn require(&mut self, tokens: &[Token<'a>], i: usize, start: usize) {
+        let module_token = if tokens[i].kind == TokenKind::Str { tokens.get(i) } else { tokens.get(i + 1) };
+        let Some(module) = module_token.filter(|t| t.kind == TokenKind::Str).map(|t| unquote(t.text)) else {
+            return;
+        };
+        let alias = (start >= 2 && tokens[start - 1].is(TokenKind::Symbol, "=") && tokens[start - 2].kind == TokenKind::Name)
+            .then(|| tokens[start - 2].text);
+        if let Some(alias) = alias {
+            self.aliases.insert(alias, module);
+        }
+        let import = ImportDeclaration {
+            ast_fields: self.fields(alias.unwrap_or(module_tail(module)), tokens[start].start, Self::arguments_end(tokens, i), None),
+            path_components: module.split(['.', '/']).map(str::to_string).collect(),
+            alias: alias.map(str::to_string),
+            import_type: ImportType::UserModule,
+            ..Default::default()
+        };
+        self.imports.push(import);
+    }
+
+    /// End byte of the argument list opened at token `i`
+    fn arguments_end(tokens: &[Token], i: usize) -> usize {
+        let (open, close) = match tokens[i].text {
+            "(" => ("(", ")"),
+            "{" => ("{", "}"),
+            _ => return tokens[i].end,
+        };
+        let mut depth = 0;
+        for token in &tokens[i..] {
+            if token.kind != TokenKind::Symbol {
+                continue;
+            }
+            if token.text == open {
+                depth += 1;
+            } else if token.text == close {
+                depth -= 1;
+                if depth == 0 {
+                    return token.end;
+                }
+            }
+        }
+        tokens[i].end
+    }
+}
+
+/// Contents of a string token: `"a.b"`, `'a.b'` or `[[a.b]]`
+fn unquote(text: &str) -> &str {
+    if let Some(rest) = text.strip_prefix('[') {
+        let level = rest.bytes().take_while(|&c| c == b'=').count();
+        text.get(level + 2..text.len().saturating_sub(level + 2)).unwrap_or("")
+    } else {
+        text.get(1..text.len().saturating_sub(1)).unwrap_or("")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_module_tail() {
+        assert_eq!(module_tail("game.utils"), "utils");
+        assert_eq!(module_tail("game/utils/init"), "utils");
+        assert_eq!(module_tail("json"), "json");
+    }
+}
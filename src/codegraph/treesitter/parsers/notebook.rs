@@ -0,0 +1,156 @@
+use std::path::PathBuf;
+
+use serde_json::Value;
+use tracing::warn;
+
+use crate::codegraph::treesitter::ast_instance_structs::AstSymbolInstanceArc;
+use crate::codegraph::treesitter::parsers::python::PythonParser;
+use crate::codegraph::treesitter::parsers::{AstLanguageParser, ParserError, TreeStats};
+
+/// Jupyter notebook (`.ipynb`) parser.
+///
+/// The code cells are joined, in order, into one Python script that is handed to
+/// `PythonParser`. IPython magics and shell escapes (`%timeit`, `!pip install`,
+/// `df?`) become `pass` and cells starting with a cell magic (`%%bash`) are
+/// blanked, so they neither break the parse nor show up as calls; every line keeps
+/// its place. Symbol ranges therefore point into that script, not into the JSON
+/// file, and [`NotebookCells::locate`] maps a script line back to its cell.
+pub(crate) struct NotebookParser {
+    python: PythonParser,
+}
+
+impl NotebookParser {
+    pub fn new() -> Result<Self, ParserError> {
+        Ok(Self { python: PythonParser::new()? })
+    }
+}
+
+impl AstLanguageParser for NotebookParser {
+    fn parse(&mut self, code: &str, path: &PathBuf) -> Vec<AstSymbolInstanceArc> {
+        match NotebookCells::parse(code) {
+            Ok(cells) => self.python.parse(&cells.script(), path),
+            Err(e) => {
+                warn!("Skipping notebook {}: {}", path.display(), e);
+                vec![]
+            }
+        }
+    }
+
+    fn tree_stats(&self) -> Option<TreeStats> {
+        self.python.tree_stats()
+    }
+}
+
+/// The Python code cells of a notebook
+#[derive(Debug, Clone, Default)]
+pub struct NotebookCells {
+    cells: Vec<CodeCell>,
+}
+
+#[derive(Debug, Clone)]
+struct CodeCell {
+    /// Position in the notebook's `cells`, markdown cells included
+    index: usize,
+    /// 1-based line of the script the cell starts on
+    first_line: usize,
+    line_count: usize,
+    source: String,
+}
+
+impl NotebookCells {
+    /// Reads the code cells of an nbformat 4 notebook. Notebooks whose kernel is not
+    /// Python have no cells.
+    pub fn parse(json: &str) -> Result<Self, String> {
+        let notebook: Value = serde_json::from_str(json).map_err(|e| format!("invalid notebook JSON: {}", e))?;
+        let metadata = &notebook["metadata"];
+        let language = metadata["kernelspec"]["language"].as_str().or(metadata["language_info"]["name"].as_str());
+        if language.is_some_and(|language| !language.eq_ignore_ascii_case("python")) {
+            return Ok(Self::default());
+        }
+        let cells = notebook["cells"].as_array().ok_or("no `cells` array (only nbformat 4 is supported)")?;
+
+        let mut code_cells = vec![];
+        let mut first_line = 1;
+        for (index, cell) in cells.iter().enumerate() {
+            if cell["cell_type"] != "code" {
+                continue;
+            }
+            // `source` is a list of lines or a single string
+            let source = match &cell["source"] {
+                Value::String(source) => source.clone(),
+                Value::Array(lines) => lines.iter().filter_map(Value::as_str).collect(),
+                _ => String::new(),
+            };
+            let line_count = source.lines().count();
+            code_cells.push(CodeCell { index, first_line, line_count, source });
+            first_line += line_count;
+        }
+        Ok(Self { cells: code_cells })
+    }
+
+    /// The code cells as one Python script
+    pub fn script(&self) -> String {
+        let mut script = String::new();
+        for cell in &self.cells {
+            let cell_magic = cell.source.trim_start().starts_with("%%");
+            for line in cell.source.lines() {
+                let trimmed = line.trim_start();
+                if cell_magic {
+                    // keep the line, drop the content
+                } else if trimmed.starts_with('%') || trimmed.starts_with('!') || (trimmed.ends_with('?') && !trimmed.starts_with('#')) {
+                    script.push_str(&line[..line.len() - trimmed.len()]);
+                    script.push_str("pass");
+                } else {
+                    script.push_str(line);
+                }
+                script.push('\n');
+            }
+        }
+        script
+    }
+
+    /// Cell index and 1-based line within the cell of a script line
+    pub fn locate(&self, line: usize) -> Option<(usize, usize)> {
+        self.cells
+            .iter()
+            .find(|cell| line >= cell.first_line && line < cell.first_line + cell.line_count)
+            .map(|cell| (cell.index, line - cell.first_line + 1))
+    }
+
+    /// Source of the code cell at `index` in the notebook
+    pub fn cell_source(&self, index: usize) -> Option<&str> {
+        self.cells.iter().find(|cell| cell.index == index).map(|cell| cell.source.as_str())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_notebook_cells_map_to_script_lines() {
+        let notebook = serde_json::json!({
+            "metadata": {"kernelspec": {"language": "python", "name": "python3"}},
+            "nbformat": 4,
+            "cells": [
+                {"cell_type": "markdown", "source": ["# Analysis\n"]},
+                {"cell_type": "code", "source": ["%matplotlib inline\n", "import pandas as pd\n"]},
+                {"cell_type": "code", "source": "def load(path):\n    !echo loading\n    return pd.read_csv(path)"},
+                {"cell_type": "code", "source": ["%%bash\n", "ls data/\n"]}
+            ]
+        });
+        let cells = NotebookCells::parse(&notebook.to_string()).unwrap();
+        assert_eq!(
+            cells.script(),
+            "pass\nimport pandas as pd\ndef load(path):\n    pass\n    return pd.read_csv(path)\n\n\n"
+        );
+        assert_eq!(cells.locate(2), Some((1, 2)));
+        assert_eq!(cells.locate(3), Some((2, 1)));
+        assert_eq!(cells.locate(7), Some((3, 2)));
+        assert_eq!(cells.locate(8), None);
+        assert_eq!(cells.cell_source(3), Some("%%bash\nls data/\n"));
+
+        let r = serde_json::json!({"metadata": {"kernelspec": {"language": "R"}}, "cells": [{"cell_type": "code", "source": "f <- function() 1"}]});
+        assert!(NotebookCells::parse(&r.to_string()).unwrap().script().is_empty());
+    }
+}
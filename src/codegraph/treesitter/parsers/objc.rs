@@ -0,0 +1,671 @@
+use std::path::PathBuf;
+
+use tree_sitter::{Point, Range};
+
+use crate::codegraph::treesitter::ast_instance_structs::AstSymbolInstanceArc;
+use crate::codegraph::treesitter::language_id::LanguageId;
+use crate::codegraph::treesitter::parsers::cpp::CppParser;
+use crate::codegraph::treesitter::parsers::{AstLanguageParser, ParserError, TreeStats};
+
+/// Objective-C / Objective-C++ parser.
+///
+/// There is no Objective-C grammar in this build, so the source is rewritten into
+/// C++ that tree-sitter-cpp understands and then handed to `CppParser`:
+/// `@interface`/`@implementation` become classes, method definitions become member
+/// functions named after their selector (`tableView:cellForRowAtIndexPath:` turns
+/// into `tableView_cellForRowAtIndexPath`) and message sends become member calls
+/// with the same name. The rewrite never adds or removes lines, so line numbers
+/// of the produced symbols still point into the original file.
+pub(crate) struct ObjCParser {
+    cpp: CppParser,
+}
+
+impl ObjCParser {
+    pub fn new() -> Result<Self, ParserError> {
+        Ok(Self { cpp: CppParser::new()? })
+    }
+}
+
+impl AstLanguageParser for ObjCParser {
+    fn parse(&mut self, code: &str, path: &PathBuf) -> Vec<AstSymbolInstanceArc> {
+        let translated = translate(code);
+        let symbols = self.cpp.parse(&translated, path);
+        let lines = line_spans(code);
+        for symbol in &symbols {
+            let mut symbol = symbol.write();
+            let fields = symbol.fields_mut();
+            fields.language = LanguageId::ObjectiveC;
+            for range in [&mut fields.full_range, &mut fields.declaration_range, &mut fields.definition_range] {
+                *range = to_original(range, &lines);
+            }
+        }
+        symbols
+    }
+
+    fn tree_stats(&self) -> Option<TreeStats> {
+        self.cpp.tree_stats()
+    }
+}
+
+/// Start byte and length of every line of `code`
+fn line_spans(code: &str) -> Vec<(usize, usize)> {
+    let mut start = 0;
+    code.split('\n')
+        .map(|line| {
+            let span = (start, line.len());
+            start += line.len() + 1;
+            span
+        })
+        .collect()
+}
+
+/// Moves a range of the translated code onto the original source. The rewrite keeps
+/// lines, so rows stay the same; byte offsets are rebuilt from the original line
+/// starts, with columns clamped to the original line
+fn to_original(range: &Range, lines: &[(usize, usize)]) -> Range {
+    let point = |point: Point| match lines.get(point.row) {
+        Some(&(start, len)) => {
+            let column = point.column.min(len);
+            (start + column, Point { row: point.row, column })
+        }
+        None => {
+            let end = lines.last().map_or(0, |&(start, len)| start + len);
+            (end, point)
+        }
+    };
+    let (start_byte, start_point) = point(range.start_point);
+    let (end_byte, end_point) = point(range.end_point);
+    Range { start_byte, end_byte: end_byte.max(start_byte), start_point, end_point }
+}
+
+/// Rewrites Objective-C source into C++ with the same number of lines
+pub(crate) fn translate(code: &str) -> String {
+    let code = rewrite_literals(code);
+    let code = rewrite_directives(&code);
+    let code = rewrite_sends(&code);
+    rewrite_blocks(&code)
+}
+
+fn is_ident_byte(b: u8) -> bool {
+    b.is_ascii_alphanumeric() || b == b'_'
+}
+
+/// If a string, char literal or comment starts at `i`, returns the index right after it
+fn skip_literal(b: &[u8], i: usize) -> Option<usize> {
+    match b[i] {
+        quote @ (b'"' | b'\'') => {
+            let mut j = i + 1;
+            while j < b.len() && b[j] != quote && b[j] != b'\n' {
+                j += if b[j] == b'\\' { 2 } else { 1 };
+            }
+            Some((j + 1).min(b.len()))
+        }
+        b'/' if b.get(i + 1) == Some(&b'/') => {
+            Some(b[i..].iter().position(|&c| c == b'\n').map_or(b.len(), |p| i + p))
+        }
+        b'/' if b.get(i + 1) == Some(&b'*') => {
+            let end = b[i + 2..].windows(2).position(|w| w == b"*/");
+            Some(end.map_or(b.len(), |p| i + 2 + p + 2))
+        }
+        _ => None,
+    }
+}
+
+/// Index of the bracket closing the one at `open`, skipping literals and comments
+fn matching_close(b: &[u8], open: usize) -> Option<usize> {
+    let mut depth = 0usize;
+    let mut i = open;
+    while i < b.len() {
+        if let Some(end) = skip_literal(b, i) {
+            i = end;
+            continue;
+        }
+        match b[i] {
+            b'(' | b'[' | b'{' => depth += 1,
+            b')' | b']' | b'}' => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(i);
+                }
+            }
+            _ => {}
+        }
+        i += 1;
+    }
+    None
+}
+
+/// Last non-whitespace byte before `i` together with the word ending there (if any)
+fn previous_token(b: &[u8], i: usize) -> (Option<u8>, &str) {
+    let mut j = i;
+    while j > 0 && b[j - 1].is_ascii_whitespace() {
+        j -= 1;
+    }
+    if j == 0 {
+        return (None, "");
+    }
+    let mut start = j;
+    while start > 0 && is_ident_byte(b[start - 1]) {
+        start -= 1;
+    }
+    (Some(b[j - 1]), std::str::from_utf8(&b[start..j]).unwrap_or(""))
+}
+
+/// Pads `replacement` with the newlines of `original` it lost
+fn keep_lines(replacement: String, original: &str) -> String {
+    let missing = original.matches('\n').count().saturating_sub(replacement.matches('\n').count());
+    replacement + &"\n".repeat(missing)
+}
+
+/// Boxed and collection literals: `@"x"`, `@[...]`, `@{...}`, `@(...)`, `@42`, `@YES`, `@selector(...)`
+fn rewrite_literals(code: &str) -> String {
+    let b = code.as_bytes();
+    let mut out = String::with_capacity(code.len());
+    let mut last = 0;
+    let mut i = 0;
+    while i < b.len() {
+        if b[i] != b'@' {
+            if let Some(end) = skip_literal(b, i) {
+                i = end;
+            } else {
+                i += 1;
+            }
+            continue;
+        }
+        let rest = &code[i + 1..];
+        let next = b.get(i + 1).copied().unwrap_or(0);
+        if next == b'[' {
+            if let Some(close) = matching_close(b, i + 1) {
+                out.push_str(&code[last..i]);
+                out.push('{');
+                out.push_str(&rewrite_literals(&code[i + 2..close]));
+                out.push('}');
+                i = close + 1;
+                last = i;
+                continue;
+            }
+        } else if matches!(next, b'"' | b'{' | b'(') || next.is_ascii_digit() || rest.starts_with("YES") || rest.starts_with("NO") {
+            out.push_str(&code[last..i]);
+            i += 1;
+            last = i;
+            continue;
+        } else if let Some(keyword) = ["selector(", "encode(", "protocol("].iter().find(|k| rest.starts_with(**k)) {
+            if let Some(close) = matching_close(b, i + keyword.len()) {
+                out.push_str(&code[last..i]);
+                out.push_str(&keep_lines("nullptr".to_string(), &code[i..=close]));
+                i = close + 1;
+                last = i;
+                continue;
+            }
+        }
+        i += 1;
+    }
+    out.push_str(&code[last..]);
+    out
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Container {
+    None,
+    Interface,
+    Implementation,
+}
+
+/// Line based rewrite of `@interface`/`@implementation`/`@end`, method headers and statement keywords
+fn rewrite_directives(code: &str) -> String {
+    let lines: Vec<(&str, &str)> = code
+        .split_inclusive('\n')
+        .map(|line| match line.strip_suffix('\n') {
+            Some(content) => (content, "\n"),
+            None => (line, ""),
+        })
+        .collect();
+    let mut rewritten: Vec<String> = Vec::with_capacity(lines.len());
+    let mut container = Container::None;
+    // brace depth inside the current container; an ivar block may open right after the header
+    let mut depth = 0i32;
+    let mut ivars_allowed = false;
+    let mut in_ivars = false;
+
+    let mut index = 0;
+    while index < lines.len() {
+        let content = lines[index].0;
+        let trimmed = content.trim();
+        let line = if trimmed.starts_with("#import") {
+            content.replacen("#import", "#include", 1)
+        } else if ["@import", "@class", "@synthesize", "@dynamic", "@optional", "@required", "@public", "@private", "@protected", "@package"]
+            .iter()
+            .any(|k| trimmed.starts_with(k))
+        {
+            String::new()
+        } else if trimmed.starts_with("@interface") || trimmed.starts_with("@protocol") || trimmed.starts_with("@implementation") {
+            if trimmed.ends_with(';') {
+                // forward declaration
+                String::new()
+            } else {
+                container = if trimmed.starts_with("@implementation") { Container::Implementation } else { Container::Interface };
+                depth = 0;
+                in_ivars = trimmed.ends_with('{');
+                ivars_allowed = !in_ivars;
+                class_header(trimmed)
+            }
+        } else if trimmed.starts_with("@end") {
+            let closed = container != Container::None;
+            container = Container::None;
+            if closed { "};".to_string() } else { String::new() }
+        } else if container != Container::None && ivars_allowed && trimmed.starts_with('{') {
+            in_ivars = true;
+            ivars_allowed = false;
+            content.replacen('{', "", 1)
+        } else if in_ivars && trimmed.starts_with('}') {
+            in_ivars = false;
+            content.replacen('}', "", 1)
+        } else if container != Container::None && depth == 0 && is_method_header(trimmed) {
+            ivars_allowed = false;
+            // selector parts may continue on the following lines until `{` or `;`
+            let mut header = String::new();
+            let mut last = index;
+            let body = loop {
+                let part = lines[last].0.trim();
+                if let Some(pos) = part.find(['{', ';']) {
+                    header.push_str(&part[..pos]);
+                    break part[pos..].to_string();
+                }
+                header.push_str(part);
+                header.push(' ');
+                if last + 1 >= lines.len() || last - index >= 8 || is_method_header(lines[last + 1].0.trim()) {
+                    break String::new();
+                }
+                last += 1;
+            };
+            let body = if container == Container::Interface && body.is_empty() { ";".to_string() } else { body };
+            let declaration = translate_method_header(&header).unwrap_or_default();
+            if last == index {
+                let separator = if body.starts_with('{') { " " } else { "" };
+                format!("{}{}{}", declaration, separator, body)
+            } else {
+                rewritten.push(declaration);
+                for _ in index + 1..last {
+                    rewritten.push(String::new());
+                }
+                index = last;
+                depth += brace_delta(&body);
+                rewritten.push(body);
+                index += 1;
+                continue;
+            }
+        } else if container == Container::Interface && trimmed.starts_with("@property") {
+            strip_property(trimmed)
+        } else {
+            if !trimmed.is_empty() {
+                ivars_allowed = false;
+            }
+            rewrite_statement_keywords(content)
+        };
+
+        if container != Container::None && !in_ivars && !trimmed.starts_with('@') {
+            depth += brace_delta(&line);
+        }
+        rewritten.push(line);
+        index += 1;
+    }
+
+    rewritten
+        .iter()
+        .zip(lines.iter())
+        .map(|(line, (_, newline))| format!("{}{}", line, newline))
+        .collect()
+}
+
+fn brace_delta(line: &str) -> i32 {
+    let b = line.as_bytes();
+    let mut delta = 0;
+    let mut i = 0;
+    while i < b.len() {
+        if let Some(end) = skip_literal(b, i) {
+            i = end;
+            continue;
+        }
+        match b[i] {
+            b'{' => delta += 1,
+            b'}' => delta -= 1,
+            _ => {}
+        }
+        i += 1;
+    }
+    delta
+}
+
+fn leading_ident(s: &str) -> &str {
+    let end = s.bytes().position(|c| !is_ident_byte(c)).unwrap_or(s.len());
+    &s[..end]
+}
+
+/// `@interface Foo : Bar <P>` -> `class Foo : public Bar {`; categories and protocols keep the class name
+fn class_header(trimmed: &str) -> String {
+    let rest = trimmed.split_once(char::is_whitespace).map(|(_, r)| r.trim_start()).unwrap_or("");
+    let name = leading_ident(rest);
+    let after = rest[name.len()..].trim_start();
+    let superclass = after.strip_prefix(':').map(|s| leading_ident(s.trim_start())).filter(|s| !s.is_empty());
+    match superclass {
+        Some(superclass) => format!("class {} : public {} {{", name, superclass),
+        None => format!("class {} {{", name),
+    }
+}
+
+fn is_method_header(trimmed: &str) -> bool {
+    let mut chars = trimmed.chars();
+    matches!(chars.next(), Some('-' | '+')) && chars.as_str().trim_start().starts_with('(')
+}
+
+/// Drops nullability and ownership qualifiers the C++ grammar does not know; block types become `id`
+fn clean_type(ty: &str) -> String {
+    if ty.contains('^') {
+        return "id".to_string();
+    }
+    const QUALIFIERS: [&str; 11] = [
+        "nullable", "nonnull", "_Nullable", "_Nonnull", "__nullable", "__nonnull", "__kindof",
+        "__weak", "__strong", "__block", "__unsafe_unretained",
+    ];
+    let cleaned = ty
+        .split_whitespace()
+        .filter(|word| !QUALIFIERS.contains(word))
+        .collect::<Vec<_>>()
+        .join(" ");
+    if cleaned.is_empty() { "id".to_string() } else { cleaned }
+}
+
+/// `- (T)part:(T1)a other:(T2)b` -> `T part_other(T1 a, T2 b)`; class methods become static
+fn translate_method_header(header: &str) -> Option<String> {
+    let header = header.trim();
+    let is_class_method = header.starts_with('+');
+    let mut rest = header[1..].trim_start();
+    let b = rest.as_bytes();
+    let return_type = if rest.starts_with('(') {
+        let close = matching_close(b, 0)?;
+        let ty = clean_type(&rest[1..close]);
+        rest = &rest[close + 1..];
+        ty
+    } else {
+        "id".to_string()
+    };
+
+    let mut names = Vec::new();
+    let mut params = Vec::new();
+    loop {
+        rest = rest.trim_start();
+        if let Some(tail) = rest.strip_prefix(',') {
+            if tail.trim_start().starts_with("...") {
+                params.push("...".to_string());
+            }
+            break;
+        }
+        let name = leading_ident(rest);
+        if name.is_empty() && !rest.starts_with(':') {
+            break;
+        }
+        rest = rest[name.len()..].trim_start();
+        names.push(name.to_string());
+        let Some(tail) = rest.strip_prefix(':') else {
+            break;
+        };
+        rest = tail.trim_start();
+        let ty = if rest.starts_with('(') {
+            let close = matching_close(rest.as_bytes(), 0)?;
+            let ty = clean_type(&rest[1..close]);
+            rest = rest[close + 1..].trim_start();
+            ty
+        } else {
+            "id".to_string()
+        };
+        let param = leading_ident(rest);
+        rest = &rest[param.len()..];
+        params.push(format!("{} {}", ty, param));
+    }
+    if names.is_empty() {
+        return None;
+    }
+    Some(format!(
+        "{}{} {}({})",
+        if is_class_method { "static " } else { "" },
+        return_type,
+        names.join("_"),
+        params.join(", ")
+    ))
+}
+
+/// `@property (nonatomic, copy) NSString *name;` -> `NSString *name;`
+fn strip_property(trimmed: &str) -> String {
+    let rest = trimmed["@property".len()..].trim_start();
+    let rest = if rest.starts_with('(') {
+        matching_close(rest.as_bytes(), 0).map_or(rest, |close| &rest[close + 1..])
+    } else {
+        rest
+    };
+    let declaration = rest.trim();
+    let ty_end = declaration.rfind(|c: char| c.is_whitespace() || c == '*').map_or(0, |p| p + 1);
+    format!("{} {}", clean_type(&declaration[..ty_end]), &declaration[ty_end..])
+}
+
+fn rewrite_statement_keywords(content: &str) -> String {
+    if !content.contains('@') {
+        return content.to_string();
+    }
+    content
+        .replace("@autoreleasepool", "")
+        .replace("@synchronized", "if")
+        .replace("@try", "try")
+        .replace("@catch", "catch")
+        .replace("@finally", "catch (...)")
+        .replace("@throw", "throw")
+}
+
+/// Message sends: `[recv sel]` -> `recv.sel()`, `[recv a:x b:y]` -> `recv.a_b(x, y)`
+fn rewrite_sends(code: &str) -> String {
+    let b = code.as_bytes();
+    let mut out = String::with_capacity(code.len());
+    let mut last = 0;
+    let mut i = 0;
+    while i < b.len() {
+        if let Some(end) = skip_literal(b, i) {
+            i = end;
+            continue;
+        }
+        if b[i] == b'[' && is_send_start(b, i) {
+            if let Some(close) = matching_close(b, i) {
+                if let Some(call) = translate_send(&code[i + 1..close]) {
+                    out.push_str(&code[last..i]);
+                    out.push_str(&keep_lines(call, &code[i..=close]));
+                    i = close + 1;
+                    last = i;
+                    continue;
+                }
+            }
+        }
+        i += 1;
+    }
+    out.push_str(&code[last..]);
+    out
+}
+
+/// A `[` after an identifier, `)` or `]` is a subscript, anything else may start a message send
+fn is_send_start(b: &[u8], i: usize) -> bool {
+    match previous_token(b, i) {
+        (None, _) => true,
+        (Some(c), word) if is_ident_byte(c) => matches!(word, "return" | "else" | "do" | "case" | "in" | "throw"),
+        (Some(c), _) => !matches!(c, b')' | b']'),
+    }
+}
+
+/// Splits `text` at top-level occurrences of whitespace, returning the first token and the rest
+fn split_receiver(text: &str) -> (&str, &str) {
+    let b = text.as_bytes();
+    let mut i = 0;
+    while i < b.len() {
+        if let Some(end) = skip_literal(b, i) {
+            i = end;
+            continue;
+        }
+        match b[i] {
+            b'(' | b'[' | b'{' => match matching_close(b, i) {
+                Some(close) => i = close + 1,
+                None => return (text, ""),
+            },
+            c if c.is_ascii_whitespace() => return (&text[..i], &text[i..]),
+            _ => i += 1,
+        }
+    }
+    (text, "")
+}
+
+fn translate_send(inner: &str) -> Option<String> {
+    let inner = inner.trim();
+    let (receiver, selector) = split_receiver(inner);
+    let selector = selector.trim();
+    let first = receiver.bytes().next()?;
+    // `[this]`, `[&, x]`, `[[nodiscard]]` and array sizes are not sends
+    if selector.is_empty() || receiver.contains(',') || !(is_ident_byte(first) || matches!(first, b'(' | b'[' | b'"')) {
+        return None;
+    }
+    let receiver = rewrite_sends(receiver);
+    if selector.bytes().all(is_ident_byte) {
+        return Some(format!("{}.{}()", receiver, selector));
+    }
+
+    // keyword parts: an identifier directly followed by ':' at top level, at the start or after whitespace
+    let b = selector.as_bytes();
+    let mut parts: Vec<(usize, usize, usize)> = Vec::new(); // (name start, name end, argument start)
+    let mut i = 0;
+    while i < b.len() {
+        if let Some(end) = skip_literal(b, i) {
+            i = end;
+            continue;
+        }
+        match b[i] {
+            b'(' | b'[' | b'{' => {
+                i = matching_close(b, i)? + 1;
+                continue;
+            }
+            b':' => {
+                let mut start = i;
+                while start > 0 && is_ident_byte(b[start - 1]) {
+                    start -= 1;
+                }
+                if start == 0 || b[start - 1].is_ascii_whitespace() {
+                    parts.push((start, i, i + 1));
+                }
+            }
+            _ => {}
+        }
+        i += 1;
+    }
+    if parts.first().is_none_or(|&(start, _, _)| start != 0) {
+        return None;
+    }
+
+    let mut names = Vec::new();
+    let mut args = Vec::new();
+    for (index, &(start, end, arg_start)) in parts.iter().enumerate() {
+        let arg_end = parts.get(index + 1).map_or(selector.len(), |next| next.0);
+        names.push(&selector[start..end]);
+        let arg = selector[arg_start..arg_end].trim();
+        if !arg.is_empty() {
+            args.push(rewrite_sends(arg));
+        }
+    }
+    Some(format!("{}.{}({})", receiver, names.join("_"), args.join(", ")))
+}
+
+/// Blocks become lambdas (`^(int x) {` -> `[&](int x) {`), block pointers become function pointers
+fn rewrite_blocks(code: &str) -> String {
+    let b = code.as_bytes();
+    let mut out = String::with_capacity(code.len());
+    let mut last = 0;
+    let mut i = 0;
+    while i < b.len() {
+        if let Some(end) = skip_literal(b, i) {
+            i = end;
+            continue;
+        }
+        if b[i] == b'^' {
+            let next = code[i + 1..].trim_start().bytes().next();
+            let replacement = match previous_token(b, i) {
+                (Some(b'('), _) if next != Some(b'(') && next != Some(b'{') => Some("*"),
+                (None, _) => Some("[&]"),
+                (Some(c), word) if matches!(next, Some(b'(' | b'{')) && (matches!(c, b'(' | b',' | b'=' | b':' | b'{' | b';') || word == "return") => Some("[&]"),
+                _ => None,
+            };
+            if let Some(replacement) = replacement {
+                out.push_str(&code[last..i]);
+                out.push_str(replacement);
+                last = i + 1;
+            }
+        }
+        i += 1;
+    }
+    out.push_str(&code[last..]);
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_translate_keeps_lines_and_rewrites_messages() {
+        let source = r#"#import "Engine.h"
+
+@interface Car : NSObject {
+    int _speed;
+}
+@property (nonatomic, copy) NSString *name;
+- (void)driveTo:(NSString *)place speed:(int)speed;
++ (instancetype)sharedCar;
+@end
+
+@implementation Car
+- (void)driveTo:(NSString *)place speed:(int)speed {
+    [self.engine startWithPower:speed
+                           mode:@"eco"];
+    NSArray *items = @[@1, @2];
+    [[Logger shared] log:[place uppercaseString]];
+    [self finish:^(BOOL done) { NSLog(@"%d", done); }];
+}
+@end
+"#;
+        let translated = translate(source);
+        assert_eq!(translated.lines().count(), source.lines().count());
+        let lines: Vec<&str> = translated.lines().collect();
+        assert_eq!(lines[0], "#include \"Engine.h\"");
+        assert_eq!(lines[2], "class Car : public NSObject {");
+        assert_eq!(lines[5].trim(), "NSString * name;");
+        assert_eq!(lines[6], "void driveTo_speed(NSString * place, int speed);");
+        assert_eq!(lines[7], "static instancetype sharedCar();");
+        assert_eq!(lines[8], "};");
+        assert_eq!(lines[11], "void driveTo_speed(NSString * place, int speed) {");
+        assert_eq!(lines[12].trim(), "self.engine.startWithPower_mode(speed, \"eco\")");
+        assert_eq!(lines[14].trim(), "NSArray *items = {1, 2};");
+        assert_eq!(lines[15].trim(), "Logger.shared().log(place.uppercaseString());");
+        assert_eq!(lines[16].trim(), "self.finish([&](BOOL done) { NSLog(\"%d\", done); });");
+        assert_eq!(lines[18], "};");
+    }
+
+    #[test]
+    fn test_translate_leaves_cpp_brackets_alone() {
+        let source = "auto f = [this](int i) { return items[i]; };\n[[nodiscard]] int g();\nint x = a ^ b;\n";
+        assert_eq!(translate(source), source);
+    }
+
+    #[test]
+    fn test_parse_ranges_point_into_original_source() {
+        let source = "#import <math.h>\n@implementation Car\n- (void)drive:(int)speed {\n    [self stop];\n}\n@end\n\ndouble square(double v) {\n    return v * v;\n}\n";
+        let symbols = ObjCParser::new().unwrap().parse(source, &PathBuf::from("Car.m"));
+        let text = |name: &str| {
+            let symbol = symbols.iter().find(|s| s.read().name() == name).unwrap().read();
+            let range = symbol.full_range();
+            source[range.start_byte..range.end_byte].to_string()
+        };
+        assert_eq!(text("square"), "double square(double v) {\n    return v * v;\n}");
+        assert!(text("drive").starts_with("- (void)drive:(int)speed {"));
+    }
+}
@@ -0,0 +1,288 @@
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use parking_lot::RwLock;
+use tree_sitter::{Point, Range};
+use uuid::Uuid;
+
+use crate::codegraph::treesitter::ast_instance_structs::{AstSymbolFields, AstSymbolInstanceArc, CommentDefinition, FunctionArg, FunctionDeclaration, ImportDeclaration, ImportType, StructDeclaration, TypeDef};
+use crate::codegraph::treesitter::language_id::LanguageId;
+use crate::codegraph::treesitter::parsers::{AstLanguageParser, ParserError};
+use crate::codegraph::treesitter::parsers::utils::get_guid;
+
+/// Protocol Buffers service analyzer.
+///
+/// No protobuf grammar is bundled, so `.proto` files are tokenized directly. Every
+/// `service` is a class and each of its `rpc`s a function named `Service.Method`,
+/// taking the request message and returning the response message (prefixed with
+/// `stream` for streaming RPCs). `import` statements are imports. Messages, enums and
+/// options are skipped. Generated client stubs are linked to these functions when the
+/// call graph is built, see `codegraph::grpc`.
+pub(crate) struct ProtoAnalyzer;
+
+impl ProtoAnalyzer {
+    pub fn new() -> Result<Self, ParserError> {
+        Ok(Self)
+    }
+}
+
+impl AstLanguageParser for ProtoAnalyzer {
+    fn parse(&mut self, code: &str, path: &PathBuf) -> Vec<AstSymbolInstanceArc> {
+        Scanner::new(code, path).scan()
+    }
+}
+
+/// The file's `package`, used as the namespace of its services and RPCs
+pub(crate) fn file_namespace(code: &str) -> Option<String> {
+    let (tokens, _) = tokenize(code);
+    tokens
+        .windows(2)
+        .find(|pair| pair[0].word("package") && pair[1].kind == TokenKind::Name)
+        .map(|pair| pair[1].text.trim_start_matches('.').to_string())
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum TokenKind {
+    /// Identifier or dotted full name
+    Name,
+    Str,
+    Symbol,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Token<'a> {
+    kind: TokenKind,
+    text: &'a str,
+    start: usize,
+    end: usize,
+}
+
+impl Token<'_> {
+    fn word(&self, word: &str) -> bool {
+        self.kind == TokenKind::Name && self.text == word
+    }
+
+    fn symbol(&self, symbol: char) -> bool {
+        self.kind == TokenKind::Symbol && self.text.starts_with(symbol)
+    }
+}
+
+/// Tokens of `code`, and the byte ranges of its comments
+fn tokenize(code: &str) -> (Vec<Token<'_>>, Vec<(usize, usize)>) {
+    let b = code.as_bytes();
+    let mut tokens = vec![];
+    let mut comments = vec![];
+    let mut i = 0;
+    while i < b.len() {
+        let c = b[i];
+        let start = i;
+        if c.is_ascii_whitespace() {
+            i += 1;
+        } else if code[i..].starts_with("//") {
+            i = code[i..].find('\n').map_or(b.len(), |n| i + n);
+            comments.push((start, i));
+        } else if code[i..].starts_with("/*") {
+            i = code[i + 2..].find("*/").map_or(b.len(), |n| i + 2 + n + 2);
+            comments.push((start, i));
+        } else if c == b'"' || c == b'\'' {
+            i += 1;
+            while i < b.len() && b[i] != c && b[i] != b'\n' {
+                i += if b[i] == b'\\' { 2 } else { 1 };
+            }
+            i = (i + 1).min(b.len());
+            tokens.push(Token { kind: TokenKind::Str, text: &code[start..i], start, end: i });
+        } else if c.is_ascii_alphabetic() || c == b'_' || (c == b'.' && b.get(i + 1).is_some_and(|n| n.is_ascii_alphabetic())) {
+            i += 1;
+            while i < b.len() && (b[i].is_ascii_alphanumeric() || b[i] == b'_' || b[i] == b'.') {
+                i += 1;
+            }
+            tokens.push(Token { kind: TokenKind::Name, text: &code[start..i], start, end: i });
+        } else {
+            // Numbers and other characters are single-character symbols; only braces,
+            // parentheses and `;` matter to the structure
+            i += code[i..].chars().next().map_or(1, char::len_utf8);
+            tokens.push(Token { kind: TokenKind::Symbol, text: &code[start..i], start, end: i });
+        }
+    }
+    (tokens, comments)
+}
+
+struct Scanner<'a> {
+    code: &'a str,
+    path: &'a PathBuf,
+    line_starts: Vec<usize>,
+    root_guid: Uuid,
+    symbols: Vec<AstSymbolInstanceArc>,
+}
+
+impl<'a> Scanner<'a> {
+    fn new(code: &'a str, path: &'a PathBuf) -> Self {
+        let line_starts = std::iter::once(0)
+            .chain(code.bytes().enumerate().filter(|(_, c)| *c == b'\n').map(|(i, _)| i + 1))
+            .collect();
+        Self { code, path, line_starts, root_guid: get_guid(), symbols: vec![] }
+    }
+
+    fn point(&self, byte: usize) -> Point {
+        let row = self.line_starts.partition_point(|&start| start <= byte) - 1;
+        Point { row, column: byte - self.line_starts[row] }
+    }
+
+    fn range(&self, start: usize, end: usize) -> Range {
+        Range { start_byte: start, end_byte: end, start_point: self.point(start), end_point: self.point(end) }
+    }
+
+    fn fields(&self, name: &str, start: usize, end: usize, parent: Option<Uuid>) -> AstSymbolFields {
+        let mut fields = AstSymbolFields::from_data(LanguageId::Proto, self.path.clone(), false);
+        fields.guid = get_guid();
+        fields.name = name.to_string();
+        fields.parent_guid = Some(parent.unwrap_or(self.root_guid));
+        fields.full_range = self.range(start, end);
+        fields.declaration_range = fields.full_range;
+        fields.definition_range = fields.full_range;
+        fields
+    }
+
+    fn scan(mut self) -> Vec<AstSymbolInstanceArc> {
+        let (tokens, comments) = tokenize(self.code);
+        let mut depth = 0usize;
+        let mut k = 0;
+        while k < tokens.len() {
+            let token = tokens[k];
+            if token.symbol('{') {
+                depth += 1;
+            } else if token.symbol('}') {
+                depth = depth.saturating_sub(1);
+            } else if depth == 0 && token.word("import") {
+                k = self.import(&tokens, k);
+                continue;
+            } else if depth == 0 && token.word("service") {
+                k = self.service(&tokens, k);
+                continue;
+            }
+            k += 1;
+        }
+
+        for (start, end) in comments {
+            let mut ast_fields = AstSymbolFields::from_data(LanguageId::Proto, self.path.clone(), false);
+            ast_fields.guid = get_guid();
+            ast_fields.parent_guid = Some(self.root_guid);
+            ast_fields.full_range = self.range(start, end);
+            self.symbols.push(Arc::new(RwLock::new(Box::new(CommentDefinition { ast_fields }))));
+        }
+        self.symbols
+    }
+
+    /// `import [public|weak] "path.proto";`, returning the index after it
+    fn import(&mut self, tokens: &[Token], k: usize) -> usize {
+        let end = tokens[k..].iter().position(|t| t.symbol(';')).map_or(tokens.len(), |n| k + n);
+        if let Some(path) = tokens[k + 1..end].iter().find(|t| t.kind == TokenKind::Str) {
+            let path = path.text.trim_matches(|c| c == '"' || c == '\'');
+            let end_byte = tokens.get(end).map_or(self.code.len(), |t| t.end);
+            let import = ImportDeclaration {
+                ast_fields: self.fields(path, tokens[k].start, end_byte, None),
+                path_components: path.split('/').filter(|s| !s.is_empty()).map(str::to_string).collect(),
+                import_type: ImportType::UserModule,
+                ..Default::default()
+            };
+            self.symbols.push(Arc::new(RwLock::new(Box::new(import))));
+        }
+        end + 1
+    }
+
+    /// `service Name { rpc ... }`, returning the index after its closing brace
+    fn service(&mut self, tokens: &[Token], k: usize) -> usize {
+        let Some(name) = tokens.get(k + 1).filter(|t| t.kind == TokenKind::Name) else {
+            return k + 1;
+        };
+        let Some(open) = tokens.get(k + 2).filter(|t| t.symbol('{')) else {
+            return k + 2;
+        };
+        let close = matching_brace(tokens, k + 2);
+        let end_byte = tokens.get(close).map_or(self.code.len(), |t| t.end);
+        let mut service = StructDeclaration {
+            ast_fields: self.fields(name.text, tokens[k].start, end_byte, None),
+            ..Default::default()
+        };
+        service.ast_fields.declaration_range = self.range(tokens[k].start, open.end);
+        let service_guid = service.ast_fields.guid;
+
+        let mut i = k + 3;
+        while i < close {
+            if tokens[i].word("rpc") {
+                if let Some((rpc, next)) = self.rpc(tokens, i, close, name.text, service_guid) {
+                    service.ast_fields.childs_guid.push(rpc.ast_fields.guid);
+                    self.symbols.push(Arc::new(RwLock::new(Box::new(rpc))));
+                    i = next;
+                    continue;
+                }
+            } else if tokens[i].symbol('{') {
+                // `option` blocks and the like
+                i = matching_brace(tokens, i);
+            }
+            i += 1;
+        }
+        self.symbols.push(Arc::new(RwLock::new(Box::new(service))));
+        close + 1
+    }
+
+    /// `rpc Method ([stream] Request) returns ([stream] Response) (; | { ... })`
+    fn rpc(&self, tokens: &[Token], k: usize, limit: usize, service: &str, parent: Uuid) -> Option<(FunctionDeclaration, usize)> {
+        let method = tokens.get(k + 1).filter(|t| t.kind == TokenKind::Name)?;
+        let (request, after_request) = message_type(tokens, k + 2, limit)?;
+        if !tokens.get(after_request).is_some_and(|t| t.word("returns")) {
+            return None;
+        }
+        let (response, after_response) = message_type(tokens, after_request + 1, limit)?;
+        let header_end = tokens[after_response - 1].end;
+        let end = match tokens.get(after_response) {
+            Some(t) if t.symbol('{') => matching_brace(tokens, after_response).min(limit),
+            Some(t) if t.symbol(';') => after_response,
+            _ => after_response - 1,
+        };
+        let end_byte = tokens.get(end).map_or(header_end, |t| t.end);
+
+        let name = format!("{}.{}", service, method.text);
+        let mut declaration = FunctionDeclaration {
+            ast_fields: self.fields(&name, tokens[k].start, end_byte, Some(parent)),
+            args: vec![FunctionArg {
+                name: "request".to_string(),
+                type_: Some(TypeDef { name: Some(request), ..Default::default() }),
+            }],
+            return_type: Some(TypeDef { name: Some(response), ..Default::default() }),
+            ..Default::default()
+        };
+        declaration.ast_fields.declaration_range = self.range(tokens[k].start, header_end);
+        Some((declaration, end + 1))
+    }
+}
+
+/// `( [stream] Type )` starting at `k`: the type as written and the index after `)`
+fn message_type(tokens: &[Token], k: usize, limit: usize) -> Option<(String, usize)> {
+    if !tokens.get(k)?.symbol('(') {
+        return None;
+    }
+    let close = (k + 1..limit).find(|&i| tokens[i].symbol(')'))?;
+    let words: Vec<&str> = tokens[k + 1..close].iter().filter(|t| t.kind == TokenKind::Name).map(|t| t.text).collect();
+    match words.as_slice() {
+        [ty] => Some((ty.to_string(), close + 1)),
+        ["stream", ty] => Some((format!("stream {}", ty), close + 1)),
+        _ => None,
+    }
+}
+
+/// Index of the `}` closing the `{` at `open`, or the last token when unbalanced
+fn matching_brace(tokens: &[Token], open: usize) -> usize {
+    let mut depth = 0;
+    for (i, token) in tokens.iter().enumerate().skip(open) {
+        if token.symbol('{') {
+            depth += 1;
+        } else if token.symbol('}') {
+            depth -= 1;
+            if depth == 0 {
+                return i;
+            }
+        }
+    }
+    tokens.len().saturating_sub(1)
+}
@@ -12,7 +12,7 @@ use uuid::Uuid;
 
 use crate::codegraph::treesitter::ast_instance_structs::{AstSymbolFields, AstSymbolInstanceArc, ClassFieldDeclaration, CommentDefinition, FunctionArg, FunctionCall, FunctionDeclaration, ImportDeclaration, ImportType, StructDeclaration, SymbolInformation, TypeDef, VariableDefinition, VariableUsage};
 use crate::codegraph::treesitter::language_id::LanguageId;
-use crate::codegraph::treesitter::parsers::{AstLanguageParser, internal_error, ParserError};
+use crate::codegraph::treesitter::parsers::{AstLanguageParser, internal_error, ParserError, TreeStats};
 use crate::codegraph::treesitter::parsers::utils::{CandidateInfo, get_children_guids, get_guid};
 use crate::codegraph::treesitter::skeletonizer::SkeletonFormatter;
 use crate::codegraph::treesitter::structs::SymbolType;
@@ -46,6 +46,7 @@ static PYTHON_MODULES: [&str; 203] = [
 
 pub(crate) struct PythonParser {
     pub parser: Parser,
+    tree_stats: Option<TreeStats>,
 }
 
 pub fn parse_type(parent: &Node, code: &str) -> Option<TypeDef> {
@@ -212,7 +213,7 @@ impl PythonParser {
         parser
             .set_language(&tree_sitter_python::LANGUAGE.into())
             .map_err(internal_error)?;
-        Ok(PythonParser { parser })
+        Ok(PythonParser { parser, tree_stats: None })
     }
 
     pub fn parse_struct_declaration<'a>(&mut self, info: &CandidateInfo<'a>, code: &str, candidates: &mut VecDeque<CandidateInfo<'a>>) -> Vec<AstSymbolInstanceArc> {
@@ -943,7 +944,12 @@ impl SkeletonFormatter for PythonSkeletonFormatter {
 impl AstLanguageParser for PythonParser {
     fn parse(&mut self, code: &str, path: &PathBuf) -> Vec<AstSymbolInstanceArc> {
         let tree = self.parser.parse(code, None).unwrap();
+        self.tree_stats = Some(TreeStats::of(&tree));
         let symbols = self.parse_(&tree.root_node(), code, path);
         symbols
     }
+
+    fn tree_stats(&self) -> Option<TreeStats> {
+        self.tree_stats.clone()
+    }
 }
@@ -0,0 +1,258 @@
+use std::collections::{HashMap, VecDeque};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use parking_lot::RwLock;
+use tree_sitter::{Node, Parser, Range};
+use uuid::Uuid;
+
+use crate::codegraph::treesitter::ast_instance_structs::{AstSymbolFields, AstSymbolInstanceArc, CommentDefinition, FunctionArg, FunctionCall, FunctionDeclaration, ImportDeclaration, ImportType, StructDeclaration, TypeDef};
+use crate::codegraph::treesitter::language_id::LanguageId;
+use crate::codegraph::treesitter::parsers::{AstLanguageParser, internal_error, ParserError, TreeStats};
+use crate::codegraph::treesitter::parsers::utils::{CandidateInfo, get_guid};
+
+/// Ruby analyzer.
+///
+/// Classes and modules are type declarations whose `def` and `def self.` methods
+/// become methods; `include`, `extend` and `prepend` add the mixin to the
+/// declaration's inherited types. Every `call` is named after its method
+/// (`Helper.round x` is `round`), except that `Invoice.new` names the class like
+/// Java's `new Invoice()`. A bare identifier statement (`setup`) is a call
+/// without arguments. `require` and `require_relative` are imports.
+pub(crate) struct RubyAnalyzer {
+    parser: Parser,
+    tree_stats: Option<TreeStats>,
+}
+
+/// Calls that declare something rather than call a method
+static MIXIN_METHODS: [&str; 3] = ["include", "extend", "prepend"];
+static VISIBILITY_METHODS: [&str; 4] = ["private", "protected", "public", "module_function"];
+
+/// Nodes whose direct children are statements
+static STATEMENT_PARENTS: [&str; 8] = ["program", "body_statement", "block_body", "then", "else", "do", "begin", "ensure"];
+
+impl RubyAnalyzer {
+    pub fn new() -> Result<Self, ParserError> {
+        let mut parser = Parser::new();
+        parser
+            .set_language(&tree_sitter_ruby::LANGUAGE.into())
+            .map_err(internal_error)?;
+        Ok(Self { parser, tree_stats: None })
+    }
+}
+
+impl AstLanguageParser for RubyAnalyzer {
+    fn parse(&mut self, code: &str, path: &PathBuf) -> Vec<AstSymbolInstanceArc> {
+        let tree = self.parser.parse(code, None).unwrap();
+        self.tree_stats = Some(TreeStats::of(&tree));
+        parse_tree(&tree.root_node(), code, path)
+    }
+
+    fn tree_stats(&self) -> Option<TreeStats> {
+        self.tree_stats.clone()
+    }
+}
+
+fn text<'a>(node: &Node, code: &'a str) -> &'a str {
+    code.get(node.byte_range()).unwrap_or_default()
+}
+
+fn new_fields(info: &CandidateInfo, node: &Node) -> AstSymbolFields {
+    let mut fields = AstSymbolFields::from_fields(&info.ast_fields);
+    fields.guid = get_guid();
+    fields.parent_guid = Some(info.parent_guid);
+    fields.full_range = node.range();
+    fields.declaration_range = node.range();
+    fields.definition_range = node.range();
+    fields
+}
+
+/// Range from the start of the declaration to the start of its body
+fn header_range(full: &Range, body: &Node) -> Range {
+    Range {
+        start_byte: full.start_byte,
+        end_byte: body.start_byte(),
+        start_point: full.start_point,
+        end_point: body.start_position(),
+    }
+}
+
+/// `Billing::Invoice` names the constant `Invoice`
+fn constant_name<'a>(node: &Node, code: &'a str) -> Option<&'a str> {
+    match node.kind() {
+        "constant" => Some(text(node, code)),
+        "scope_resolution" => node.child_by_field_name("name").map(|name| text(&name, code)),
+        _ => None,
+    }
+}
+
+fn push_children<'a>(node: &Node<'a>, ast_fields: &AstSymbolFields, parent_guid: Uuid, candidates: &mut VecDeque<CandidateInfo<'a>>) {
+    let mut cursor = node.walk();
+    for child in node.children(&mut cursor) {
+        candidates.push_back(CandidateInfo { ast_fields: ast_fields.clone(), node: child, parent_guid });
+    }
+}
+
+/// The method name of a call without a receiver, such as `include Comparable`
+fn plain_call_name<'a>(node: &Node, code: &'a str) -> Option<&'a str> {
+    if node.kind() != "call" || node.child_by_field_name("receiver").is_some() {
+        return None;
+    }
+    node.child_by_field_name("method").map(|method| text(&method, code))
+}
+
+fn parse_class<'a>(info: &CandidateInfo<'a>, code: &str, candidates: &mut VecDeque<CandidateInfo<'a>>) -> AstSymbolInstanceArc {
+    let mut decl = StructDeclaration { ast_fields: new_fields(info, &info.node), ..Default::default() };
+    if let Some(name) = info.node.child_by_field_name("name").and_then(|name| constant_name(&name, code)) {
+        decl.ast_fields.name = name.to_string();
+    }
+    let type_def = |name: &str| TypeDef { name: Some(name.to_string()), ..Default::default() };
+    if let Some(superclass) = info.node.child_by_field_name("superclass") {
+        let mut cursor = superclass.walk();
+        let name = superclass.named_children(&mut cursor).find_map(|child| constant_name(&child, code));
+        decl.inherited_types.extend(name.map(type_def));
+    }
+    if let Some(body) = info.node.child_by_field_name("body") {
+        decl.ast_fields.declaration_range = header_range(&decl.ast_fields.full_range, &body);
+        decl.ast_fields.definition_range = body.range();
+        let mut cursor = body.walk();
+        for statement in body.named_children(&mut cursor) {
+            if !plain_call_name(&statement, code).is_some_and(|method| MIXIN_METHODS.contains(&method)) {
+                continue;
+            }
+            if let Some(arguments) = statement.child_by_field_name("arguments") {
+                let mut cursor = arguments.walk();
+                let mixins: Vec<TypeDef> = arguments.named_children(&mut cursor).filter_map(|argument| constant_name(&argument, code)).map(type_def).collect();
+                decl.inherited_types.extend(mixins);
+            }
+        }
+        push_children(&body, &info.ast_fields, decl.ast_fields.guid, candidates);
+    }
+    Arc::new(RwLock::new(Box::new(decl)))
+}
+
+fn parse_function<'a>(info: &CandidateInfo<'a>, code: &str, candidates: &mut VecDeque<CandidateInfo<'a>>) -> AstSymbolInstanceArc {
+    let mut decl = FunctionDeclaration { ast_fields: new_fields(info, &info.node), ..Default::default() };
+    if let Some(name) = info.node.child_by_field_name("name") {
+        decl.ast_fields.name = text(&name, code).to_string();
+    }
+    if let Some(parameters) = info.node.child_by_field_name("parameters") {
+        let mut cursor = parameters.walk();
+        for parameter in parameters.named_children(&mut cursor) {
+            // `items`, `limit = 10`, `*rest`, `key:`, `&block`
+            let name = if parameter.kind() == "identifier" { Some(parameter) } else { parameter.child_by_field_name("name") };
+            if let Some(name) = name {
+                decl.args.push(FunctionArg { name: text(&name, code).to_string(), type_: None });
+            }
+        }
+    }
+    if let Some(body) = info.node.child_by_field_name("body") {
+        decl.ast_fields.declaration_range = header_range(&decl.ast_fields.full_range, &body);
+        decl.ast_fields.definition_range = body.range();
+        push_children(&body, &info.ast_fields, decl.ast_fields.guid, candidates);
+    }
+    Arc::new(RwLock::new(Box::new(decl)))
+}
+
+fn parse_call<'a>(info: &CandidateInfo<'a>, code: &str, candidates: &mut VecDeque<CandidateInfo<'a>>) -> Option<AstSymbolInstanceArc> {
+    let mut decl = FunctionCall { ast_fields: new_fields(info, &info.node), ..Default::default() };
+    if let Some(caller_guid) = info.ast_fields.caller_guid {
+        decl.ast_fields.guid = caller_guid;
+    }
+    decl.ast_fields.caller_guid = Some(get_guid());
+
+    let method = info.node.child_by_field_name("method").map(|method| text(&method, code)).unwrap_or_default();
+    let receiver = info.node.child_by_field_name("receiver");
+    decl.ast_fields.name = match receiver.as_ref().and_then(|receiver| constant_name(receiver, code)) {
+        Some(class) if method == "new" => class.to_string(),
+        _ => method.to_string(),
+    };
+    if let Some(receiver) = receiver {
+        // Calls in the receiver are chained to this call through `caller_guid`
+        let mut receiver_fields = info.ast_fields.clone();
+        receiver_fields.caller_guid = decl.ast_fields.caller_guid;
+        candidates.push_back(CandidateInfo { ast_fields: receiver_fields, node: receiver, parent_guid: info.parent_guid });
+    }
+    let mut arguments_fields = info.ast_fields.clone();
+    arguments_fields.caller_guid = None;
+    for field in ["arguments", "block"] {
+        if let Some(node) = info.node.child_by_field_name(field) {
+            candidates.push_back(CandidateInfo { ast_fields: arguments_fields.clone(), node, parent_guid: info.parent_guid });
+        }
+    }
+    if decl.ast_fields.name.is_empty() {
+        return None;
+    }
+    Some(Arc::new(RwLock::new(Box::new(decl))))
+}
+
+/// `require 'json'` loads a gem, `require_relative 'lib/helper'` a file of the project
+fn parse_import(info: &CandidateInfo, code: &str, relative: bool) -> Option<AstSymbolInstanceArc> {
+    let arguments = info.node.child_by_field_name("arguments")?;
+    let string = arguments.named_child(0).filter(|argument| argument.kind() == "string")?;
+    let mut cursor = string.walk();
+    let content = string.named_children(&mut cursor).find(|child| child.kind() == "string_content")?;
+    let path = text(&content, code).trim_end_matches(".rb");
+    let mut def = ImportDeclaration { ast_fields: new_fields(info, &info.node), ..Default::default() };
+    def.path_components = path.split('/').filter(|part| !part.is_empty() && *part != "." && *part != "..").map(str::to_string).collect();
+    def.import_type = if relative { ImportType::UserModule } else { ImportType::Library };
+    def.ast_fields.name = def.path_components.last().cloned().unwrap_or_default();
+    Some(Arc::new(RwLock::new(Box::new(def))))
+}
+
+fn parse_usages<'a>(info: &CandidateInfo<'a>, code: &str, candidates: &mut VecDeque<CandidateInfo<'a>>) -> Vec<AstSymbolInstanceArc> {
+    let mut symbols: Vec<AstSymbolInstanceArc> = vec![];
+    match info.node.kind() {
+        "class" | "module" => symbols.push(parse_class(info, code, candidates)),
+        "method" | "singleton_method" => symbols.push(parse_function(info, code, candidates)),
+        "call" => match plain_call_name(&info.node, code) {
+            Some(method @ ("require" | "require_relative")) => symbols.extend(parse_import(info, code, method == "require_relative")),
+            Some(method) if MIXIN_METHODS.contains(&method) => {}
+            _ => symbols.extend(parse_call(info, code, candidates)),
+        },
+        // `setup` on a line of its own calls a method; local variables read this way are dropped when unresolved
+        "identifier" if info.node.parent().is_some_and(|parent| STATEMENT_PARENTS.contains(&parent.kind())) => {
+            let name = text(&info.node, code);
+            if !VISIBILITY_METHODS.contains(&name) {
+                let mut decl = FunctionCall { ast_fields: new_fields(info, &info.node), ..Default::default() };
+                decl.ast_fields.name = name.to_string();
+                symbols.push(Arc::new(RwLock::new(Box::new(decl))));
+            }
+        }
+        "comment" => {
+            let def = CommentDefinition { ast_fields: new_fields(info, &info.node) };
+            symbols.push(Arc::new(RwLock::new(Box::new(def))));
+        }
+        "ERROR" => {
+            let mut ast_fields = info.ast_fields.clone();
+            ast_fields.is_error = true;
+            push_children(&info.node, &ast_fields, info.parent_guid, candidates);
+        }
+        _ => push_children(&info.node, &info.ast_fields, info.parent_guid, candidates),
+    }
+    symbols
+}
+
+fn parse_tree(root: &Node, code: &str, path: &Path) -> Vec<AstSymbolInstanceArc> {
+    let mut symbols: Vec<AstSymbolInstanceArc> = vec![];
+    let mut candidates = VecDeque::from(vec![CandidateInfo {
+        ast_fields: AstSymbolFields::from_data(LanguageId::Ruby, path.to_path_buf(), false),
+        node: *root,
+        parent_guid: get_guid(),
+    }]);
+    while let Some(candidate) = candidates.pop_front() {
+        symbols.extend(parse_usages(&candidate, code, &mut candidates));
+    }
+
+    let guid_to_symbol: HashMap<Uuid, AstSymbolInstanceArc> = symbols.iter().map(|s| (*s.read().guid(), s.clone())).collect();
+    for symbol in &symbols {
+        let (guid, parent_guid) = {
+            let symbol = symbol.read();
+            (*symbol.guid(), *symbol.parent_guid())
+        };
+        if let Some(parent) = parent_guid.and_then(|parent_guid| guid_to_symbol.get(&parent_guid)) {
+            parent.write().fields_mut().childs_guid.push(guid);
+        }
+    }
+    symbols
+}
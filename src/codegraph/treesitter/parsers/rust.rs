@@ -9,7 +9,7 @@ use uuid::Uuid;
 
 use crate::codegraph::treesitter::ast_instance_structs::{AstSymbolInstance, AstSymbolInstanceArc, ClassFieldDeclaration, CommentDefinition, FunctionArg, FunctionCall, FunctionDeclaration, ImportDeclaration, ImportType, StructDeclaration, TypeAlias, TypeDef, VariableDefinition, VariableUsage};
 use crate::codegraph::treesitter::language_id::LanguageId;
-use crate::codegraph::treesitter::parsers::{AstLanguageParser, internal_error, ParserError};
+use crate::codegraph::treesitter::parsers::{AstLanguageParser, internal_error, ParserError, TreeStats};
 use crate::codegraph::treesitter::parsers::utils::{get_children_guids, get_guid};
 use crate::codegraph::treesitter::skeletonizer::SkeletonFormatter;
 use std::collections::{HashMap, VecDeque};
@@ -19,6 +19,7 @@ use crate::codegraph::treesitter::structs::SymbolType;
 
 pub(crate) struct RustParser {
     pub parser: Parser,
+    tree_stats: Option<TreeStats>,
 }
 
 static RUST_KEYWORDS: [&str; 37] = [
@@ -34,7 +35,7 @@ impl RustParser {
         parser
             .set_language(&tree_sitter_rust::LANGUAGE.into())
             .map_err(internal_error)?;
-        Ok(RustParser { parser })
+        Ok(RustParser { parser, tree_stats: None })
     }
 
     pub fn parse_type(parent: &Node, code: &str) -> Option<TypeDef> {
@@ -201,6 +202,47 @@ impl RustParser {
         symbols
     }
 
+    /// 闭包解析为没有名字的函数，构建图时再按外层函数命名
+    pub fn parse_closure_expression(&mut self, parent: &Node, code: &str, path: &PathBuf, parent_guid: &Uuid, is_error: bool) -> Vec<AstSymbolInstanceArc> {
+        let mut symbols: Vec<AstSymbolInstanceArc> = Default::default();
+        let mut decl = FunctionDeclaration::default();
+        decl.ast_fields.language = LanguageId::Rust;
+        decl.ast_fields.full_range = parent.range();
+        decl.ast_fields.declaration_range = parent.range();
+        decl.ast_fields.file_path = path.clone();
+        decl.ast_fields.parent_guid = Some(*parent_guid);
+        decl.ast_fields.is_error = is_error;
+        decl.ast_fields.guid = get_guid();
+
+        if let Some(parameters_node) = parent.child_by_field_name("parameters") {
+            for idx in 0..parameters_node.child_count() {
+                let child = parameters_node.child(idx).unwrap();
+                match child.kind() {
+                    "parameter" => {
+                        // 没有 pattern 的参数（语法错误恢复时）按原文记录
+                        let name = child.child_by_field_name("pattern").unwrap_or(child);
+                        decl.args.push(FunctionArg {
+                            name: code.slice(name.byte_range()).to_string(),
+                            type_: child.child_by_field_name("type").and_then(|type_node| RustParser::parse_type(&type_node, code)),
+                        });
+                    }
+                    "|" | "," => {}
+                    _ => decl.args.push(FunctionArg { name: code.slice(child.byte_range()).to_string(), type_: None }),
+                }
+            }
+        }
+        if let Some(return_type) = parent.child_by_field_name("return_type") {
+            decl.return_type = RustParser::parse_type(&return_type, code);
+        }
+        if let Some(body_node) = parent.child_by_field_name("body") {
+            decl.ast_fields.definition_range = body_node.range();
+            symbols.extend(self.parse_expression_statement(&body_node, code, path, &decl.ast_fields.guid, is_error));
+        }
+        decl.ast_fields.childs_guid = get_children_guids(&decl.ast_fields.guid, &symbols);
+        symbols.push(Arc::new(RwLock::new(Box::new(decl))));
+        symbols
+    }
+
     pub fn parse_struct_declaration(&mut self, parent: &Node, code: &str, path: &PathBuf, parent_guid: &Uuid, is_error: bool) -> Vec<AstSymbolInstanceArc> {
         let mut symbols: Vec<AstSymbolInstanceArc> = Default::default();
         let mut decl = StructDeclaration::default();
@@ -400,7 +442,12 @@ impl RustParser {
         }
 
         if let Some(value_node) = parent.child_by_field_name("value") {
-            decl.type_ = parse_type_in_value(&value_node, code);
+            // an explicit annotation (`let s: Shape = ...`) wins over the type seen in the value
+            if decl.type_.name.is_none() {
+                decl.type_ = parse_type_in_value(&value_node, code);
+            } else {
+                decl.type_.inference_info = Some(code.slice(value_node.byte_range()).to_string());
+            }
             symbols.extend(self.parse_usages(&value_node, code, path, &parent_guid, is_error));
         }
 
@@ -487,6 +534,9 @@ impl RustParser {
             "call_expression" => {
                 symbols.extend(self.parse_call_expression(&parent, code, path, parent_guid, is_error));
             }
+            "closure_expression" => {
+                symbols.extend(self.parse_closure_expression(parent, code, path, parent_guid, is_error));
+            }
             "let_condition" => {
                 symbols.extend(self.parse_variable_definition(&parent, code, path, parent_guid, is_error));
             }
@@ -1009,10 +1059,15 @@ impl RustParser {
 impl AstLanguageParser for RustParser {
     fn parse(&mut self, code: &str, path: &PathBuf) -> Vec<AstSymbolInstanceArc> {
         let tree = self.parser.parse(code, None).unwrap();
+        self.tree_stats = Some(TreeStats::of(&tree));
         let parent_guid = get_guid();
         let symbols = self.parse_block(&tree.root_node(), code, path, &parent_guid, false);
         symbols
     }
+
+    fn tree_stats(&self) -> Option<TreeStats> {
+        self.tree_stats.clone()
+    }
 }
 
 pub struct RustSkeletonFormatter;
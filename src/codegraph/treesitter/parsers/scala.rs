@@ -0,0 +1,326 @@
+use std::collections::{HashMap, VecDeque};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use parking_lot::RwLock;
+use tree_sitter::{Node, Parser, Range};
+use uuid::Uuid;
+
+use crate::codegraph::treesitter::ast_instance_structs::{AstSymbolFields, AstSymbolInstanceArc, CommentDefinition, FunctionArg, FunctionCall, FunctionDeclaration, ImportDeclaration, ImportType, StructDeclaration, TypeDef};
+use crate::codegraph::treesitter::language_id::LanguageId;
+use crate::codegraph::treesitter::parsers::{AstLanguageParser, internal_error, ParserError, TreeStats};
+use crate::codegraph::treesitter::parsers::utils::{CandidateInfo, get_guid};
+
+/// Scala analyzer.
+///
+/// Classes, objects, traits and enums are type declarations whose `def`s become
+/// methods; a companion object is a second declaration with the class's name.
+/// Besides `call_expression` and `new T(..)`, Scala calls methods without
+/// parentheses, so a member selection that is not itself called
+/// (`items.map(price).sum`), a postfix selection (`cart total`) and an infix
+/// expression with an alphanumeric operator (`a max b`) are calls as well.
+/// Every call is named after its last segment.
+pub(crate) struct ScalaAnalyzer {
+    parser: Parser,
+    tree_stats: Option<TreeStats>,
+}
+
+/// Packages of the standard library and the platform
+static SYSTEM_PACKAGES: [&str; 3] = ["scala", "java", "javax"];
+
+impl ScalaAnalyzer {
+    pub fn new() -> Result<Self, ParserError> {
+        let mut parser = Parser::new();
+        parser
+            .set_language(&tree_sitter_scala::LANGUAGE.into())
+            .map_err(internal_error)?;
+        Ok(Self { parser, tree_stats: None })
+    }
+}
+
+impl AstLanguageParser for ScalaAnalyzer {
+    fn parse(&mut self, code: &str, path: &PathBuf) -> Vec<AstSymbolInstanceArc> {
+        let tree = self.parser.parse(code, None).unwrap();
+        self.tree_stats = Some(TreeStats::of(&tree));
+        parse_tree(&tree.root_node(), code, path)
+    }
+
+    fn tree_stats(&self) -> Option<TreeStats> {
+        self.tree_stats.clone()
+    }
+}
+
+fn text<'a>(node: &Node, code: &'a str) -> &'a str {
+    code.get(node.byte_range()).unwrap_or_default()
+}
+
+fn new_fields(info: &CandidateInfo, node: &Node) -> AstSymbolFields {
+    let mut fields = AstSymbolFields::from_fields(&info.ast_fields);
+    fields.guid = get_guid();
+    fields.parent_guid = Some(info.parent_guid);
+    fields.full_range = node.range();
+    fields.declaration_range = node.range();
+    fields.definition_range = node.range();
+    fields
+}
+
+/// Range from the start of the declaration to the start of its body
+fn header_range(full: &Range, body: &Node) -> Range {
+    Range {
+        start_byte: full.start_byte,
+        end_byte: body.start_byte(),
+        start_point: full.start_point,
+        end_point: body.start_position(),
+    }
+}
+
+/// `Base[T]` and `pkg.Base` name the type `Base`
+fn type_name(node: &Node, code: &str) -> Option<TypeDef> {
+    match node.kind() {
+        "type_identifier" => Some(TypeDef { name: Some(text(node, code).to_string()), ..Default::default() }),
+        "generic_type" => type_name(&node.child_by_field_name("type")?, code),
+        "stable_type_identifier" => type_name(&node.named_child(node.named_child_count().checked_sub(1)?)?, code),
+        _ => None,
+    }
+}
+
+fn push_children<'a>(node: &Node<'a>, ast_fields: &AstSymbolFields, parent_guid: Uuid, candidates: &mut VecDeque<CandidateInfo<'a>>) {
+    let mut cursor = node.walk();
+    for child in node.children(&mut cursor) {
+        candidates.push_back(CandidateInfo { ast_fields: ast_fields.clone(), node: child, parent_guid });
+    }
+}
+
+fn parse_class<'a>(info: &CandidateInfo<'a>, code: &str, candidates: &mut VecDeque<CandidateInfo<'a>>) -> AstSymbolInstanceArc {
+    let mut decl = StructDeclaration { ast_fields: new_fields(info, &info.node), ..Default::default() };
+    if let Some(name) = info.node.child_by_field_name("name") {
+        decl.ast_fields.name = text(&name, code).to_string();
+    }
+    if let Some(extends) = info.node.child_by_field_name("extend") {
+        let mut cursor = extends.walk();
+        let types: Vec<TypeDef> = extends.children_by_field_name("type", &mut cursor).filter_map(|type_| type_name(&type_, code)).collect();
+        decl.inherited_types.extend(types);
+        // The superclass constructor arguments `extends Base(x)`
+        if let Some(arguments) = extends.child_by_field_name("arguments") {
+            candidates.push_back(CandidateInfo { ast_fields: info.ast_fields.clone(), node: arguments, parent_guid: info.parent_guid });
+        }
+    }
+    if let Some(body) = info.node.child_by_field_name("body") {
+        decl.ast_fields.declaration_range = header_range(&decl.ast_fields.full_range, &body);
+        decl.ast_fields.definition_range = body.range();
+        push_children(&body, &info.ast_fields, decl.ast_fields.guid, candidates);
+    }
+    Arc::new(RwLock::new(Box::new(decl)))
+}
+
+fn parse_function<'a>(info: &CandidateInfo<'a>, code: &str, candidates: &mut VecDeque<CandidateInfo<'a>>) -> AstSymbolInstanceArc {
+    let mut decl = FunctionDeclaration { ast_fields: new_fields(info, &info.node), ..Default::default() };
+    if let Some(name) = info.node.child_by_field_name("name") {
+        decl.ast_fields.name = text(&name, code).to_string();
+    }
+    // Every parameter list of a curried `def f(a: A)(implicit b: B)`
+    let mut cursor = info.node.walk();
+    for parameters in info.node.children_by_field_name("parameters", &mut cursor) {
+        let mut cursor = parameters.walk();
+        for parameter in parameters.named_children(&mut cursor).filter(|child| child.kind() == "parameter") {
+            decl.args.push(FunctionArg {
+                name: parameter.child_by_field_name("name").map(|name| text(&name, code).to_string()).unwrap_or_default(),
+                type_: parameter.child_by_field_name("type").map(|type_| {
+                    type_name(&type_, code).unwrap_or_else(|| TypeDef { name: Some(text(&type_, code).to_string()), ..Default::default() })
+                }),
+            });
+        }
+    }
+    // Abstract members have no body and keep the full range as their declaration
+    if let Some(body) = info.node.child_by_field_name("body") {
+        decl.ast_fields.declaration_range = header_range(&decl.ast_fields.full_range, &body);
+        decl.ast_fields.definition_range = body.range();
+        candidates.push_back(CandidateInfo { ast_fields: info.ast_fields.clone(), node: body, parent_guid: decl.ast_fields.guid });
+    }
+    Arc::new(RwLock::new(Box::new(decl)))
+}
+
+/// A call named `name`; `receiver` is chained to it through `caller_guid`, `arguments` are analyzed on their own
+fn new_call<'a>(
+    info: &CandidateInfo<'a>,
+    name: &str,
+    receiver: Option<Node<'a>>,
+    arguments: impl IntoIterator<Item = Node<'a>>,
+    candidates: &mut VecDeque<CandidateInfo<'a>>,
+) -> Option<AstSymbolInstanceArc> {
+    let mut decl = FunctionCall { ast_fields: new_fields(info, &info.node), ..Default::default() };
+    if let Some(caller_guid) = info.ast_fields.caller_guid {
+        decl.ast_fields.guid = caller_guid;
+    }
+    decl.ast_fields.caller_guid = Some(get_guid());
+    decl.ast_fields.name = name.to_string();
+
+    if let Some(receiver) = receiver {
+        let mut receiver_fields = info.ast_fields.clone();
+        receiver_fields.caller_guid = decl.ast_fields.caller_guid;
+        candidates.push_back(CandidateInfo { ast_fields: receiver_fields, node: receiver, parent_guid: info.parent_guid });
+    }
+    let mut arguments_fields = info.ast_fields.clone();
+    arguments_fields.caller_guid = None;
+    for node in arguments {
+        candidates.push_back(CandidateInfo { ast_fields: arguments_fields.clone(), node, parent_guid: info.parent_guid });
+    }
+    if decl.ast_fields.name.is_empty() {
+        return None;
+    }
+    Some(Arc::new(RwLock::new(Box::new(decl))))
+}
+
+fn parse_call<'a>(info: &CandidateInfo<'a>, code: &str, candidates: &mut VecDeque<CandidateInfo<'a>>) -> Option<AstSymbolInstanceArc> {
+    let mut function = info.node.child_by_field_name("function")?;
+    // `parse[Int](s)`
+    while function.kind() == "generic_function" {
+        function = function.child_by_field_name("function")?;
+    }
+    let mut cursor = info.node.walk();
+    let mut arguments: Vec<Node> = info.node.named_children(&mut cursor).filter(|child| child.id() != function.id()).collect();
+    let (name, receiver) = match function.kind() {
+        "identifier" | "operator_identifier" => (text(&function, code), None),
+        "field_expression" => (function.child_by_field_name("field").map(|field| text(&field, code)).unwrap_or_default(), function.child_by_field_name("value")),
+        _ => {
+            // `(f andThen g)(x)`: the callee is an expression of its own
+            arguments.push(function);
+            ("", None)
+        }
+    };
+    if name.is_empty() {
+        for node in arguments {
+            candidates.push_back(CandidateInfo { ast_fields: info.ast_fields.clone(), node, parent_guid: info.parent_guid });
+        }
+        return None;
+    }
+    new_call(info, name, receiver, arguments, candidates)
+}
+
+fn parse_import(info: &CandidateInfo, code: &str) -> Vec<AstSymbolInstanceArc> {
+    let mut cursor = info.node.walk();
+    let path: Vec<String> = info
+        .node
+        .children_by_field_name("path", &mut cursor)
+        .filter(|part| part.kind() == "identifier")
+        .map(|part| text(&part, code).to_string())
+        .collect();
+    let import = |mut path_components: Vec<String>, name: &str, alias: Option<String>| -> AstSymbolInstanceArc {
+        path_components.push(name.to_string());
+        let mut def = ImportDeclaration { ast_fields: new_fields(info, &info.node), ..Default::default() };
+        if path_components.first().is_some_and(|first| SYSTEM_PACKAGES.contains(&first.as_str())) {
+            def.import_type = ImportType::System;
+        }
+        def.ast_fields.name = alias.clone().unwrap_or_else(|| name.to_string());
+        def.path_components = path_components;
+        def.alias = alias;
+        Arc::new(RwLock::new(Box::new(def)))
+    };
+
+    let mut cursor = info.node.walk();
+    let Some(selectors) = info.node.named_children(&mut cursor).find(|child| child.kind() == "namespace_selectors") else {
+        // `import scala.concurrent.Future`
+        let Some((name, package)) = path.split_last() else { return vec![] };
+        return vec![import(package.to_vec(), name, None)];
+    };
+    // `import com.example.util.{Logger, Json => J, _}`
+    let mut imports = vec![];
+    let mut cursor = selectors.walk();
+    for selector in selectors.named_children(&mut cursor) {
+        match selector.kind() {
+            "identifier" => imports.push(import(path.clone(), text(&selector, code), None)),
+            "arrow_renamed_identifier" | "as_renamed_identifier" => {
+                let (Some(name), Some(alias)) = (selector.child_by_field_name("name"), selector.child_by_field_name("alias")) else { continue };
+                imports.push(import(path.clone(), text(&name, code), Some(text(&alias, code).to_string())));
+            }
+            _ => {}
+        }
+    }
+    imports
+}
+
+/// Whether an infix operator is a method name (`a max b`) rather than a symbol (`a + b`)
+fn is_word_operator(operator: &str) -> bool {
+    operator.starts_with(|c: char| c.is_alphabetic() || c == '_')
+}
+
+fn parse_usages<'a>(info: &CandidateInfo<'a>, code: &str, candidates: &mut VecDeque<CandidateInfo<'a>>) -> Vec<AstSymbolInstanceArc> {
+    let mut symbols: Vec<AstSymbolInstanceArc> = vec![];
+    let node = info.node;
+    match node.kind() {
+        "class_definition" | "object_definition" | "trait_definition" | "enum_definition" => symbols.push(parse_class(info, code, candidates)),
+        "function_definition" | "function_declaration" => symbols.push(parse_function(info, code, candidates)),
+        "call_expression" => symbols.extend(parse_call(info, code, candidates)),
+        // `new Cart(items)` calls the constructor like Java's `new`
+        "instance_expression" => {
+            let mut cursor = node.walk();
+            let class = node.named_children(&mut cursor).find_map(|child| type_name(&child, code)).and_then(|type_| type_.name);
+            let arguments = node.child_by_field_name("arguments");
+            match class {
+                Some(class) => symbols.extend(new_call(info, &class, None, arguments, candidates)),
+                None => push_children(&node, &info.ast_fields, info.parent_guid, candidates),
+            }
+        }
+        // A member selection that is not called with arguments: `items.map(price).sum`
+        "field_expression" => {
+            let field = node.child_by_field_name("field").map(|field| text(&field, code)).unwrap_or_default();
+            symbols.extend(new_call(info, field, node.child_by_field_name("value"), None, candidates));
+        }
+        // `cart total`
+        "postfix_expression" => {
+            let mut cursor = node.walk();
+            let children: Vec<Node> = node.named_children(&mut cursor).collect();
+            if let Some((method, receiver)) = children.split_last().filter(|(method, _)| method.kind() == "identifier") {
+                symbols.extend(new_call(info, text(method, code), receiver.first().copied(), None, candidates));
+            } else {
+                push_children(&node, &info.ast_fields, info.parent_guid, candidates);
+            }
+        }
+        // `a max b` calls `max` on `a`
+        "infix_expression" => {
+            let operator = node.child_by_field_name("operator").map(|operator| text(&operator, code)).unwrap_or_default();
+            if is_word_operator(operator) {
+                symbols.extend(new_call(info, operator, node.child_by_field_name("left"), node.child_by_field_name("right"), candidates));
+            } else {
+                push_children(&node, &info.ast_fields, info.parent_guid, candidates);
+            }
+        }
+        "import_declaration" => symbols.extend(parse_import(info, code)),
+        "comment" | "block_comment" => {
+            let def = CommentDefinition { ast_fields: new_fields(info, &node) };
+            symbols.push(Arc::new(RwLock::new(Box::new(def))));
+        }
+        "package_clause" => {}
+        "ERROR" => {
+            let mut ast_fields = info.ast_fields.clone();
+            ast_fields.is_error = true;
+            push_children(&node, &ast_fields, info.parent_guid, candidates);
+        }
+        _ => push_children(&node, &info.ast_fields, info.parent_guid, candidates),
+    }
+    symbols
+}
+
+fn parse_tree(root: &Node, code: &str, path: &Path) -> Vec<AstSymbolInstanceArc> {
+    let mut symbols: Vec<AstSymbolInstanceArc> = vec![];
+    let mut candidates = VecDeque::from(vec![CandidateInfo {
+        ast_fields: AstSymbolFields::from_data(LanguageId::Scala, path.to_path_buf(), false),
+        node: *root,
+        parent_guid: get_guid(),
+    }]);
+    while let Some(candidate) = candidates.pop_front() {
+        symbols.extend(parse_usages(&candidate, code, &mut candidates));
+    }
+
+    let guid_to_symbol: HashMap<Uuid, AstSymbolInstanceArc> = symbols.iter().map(|s| (*s.read().guid(), s.clone())).collect();
+    for symbol in &symbols {
+        let (guid, parent_guid) = {
+            let symbol = symbol.read();
+            (*symbol.guid(), *symbol.parent_guid())
+        };
+        if let Some(parent) = parent_guid.and_then(|parent_guid| guid_to_symbol.get(&parent_guid)) {
+            parent.write().fields_mut().childs_guid.push(guid);
+        }
+    }
+    symbols
+}
@@ -0,0 +1,142 @@
+use std::path::PathBuf;
+
+use crate::codegraph::treesitter::ast_instance_structs::AstSymbolInstanceArc;
+use crate::codegraph::treesitter::parsers::js::JSParser;
+use crate::codegraph::treesitter::parsers::ts::TSParser;
+use crate::codegraph::treesitter::parsers::{AstLanguageParser, ParserError, TreeStats};
+
+/// Vue and Svelte single-file component parser.
+///
+/// The `<script>` blocks (`<script setup>`, `<script context="module">`, ...) are kept
+/// and everything else in the file is overwritten with spaces, newlines excepted, so
+/// the result has the same lines and byte offsets as the component. It is parsed with
+/// `TSParser` when a block is marked `lang="ts"` and with `JSParser` otherwise, and the
+/// symbol ranges point straight into the `.vue`/`.svelte` file. Templates and styles
+/// are not analyzed.
+pub(crate) struct SfcParser {
+    ts: TSParser,
+    js: JSParser,
+    typescript: bool,
+}
+
+impl SfcParser {
+    pub fn new() -> Result<Self, ParserError> {
+        Ok(Self { ts: TSParser::new()?, js: JSParser::new()?, typescript: false })
+    }
+}
+
+impl AstLanguageParser for SfcParser {
+    fn parse(&mut self, code: &str, path: &PathBuf) -> Vec<AstSymbolInstanceArc> {
+        let blocks = script_blocks(code);
+        if blocks.is_empty() {
+            return vec![];
+        }
+        let script = blank_outside(code, &blocks);
+        self.typescript = blocks.iter().any(|block| block.typescript);
+        if self.typescript {
+            self.ts.parse(&script, path)
+        } else {
+            self.js.parse(&script, path)
+        }
+    }
+
+    fn tree_stats(&self) -> Option<TreeStats> {
+        if self.typescript {
+            self.ts.tree_stats()
+        } else {
+            self.js.tree_stats()
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct ScriptBlock {
+    /// Byte range of the block content, between `<script ...>` and `</script>`
+    pub start: usize,
+    pub end: usize,
+    pub typescript: bool,
+}
+
+/// Top-level `<script>` blocks of a component; `<script>` inside HTML comments is ignored
+pub(crate) fn script_blocks(code: &str) -> Vec<ScriptBlock> {
+    let lower = code.to_ascii_lowercase();
+    let mut blocks = vec![];
+    let mut pos = 0;
+    while let Some(offset) = lower[pos..].find('<') {
+        let tag = pos + offset;
+        if lower[tag..].starts_with("<!--") {
+            pos = lower[tag..].find("-->").map_or(lower.len(), |end| tag + end + 3);
+            continue;
+        }
+        let is_script = lower[tag..].starts_with("<script")
+            && lower[tag + 7..].starts_with(|c: char| c == '>' || c == '/' || c.is_ascii_whitespace());
+        if !is_script {
+            pos = tag + 1;
+            continue;
+        }
+        let Some(open_end) = lower[tag..].find('>').map(|end| tag + end) else { break };
+        let attributes = &lower[tag + 7..open_end];
+        if attributes.trim_end().ends_with('/') {
+            // `<script src="..." />`
+            pos = open_end + 1;
+            continue;
+        }
+        let start = open_end + 1;
+        let end = lower[start..].find("</script").map_or(lower.len(), |end| start + end);
+        blocks.push(ScriptBlock { start, end, typescript: is_typescript(attributes) });
+        pos = end;
+    }
+    blocks
+}
+
+fn is_typescript(attributes: &str) -> bool {
+    ["lang=\"ts\"", "lang='ts'", "lang=ts", "lang=\"typescript\"", "lang='typescript'", "lang=\"tsx\"", "lang='tsx'"]
+        .iter()
+        .any(|lang| attributes.contains(lang))
+}
+
+/// Replaces every byte outside the blocks with a space, keeping line breaks
+pub(crate) fn blank_outside(code: &str, blocks: &[ScriptBlock]) -> String {
+    let mut bytes = code.as_bytes().to_vec();
+    let mut kept = 0;
+    for block in blocks.iter().map(|block| (block.start, block.end)).chain([(code.len(), code.len())]) {
+        for byte in &mut bytes[kept..block.0] {
+            if *byte != b'\n' && *byte != b'\r' {
+                *byte = b' ';
+            }
+        }
+        kept = block.1;
+    }
+    // only whole characters are replaced, each byte by an ASCII space
+    String::from_utf8(bytes).unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_script_blocks_keep_component_offsets() {
+        let vue = "<template>\n  <button @click=\"save\">Sauvegardé</button>\n</template>\n\
+                   <!-- <script>ignored()</script> -->\n\
+                   <script setup lang=\"ts\">\nfunction save(): void {\n  persist()\n}\n</script>\n\
+                   <style>.a { color: red }</style>\n";
+        let blocks = script_blocks(vue);
+        assert_eq!(blocks.len(), 1);
+        assert!(blocks[0].typescript);
+
+        let script = blank_outside(vue, &blocks);
+        assert_eq!(script.len(), vue.len());
+        let lines: Vec<&str> = script.lines().collect();
+        assert_eq!(lines.len(), vue.lines().count());
+        assert_eq!(lines[5], "function save(): void {");
+        assert!(lines[1].trim().is_empty() && lines[3].trim().is_empty() && lines[9].trim().is_empty());
+
+        let svelte = "<script context=\"module\">\nexport const x = 1\n</script>\n<script src=\"./a.js\" />\n<SCRIPT>\nlet n = 0\n</SCRIPT>\n<p>{n}</p>";
+        let blocks = script_blocks(svelte);
+        assert_eq!(blocks.len(), 2);
+        assert!(!blocks[0].typescript && !blocks[1].typescript);
+        assert_eq!(&svelte[blocks[1].start..blocks[1].end], "\nlet n = 0\n");
+        assert!(script_blocks("<template><p>static</p></template>").is_empty());
+    }
+}
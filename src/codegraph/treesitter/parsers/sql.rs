@@ -0,0 +1,777 @@
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use parking_lot::RwLock;
+use tree_sitter::{Point, Range};
+use uuid::Uuid;
+
+use crate::codegraph::treesitter::ast_instance_structs::{AstSymbolFields, AstSymbolInstanceArc, CommentDefinition, FunctionArg, FunctionCall, FunctionDeclaration, TypeDef};
+use crate::codegraph::treesitter::language_id::LanguageId;
+use crate::codegraph::treesitter::parsers::{AstLanguageParser, ParserError};
+use crate::codegraph::treesitter::parsers::utils::get_guid;
+
+/// SQL stored-procedure analyzer.
+///
+/// No SQL grammar is bundled, and procedural SQL comes in several dialects, so the
+/// source is tokenized directly. `CREATE PROCEDURE`, `CREATE FUNCTION` and
+/// `CREATE TRIGGER` statements, and the procedures and functions of an Oracle
+/// `PACKAGE BODY`, are functions. `CALL p()`, `EXEC p`, `EXECUTE FUNCTION f()` and
+/// calls of non-builtin functions inside statements are calls. When a file uses batch
+/// separators (`GO`, a lone `/`, or a `DELIMITER`) a routine ends at the next one,
+/// otherwise at the end of its dollar-quoted body or of its outermost `BEGIN ... END`.
+/// Unquoted SQL identifiers are case-insensitive, so all names are lowercased.
+pub(crate) struct SqlAnalyzer;
+
+impl SqlAnalyzer {
+    pub fn new() -> Result<Self, ParserError> {
+        Ok(Self)
+    }
+}
+
+impl AstLanguageParser for SqlAnalyzer {
+    fn parse(&mut self, code: &str, path: &PathBuf) -> Vec<AstSymbolInstanceArc> {
+        Scanner::new(code, path).scan()
+    }
+}
+
+static SQL_KEYWORDS: [&str; 133] = [
+    "after", "all", "alter", "and", "any", "as", "asc", "atomic", "before", "begin", "between", "body", "by",
+    "call", "cascade", "case", "catch", "check", "close", "commit", "conflict", "constraint", "continue",
+    "create", "cross", "cursor", "declare", "default", "definer", "delete", "desc", "distinct", "do", "drop",
+    "each", "editionable", "else", "elseif", "elsif", "end", "except", "exception", "exec", "execute", "exists",
+    "exit", "fetch", "filter", "for", "foreign", "from", "full", "function", "group", "handler", "having", "if",
+    "in", "index", "inner", "inout", "insert", "instead", "intersect", "into", "is", "iterate", "join", "key",
+    "language", "lateral", "leave", "left", "like", "limit", "loop", "merge", "noneditionable", "not", "null",
+    "of", "offset", "on", "open", "option", "or", "order", "out", "outer", "output", "over", "package",
+    "partition", "perform", "primary", "print", "proc", "procedure", "raise", "record", "references", "repeat",
+    "replace", "return", "returning", "returns", "right", "rollback", "row", "select", "set", "signal", "table",
+    "temp", "temporary", "then", "top", "tran", "transaction", "trigger", "try", "type", "union", "unique",
+    "until", "update", "using", "values", "view", "when", "where", "while", "with",
+];
+
+/// Keywords that may sit between `CREATE` and the kind of object being created
+static CREATE_MODIFIERS: [&str; 8] = ["or", "replace", "alter", "definer", "editionable", "noneditionable", "temp", "temporary"];
+
+/// A name followed by `(` after one of these is a table, column list or declaration, not a call
+static NON_CALL_CONTEXTS: [&str; 19] = [
+    "as", "body", "call", "constraint", "cursor", "exec", "execute", "function", "index", "insert", "into",
+    "key", "package", "proc", "procedure", "references", "table", "trigger", "with",
+];
+
+/// Parameter modes, skipped when reading parameter names and types
+static PARAMETER_MODES: [&str; 7] = ["in", "out", "inout", "output", "readonly", "variadic", "nocopy"];
+
+/// Builtin functions, types and system procedures of the common dialects
+static SQL_BUILTINS: [&str; 136] = [
+    "abs", "add_months", "age", "avg", "bigint", "binary", "bit", "cast", "ceil", "ceiling", "char",
+    "char_length", "charindex", "clock_timestamp", "coalesce", "concat", "concat_ws", "convert", "count",
+    "count_big", "currval", "date", "date_part", "date_trunc", "dateadd", "datediff", "datefromparts",
+    "datename", "datepart", "datetime", "datetime2", "datetimeoffset", "dec", "decimal", "decode",
+    "dense_rank", "eomonth", "error_line", "error_message", "error_number", "error_procedure",
+    "error_severity", "error_state", "extract", "first_value", "float", "floor", "format", "formatmessage",
+    "gen_random_uuid", "generate_series", "getdate", "getutcdate", "greatest", "group_concat", "identity",
+    "ifnull", "iif", "instr", "int", "integer", "interval", "isnull", "lag", "last_insert_id", "last_value",
+    "lead", "least", "len", "length", "lower", "ltrim", "max", "md5", "min", "mod", "nchar", "newid", "nextval",
+    "now", "nullif", "number", "numeric", "nvarchar", "nvl", "nvl2", "object_id", "openjson", "patindex",
+    "position", "power", "quote_ident", "quote_literal", "quotename", "raise_application_error", "raiserror",
+    "random", "rank", "real", "round", "row_number", "rtrim", "scope_identity", "setval", "smallint",
+    "sp_executesql", "split_part", "sqrt", "string_agg", "string_split", "string_to_array", "stuff", "substr",
+    "substring", "sum", "sysdate", "sysdatetime", "systimestamp", "time", "timestamp", "tinyint", "to_char",
+    "to_date", "to_number", "to_timestamp", "trim", "trunc", "try_cast", "try_convert", "unnest", "upper",
+    "uuid", "varbinary", "varchar", "varchar2", "xact_state",
+];
+
+/// Prefixes of builtin function families (`pg_advisory_lock`, `jsonb_build_object`, ...)
+static BUILTIN_PREFIXES: [&str; 4] = ["pg_", "json", "array_", "regexp_"];
+
+/// Schemas and packages of the database itself; calls through them are not recorded
+static SYSTEM_SCHEMAS: [&str; 4] = ["sys", "pg_catalog", "information_schema", "master"];
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum TokenKind {
+    Name,
+    Keyword,
+    Str,
+    Number,
+    /// `@name`, `@@name` or a `:name` bind variable
+    Variable,
+    Symbol,
+    /// Delimiter of a dollar-quoted body whose contents are tokenized as code
+    DollarQuote,
+    /// `GO`, a lone `/` or a `DELIMITER` delimiter, ending the current batch
+    Separator,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Token<'a> {
+    kind: TokenKind,
+    text: &'a str,
+    start: usize,
+    end: usize,
+}
+
+impl Token<'_> {
+    fn keyword(&self, keyword: &str) -> bool {
+        self.kind == TokenKind::Keyword && self.text.eq_ignore_ascii_case(keyword)
+    }
+
+    fn symbol(&self, symbol: &str) -> bool {
+        self.kind == TokenKind::Symbol && self.text == symbol
+    }
+
+    /// Keyword or unquoted name with the given spelling
+    fn word(&self, word: &str) -> bool {
+        matches!(self.kind, TokenKind::Keyword | TokenKind::Name) && self.text.eq_ignore_ascii_case(word)
+    }
+}
+
+/// Normalized identifier: quotes removed and lowercased
+fn ident(text: &str) -> String {
+    let unquoted = match text.as_bytes().first() {
+        Some(b'"' | b'`' | b'[') => text.get(1..text.len().saturating_sub(1)).unwrap_or(""),
+        _ => text,
+    };
+    unquoted.to_lowercase()
+}
+
+fn is_ident_char(c: char) -> bool {
+    c.is_alphanumeric() || matches!(c, '_' | '$' | '#')
+}
+
+/// Index right after the closing `quote`, where a doubled quote is an escaped one
+fn quoted_end(b: &[u8], i: usize, quote: u8) -> usize {
+    let mut j = i + 1;
+    while j < b.len() {
+        if b[j] == quote {
+            if b.get(j + 1) == Some(&quote) {
+                j += 2;
+                continue;
+            }
+            return j + 1;
+        }
+        j += 1;
+    }
+    b.len()
+}
+
+/// Tag of a dollar quote (`$$`, `$body$`) starting at `i`
+fn dollar_tag(code: &str, i: usize) -> Option<&str> {
+    let b = code.as_bytes();
+    if b.get(i) != Some(&b'$') || b.get(i + 1).is_some_and(u8::is_ascii_digit) {
+        return None;
+    }
+    let len = b[i + 1..].iter().take_while(|c| c.is_ascii_alphanumeric() || **c == b'_').count();
+    (b.get(i + 1 + len) == Some(&b'$')).then(|| &code[i..i + len + 2])
+}
+
+/// Splits SQL source into tokens, returning comment spans separately
+fn tokenize(code: &str) -> (Vec<Token<'_>>, Vec<(usize, usize)>) {
+    const SYMBOLS: [&str; 8] = ["::", ":=", "<>", "<=", ">=", "!=", "||", "=>"];
+    let b = code.as_bytes();
+    let mut tokens = Vec::new();
+    let mut comments = Vec::new();
+    // Statement delimiter set by the MySQL client's `DELIMITER //`
+    let mut delimiter: Option<&str> = None;
+    // Tag of the open dollar quote whose contents are code
+    let mut dollar_body: Option<&str> = None;
+    let mut i = 0;
+    while i < b.len() {
+        if i == 0 || b[i - 1] == b'\n' {
+            let eol = b[i..].iter().position(|&c| c == b'\n').map_or(b.len(), |p| i + p);
+            let line = code[i..eol].trim();
+            let line_start = i + (code[i..eol].len() - code[i..eol].trim_start().len());
+            let directive = line.get(..10).is_some_and(|d| d.eq_ignore_ascii_case("delimiter "));
+            if line.eq_ignore_ascii_case("go") || line == "/" || directive {
+                if directive {
+                    delimiter = Some(line[10..].trim()).filter(|d| *d != ";");
+                }
+                tokens.push(Token { kind: TokenKind::Separator, text: line, start: line_start, end: line_start + line.len() });
+                i = eol;
+                continue;
+            }
+        }
+        let c = b[i];
+        let start = i;
+        let kind = if c.is_ascii_whitespace() {
+            i += 1;
+            continue;
+        } else if let Some(d) = delimiter.filter(|d| code[i..].starts_with(*d)) {
+            i += d.len();
+            TokenKind::Separator
+        } else if code[i..].starts_with("--") {
+            i = b[i..].iter().position(|&c| c == b'\n').map_or(b.len(), |p| i + p);
+            comments.push((start, i));
+            continue;
+        } else if code[i..].starts_with("/*") {
+            i = code[i + 2..].find("*/").map_or(b.len(), |p| i + 2 + p + 2);
+            comments.push((start, i));
+            continue;
+        } else if c == b'\'' {
+            i = quoted_end(b, i, b'\'');
+            TokenKind::Str
+        } else if let Some(tag) = dollar_tag(code, i) {
+            match dollar_body {
+                Some(open) if open == tag => {
+                    dollar_body = None;
+                    i += tag.len();
+                    TokenKind::DollarQuote
+                }
+                None => {
+                    dollar_body = Some(tag);
+                    i += tag.len();
+                    TokenKind::DollarQuote
+                }
+                // a dollar-quoted string inside a dollar-quoted body
+                Some(_) => {
+                    i = code[i + tag.len()..].find(tag).map_or(b.len(), |p| i + tag.len() + p + tag.len());
+                    TokenKind::Str
+                }
+            }
+        } else if c == b'"' || c == b'`' {
+            i = quoted_end(b, i, c);
+            TokenKind::Name
+        } else if c == b'['
+            && b.get(i + 1).is_some_and(|c| c.is_ascii_alphabetic() || *c == b'_')
+            && b[i..].iter().take_while(|&&c| c != b'\n').any(|&c| c == b']')
+        {
+            i += b[i..].iter().position(|&c| c == b']').map_or(1, |p| p + 1);
+            TokenKind::Name
+        } else if (c == b'@' || (c == b':' && b.get(i + 1).is_some_and(|c| c.is_ascii_alphabetic())))
+            && !code[i..].starts_with("::")
+        {
+            i += 1;
+            while i < b.len() && (b[i] == b'@' || code[i..].chars().next().is_some_and(is_ident_char)) {
+                i += code[i..].chars().next().map_or(1, char::len_utf8);
+            }
+            TokenKind::Variable
+        } else if c.is_ascii_digit() {
+            while i < b.len() && (b[i].is_ascii_alphanumeric() || b[i] == b'.') {
+                i += 1;
+            }
+            TokenKind::Number
+        } else if code[i..].chars().next().is_some_and(|ch| ch.is_alphabetic() || ch == '_' || ch == '#') {
+            while let Some(ch) = code[i..].chars().next().filter(|ch| is_ident_char(*ch)) {
+                i += ch.len_utf8();
+            }
+            if SQL_KEYWORDS.contains(&code[start..i].to_lowercase().as_str()) { TokenKind::Keyword } else { TokenKind::Name }
+        } else {
+            i += SYMBOLS.iter().find(|s| code[i..].starts_with(**s)).map_or_else(
+                || code[i..].chars().next().map_or(1, char::len_utf8),
+                |s| s.len(),
+            );
+            TokenKind::Symbol
+        };
+        tokens.push(Token { kind, text: &code[start..i], start, end: i });
+    }
+    (tokens, comments)
+}
+
+/// Index of the object keyword of a `CREATE`/`ALTER` statement starting at `i`
+/// when it creates a routine or package
+fn routine_keyword(tokens: &[Token], i: usize) -> Option<usize> {
+    for (j, token) in tokens.iter().enumerate().skip(i + 1).take(8) {
+        if token.symbol("(") || token.symbol(";") || token.kind == TokenKind::Separator {
+            return None;
+        }
+        if token.kind != TokenKind::Keyword || CREATE_MODIFIERS.contains(&token.text.to_lowercase().as_str()) {
+            continue;
+        }
+        return ["procedure", "proc", "function", "trigger", "package"].iter().any(|k| token.keyword(k)).then_some(j);
+    }
+    None
+}
+
+/// Index of the last name of the `a.b.c` path starting at `i`
+fn path_end(tokens: &[Token], i: usize) -> Option<usize> {
+    if tokens.get(i)?.kind != TokenKind::Name {
+        return None;
+    }
+    let mut last = i;
+    while tokens.get(last + 1).is_some_and(|t| t.symbol("."))
+        && tokens.get(last + 2).is_some_and(|t| t.kind == TokenKind::Name)
+    {
+        last += 2;
+    }
+    Some(last)
+}
+
+/// Start index of the `a.b.c` path ending at token `last`
+fn path_start(tokens: &[Token], last: usize) -> usize {
+    let mut start = last;
+    while start >= 2 && tokens[start - 1].symbol(".") && tokens[start - 2].kind == TokenKind::Name {
+        start -= 2;
+    }
+    start
+}
+
+fn path_segments(tokens: &[Token], start: usize, last: usize) -> Vec<String> {
+    (start..=last).step_by(2).map(|k| ident(tokens[k].text)).collect()
+}
+
+fn is_builtin(name: &str) -> bool {
+    SQL_BUILTINS.contains(&name) || BUILTIN_PREFIXES.iter().any(|p| name.starts_with(p))
+}
+
+/// Name a call is recorded under: `proc` or `schema.proc`, `None` for builtins
+fn call_name(segments: &[String]) -> Option<String> {
+    let name = segments.last()?;
+    match segments {
+        [.., qualifier, _] => {
+            let system = SYSTEM_SCHEMAS.contains(&qualifier.as_str())
+                || qualifier.starts_with("dbms_")
+                || qualifier.starts_with("utl_");
+            (!system).then(|| format!("{}.{}", qualifier, name))
+        }
+        _ => (!is_builtin(name)).then(|| name.clone()),
+    }
+}
+
+/// Schema or package the routines of a SQL file belong to: the name of its Oracle
+/// `PACKAGE BODY`, otherwise the schema qualifying its first routine
+pub(crate) fn file_namespace(code: &str) -> Option<String> {
+    let (tokens, _) = tokenize(code);
+    let routines: Vec<usize> = tokens
+        .iter()
+        .enumerate()
+        .filter(|(_, t)| t.keyword("create") || t.keyword("alter"))
+        .filter_map(|(i, _)| routine_keyword(&tokens, i))
+        .collect();
+    let package = routines.iter().find_map(|&j| {
+        (tokens[j].keyword("package") && tokens.get(j + 1)?.keyword("body")).then_some(())?;
+        let last = path_end(&tokens, j + 2)?;
+        Some(ident(tokens[last].text))
+    });
+    package.or_else(|| {
+        routines.iter().filter(|&&j| !tokens[j].keyword("package")).find_map(|&j| {
+            let last = path_end(&tokens, j + 1)?;
+            (last > j + 1).then(|| ident(tokens[last - 2].text))
+        })
+    })
+}
+
+struct Routine {
+    /// Index into `Scanner::functions`
+    index: usize,
+    /// Declared inside a package body; always ends at its own `END`
+    member: bool,
+    /// The body is dollar-quoted and ends with the closing quote
+    dollar: bool,
+    /// `AS`, `IS`, `DECLARE` or `BEGIN` has started the body
+    body_started: bool,
+    /// The body is procedural code, so a `;` does not end the routine
+    code_body: bool,
+}
+
+enum Frame {
+    Routine(Routine),
+    Package,
+    Begin,
+    Case,
+}
+
+struct Scanner<'a> {
+    code: &'a str,
+    path: &'a PathBuf,
+    line_starts: Vec<usize>,
+    root_guid: Uuid,
+    functions: Vec<FunctionDeclaration>,
+    calls: Vec<FunctionCall>,
+    stack: Vec<Frame>,
+    /// Inside a dollar-quoted body
+    in_dollar: bool,
+    /// The file ends its batches with separators
+    separated: bool,
+}
+
+impl<'a> Scanner<'a> {
+    fn new(code: &'a str, path: &'a PathBuf) -> Self {
+        let line_starts = std::iter::once(0)
+            .chain(code.bytes().enumerate().filter(|(_, c)| *c == b'\n').map(|(i, _)| i + 1))
+            .collect();
+        Self {
+            code,
+            path,
+            line_starts,
+            root_guid: get_guid(),
+            functions: vec![],
+            calls: vec![],
+            stack: vec![],
+            in_dollar: false,
+            separated: false,
+        }
+    }
+
+    fn point(&self, byte: usize) -> Point {
+        let row = self.line_starts.partition_point(|&start| start <= byte) - 1;
+        Point { row, column: byte - self.line_starts[row] }
+    }
+
+    fn range(&self, start: usize, end: usize) -> Range {
+        Range { start_byte: start, end_byte: end, start_point: self.point(start), end_point: self.point(end) }
+    }
+
+    fn fields(&self, name: &str, start: usize, end: usize, parent: Option<Uuid>) -> AstSymbolFields {
+        let mut fields = AstSymbolFields::from_data(LanguageId::Sql, self.path.clone(), false);
+        fields.guid = get_guid();
+        fields.name = name.to_string();
+        fields.parent_guid = Some(parent.unwrap_or(self.root_guid));
+        fields.full_range = self.range(start, end);
+        fields.declaration_range = fields.full_range;
+        fields.definition_range = fields.full_range;
+        fields
+    }
+
+    /// Innermost open routine
+    fn enclosing(&self) -> Option<Uuid> {
+        self.stack.iter().rev().find_map(|frame| match frame {
+            Frame::Routine(routine) => Some(self.functions[routine.index].ast_fields.guid),
+            _ => None,
+        })
+    }
+
+    fn top_routine(&mut self) -> Option<&mut Routine> {
+        match self.stack.last_mut() {
+            Some(Frame::Routine(routine)) => Some(routine),
+            _ => None,
+        }
+    }
+
+    fn scan(mut self) -> Vec<AstSymbolInstanceArc> {
+        let (tokens, comments) = tokenize(self.code);
+        self.separated = tokens.iter().any(|t| t.kind == TokenKind::Separator);
+
+        for i in 0..tokens.len() {
+            let token = tokens[i];
+            match token.kind {
+                TokenKind::Separator => {
+                    let end = if i > 0 { tokens[i - 1].end } else { token.start };
+                    self.close_all(end);
+                }
+                TokenKind::DollarQuote => self.dollar_quote(),
+                TokenKind::Keyword => self.keyword(&tokens, i),
+                TokenKind::Symbol if token.text == "(" => self.expression_call(&tokens, i),
+                TokenKind::Symbol if token.text == ";" => {
+                    let in_dollar = self.in_dollar;
+                    if let Some(routine) = self.top_routine().filter(|r| !r.code_body && !in_dollar) {
+                        let index = routine.index;
+                        self.stack.pop();
+                        self.close_routine(index, token.end);
+                    }
+                }
+                _ => {}
+            }
+        }
+        self.close_all(tokens.last().map_or(0, |t| t.end));
+
+        let comment_ranges: Vec<Range> = comments.iter().map(|&(start, end)| self.range(start, end)).collect();
+        let mut symbols: Vec<AstSymbolInstanceArc> = vec![];
+        let mut children: HashMap<Uuid, Vec<Uuid>> = HashMap::new();
+        for fields in self.calls.iter().map(|c| &c.ast_fields) {
+            if let Some(parent) = fields.parent_guid {
+                children.entry(parent).or_default().push(fields.guid);
+            }
+        }
+        for mut function in self.functions {
+            function.ast_fields.childs_guid = children.remove(&function.ast_fields.guid).unwrap_or_default();
+            symbols.push(Arc::new(RwLock::new(Box::new(function))));
+        }
+        for call in self.calls {
+            symbols.push(Arc::new(RwLock::new(Box::new(call))));
+        }
+        for range in comment_ranges {
+            let mut ast_fields = AstSymbolFields::from_data(LanguageId::Sql, self.path.clone(), false);
+            ast_fields.guid = get_guid();
+            ast_fields.parent_guid = Some(self.root_guid);
+            ast_fields.full_range = range;
+            let comment = CommentDefinition { ast_fields };
+            symbols.push(Arc::new(RwLock::new(Box::new(comment))));
+        }
+        symbols
+    }
+
+    fn keyword(&mut self, tokens: &[Token<'a>], i: usize) {
+        let token = tokens[i];
+        let next = tokens.get(i + 1);
+        let prev = i.checked_sub(1).map(|p| tokens[p]);
+        match token.text.to_lowercase().as_str() {
+            "create" => self.create(tokens, i),
+            "alter" if !prev.is_some_and(|p| p.keyword("or")) => self.create(tokens, i),
+            "procedure" | "function" if matches!(self.stack.last(), Some(Frame::Package)) => {
+                self.declare(tokens, i, token.start, true);
+            }
+            "as" | "is" | "declare" => {
+                let code_body = !next.is_some_and(|t| matches!(t.kind, TokenKind::Str | TokenKind::DollarQuote));
+                if let Some(routine) = self.top_routine().filter(|r| !r.body_started) {
+                    routine.body_started = true;
+                    routine.code_body = code_body;
+                }
+            }
+            "begin" => {
+                let transaction = next.is_none_or(|t| {
+                    t.symbol(";")
+                        || t.kind == TokenKind::Separator
+                        || ["tran", "transaction", "work", "distributed"].iter().any(|w| t.word(w))
+                });
+                if transaction {
+                    return;
+                }
+                if let Some(routine) = self.top_routine().filter(|r| !r.body_started) {
+                    routine.body_started = true;
+                    routine.code_body = true;
+                }
+                self.stack.push(Frame::Begin);
+            }
+            "case" if !prev.is_some_and(|p| p.keyword("end")) => self.stack.push(Frame::Case),
+            "end" => {
+                if next.is_some_and(|t| ["if", "loop", "while", "repeat", "for"].iter().any(|w| t.word(w))) {
+                    return;
+                }
+                match self.stack.last() {
+                    Some(Frame::Begin | Frame::Case) => {}
+                    Some(Frame::Package) => {
+                        self.stack.pop();
+                        return;
+                    }
+                    _ => return,
+                }
+                let closed_begin = matches!(self.stack.pop(), Some(Frame::Begin));
+                let separated = self.separated;
+                let ends = self.top_routine().filter(|r| closed_begin && (r.member || (!separated && !r.dollar))).map(|r| r.index);
+                if let Some(index) = ends {
+                    // `END name` of PL/SQL
+                    let end = next.filter(|t| t.kind == TokenKind::Name).map_or(token.end, |t| t.end);
+                    self.stack.pop();
+                    self.close_routine(index, end);
+                }
+            }
+            "call" => self.statement_call(tokens, i + 1),
+            "exec" | "execute" => {
+                let mut j = i + 1;
+                if tokens.get(j).is_some_and(|t| t.kind == TokenKind::Variable) && tokens.get(j + 1).is_some_and(|t| t.symbol("=")) {
+                    j += 2;
+                }
+                if tokens.get(j).is_some_and(|t| t.keyword("function") || t.keyword("procedure")) {
+                    j += 1;
+                }
+                self.statement_call(tokens, j);
+            }
+            _ => {}
+        }
+    }
+
+    /// `CREATE [OR REPLACE] PROCEDURE|FUNCTION|TRIGGER|PACKAGE BODY ...`
+    fn create(&mut self, tokens: &[Token<'a>], i: usize) {
+        if self.stack.iter().any(|f| matches!(f, Frame::Begin | Frame::Case)) {
+            return;
+        }
+        let routine = routine_keyword(tokens, i);
+        if routine.is_some() || !self.separated {
+            let end = if i > 0 { tokens[i - 1].end } else { tokens[i].start };
+            self.close_all(end);
+        }
+        let Some(j) = routine else {
+            return;
+        };
+        if tokens[j].keyword("package") {
+            if tokens.get(j + 1).is_some_and(|t| t.keyword("body")) {
+                self.stack.push(Frame::Package);
+            }
+            return;
+        }
+        self.declare(tokens, j, tokens[i].start, false);
+    }
+
+    /// Records the routine named after the `PROCEDURE`/`FUNCTION`/`TRIGGER` keyword at `j`
+    fn declare(&mut self, tokens: &[Token<'a>], j: usize, start: usize, member: bool) {
+        let Some(last) = path_end(tokens, j + 1) else {
+            return;
+        };
+        let name = ident(tokens[last].text);
+        let mut k = last + 1;
+        let mut args = vec![];
+        if tokens.get(k).is_some_and(|t| t.symbol("(")) {
+            let close = Self::matching_paren(tokens, k);
+            args = self.parameters(&tokens[k + 1..close]);
+            k = (close + 1).min(tokens.len());
+        } else if tokens.get(k).is_some_and(|t| t.kind == TokenKind::Variable) {
+            // T-SQL parameters are not parenthesized
+            let end = (k..tokens.len())
+                .find(|&m| {
+                    let t = &tokens[m];
+                    t.keyword("as") || t.keyword("with") || t.keyword("for") || t.symbol(";") || t.kind == TokenKind::Separator
+                })
+                .unwrap_or(tokens.len());
+            args = self.parameters(&tokens[k..end]);
+            k = end;
+        }
+        let mut header_end = tokens[k - 1].end;
+
+        let mut return_type = None;
+        if tokens.get(k).is_some_and(|t| t.keyword("returns") || t.keyword("return")) && k + 1 < tokens.len() {
+            let mut m = k + 1;
+            let mut depth = 0;
+            while let Some(t) = tokens.get(m + 1) {
+                if t.symbol("(") {
+                    depth += 1;
+                } else if t.symbol(")") {
+                    depth -= 1;
+                } else if depth == 0
+                    && (matches!(t.kind, TokenKind::Keyword | TokenKind::Str | TokenKind::DollarQuote | TokenKind::Separator) || t.symbol(";"))
+                {
+                    break;
+                }
+                m += 1;
+            }
+            header_end = tokens[m].end;
+            return_type = Some(TypeDef {
+                name: Some(self.code[tokens[k + 1].start..header_end].to_string()),
+                ..Default::default()
+            });
+        }
+
+        let declaration = FunctionDeclaration {
+            ast_fields: self.fields(&name, start, header_end, None),
+            args,
+            return_type,
+            ..Default::default()
+        };
+        self.functions.push(declaration);
+        self.stack.push(Frame::Routine(Routine {
+            index: self.functions.len() - 1,
+            member,
+            dollar: false,
+            body_started: false,
+            code_body: false,
+        }));
+    }
+
+    /// Parameters separated by top-level commas: `[mode] name [mode] type [= default]`
+    fn parameters(&self, tokens: &[Token<'a>]) -> Vec<FunctionArg> {
+        let mut args = vec![];
+        let mut depth = 0;
+        let mut param: Vec<Token> = vec![];
+        for token in tokens.iter().chain(std::iter::once(&Token { kind: TokenKind::Symbol, text: ",", start: 0, end: 0 })) {
+            if token.symbol("(") {
+                depth += 1;
+            } else if token.symbol(")") {
+                depth -= 1;
+            } else if depth == 0 && token.symbol(",") {
+                let words: Vec<&Token> = param
+                    .iter()
+                    .take_while(|t| !t.symbol("=") && !t.symbol(":=") && !t.keyword("default"))
+                    .filter(|t| !PARAMETER_MODES.contains(&t.text.to_lowercase().as_str()))
+                    .collect();
+                if let Some(first) = words.first() {
+                    let (name, type_words) = if words.len() > 1 || first.kind == TokenKind::Variable {
+                        (ident(first.text), &words[1..])
+                    } else {
+                        // unnamed PostgreSQL parameter: only the type is given
+                        (String::new(), &words[..])
+                    };
+                    let type_ = type_words.first().zip(type_words.last()).map(|(a, b)| TypeDef {
+                        name: Some(self.code[a.start..b.end].to_string()),
+                        ..Default::default()
+                    });
+                    args.push(FunctionArg { name, type_ });
+                }
+                param.clear();
+                continue;
+            }
+            param.push(*token);
+        }
+        args
+    }
+
+    fn dollar_quote(&mut self) {
+        if !self.in_dollar {
+            self.in_dollar = true;
+            if let Some(routine) = self.top_routine() {
+                routine.dollar = true;
+                routine.body_started = true;
+            }
+            return;
+        }
+        self.in_dollar = false;
+        let position = self.stack.iter().rposition(|f| matches!(f, Frame::Routine(r) if r.dollar));
+        if let Some(position) = position {
+            // the statement goes on with `LANGUAGE plpgsql;`, which ends the routine
+            self.stack.truncate(position + 1);
+            if let Some(routine) = self.top_routine() {
+                routine.code_body = false;
+            }
+        }
+    }
+
+    fn close_routine(&mut self, index: usize, end: usize) {
+        let start = self.functions[index].ast_fields.full_range.start_byte;
+        let body_start = self.functions[index].ast_fields.declaration_range.end_byte;
+        self.functions[index].ast_fields.full_range = self.range(start, end);
+        self.functions[index].ast_fields.definition_range = self.range(body_start, end);
+    }
+
+    /// Ends every open routine at `end`
+    fn close_all(&mut self, end: usize) {
+        for frame in std::mem::take(&mut self.stack).into_iter().rev() {
+            if let Frame::Routine(routine) = frame {
+                let start = self.functions[routine.index].ast_fields.full_range.start_byte;
+                self.close_routine(routine.index, end.max(start));
+            }
+        }
+    }
+
+    /// The routine named after `CALL`, `EXEC` or `EXECUTE FUNCTION` at token `j`
+    fn statement_call(&mut self, tokens: &[Token<'a>], j: usize) {
+        let Some(last) = path_end(tokens, j) else {
+            return;
+        };
+        let end = if tokens.get(last + 1).is_some_and(|t| t.symbol("(")) {
+            tokens[Self::matching_paren(tokens, last + 1).min(tokens.len() - 1)].end
+        } else {
+            tokens[last].end
+        };
+        self.record_call(&path_segments(tokens, j, last), tokens[j].start, end);
+    }
+
+    /// A name path followed by `(` is a function call unless it names a table,
+    /// a column list or the routine being declared
+    fn expression_call(&mut self, tokens: &[Token<'a>], i: usize) {
+        if i == 0 || tokens[i - 1].kind != TokenKind::Name {
+            return;
+        }
+        let start = path_start(tokens, i - 1);
+        let context = start.checked_sub(1).map(|p| tokens[p]);
+        if context.is_some_and(|t| t.kind == TokenKind::Keyword && NON_CALL_CONTEXTS.contains(&t.text.to_lowercase().as_str())) {
+            return;
+        }
+        let end = tokens[Self::matching_paren(tokens, i).min(tokens.len() - 1)].end;
+        self.record_call(&path_segments(tokens, start, i - 1), tokens[start].start, end);
+    }
+
+    fn record_call(&mut self, segments: &[String], start: usize, end: usize) {
+        let Some(name) = call_name(segments) else {
+            return;
+        };
+        let call = FunctionCall {
+            ast_fields: self.fields(&name, start, end, self.enclosing()),
+            ..Default::default()
+        };
+        self.calls.push(call);
+    }
+
+    /// Index of the `)` closing the `(` at token `i`, or the end of the tokens
+    fn matching_paren(tokens: &[Token], i: usize) -> usize {
+        let mut depth = 0;
+        for (k, token) in tokens.iter().enumerate().skip(i) {
+            if token.symbol("(") {
+                depth += 1;
+            } else if token.symbol(")") {
+                depth -= 1;
+                if depth == 0 {
+                    return k;
+                }
+            }
+        }
+        tokens.len()
+    }
+}
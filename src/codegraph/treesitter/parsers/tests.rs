@@ -41,6 +41,15 @@ mod cpp;
 mod ts;
 mod js;
 mod go;
+mod kotlin;
+mod ruby;
+mod swift;
+mod scala;
+mod objc;
+mod lua;
+mod bash;
+mod sql;
+mod proto;
 
 pub(crate) fn print(symbols: &Vec<AstSymbolInstanceArc>, code: &str) {
     let guid_to_symbol_map = symbols.iter()
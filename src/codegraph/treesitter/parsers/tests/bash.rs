@@ -0,0 +1,65 @@
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use crate::codegraph::treesitter::parsers::AstLanguageParser;
+    use crate::codegraph::treesitter::parsers::bash::BashAnalyzer;
+    use crate::codegraph::treesitter::parsers::tests::base_parser_test;
+    use crate::codegraph::treesitter::structs::SymbolType;
+
+    const MAIN_SH_CODE: &str = include_str!("cases/bash/main.sh");
+    const MAIN_SH_SYMBOLS: &str = include_str!("cases/bash/main.sh.json");
+
+    #[test]
+    fn parser_test() {
+        let mut parser: Box<dyn AstLanguageParser> = Box::new(BashAnalyzer::new().expect("BashAnalyzer::new"));
+        let path = PathBuf::from("/main.sh");
+        base_parser_test(&mut parser, &path, MAIN_SH_CODE, MAIN_SH_SYMBOLS);
+    }
+
+    #[test]
+    fn functions_and_calls_test() {
+        let mut parser = BashAnalyzer::new().unwrap();
+        let symbols = parser.parse(MAIN_SH_CODE, &PathBuf::from("/main.sh"));
+        let by_type = |t: SymbolType| {
+            symbols
+                .iter()
+                .filter(|s| s.read().symbol_type() == t)
+                .map(|s| {
+                    let s = s.read();
+                    (s.name().to_string(), s.full_range().start_point.row + 1, s.full_range().end_point.row + 1)
+                })
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            by_type(SymbolType::FunctionDeclaration),
+            vec![("main.sh".to_string(), 1, 22), ("build".to_string(), 6, 12), ("deploy".to_string(), 14, 20)]
+        );
+        let calls: Vec<(String, usize)> = by_type(SymbolType::FunctionCall).into_iter().map(|(name, line, _)| (name, line)).collect();
+        assert_eq!(
+            calls,
+            vec![
+                ("common.sh".to_string(), 3),
+                ("compute_path".to_string(), 7),
+                ("compile".to_string(), 8),
+                ("push.sh".to_string(), 16),
+                ("log_error".to_string(), 17),
+                ("notify.sh".to_string(), 19),
+                ("build".to_string(), 22),
+                ("deploy".to_string(), 22),
+            ]
+        );
+        assert_eq!(by_type(SymbolType::ImportDeclaration), vec![("common.sh".to_string(), 3, 3)]);
+        assert_eq!(by_type(SymbolType::CommentDefinition).len(), 2);
+
+        let parent_of = |name: &str| {
+            let call = symbols.iter().find(|s| s.read().symbol_type() == SymbolType::FunctionCall && s.read().name() == name).unwrap();
+            let parent = *call.read().parent_guid();
+            symbols.iter().find(|s| Some(*s.read().guid()) == parent).map(|s| s.read().name().to_string())
+        };
+        assert_eq!(parent_of("compute_path").as_deref(), Some("build"));
+        assert_eq!(parent_of("push.sh").as_deref(), Some("deploy"));
+        assert_eq!(parent_of("deploy").as_deref(), Some("main.sh"));
+    }
+}
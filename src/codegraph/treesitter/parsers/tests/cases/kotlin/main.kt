@@ -0,0 +1,29 @@
+package com.example.app
+
+import com.example.util.Logger
+import kotlin.math.max as maximum
+
+// loads users
+class UserService(private val repo: Repository) : BaseService(), Auditable {
+    fun load(id: Int): User {
+        val user = repo.find(id)
+        Logger.info("loaded")
+        return helper(user).also { audit(it) }
+    }
+
+    private fun helper(u: User) = u.copy()
+
+    companion object {
+        fun create(): UserService = UserService(Repository())
+    }
+}
+
+interface Auditable {
+    fun audit(x: Any)
+}
+
+object Registry {
+    fun register() { maximum(1, 2) }
+}
+
+fun String.shout(): String = uppercase()
@@ -0,0 +1,20 @@
+local utils = require("game.utils")
+local M = {}
+
+--[[ spawns an enemy
+     at the given position ]]
+function M.spawn(x, y)
+  local e = utils.clamp(x, 0, 10)
+  if e > 5 then
+    string.format("%d", e)
+  end
+  return M.track(e)
+end
+
+local function track(e)
+  self:emit "spawned"
+end
+
+M.track = function(e)
+  for _, v in ipairs({e}) do print(v) end
+end
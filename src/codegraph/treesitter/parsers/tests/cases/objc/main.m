@@ -0,0 +1,32 @@
+#import "Engine.h"
+
+// A car with an engine
+@interface Car : NSObject {
+    int _speed;
+}
+@property (nonatomic, copy) NSString *name;
+- (void)driveTo:(NSString *)place speed:(int)speed;
++ (instancetype)sharedCar;
+@end
+
+@implementation Car
+- (void)driveTo:(NSString *)place speed:(int)speed {
+    [self.engine startWithPower:speed
+                           mode:@"eco"];
+    [[Logger shared] log:[place uppercaseString]];
+    [self finish];
+}
+
++ (instancetype)sharedCar {
+    return [[Car alloc] init];
+}
+
+- (void)finish {
+    NSLog(@"done");
+}
+@end
+
+int main(int argc, char *argv[]) {
+    [[Car sharedCar] driveTo:@"home" speed:3];
+    return 0;
+}
@@ -0,0 +1,33 @@
+require 'json'
+require_relative 'lib/helper'
+
+# Billing module
+module Billing
+  class Invoice < Base
+    include Comparable
+
+    def initialize(items, limit = 10, *rest, key:, &block)
+      @items = items
+      setup
+    end
+
+    def total
+      @items.map { |i| price(i) }.sum
+    end
+
+    def self.build(data)
+      new(JSON.parse(data))
+    end
+
+    private
+
+    def price(item)
+      Helper.round item.amount
+    end
+  end
+end
+
+def main
+  invoice = Billing::Invoice.new("[]")
+  puts invoice.total
+end
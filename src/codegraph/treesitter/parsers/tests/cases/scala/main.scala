@@ -0,0 +1,30 @@
+package com.example.shop
+
+import scala.concurrent.Future
+import com.example.util.{Logger, Json => J}
+
+// Cart things
+trait Pricing {
+  def price(item: Item): Double
+}
+
+class Cart(items: List[Item]) extends Base with Pricing {
+  def total: Double = items.map(price).sum
+
+  def price(item: Item): Double = {
+    Logger.info("pricing")
+    discount(item.amount)
+  }
+
+  private def discount(x: Double) = x * 0.9
+}
+
+object Cart {
+  def apply(items: Item*): Cart = new Cart(items.toList)
+
+  def main(args: Array[String]): Unit = {
+    val cart = Cart(Item(1))
+    println(cart.total max 10)
+    cart total
+  }
+}
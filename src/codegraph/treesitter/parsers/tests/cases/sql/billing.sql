@@ -0,0 +1,20 @@
+CREATE OR REPLACE FUNCTION billing.charge(p_account int, amount numeric)
+RETURNS void LANGUAGE plpgsql AS $$
+BEGIN
+  PERFORM billing.audit('charge', p_account);
+  IF amount > 0 THEN
+    CALL apply_charge(p_account, amount);
+  END IF;
+END;
+$$;
+
+CREATE TRIGGER charge_audit AFTER INSERT ON charges
+  FOR EACH ROW EXECUTE FUNCTION billing.audit_row();
+
+CREATE FUNCTION billing.audit(kind text, account int) RETURNS void AS 'INSERT INTO audit VALUES ($1, $2)' LANGUAGE sql;
+
+CREATE PROCEDURE apply_charge(IN account INT, IN amount DECIMAL(10,2))
+BEGIN
+  UPDATE accounts SET balance = balance - amount WHERE id = account;
+  CALL notify_owner(account);
+END;
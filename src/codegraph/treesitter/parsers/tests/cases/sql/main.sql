@@ -0,0 +1,27 @@
+-- order processing
+CREATE PROCEDURE [dbo].[usp_PlaceOrder]
+    @CustomerId INT,
+    @Total DECIMAL(10, 2) = 0 OUTPUT
+AS
+BEGIN
+    SET NOCOUNT ON;
+    BEGIN TRANSACTION;
+    BEGIN TRY
+        EXEC dbo.usp_ReserveStock @CustomerId;
+        INSERT INTO Orders (CustomerId, Total) VALUES (@CustomerId, dbo.fn_Tax(@Total));
+        COMMIT;
+    END TRY
+    BEGIN CATCH
+        EXEC @rc = usp_LogError;
+        EXEC sp_executesql N'SELECT 1';
+    END CATCH
+END
+GO
+
+CREATE OR ALTER FUNCTION dbo.fn_Tax (@Amount DECIMAL(10, 2))
+RETURNS DECIMAL(10, 2)
+AS
+BEGIN
+    RETURN CASE WHEN @Amount > 0 THEN ROUND(@Amount * 0.2, 2) ELSE 0 END;
+END
+GO
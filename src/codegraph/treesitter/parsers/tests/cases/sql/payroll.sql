@@ -0,0 +1,15 @@
+CREATE OR REPLACE PACKAGE BODY payroll AS
+  PROCEDURE log_run(p_msg VARCHAR2) IS
+  BEGIN
+    DBMS_OUTPUT.PUT_LINE(p_msg);
+  END log_run;
+
+  FUNCTION run(p_month IN NUMBER) RETURN NUMBER IS
+    v_total NUMBER;
+  BEGIN
+    log_run('start');
+    v_total := ledger.total_for(p_month);
+    RETURN v_total;
+  END run;
+END payroll;
+/
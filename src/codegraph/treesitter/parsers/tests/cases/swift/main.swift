@@ -0,0 +1,33 @@
+import Foundation
+import MyKit
+
+// A protocol
+protocol Greeter {
+    func greet(name: String) -> String
+}
+
+class Service: NSObject, Greeter {
+    let store = Store()
+
+    init(store: Store) {
+        super.init()
+        configure()
+    }
+
+    func greet(name: String) -> String {
+        return format(name).uppercased()
+    }
+
+    private func format(_ s: String) -> String { s }
+}
+
+extension Service {
+    func reset() {
+        store.clear()
+        items.forEach { log($0) }
+    }
+}
+
+func main() {
+    print(Service(store: Store()).greet(name: "x"))
+}
@@ -90,6 +90,39 @@ func main() {
         println!("GoParser successfully parsed {} symbols", symbols.len());
     }
 
+    #[test]
+    fn interface_declaration_test() {
+        // 接口与结构体一样解析为类型声明
+        let code = r#"
+package shapes
+
+type Shape interface {
+    Area() float64
+}
+
+type Circle struct {
+    R float64
+}
+
+func (c *Circle) Area() float64 {
+    return c.R * c.R
+}
+"#;
+        let path = std::path::PathBuf::from("/shapes.go");
+        let mut parser: Box<dyn AstLanguageParser> = Box::new(GoParser::new().expect("GoParser::new"));
+        let symbols = parser.parse(code, &path);
+
+        let names = |symbol_type: SymbolType| -> Vec<String> {
+            symbols.iter()
+                .filter(|s| s.read().symbol_type() == symbol_type)
+                .map(|s| s.read().name().to_string())
+                .collect()
+        };
+        assert_eq!(names(SymbolType::StructDeclaration), vec!["Shape", "Circle"]);
+        // 接口中的方法签名不是函数声明
+        assert_eq!(names(SymbolType::FunctionDeclaration), vec!["Area"]);
+    }
+
     #[test]
     fn debug_shape_parsing() {
         let code = include_str!("./cases/go/shape.go");
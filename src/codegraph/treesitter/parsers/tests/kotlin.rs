@@ -0,0 +1,64 @@
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use crate::codegraph::treesitter::parsers::AstLanguageParser;
+    use crate::codegraph::treesitter::parsers::kotlin::KotlinAnalyzer;
+    use crate::codegraph::treesitter::parsers::tests::base_parser_test;
+    use crate::codegraph::treesitter::structs::SymbolType;
+
+    const MAIN_KT_CODE: &str = include_str!("cases/kotlin/main.kt");
+    const MAIN_KT_SYMBOLS: &str = include_str!("cases/kotlin/main.kt.json");
+
+    #[test]
+    fn parser_test() {
+        let mut parser: Box<dyn AstLanguageParser> = Box::new(KotlinAnalyzer::new().expect("KotlinAnalyzer::new"));
+        let path = PathBuf::from("/main.kt");
+        base_parser_test(&mut parser, &path, MAIN_KT_CODE, MAIN_KT_SYMBOLS);
+    }
+
+    #[test]
+    fn functions_and_calls_test() {
+        let mut analyzer = KotlinAnalyzer::new().unwrap();
+        let symbols = analyzer.parse(MAIN_KT_CODE, &PathBuf::from("/main.kt"));
+        let by_type = |t: SymbolType| {
+            let mut found: Vec<(String, usize)> = symbols
+                .iter()
+                .filter(|s| s.read().symbol_type() == t)
+                .map(|s| (s.read().name().to_string(), s.read().full_range().start_point.row + 1))
+                .collect();
+            found.sort_by_key(|(name, line)| (*line, name.clone()));
+            found
+        };
+        let names = |t: SymbolType| by_type(t).into_iter().map(|(name, _)| name).collect::<Vec<_>>();
+
+        assert_eq!(
+            by_type(SymbolType::FunctionDeclaration),
+            vec![
+                ("load".to_string(), 8),
+                ("helper".to_string(), 14),
+                ("create".to_string(), 17),
+                ("audit".to_string(), 22),
+                ("register".to_string(), 26),
+                ("shout".to_string(), 29),
+            ]
+        );
+        assert_eq!(names(SymbolType::StructDeclaration), vec!["UserService", "Auditable", "Registry"]);
+        assert_eq!(
+            names(SymbolType::FunctionCall),
+            vec!["BaseService", "find", "info", "also", "audit", "helper", "copy", "Repository", "UserService", "maximum", "uppercase"]
+        );
+        assert_eq!(names(SymbolType::ImportDeclaration), vec!["Logger", "maximum"]);
+        assert_eq!(by_type(SymbolType::CommentDefinition).len(), 1);
+
+        // Methods, including those of a companion object, belong to their class
+        let owner = |name: &str| {
+            let function = symbols.iter().find(|s| s.read().symbol_type() == SymbolType::FunctionDeclaration && s.read().name() == name).unwrap();
+            let parent_guid = (*function.read().parent_guid()).unwrap();
+            symbols.iter().find(|s| *s.read().guid() == parent_guid).map(|s| s.read().name().to_string())
+        };
+        assert_eq!(owner("create").as_deref(), Some("UserService"));
+        assert_eq!(owner("audit").as_deref(), Some("Auditable"));
+        assert_eq!(owner("shout"), None);
+    }
+}
@@ -0,0 +1,45 @@
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use crate::codegraph::treesitter::parsers::AstLanguageParser;
+    use crate::codegraph::treesitter::parsers::lua::LuaParser;
+    use crate::codegraph::treesitter::parsers::tests::base_parser_test;
+    use crate::codegraph::treesitter::structs::SymbolType;
+
+    const MAIN_LUA_CODE: &str = include_str!("cases/lua/main.lua");
+    const MAIN_LUA_SYMBOLS: &str = include_str!("cases/lua/main.lua.json");
+
+    #[test]
+    fn parser_test() {
+        let mut parser: Box<dyn AstLanguageParser> = Box::new(LuaParser::new().expect("LuaParser::new"));
+        let path = PathBuf::from("/main.lua");
+        base_parser_test(&mut parser, &path, MAIN_LUA_CODE, MAIN_LUA_SYMBOLS);
+    }
+
+    #[test]
+    fn functions_and_calls_test() {
+        let mut parser = LuaParser::new().unwrap();
+        let symbols = parser.parse(MAIN_LUA_CODE, &PathBuf::from("/main.lua"));
+        let by_type = |t: SymbolType| {
+            symbols
+                .iter()
+                .filter(|s| s.read().symbol_type() == t)
+                .map(|s| {
+                    let s = s.read();
+                    (s.name().to_string(), s.full_range().start_point.row + 1, s.full_range().end_point.row + 1)
+                })
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            by_type(SymbolType::FunctionDeclaration),
+            vec![("spawn".to_string(), 6, 12), ("track".to_string(), 14, 16), ("track".to_string(), 18, 20)]
+        );
+        let calls: Vec<String> = by_type(SymbolType::FunctionCall).into_iter().map(|(name, _, _)| name).collect();
+        assert_eq!(calls, vec!["utils.clamp", "string.format", "track", "emit", "ipairs", "print"]);
+        let imports = by_type(SymbolType::ImportDeclaration);
+        assert_eq!(imports, vec![("utils".to_string(), 1, 1)]);
+        assert_eq!(by_type(SymbolType::CommentDefinition).len(), 1);
+    }
+}
@@ -0,0 +1,63 @@
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use crate::codegraph::treesitter::language_id::LanguageId;
+    use crate::codegraph::treesitter::parsers::AstLanguageParser;
+    use crate::codegraph::treesitter::parsers::objc::ObjCParser;
+    use crate::codegraph::treesitter::parsers::tests::base_parser_test;
+    use crate::codegraph::treesitter::structs::SymbolType;
+
+    const MAIN_M_CODE: &str = include_str!("cases/objc/main.m");
+    const MAIN_M_SYMBOLS: &str = include_str!("cases/objc/main.m.json");
+
+    #[test]
+    fn parser_test() {
+        let mut parser: Box<dyn AstLanguageParser> = Box::new(ObjCParser::new().expect("ObjCParser::new"));
+        let path = PathBuf::from("/main.m");
+        base_parser_test(&mut parser, &path, MAIN_M_CODE, MAIN_M_SYMBOLS);
+    }
+
+    #[test]
+    fn functions_and_calls_test() {
+        let mut parser = ObjCParser::new().unwrap();
+        let symbols = parser.parse(MAIN_M_CODE, &PathBuf::from("/main.m"));
+        let by_type = |t: SymbolType| {
+            let mut found: Vec<(String, usize)> = symbols
+                .iter()
+                .filter(|s| s.read().symbol_type() == t)
+                .map(|s| (s.read().name().to_string(), s.read().full_range().start_point.row + 1))
+                .collect();
+            found.sort_by_key(|(name, line)| (*line, name.clone()));
+            found
+        };
+        let names = |t: SymbolType| by_type(t).into_iter().map(|(name, _)| name).collect::<Vec<_>>();
+
+        // Methods are named after their selector and calls are message sends
+        assert_eq!(
+            by_type(SymbolType::FunctionDeclaration),
+            vec![
+                ("driveTo_speed".to_string(), 13),
+                ("sharedCar".to_string(), 20),
+                ("finish".to_string(), 24),
+                ("main".to_string(), 29),
+            ]
+        );
+        assert_eq!(names(SymbolType::StructDeclaration), vec!["Car", "Car"]);
+        assert_eq!(
+            names(SymbolType::FunctionCall),
+            vec!["startWithPower_mode", "log", "shared", "uppercaseString", "finish", "alloc", "init", "NSLog", "driveTo_speed", "sharedCar"]
+        );
+        assert_eq!(by_type(SymbolType::ImportDeclaration).len(), 1);
+        assert_eq!(by_type(SymbolType::CommentDefinition), vec![("".to_string(), 3)]);
+        assert!(symbols.iter().all(|s| s.read().language() == &LanguageId::ObjectiveC));
+
+        let owner = |name: &str| {
+            let function = symbols.iter().find(|s| s.read().symbol_type() == SymbolType::FunctionDeclaration && s.read().name() == name).unwrap();
+            let parent_guid = (*function.read().parent_guid()).unwrap();
+            symbols.iter().find(|s| *s.read().guid() == parent_guid).map(|s| s.read().name().to_string())
+        };
+        assert_eq!(owner("finish").as_deref(), Some("Car"));
+        assert_eq!(owner("main"), None);
+    }
+}
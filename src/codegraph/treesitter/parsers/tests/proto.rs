@@ -0,0 +1,42 @@
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use crate::codegraph::treesitter::ast_instance_structs::FunctionDeclaration;
+    use crate::codegraph::treesitter::parsers::AstLanguageParser;
+    use crate::codegraph::treesitter::parsers::proto::{file_namespace, ProtoAnalyzer};
+    use crate::codegraph::treesitter::parsers::tests::base_parser_test;
+    use crate::codegraph::treesitter::structs::SymbolType;
+
+    const MAIN_PROTO_CODE: &str = include_str!("cases/proto/main.proto");
+    const MAIN_PROTO_SYMBOLS: &str = include_str!("cases/proto/main.proto.json");
+
+    #[test]
+    fn parser_test() {
+        let mut parser: Box<dyn AstLanguageParser> = Box::new(ProtoAnalyzer::new().expect("ProtoAnalyzer::new"));
+        let path = PathBuf::from("/main.proto");
+        base_parser_test(&mut parser, &path, MAIN_PROTO_CODE, MAIN_PROTO_SYMBOLS);
+    }
+
+    #[test]
+    fn services_and_rpcs_test() {
+        let symbols = ProtoAnalyzer::new().unwrap().parse(MAIN_PROTO_CODE, &PathBuf::from("/main.proto"));
+        let of_type = |kind: SymbolType| -> Vec<String> {
+            symbols.iter().filter(|s| s.read().symbol_type() == kind).map(|s| s.read().name().to_string()).collect()
+        };
+        assert_eq!(of_type(SymbolType::StructDeclaration), vec!["UserService"]);
+        assert_eq!(of_type(SymbolType::FunctionDeclaration), vec!["UserService.GetUser", "UserService.WatchUsers"]);
+        assert_eq!(of_type(SymbolType::ImportDeclaration), vec!["google/protobuf/empty.proto", "acme/common.proto"]);
+        assert_eq!(file_namespace(MAIN_PROTO_CODE).as_deref(), Some("acme.user.v1"));
+
+        let mut watch = symbols
+            .iter()
+            .find(|s| s.read().name() == "UserService.WatchUsers")
+            .unwrap()
+            .write();
+        let watch = watch.as_any_mut().downcast_ref::<FunctionDeclaration>().unwrap();
+        assert_eq!(watch.args[0].type_.as_ref().unwrap().name.as_deref(), Some("google.protobuf.Empty"));
+        assert_eq!(watch.return_type.as_ref().unwrap().name.as_deref(), Some("stream User"));
+        assert_eq!((watch.ast_fields.full_range.start_point.row, watch.ast_fields.full_range.end_point.row), (10, 12));
+    }
+}
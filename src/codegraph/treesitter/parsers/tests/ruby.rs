@@ -0,0 +1,73 @@
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use crate::codegraph::treesitter::ast_instance_structs::{FunctionDeclaration, StructDeclaration};
+    use crate::codegraph::treesitter::parsers::AstLanguageParser;
+    use crate::codegraph::treesitter::parsers::ruby::RubyAnalyzer;
+    use crate::codegraph::treesitter::parsers::tests::base_parser_test;
+    use crate::codegraph::treesitter::structs::SymbolType;
+
+    const MAIN_RB_CODE: &str = include_str!("cases/ruby/main.rb");
+    const MAIN_RB_SYMBOLS: &str = include_str!("cases/ruby/main.rb.json");
+
+    #[test]
+    fn parser_test() {
+        let mut parser: Box<dyn AstLanguageParser> = Box::new(RubyAnalyzer::new().expect("RubyAnalyzer::new"));
+        let path = PathBuf::from("/main.rb");
+        base_parser_test(&mut parser, &path, MAIN_RB_CODE, MAIN_RB_SYMBOLS);
+    }
+
+    #[test]
+    fn functions_and_calls_test() {
+        let mut analyzer = RubyAnalyzer::new().unwrap();
+        let symbols = analyzer.parse(MAIN_RB_CODE, &PathBuf::from("/main.rb"));
+        let by_type = |t: SymbolType| {
+            let mut found: Vec<(String, usize)> = symbols
+                .iter()
+                .filter(|s| s.read().symbol_type() == t)
+                .map(|s| (s.read().name().to_string(), s.read().full_range().start_point.row + 1))
+                .collect();
+            found.sort_by_key(|(name, line)| (*line, name.clone()));
+            found
+        };
+        let names = |t: SymbolType| by_type(t).into_iter().map(|(name, _)| name).collect::<Vec<_>>();
+        let find = |t: SymbolType, name: &str| symbols.iter().find(|s| s.read().symbol_type() == t && s.read().name() == name).unwrap().clone();
+
+        assert_eq!(
+            by_type(SymbolType::FunctionDeclaration),
+            vec![("initialize".to_string(), 9), ("total".to_string(), 14), ("build".to_string(), 18), ("price".to_string(), 24), ("main".to_string(), 30)]
+        );
+        assert_eq!(names(SymbolType::StructDeclaration), vec!["Billing", "Invoice"]);
+        assert_eq!(
+            names(SymbolType::FunctionCall),
+            vec!["setup", "map", "price", "sum", "new", "parse", "amount", "round", "Invoice", "puts", "total"]
+        );
+        assert_eq!(names(SymbolType::ImportDeclaration), vec!["json", "helper"]);
+        assert_eq!(by_type(SymbolType::CommentDefinition).len(), 1);
+
+        let initialize = find(SymbolType::FunctionDeclaration, "initialize");
+        let args: Vec<String> = {
+            let mut initialize = initialize.write();
+            let initialize = initialize.as_any_mut().downcast_ref::<FunctionDeclaration>().unwrap();
+            initialize.args.iter().map(|arg| arg.name.clone()).collect()
+        };
+        assert_eq!(args, vec!["items", "limit", "rest", "key", "block"]);
+
+        let invoice = find(SymbolType::StructDeclaration, "Invoice");
+        let inherited: Vec<String> = {
+            let mut invoice = invoice.write();
+            let invoice = invoice.as_any_mut().downcast_ref::<StructDeclaration>().unwrap();
+            invoice.inherited_types.iter().filter_map(|t| t.name.clone()).collect()
+        };
+        assert_eq!(inherited, vec!["Base", "Comparable"]);
+
+        // `def self.build` is a method of the class, `main` is a top-level function
+        let owner = |name: &str| {
+            let parent_guid = (*find(SymbolType::FunctionDeclaration, name).read().parent_guid()).unwrap();
+            symbols.iter().find(|s| *s.read().guid() == parent_guid).map(|s| s.read().name().to_string())
+        };
+        assert_eq!(owner("build").as_deref(), Some("Invoice"));
+        assert_eq!(owner("main"), None);
+    }
+}
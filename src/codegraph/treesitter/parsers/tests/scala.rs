@@ -0,0 +1,74 @@
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use crate::codegraph::treesitter::ast_instance_structs::StructDeclaration;
+    use crate::codegraph::treesitter::parsers::AstLanguageParser;
+    use crate::codegraph::treesitter::parsers::scala::ScalaAnalyzer;
+    use crate::codegraph::treesitter::parsers::tests::base_parser_test;
+    use crate::codegraph::treesitter::structs::SymbolType;
+
+    const MAIN_SCALA_CODE: &str = include_str!("cases/scala/main.scala");
+    const MAIN_SCALA_SYMBOLS: &str = include_str!("cases/scala/main.scala.json");
+
+    #[test]
+    fn parser_test() {
+        let mut parser: Box<dyn AstLanguageParser> = Box::new(ScalaAnalyzer::new().expect("ScalaAnalyzer::new"));
+        let path = PathBuf::from("/main.scala");
+        base_parser_test(&mut parser, &path, MAIN_SCALA_CODE, MAIN_SCALA_SYMBOLS);
+    }
+
+    #[test]
+    fn functions_and_calls_test() {
+        let mut analyzer = ScalaAnalyzer::new().unwrap();
+        let symbols = analyzer.parse(MAIN_SCALA_CODE, &PathBuf::from("/main.scala"));
+        let by_type = |t: SymbolType| {
+            let mut found: Vec<(String, usize)> = symbols
+                .iter()
+                .filter(|s| s.read().symbol_type() == t)
+                .map(|s| (s.read().name().to_string(), s.read().full_range().start_point.row + 1))
+                .collect();
+            found.sort_by_key(|(name, line)| (*line, name.clone()));
+            found
+        };
+        let names = |t: SymbolType| by_type(t).into_iter().map(|(name, _)| name).collect::<Vec<_>>();
+        let find = |t: SymbolType, name: &str| symbols.iter().find(|s| s.read().symbol_type() == t && s.read().name() == name).unwrap().clone();
+
+        assert_eq!(
+            by_type(SymbolType::FunctionDeclaration),
+            vec![
+                ("price".to_string(), 8),
+                ("total".to_string(), 12),
+                ("price".to_string(), 14),
+                ("discount".to_string(), 19),
+                ("apply".to_string(), 23),
+                ("main".to_string(), 25),
+            ]
+        );
+        assert_eq!(names(SymbolType::StructDeclaration), vec!["Pricing", "Cart", "Cart"]);
+        assert_eq!(
+            names(SymbolType::FunctionCall),
+            vec!["map", "sum", "info", "amount", "discount", "Cart", "toList", "Cart", "Item", "max", "println", "total", "total"]
+        );
+        assert_eq!(names(SymbolType::ImportDeclaration), vec!["Future", "J", "Logger"]);
+        assert_eq!(by_type(SymbolType::CommentDefinition).len(), 1);
+
+        let cart = symbols
+            .iter()
+            .find(|s| s.read().symbol_type() == SymbolType::StructDeclaration && s.read().full_range().start_point.row == 10)
+            .unwrap();
+        let inherited: Vec<String> = {
+            let mut cart = cart.write();
+            let cart = cart.as_any_mut().downcast_ref::<StructDeclaration>().unwrap();
+            cart.inherited_types.iter().filter_map(|t| t.name.clone()).collect()
+        };
+        assert_eq!(inherited, vec!["Base", "Pricing"]);
+
+        let owner = |name: &str| {
+            let parent_guid = (*find(SymbolType::FunctionDeclaration, name).read().parent_guid()).unwrap();
+            symbols.iter().find(|s| *s.read().guid() == parent_guid).map(|s| s.read().name().to_string())
+        };
+        assert_eq!(owner("discount").as_deref(), Some("Cart"));
+        assert_eq!(owner("price").as_deref(), Some("Pricing"));
+    }
+}
@@ -0,0 +1,98 @@
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use crate::codegraph::treesitter::ast_instance_structs::{AstSymbolInstanceArc, FunctionDeclaration};
+    use crate::codegraph::treesitter::parsers::AstLanguageParser;
+    use crate::codegraph::treesitter::parsers::sql::{file_namespace, SqlAnalyzer};
+    use crate::codegraph::treesitter::parsers::tests::base_parser_test;
+    use crate::codegraph::treesitter::structs::SymbolType;
+
+    const MAIN_SQL_CODE: &str = include_str!("cases/sql/main.sql");
+    const MAIN_SQL_SYMBOLS: &str = include_str!("cases/sql/main.sql.json");
+    const BILLING_SQL_CODE: &str = include_str!("cases/sql/billing.sql");
+    const PAYROLL_SQL_CODE: &str = include_str!("cases/sql/payroll.sql");
+
+    #[test]
+    fn parser_test() {
+        let mut parser: Box<dyn AstLanguageParser> = Box::new(SqlAnalyzer::new().expect("SqlAnalyzer::new"));
+        let path = PathBuf::from("/main.sql");
+        base_parser_test(&mut parser, &path, MAIN_SQL_CODE, MAIN_SQL_SYMBOLS);
+    }
+
+    fn by_type(symbols: &[AstSymbolInstanceArc], t: SymbolType) -> Vec<(String, usize, usize)> {
+        symbols
+            .iter()
+            .filter(|s| s.read().symbol_type() == t)
+            .map(|s| {
+                let s = s.read();
+                (s.name().to_string(), s.full_range().start_point.row + 1, s.full_range().end_point.row + 1)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn tsql_procedures_and_exec_test() {
+        let mut parser = SqlAnalyzer::new().unwrap();
+        let symbols = parser.parse(MAIN_SQL_CODE, &PathBuf::from("/main.sql"));
+
+        assert_eq!(
+            by_type(&symbols, SymbolType::FunctionDeclaration),
+            vec![("usp_placeorder".to_string(), 2, 18), ("fn_tax".to_string(), 21, 26)]
+        );
+        let calls: Vec<(String, usize)> = by_type(&symbols, SymbolType::FunctionCall).into_iter().map(|(name, line, _)| (name, line)).collect();
+        assert_eq!(
+            calls,
+            vec![
+                ("dbo.usp_reservestock".to_string(), 10),
+                ("dbo.fn_tax".to_string(), 11),
+                ("usp_logerror".to_string(), 15),
+            ]
+        );
+        assert_eq!(by_type(&symbols, SymbolType::CommentDefinition).len(), 1);
+
+        let procedure = symbols.iter().find(|s| s.read().name() == "usp_placeorder").unwrap();
+        let mut procedure = procedure.write();
+        let declaration = procedure.as_any_mut().downcast_ref::<FunctionDeclaration>().unwrap();
+        let args: Vec<(&str, Option<&str>)> =
+            declaration.args.iter().map(|a| (a.name.as_str(), a.type_.as_ref().and_then(|t| t.name.as_deref()))).collect();
+        assert_eq!(args, vec![("@customerid", Some("INT")), ("@total", Some("DECIMAL(10, 2)"))]);
+        assert_eq!(file_namespace(MAIN_SQL_CODE).as_deref(), Some("dbo"));
+    }
+
+    #[test]
+    fn postgres_mysql_and_oracle_routines_test() {
+        let mut parser = SqlAnalyzer::new().unwrap();
+        let symbols = parser.parse(BILLING_SQL_CODE, &PathBuf::from("/billing.sql"));
+
+        assert_eq!(
+            by_type(&symbols, SymbolType::FunctionDeclaration),
+            vec![
+                ("charge".to_string(), 1, 9),
+                ("charge_audit".to_string(), 11, 12),
+                ("audit".to_string(), 14, 14),
+                ("apply_charge".to_string(), 16, 20),
+            ]
+        );
+        let calls: Vec<(String, usize)> = by_type(&symbols, SymbolType::FunctionCall).into_iter().map(|(name, line, _)| (name, line)).collect();
+        assert_eq!(
+            calls,
+            vec![
+                ("billing.audit".to_string(), 4),
+                ("apply_charge".to_string(), 6),
+                ("billing.audit_row".to_string(), 12),
+                ("notify_owner".to_string(), 19),
+            ]
+        );
+        assert_eq!(file_namespace(BILLING_SQL_CODE).as_deref(), Some("billing"));
+
+        let symbols = parser.parse(PAYROLL_SQL_CODE, &PathBuf::from("/payroll.pkb.sql"));
+        assert_eq!(
+            by_type(&symbols, SymbolType::FunctionDeclaration),
+            vec![("log_run".to_string(), 2, 5), ("run".to_string(), 7, 13)]
+        );
+        let calls: Vec<String> = by_type(&symbols, SymbolType::FunctionCall).into_iter().map(|(name, _, _)| name).collect();
+        assert_eq!(calls, vec!["log_run", "ledger.total_for"]);
+        assert_eq!(file_namespace(PAYROLL_SQL_CODE).as_deref(), Some("payroll"));
+    }
+}
@@ -0,0 +1,73 @@
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use crate::codegraph::treesitter::ast_instance_structs::FunctionDeclaration;
+    use crate::codegraph::treesitter::parsers::AstLanguageParser;
+    use crate::codegraph::treesitter::parsers::swift::SwiftAnalyzer;
+    use crate::codegraph::treesitter::parsers::tests::base_parser_test;
+    use crate::codegraph::treesitter::structs::SymbolType;
+
+    const MAIN_SWIFT_CODE: &str = include_str!("cases/swift/main.swift");
+    const MAIN_SWIFT_SYMBOLS: &str = include_str!("cases/swift/main.swift.json");
+
+    #[test]
+    fn parser_test() {
+        let mut parser: Box<dyn AstLanguageParser> = Box::new(SwiftAnalyzer::new().expect("SwiftAnalyzer::new"));
+        let path = PathBuf::from("/main.swift");
+        base_parser_test(&mut parser, &path, MAIN_SWIFT_CODE, MAIN_SWIFT_SYMBOLS);
+    }
+
+    #[test]
+    fn functions_and_calls_test() {
+        let mut analyzer = SwiftAnalyzer::new().unwrap();
+        let symbols = analyzer.parse(MAIN_SWIFT_CODE, &PathBuf::from("/main.swift"));
+        let by_type = |t: SymbolType| {
+            let mut found: Vec<(String, usize)> = symbols
+                .iter()
+                .filter(|s| s.read().symbol_type() == t)
+                .map(|s| (s.read().name().to_string(), s.read().full_range().start_point.row + 1))
+                .collect();
+            found.sort_by_key(|(name, line)| (*line, name.clone()));
+            found
+        };
+        let names = |t: SymbolType| by_type(t).into_iter().map(|(name, _)| name).collect::<Vec<_>>();
+        let find = |t: SymbolType, name: &str| symbols.iter().find(|s| s.read().symbol_type() == t && s.read().name() == name).unwrap().clone();
+
+        assert_eq!(
+            by_type(SymbolType::FunctionDeclaration),
+            vec![
+                ("greet".to_string(), 6),
+                ("init".to_string(), 12),
+                ("greet".to_string(), 17),
+                ("format".to_string(), 21),
+                ("reset".to_string(), 25),
+                ("main".to_string(), 31),
+            ]
+        );
+        assert_eq!(names(SymbolType::StructDeclaration), vec!["Greeter", "Service", "Service"]);
+        assert_eq!(
+            names(SymbolType::FunctionCall),
+            vec!["Store", "init", "configure", "format", "uppercased", "clear", "forEach", "log", "Service", "Store", "greet", "print"]
+        );
+        assert_eq!(names(SymbolType::ImportDeclaration), vec!["Foundation", "MyKit"]);
+        assert_eq!(by_type(SymbolType::CommentDefinition).len(), 1);
+
+        let format = find(SymbolType::FunctionDeclaration, "format");
+        let args: Vec<(String, Option<String>)> = {
+            let mut format = format.write();
+            let format = format.as_any_mut().downcast_ref::<FunctionDeclaration>().unwrap();
+            format.args.iter().map(|arg| (arg.name.clone(), arg.type_.as_ref().and_then(|t| t.name.clone()))).collect()
+        };
+        assert_eq!(args, vec![("s".to_string(), Some("String".to_string()))]);
+
+        // Methods declared in an extension belong to the extended type
+        let owner = |name: &str| {
+            let parent_guid = (*find(SymbolType::FunctionDeclaration, name).read().parent_guid()).unwrap();
+            symbols.iter().find(|s| *s.read().guid() == parent_guid).map(|s| s.read().name().to_string())
+        };
+        assert_eq!(owner("reset").as_deref(), Some("Service"));
+        assert_eq!(owner("init").as_deref(), Some("Service"));
+        assert_eq!(owner("main"), None);
+    }
+}
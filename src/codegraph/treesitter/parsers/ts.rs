@@ -12,7 +12,7 @@ use uuid::Uuid;
 
 use crate::codegraph::treesitter::ast_instance_structs::{AstSymbolFields, AstSymbolInstanceArc, ClassFieldDeclaration, CommentDefinition, FunctionArg, FunctionCall, FunctionDeclaration, ImportDeclaration, ImportType, StructDeclaration, TypeDef, VariableDefinition, VariableUsage};
 use crate::codegraph::treesitter::language_id::LanguageId;
-use crate::codegraph::treesitter::parsers::{AstLanguageParser, internal_error, ParserError};
+use crate::codegraph::treesitter::parsers::{AstLanguageParser, internal_error, ParserError, TreeStats};
 use crate::codegraph::treesitter::parsers::utils::{CandidateInfo, get_guid};
 use crate::codegraph::treesitter::skeletonizer::SkeletonFormatter;
 use crate::codegraph::treesitter::ast_instance_structs::SymbolInformation;
@@ -20,6 +20,7 @@ use crate::codegraph::treesitter::structs::SymbolType;
 
 pub(crate) struct TSParser {
     pub parser: Parser,
+    tree_stats: Option<TreeStats>,
 }
 
 pub fn parse_type(parent: &Node, code: &str) -> Option<TypeDef> {
@@ -138,7 +139,7 @@ impl TSParser {
         parser
             .set_language(&tree_sitter_typescript::LANGUAGE_TYPESCRIPT.into())
             .map_err(internal_error)?;
-        Ok(Self { parser })
+        Ok(Self { parser, tree_stats: None })
     }
 
     pub fn parse_struct_declaration<'a>(
@@ -363,7 +364,13 @@ impl TSParser {
         symbols
     }
 
-    pub fn parse_function_declaration<'a>(&mut self, info: &CandidateInfo<'a>, code: &str, candidates: &mut VecDeque<CandidateInfo<'a>>) -> Vec<AstSymbolInstanceArc> {
+    pub fn parse_function_declaration<'a>(
+        &mut self,
+        info: &CandidateInfo<'a>,
+        code: &str,
+        candidates: &mut VecDeque<CandidateInfo<'a>>,
+        name_from_var: Option<String>,
+    ) -> Vec<AstSymbolInstanceArc> {
         let mut symbols: Vec<AstSymbolInstanceArc> = Default::default();
         let mut decl = FunctionDeclaration::default();
         decl.ast_fields = AstSymbolFields::from_fields(&info.ast_fields);
@@ -377,6 +384,8 @@ impl TSParser {
 
         if let Some(name) = info.node.child_by_field_name("name") {
             decl.ast_fields.name = code.slice(name.byte_range()).to_string();
+        } else if let Some(name) = name_from_var {
+            decl.ast_fields.name = name;
         }
 
         if let Some(type_parameters) = info.node.child_by_field_name("type_parameters") {
@@ -606,10 +615,23 @@ impl TSParser {
                 symbols.extend(self.parse_struct_declaration(info, code, candidates));
             }
             /*"lexical_declaration" |*/ "variable_declarator" => {
-                symbols.extend(self.parse_variable_definition(info, code, candidates));
+                // `const f = () => ..` 按变量名命名函数
+                let value = info.node.child_by_field_name("value").filter(|value| matches!(value.kind(), "arrow_function" | "function_expression"));
+                match (value, info.node.child_by_field_name("name")) {
+                    (Some(value), Some(name)) => {
+                        let name = code.slice(name.byte_range()).to_string();
+                        let new_info = CandidateInfo {
+                            ast_fields: info.ast_fields.clone(),
+                            node: value,
+                            parent_guid: info.parent_guid,
+                        };
+                        symbols.extend(self.parse_function_declaration(&new_info, code, candidates, Some(name)));
+                    }
+                    _ => symbols.extend(self.parse_variable_definition(info, code, candidates)),
+                }
             }
             "function_declaration" | "method_definition" | "arrow_function" | "function_expression" => {
-                symbols.extend(self.parse_function_declaration(info, code, candidates));
+                symbols.extend(self.parse_function_declaration(info, code, candidates, None));
             }
             "call_expression" => {
                 symbols.extend(self.parse_call_expression(info, code, candidates));
@@ -823,9 +845,14 @@ impl TSParser {
 impl AstLanguageParser for TSParser {
     fn parse(&mut self, code: &str, path: &PathBuf) -> Vec<AstSymbolInstanceArc> {
         let tree = self.parser.parse(code, None).unwrap();
+        self.tree_stats = Some(TreeStats::of(&tree));
         let symbols = self.parse_(&tree.root_node(), code, path);
         symbols
     }
+
+    fn tree_stats(&self) -> Option<TreeStats> {
+        self.tree_stats.clone()
+    }
 }
 
 pub struct TypescriptSkeletonFormatter;
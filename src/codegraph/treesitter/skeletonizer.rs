@@ -66,8 +66,9 @@ pub trait SkeletonFormatter {
             .map(|x| x.replace("\r", "")
                 .replace("\t", "    ").to_string())
             .collect::<Vec<_>>();
-        let indent_n = content.iter().map(|x| {
-            if x.is_empty() {
+        // Measure indentation after tabs are expanded, otherwise a tab counts as one column
+        let indent_n = lines.iter().map(|x| {
+            if x.trim().is_empty() {
                 return usize::MAX;
             } else {
                 x.len() - x.trim_start().len()
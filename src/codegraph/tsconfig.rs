@@ -0,0 +1,240 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde_json::Value;
+
+use crate::codegraph::reexports::normalize;
+
+/// 路径别名中可以省略的扩展名，按 TypeScript 的查找顺序排列
+const EXTENSIONS: [&str; 8] = ["ts", "tsx", "d.ts", "js", "jsx", "mjs", "vue", "svelte"];
+
+/// `extends` 链的最大深度，防止循环引用
+const MAX_EXTENDS_DEPTH: usize = 8;
+
+/// `tsconfig.json`/`jsconfig.json` 中影响模块解析的选项
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TsConfig {
+    /// `compilerOptions.baseUrl`，已换算为绝对路径
+    base_url: Option<PathBuf>,
+    /// `compilerOptions.paths`：(模式, 替换路径)，如 (`@app/*`, [`src/app/*`])
+    paths: Vec<(String, Vec<String>)>,
+    /// 定义 `paths` 的配置文件所在目录；没有 `baseUrl` 时替换路径相对于它
+    paths_dir: PathBuf,
+}
+
+impl TsConfig {
+    /// 从 `dir` 向上查找最近的 `tsconfig.json` 或 `jsconfig.json`
+    pub fn find(dir: &Path) -> Option<TsConfig> {
+        dir.ancestors().find_map(|dir| {
+            ["tsconfig.json", "jsconfig.json"]
+                .iter()
+                .map(|name| dir.join(name))
+                .find(|path| path.is_file())
+                .and_then(|path| Self::load(&path))
+        })
+    }
+
+    /// 读取配置文件，合并 `extends` 指向的相对路径配置；npm 包中的配置不展开
+    pub fn load(path: &Path) -> Option<TsConfig> {
+        Self::load_with_depth(path, 0)
+    }
+
+    fn load_with_depth(path: &Path, depth: usize) -> Option<TsConfig> {
+        if depth > MAX_EXTENDS_DEPTH {
+            return None;
+        }
+        let content = fs::read_to_string(path).ok()?;
+        let json: Value = serde_json::from_str(&strip_jsonc(&content)).ok()?;
+        let dir = path.parent().unwrap_or(Path::new(""));
+
+        let extends = match json.get("extends") {
+            Some(Value::String(parent)) => vec![parent.as_str()],
+            Some(Value::Array(parents)) => parents.iter().filter_map(Value::as_str).collect(),
+            _ => Vec::new(),
+        };
+        let mut config = TsConfig { paths_dir: dir.to_path_buf(), ..Default::default() };
+        for parent in extends.into_iter().filter(|parent| parent.starts_with('.')) {
+            // `./tsconfig.base` 不存在时按 `./tsconfig.base.json` 查找
+            let mut parent_path = dir.join(parent);
+            if !parent_path.is_file() {
+                parent_path = dir.join(format!("{}.json", parent));
+            }
+            if let Some(parent) = Self::load_with_depth(&parent_path, depth + 1) {
+                config = parent;
+            }
+        }
+
+        let options = json.get("compilerOptions");
+        if let Some(base_url) = options.and_then(|o| o.get("baseUrl")).and_then(Value::as_str) {
+            config.base_url = Some(normalize(&dir.join(base_url)));
+        }
+        if let Some(paths) = options.and_then(|o| o.get("paths")).and_then(Value::as_object) {
+            config.paths = paths
+                .iter()
+                .map(|(pattern, targets)| {
+                    let targets = targets.as_array().map(|t| t.iter().filter_map(Value::as_str).map(str::to_string).collect());
+                    (pattern.clone(), targets.unwrap_or_default())
+                })
+                .collect();
+            config.paths_dir = dir.to_path_buf();
+        }
+        Some(config)
+    }
+
+    /// 非相对路径的模块（如 `@app/utils`）指向的模块路径（绝对路径，不含扩展名）。
+    ///
+    /// 先按 `paths` 中前缀最长的匹配模式替换，多个替换路径取第一个存在的；
+    /// 没有匹配的模式时在 `baseUrl` 下查找。找不到时为 `None`（多半是 npm 包）
+    pub fn resolve(&self, module: &str) -> Option<PathBuf> {
+        if module.starts_with('.') || module.starts_with('/') {
+            return None;
+        }
+        let base = self.base_url.as_deref().unwrap_or(&self.paths_dir);
+        let matched = self
+            .paths
+            .iter()
+            .filter_map(|(pattern, targets)| Some((pattern, targets, match_pattern(pattern, module)?)))
+            .max_by_key(|(pattern, _, _)| pattern.find('*').unwrap_or(pattern.len()));
+        if let Some((_, targets, wildcard)) = matched {
+            let candidates: Vec<PathBuf> = targets
+                .iter()
+                .map(|target| normalize(&base.join(target.replacen('*', wildcard, 1))))
+                .map(|candidate| strip_extension(&candidate))
+                .collect();
+            return candidates.iter().find(|candidate| module_exists(candidate)).or(candidates.first()).cloned();
+        }
+        let candidate = strip_extension(&normalize(&self.base_url.as_ref()?.join(module)));
+        module_exists(&candidate).then_some(candidate)
+    }
+}
+
+/// `@app/*` 匹配 `@app/utils` 时返回 `utils`，不含 `*` 的模式需要完全相同
+fn match_pattern<'a>(pattern: &str, module: &'a str) -> Option<&'a str> {
+    match pattern.split_once('*') {
+        None => (pattern == module).then_some(""),
+        Some((prefix, suffix)) => {
+            let rest = module.strip_prefix(prefix)?.strip_suffix(suffix)?;
+            Some(rest)
+        }
+    }
+}
+
+/// 去掉模块路径中写出的源码扩展名：`src/utils.ts` -> `src/utils`
+fn strip_extension(path: &Path) -> PathBuf {
+    let text = path.to_string_lossy();
+    EXTENSIONS
+        .iter()
+        .find_map(|ext| text.strip_suffix(&format!(".{}", ext)))
+        .map_or_else(|| path.to_path_buf(), PathBuf::from)
+}
+
+/// 模块路径是否对应一个文件：`base.ts` 等，或目录下的 `index.ts` 等
+fn module_exists(base: &Path) -> bool {
+    base.is_file()
+        || EXTENSIONS.iter().any(|ext| {
+            Path::new(&format!("{}.{}", base.display(), ext)).is_file() || base.join(format!("index.{}", ext)).is_file()
+        })
+}
+
+/// 去掉 tsconfig 中允许的注释和尾随逗号，字符串中的内容（如 `"src/*"`）保持不变
+fn strip_jsonc(content: &str) -> String {
+    let mut without_comments = String::with_capacity(content.len());
+    let mut chars = content.chars().peekable();
+    let mut in_string = false;
+    while let Some(c) = chars.next() {
+        if in_string {
+            without_comments.push(c);
+            match c {
+                '\\' => without_comments.extend(chars.next()),
+                '"' => in_string = false,
+                _ => {}
+            }
+            continue;
+        }
+        match (c, chars.peek()) {
+            ('"', _) => {
+                in_string = true;
+                without_comments.push(c);
+            }
+            ('/', Some('/')) => {
+                for c in chars.by_ref() {
+                    if c == '\n' {
+                        without_comments.push('\n');
+                        break;
+                    }
+                }
+            }
+            ('/', Some('*')) => {
+                chars.next();
+                let mut previous = ' ';
+                for c in chars.by_ref() {
+                    if previous == '*' && c == '/' {
+                        break;
+                    }
+                    previous = c;
+                }
+                without_comments.push(' ');
+            }
+            _ => without_comments.push(c),
+        }
+    }
+
+    let mut result = String::with_capacity(without_comments.len());
+    let mut in_string = false;
+    let mut escaped = false;
+    for (i, c) in without_comments.char_indices() {
+        if in_string {
+            in_string = escaped || c != '"';
+            escaped = !escaped && c == '\\';
+        } else if c == '"' {
+            in_string = true;
+        } else if c == ',' {
+            let next = without_comments[i + 1..].trim_start().chars().next();
+            if matches!(next, Some('}') | Some(']')) {
+                continue;
+            }
+        }
+        result.push(c);
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_strip_jsonc_keeps_strings() {
+        let content = "{\n  // comment\n  \"paths\": { \"@app/*\": [\"src/*\"], /* block */ },\n  \"a\": \"x\\\"//y\",\n}\n";
+        let json: Value = serde_json::from_str(&strip_jsonc(content)).unwrap();
+        assert_eq!(json["paths"]["@app/*"][0], "src/*");
+        assert_eq!(json["a"], "x\"//y");
+    }
+
+    #[test]
+    fn test_resolve_paths_and_base_url() {
+        let temp_dir = tempdir().unwrap();
+        let root = temp_dir.path();
+        fs::create_dir_all(root.join("src/app/utils")).unwrap();
+        fs::create_dir_all(root.join("src/shared")).unwrap();
+        fs::write(root.join("src/app/utils/index.ts"), "").unwrap();
+        fs::write(root.join("src/shared/format.ts"), "").unwrap();
+        fs::write(root.join("src/config.ts"), "").unwrap();
+        fs::write(
+            root.join("tsconfig.base.json"),
+            "{\n  \"compilerOptions\": {\n    \"baseUrl\": \"./src\",\n    // aliases\n    \"paths\": {\n      \"@app/*\": [\"app/*\"],\n      \"@shared/*\": [\"missing/*\", \"shared/*\"],\n      \"@config\": [\"config.ts\"],\n    },\n  },\n}\n",
+        )
+        .unwrap();
+        fs::write(root.join("tsconfig.json"), "{ \"extends\": \"./tsconfig.base\", \"include\": [\"src\"] }").unwrap();
+
+        let config = TsConfig::find(&root.join("src/app")).unwrap();
+        assert_eq!(config.resolve("@app/utils"), Some(root.join("src/app/utils")));
+        assert_eq!(config.resolve("@shared/format"), Some(root.join("src/shared/format")));
+        assert_eq!(config.resolve("@config"), Some(root.join("src/config")));
+        // 没有匹配的模式时在 baseUrl 下查找
+        assert_eq!(config.resolve("shared/format"), Some(root.join("src/shared/format")));
+        assert_eq!(config.resolve("lodash"), None);
+        assert_eq!(config.resolve("./local"), None);
+    }
+}
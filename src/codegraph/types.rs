@@ -1,13 +1,15 @@
 use std::collections::HashMap;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 use petgraph::graph::{DiGraph, NodeIndex};
 use petgraph::Direction;
 use petgraph::visit::EdgeRef;
 
+use super::diagram::{render_d2, render_dot, render_mermaid, render_plantuml};
+
 /// 函数信息
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct FunctionInfo {
     pub id: Uuid,
     pub name: String,
@@ -17,10 +19,90 @@ pub struct FunctionInfo {
     pub namespace: String,
     pub language: String,
     pub signature: Option<String>,
+    /// Jupyter notebook 中函数所在代码单元的序号（从0开始，含 markdown 单元），此时行号相对该单元
+    #[serde(default)]
+    pub cell: Option<usize>,
+    /// 规范化（去掉注释和多余空白）后函数体的哈希，用于查找重复的函数；没有源码的节点为空
+    #[serde(default)]
+    pub body_hash: Option<String>,
+    /// 函数与语法错误区域（tree-sitter 的 ERROR/MISSING 节点）重叠或从中恢复，提取的信息可能不完整
+    #[serde(default)]
+    pub partial: bool,
+    /// 声明的返回类型，未写出或语言没有返回类型时为空
+    #[serde(default)]
+    pub return_type: Option<String>,
+    /// 声明中的形参，按声明顺序
+    #[serde(default)]
+    pub parameters: Vec<FunctionParameter>,
+    /// 方法所属类型的 ID（见 [`ClassInfo::stable_id`]），自由函数为空
+    #[serde(default)]
+    pub parent_class_id: Option<Uuid>,
+    /// 节点的种类
+    #[serde(default)]
+    pub kind: FunctionKind,
+}
+
+/// 函数声明中的一个形参
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct FunctionParameter {
+    /// 参数名，只写了类型时（如 C 的原型、Go 的匿名参数）为空
+    pub name: String,
+    /// 声明的类型，未写出时为空
+    #[serde(default, rename = "type")]
+    pub type_: Option<String>,
+}
+
+/// 函数节点的种类
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FunctionKind {
+    /// 普通函数或方法
+    #[default]
+    Function,
+    /// 未解析调用的占位节点
+    Unresolved,
+    /// `macro_rules!` 定义
+    Macro,
+    /// 宏展开生成的函数
+    Generated,
+    /// 门面模块中的再导出节点（`pub use`、`export * from` 等），它调用被再导出的函数
+    Reexport,
+    /// 只有声明没有实现的方法（接口方法、抽象方法、纯虚函数）
+    Abstract,
+}
+
+impl FunctionKind {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            FunctionKind::Function => "function",
+            FunctionKind::Unresolved => "unresolved",
+            FunctionKind::Macro => "macro",
+            FunctionKind::Generated => "generated",
+            FunctionKind::Reexport => "reexport",
+            FunctionKind::Abstract => "abstract",
+        }
+    }
+}
+
+impl FunctionInfo {
+    /// 是否为未解析调用的占位节点
+    pub fn is_unresolved(&self) -> bool {
+        self.kind == FunctionKind::Unresolved
+    }
+
+    /// 是否为再导出节点
+    pub fn is_reexport(&self) -> bool {
+        self.kind == FunctionKind::Reexport
+    }
+
+    /// 是否为只有声明没有实现的方法
+    pub fn is_abstract(&self) -> bool {
+        self.kind == FunctionKind::Abstract
+    }
 }
 
 /// 调用关系
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct CallRelation {
     pub caller_id: Uuid,
     pub callee_id: Uuid,
@@ -30,6 +112,81 @@ pub struct CallRelation {
     pub callee_file: PathBuf,
     pub line_number: usize,
     pub is_resolved: bool,
+    /// 运行时插桩观测到过该调用
+    #[serde(default)]
+    pub observed: bool,
+    /// 经C++模板实例化的调用在调用处写出的模板实参（类模板的实参在前），例如 `max<int>(a, b)` 为 `int`
+    #[serde(default)]
+    pub template_args: Option<String>,
+    /// 调用方式：同步调用、被 await 的异步调用或派生出的任务
+    #[serde(default)]
+    pub call_kind: CallKind,
+    /// 从多个同名重载（Java/C++/TypeScript）中选出被调用函数时的置信度 (0, 1]：按实参个数和类型筛选后
+    /// 剩下的不同形参列表数的倒数；没有重载可选时为空
+    #[serde(default)]
+    pub confidence: Option<f32>,
+}
+
+#[cfg(test)]
+impl CallRelation {
+    /// 测试用：`caller` 在首行之后调用 `callee`，调用占位节点时为未解析调用
+    pub(crate) fn between(caller: &FunctionInfo, callee: &FunctionInfo) -> Self {
+        Self {
+            caller_id: caller.id,
+            callee_id: callee.id,
+            caller_name: caller.name.clone(),
+            callee_name: callee.name.clone(),
+            caller_file: caller.file_path.clone(),
+            callee_file: callee.file_path.clone(),
+            line_number: caller.line_start + 1,
+            is_resolved: !callee.is_unresolved(),
+            ..Default::default()
+        }
+    }
+}
+
+#[cfg(test)]
+impl PetCodeGraph {
+    /// 测试用：添加 [`CallRelation::between`] 构造的调用边
+    pub(crate) fn add_call(&mut self, caller: &FunctionInfo, callee: &FunctionInfo) {
+        self.add_call_relation(CallRelation::between(caller, callee)).unwrap();
+    }
+}
+
+/// 调用点上的调用方式，用于在查询中区分异步边界
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CallKind {
+    /// 普通的同步调用，或未等待的异步调用
+    #[default]
+    Sync,
+    /// 被 `await` 的异步调用
+    Await,
+    /// 作为任务派生出去的调用（`tokio::spawn`、`asyncio.create_task`、Promise 链的回调等）
+    Spawn,
+}
+
+impl CallKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CallKind::Sync => "sync",
+            CallKind::Await => "await",
+            CallKind::Spawn => "spawn",
+        }
+    }
+}
+
+impl std::str::FromStr for CallKind {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "sync" => Ok(CallKind::Sync),
+            "await" => Ok(CallKind::Await),
+            "spawn" => Ok(CallKind::Spawn),
+            other => Err(format!("Unknown call kind '{}', expected sync, await or spawn", other)),
+        }
+    }
 }
 
 /// 图节点
@@ -82,6 +239,69 @@ impl Default for CodeGraphStats {
     }
 }
 
+/// 运行时观测到的调用合并进图的方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ObservedCallMerge {
+    /// 静态分析已解析出该调用
+    Confirmed,
+    /// 未解析的调用（同名占位节点、启发式边）被确认
+    Upgraded,
+    /// 静态分析没有发现该调用（动态分发、反射、回调等）
+    Added,
+}
+
+/// 调用点上的名称（如 `obj.method`、`mod::func`）最后一段是否为 `name`
+fn call_name_matches(call_name: &str, name: &str) -> bool {
+    call_name.rsplit(['.', ':', '>']).next() == Some(name)
+}
+
+/// 图不变量检查发现的问题
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum GraphViolation {
+    /// 调用边记录的调用方/被调用方与边的实际端点不一致
+    DanglingEdge { caller_id: Uuid, callee_id: Uuid, source: usize, target: usize },
+    /// 节点在 `function_to_node` 中缺失或指向了其他节点
+    UnmappedNode { node: usize, function_id: Uuid },
+    /// `function_to_node` 中的条目指向不存在或不匹配的节点
+    StaleFunctionMapping { function_id: Uuid, node: usize },
+    /// `node_to_function` 中的条目与节点不一致
+    StaleNodeMapping { node: usize, function_id: Uuid },
+    /// 名称/文件索引引用了不存在的函数，或键与函数不匹配
+    StaleIndexEntry { index: String, key: String, function_id: Uuid },
+    /// 函数没有出现在名称/文件索引中
+    MissingIndexEntry { index: String, function_id: Uuid },
+    /// 统计信息与实际数量不符
+    StatsMismatch { field: String, recorded: usize, actual: usize },
+}
+
+impl std::fmt::Display for GraphViolation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::DanglingEdge { caller_id, callee_id, source, target } => write!(
+                f, "call edge {} -> {} is attached to nodes {} -> {}", caller_id, callee_id, source, target
+            ),
+            Self::UnmappedNode { node, function_id } => {
+                write!(f, "node {} ({}) is missing from function_to_node", node, function_id)
+            }
+            Self::StaleFunctionMapping { function_id, node } => {
+                write!(f, "function_to_node maps {} to node {} which holds another function", function_id, node)
+            }
+            Self::StaleNodeMapping { node, function_id } => {
+                write!(f, "node_to_function maps node {} to {} which does not match", node, function_id)
+            }
+            Self::StaleIndexEntry { index, key, function_id } => {
+                write!(f, "{}[{}] references {} which is missing or does not match", index, key, function_id)
+            }
+            Self::MissingIndexEntry { index, function_id } => write!(f, "{} is missing from {}", function_id, index),
+            Self::StatsMismatch { field, recorded, actual } => {
+                write!(f, "stats.{} is {} but the graph has {}", field, recorded, actual)
+            }
+        }
+    }
+}
+
 /// 基于petgraph的代码图结构
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PetCodeGraph {
@@ -158,11 +378,113 @@ impl PetCodeGraph {
         Ok(())
     }
 
+    /// 记录运行时观测到的一次调用：已有的边标记为observed，未解析的边升级为已解析；
+    /// 调用者指向同名未解析节点的边改指向真实的被调函数；都没有时新增一条observed边
+    pub fn record_observed_call(&mut self, caller_id: &Uuid, callee_id: &Uuid) -> Result<ObservedCallMerge, String> {
+        let caller_node = self.get_node_index(caller_id)
+            .ok_or_else(|| format!("Caller function {} not found", caller_id))?;
+        let callee_node = self.get_node_index(callee_id)
+            .ok_or_else(|| format!("Callee function {} not found", callee_id))?;
+
+        if let Some(edge) = self.graph.find_edge(caller_node, callee_node) {
+            let relation = &mut self.graph[edge];
+            relation.observed = true;
+            if relation.is_resolved {
+                return Ok(ObservedCallMerge::Confirmed);
+            }
+            relation.is_resolved = true;
+            self.stats.unresolved_calls = self.stats.unresolved_calls.saturating_sub(1);
+            self.stats.resolved_calls += 1;
+            return Ok(ObservedCallMerge::Upgraded);
+        }
+
+        let callee = self.graph[callee_node].clone();
+        let placeholder = self
+            .graph
+            .edges_directed(caller_node, Direction::Outgoing)
+            .find(|edge| {
+                let target = &self.graph[edge.target()];
+                target.is_unresolved() && call_name_matches(&edge.weight().callee_name, &callee.name)
+            })
+            .map(|edge| (edge.id(), edge.target()));
+
+        if let Some((edge, placeholder_node)) = placeholder {
+            let mut relation = self.graph.remove_edge(edge).ok_or("Unresolved call edge disappeared")?;
+            self.stats.unresolved_calls = self.stats.unresolved_calls.saturating_sub(1);
+            relation.callee_id = callee.id;
+            relation.callee_name = callee.name.clone();
+            relation.callee_file = callee.file_path.clone();
+            relation.is_resolved = true;
+            relation.observed = true;
+            self.add_call_relation(relation)?;
+
+            // 占位节点不再被任何调用引用时一并移除
+            let placeholder_id = self.graph[placeholder_node].id;
+            if self.graph.edges_directed(placeholder_node, Direction::Incoming).next().is_none() {
+                self.remove_function(&placeholder_id);
+            }
+            return Ok(ObservedCallMerge::Upgraded);
+        }
+
+        let caller = &self.graph[caller_node];
+        let relation = CallRelation {
+            caller_id: caller.id,
+            callee_id: callee.id,
+            caller_name: caller.name.clone(),
+            callee_name: callee.name.clone(),
+            caller_file: caller.file_path.clone(),
+            callee_file: callee.file_path.clone(),
+            line_number: caller.line_start,
+            is_resolved: true,
+            observed: true,
+            template_args: None,
+            call_kind: CallKind::Sync,
+            confidence: None,
+        };
+        self.add_call_relation(relation)?;
+        Ok(ObservedCallMerge::Added)
+    }
+
     /// 根据函数ID获取节点索引
     pub fn get_node_index(&self, function_id: &Uuid) -> Option<NodeIndex> {
         self.function_to_node.get(function_id).copied()
     }
 
+    /// 把函数移动到新位置（如 source map 还原出的原始源码），同步更新文件索引和调用边上的文件路径
+    pub fn relocate_function(&mut self, function_id: &Uuid, file_path: PathBuf, line_start: usize, line_end: usize) -> bool {
+        let Some(&node_index) = self.function_to_node.get(function_id) else {
+            return false;
+        };
+        let Some(function) = self.graph.node_weight_mut(node_index) else {
+            return false;
+        };
+        let old_path = std::mem::replace(&mut function.file_path, file_path.clone());
+        function.line_start = line_start;
+        function.line_end = line_end;
+
+        if let Some(ids) = self.file_functions.get_mut(&old_path) {
+            ids.retain(|id| id != function_id);
+            if ids.is_empty() {
+                self.file_functions.remove(&old_path);
+            }
+        }
+        self.file_functions.entry(file_path.clone()).or_default().push(*function_id);
+
+        let incoming: Vec<_> = self.graph.edges_directed(node_index, Direction::Incoming).map(|e| e.id()).collect();
+        let outgoing: Vec<_> = self.graph.edges_directed(node_index, Direction::Outgoing).map(|e| e.id()).collect();
+        for edge in incoming {
+            if let Some(relation) = self.graph.edge_weight_mut(edge) {
+                relation.callee_file = file_path.clone();
+            }
+        }
+        for edge in outgoing {
+            if let Some(relation) = self.graph.edge_weight_mut(edge) {
+                relation.caller_file = file_path.clone();
+            }
+        }
+        true
+    }
+
     /// 根据节点索引获取函数信息
     pub fn get_function(&self, node_index: NodeIndex) -> Option<&FunctionInfo> {
         self.graph.node_weight(node_index)
@@ -188,6 +510,23 @@ impl PetCodeGraph {
         callers
     }
 
+    /// 函数的使用者：直接调用者，经过再导出节点时改为通过门面模块使用它的调用者
+    pub fn get_users(&self, function_id: &Uuid) -> Vec<(&FunctionInfo, &CallRelation)> {
+        let mut users = Vec::new();
+        let mut visited = std::collections::HashSet::from([*function_id]);
+        let mut pending = vec![*function_id];
+        while let Some(id) = pending.pop() {
+            for (caller, relation) in self.get_callers(&id) {
+                if !caller.is_reexport() {
+                    users.push((caller, relation));
+                } else if visited.insert(caller.id) {
+                    pending.push(caller.id);
+                }
+            }
+        }
+        users
+    }
+
     /// 获取函数调用的函数
     pub fn get_callees(&self, function_id: &Uuid) -> Vec<(&FunctionInfo, &CallRelation)> {
         let mut callees = Vec::new();
@@ -218,6 +557,38 @@ impl PetCodeGraph {
             .unwrap_or_default()
     }
 
+    /// 按广度优先获取指定深度内的所有上游调用者及其距离
+    pub fn get_callers_within(&self, function_id: &Uuid, max_depth: usize) -> Vec<(&FunctionInfo, usize)> {
+        self._collect_within(function_id, max_depth, Direction::Incoming)
+    }
+
+    /// 按广度优先获取指定深度内的所有下游被调用者及其距离
+    pub fn get_callees_within(&self, function_id: &Uuid, max_depth: usize) -> Vec<(&FunctionInfo, usize)> {
+        self._collect_within(function_id, max_depth, Direction::Outgoing)
+    }
+
+    fn _collect_within(&self, function_id: &Uuid, max_depth: usize, direction: Direction) -> Vec<(&FunctionInfo, usize)> {
+        let mut result = Vec::new();
+        let Some(&start) = self.function_to_node.get(function_id) else {
+            return result;
+        };
+
+        let mut visited = std::collections::HashSet::from([start]);
+        let mut queue = std::collections::VecDeque::from([(start, 0usize)]);
+        while let Some((node, depth)) = queue.pop_front() {
+            if depth >= max_depth {
+                continue;
+            }
+            for neighbor in self.graph.neighbors_directed(node, direction) {
+                if visited.insert(neighbor) {
+                    result.push((&self.graph[neighbor], depth + 1));
+                    queue.push_back((neighbor, depth + 1));
+                }
+            }
+        }
+        result
+    }
+
     /// 获取调用链（递归）
     pub fn get_call_chain(&self, function_id: &Uuid, max_depth: usize) -> Vec<Vec<Uuid>> {
         let mut chains = Vec::new();
@@ -260,35 +631,22 @@ impl PetCodeGraph {
 
     /// 导出为DOT格式
     pub fn to_dot(&self) -> String {
-        let mut dot = String::from("digraph CodeGraph {\n");
-        dot.push_str("    rankdir=TB;\n");
-        dot.push_str("    node [shape=box];\n\n");
-        
-        // 添加节点
-        for node_index in self.graph.node_indices() {
-            if let Some(function) = self.graph.node_weight(node_index) {
-                let node_id = function.id.to_string().replace("-", "_");
-                let label = format!("{}\\n{}", function.name, function.file_path.display());
-                dot.push_str(&format!("    {} [label=\"{}\"];\n", node_id, label));
-            }
-        }
-        
-        // 添加边
-        for edge in self.graph.edge_indices() {
-            if let Some((source, target)) = self.graph.edge_endpoints(edge) {
-                if let (Some(caller), Some(callee)) = (self.graph.node_weight(source), self.graph.node_weight(target)) {
-                    let caller_id = caller.id.to_string().replace("-", "_");
-                    let callee_id = callee.id.to_string().replace("-", "_");
-                    if let Some(relation) = self.graph.edge_weight(edge) {
-                        let style = if relation.is_resolved { "" } else { " [style=dashed]" };
-                        dot.push_str(&format!("    {} -> {}{};\n", caller_id, callee_id, style));
-                    }
-                }
-            }
-        }
-        
-        dot.push_str("}\n");
-        dot
+        render_dot(&self.get_all_functions(), &self.get_all_call_relations())
+    }
+
+    /// 导出为Mermaid流程图
+    pub fn to_mermaid(&self) -> String {
+        render_mermaid(&self.get_all_functions(), &self.get_all_call_relations())
+    }
+
+    /// 导出为D2
+    pub fn to_d2(&self) -> String {
+        render_d2(&self.get_all_functions(), &self.get_all_call_relations())
+    }
+
+    /// 导出为PlantUML
+    pub fn to_plantuml(&self) -> String {
+        render_plantuml(&self.get_all_functions(), &self.get_all_call_relations())
     }
 
     /// 导出为JSON格式
@@ -312,6 +670,204 @@ impl PetCodeGraph {
         self.stats.total_languages = self.stats.languages.len();
     }
 
+    /// 移除函数节点及其调用边，同步更新映射、索引和统计信息
+    pub fn remove_function(&mut self, function_id: &Uuid) -> Option<FunctionInfo> {
+        let node_index = self.function_to_node.remove(function_id)?;
+        if self.graph.node_weight(node_index).map(|f| f.id) != Some(*function_id) {
+            return None;
+        }
+
+        // 自环同时出现在入边和出边中，按边ID去重
+        let mut edges: Vec<_> = self
+            .graph
+            .edges_directed(node_index, Direction::Outgoing)
+            .chain(self.graph.edges_directed(node_index, Direction::Incoming))
+            .map(|e| (e.id(), e.weight().is_resolved))
+            .collect();
+        edges.sort_unstable_by_key(|(id, _)| *id);
+        edges.dedup_by_key(|(id, _)| *id);
+        for (_, resolved) in edges {
+            if resolved {
+                self.stats.resolved_calls = self.stats.resolved_calls.saturating_sub(1);
+            } else {
+                self.stats.unresolved_calls = self.stats.unresolved_calls.saturating_sub(1);
+            }
+        }
+
+        // petgraph 用最后一个节点填补被删除的位置，需要重新映射被移动的节点
+        let last = NodeIndex::new(self.graph.node_count() - 1);
+        let function = self.graph.remove_node(node_index)?;
+        self.node_to_function.remove(&node_index);
+        if last != node_index {
+            self.node_to_function.remove(&last);
+            if let Some(moved) = self.graph.node_weight(node_index) {
+                self.function_to_node.insert(moved.id, node_index);
+                self.node_to_function.insert(node_index, moved.id);
+            }
+        }
+
+        if let Some(ids) = self.function_names.get_mut(&function.name) {
+            ids.retain(|id| id != function_id);
+            if ids.is_empty() {
+                self.function_names.remove(&function.name);
+            }
+        }
+        if let Some(ids) = self.file_functions.get_mut(&function.file_path) {
+            ids.retain(|id| id != function_id);
+            if ids.is_empty() {
+                self.file_functions.remove(&function.file_path);
+            }
+        }
+        self.stats.total_functions = self.stats.total_functions.saturating_sub(1);
+        if let Some(count) = self.stats.languages.get_mut(&function.language) {
+            *count = count.saturating_sub(1);
+            if *count == 0 {
+                self.stats.languages.remove(&function.language);
+            }
+        }
+
+        Some(function)
+    }
+
+    /// 移除文件中的所有函数，返回被移除的函数
+    pub fn remove_file(&mut self, file_path: &PathBuf) -> Vec<FunctionInfo> {
+        let function_ids = self.file_functions.get(file_path).cloned().unwrap_or_default();
+        let removed = function_ids.iter().filter_map(|id| self.remove_function(id)).collect();
+        self.update_stats();
+        removed
+    }
+
+    /// 检查图的内部一致性：边与端点、两个映射互逆、名称/文件索引、统计信息
+    pub fn validate(&self) -> Vec<GraphViolation> {
+        let mut violations = Vec::new();
+
+        for edge in self.graph.edge_references() {
+            let relation = edge.weight();
+            let source = self.graph[edge.source()].id;
+            let target = self.graph[edge.target()].id;
+            if relation.caller_id != source || relation.callee_id != target {
+                violations.push(GraphViolation::DanglingEdge {
+                    caller_id: relation.caller_id,
+                    callee_id: relation.callee_id,
+                    source: edge.source().index(),
+                    target: edge.target().index(),
+                });
+            }
+        }
+
+        for node_index in self.graph.node_indices() {
+            let function = &self.graph[node_index];
+            if self.function_to_node.get(&function.id) != Some(&node_index) {
+                violations.push(GraphViolation::UnmappedNode { node: node_index.index(), function_id: function.id });
+            }
+            if !self.function_names.get(&function.name).is_some_and(|ids| ids.contains(&function.id)) {
+                violations.push(GraphViolation::MissingIndexEntry {
+                    index: "function_names".to_string(),
+                    function_id: function.id,
+                });
+            }
+            if !self.file_functions.get(&function.file_path).is_some_and(|ids| ids.contains(&function.id)) {
+                violations.push(GraphViolation::MissingIndexEntry {
+                    index: "file_functions".to_string(),
+                    function_id: function.id,
+                });
+            }
+        }
+        for (function_id, node_index) in &self.function_to_node {
+            if self.graph.node_weight(*node_index).map(|f| f.id) != Some(*function_id) {
+                violations.push(GraphViolation::StaleFunctionMapping { function_id: *function_id, node: node_index.index() });
+            }
+        }
+        for (node_index, function_id) in &self.node_to_function {
+            if self.graph.node_weight(*node_index).map(|f| f.id) != Some(*function_id) {
+                violations.push(GraphViolation::StaleNodeMapping { node: node_index.index(), function_id: *function_id });
+            }
+        }
+        for node_index in self.graph.node_indices() {
+            if !self.node_to_function.contains_key(&node_index) {
+                violations.push(GraphViolation::StaleNodeMapping {
+                    node: node_index.index(),
+                    function_id: self.graph[node_index].id,
+                });
+            }
+        }
+
+        for (name, ids) in &self.function_names {
+            for id in ids {
+                if self.get_function_by_id(id).is_none_or(|f| &f.name != name) {
+                    violations.push(GraphViolation::StaleIndexEntry {
+                        index: "function_names".to_string(),
+                        key: name.clone(),
+                        function_id: *id,
+                    });
+                }
+            }
+        }
+        for (path, ids) in &self.file_functions {
+            for id in ids {
+                if self.get_function_by_id(id).is_none_or(|f| &f.file_path != path) {
+                    violations.push(GraphViolation::StaleIndexEntry {
+                        index: "file_functions".to_string(),
+                        key: path.display().to_string(),
+                        function_id: *id,
+                    });
+                }
+            }
+        }
+
+        let resolved = self.graph.edge_weights().filter(|r| r.is_resolved).count();
+        let mut languages: HashMap<&str, usize> = HashMap::new();
+        for function in self.graph.node_weights() {
+            *languages.entry(function.language.as_str()).or_default() += 1;
+        }
+        let mut counts = vec![
+            ("total_functions".to_string(), self.stats.total_functions, self.graph.node_count()),
+            ("total_files".to_string(), self.stats.total_files, self.file_functions.len()),
+            ("total_languages".to_string(), self.stats.total_languages, self.stats.languages.len()),
+            ("resolved_calls".to_string(), self.stats.resolved_calls, resolved),
+            ("unresolved_calls".to_string(), self.stats.unresolved_calls, self.graph.edge_count() - resolved),
+        ];
+        for (language, &recorded) in &self.stats.languages {
+            counts.push((format!("languages.{}", language), recorded, languages.get(language.as_str()).copied().unwrap_or(0)));
+        }
+        for (language, &actual) in &languages {
+            if !self.stats.languages.contains_key(*language) {
+                counts.push((format!("languages.{}", language), 0, actual));
+            }
+        }
+        for (field, recorded, actual) in counts {
+            if recorded != actual {
+                violations.push(GraphViolation::StatsMismatch { field, recorded, actual });
+            }
+        }
+
+        violations
+    }
+
+    /// 在调试构建或 `strict` 模式下执行 [`validate`](Self::validate)；
+    /// 发现问题时记录日志，`strict` 模式下返回错误
+    pub fn check_invariants(&self, strict: bool, context: &str) -> Result<(), String> {
+        if !strict && !cfg!(debug_assertions) {
+            return Ok(());
+        }
+        let violations = self.validate();
+        if violations.is_empty() {
+            return Ok(());
+        }
+        for violation in &violations {
+            tracing::warn!("Graph invariant violated after {}: {}", context, violation);
+        }
+        if strict {
+            return Err(format!(
+                "{} graph invariant violation(s) after {}: {}",
+                violations.len(),
+                context,
+                violations[0]
+            ));
+        }
+        Ok(())
+    }
+
     /// 获取所有函数
     pub fn get_all_functions(&self) -> Vec<&FunctionInfo> {
         self.graph.node_weights().collect()
@@ -361,8 +917,23 @@ pub struct ClassInfo {
     pub member_variables: Vec<String>,
 }
 
+impl ClassInfo {
+    /// 由类型的位置和名字确定的 ID，重新提取同一个类型时不变，方法的 `parent_class_id` 也由此得到。
+    ///
+    /// 同一文件中同名的声明（例如 Rust 的结构体和它的各个 impl 块）共用一个 ID；
+    /// Go 的类型属于包，按目录区分；C++ 的类可以在头文件之外定义方法，按命名空间区分
+    pub fn stable_id(language: &str, file_path: &Path, namespace: &str, name: &str) -> Uuid {
+        let key = match language {
+            "go" => format!("go:{}:{}", file_path.parent().unwrap_or(file_path).display(), name),
+            "cpp" => format!("cpp:{}::{}", namespace, name),
+            _ => format!("{}:{}:{}", language, file_path.display(), name),
+        };
+        Uuid::from_bytes(md5::compute(key).0)
+    }
+}
+
 /// 类类型
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum ClassType {
     Class,
     Struct,
@@ -386,6 +957,8 @@ pub enum EntityEdgeType {
     Implements,    // 类实现接口
     Imports,       // 导入关系
     DefinesIn,     // 在文件中定义
+    Overrides,     // 方法覆盖（或实现）父类型中的同名方法
+    DecoratedBy,   // 函数或类被装饰器（注解）装饰，元数据记录装饰器名、参数、行号和框架入口类型
 }
 
 /// 实体边
@@ -653,6 +1226,84 @@ impl EntityGraph {
         }).collect()
     }
 
+    /// 直接或间接的父类型（沿 `Inherits`、`Implements` 边）
+    pub fn get_supertypes(&self, class_id: &Uuid) -> Vec<&ClassInfo> {
+        let mut supertypes = Vec::new();
+        let mut visited = std::collections::HashSet::from([*class_id]);
+        let mut queue: std::collections::VecDeque<NodeIndex> = self.get_node_index(class_id).into_iter().collect();
+        while let Some(node) = queue.pop_front() {
+            for edge in self.graph.edges_directed(node, Direction::Outgoing) {
+                if !matches!(edge.weight().edge_type, EntityEdgeType::Inherits | EntityEdgeType::Implements) {
+                    continue;
+                }
+                if let Some(EntityNode::Class(class)) = self.graph.node_weight(edge.target()) {
+                    if visited.insert(class.id) {
+                        supertypes.push(class);
+                        queue.push_back(edge.target());
+                    }
+                }
+            }
+        }
+        supertypes
+    }
+
+    /// 方法所在的类
+    pub fn get_owner_class(&self, function_id: &Uuid) -> Option<&ClassInfo> {
+        let node = self.get_node_index(function_id)?;
+        self.graph
+            .edges_directed(node, Direction::Incoming)
+            .filter(|edge| matches!(edge.weight().edge_type, EntityEdgeType::Contains))
+            .find_map(|edge| match self.graph.node_weight(edge.source()) {
+                Some(EntityNode::Class(class)) => Some(class),
+                _ => None,
+            })
+    }
+
+    /// 被框架装饰器（`@app.route`、`@Scheduled`、`@Get` 等）注册为入口的函数及其 `DecoratedBy` 边，
+    /// 按文件和行号排序
+    pub fn entry_points(&self) -> Vec<(&FunctionInfo, &EntityEdge)> {
+        let mut entry_points: Vec<(&FunctionInfo, &EntityEdge)> = self
+            .graph
+            .edge_references()
+            .filter(|edge| {
+                matches!(edge.weight().edge_type, EntityEdgeType::DecoratedBy)
+                    && edge.weight().metadata.as_ref().is_some_and(|m| m.get("entry_point").is_some())
+            })
+            .filter_map(|edge| match self.graph.node_weight(edge.source()) {
+                Some(EntityNode::Function(function)) => Some((function, edge.weight())),
+                _ => None,
+            })
+            .collect();
+        entry_points.sort_by(|a, b| (&a.0.file_path, a.0.line_start).cmp(&(&b.0.file_path, b.0.line_start)));
+        entry_points
+    }
+
+    /// 接口（或基类）`type_name` 中 `method` 的具体实现：直接或间接继承该类型的类中覆盖该方法、且有实现的方法，
+    /// 与所在的类一起返回
+    pub fn find_implementations(&self, type_name: &str, method: &str) -> Vec<(&ClassInfo, &FunctionInfo)> {
+        let mut seen = std::collections::HashSet::new();
+        let mut implementations = Vec::new();
+        for class in self.find_classes_by_name(type_name) {
+            for declared in self.get_class_members(&class.id).into_iter().filter(|f| f.name == method) {
+                let Some(node) = self.get_node_index(&declared.id) else { continue };
+                for edge in self.graph.edges_directed(node, Direction::Incoming) {
+                    if !matches!(edge.weight().edge_type, EntityEdgeType::Overrides) {
+                        continue;
+                    }
+                    let Some(EntityNode::Function(function)) = self.graph.node_weight(edge.source()) else { continue };
+                    if function.is_abstract() || !seen.insert(function.id) {
+                        continue;
+                    }
+                    if let Some(owner) = self.get_owner_class(&function.id) {
+                        implementations.push((owner, function));
+                    }
+                }
+            }
+        }
+        implementations.sort_by(|a, b| (&a.1.file_path, a.1.line_start).cmp(&(&b.1.file_path, b.1.line_start)));
+        implementations
+    }
+
     /// 导出为JSON格式
     pub fn to_json(&self) -> serde_json::Result<String> {
         serde_json::to_string_pretty(self)
@@ -680,6 +1331,39 @@ pub struct FileMetadata {
     pub language: String,
 }
 
+/// ERROR 节点占语法树节点的比例达到该值时，认为文件的提取结果不可靠
+pub const SUSPECT_ERROR_RATIO: f64 = 0.01;
+
+/// 构建时一个文件的解析统计
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FileParseStats {
+    pub language: String,
+    /// 解析并提取符号的耗时（毫秒）
+    pub duration_ms: f64,
+    /// 语法树的节点数；不使用 tree-sitter 语法的语言（SQL、Bash 等）为空
+    pub node_count: Option<usize>,
+    /// tree-sitter 无法匹配语法而插入的 ERROR 节点数
+    pub error_nodes: Option<usize>,
+    /// 解析失败的原因，此时文件中的函数和调用不在图中
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub parse_error: Option<String>,
+}
+
+impl FileParseStats {
+    /// ERROR 节点占全部节点的比例
+    pub fn error_ratio(&self) -> Option<f64> {
+        match (self.error_nodes, self.node_count) {
+            (Some(errors), Some(nodes)) if nodes > 0 => Some(errors as f64 / nodes as f64),
+            _ => None,
+        }
+    }
+
+    /// 语法错误较多，从中提取的函数和调用可能缺失或错误
+    pub fn is_suspect(&self) -> bool {
+        self.parse_error.is_some() || self.error_ratio().is_some_and(|ratio| ratio >= SUSPECT_ERROR_RATIO)
+    }
+}
+
 /// 文件索引
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct FileIndex {
@@ -818,4 +1502,112 @@ impl SnippetIndex {
     pub fn clear_file_cache(&mut self, file_path: &PathBuf) {
         self.snippet_cache.retain(|(path, _, _), _| path != file_path);
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn function(name: &str, file: &str) -> FunctionInfo {
+        FunctionInfo {
+            id: Uuid::new_v4(),
+            name: name.to_string(),
+            file_path: PathBuf::from(file),
+            line_start: 1,
+            line_end: 2,
+            namespace: String::new(),
+            language: "rust".to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_function_kind_serialization() {
+        let mut macro_node = function("getter!", "lib.rs");
+        macro_node.kind = FunctionKind::Macro;
+        let value = serde_json::to_value(&macro_node).unwrap();
+        assert_eq!(value["kind"], "macro");
+
+        // 没有 kind 字段的旧数据按普通函数读取
+        let mut object = value.as_object().unwrap().clone();
+        object.remove("kind");
+        let function: FunctionInfo = serde_json::from_value(serde_json::Value::Object(object)).unwrap();
+        assert_eq!(function.kind, FunctionKind::Function);
+
+        let bytes = bincode::serialize(&macro_node).unwrap();
+        assert_eq!(bincode::deserialize::<FunctionInfo>(&bytes).unwrap().kind, FunctionKind::Macro);
+    }
+
+    #[test]
+    fn test_remove_function_keeps_invariants() {
+        let (a, b, c) = (function("a", "a.rs"), function("b", "a.rs"), function("c", "c.rs"));
+        let mut graph = PetCodeGraph::new();
+        for f in [&a, &b, &c] {
+            graph.add_function(f.clone());
+        }
+        graph.add_call(&a, &b);
+        graph.add_call(&c, &a);
+        graph.add_call(&a, &a);
+        graph.update_stats();
+        assert!(graph.validate().is_empty());
+
+        // 删除第一个节点后，原来的最后一个节点 c 被移动到索引 0
+        assert_eq!(graph.remove_function(&a.id).unwrap().name, "a");
+        graph.update_stats();
+        assert!(graph.validate().is_empty(), "{:?}", graph.validate());
+        assert_eq!(graph.get_function_by_id(&c.id).unwrap().name, "c");
+        assert_eq!(graph.stats.resolved_calls, 0);
+        assert!(graph.remove_function(&a.id).is_none());
+
+        let removed = graph.remove_file(&PathBuf::from("c.rs"));
+        assert_eq!(removed.len(), 1);
+        assert!(graph.validate().is_empty(), "{:?}", graph.validate());
+        assert_eq!((graph.stats.total_functions, graph.stats.total_files), (1, 1));
+        graph.add_function(c.clone());
+        graph.update_stats();
+
+        // 像旧的增量删除代码那样手工修改映射会被检测出来
+        let node = graph.get_node_index(&b.id).unwrap();
+        graph.graph.remove_node(node);
+        graph.function_to_node.remove(&b.id);
+        graph.node_to_function.remove(&node);
+        let violations = graph.validate();
+        assert!(violations.contains(&GraphViolation::UnmappedNode { node: node.index(), function_id: c.id }));
+        assert!(violations.contains(&GraphViolation::StatsMismatch {
+            field: "total_functions".to_string(),
+            recorded: 2,
+            actual: 1,
+        }));
+        assert!(graph.check_invariants(true, "test").is_err());
+    }
+
+    #[test]
+    fn test_record_observed_call() {
+        let (main, handler, plugin) = (function("main", "main.rs"), function("handle", "handler.rs"), function("run", "plugin.rs"));
+        let mut placeholder = function("handle", "main.rs");
+        placeholder.namespace = "unresolved".to_string();
+        placeholder.kind = FunctionKind::Unresolved;
+        let mut graph = PetCodeGraph::new();
+        for f in [&main, &handler, &plugin, &placeholder] {
+            graph.add_function(f.clone());
+        }
+        let unresolved = CallRelation { callee_name: "router.handle".to_string(), ..CallRelation::between(&main, &placeholder) };
+        graph.add_call_relation(unresolved).unwrap();
+        graph.add_call(&handler, &plugin);
+        graph.update_stats();
+
+        // 指向占位节点的边改指向真实函数，占位节点被移除
+        assert_eq!(graph.record_observed_call(&main.id, &handler.id), Ok(ObservedCallMerge::Upgraded));
+        assert!(graph.get_function_by_id(&placeholder.id).is_none());
+        assert_eq!(graph.record_observed_call(&handler.id, &plugin.id), Ok(ObservedCallMerge::Confirmed));
+        assert_eq!(graph.record_observed_call(&main.id, &plugin.id), Ok(ObservedCallMerge::Added));
+        assert!(graph.record_observed_call(&main.id, &placeholder.id).is_err());
+
+        graph.update_stats();
+        assert!(graph.validate().is_empty(), "{:?}", graph.validate());
+        assert_eq!((graph.stats.resolved_calls, graph.stats.unresolved_calls), (3, 0));
+        let callees = graph.get_callees(&main.id);
+        assert_eq!(callees.len(), 2);
+        assert!(callees.iter().all(|(_, relation)| relation.observed && relation.is_resolved));
+    }
+}
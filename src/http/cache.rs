@@ -0,0 +1,225 @@
+use std::collections::{HashMap, VecDeque};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use axum::{
+    body::{Body, Bytes},
+    extract::{Request, State},
+    http::{
+        header::{CACHE_CONTROL, CONTENT_TYPE, ETAG, IF_NONE_MATCH},
+        HeaderMap, HeaderValue, StatusCode,
+    },
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use parking_lot::Mutex;
+
+use super::config::CacheConfig;
+use super::state::AppState;
+use super::tenant::request_token;
+use super::versioning::ApiVersion;
+
+/// Largest request body the cache layer buffers to compute a key
+const MAX_REQUEST_BYTES: usize = 1024 * 1024;
+
+#[derive(Clone)]
+struct CachedResponse {
+    content_type: Option<HeaderValue>,
+    body: Bytes,
+}
+
+#[derive(Default)]
+struct Entries {
+    map: HashMap<String, CachedResponse>,
+    // least recently used first
+    order: VecDeque<String>,
+}
+
+/// LRU cache of query responses keyed by their ETag.
+///
+/// The ETag hashes the tenant's storage snapshot together with the request,
+/// so any rebuild changes every key and stale entries simply age out. A
+/// config reload clears the cache, as new limits may change the answer.
+pub struct QueryCache {
+    config: CacheConfig,
+    entries: Mutex<Entries>,
+    // bumped on config reload, since limits can change a query's outcome
+    generation: AtomicU64,
+}
+
+impl QueryCache {
+    pub fn new(config: CacheConfig) -> Self {
+        Self {
+            config,
+            entries: Mutex::new(Entries::default()),
+            generation: AtomicU64::new(0),
+        }
+    }
+
+    /// Drop all entries and invalidate previously issued ETags
+    pub fn clear(&self) {
+        *self.entries.lock() = Entries::default();
+        self.generation.fetch_add(1, Ordering::SeqCst);
+    }
+
+    fn get(&self, key: &str) -> Option<CachedResponse> {
+        let mut entries = self.entries.lock();
+        let hit = entries.map.get(key).cloned()?;
+        if let Some(pos) = entries.order.iter().position(|k| k == key) {
+            entries.order.remove(pos);
+        }
+        entries.order.push_back(key.to_string());
+        Some(hit)
+    }
+
+    fn insert(&self, key: String, response: CachedResponse) {
+        if self.config.max_entries == 0 || response.body.len() > self.config.max_response_bytes {
+            return;
+        }
+        let mut entries = self.entries.lock();
+        if entries.map.insert(key.clone(), response).is_none() {
+            entries.order.push_back(key);
+        }
+        while entries.map.len() > self.config.max_entries {
+            let Some(oldest) = entries.order.pop_front() else {
+                break;
+            };
+            entries.map.remove(&oldest);
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.lock().map.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+/// ETag for a request against a storage snapshot
+fn compute_etag(snapshot: &str, tenant: Option<&str>, method: &str, uri: &str, body: &[u8]) -> String {
+    let mut context = md5::Context::new();
+    for part in [snapshot.as_bytes(), tenant.unwrap_or("").as_bytes(), method.as_bytes(), uri.as_bytes()] {
+        context.consume(part);
+        context.consume([0u8]);
+    }
+    context.consume(body);
+    format!("\"{:x}\"", context.compute())
+}
+
+fn etag_matches(headers: &HeaderMap, etag: &str) -> bool {
+    headers
+        .get_all(IF_NONE_MATCH)
+        .iter()
+        .filter_map(|v| v.to_str().ok())
+        .flat_map(|v| v.split(','))
+        .map(|tag| tag.trim().trim_start_matches("W/"))
+        .any(|tag| tag == "*" || tag == etag)
+}
+
+fn with_validators(mut response: Response, etag: &str, cache_status: &'static str) -> Response {
+    let headers = response.headers_mut();
+    if let Ok(value) = HeaderValue::from_str(etag) {
+        headers.insert(ETAG, value);
+    }
+    headers.insert(CACHE_CONTROL, HeaderValue::from_static("private, no-cache"));
+    headers.insert("x-cache", HeaderValue::from_static(cache_status));
+    response
+}
+
+/// Middleware for pure query endpoints: answers `If-None-Match` with 304,
+/// serves repeated queries from the cache and tags responses with an ETag.
+pub async fn cache_query_response(State(state): State<AppState>, request: Request, next: Next) -> Response {
+    let token = request_token(request.headers(), request.uri());
+    // Unauthenticated requests go straight to the handler, which rejects them
+    let Ok(storage) = state.tenants.load().storage_for_token(token.as_deref()) else {
+        return next.run(request).await;
+    };
+
+    let (parts, body) = request.into_parts();
+    let Ok(body) = axum::body::to_bytes(body, MAX_REQUEST_BYTES).await else {
+        return StatusCode::PAYLOAD_TOO_LARGE.into_response();
+    };
+    // v1 and v2 bodies of the same query differ, so their ETags must too
+    let version = parts.extensions.get::<ApiVersion>().copied().unwrap_or_default();
+    let snapshot = format!(
+        "{}/{}/v{}",
+        storage.snapshot_id(),
+        state.cache.generation.load(Ordering::SeqCst),
+        version.number()
+    );
+    let etag = compute_etag(
+        &snapshot,
+        storage.tenant_id(),
+        parts.method.as_str(),
+        &parts.uri.to_string(),
+        &body,
+    );
+
+    if etag_matches(&parts.headers, &etag) {
+        return with_validators(StatusCode::NOT_MODIFIED.into_response(), &etag, "revalidated");
+    }
+    if let Some(hit) = state.cache.get(&etag) {
+        let mut response = Response::new(Body::from(hit.body));
+        if let Some(content_type) = hit.content_type {
+            response.headers_mut().insert(CONTENT_TYPE, content_type);
+        }
+        return with_validators(response, &etag, "hit");
+    }
+
+    let response = next.run(Request::from_parts(parts, Body::from(body))).await;
+    if response.status() != StatusCode::OK {
+        return response;
+    }
+    let (parts, body) = response.into_parts();
+    let Ok(body) = axum::body::to_bytes(body, usize::MAX).await else {
+        return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+    };
+    state.cache.insert(
+        etag.clone(),
+        CachedResponse {
+            content_type: parts.headers.get(CONTENT_TYPE).cloned(),
+            body: body.clone(),
+        },
+    );
+    with_validators(Response::from_parts(parts, Body::from(body)), &etag, "miss")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lru_eviction() {
+        let cache = QueryCache::new(CacheConfig {
+            max_entries: 2,
+            max_response_bytes: 16,
+        });
+        let entry = |body: &'static str| CachedResponse {
+            content_type: None,
+            body: Bytes::from_static(body.as_bytes()),
+        };
+        cache.insert("a".to_string(), entry("1"));
+        cache.insert("b".to_string(), entry("2"));
+        assert!(cache.get("a").is_some());
+        cache.insert("c".to_string(), entry("3"));
+        assert!(cache.get("b").is_none());
+        assert!(cache.get("a").is_some() && cache.get("c").is_some());
+
+        cache.insert("big".to_string(), entry("this body is over the limit"));
+        assert!(cache.get("big").is_none());
+    }
+
+    #[test]
+    fn test_etag_depends_on_snapshot_and_request() {
+        let etag = compute_etag("s1", None, "POST", "/query_call_graph", b"{}");
+        assert_eq!(etag, compute_etag("s1", None, "POST", "/query_call_graph", b"{}"));
+        assert_ne!(etag, compute_etag("s2", None, "POST", "/query_call_graph", b"{}"));
+        assert_ne!(etag, compute_etag("s1", Some("team"), "POST", "/query_call_graph", b"{}"));
+        assert_ne!(etag, compute_etag("s1", None, "POST", "/query_call_graph", b"{\"a\":1}"));
+
+        let mut headers = HeaderMap::new();
+        headers.insert(IF_NONE_MATCH, HeaderValue::from_str(&format!("\"x\", W/{}", etag)).unwrap());
+        assert!(etag_matches(&headers, &etag));
+    }
+}
@@ -0,0 +1,324 @@
+use std::fs;
+use std::path::Path;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use crate::storage::StorageQuota;
+
+use super::versioning::ApiVersion;
+
+/// Limits applied to graph traversal queries
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct QueryLimits {
+    /// Largest `max_depth` accepted by traversal endpoints
+    pub max_depth: usize,
+    /// Largest number of nodes a single traversal response may contain
+    pub max_nodes: usize,
+    /// Files parsed concurrently by batch endpoints such as `query_code_skeleton`
+    pub batch_parallelism: usize,
+}
+
+impl Default for QueryLimits {
+    fn default() -> Self {
+        Self {
+            max_depth: 5,
+            max_nodes: 2000,
+            batch_parallelism: 8,
+        }
+    }
+}
+
+/// Cross-origin access policy
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CorsConfig {
+    /// Origins allowed to call the API, e.g. `https://ide.example.com`; `"*"` allows any
+    pub allowed_origins: Vec<String>,
+    pub allowed_methods: Vec<String>,
+    pub allowed_headers: Vec<String>,
+    /// Let browsers send cookies/credentials; requires explicit origins, methods and headers
+    pub allow_credentials: bool,
+    pub max_age_secs: u64,
+}
+
+impl Default for CorsConfig {
+    fn default() -> Self {
+        Self {
+            allowed_origins: vec!["*".to_string()],
+            allowed_methods: vec!["GET".to_string(), "POST".to_string(), "OPTIONS".to_string()],
+            allowed_headers: ["content-type", "authorization", "if-none-match", "x-request-id"]
+                .iter()
+                .map(|h| h.to_string())
+                .collect(),
+            allow_credentials: false,
+            max_age_secs: 3600,
+        }
+    }
+}
+
+/// Standard security headers added to every response
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SecurityHeadersConfig {
+    pub enabled: bool,
+    pub content_security_policy: String,
+    /// Send `Strict-Transport-Security` with this max-age; only enable behind TLS
+    pub hsts_max_age_secs: Option<u64>,
+}
+
+impl Default for SecurityHeadersConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            // The graph pages use inline scripts and load ECharts from jsDelivr
+            content_security_policy: "default-src 'self'; script-src 'self' 'unsafe-inline' https://cdn.jsdelivr.net; \
+                style-src 'self' 'unsafe-inline'; img-src 'self' data:; frame-ancestors 'none'"
+                .to_string(),
+            hsts_max_age_secs: None,
+        }
+    }
+}
+
+/// Response cache for pure query endpoints
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CacheConfig {
+    /// Number of cached responses kept; 0 disables caching (ETags still work)
+    pub max_entries: usize,
+    /// Responses larger than this are not cached
+    pub max_response_bytes: usize,
+}
+
+impl Default for CacheConfig {
+    fn default() -> Self {
+        Self {
+            max_entries: 512,
+            max_response_bytes: 4 * 1024 * 1024,
+        }
+    }
+}
+
+/// A project re-indexed periodically by the background scheduler
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReindexSchedule {
+    /// Project directory, as passed to `/build_graph`
+    pub project_dir: String,
+    /// Interval between runs, e.g. `"90s"`, `"15m"`, `"6h"`, `"1d"`
+    pub every: String,
+    /// Ignore saved file hashes and rebuild from scratch instead of refreshing incrementally
+    #[serde(default)]
+    pub full_rebuild: bool,
+    /// Also run once right after the server starts
+    #[serde(default)]
+    pub run_on_start: bool,
+    /// Tenant whose storage receives the graph; the default storage when unset
+    #[serde(default)]
+    pub tenant: Option<String>,
+    #[serde(default)]
+    pub languages: Vec<String>,
+    #[serde(default)]
+    pub exclude_paths: Vec<String>,
+}
+
+impl ReindexSchedule {
+    /// Parse `every` into a duration
+    pub fn interval(&self) -> Result<Duration, String> {
+        parse_interval(&self.every)
+    }
+}
+
+/// Parse an interval such as `"30s"`, `"15m"`, `"2h"` or `"1d"`; a bare number is seconds
+pub fn parse_interval(value: &str) -> Result<Duration, String> {
+    let value = value.trim();
+    let split = value.find(|c: char| !c.is_ascii_digit()).unwrap_or(value.len());
+    let (number, unit) = value.split_at(split);
+    let number: u64 = number
+        .parse()
+        .map_err(|_| format!("Invalid interval '{}', expected e.g. \"15m\"", value))?;
+    let seconds = match unit.trim() {
+        "" | "s" => number,
+        "m" => number * 60,
+        "h" => number * 3600,
+        "d" => number * 86400,
+        other => return Err(format!("Unknown interval unit '{}' in '{}'", other, value)),
+    };
+    if seconds == 0 {
+        return Err(format!("Interval '{}' must be greater than zero", value));
+    }
+    Ok(Duration::from_secs(seconds))
+}
+
+/// Response versioning
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ApiConfig {
+    /// Shape of responses to requests whose `Accept` header names no
+    /// `application/vnd.codegraph.v<N>+json` type
+    pub default_version: ApiVersion,
+}
+
+/// A repository whose push events trigger a rebuild via `POST /hooks/git`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WebhookRepository {
+    /// `owner/name` (GitHub `full_name`, GitLab `path_with_namespace`) or a clone URL
+    pub name: String,
+    /// Local checkout that is re-analyzed
+    pub project_dir: String,
+    /// Only react to pushes to these branches; empty means any ref
+    #[serde(default)]
+    pub branches: Vec<String>,
+    /// Fetch the pushed ref and fast-forward the checkout before analyzing
+    #[serde(default)]
+    pub sync: bool,
+    /// Tenant whose storage receives the graph; the default storage when unset
+    #[serde(default)]
+    pub tenant: Option<String>,
+    #[serde(default)]
+    pub languages: Vec<String>,
+    #[serde(default)]
+    pub exclude_paths: Vec<String>,
+}
+
+/// SCM push webhook settings
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WebhookConfig {
+    /// Shared secret: GitHub signs the body with it, GitLab sends it as `X-Gitlab-Token`.
+    /// Webhooks are rejected while it is unset.
+    pub secret: Option<String>,
+    #[serde(rename = "repository")]
+    pub repositories: Vec<WebhookRepository>,
+}
+
+/// Expiring read-only links to one project snapshot, issued by `POST /projects/:id/share`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ShareConfig {
+    /// Key that signs share tokens; sharing is disabled while it is unset.
+    /// Changing it revokes every token issued so far.
+    pub secret: Option<String>,
+    /// Lifetime of a token when the request does not ask for one, e.g. `"1d"`
+    pub default_ttl: String,
+    /// Longest lifetime a token may be issued for
+    pub max_ttl: String,
+}
+
+impl Default for ShareConfig {
+    fn default() -> Self {
+        Self {
+            secret: None,
+            default_ttl: "1d".to_string(),
+            max_ttl: "7d".to_string(),
+        }
+    }
+}
+
+/// A tenant with its own isolated storage, selected by its bearer token
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TenantConfig {
+    /// Storage directory name under `.codegraph_db/tenants/`
+    pub id: String,
+    /// Clients send `Authorization: Bearer <token>` (or `?access_token=`) to act as this tenant
+    pub token: String,
+    #[serde(flatten)]
+    pub quota: StorageQuota,
+}
+
+/// A project whose stored graph is loaded into memory at startup and on reload
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PreloadProject {
+    pub project_dir: String,
+    /// Tenant whose storage holds the project; the default storage when unset
+    #[serde(default)]
+    pub tenant: Option<String>,
+}
+
+/// Server-wide configuration, loaded from a TOML file via `server --config`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ServerConfig {
+    pub limits: QueryLimits,
+    pub cors: CorsConfig,
+    pub security_headers: SecurityHeadersConfig,
+    pub cache: CacheConfig,
+    pub api: ApiConfig,
+    #[serde(rename = "schedule")]
+    pub schedules: Vec<ReindexSchedule>,
+    pub webhook: WebhookConfig,
+    /// When non-empty, every request must carry a tenant token
+    #[serde(rename = "tenant")]
+    pub tenants: Vec<TenantConfig>,
+    /// Log filter, e.g. `"info"` or `"codegraph_cli=debug,info"`; overrides `RUST_LOG`
+    pub log_level: Option<String>,
+    /// Bearer token for `/admin/*` endpoints; they are disabled while unset
+    pub admin_token: Option<String>,
+    #[serde(rename = "preload")]
+    pub preload: Vec<PreloadProject>,
+    pub share: ShareConfig,
+}
+
+impl ServerConfig {
+    /// Reject combinations that are unsafe or that browsers refuse
+    pub fn validate(&self) -> Result<(), String> {
+        if self.cors.allow_credentials {
+            // Browsers reject a wildcard in any of these once credentials are allowed
+            let lists = [
+                ("allowed_origins", &self.cors.allowed_origins),
+                ("allowed_methods", &self.cors.allowed_methods),
+                ("allowed_headers", &self.cors.allowed_headers),
+            ];
+            if let Some((name, _)) = lists.iter().find(|(_, values)| values.iter().any(|v| v == "*")) {
+                return Err(format!("cors.allow_credentials requires explicit {} instead of \"*\"", name));
+            }
+        }
+        for schedule in &self.schedules {
+            schedule
+                .interval()
+                .map_err(|e| format!("Invalid schedule for {}: {}", schedule.project_dir, e))?;
+        }
+        let default_ttl = parse_interval(&self.share.default_ttl).map_err(|e| format!("Invalid share.default_ttl: {}", e))?;
+        let max_ttl = parse_interval(&self.share.max_ttl).map_err(|e| format!("Invalid share.max_ttl: {}", e))?;
+        if default_ttl > max_ttl {
+            return Err("share.default_ttl must not exceed share.max_ttl".to_string());
+        }
+        Ok(())
+    }
+
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let content = fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read config {}: {}", path.display(), e))?;
+        let config: Self =
+            toml::from_str(&content).map_err(|e| format!("Invalid config {}: {}", path.display(), e))?;
+        config
+            .validate()
+            .map_err(|e| format!("Invalid config {}: {}", path.display(), e))?;
+        Ok(config)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate_rejects_credentials_with_wildcards() {
+        let mut config = ServerConfig::default();
+        config.cors.allow_credentials = true;
+        assert!(config.validate().unwrap_err().contains("allowed_origins"));
+
+        config.cors.allowed_origins = vec!["https://ide.example.com".to_string()];
+        assert!(config.validate().is_ok());
+
+        config.cors.allowed_methods = vec!["*".to_string()];
+        assert!(config.validate().unwrap_err().contains("allowed_methods"));
+
+        config.cors.allowed_methods = vec!["GET".to_string()];
+        config.cors.allowed_headers = vec!["content-type".to_string(), "*".to_string()];
+        assert!(config.validate().unwrap_err().contains("allowed_headers"));
+
+        config.cors.allow_credentials = false;
+        assert!(config.validate().is_ok());
+    }
+}
@@ -1,20 +1,27 @@
 use axum::{
-    extract::{State, Query},
+    extract::{State, Query, Path},
     response::{Json, Html},
-    http::StatusCode,
+    http::{HeaderMap, StatusCode},
+    body::Bytes,
 };
 use std::sync::Arc;
 use crate::storage::StorageManager;
 use crate::services::CodeAnalyzer;
+use super::config::ServerConfig;
+use super::scheduler::{ReindexJob, ReindexScheduler};
+use super::tenant::{TenantRegistry, TenantStorage};
+use super::state::AppState;
+use super::reload::{reload_config, ReloadReport};
 use super::models::*;
 use md5;
 use uuid;
 use serde_json::json;
 
 pub async fn build_graph(
-    State(storage): State<Arc<StorageManager>>,
+    State(scheduler): State<Arc<ReindexScheduler>>,
+    TenantStorage(storage): TenantStorage,
     Json(request): Json<BuildGraphRequest>,
-) -> Result<Json<ApiResponse<BuildGraphResponse>>, StatusCode> {
+) -> Result<Json<ApiResponse<BuildGraphResponse>>, ApiError> {
     let start_time = std::time::Instant::now();
 
     // Get project directory path
@@ -22,109 +29,97 @@ pub async fn build_graph(
     
     // Validate directory
     if !project_dir.exists() || !project_dir.is_dir() {
-        return Err(StatusCode::BAD_REQUEST);
+        return Err(StatusCode::BAD_REQUEST.into());
     }
 
-    // Generate project ID using MD5 hash of project directory
-    let project_id = format!("{:x}", md5::compute(request.project_dir.as_bytes()));
-
-    // Build the graph using CodeAnalyzer once
-    let mut analyzer = CodeAnalyzer::new();
-    let mut total_files = 0;
-    let mut total_functions = 0;
-
-    match analyzer.analyze_directory(project_dir) {
-        Ok(_code_graph) => {
-            if let Some(stats) = analyzer.get_stats() {
-                total_files = stats.total_files;
-                total_functions = stats.total_functions;
-            }
-
-            // Get the actual code graph for saving
-            if let Some(cg) = analyzer.get_code_graph() {
-                // Convert to PetCodeGraph for storage
-                let mut pet_graph = crate::codegraph::types::PetCodeGraph::new();
-
-                // Add all functions to the pet graph
-                for function in cg.functions.values() {
-                    pet_graph.add_function(function.clone());
-                }
-
-                tracing::info!("Added {} functions to PetCodeGraph", cg.functions.len());
-
-                // Add all call relations
-                let mut successful_relations = 0;
-                for relation in &cg.call_relations {
-                    if let Err(e) = pet_graph.add_call_relation(relation.clone()) {
-                        tracing::warn!("Failed to add call relation: {}", e);
-                    } else {
-                        successful_relations += 1;
-                    }
-                }
-
-                tracing::info!(
-                    "Successfully added {}/{} call relations to PetCodeGraph",
-                    successful_relations,
-                    cg.call_relations.len()
-                );
-
-                // Update stats and save the graph
-                pet_graph.update_stats();
-
-                if let Err(e) = storage.get_persistence().save_graph(&project_id, &pet_graph) {
-                    tracing::error!("Failed to save graph: {}", e);
-                    return Err(StatusCode::INTERNAL_SERVER_ERROR);
-                }
-
-                // Register this project as parsed for later querying
-                if let Err(e) = storage.get_persistence().register_project(&project_id, &request.project_dir) {
-                    tracing::warn!("Failed to register project in registry: {}", e);
-                }
-
-                // Cache the graph in memory for subsequent queries
-                storage.set_graph(pet_graph);
-            } else {
-                tracing::error!("Analyzer produced no code graph");
-                return Err(StatusCode::INTERNAL_SERVER_ERROR);
-            }
-        }
-        Err(e) => {
-            tracing::error!("Failed to analyze directory: {}", e);
-            return Err(StatusCode::INTERNAL_SERVER_ERROR);
-        }
+    // Scope the build to the requested languages and paths
+    let mut options = crate::codegraph::AnalyzeOptions {
+        force_rebuild: request.force_rebuild.unwrap_or(false),
+        strict: request.strict.unwrap_or(false),
+        expand_macros: request.expand_macros.unwrap_or(false),
+        embed_sources: request.embed_sources.unwrap_or(false),
+        heuristic_fallback: request.heuristic_fallback.unwrap_or(false),
+        framework_entry_points: request.framework_entry_points.unwrap_or(false),
+        ..Default::default()
+    };
+    if let Some(languages) = &request.languages {
+        options = options.with_languages(languages).map_err(|e| {
+            tracing::warn!("{}", e);
+            StatusCode::BAD_REQUEST
+        })?;
     }
+    options.exclude_paths.extend(request.exclude_patterns.clone().unwrap_or_default());
+    options.exclude_paths.extend(request.exclude_paths.clone().unwrap_or_default());
+
+    // Background re-indexes and storage compaction of the same project wait for this build
+    let _project_guard = scheduler
+        .lock_project(storage.tenant_id(), &crate::services::graph_builder::project_id_for(&request.project_dir))
+        .await;
+    let outcome = crate::services::build_and_store(&storage, &request.project_dir, options).map_err(|e| {
+        use crate::services::BuildError;
+        tracing::error!("{}", e);
+        match e {
+            BuildError::NotFound(msg) => ApiError::new(StatusCode::BAD_REQUEST, msg),
+            BuildError::QuotaExceeded(msg) => ApiError::new(StatusCode::FORBIDDEN, msg),
+            BuildError::Failed(_) => ApiError::from(StatusCode::INTERNAL_SERVER_ERROR),
+        }
+    })?;
+
+    // Cache the graph in memory for subsequent queries
+    let snapshot = outcome.snapshot;
+    storage.set_graph_with_snapshot(outcome.graph, Some(snapshot.clone()));
 
     let build_time_ms = start_time.elapsed().as_millis() as u64;
 
     let response = BuildGraphResponse {
-        project_id,
-        total_files,
-        total_functions,
+        project_id: outcome.project_id,
+        total_files: outcome.total_files,
+        total_functions: outcome.total_functions,
         build_time_ms,
     };
 
     Ok(Json(ApiResponse {
         success: true,
         data: response,
+        snapshot: Some(snapshot),
     }))
 }
 
 pub async fn query_call_graph(
-    State(storage): State<Arc<StorageManager>>,
+    TenantStorage(storage): TenantStorage,
+    State(config): State<Arc<ServerConfig>>,
     Json(request): Json<QueryCallGraphRequest>,
-) -> Result<Json<ApiResponse<QueryCallGraphResponse>>, StatusCode> {
+) -> Result<Json<ApiResponse<QueryCallGraphResponse>>, ApiError> {
+    // Retrieve a graph from the in-memory cache populated by init/build_graph
+    let graph = storage.get_graph_clone().ok_or(StatusCode::NOT_FOUND)?;
+    let response = call_graph_response(&storage, &graph, &config, request)?;
+
+    Ok(Json(ApiResponse {
+        success: true,
+        data: response,
+        snapshot: storage.graph_snapshot(),
+    }))
+}
+
+/// Callers and callees of the requested function (or of every function in the file), expanded to `max_depth`
+fn call_graph_response(
+    storage: &StorageManager,
+    graph: &crate::codegraph::types::PetCodeGraph,
+    config: &ServerConfig,
+    request: QueryCallGraphRequest,
+) -> Result<QueryCallGraphResponse, ApiError> {
+    let filter = EdgeFilter::new(&request);
     // Extract request parameters
     let filepath = request.filepath;
     let function_name = request.function_name;
     let max_depth = request.max_depth.unwrap_or(2); // Default max depth is 2
-    
-    // Retrieve a graph from the in-memory cache populated by init/build_graph
-    let graph = storage.get_graph_clone().ok_or(StatusCode::NOT_FOUND)?;
+    check_depth_limit(config, max_depth)?;
     
     // Debug: Log graph information
     tracing::info!("Loaded graph with {} functions", graph.get_stats().total_functions);
     
     let mut functions = Vec::new();
+    let mut budget = NodeBudget::new(config);
     
     if let Some(func_name) = function_name {
         // Query specific function by name
@@ -151,20 +146,16 @@ pub async fn query_call_graph(
                 name: function.name.clone(),
                 line_start: function.line_start,
                 line_end: function.line_end,
-                callers: callers.iter().map(|(caller_func, relation)| {
-                    super::models::CallRelation {
-                        function_name: caller_func.name.clone(),
-                        file_path: caller_func.file_path.display().to_string(),
-                    }
-                }).collect(),
-                callees: callees.iter().map(|(callee_func, relation)| {
-                    super::models::CallRelation {
-                        function_name: callee_func.name.clone(),
-                        file_path: callee_func.file_path.display().to_string(),
-                    }
-                }).collect(),
+                cell: function.cell,
+                parent_class_id: function.parent_class_id.map(|id| id.to_string()),
+                callers: filter.relations(graph, &callers),
+                callees: filter.relations(graph, &callees),
+                metrics: None,
             };
             
+            if !budget.take() {
+                break;
+            }
             functions.push(api_function);
         }
     } else {
@@ -193,20 +184,16 @@ pub async fn query_call_graph(
                 name: function.name.clone(),
                 line_start: function.line_start,
                 line_end: function.line_end,
-                callers: callers.iter().map(|(caller_func, relation)| {
-                    super::models::CallRelation {
-                        function_name: caller_func.name.clone(),
-                        file_path: caller_func.file_path.display().to_string(),
-                    }
-                }).collect(),
-                callees: callees.iter().map(|(callee_func, relation)| {
-                    super::models::CallRelation {
-                        function_name: callee_func.name.clone(),
-                        file_path: callee_func.file_path.display().to_string(),
-                    }
-                }).collect(),
+                cell: function.cell,
+                parent_class_id: function.parent_class_id.map(|id| id.to_string()),
+                callers: filter.relations(graph, &callers),
+                callees: filter.relations(graph, &callees),
+                metrics: None,
             };
             
+            if !budget.take() {
+                break;
+            }
             functions.push(api_function);
         }
     }
@@ -218,28 +205,114 @@ pub async fn query_call_graph(
         for function in &functions {
             // Expand callers chain
             let mut visited = std::collections::HashSet::new();
-            expand_call_chain(&graph, &function.id, &mut visited, &mut expanded_functions, max_depth - 1, true);
+            expand_call_chain(graph, &function.id, &mut visited, &mut expanded_functions, max_depth - 1, true, &filter, &mut budget);
             
             // Expand callees chain
             let mut visited = std::collections::HashSet::new();
-            expand_call_chain(&graph, &function.id, &mut visited, &mut expanded_functions, max_depth - 1, false);
+            expand_call_chain(graph, &function.id, &mut visited, &mut expanded_functions, max_depth - 1, false, &filter, &mut budget);
         }
         
         functions = expanded_functions;
     }
+    budget.check(config, "lower max_depth or narrow the query with function_name")?;
+
+    // Attach runtime metrics uploaded for the project that owns this file
+    let metrics = file_annotations(storage, graph, &filepath);
+    if !metrics.is_empty() {
+        for function in &mut functions {
+            function.metrics = metrics.get(&function.id).cloned();
+        }
+    }
     
-    let response = QueryCallGraphResponse {
+    Ok(QueryCallGraphResponse {
         filepath,
         functions,
-    };
-    
-    Ok(Json(ApiResponse {
-        success: true,
-        data: response,
-    }))
+    })
+}
+
+/// Which edges a call graph query follows and how it reports them
+struct EdgeFilter {
+    call_kinds: Option<Vec<crate::codegraph::types::CallKind>>,
+    include_unresolved: bool,
+    include_heuristic: bool,
+    group_call_sites: bool,
+}
+
+impl EdgeFilter {
+    fn new(request: &QueryCallGraphRequest) -> Self {
+        let call_kinds = match (&request.call_kinds, request.call_kind) {
+            (None, None) => None,
+            (kinds, kind) => Some(kinds.iter().flatten().copied().chain(kind).collect()),
+        };
+        Self {
+            call_kinds,
+            include_unresolved: request.include_unresolved.unwrap_or(true),
+            include_heuristic: request.include_heuristic.unwrap_or(true),
+            group_call_sites: request.group_call_sites.unwrap_or(false),
+        }
+    }
+
+    /// Followed edges in API form: one entry per call, or one per function and call kind with its call lines when grouping
+    fn relations(
+        &self,
+        graph: &crate::codegraph::types::PetCodeGraph,
+        edges: &[(&crate::codegraph::types::FunctionInfo, &crate::codegraph::types::CallRelation)],
+    ) -> Vec<super::models::CallRelation> {
+        let mut relations: Vec<super::models::CallRelation> = Vec::new();
+        let mut grouped: std::collections::HashMap<(uuid::Uuid, crate::codegraph::types::CallKind), usize> = std::collections::HashMap::new();
+        for (function, relation) in edges.iter().filter(|(_, relation)| self.follows(graph, relation)) {
+            if self.group_call_sites {
+                if let Some(&index) = grouped.get(&(function.id, relation.call_kind)) {
+                    let existing = &mut relations[index];
+                    existing.call_sites.get_or_insert_with(Vec::new).push(relation.line_number);
+                    existing.confidence = match (existing.confidence, relation.confidence) {
+                        (Some(a), Some(b)) => Some(a.min(b)),
+                        (a, b) => a.or(b),
+                    };
+                    continue;
+                }
+                grouped.insert((function.id, relation.call_kind), relations.len());
+            }
+            relations.push(self.relation(function, relation));
+        }
+        for relation in &mut relations {
+            if let Some(sites) = relation.call_sites.as_mut() {
+                sites.sort_unstable();
+                sites.dedup();
+            }
+        }
+        relations
+    }
+
+    fn relation(
+        &self,
+        function: &crate::codegraph::types::FunctionInfo,
+        relation: &crate::codegraph::types::CallRelation,
+    ) -> super::models::CallRelation {
+        super::models::CallRelation {
+            function_name: function.name.clone(),
+            file_path: function.file_path.display().to_string(),
+            call_kind: relation.call_kind,
+            confidence: relation.confidence,
+            call_sites: self.group_call_sites.then(|| vec![relation.line_number]),
+        }
+    }
+
+    fn follows(&self, graph: &crate::codegraph::types::PetCodeGraph, relation: &crate::codegraph::types::CallRelation) -> bool {
+        if self.call_kinds.as_ref().is_some_and(|kinds| !kinds.contains(&relation.call_kind)) {
+            return false;
+        }
+        if relation.is_resolved {
+            return self.include_heuristic || relation.confidence.is_none_or(|confidence| confidence >= 1.0);
+        }
+        // Unresolved calls point at a placeholder node; unresolved edges to real functions come from the name-based fallback
+        let placeholder = graph.get_function_by_id(&relation.callee_id).is_none_or(|callee| callee.is_unresolved());
+        if placeholder { self.include_unresolved } else { self.include_heuristic }
+    }
 }
 
 /// Helper function to expand call chains recursively
+#[allow(clippy::too_many_arguments)]
 fn expand_call_chain(
     graph: &crate::codegraph::types::PetCodeGraph,
     function_id: &str,
@@ -247,8 +320,10 @@ fn expand_call_chain(
     functions: &mut Vec<super::models::FunctionInfo>,
     depth: usize,
     is_caller: bool,
+    filter: &EdgeFilter,
+    budget: &mut NodeBudget,
 ) {
-    if depth == 0 || visited.contains(function_id) {
+    if depth == 0 || budget.exhausted || visited.contains(function_id) {
         return;
     }
     
@@ -267,6 +342,9 @@ fn expand_call_chain(
     };
     
     for (related_func, relation) in relations {
+        if !filter.follows(graph, relation) {
+            continue;
+        }
         // Check if we already have this function in our list
         let existing_function = functions.iter_mut().find(|f| f.id == related_func.id.to_string());
         
@@ -274,110 +352,132 @@ fn expand_call_chain(
             // Update existing function with new relations
             if is_caller {
                 // Add caller relation
-                let caller_relation = super::models::CallRelation {
-                    function_name: related_func.name.clone(),
-                    file_path: related_func.file_path.display().to_string(),
-                };
+                let caller_relation = filter.relation(related_func, relation);
                 
-                if !existing_function.callers.iter().any(|c| c.function_name == caller_relation.function_name) {
-                    existing_function.callers.push(caller_relation);
+                match existing_function.callers.iter_mut().find(|c| c.function_name == caller_relation.function_name) {
+                    Some(existing) => existing.call_sites.iter_mut().for_each(|sites| {
+                        if !sites.contains(&relation.line_number) {
+                            sites.push(relation.line_number);
+                            sites.sort_unstable();
+                        }
+                    }),
+                    None => existing_function.callers.push(caller_relation),
                 }
             } else {
                 // Add callee relation
-                let callee_relation = super::models::CallRelation {
-                    function_name: related_func.name.clone(),
-                    file_path: related_func.file_path.display().to_string(),
-                };
+                let callee_relation = filter.relation(related_func, relation);
                 
-                if !existing_function.callees.iter().any(|c| c.function_name == callee_relation.function_name) {
-                    existing_function.callees.push(callee_relation);
+                match existing_function.callees.iter_mut().find(|c| c.function_name == callee_relation.function_name) {
+                    Some(existing) => existing.call_sites.iter_mut().for_each(|sites| {
+                        if !sites.contains(&relation.line_number) {
+                            sites.push(relation.line_number);
+                            sites.sort_unstable();
+                        }
+                    }),
+                    None => existing_function.callees.push(callee_relation),
                 }
             }
         } else {
-            // Create new function entry
+            // Create new function entry, or stop the whole expansion once the budget is spent
+            if !budget.take() {
+                return;
+            }
             let mut new_function = super::models::FunctionInfo {
                 id: related_func.id.to_string(),
                 name: related_func.name.clone(),
                 line_start: related_func.line_start,
                 line_end: related_func.line_end,
+                cell: related_func.cell,
+                parent_class_id: related_func.parent_class_id.map(|id| id.to_string()),
                 callers: Vec::new(),
                 callees: Vec::new(),
+                metrics: None,
             };
             
             if is_caller {
                 // Add caller relation
-                new_function.callers.push(super::models::CallRelation {
-                    function_name: related_func.name.clone(),
-                    file_path: related_func.file_path.display().to_string(),
-                });
+                new_function.callers.push(filter.relation(related_func, relation));
             } else {
                 // Add callee relation
-                new_function.callees.push(super::models::CallRelation {
-                    function_name: related_func.name.clone(),
-                    file_path: related_func.file_path.display().to_string(),
-                });
+                new_function.callees.push(filter.relation(related_func, relation));
             }
             
             functions.push(new_function);
         }
         
         // Recursively expand this function's relations
-        expand_call_chain(graph, &related_func.id.to_string(), visited, functions, depth - 1, is_caller);
+        expand_call_chain(graph, &related_func.id.to_string(), visited, functions, depth - 1, is_caller, filter, budget);
     }
 }
 
 /// New handler for hierarchical tree structure output
 pub async fn query_hierarchical_graph(
-    State(storage): State<Arc<StorageManager>>,
+    TenantStorage(storage): TenantStorage,
+    State(config): State<Arc<ServerConfig>>,
     Json(request): Json<super::models::QueryHierarchicalGraphRequest>,
-) -> Result<Json<ApiResponse<super::models::QueryHierarchicalGraphResponse>>, StatusCode> {
+) -> Result<Json<ApiResponse<super::models::QueryHierarchicalGraphResponse>>, ApiError> {
+    // Load the requested project, or the first available one if none specified
+    let (project_id, graph) = load_project_graph(&storage, request.project_id.clone())?;
+    let snapshot = project_snapshot(&storage, &project_id);
+    let response = hierarchical_graph_response(&storage, &graph, project_id, &config, &request)?;
+
+    Ok(Json(ApiResponse {
+        success: true,
+        data: response,
+        snapshot,
+    }))
+}
+
+/// Call tree under `root_function`; without a root, the call trees of the framework entry points,
+/// or every function grouped by file when the project has none
+fn hierarchical_graph_response(
+    storage: &StorageManager,
+    graph: &crate::codegraph::types::PetCodeGraph,
+    project_id: String,
+    config: &ServerConfig,
+    request: &super::models::QueryHierarchicalGraphRequest,
+) -> Result<super::models::QueryHierarchicalGraphResponse, ApiError> {
     let max_depth = request.max_depth.unwrap_or(2); // Default max depth is 2
     let include_file_info = request.include_file_info.unwrap_or(true);
-    
-    // Try to find the project ID
-    let project_id = if let Some(pid) = request.project_id {
-        pid
-    } else if let Ok(projects) = storage.get_persistence().list_projects() {
-        // Use the first available project if none specified
-        projects.first().cloned().ok_or(StatusCode::NOT_FOUND)?
-    } else {
-        return Err(StatusCode::NOT_FOUND);
-    };
-    
-    // Load the code graph for the project
-    let graph = match storage.get_persistence().load_graph(&project_id) {
-        Ok(Some(graph)) => graph,
-        Ok(None) => return Err(StatusCode::NOT_FOUND),
-        Err(_) => return Err(StatusCode::INTERNAL_SERVER_ERROR),
-    };
+    check_depth_limit(config, max_depth)?;
     
     let stats = graph.get_stats();
     let total_functions = stats.total_functions;
     let total_relations = stats.resolved_calls + stats.unresolved_calls;
     
-    // Build hierarchical tree structure
-    let tree_structure = if let Some(root_func_name) = &request.root_function {
-        // Start from specific function
-        build_hierarchical_tree_from_function(&graph, root_func_name, max_depth, include_file_info)
-            .unwrap_or_else(|| create_default_tree_structure(&graph, include_file_info))
-    } else {
-        // Create default tree structure starting from main functions
-        create_default_tree_structure(&graph, include_file_info)
+    // Build hierarchical tree structure; call trees stop growing once the node budget is spent
+    let mut budget = NodeBudget::new(config);
+    let rooted = request.root_function.as_ref().and_then(|root_func_name| {
+        build_hierarchical_tree_from_function(graph, root_func_name, max_depth, include_file_info, &mut budget)
+    });
+    let call_tree = match rooted {
+        Some(tree) => {
+            budget.check(config, "lower max_depth or start from a narrower root_function")?;
+            Some(tree)
+        }
+        None if request.group_by_file != Some(true) => {
+            let entry_points = project_entry_points(storage, &project_id, graph);
+            let tree = build_entry_point_tree(graph, &entry_points, max_depth, include_file_info, &mut budget);
+            budget.check(config, "lower max_depth, pass a root_function, or set group_by_file to page through all functions")?;
+            tree
+        }
+        None => None,
+    };
+    // The grouped view is paged by file instead of rejected
+    let (tree_structure, next_offset) = match call_tree {
+        Some(tree) => (tree, None),
+        None => create_default_tree_structure(graph, include_file_info, request.offset.unwrap_or(0), config.limits.max_nodes),
     };
     
-    let response = super::models::QueryHierarchicalGraphResponse {
+    Ok(super::models::QueryHierarchicalGraphResponse {
         project_id,
         root_function: request.root_function.clone(),
         max_depth,
         tree_structure,
         total_functions,
         total_relations,
-    };
-    
-    Ok(Json(ApiResponse {
-        success: true,
-        data: response,
-    }))
+        next_offset,
+    })
 }
 
 /// Helper function to build hierarchical tree starting from a specific function
@@ -386,6 +486,7 @@ fn build_hierarchical_tree_from_function(
     function_name: &str,
     max_depth: usize,
     include_file_info: bool,
+    budget: &mut NodeBudget,
 ) -> Option<super::models::HierarchicalNode> {
     // Find the function by name
     let functions = graph.find_functions_by_name(function_name);
@@ -403,14 +504,61 @@ fn build_hierarchical_tree_from_function(
         0,
         &mut visited,
         include_file_info,
+        budget,
     ))
 }
 
-/// Helper function to create default tree structure
+/// Call trees of the functions that frameworks call (routes, scheduled jobs, listeners), or `None` when there are none
+fn build_entry_point_tree(
+    graph: &crate::codegraph::types::PetCodeGraph,
+    entry_points: &[crate::codegraph::entrypoints::EntryPoint],
+    max_depth: usize,
+    include_file_info: bool,
+    budget: &mut NodeBudget,
+) -> Option<super::models::HierarchicalNode> {
+    let mut children = Vec::new();
+    for entry in entry_points {
+        if budget.exhausted {
+            break;
+        }
+        let Some(function) = graph.get_function_by_id(&entry.function_id) else {
+            continue;
+        };
+        // Each entry point gets its own tree, so shared helpers appear under every route that reaches them
+        let mut visited = std::collections::HashSet::new();
+        let mut node = build_hierarchical_node(graph, function, max_depth, 0, &mut visited, include_file_info, budget);
+        let label = match (&entry.method, &entry.route) {
+            (Some(method), Some(route)) => format!("{} {}", method, route),
+            (None, Some(route)) => route.clone(),
+            _ => entry.kind.clone(),
+        };
+        node.name = format!("{} ({})", function.name, label);
+        node.call_type = Some("entry_point".to_string());
+        children.push(node);
+    }
+    if children.is_empty() {
+        return None;
+    }
+
+    Some(super::models::HierarchicalNode {
+        name: "Entry Points".to_string(),
+        function_id: None,
+        file_path: None,
+        line_start: None,
+        line_end: None,
+        children,
+        call_type: None,
+    })
+}
+
+/// Every function grouped by file, one page of files starting at `offset` that fits in `max_nodes`
+/// (a page holds at least one file), with the offset of the next page if more files follow
 fn create_default_tree_structure(
     graph: &crate::codegraph::types::PetCodeGraph,
     _include_file_info: bool,
-) -> super::models::HierarchicalNode {
+    offset: usize,
+    max_nodes: usize,
+) -> (super::models::HierarchicalNode, Option<usize>) {
     let _stats = graph.get_stats();
     
     // Create a root node that contains all functions
@@ -424,16 +572,24 @@ fn create_default_tree_structure(
         call_type: None,
     };
     
-    // Group functions by file for better organization
-    let mut file_groups: std::collections::HashMap<String, Vec<_>> = std::collections::HashMap::new();
+    // Group functions by file for better organization, and methods under their class
+    let mut file_groups: std::collections::BTreeMap<String, Vec<_>> = std::collections::BTreeMap::new();
+    let classes: std::collections::HashMap<uuid::Uuid, crate::codegraph::types::ClassInfo> =
+        if graph.get_all_functions().iter().any(|function| function.parent_class_id.is_some()) {
+            crate::services::graph_export::extract_classes(graph).into_iter().map(|class| (class.id, class)).collect()
+        } else {
+            std::collections::HashMap::new()
+        };
     
     for function in graph.get_all_functions() {
         let file_path = function.file_path.display().to_string();
         file_groups.entry(file_path).or_insert_with(Vec::new).push(function);
     }
     
-    // Create file-level nodes
-    for (file_path, functions) in file_groups {
+    // Create file-level nodes, in path order so pages are stable
+    let mut used = 1;
+    let mut next_offset = None;
+    for (index, (file_path, functions)) in file_groups.into_iter().enumerate().skip(offset) {
         let mut file_node = super::models::HierarchicalNode {
             name: format!("📁 {}", std::path::Path::new(&file_path).file_name().unwrap_or_default().to_string_lossy()),
             function_id: None,
@@ -444,7 +600,8 @@ fn create_default_tree_structure(
             call_type: None,
         };
         
-        // Add functions to file node
+        // Add functions to file node; methods go under a node for their class
+        let mut class_nodes: Vec<(uuid::Uuid, super::models::HierarchicalNode)> = Vec::new();
         for function in functions {
             let function_node = super::models::HierarchicalNode {
                 name: function.name.clone(),
@@ -456,13 +613,35 @@ fn create_default_tree_structure(
                 call_type: Some("function".to_string()),
             };
             
-            file_node.children.push(function_node);
+            let Some(class) = function.parent_class_id.and_then(|id| classes.get(&id)) else {
+                file_node.children.push(function_node);
+                continue;
+            };
+            match class_nodes.iter_mut().find(|(id, _)| *id == class.id) {
+                Some((_, class_node)) => class_node.children.push(function_node),
+                None => class_nodes.push((class.id, super::models::HierarchicalNode {
+                    name: class.name.clone(),
+                    function_id: None,
+                    file_path: Some(class.file_path.display().to_string()),
+                    line_start: Some(class.line_start),
+                    line_end: Some(class.line_end),
+                    children: vec![function_node],
+                    call_type: Some("class".to_string()),
+                })),
+            }
         }
+        file_node.children.extend(class_nodes.into_iter().map(|(_, class_node)| class_node));
         
+        let nodes = count_tree_nodes(&file_node);
+        if !root_node.children.is_empty() && used + nodes > max_nodes {
+            next_offset = Some(index);
+            break;
+        }
+        used += nodes;
         root_node.children.push(file_node);
     }
     
-    root_node
+    (root_node, next_offset)
 }
 
 /// Recursive function to build hierarchical node structure
@@ -473,8 +652,10 @@ fn build_hierarchical_node(
     current_depth: usize,
     visited: &mut std::collections::HashSet<String>,
     include_file_info: bool,
+    budget: &mut NodeBudget,
 ) -> super::models::HierarchicalNode {
-    if current_depth >= max_depth || visited.contains(&function.id.to_string()) {
+    budget.take();
+    if current_depth >= max_depth || budget.exhausted || visited.contains(&function.id.to_string()) {
         return super::models::HierarchicalNode {
             name: format!("{} (max depth reached)", function.name),
             function_id: Some(function.id.to_string()),
@@ -494,6 +675,9 @@ fn build_hierarchical_node(
     let mut children = Vec::new();
     
     for (callee_func, _relation) in callees {
+        if budget.exhausted {
+            break;
+        }
         let child_node = build_hierarchical_node(
             graph,
             callee_func,
@@ -501,6 +685,7 @@ fn build_hierarchical_node(
             current_depth + 1,
             visited,
             include_file_info,
+            budget,
         );
         children.push(child_node);
     }
@@ -517,17 +702,11 @@ fn build_hierarchical_node(
 }
 
 pub async fn query_code_snippet(
-    State(storage): State<Arc<StorageManager>>,
+    TenantStorage(storage): TenantStorage,
     Json(request): Json<QueryCodeSnippetRequest>,
 ) -> Result<Json<ApiResponse<CodeSnippetResponse>>, StatusCode> {
-    // Try to find the project ID by searching through stored graphs
-    let project_id = if let Ok(projects) = storage.get_persistence().list_projects() {
-        projects.first().cloned()
-    } else {
-        return Err(StatusCode::NOT_FOUND);
-    };
-    
-    let project_id = project_id.ok_or(StatusCode::NOT_FOUND)?;
+    // The registered project whose directory contains the file
+    let project_id = file_project_id(&storage, &request.filepath).ok_or(StatusCode::NOT_FOUND)?;
     
     // Load the code graph for the project
     let graph = match storage.get_persistence().load_graph(&project_id) {
@@ -556,20 +735,47 @@ pub async fn query_code_snippet(
         file_functions[0]
     };
     
+    // Prefer the source embedded at build time: it matches the analyzed revision
+    let embedded = storage
+        .get_persistence()
+        .load_function_bodies(&project_id)
+        .unwrap_or_else(|e| {
+            tracing::warn!("Failed to load embedded function bodies for {}: {}", project_id, e);
+            None
+        })
+        .and_then(|bodies| bodies.get(&target_function.id).cloned());
+
     // Read the file contents
-    let file_contents = match std::fs::read_to_string(&target_function.file_path) {
-        Ok(contents) => contents,
-        Err(e) => {
-            tracing::error!("Failed to read file {}: {}", target_function.file_path.display(), e);
-            return Err(StatusCode::INTERNAL_SERVER_ERROR);
-        }
+    let file_contents = match &embedded {
+        Some(body) => body.text.clone(),
+        None => match std::fs::read_to_string(&target_function.file_path) {
+            Ok(contents) => contents,
+            Err(e) => {
+                tracing::error!("Failed to read file {}: {}", target_function.file_path.display(), e);
+                return Err(StatusCode::INTERNAL_SERVER_ERROR);
+            }
+        },
+    };
+    // Notebook functions are numbered within their cell (embedded bodies already are the cell)
+    let file_contents = match (target_function.cell, &embedded) {
+        (Some(cell), None) => crate::codegraph::NotebookCells::parse(&file_contents)
+            .ok()
+            .and_then(|cells| cells.cell_source(cell).map(str::to_string))
+            .unwrap_or_default(),
+        _ => file_contents,
     };
     
     // Split file into lines
-    let lines: Vec<&str> = file_contents.lines().collect();
+    let lines: Vec<&str> = match &embedded {
+        Some(body) => body.lines(),
+        None => file_contents.lines().collect(),
+    };
     
     // Calculate line range for the snippet
-    let context_lines = request.context_lines.unwrap_or(3);
+    let mut context_lines = request.context_lines.unwrap_or(3);
+    if embedded.is_some() {
+        context_lines = context_lines.min(crate::storage::function_bodies::EMBEDDED_CONTEXT_LINES);
+    }
     let include_context = request.include_context.unwrap_or(true);
     
     let (line_start, line_end) = if include_context {
@@ -598,18 +804,26 @@ pub async fn query_code_snippet(
         .and_then(|ext| ext.to_str())
         .map(|ext| match ext.to_lowercase().as_str() {
             "rs" => "rust",
-            "py" => "python",
+            "py" | "ipynb" => "python",
             "js" => "javascript",
             "ts" => "typescript",
             "java" => "java",
             "cpp" | "cc" | "cxx" => "cpp",
             "c" => "c",
             "go" => "go",
+            "m" | "mm" => "objc",
+            "lua" => "lua",
+            "sh" | "bash" => "bash",
+            "sql" => "sql",
+            "proto" => "proto",
+            "vue" => "vue",
+            "svelte" => "svelte",
+            "html" | "htm" => "html",
             "php" => "php",
-            "rb" => "ruby",
+            "rb" | "rake" => "ruby",
             "swift" => "swift",
-            "kt" => "kotlin",
-            "scala" => "scala",
+            "kt" | "kts" => "kotlin",
+            "scala" | "sc" => "scala",
             "cs" => "csharp",
             _ => "unknown"
         })
@@ -623,112 +837,76 @@ pub async fn query_code_snippet(
         line_start: target_function.line_start,
         line_end: target_function.line_end,
         language,
+        from_snapshot: embedded.is_some(),
     };
     
     Ok(Json(ApiResponse {
         success: true,
         data: response,
+        snapshot: project_snapshot(&storage, &project_id),
     }))
 } 
 
 pub async fn query_code_skeleton(
-    State(_storage): State<Arc<StorageManager>>,
+    TenantStorage(storage): TenantStorage,
+    State(config): State<Arc<ServerConfig>>,
     Json(request): Json<QueryCodeSkeletonRequest>,
 ) -> Result<Json<ApiResponse<CodeSkeletonBatchResponse>>, StatusCode> {
+    let paths = request.filepaths.iter().map(std::path::PathBuf::from).collect();
+    // Files are parsed concurrently; unchanged ones are served from the skeleton cache
+    let results =
+        crate::services::cached_skeletons(storage.get_skeleton_cache(), paths, config.limits.batch_parallelism).await;
     let mut skeletons = Vec::new();
 
-    for filepath in &request.filepaths {
-        // Read file contents
-        let path = std::path::PathBuf::from(filepath);
-        let code = match std::fs::read_to_string(&path) {
-            Ok(c) => c,
-            Err(_) => {
-                // Skip files that can't be read, but continue processing others
-                tracing::warn!("Failed to read file: {}", filepath);
-                continue;
-            }
-        };
-
-        // Get parser and language
-        let (mut parser, language_id) = match crate::codegraph::treesitter::parsers::get_ast_parser_by_filename(&path) {
-            Ok(v) => v,
-            Err(_) => {
-                // Skip files that can't be parsed, but continue processing others
-                tracing::warn!("Failed to get parser for file: {}", filepath);
-                continue;
+    for (path, result) in results {
+        match result {
+            Ok((skeleton, cached)) => skeletons.push(CodeSkeletonResponse {
+                filepath: path.display().to_string(),
+                language: skeleton.language,
+                skeleton_text: skeleton.skeleton_text,
+                cached,
+            }),
+            Err(e) => {
+                // Skip files that can't be read or parsed, but continue processing others
+                tracing::warn!("{}", e);
             }
-        };
-
-        // Parse and build symbol maps
-        let symbols = parser.parse(&code, &path);
-        let symbols_struct: Vec<crate::codegraph::treesitter::ast_instance_structs::SymbolInformation> =
-            symbols.iter().map(|s| s.read().symbol_info_struct()).collect();
+        }
+    }
 
-        // Build guid maps similar to tests
-        use uuid::Uuid;
-        use std::collections::HashMap;
-        let guid_to_children: HashMap<Uuid, Vec<Uuid>> = symbols
+    // Trim to the token budget here so callers don't each re-implement the prioritisation
+    let budget = request.max_tokens.map(|max_tokens| {
+        let files: Vec<crate::services::ContextFile> = skeletons
             .iter()
-            .map(|s| (s.read().guid().clone(), s.read().childs_guid().clone()))
+            .map(|s| crate::services::ContextFile {
+                filepath: &s.filepath,
+                language: &s.language,
+                skeleton_text: &s.skeleton_text,
+            })
             .collect();
-
-        // Build a minimal FileASTMarkup-compatible list
-        let ast_markup = crate::codegraph::treesitter::file_ast_markup::FileASTMarkup {
-            symbols_sorted_by_path_len: symbols_struct.clone(),
-        };
-        let guid_to_info: HashMap<Uuid, &crate::codegraph::treesitter::ast_instance_structs::SymbolInformation> =
-            ast_markup
-                .symbols_sorted_by_path_len
-                .iter()
-                .map(|s| (s.guid.clone(), s))
-                .collect();
-
-        // Make formatter
-        let formatter = crate::codegraph::treesitter::skeletonizer::make_formatter(&language_id);
-
-        // Filter top-level struct/class and function symbols and build skeleton text
-        use crate::codegraph::treesitter::structs::SymbolType;
-        let class_symbols: Vec<_> = ast_markup
-            .symbols_sorted_by_path_len
-            .iter()
-            .filter(|x| x.symbol_type == SymbolType::StructDeclaration || x.symbol_type == SymbolType::FunctionDeclaration)
+        let assembly = crate::services::assemble_context(&files, max_tokens);
+        skeletons = skeletons
+            .drain(..)
+            .zip(assembly.texts)
+            .filter_map(|(skeleton, text)| Some(CodeSkeletonResponse { skeleton_text: text?, ..skeleton }))
             .collect();
-
-        let mut lines: Vec<String> = Vec::new();
-        for symbol in class_symbols {
-            let skeleton_line = formatter.make_skeleton(&symbol, &code.to_string(), &guid_to_children, &guid_to_info);
-            lines.push(skeleton_line);
-        }
-
-        let skeleton_text = if lines.is_empty() {
-            String::new()
-        } else {
-            lines.join("\n\n")
-        };
-
-        let language = language_id.to_string();
-
-        let skeleton_response = CodeSkeletonResponse {
-            filepath: path.display().to_string(),
-            language,
-            skeleton_text,
-        };
-
-        skeletons.push(skeleton_response);
-    }
+        assembly.budget
+    });
 
     let response = CodeSkeletonBatchResponse {
         skeletons,
+        budget,
     };
 
     Ok(Json(ApiResponse {
         success: true,
         data: response,
+        snapshot: None,
     }))
-} 
+}
 
 pub async fn draw_call_graph(
-    State(storage): State<Arc<StorageManager>>,
+    TenantStorage(storage): TenantStorage,
+    State(config): State<Arc<ServerConfig>>,
     Query(query): Query<super::models::DrawCallGraphQuery>,
 ) -> Result<Html<String>, StatusCode> {
     // Check if we have the required parameters
@@ -741,30 +919,61 @@ pub async fn draw_call_graph(
         filepath: query.filepath.clone(),
         function_name: query.function_name.clone(),
         max_depth: query.max_depth,
+        call_kind: None,
+        call_kinds: None,
+        include_unresolved: None,
+        include_heuristic: None,
+        group_call_sites: None,
     };
     
-    match query_call_graph(State(storage.clone()), Json(call_graph_request)).await {
-        Ok(resp) => {
-            let call_graph_data = resp.0.data;
-            let html_content = generate_echarts_call_graph_html(&call_graph_data);
-            Ok(Html(html_content))
-        }
-        Err(status) => {
-            let html = generate_error_page_html(
-                &query.filepath,
-                query.function_name.as_deref().unwrap_or(""),
-                status,
-            );
-            Ok(Html(html))
+    let result = query_call_graph(TenantStorage(storage.clone()), State(config), Json(call_graph_request))
+        .await
+        .map(|resp| resp.0.data);
+    Ok(Html(call_graph_page(&storage, &query, result, "/draw_call_graph")))
+}
+
+/// The interactive call-graph page, or an error page; `draw_url` is where the page's forms navigate
+fn call_graph_page(
+    storage: &StorageManager,
+    query: &super::models::DrawCallGraphQuery,
+    result: Result<QueryCallGraphResponse, ApiError>,
+    draw_url: &str,
+) -> String {
+    match result {
+        Ok(call_graph_data) => {
+            // Caller -> callee pairs on the hottest profiled paths, drawn as highlighted edges
+            let hot_edges: std::collections::HashSet<(String, String)> = file_project_annotations(storage, &query.filepath)
+                .map(|annotations| annotations.hot_paths)
+                .unwrap_or_default()
+                .iter()
+                .flat_map(|path| {
+                    let names: Vec<String> = path
+                        .functions
+                        .iter()
+                        .map(|f| f.rsplit_once("::").map_or(f.as_str(), |(_, name)| name).to_string())
+                        .collect();
+                    names.windows(2).map(|pair| (pair[0].clone(), pair[1].clone())).collect::<Vec<_>>()
+                })
+                .collect();
+            generate_echarts_call_graph_html(&call_graph_data, query.color_by.as_deref(), &hot_edges, draw_url)
         }
+        Err(error) => generate_error_page_html(
+            &query.filepath,
+            query.function_name.as_deref().unwrap_or(""),
+            &error,
+            draw_url,
+        ),
     }
 }
 
-fn generate_error_page_html(filepath: &str, function_name: &str, status: axum::http::StatusCode) -> String {
+fn generate_error_page_html(filepath: &str, function_name: &str, error: &ApiError, draw_url: &str) -> String {
     let title = "Function Call Graph - Error";
+    let status = StatusCode::from_u16(error.code).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR);
     let status_text = format!("{} {}", status.as_u16(), status.canonical_reason().unwrap_or("Error"));
     let suggestion = if status == axum::http::StatusCode::NOT_FOUND {
         "Graph data not found.".to_string()
+    } else if status == axum::http::StatusCode::UNPROCESSABLE_ENTITY {
+        error.error.clone()
     } else {
         "An error occurred while generating the call graph. Please check server logs.".to_string()
     };
@@ -775,6 +984,7 @@ fn generate_error_page_html(filepath: &str, function_name: &str, status: axum::h
     html = html.replace("__SUGGESTION__", &suggestion);
     html = html.replace("__FILEPATH__", filepath);
     html = html.replace("__FUNCTION_NAME__", function_name);
+    html = html.replace("__DRAW_URL__", draw_url);
     html
 }
 
@@ -788,19 +998,39 @@ fn generate_main_page_html() -> String {
 }
 
 
-fn generate_echarts_call_graph_html(call_graph_data: &super::models::QueryCallGraphResponse) -> String {
+fn generate_echarts_call_graph_html(
+    call_graph_data: &super::models::QueryCallGraphResponse,
+    color_by: Option<&str>,
+    hot_edges: &std::collections::HashSet<(String, String)>,
+    draw_url: &str,
+) -> String {
+    // Normalize the coloring metric to 0..1 across the drawn nodes
+    let metric_of = |function: &super::models::FunctionInfo| {
+        color_by.and_then(|metric| function.metrics.as_ref()?.get(metric).copied())
+    };
+    let (min_metric, max_metric) = call_graph_data
+        .functions
+        .iter()
+        .filter_map(metric_of)
+        .fold((f64::INFINITY, f64::NEG_INFINITY), |(lo, hi), v| (lo.min(v), hi.max(v)));
+
     // Prepare nodes with names and metadata (use function name for link resolution)
     let mut nodes: Vec<serde_json::Value> = Vec::new();
     let mut name_set: std::collections::HashSet<String> = std::collections::HashSet::new();
 
     for function in &call_graph_data.functions {
         name_set.insert(function.name.clone());
+        let heat = metric_of(function).map(|v| {
+            if max_metric > min_metric { (v - min_metric) / (max_metric - min_metric) } else { 1.0 }
+        });
         nodes.push(json!({
             "id": function.name,
             "name": function.name,
             "file_path": call_graph_data.filepath,
             "line_start": function.line_start,
-            "line_end": function.line_end
+            "line_end": function.line_end,
+            "metrics": function.metrics,
+            "heat": heat
         }));
     }
 
@@ -813,7 +1043,8 @@ fn generate_echarts_call_graph_html(call_graph_data: &super::models::QueryCallGr
                 links.push(json!({
                     "source": function.name,
                     "target": callee.function_name,
-                    "type": "calls"
+                    "type": "calls",
+                    "hot": hot_edges.contains(&(function.name.clone(), callee.function_name.clone()))
                 }));
             }
         }
@@ -823,7 +1054,8 @@ fn generate_echarts_call_graph_html(call_graph_data: &super::models::QueryCallGr
                 links.push(json!({
                     "source": caller.function_name,
                     "target": function.name,
-                    "type": "called_by"
+                    "type": "called_by",
+                    "hot": hot_edges.contains(&(caller.function_name.clone(), function.name.clone()))
                 }));
             }
         }
@@ -843,13 +1075,15 @@ fn generate_echarts_call_graph_html(call_graph_data: &super::models::QueryCallGr
         .map(|f| f.name.clone())
         .unwrap_or_else(|| "All functions".to_string());
     html = html.replace("__FUNCTION_NAME_INPUT__", &fn_input);
+    html = html.replace("__COLOR_BY_INPUT__", color_by.unwrap_or_default());
     html = html.replace("__GRAPH_JSON__", &serde_json::to_string(&graph_data).unwrap());
+    html = html.replace("__DRAW_URL__", draw_url);
 
     html
 } 
 
 pub async fn init(
-    State(storage): State<Arc<StorageManager>>,
+    TenantStorage(storage): TenantStorage,
     Json(request): Json<InitRequest>,
 ) -> Result<Json<ApiResponse<InitResponse>>, StatusCode> {
     let project_dir = std::path::Path::new(&request.project_dir);
@@ -864,8 +1098,9 @@ pub async fn init(
     match storage.get_persistence().load_graph(&project_id) {
         Ok(Some(graph)) => {
             let stats = graph.get_stats().clone();
+            let snapshot = project_snapshot(&storage, &project_id);
             // Cache in memory
-            storage.set_graph(graph);
+            storage.set_graph_with_snapshot(graph, snapshot.clone());
 
             let resp = InitResponse {
                 project_id,
@@ -874,10 +1109,11 @@ pub async fn init(
                 total_files: stats.total_files,
             };
 
-            Ok(Json(ApiResponse { success: true, data: resp }))
+            Ok(Json(ApiResponse { success: true, data: resp, snapshot }))
         }
         Ok(None) => {
             // Build and persist, then cache
+            let snapshot = crate::storage::GraphSnapshot::capture(project_dir);
             let mut analyzer = CodeAnalyzer::new();
             match analyzer.analyze_directory(project_dir) {
                 Ok(cg) => {
@@ -900,13 +1136,43 @@ pub async fn init(
                         return Err(StatusCode::INTERNAL_SERVER_ERROR);
                     }
 
+                    if let Err(e) = storage.get_persistence().save_snapshot(&project_id, &snapshot) {
+                        tracing::warn!("Failed to save snapshot info: {}", e);
+                    }
+                    let entry_points = crate::codegraph::entrypoints::detect_entry_points(&pet_graph);
+                    if let Err(e) = storage.get_persistence().save_entry_points(&project_id, &entry_points) {
+                        tracing::warn!("Failed to save entry points: {}", e);
+                    }
+                    let manifest = crate::storage::BuildManifest::capture(project_dir, &Default::default(), &snapshot);
+                    if let Err(e) = storage.get_persistence().save_manifest(&project_id, &manifest) {
+                        tracing::warn!("Failed to save build manifest: {}", e);
+                    }
+
                     // Register this project as parsed for later querying
                     if let Err(e) = storage.get_persistence().register_project(&project_id, &request.project_dir) {
                         tracing::warn!("Failed to register project in registry: {}", e);
                     }
 
+                    let files: std::collections::BTreeSet<String> = pet_graph
+                        .get_all_functions()
+                        .iter()
+                        .map(|function| function.file_path.display().to_string())
+                        .collect();
+                    crate::services::PostAnalysisHooks::run(&crate::services::AnalyzeResult {
+                        project_id: project_id.clone(),
+                        project_dir: request.project_dir.clone(),
+                        tenant: storage.tenant_id().map(str::to_string),
+                        incremental: false,
+                        total_files: stats.total_files,
+                        total_functions: stats.total_functions,
+                        resolved_calls: pet_graph.get_stats().resolved_calls,
+                        unresolved_calls: pet_graph.get_stats().unresolved_calls,
+                        changed_files: files.into_iter().collect(),
+                        snapshot: snapshot.clone(),
+                    });
+
                     // Cache in memory
-                    storage.set_graph(pet_graph);
+                    storage.set_graph_with_snapshot(pet_graph, Some(snapshot.clone()));
 
                     let resp = InitResponse {
                         project_id,
@@ -915,7 +1181,7 @@ pub async fn init(
                         total_files: stats.total_files,
                     };
 
-                    Ok(Json(ApiResponse { success: true, data: resp }))
+                    Ok(Json(ApiResponse { success: true, data: resp, snapshot: Some(snapshot) }))
                 }
                 Err(e) => {
                     tracing::error!("Failed to analyze directory: {}", e);
@@ -931,13 +1197,13 @@ pub async fn init(
 } 
 
 pub async fn investigate_repo(
-	State(storage): State<Arc<StorageManager>>,
+	TenantStorage(storage): TenantStorage,
 	Json(request): Json<super::models::InvestigateRepoRequest>,
 ) -> Result<Json<ApiResponse<super::models::InvestigateRepoResponse>>, StatusCode> {
 	// Ensure project is initialized (reuse init logic quickly)
 	let init_req = super::models::InitRequest { project_dir: request.project_dir.clone() };
-	let init_resp = match init(State(storage.clone()), Json(init_req)).await {
-		Ok(r) => r.0.data,
+	let (init_resp, snapshot) = match init(TenantStorage(storage.clone()), Json(init_req)).await {
+		Ok(r) => (r.0.data, r.0.snapshot),
 		Err(e) => return Err(e),
 	};
 
@@ -1084,7 +1350,7 @@ pub async fn investigate_repo(
 			let mut callers_set: std::collections::HashSet<(String, String)> = std::collections::HashSet::new();
 			let callers = graph.get_callers(func_id)
 				.into_iter()
-				.filter_map(|(caller, _rel)| {
+				.filter_map(|(caller, rel)| {
 					let function_name = caller.name.clone();
 					let file_path = caller.file_path.display().to_string().replace(&request.project_dir, "").trim_start_matches('/').to_string();
 					let key = (function_name.clone(), file_path.clone());
@@ -1092,6 +1358,9 @@ pub async fn investigate_repo(
 						Some(super::models::CallRelation {
 							function_name,
 							file_path,
+							call_kind: rel.call_kind,
+							confidence: rel.confidence,
+							call_sites: None,
 						})
 					} else {
 						None
@@ -1103,7 +1372,7 @@ pub async fn investigate_repo(
 			let mut callees_set: std::collections::HashSet<(String, String)> = std::collections::HashSet::new();
 			let callees = graph.get_callees(func_id)
 				.into_iter()
-				.filter_map(|(callee, _rel)| {
+				.filter_map(|(callee, rel)| {
 					let function_name = callee.name.clone();
 					let file_path = callee.file_path.display().to_string().replace(&request.project_dir, "").trim_start_matches('/').to_string();
 					let key = (function_name.clone(), file_path.clone());
@@ -1111,6 +1380,9 @@ pub async fn investigate_repo(
 						Some(super::models::CallRelation {
 							function_name,
 							file_path,
+							call_kind: rel.call_kind,
+							confidence: rel.confidence,
+							call_sites: None,
 						})
 					} else {
 						None
@@ -1129,54 +1401,19 @@ pub async fn investigate_repo(
 		}
 	}
 
-	// For each unique file, build code skeleton text (reuse skeleton builder logic)
+	// For each unique file, build (or reuse the cached) code skeleton text
+	let cache = storage.get_skeleton_cache();
 	let mut file_skeletons: Vec<super::models::CodeSkeletonResponse> = Vec::new();
 	for path in files_needed.into_iter() {
 		let rel_path = path.display().to_string().replace(&request.project_dir, "").trim_start_matches('/').to_string();
-		// Reuse existing batch skeletonizer by calling internal logic inline
-		let code = match std::fs::read_to_string(&path) {
-			Ok(c) => c,
-			Err(_) => continue,
-		};
-		let (mut parser, language_id) = match crate::codegraph::treesitter::parsers::get_ast_parser_by_filename(&path) {
-			Ok(v) => v,
-			Err(_) => continue,
+		let Ok((skeleton, cached)) = crate::services::cached_skeleton(&cache, &path) else {
+			continue;
 		};
-		let symbols = parser.parse(&code, &path);
-		let symbols_struct: Vec<crate::codegraph::treesitter::ast_instance_structs::SymbolInformation> =
-			symbols.iter().map(|s| s.read().symbol_info_struct()).collect();
-		use uuid::Uuid;
-		use std::collections::HashMap;
-		let guid_to_children: HashMap<Uuid, Vec<Uuid>> = symbols
-			.iter()
-			.map(|s| (s.read().guid().clone(), s.read().childs_guid().clone()))
-			.collect();
-		let ast_markup = crate::codegraph::treesitter::file_ast_markup::FileASTMarkup {
-			symbols_sorted_by_path_len: symbols_struct.clone(),
-		};
-		let guid_to_info: HashMap<Uuid, &crate::codegraph::treesitter::ast_instance_structs::SymbolInformation> =
-			ast_markup
-				.symbols_sorted_by_path_len
-				.iter()
-				.map(|s| (s.guid.clone(), s))
-				.collect();
-		let formatter = crate::codegraph::treesitter::skeletonizer::make_formatter(&language_id);
-		use crate::codegraph::treesitter::structs::SymbolType;
-		let class_symbols: Vec<_> = ast_markup
-			.symbols_sorted_by_path_len
-			.iter()
-			.filter(|x| x.symbol_type == SymbolType::StructDeclaration || x.symbol_type == SymbolType::FunctionDeclaration)
-			.collect();
-		let mut lines: Vec<String> = Vec::new();
-		for symbol in class_symbols {
-			let skeleton_line = formatter.make_skeleton(&symbol, &code.to_string(), &guid_to_children, &guid_to_info);
-			lines.push(skeleton_line);
-		}
-		let skeleton_text = if lines.is_empty() { String::new() } else { lines.join("\n\n") };
 		file_skeletons.push(super::models::CodeSkeletonResponse {
 			filepath: rel_path,
-			language: language_id.to_string(),
-			skeleton_text,
+			language: skeleton.language,
+			skeleton_text: skeleton.skeleton_text,
+			cached,
 		});
 	}
 
@@ -1194,5 +1431,1851 @@ pub async fn investigate_repo(
 		directory_tree,
 	};
 
-	Ok(Json(ApiResponse { success: true, data: resp }))
-} 
\ No newline at end of file
+	Ok(Json(ApiResponse { success: true, data: resp, snapshot }))
+} 
+/// Resolve the requested project (or the first registered one) and load its graph.
+fn load_project_graph(
+    storage: &StorageManager,
+    project_id: Option<String>,
+) -> Result<(String, crate::codegraph::types::PetCodeGraph), StatusCode> {
+    let project_id = match project_id {
+        Some(pid) => pid,
+        None => storage
+            .get_persistence()
+            .list_projects()
+            .map_err(|_| StatusCode::NOT_FOUND)?
+            .first()
+            .cloned()
+            .ok_or(StatusCode::NOT_FOUND)?,
+    };
+
+    match storage.get_persistence().load_graph(&project_id) {
+        Ok(Some(graph)) => Ok((project_id, graph)),
+        Ok(None) => Err(StatusCode::NOT_FOUND),
+        Err(_) => Err(StatusCode::INTERNAL_SERVER_ERROR),
+    }
+}
+
+/// Provenance recorded when the project's graph was built; absent for graphs built before it was tracked.
+fn project_snapshot(storage: &StorageManager, project_id: &str) -> Option<crate::storage::GraphSnapshot> {
+    storage.get_persistence().load_snapshot(project_id).unwrap_or_else(|e| {
+        tracing::warn!("Failed to load snapshot info for {}: {}", project_id, e);
+        None
+    })
+}
+
+/// Id of the registered project whose directory contains `filepath`; the innermost one when projects are nested.
+fn file_project_id(storage: &StorageManager, filepath: &str) -> Option<String> {
+    storage
+        .get_persistence()
+        .list_parsed_projects()
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|record| std::path::Path::new(filepath).starts_with(&record.project_dir))
+        .max_by_key(|record| record.project_dir.len())
+        .map(|record| record.project_id)
+}
+
+/// Annotations of the registered project whose directory contains `filepath`.
+fn file_project_annotations(storage: &StorageManager, filepath: &str) -> Option<crate::storage::NodeAnnotations> {
+    let project_id = file_project_id(storage, filepath)?;
+    storage
+        .get_persistence()
+        .load_annotations(&project_id)
+        .map_err(|e| tracing::warn!("Failed to load annotations for {}: {}", project_id, e))
+        .ok()
+}
+
+/// Metrics annotated on functions of the project containing `filepath`, keyed by function id.
+fn file_annotations(
+    storage: &StorageManager,
+    graph: &crate::codegraph::types::PetCodeGraph,
+    filepath: &str,
+) -> std::collections::HashMap<String, crate::storage::MetricMap> {
+    file_project_annotations(storage, filepath)
+        .map(|annotations| {
+            annotations
+                .resolve(graph)
+                .0
+                .into_iter()
+                .map(|(id, metrics)| (id.to_string(), metrics))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+pub async fn query_refactor_suggestions(
+    TenantStorage(storage): TenantStorage,
+    Json(request): Json<QueryRefactorSuggestionsRequest>,
+) -> Result<Json<ApiResponse<QueryRefactorSuggestionsResponse>>, StatusCode> {
+    let (project_id, graph) = load_project_graph(&storage, request.project_id)?;
+    let snapshot = project_snapshot(&storage, &project_id);
+
+    let defaults = crate::services::RefactorConfig::default();
+    let config = crate::services::RefactorConfig {
+        min_calls: request.min_calls.unwrap_or(defaults.min_calls),
+        min_ratio: request.min_ratio.unwrap_or(defaults.min_ratio),
+    };
+
+    let mut suggestions = crate::services::RefactorAdvisor::new(config).suggest(&graph);
+    let total_suggestions = suggestions.len();
+    if let Some(limit) = request.limit {
+        suggestions.truncate(limit);
+    }
+
+    Ok(Json(ApiResponse {
+        success: true,
+        data: QueryRefactorSuggestionsResponse {
+            project_id,
+            total_suggestions,
+            suggestions,
+        },
+        snapshot,
+    }))
+}
+
+pub async fn query_unresolved_calls(
+    TenantStorage(storage): TenantStorage,
+    Json(request): Json<QueryUnresolvedCallsRequest>,
+) -> Result<Json<ApiResponse<QueryUnresolvedCallsResponse>>, StatusCode> {
+    let (project_id, graph) = load_project_graph(&storage, request.project_id)?;
+    let snapshot = project_snapshot(&storage, &project_id);
+
+    let report = crate::services::triage_unresolved_calls(&graph);
+    let total_groups = report.groups.len();
+    let mut groups = report.groups;
+    if let Some(min_count) = request.min_count {
+        groups.retain(|group| group.count >= min_count);
+    }
+    if let Some(limit) = request.limit {
+        groups.truncate(limit);
+    }
+
+    Ok(Json(ApiResponse {
+        success: true,
+        data: QueryUnresolvedCallsResponse {
+            project_id,
+            total_calls: report.total_calls,
+            unresolved_calls: report.unresolved_calls,
+            total_groups,
+            groups,
+        },
+        snapshot,
+    }))
+}
+
+pub async fn project_risk(
+    TenantStorage(storage): TenantStorage,
+    Path(project_id): Path<String>,
+    Query(query): Query<ProjectRiskQuery>,
+) -> Result<Json<ApiResponse<ProjectRiskResponse>>, StatusCode> {
+    let (project_id, graph) = load_project_graph(&storage, Some(project_id))?;
+    let snapshot = project_snapshot(&storage, &project_id);
+
+    // Churn needs the project checkout; without it scores fall back to graph metrics only.
+    // It walks the whole git log, so keep it off the async runtime
+    let churn = match storage.get_persistence().get_project_record(&project_id) {
+        Ok(Some(record)) => tokio::task::spawn_blocking(move || {
+            crate::services::risk::file_churn(std::path::Path::new(&record.project_dir))
+        })
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?,
+        _ => std::collections::HashMap::new(),
+    };
+
+    let mut functions = crate::services::RiskScorer::default().score(&graph, &churn);
+    let total_functions = functions.len();
+
+    if let Some(files) = &query.files {
+        let touched: Vec<&str> = files.split(',').map(|f| f.trim()).filter(|f| !f.is_empty()).collect();
+        // Match whole path components so that `a.rs` does not select `data.rs`
+        functions.retain(|f| touched.iter().any(|t| std::path::Path::new(&f.file_path).ends_with(t)));
+    }
+    if let Some(min_score) = query.min_score {
+        functions.retain(|f| f.score >= min_score);
+    }
+    if query.untested_only.unwrap_or(false) {
+        functions.retain(|f| !f.tested);
+    }
+
+    let gate_threshold = query.gate_threshold.unwrap_or(70.0);
+    let high_risk_untested = functions
+        .iter()
+        .filter(|f| !f.tested && f.score >= gate_threshold)
+        .count();
+
+    if let Some(limit) = query.limit {
+        functions.truncate(limit);
+    }
+
+    Ok(Json(ApiResponse {
+        success: true,
+        data: ProjectRiskResponse {
+            project_id,
+            total_functions,
+            high_risk_untested,
+            gate_passed: high_risk_untested == 0,
+            functions,
+        },
+        snapshot,
+    }))
+}
+
+/// Export a project's call graph for Gephi, Graphviz, Mermaid or protobuf consumers
+pub async fn export_project_graph(
+    TenantStorage(storage): TenantStorage,
+    Path(project_id): Path<String>,
+    Query(query): Query<ExportGraphQuery>,
+) -> Result<axum::response::Response, ApiError> {
+    use axum::response::IntoResponse;
+    use crate::services::graph_export::{export_graph_bytes, ExportFormat};
+
+    let format = match query.format.as_deref() {
+        Some(format) => format
+            .parse::<ExportFormat>()
+            .map_err(|e| ApiError::new(StatusCode::BAD_REQUEST, e))?,
+        None => ExportFormat::Gexf,
+    };
+    let (project_id, graph) = load_project_graph(&storage, Some(project_id))?;
+    let graph = match query.slice.as_deref() {
+        Some(expr) => slice_project_graph(&storage, &project_id, &graph, expr)?,
+        None => graph,
+    };
+
+    // Complexity metrics re-parse the source files, so keep it off the async runtime
+    let content = tokio::task::spawn_blocking(move || export_graph_bytes(&graph, format))
+        .await
+        .map_err(|_| ApiError::from(StatusCode::INTERNAL_SERVER_ERROR))?
+        .map_err(|e| ApiError::new(StatusCode::INTERNAL_SERVER_ERROR, e))?;
+
+    Ok(([(axum::http::header::CONTENT_TYPE, format.content_type())], content).into_response())
+}
+
+/// The subgraph induced by the functions of a project matching an attribute predicate.
+fn slice_project_graph(
+    storage: &StorageManager,
+    project_id: &str,
+    graph: &crate::codegraph::types::PetCodeGraph,
+    expr: &str,
+) -> Result<crate::codegraph::types::PetCodeGraph, ApiError> {
+    use crate::services::{slice_graph, SliceAttributes, SliceExpr};
+
+    let expr: SliceExpr = expr
+        .parse()
+        .map_err(|e| ApiError::new(StatusCode::BAD_REQUEST, format!("Invalid slice expression: {}", e)))?;
+    let persistence = storage.get_persistence();
+    // Owners come from the project checkout's CODEOWNERS; without it `owner` matches nothing
+    let project_dir = match persistence.get_project_record(project_id) {
+        Ok(Some(record)) => std::path::PathBuf::from(record.project_dir),
+        _ => std::path::PathBuf::new(),
+    };
+    let metrics = persistence
+        .load_annotations(project_id)
+        .map(|annotations| annotations.resolve(graph).0)
+        .unwrap_or_default();
+    let attributes = SliceAttributes::new(graph, &project_dir, metrics);
+    Ok(slice_graph(graph, &expr, &attributes))
+}
+
+/// Select the functions matching an attribute predicate and return the induced
+/// subgraph, as JSON nodes and edges or in one of the export formats.
+pub async fn slice_graph(
+    TenantStorage(storage): TenantStorage,
+    Json(request): Json<SliceGraphRequest>,
+) -> Result<axum::response::Response, ApiError> {
+    use axum::response::IntoResponse;
+    use crate::services::graph_export::{export_graph_bytes, ExportFormat};
+
+    let format = request
+        .format
+        .as_deref()
+        .map(|format| format.parse::<ExportFormat>().map_err(|e| ApiError::new(StatusCode::BAD_REQUEST, e)))
+        .transpose()?;
+    let (project_id, graph) = load_project_graph(&storage, request.project_id)?;
+    let snapshot = project_snapshot(&storage, &project_id);
+    let total_functions = graph.get_all_functions().len();
+    let slice = slice_project_graph(&storage, &project_id, &graph, &request.expr)?;
+
+    if let Some(format) = format {
+        let content = tokio::task::spawn_blocking(move || export_graph_bytes(&slice, format))
+            .await
+            .map_err(|_| ApiError::from(StatusCode::INTERNAL_SERVER_ERROR))?
+            .map_err(|e| ApiError::new(StatusCode::INTERNAL_SERVER_ERROR, e))?;
+        return Ok(([(axum::http::header::CONTENT_TYPE, format.content_type())], content).into_response());
+    }
+
+    Ok(Json(ApiResponse {
+        success: true,
+        data: SliceGraphResponse {
+            project_id,
+            expr: request.expr,
+            total_functions,
+            functions: slice.get_all_functions().into_iter().cloned().collect(),
+            calls: slice.get_all_call_relations().into_iter().cloned().collect(),
+        },
+        snapshot,
+    })
+    .into_response())
+}
+
+/// Bytes buffered before a chunk of the NDJSON export is sent
+const NDJSON_CHUNK_BYTES: usize = 64 * 1024;
+
+/// Stream a project's functions and call edges as JSON Lines.
+///
+/// The output is byte-for-byte stable for a given graph, so the ETag (a hash
+/// of the body) lets clients resume an interrupted download with `Range` and
+/// `If-Range`. The body is generated twice, once to size and hash it and once
+/// to stream it, and is never held in memory as a whole.
+pub async fn export_project_ndjson(
+    TenantStorage(storage): TenantStorage,
+    Path(project_id): Path<String>,
+    headers: HeaderMap,
+) -> Result<axum::response::Response, ApiError> {
+    use axum::http::header::{ACCEPT_RANGES, CONTENT_LENGTH, CONTENT_RANGE, CONTENT_TYPE, ETAG};
+    use axum::response::IntoResponse;
+    use crate::services::graph_export::ndjson_lines;
+    use super::range::{requested_range, RangeRequest};
+
+    let (_, graph) = load_project_graph(&storage, Some(project_id))?;
+    let graph = Arc::new(graph);
+
+    let sizing = graph.clone();
+    let (total, etag) = tokio::task::spawn_blocking(move || {
+        let mut context = md5::Context::new();
+        let mut total = 0u64;
+        for line in ndjson_lines(&sizing) {
+            context.consume(line.as_bytes());
+            total += line.len() as u64;
+        }
+        (total, format!("\"{:x}\"", context.compute()))
+    })
+    .await
+    .map_err(|_| ApiError::from(StatusCode::INTERNAL_SERVER_ERROR))?;
+
+    let range = match requested_range(&headers, &etag, total) {
+        RangeRequest::Full => None,
+        RangeRequest::Partial(range) => Some(range),
+        RangeRequest::Unsatisfiable => {
+            return Ok((StatusCode::RANGE_NOT_SATISFIABLE, [(CONTENT_RANGE, format!("bytes */{}", total))]).into_response());
+        }
+    };
+    let (start, end) = range.map(|r| (r.start, r.end + 1)).unwrap_or((0, total));
+
+    let (sender, receiver) = tokio::sync::mpsc::channel::<Bytes>(16);
+    tokio::task::spawn_blocking(move || {
+        let mut offset = 0u64;
+        let mut chunk = Vec::with_capacity(NDJSON_CHUNK_BYTES);
+        for line in ndjson_lines(&graph) {
+            let line_start = offset;
+            offset += line.len() as u64;
+            if offset <= start {
+                continue;
+            }
+            if line_start >= end {
+                break;
+            }
+            let from = start.saturating_sub(line_start) as usize;
+            let to = (end.min(offset) - line_start) as usize;
+            chunk.extend_from_slice(&line.as_bytes()[from..to]);
+            // A failed send means the client went away
+            if chunk.len() >= NDJSON_CHUNK_BYTES && sender.blocking_send(Bytes::from(std::mem::take(&mut chunk))).is_err() {
+                return;
+            }
+        }
+        if !chunk.is_empty() {
+            let _ = sender.blocking_send(Bytes::from(chunk));
+        }
+    });
+    let body = axum::body::Body::from_stream(futures_util::stream::unfold(receiver, |mut receiver| async move {
+        receiver.recv().await.map(|chunk| (Ok::<_, std::convert::Infallible>(chunk), receiver))
+    }));
+
+    let mut response = axum::response::Response::new(body);
+    let response_headers = response.headers_mut();
+    response_headers.insert(CONTENT_TYPE, axum::http::HeaderValue::from_static("application/x-ndjson"));
+    response_headers.insert(ACCEPT_RANGES, axum::http::HeaderValue::from_static("bytes"));
+    response_headers.insert(CONTENT_LENGTH, (end - start).into());
+    if let Ok(value) = etag.parse() {
+        response_headers.insert(ETAG, value);
+    }
+    if let Some(range) = range {
+        if let Ok(value) = range.content_range(total).parse() {
+            response_headers.insert(CONTENT_RANGE, value);
+        }
+        *response.status_mut() = StatusCode::PARTIAL_CONTENT;
+    }
+    Ok(response)
+}
+
+pub async fn explain_stacktrace(
+    TenantStorage(storage): TenantStorage,
+    Json(request): Json<ExplainStacktraceRequest>,
+) -> Result<Json<ApiResponse<ExplainStacktraceResponse>>, StatusCode> {
+    if request.stacktrace.trim().is_empty() {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    let (project_id, graph) = load_project_graph(&storage, request.project_id)?;
+    let snapshot = project_snapshot(&storage, &project_id);
+
+    let snippet_lines = if request.include_snippets.unwrap_or(true) {
+        Some(request.max_snippet_lines.unwrap_or(40))
+    } else {
+        None
+    };
+    let explained = crate::services::explain_stack_trace(&graph, &request.stacktrace, snippet_lines);
+
+    Ok(Json(ApiResponse {
+        success: true,
+        data: ExplainStacktraceResponse {
+            project_id,
+            frames: explained.frames,
+            resolved_frames: explained.resolved_frames,
+            inferred_frames: explained.inferred_frames,
+            unresolved_frames: explained.unresolved_frames,
+        },
+        snapshot,
+    }))
+}
+
+/// Concrete implementations of an interface method, from the class hierarchy of the project's Java, TypeScript and C++ files.
+pub async fn query_implementations(
+    TenantStorage(storage): TenantStorage,
+    Json(request): Json<QueryImplementationsRequest>,
+) -> Result<Json<ApiResponse<QueryImplementationsResponse>>, ApiError> {
+    if request.interface.trim().is_empty() || request.method.trim().is_empty() {
+        return Err(ApiError::new(StatusCode::BAD_REQUEST, "interface and method must not be empty"));
+    }
+    let (project_id, graph) = load_project_graph(&storage, request.project_id)?;
+    // The hierarchy is read from the project checkout; the stored graph only holds functions
+    let project_dir = match storage.get_persistence().get_project_record(&project_id) {
+        Ok(Some(record)) => std::path::PathBuf::from(record.project_dir),
+        _ => return Err(ApiError::new(StatusCode::NOT_FOUND, format!("No project directory recorded for {}", project_id))),
+    };
+    let entity_graph = crate::codegraph::parser::CodeParser::new().build_entity_graph(&project_dir, &graph);
+
+    let declared_in: Vec<String> = entity_graph
+        .find_classes_by_name(&request.interface)
+        .iter()
+        .map(|class| class.file_path.display().to_string())
+        .collect();
+    if declared_in.is_empty() {
+        return Err(ApiError::new(StatusCode::NOT_FOUND, format!("No interface or class named {}", request.interface)));
+    }
+    let implementations = entity_graph
+        .find_implementations(&request.interface, &request.method)
+        .into_iter()
+        .map(|(class, function)| ImplementationInfo {
+            class_name: class.name.clone(),
+            class_type: class.class_type.clone(),
+            file_path: function.file_path.display().to_string(),
+            function_id: function.id.to_string(),
+            function_name: function.name.clone(),
+            line_start: function.line_start,
+            line_end: function.line_end,
+        })
+        .collect();
+
+    let snapshot = project_snapshot(&storage, &project_id);
+    Ok(Json(ApiResponse {
+        success: true,
+        data: QueryImplementationsResponse {
+            project_id,
+            interface: request.interface,
+            method: request.method,
+            declared_in,
+            implementations,
+        },
+        snapshot,
+    }))
+}
+
+/// Functions that web, scheduling and messaging frameworks call: decorated or annotated handlers and
+/// routes registered with axum, actix-web or Express, as detected when the project was built.
+pub async fn query_entry_points(
+    TenantStorage(storage): TenantStorage,
+    Json(request): Json<QueryEntryPointsRequest>,
+) -> Result<Json<ApiResponse<QueryEntryPointsResponse>>, ApiError> {
+    let (project_id, graph) = load_project_graph(&storage, request.project_id)?;
+    let wanted = |filter: &Option<Vec<String>>, value: &str| filter.as_ref().is_none_or(|values| values.iter().any(|v| v == value));
+
+    let entry_points = project_entry_points(&storage, &project_id, &graph)
+        .into_iter()
+        .filter(|entry| wanted(&request.kinds, &entry.kind) && wanted(&request.frameworks, &entry.framework))
+        .filter_map(|entry| {
+            let function = graph.get_function_by_id(&entry.function_id)?;
+            Some(EntryPointInfo {
+                function_id: function.id.to_string(),
+                function_name: function.name.clone(),
+                file_path: function.file_path.display().to_string(),
+                line_start: function.line_start,
+                line_end: function.line_end,
+                kind: entry.kind,
+                decorator: entry.decorator,
+                arguments: entry.arguments,
+                framework: entry.framework,
+                method: entry.method,
+                route: entry.route,
+                registration_file: entry.file_path.display().to_string(),
+                registration_line: entry.line,
+            })
+        })
+        .collect();
+
+    let snapshot = project_snapshot(&storage, &project_id);
+    Ok(Json(ApiResponse { success: true, data: QueryEntryPointsResponse { project_id, entry_points }, snapshot }))
+}
+
+/// Entry points stored with the project's graph; projects built before they were stored
+/// fall back to detecting them from the current files.
+fn project_entry_points(
+    storage: &StorageManager,
+    project_id: &str,
+    graph: &crate::codegraph::types::PetCodeGraph,
+) -> Vec<crate::codegraph::entrypoints::EntryPoint> {
+    match storage.get_persistence().load_entry_points(project_id) {
+        Ok(Some(entry_points)) => entry_points,
+        Ok(None) => crate::codegraph::entrypoints::detect_entry_points(graph),
+        Err(e) => {
+            tracing::warn!("Failed to load entry points for {}: {}", project_id, e);
+            crate::codegraph::entrypoints::detect_entry_points(graph)
+        }
+    }
+}
+
+pub async fn explain_function(
+    TenantStorage(storage): TenantStorage,
+    Json(request): Json<ExplainFunctionRequest>,
+) -> Result<Json<ApiResponse<ExplainFunctionResponse>>, ApiError> {
+    let function_id = match request.function_id.as_deref() {
+        Some(id) => Some(uuid::Uuid::parse_str(id).map_err(|_| {
+            ApiError::new(StatusCode::BAD_REQUEST, format!("`{}` is not a function id", id))
+        })?),
+        None => None,
+    };
+    if function_id.is_none() && request.function_name.is_none() && request.filepath.is_none() {
+        return Err(ApiError::new(
+            StatusCode::BAD_REQUEST,
+            "Pass function_id, function_name or filepath",
+        ));
+    }
+
+    let (project_id, graph) = load_project_graph(&storage, request.project_id)?;
+    let snapshot = project_snapshot(&storage, &project_id);
+
+    let function = crate::services::explain::find_function(
+        &graph,
+        function_id.as_ref(),
+        request.function_name.as_deref(),
+        request.filepath.as_deref(),
+    )
+    .map_err(|candidates| match candidates.len() {
+        0 => ApiError::new(StatusCode::NOT_FOUND, "No matching function in the graph"),
+        n => {
+            // Ambiguous: list where the candidates are so the caller can narrow with filepath or function_id
+            let locations: Vec<String> = candidates
+                .iter()
+                .take(10)
+                .map(|f| format!("{} ({}:{}, id {})", f.name, f.file_path.display(), f.line_start, f.id))
+                .collect();
+            ApiError::new(
+                StatusCode::CONFLICT,
+                format!("{} functions match; narrow with filepath or function_id: {}", n, locations.join(", ")),
+            )
+        }
+    })?;
+
+    let mut options = crate::services::ExplainOptions::default();
+    if let Some(max_snippet_lines) = request.max_snippet_lines {
+        options.max_snippet_lines = max_snippet_lines;
+    }
+    if let Some(max_relations) = request.max_relations {
+        options.max_relations = max_relations;
+    }
+    let explanation = crate::services::explain_function(&graph, function, &options);
+
+    Ok(Json(ApiResponse {
+        success: true,
+        data: ExplainFunctionResponse {
+            project_id,
+            function: explanation,
+        },
+        snapshot,
+    }))
+}
+
+pub async fn find_log_origin(
+    TenantStorage(storage): TenantStorage,
+    Json(request): Json<FindLogOriginRequest>,
+) -> Result<Json<ApiResponse<FindLogOriginResponse>>, StatusCode> {
+    if request.log_line.trim().is_empty() {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    let (project_id, graph) = load_project_graph(&storage, request.project_id)?;
+    let snapshot = project_snapshot(&storage, &project_id);
+
+    let index = crate::services::LogIndex::build(&graph);
+    let matches = index.find_origin(
+        &graph,
+        &request.log_line,
+        request.caller_depth.unwrap_or(2),
+        request.limit.unwrap_or(10),
+    );
+
+    Ok(Json(ApiResponse {
+        success: true,
+        data: FindLogOriginResponse {
+            project_id,
+            indexed_statements: index.statements.len(),
+            matches,
+        },
+        snapshot,
+    }))
+}
+
+pub async fn preview_rename(
+    TenantStorage(storage): TenantStorage,
+    Json(request): Json<PreviewRenameRequest>,
+) -> Result<Json<ApiResponse<PreviewRenameResponse>>, ApiError> {
+    let name = request.name.trim().to_string();
+    if !crate::services::rename::is_identifier(&name) {
+        return Err(ApiError::new(StatusCode::BAD_REQUEST, format!("`{}` is not an identifier", name)));
+    }
+    let new_name = request.new_name.map(|n| n.trim().to_string());
+    if let Some(new_name) = new_name.as_deref().filter(|n| !crate::services::rename::is_identifier(n)) {
+        return Err(ApiError::new(StatusCode::BAD_REQUEST, format!("`{}` is not an identifier", new_name)));
+    }
+
+    let (project_id, graph) = load_project_graph(&storage, request.project_id)?;
+    let snapshot = project_snapshot(&storage, &project_id);
+    let project_dir = match storage.get_persistence().get_project_record(&project_id) {
+        Ok(Some(record)) => Some(std::path::PathBuf::from(record.project_dir)),
+        _ => None,
+    };
+    let options = crate::services::RenameOptions {
+        name,
+        new_name,
+        kind: request.kind,
+        include_strings: request.include_strings.unwrap_or(false),
+        include_comments: request.include_comments.unwrap_or(false),
+    };
+    let preview = crate::services::preview_rename(&graph, project_dir.as_deref(), &options);
+
+    Ok(Json(ApiResponse {
+        success: true,
+        data: PreviewRenameResponse { project_id, preview },
+        snapshot,
+    }))
+}
+
+pub async fn query_feature_flags(
+    TenantStorage(storage): TenantStorage,
+    Json(request): Json<QueryFeatureFlagsRequest>,
+) -> Result<Json<ApiResponse<QueryFeatureFlagsResponse>>, StatusCode> {
+    let (project_id, graph) = load_project_graph(&storage, request.project_id)?;
+    let snapshot = project_snapshot(&storage, &project_id);
+
+    let mut patterns: Vec<String> = match request.patterns {
+        Some(patterns) => patterns,
+        None => crate::services::feature_flags::DEFAULT_FLAG_PATTERNS.iter().map(|p| p.to_string()).collect(),
+    };
+    patterns.extend(request.extra_patterns.unwrap_or_default());
+
+    let index = crate::services::FeatureFlagIndex::build(&graph, &patterns).map_err(|e| {
+        tracing::warn!("{}", e);
+        StatusCode::BAD_REQUEST
+    })?;
+
+    let flags = index
+        .flags
+        .iter()
+        .map(|(flag, usages)| {
+            let mut functions: Vec<String> = usages.iter().filter_map(|u| u.function_name.clone()).collect();
+            functions.sort();
+            functions.dedup();
+            FeatureFlagSummary { flag: flag.clone(), usage_count: usages.len(), functions }
+        })
+        .collect();
+
+    let (usages, guarded_functions) = match &request.flag {
+        Some(flag) => (
+            index.flags.get(flag).cloned().unwrap_or_default(),
+            index.guarded_functions(&graph, flag, request.max_depth.unwrap_or(3)),
+        ),
+        None => (Vec::new(), Vec::new()),
+    };
+
+    Ok(Json(ApiResponse {
+        success: true,
+        data: QueryFeatureFlagsResponse {
+            project_id,
+            flags,
+            usages,
+            guarded_functions,
+        },
+        snapshot,
+    }))
+}
+
+pub async fn query_concurrency(
+    TenantStorage(storage): TenantStorage,
+    Json(request): Json<QueryConcurrencyRequest>,
+) -> Result<Json<ApiResponse<QueryConcurrencyResponse>>, StatusCode> {
+    let (project_id, graph) = load_project_graph(&storage, request.project_id)?;
+    let snapshot = project_snapshot(&storage, &project_id);
+
+    let report = crate::services::ConcurrencyReport::build(&graph, &crate::services::PatternCatalog::new());
+    let mut functions = report.functions;
+    if let Some(kinds) = &request.kinds {
+        functions.retain(|f| kinds.iter().any(|k| f.kinds.contains(k)));
+    }
+
+    Ok(Json(ApiResponse {
+        success: true,
+        data: QueryConcurrencyResponse {
+            project_id,
+            total_functions: functions.len(),
+            functions,
+        },
+        snapshot,
+    }))
+}
+
+pub async fn query_lock_order_inversions(
+    TenantStorage(storage): TenantStorage,
+    Json(request): Json<QueryLockOrderRequest>,
+) -> Result<Json<ApiResponse<QueryLockOrderResponse>>, StatusCode> {
+    let (project_id, graph) = load_project_graph(&storage, request.project_id)?;
+    let snapshot = project_snapshot(&storage, &project_id);
+
+    let report = crate::services::ConcurrencyReport::build(&graph, &crate::services::PatternCatalog::new());
+    let inversions = report.find_lock_order_inversions(&graph, request.max_depth.unwrap_or(3));
+
+    Ok(Json(ApiResponse {
+        success: true,
+        data: QueryLockOrderResponse { project_id, inversions },
+        snapshot,
+    }))
+}
+
+pub async fn query_latency_budget(
+    TenantStorage(storage): TenantStorage,
+    Json(request): Json<QueryLatencyBudgetRequest>,
+) -> Result<Json<ApiResponse<QueryLatencyBudgetResponse>>, StatusCode> {
+    use crate::services::latency::DEFAULT_BUDGET_FILE;
+    use crate::services::{BudgetConfig, LatencyBudgetAnalyzer};
+
+    let (project_id, graph) = load_project_graph(&storage, request.project_id)?;
+    let snapshot = project_snapshot(&storage, &project_id);
+
+    // An explicit path must exist; the default file in the project directory is optional
+    let mut config = match &request.annotations_path {
+        Some(path) => BudgetConfig::load(std::path::Path::new(path)).map_err(|e| {
+            tracing::warn!("{}", e);
+            StatusCode::BAD_REQUEST
+        })?,
+        None => match storage.get_persistence().get_project_record(&project_id) {
+            Ok(Some(record)) => {
+                let default_path = std::path::Path::new(&record.project_dir).join(DEFAULT_BUDGET_FILE);
+                if default_path.exists() {
+                    BudgetConfig::load(&default_path).map_err(|e| {
+                        tracing::warn!("{}", e);
+                        StatusCode::BAD_REQUEST
+                    })?
+                } else {
+                    BudgetConfig::default()
+                }
+            }
+            _ => BudgetConfig::default(),
+        },
+    };
+    config.annotations.extend(request.annotations.unwrap_or_default());
+
+    let mut analyzer = LatencyBudgetAnalyzer::new(&graph, &config);
+    let mut budgets = analyzer.rollup(request.entry_points.as_deref());
+    if let Some(limit) = request.limit {
+        budgets.truncate(limit);
+    }
+
+    Ok(Json(ApiResponse {
+        success: true,
+        data: QueryLatencyBudgetResponse {
+            project_id,
+            annotated_functions: analyzer.annotated_functions(),
+            budgets,
+        },
+        snapshot,
+    }))
+}
+
+/// Rank owners (CODEOWNERS teams, else each file's most frequent git author)
+/// by how much of their code the change reaches through callers
+pub async fn suggest_reviewers(
+    TenantStorage(storage): TenantStorage,
+    Json(request): Json<SuggestReviewersRequest>,
+) -> Result<Json<ApiResponse<SuggestReviewersResponse>>, StatusCode> {
+    use crate::services::OwnershipData;
+
+    if request.changed_files.iter().all(|f| f.trim().is_empty()) {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+    let (project_id, graph) = load_project_graph(&storage, request.project_id)?;
+    let snapshot = project_snapshot(&storage, &project_id);
+
+    // Ownership and churn need the project checkout; without it every function is unowned
+    let project_dir = match storage.get_persistence().get_project_record(&project_id) {
+        Ok(Some(record)) => std::path::PathBuf::from(record.project_dir),
+        _ => std::path::PathBuf::new(),
+    };
+    let ownership = if project_dir.as_os_str().is_empty() {
+        OwnershipData::default()
+    } else {
+        OwnershipData::from_project(&project_dir)
+    };
+
+    let mut report = crate::services::suggest_reviewers(
+        &graph,
+        &project_dir,
+        &ownership,
+        &request.changed_files,
+        request.max_depth.unwrap_or(3),
+    );
+    if let Some(limit) = request.limit {
+        report.reviewers.truncate(limit);
+    }
+
+    Ok(Json(ApiResponse {
+        success: true,
+        data: SuggestReviewersResponse {
+            project_id,
+            changed_functions: report.changed_functions,
+            impacted_functions: report.impacted_functions,
+            unowned_functions: report.unowned_functions,
+            reviewers: report.reviewers,
+        },
+        snapshot,
+    }))
+}
+
+/// Attach external metrics (profiler samples, coverage, ...) to functions by qualified name
+pub async fn annotate_project_nodes(
+    TenantStorage(storage): TenantStorage,
+    Path(project_id): Path<String>,
+    Json(request): Json<AnnotateNodesRequest>,
+) -> Result<Json<ApiResponse<ProjectAnnotationsResponse>>, StatusCode> {
+    let (project_id, graph) = load_project_graph(&storage, Some(project_id))?;
+    let persistence = storage.get_persistence();
+
+    let mut annotations = if request.replace.unwrap_or(false) {
+        crate::storage::NodeAnnotations::default()
+    } else {
+        persistence.load_annotations(&project_id).map_err(|e| {
+            tracing::error!("Failed to load annotations for {}: {}", project_id, e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?
+    };
+    annotations.merge(request.annotations);
+    persistence.save_annotations(&project_id, &annotations).map_err(|e| {
+        tracing::error!("Failed to save annotations for {}: {}", project_id, e);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+    // Cached call graph responses embed the previous metrics
+    storage.mark_updated();
+
+    Ok(Json(annotations_response(&storage, project_id, &graph, annotations)))
+}
+
+pub async fn project_annotations(
+    TenantStorage(storage): TenantStorage,
+    Path(project_id): Path<String>,
+) -> Result<Json<ApiResponse<ProjectAnnotationsResponse>>, StatusCode> {
+    let (project_id, graph) = load_project_graph(&storage, Some(project_id))?;
+    let annotations = storage.get_persistence().load_annotations(&project_id).map_err(|e| {
+        tracing::error!("Failed to load annotations for {}: {}", project_id, e);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+    Ok(Json(annotations_response(&storage, project_id, &graph, annotations)))
+}
+
+/// Import a pprof / perf script / collapsed-stack profile: resolve frames to functions,
+/// store per-function sample counts as annotations and record the hottest call paths
+pub async fn import_project_profile(
+    TenantStorage(storage): TenantStorage,
+    Path(project_id): Path<String>,
+    Query(query): Query<ImportProfileQuery>,
+    body: Bytes,
+) -> Result<Json<ApiResponse<ImportProfileResponse>>, ApiError> {
+    use crate::services::ProfileFormat;
+
+    if body.is_empty() {
+        return Err(ApiError::new(StatusCode::BAD_REQUEST, "profile body is empty"));
+    }
+    let format = query
+        .format
+        .as_deref()
+        .map(ProfileFormat::parse)
+        .transpose()
+        .map_err(|e| ApiError::new(StatusCode::BAD_REQUEST, e))?;
+    let (project_id, graph) = load_project_graph(&storage, Some(project_id))?;
+    let snapshot = project_snapshot(&storage, &project_id);
+    let persistence = storage.get_persistence();
+    let project_dir = match persistence.get_project_record(&project_id) {
+        Ok(Some(record)) => std::path::PathBuf::from(record.project_dir),
+        _ => std::path::PathBuf::new(),
+    };
+
+    let profile = crate::services::import_profile(
+        &body,
+        format,
+        query.sample_index,
+        &graph,
+        &project_dir,
+        query.hot_paths.unwrap_or(10),
+    )
+    .map_err(|e| ApiError::new(StatusCode::BAD_REQUEST, e))?;
+
+    let mut annotations = persistence.load_annotations(&project_id).map_err(|e| {
+        tracing::error!("Failed to load annotations for {}: {}", project_id, e);
+        ApiError::from(StatusCode::INTERNAL_SERVER_ERROR)
+    })?;
+    annotations.merge(profile.to_annotations(&graph, &project_dir));
+    annotations.hot_paths = profile.hot_paths.clone();
+    persistence.save_annotations(&project_id, &annotations).map_err(|e| {
+        tracing::error!("Failed to save annotations for {}: {}", project_id, e);
+        ApiError::from(StatusCode::INTERNAL_SERVER_ERROR)
+    })?;
+    storage.mark_updated();
+
+    Ok(Json(ApiResponse {
+        success: true,
+        data: ImportProfileResponse {
+            project_id,
+            format: profile.format,
+            total_samples: profile.total_samples,
+            resolved_samples: profile.resolved_samples,
+            matched_functions: profile.functions.len(),
+            unresolved_frames: profile.unresolved_frames,
+            hot_paths: profile.hot_paths,
+        },
+        snapshot,
+    }))
+}
+
+/// Import an lcov / Cobertura report: mark functions covered or uncovered (stored as
+/// `covered` / `coverage_pct` annotations) and report uncovered functions with many callers
+pub async fn import_project_coverage(
+    TenantStorage(storage): TenantStorage,
+    Path(project_id): Path<String>,
+    Query(query): Query<ImportCoverageQuery>,
+    body: Bytes,
+) -> Result<Json<ApiResponse<ImportCoverageResponse>>, ApiError> {
+    use crate::services::{CoverageData, CoverageFormat};
+
+    let content = std::str::from_utf8(&body)
+        .map_err(|e| ApiError::new(StatusCode::BAD_REQUEST, format!("coverage report is not UTF-8: {}", e)))?;
+    let format = query
+        .format
+        .as_deref()
+        .map(CoverageFormat::parse)
+        .transpose()
+        .map_err(|e| ApiError::new(StatusCode::BAD_REQUEST, e))?;
+    let data = CoverageData::parse(content, format).map_err(|e| ApiError::new(StatusCode::BAD_REQUEST, e))?;
+
+    let (project_id, graph) = load_project_graph(&storage, Some(project_id))?;
+    let snapshot = project_snapshot(&storage, &project_id);
+    let persistence = storage.get_persistence();
+    let project_dir = match persistence.get_project_record(&project_id) {
+        Ok(Some(record)) => std::path::PathBuf::from(record.project_dir),
+        _ => std::path::PathBuf::new(),
+    };
+
+    let mut report = crate::services::analyze_coverage(&graph, &data, query.min_fan_in.unwrap_or(3));
+    let mut annotations = persistence.load_annotations(&project_id).map_err(|e| {
+        tracing::error!("Failed to load annotations for {}: {}", project_id, e);
+        ApiError::from(StatusCode::INTERNAL_SERVER_ERROR)
+    })?;
+    annotations.merge(report.to_annotations(&project_dir, &graph));
+    persistence.save_annotations(&project_id, &annotations).map_err(|e| {
+        tracing::error!("Failed to save annotations for {}: {}", project_id, e);
+        ApiError::from(StatusCode::INTERNAL_SERVER_ERROR)
+    })?;
+    storage.mark_updated();
+
+    if let Some(limit) = query.limit {
+        report.test_gaps.truncate(limit);
+    }
+
+    Ok(Json(ApiResponse {
+        success: true,
+        data: ImportCoverageResponse {
+            project_id,
+            format: data.format,
+            covered: report.covered,
+            uncovered: report.uncovered,
+            unknown: report.unknown,
+            test_gaps: report.test_gaps,
+        },
+        snapshot,
+    }))
+}
+
+/// Replay exported OpenTelemetry / Zipkin traces on the static call graph: map each span to a
+/// function and tell whether its parent span's function reaches it statically, across services
+/// or not at all
+pub async fn replay_project_traces(
+    TenantStorage(storage): TenantStorage,
+    Path(project_id): Path<String>,
+    Query(query): Query<ReplayTracesQuery>,
+    body: Bytes,
+) -> Result<Json<ApiResponse<ReplayTracesResponse>>, ApiError> {
+    let content = std::str::from_utf8(&body)
+        .map_err(|e| ApiError::new(StatusCode::BAD_REQUEST, format!("trace export is not UTF-8: {}", e)))?;
+    let spans = crate::services::parse_trace_export(content).map_err(|e| ApiError::new(StatusCode::BAD_REQUEST, e))?;
+
+    let (project_id, graph) = load_project_graph(&storage, Some(project_id))?;
+    let snapshot = project_snapshot(&storage, &project_id);
+    let traces = crate::services::replay_traces(&graph, &spans, query.trace_id.as_deref());
+    if traces.is_empty() {
+        return Err(ApiError::new(StatusCode::NOT_FOUND, "no spans for the requested trace"));
+    }
+
+    if query.annotate.unwrap_or(true) {
+        let persistence = storage.get_persistence();
+        let project_dir = match persistence.get_project_record(&project_id) {
+            Ok(Some(record)) => std::path::PathBuf::from(record.project_dir),
+            _ => std::path::PathBuf::new(),
+        };
+        let mut annotations = persistence.load_annotations(&project_id).map_err(|e| {
+            tracing::error!("Failed to load annotations for {}: {}", project_id, e);
+            ApiError::from(StatusCode::INTERNAL_SERVER_ERROR)
+        })?;
+        annotations.merge(crate::services::trace_annotations(&traces, &graph, &project_dir));
+        persistence.save_annotations(&project_id, &annotations).map_err(|e| {
+            tracing::error!("Failed to save annotations for {}: {}", project_id, e);
+            ApiError::from(StatusCode::INTERNAL_SERVER_ERROR)
+        })?;
+        storage.mark_updated();
+    }
+
+    Ok(Json(ApiResponse {
+        success: true,
+        data: ReplayTracesResponse {
+            project_id,
+            total_spans: traces.iter().map(|t| t.spans.len()).sum(),
+            mapped_spans: traces.iter().map(|t| t.mapped_spans).sum(),
+            traces,
+        },
+        snapshot,
+    }))
+}
+
+/// Merges (caller, callee) pairs observed by runtime instrumentation into the saved graph as `observed` edges.
+pub async fn merge_project_observed_calls(
+    TenantStorage(storage): TenantStorage,
+    Path(project_id): Path<String>,
+    body: Bytes,
+) -> Result<Json<ApiResponse<ObservedCallsResponse>>, ApiError> {
+    let content = std::str::from_utf8(&body)
+        .map_err(|e| ApiError::new(StatusCode::BAD_REQUEST, format!("observed calls are not UTF-8: {}", e)))?;
+    let calls = crate::services::parse_observed_calls(content).map_err(|e| ApiError::new(StatusCode::BAD_REQUEST, e))?;
+
+    let (project_id, mut graph) = load_project_graph(&storage, Some(project_id))?;
+    let report = crate::services::merge_observed_calls(&mut graph, &calls);
+    if report.edges.is_empty() {
+        return Err(ApiError::new(StatusCode::UNPROCESSABLE_ENTITY, "no observed call could be mapped to the graph"));
+    }
+    storage.get_persistence().save_graph(&project_id, &graph).map_err(|e| {
+        tracing::error!("Failed to save graph for {}: {}", project_id, e);
+        ApiError::from(StatusCode::INTERNAL_SERVER_ERROR)
+    })?;
+    storage.mark_updated();
+
+    let snapshot = project_snapshot(&storage, &project_id);
+    let stats = graph.get_stats();
+    Ok(Json(ApiResponse {
+        success: true,
+        data: ObservedCallsResponse {
+            project_id,
+            resolved_calls: stats.resolved_calls,
+            unresolved_calls: stats.unresolved_calls,
+            report,
+        },
+        snapshot,
+    }))
+}
+
+pub async fn refresh_file(
+    TenantStorage(storage): TenantStorage,
+    Json(request): Json<RefreshFileRequest>,
+) -> Result<Json<ApiResponse<RefreshFileResponse>>, ApiError> {
+    let outcome = crate::services::refresh_stored_file(&storage, &request.project_id, &request.file_path).map_err(|e| {
+        use crate::services::BuildError;
+        tracing::error!("{}", e);
+        match e {
+            BuildError::NotFound(msg) => ApiError::new(StatusCode::BAD_REQUEST, msg),
+            BuildError::QuotaExceeded(msg) => ApiError::new(StatusCode::FORBIDDEN, msg),
+            BuildError::Failed(_) => ApiError::from(StatusCode::INTERNAL_SERVER_ERROR),
+        }
+    })?;
+
+    // Keep the in-memory graph answering query_call_graph current when it holds this project
+    storage.replace_project_graph(&outcome.project_dir, outcome.graph.clone(), storage.graph_snapshot());
+
+    let snapshot = project_snapshot(&storage, &outcome.project_id);
+    let stats = outcome.graph.get_stats();
+    Ok(Json(ApiResponse {
+        success: true,
+        data: RefreshFileResponse {
+            project_id: outcome.project_id,
+            file_path: outcome.file_path.display().to_string(),
+            delta: outcome.delta,
+            resolved_calls: stats.resolved_calls,
+            unresolved_calls: stats.unresolved_calls,
+        },
+        snapshot,
+    }))
+}
+
+fn annotations_response(
+    storage: &StorageManager,
+    project_id: String,
+    graph: &crate::codegraph::types::PetCodeGraph,
+    annotations: crate::storage::NodeAnnotations,
+) -> ApiResponse<ProjectAnnotationsResponse> {
+    let (resolved, unmatched) = annotations.resolve(graph);
+    let snapshot = project_snapshot(storage, &project_id);
+    ApiResponse {
+        success: true,
+        data: ProjectAnnotationsResponse {
+            project_id,
+            annotations: annotations.nodes,
+            matched_functions: resolved.len(),
+            unmatched,
+            hot_paths: annotations.hot_paths,
+        },
+        snapshot,
+    }
+}
+
+pub async fn project_metadata(
+    TenantStorage(storage): TenantStorage,
+    State(scheduler): State<Arc<ReindexScheduler>>,
+    Path(project_id): Path<String>,
+) -> Result<Json<ApiResponse<ProjectMetadataResponse>>, StatusCode> {
+    let record = storage
+        .get_persistence()
+        .get_project_record(&project_id)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    let reindex = scheduler.status(storage.tenant_id(), &project_id);
+
+    let project_dir = match (&record, &reindex) {
+        (Some(record), _) => record.project_dir.clone(),
+        (None, Some(status)) => status.project_dir.clone(),
+        (None, None) => return Err(StatusCode::NOT_FOUND),
+    };
+    let snapshot = project_snapshot(&storage, &project_id);
+
+    Ok(Json(ApiResponse {
+        success: true,
+        data: ProjectMetadataResponse {
+            project_id,
+            project_dir,
+            parsed_at: record.map(|r| r.parsed_at),
+            reindex,
+        },
+        snapshot,
+    }))
+}
+
+/// Files of a stored graph with their function counts and the parse statistics of their last build
+pub async fn project_files(
+    TenantStorage(storage): TenantStorage,
+    Path(project_id): Path<String>,
+    Query(query): Query<ProjectFilesQuery>,
+) -> Result<Json<ApiResponse<ProjectFilesResponse>>, StatusCode> {
+    let (project_id, graph) = load_project_graph(&storage, Some(project_id))?;
+    let snapshot = project_snapshot(&storage, &project_id);
+    let parse_stats = storage.get_persistence().load_parse_stats(&project_id).unwrap_or_default();
+
+    let mut functions: std::collections::HashMap<String, (String, usize)> = std::collections::HashMap::new();
+    for function in graph.get_all_functions() {
+        if function.is_unresolved() {
+            continue;
+        }
+        let entry = functions
+            .entry(function.file_path.to_string_lossy().to_string())
+            .or_insert_with(|| (function.language.clone(), 0));
+        entry.1 += 1;
+    }
+
+    let mut paths: Vec<&String> = parse_stats.keys().chain(functions.keys()).collect();
+    paths.sort();
+    paths.dedup();
+    let mut files: Vec<ProjectFile> = paths
+        .into_iter()
+        .map(|path| {
+            let stats = parse_stats.get(path);
+            let (language, count) = functions.get(path).cloned().unwrap_or_default();
+            ProjectFile {
+                path: path.clone(),
+                language: stats.map_or(language, |s| s.language.clone()),
+                functions: count,
+                duration_ms: stats.map(|s| s.duration_ms),
+                node_count: stats.and_then(|s| s.node_count),
+                error_nodes: stats.and_then(|s| s.error_nodes),
+                error_ratio: stats.and_then(|s| s.error_ratio()),
+                suspect: stats.is_some_and(|s| s.is_suspect()),
+                parse_error: stats.and_then(|s| s.parse_error.clone()),
+            }
+        })
+        .collect();
+    let total_files = files.len();
+    let suspect_files = files.iter().filter(|f| f.suspect).count();
+    let failed_files = files.iter().filter(|f| f.parse_error.is_some()).count();
+    if query.suspect_only.unwrap_or(false) {
+        files.retain(|f| f.suspect);
+    }
+    if query.failed_only.unwrap_or(false) {
+        files.retain(|f| f.parse_error.is_some());
+    }
+
+    Ok(Json(ApiResponse {
+        success: true,
+        data: ProjectFilesResponse { project_id, total_files, suspect_files, failed_files, files },
+        snapshot,
+    }))
+}
+
+/// Inputs of the project's last build: analyzer and grammar versions, options, ignore rules and file hashes
+pub async fn project_manifest(
+    TenantStorage(storage): TenantStorage,
+    Path(project_id): Path<String>,
+) -> Result<Json<ApiResponse<crate::storage::BuildManifest>>, ApiError> {
+    let manifest = load_manifest(&storage, ManifestSource::Project { project_id: project_id.clone() })?;
+    let snapshot = project_snapshot(&storage, &project_id);
+
+    Ok(Json(ApiResponse { success: true, data: manifest, snapshot }))
+}
+
+/// Differences between two build manifests, explaining why two builds of the same commit disagree
+pub async fn compare_manifests(
+    TenantStorage(storage): TenantStorage,
+    Json(request): Json<CompareManifestsRequest>,
+) -> Result<Json<ApiResponse<crate::storage::ManifestDiff>>, ApiError> {
+    let base = load_manifest(&storage, request.base)?;
+    let head = load_manifest(&storage, request.head)?;
+
+    Ok(Json(ApiResponse { success: true, data: base.diff(&head), snapshot: None }))
+}
+
+fn load_manifest(storage: &StorageManager, source: ManifestSource) -> Result<crate::storage::BuildManifest, ApiError> {
+    match source {
+        ManifestSource::Manifest(manifest) => Ok(*manifest),
+        ManifestSource::Project { project_id } => storage
+            .get_persistence()
+            .load_manifest(&project_id)
+            .map_err(|_| ApiError::from(StatusCode::INTERNAL_SERVER_ERROR))?
+            .ok_or_else(|| {
+                ApiError::new(
+                    StatusCode::NOT_FOUND,
+                    format!("no build manifest for project {}; rebuild it to record one", project_id),
+                )
+            }),
+    }
+}
+
+/// Issue an expiring token that grants read-only access to the current build
+/// of one project, without the caller's tenant token.
+pub async fn create_project_share(
+    TenantStorage(storage): TenantStorage,
+    State(config): State<Arc<ServerConfig>>,
+    Path(project_id): Path<String>,
+    request: Option<Json<CreateShareRequest>>,
+) -> Result<Json<ApiResponse<ShareTokenResponse>>, ApiError> {
+    let Some(secret) = config.share.secret.as_deref() else {
+        return Err(ApiError::new(StatusCode::FORBIDDEN, "sharing is not configured"));
+    };
+    let request = request.map(|Json(request)| request).unwrap_or_default();
+    let ttl = request.expires_in.as_deref().unwrap_or(&config.share.default_ttl);
+    let ttl = super::config::parse_interval(ttl).map_err(|e| ApiError::new(StatusCode::BAD_REQUEST, e))?;
+    let max_ttl = super::config::parse_interval(&config.share.max_ttl)
+        .map_err(|_| ApiError::from(StatusCode::INTERNAL_SERVER_ERROR))?;
+    if ttl > max_ttl {
+        return Err(ApiError::new(
+            StatusCode::UNPROCESSABLE_ENTITY,
+            format!("expires_in must not exceed {}", config.share.max_ttl),
+        ));
+    }
+
+    // Tokens are bound to a build, so a project without a recorded snapshot cannot be shared
+    let snapshot = storage
+        .get_persistence()
+        .load_snapshot(&project_id)
+        .map_err(|_| ApiError::from(StatusCode::INTERNAL_SERVER_ERROR))?
+        .ok_or_else(|| ApiError::new(StatusCode::CONFLICT, "project has no snapshot to share; rebuild it first"))?;
+    let expires_at = chrono::Utc::now()
+        + chrono::Duration::from_std(ttl).map_err(|_| ApiError::new(StatusCode::BAD_REQUEST, "expires_in is too long"))?;
+    let claims = super::share::ShareClaims {
+        tenant: storage.tenant_id().map(str::to_string),
+        project_id: project_id.clone(),
+        built_at: snapshot.built_at,
+        expires_at,
+    };
+    let token = claims.sign(secret);
+
+    let mut view_url = format!("/shared/{}/draw_call_graph", token);
+    if let Some(filepath) = &request.filepath {
+        view_url.push_str(&format!("?filepath={}", encode_query_value(filepath)));
+        if let Some(function_name) = &request.function_name {
+            view_url.push_str(&format!("&function_name={}", encode_query_value(function_name)));
+        }
+    }
+
+    Ok(Json(ApiResponse {
+        success: true,
+        data: ShareTokenResponse { project_id, token, expires_at, view_url },
+        snapshot: Some(snapshot),
+    }))
+}
+
+/// Percent-encode everything but unreserved characters so a value fits in a query string
+fn encode_query_value(value: &str) -> String {
+    value
+        .bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' | b'/' => (b as char).to_string(),
+            _ => format!("%{:02X}", b),
+        })
+        .collect()
+}
+
+/// Summary of the shared project, including the files that can be queried with the token
+pub async fn shared_project(
+    shared: super::share::SharedSnapshot,
+) -> Result<Json<ApiResponse<SharedProjectResponse>>, ApiError> {
+    let project_dir = shared
+        .storage
+        .get_persistence()
+        .get_project_record(&shared.claims.project_id)
+        .map_err(|_| ApiError::from(StatusCode::INTERNAL_SERVER_ERROR))?
+        .map(|record| record.project_dir)
+        .unwrap_or_default();
+    let files: std::collections::BTreeSet<String> = shared
+        .graph
+        .get_all_functions()
+        .iter()
+        .map(|function| function.file_path.display().to_string())
+        .collect();
+
+    Ok(Json(ApiResponse {
+        success: true,
+        data: SharedProjectResponse {
+            project_id: shared.claims.project_id,
+            project_dir,
+            expires_at: shared.claims.expires_at,
+            total_functions: shared.graph.get_stats().total_functions,
+            total_files: files.len(),
+            files: files.into_iter().collect(),
+        },
+        snapshot: Some(shared.snapshot),
+    }))
+}
+
+/// `/query_call_graph` against the shared snapshot
+pub async fn shared_query_call_graph(
+    shared: super::share::SharedSnapshot,
+    State(config): State<Arc<ServerConfig>>,
+    Json(request): Json<QueryCallGraphRequest>,
+) -> Result<Json<ApiResponse<QueryCallGraphResponse>>, ApiError> {
+    let response = call_graph_response(&shared.storage, &shared.graph, &config, request)?;
+
+    Ok(Json(ApiResponse {
+        success: true,
+        data: response,
+        snapshot: Some(shared.snapshot),
+    }))
+}
+
+/// `/query_hierarchical_graph` against the shared snapshot; `project_id` may only name the shared project
+pub async fn shared_query_hierarchical_graph(
+    shared: super::share::SharedSnapshot,
+    State(config): State<Arc<ServerConfig>>,
+    Json(request): Json<QueryHierarchicalGraphRequest>,
+) -> Result<Json<ApiResponse<QueryHierarchicalGraphResponse>>, ApiError> {
+    if request.project_id.as_ref().is_some_and(|id| *id != shared.claims.project_id) {
+        return Err(ApiError::new(StatusCode::FORBIDDEN, "share token does not grant access to this project"));
+    }
+    let response = hierarchical_graph_response(&shared.storage, &shared.graph, shared.claims.project_id, &config, &request)?;
+
+    Ok(Json(ApiResponse {
+        success: true,
+        data: response,
+        snapshot: Some(shared.snapshot),
+    }))
+}
+
+/// `/draw_call_graph` against the shared snapshot; the page's forms stay under the token's path
+pub async fn shared_draw_call_graph(
+    Path(token): Path<String>,
+    shared: super::share::SharedSnapshot,
+    State(config): State<Arc<ServerConfig>>,
+    Query(query): Query<super::models::DrawCallGraphQuery>,
+) -> Html<String> {
+    let draw_url = format!("/shared/{}/draw_call_graph", token);
+    let result = if query.filepath.is_empty() {
+        Err(ApiError::new(StatusCode::UNPROCESSABLE_ENTITY, "Enter a file path from the shared project to draw its call graph."))
+    } else {
+        call_graph_response(
+            &shared.storage,
+            &shared.graph,
+            &config,
+            QueryCallGraphRequest {
+                filepath: query.filepath.clone(),
+                function_name: query.function_name.clone(),
+                max_depth: query.max_depth,
+                call_kind: None,
+                call_kinds: None,
+                include_unresolved: None,
+                include_heuristic: None,
+                group_call_sites: None,
+            },
+        )
+    };
+    Html(call_graph_page(&shared.storage, &query, result, &draw_url))
+}
+
+/// Receive a GitHub/GitLab push event and queue an incremental rebuild of the
+/// configured project for that repository.
+pub async fn git_webhook(
+    State(tenants): State<Arc<TenantRegistry>>,
+    State(config): State<Arc<ServerConfig>>,
+    State(scheduler): State<Arc<ReindexScheduler>>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Result<(StatusCode, Json<ApiResponse<GitWebhookResponse>>), ApiError> {
+    use super::webhook::{is_push_event, match_repository, parse_push_event, verify_signature};
+
+    let Some(secret) = config.webhook.secret.as_deref() else {
+        return Err(ApiError::new(StatusCode::FORBIDDEN, "webhook secret is not configured"));
+    };
+    if !verify_signature(secret, &headers, &body) {
+        return Err(ApiError::new(StatusCode::UNAUTHORIZED, "invalid webhook signature"));
+    }
+
+    let ignored = |reason: &str| {
+        Ok((
+            StatusCode::OK,
+            Json(ApiResponse { success: true, data: GitWebhookResponse::ignored(reason), snapshot: None }),
+        ))
+    };
+    if !is_push_event(&headers) {
+        return ignored("not a push event");
+    }
+
+    let payload: serde_json::Value = serde_json::from_slice(&body)
+        .map_err(|e| ApiError::new(StatusCode::BAD_REQUEST, format!("invalid JSON payload: {}", e)))?;
+    let event = parse_push_event(&payload)
+        .ok_or_else(|| ApiError::new(StatusCode::BAD_REQUEST, "payload is not a push event"))?;
+    if event.is_deletion() {
+        return ignored("ref was deleted");
+    }
+    let Some(repository) = match_repository(&config.webhook, &event) else {
+        tracing::info!("Ignoring push to {} {}: no matching repository", event.repository, event.git_ref);
+        return ignored("no configured repository matches this push");
+    };
+
+    let storage = tenants.storage_for(repository.tenant.as_deref()).ok_or_else(|| {
+        ApiError::new(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            format!("repository {} refers to an unknown tenant", repository.name),
+        )
+    })?;
+    let job = ReindexJob {
+        project_dir: repository.project_dir.clone(),
+        full_rebuild: false,
+        languages: repository.languages.clone(),
+        exclude_paths: repository.exclude_paths.clone(),
+        trigger: format!("push {}", event.git_ref),
+        sync_ref: repository.sync.then(|| event.git_ref.clone()),
+        tenant: repository.tenant.clone(),
+    };
+    let project_id = scheduler.enqueue(job, storage);
+    tracing::info!("Queued rebuild of {} for push to {}", repository.project_dir, event.git_ref);
+
+    Ok((
+        StatusCode::ACCEPTED,
+        Json(ApiResponse {
+            success: true,
+            data: GitWebhookResponse {
+                queued: true,
+                project_id: Some(project_id),
+                project_dir: Some(repository.project_dir.clone()),
+                git_ref: Some(event.git_ref),
+                reason: None,
+            },
+            snapshot: None,
+        }),
+    ))
+}
+
+/// Re-read the configuration file without restarting; requires the admin token.
+pub async fn admin_reload(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> Result<Json<ApiResponse<ReloadReport>>, ApiError> {
+    require_admin(&state, &headers)?;
+
+    let report = reload_config(&state)
+        .await
+        .map_err(|e| ApiError::new(StatusCode::UNPROCESSABLE_ENTITY, e))?;
+    Ok(Json(ApiResponse { success: true, data: report, snapshot: None }))
+}
+
+/// Languages the server can analyze, with their file extensions, extraction features and grammar versions
+pub async fn capabilities() -> Json<ApiResponse<CapabilitiesResponse>> {
+    Json(ApiResponse {
+        success: true,
+        data: CapabilitiesResponse {
+            tree_sitter_abi_version: tree_sitter::LANGUAGE_VERSION,
+            languages: crate::codegraph::capabilities::capabilities(),
+        },
+        snapshot: None,
+    })
+}
+
+/// Analyzers with their enabled state; requires the admin token.
+pub async fn admin_analyzers(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> Result<Json<ApiResponse<AdminAnalyzersResponse>>, ApiError> {
+    require_admin(&state, &headers)?;
+    let languages = crate::codegraph::capabilities::capabilities();
+    Ok(Json(ApiResponse { success: true, data: AdminAnalyzersResponse { languages, reloaded: vec![] }, snapshot: None }))
+}
+
+/// Disable or re-enable language analyzers and reload external ones without restarting;
+/// requires the admin token.
+pub async fn update_admin_analyzers(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(request): Json<AdminAnalyzersRequest>,
+) -> Result<Json<ApiResponse<AdminAnalyzersResponse>>, ApiError> {
+    require_admin(&state, &headers)?;
+
+    let mut reloaded = vec![];
+    if request.reload {
+        let manifests = tokio::task::spawn_blocking(crate::codegraph::AnalyzerRegistry::reload_dynamic)
+            .await
+            .map_err(|e| ApiError::new(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+            .map_err(|e| ApiError::new(StatusCode::UNPROCESSABLE_ENTITY, e))?;
+        reloaded = manifests.into_iter().map(|manifest| manifest.name).collect();
+    }
+
+    // Validated against the languages after the reload, which may add or rename external analyzers
+    let known: Vec<String> = crate::codegraph::capabilities::capabilities().into_iter().map(|l| l.language).collect();
+    for language in request.disable.iter().chain(&request.enable) {
+        if !known.contains(language) {
+            return Err(ApiError::new(
+                StatusCode::UNPROCESSABLE_ENTITY,
+                format!("Unknown language '{}'; expected one of {}", language, known.join(", ")),
+            ));
+        }
+    }
+    if let Some(language) = request.disable.iter().find(|language| request.enable.contains(language)) {
+        return Err(ApiError::new(StatusCode::UNPROCESSABLE_ENTITY, format!("'{}' is both disabled and enabled", language)));
+    }
+    for language in &request.disable {
+        crate::codegraph::AnalyzerRegistry::set_enabled(language, false);
+    }
+    for language in &request.enable {
+        crate::codegraph::AnalyzerRegistry::set_enabled(language, true);
+    }
+
+    let languages = crate::codegraph::capabilities::capabilities();
+    Ok(Json(ApiResponse { success: true, data: AdminAnalyzersResponse { languages, reloaded }, snapshot: None }))
+}
+
+/// Rewrite the stored graphs of every tenant and drop data left behind by deleted
+/// functions and files; requires the admin token.
+pub async fn admin_compact(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> Result<Json<ApiResponse<crate::storage::CompactionReport>>, ApiError> {
+    require_admin(&state, &headers)?;
+
+    // Tenant directories live under the default storage directory
+    let storage = state
+        .tenants
+        .load()
+        .storage_for(None)
+        .ok_or_else(|| ApiError::new(StatusCode::INTERNAL_SERVER_ERROR, "default storage is not available"))?;
+    let base_dir = storage.get_persistence().base_dir().to_path_buf();
+    let scheduler = state.scheduler.clone();
+    let report = tokio::task::spawn_blocking(move || {
+        crate::storage::compact_storage_locked(&base_dir, |tenant, project_id| {
+            scheduler.blocking_lock_project(tenant, project_id)
+        })
+    })
+        .await
+        .map_err(|e| ApiError::new(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+        .map_err(|e| ApiError::new(StatusCode::INTERNAL_SERVER_ERROR, format!("Compaction failed: {}", e)))?;
+    tracing::info!("Storage compaction reclaimed {} bytes", report.reclaimed_bytes);
+    Ok(Json(ApiResponse { success: true, data: report, snapshot: None }))
+}
+
+fn require_admin(state: &AppState, headers: &HeaderMap) -> Result<(), ApiError> {
+    let config = state.config.load();
+    let Some(admin_token) = config.admin_token.as_deref() else {
+        return Err(ApiError::new(StatusCode::FORBIDDEN, "admin_token is not configured"));
+    };
+    if super::tenant::bearer_token(headers).as_deref() != Some(admin_token) {
+        return Err(ApiError::new(StatusCode::UNAUTHORIZED, "invalid admin token"));
+    }
+    Ok(())
+}
+
+/// Register a repository for federated queries, reusing its stored graph when present.
+pub async fn register_federated_repository(
+    TenantStorage(storage): TenantStorage,
+    Json(request): Json<RegisterRepositoryRequest>,
+) -> Result<Json<ApiResponse<FederatedRepositoriesResponse>>, ApiError> {
+    if request.name.trim().is_empty() {
+        return Err(ApiError::new(StatusCode::BAD_REQUEST, "repository name must not be empty"));
+    }
+
+    let project_id = crate::services::graph_builder::project_id_for(&request.project_dir);
+    let stored = if request.rebuild.unwrap_or(false) {
+        None
+    } else {
+        storage.get_persistence().load_graph(&project_id).unwrap_or_else(|e| {
+            tracing::warn!("Failed to load stored graph for {}: {}", project_id, e);
+            None
+        })
+    };
+    let graph = match stored {
+        Some(graph) => graph,
+        None => {
+            let mut options = crate::codegraph::AnalyzeOptions::default();
+            if let Some(languages) = &request.languages {
+                options = options
+                    .with_languages(languages)
+                    .map_err(|e| ApiError::new(StatusCode::BAD_REQUEST, e))?;
+            }
+            options.exclude_paths = request.exclude_paths.clone().unwrap_or_default();
+            crate::services::build_and_store(&storage, &request.project_dir, options)
+                .map_err(|e| {
+                    use crate::services::BuildError;
+                    tracing::error!("{}", e);
+                    match e {
+                        BuildError::NotFound(msg) => ApiError::new(StatusCode::BAD_REQUEST, msg),
+                        BuildError::QuotaExceeded(msg) => ApiError::new(StatusCode::FORBIDDEN, msg),
+                        BuildError::Failed(_) => ApiError::from(StatusCode::INTERNAL_SERVER_ERROR),
+                    }
+                })?
+                .graph
+        }
+    };
+
+    let federation = storage.get_federation();
+    let repository = crate::codegraph::RepositoryManager::with_call_graph(request.project_dir.clone().into(), graph);
+    if federation.register(&request.name, repository) {
+        tracing::info!("Replaced federated repository {}", request.name);
+    }
+
+    Ok(Json(ApiResponse {
+        success: true,
+        data: FederatedRepositoriesResponse { repositories: federation.list() },
+        snapshot: None,
+    }))
+}
+
+pub async fn list_federated_repositories(
+    TenantStorage(storage): TenantStorage,
+) -> Json<ApiResponse<FederatedRepositoriesResponse>> {
+    Json(ApiResponse {
+        success: true,
+        data: FederatedRepositoriesResponse { repositories: storage.get_federation().list() },
+        snapshot: None,
+    })
+}
+
+pub async fn unregister_federated_repository(
+    TenantStorage(storage): TenantStorage,
+    Path(name): Path<String>,
+) -> Result<Json<ApiResponse<FederatedRepositoriesResponse>>, ApiError> {
+    let federation = storage.get_federation();
+    if !federation.unregister(&name) {
+        return Err(ApiError::new(StatusCode::NOT_FOUND, format!("repository '{}' is not registered", name)));
+    }
+    Ok(Json(ApiResponse {
+        success: true,
+        data: FederatedRepositoriesResponse { repositories: federation.list() },
+        snapshot: None,
+    }))
+}
+
+/// Run one query against every registered repository and merge the hits.
+pub async fn federated_search(
+    TenantStorage(storage): TenantStorage,
+    Json(request): Json<FederatedSearchRequest>,
+) -> Result<Json<ApiResponse<FederatedSearchResponse>>, ApiError> {
+    if request.function_name.trim().is_empty() {
+        return Err(ApiError::new(StatusCode::BAD_REQUEST, "function_name must not be empty"));
+    }
+    let federation = storage.get_federation();
+    let only = request.repositories.unwrap_or_default();
+    if let Some(unknown) = only.iter().find(|name| federation.get(name).is_none()) {
+        return Err(ApiError::new(StatusCode::NOT_FOUND, format!("repository '{}' is not registered", unknown)));
+    }
+
+    let kind = request.kind.unwrap_or_default();
+    let (searched_repositories, mut hits) = federation.search(&request.function_name, kind, &only);
+    let total_hits = hits.len();
+    hits.truncate(request.limit.unwrap_or(500));
+
+    Ok(Json(ApiResponse {
+        success: true,
+        data: FederatedSearchResponse {
+            function_name: request.function_name,
+            kind,
+            searched_repositories,
+            total_hits,
+            truncated: hits.len() < total_hits,
+            hits,
+        },
+        snapshot: None,
+    }))
+}
+
+/// Functions with identical bodies duplicated across registered repositories.
+pub async fn federated_duplicates(
+    TenantStorage(storage): TenantStorage,
+    Json(request): Json<FederatedDuplicatesRequest>,
+) -> Result<Json<ApiResponse<FederatedDuplicatesResponse>>, ApiError> {
+    let federation = storage.get_federation();
+    let only = request.repositories.unwrap_or_default();
+    if let Some(unknown) = only.iter().find(|name| federation.get(name).is_none()) {
+        return Err(ApiError::new(StatusCode::NOT_FOUND, format!("repository '{}' is not registered", unknown)));
+    }
+
+    let (searched_repositories, mut groups) = federation.duplicates(&only, request.min_lines.unwrap_or(5));
+    let total_groups = groups.len();
+    groups.truncate(request.limit.unwrap_or(100));
+
+    Ok(Json(ApiResponse {
+        success: true,
+        data: FederatedDuplicatesResponse {
+            searched_repositories,
+            total_groups,
+            truncated: groups.len() < total_groups,
+            groups,
+        },
+        snapshot: None,
+    }))
+}
+
+/// Package dependencies between registered repositories and the calls crossing them.
+pub async fn federated_dependencies(
+    TenantStorage(storage): TenantStorage,
+) -> Json<ApiResponse<FederatedDependenciesResponse>> {
+    let federation = storage.get_federation();
+    Json(ApiResponse {
+        success: true,
+        data: FederatedDependenciesResponse {
+            links: federation.package_links(),
+            edges: federation.cross_repo_edges().as_ref().clone(),
+        },
+        snapshot: None,
+    })
+}
+
+/// Transitive callers of a function, following cross-repository edges.
+pub async fn federated_impact(
+    TenantStorage(storage): TenantStorage,
+    State(config): State<Arc<ServerConfig>>,
+    Json(request): Json<FederatedImpactRequest>,
+) -> Result<Json<ApiResponse<FederatedImpactResponse>>, ApiError> {
+    let max_depth = request.max_depth.unwrap_or(3);
+    check_depth_limit(&config, max_depth)?;
+
+    let impacted = storage
+        .get_federation()
+        // One more than the limit tells a truncated result from one that fits exactly
+        .impact(&request.repository, &request.function_name, max_depth, config.limits.max_nodes.saturating_add(1))
+        .map_err(|e| ApiError::new(StatusCode::NOT_FOUND, e))?;
+    if impacted.len() > config.limits.max_nodes {
+        return Err(node_limit_error(&config, "lower max_depth"));
+    }
+
+    let mut affected_repositories: Vec<String> = impacted.iter().map(|i| i.repository.clone()).collect();
+    affected_repositories.sort();
+    affected_repositories.dedup();
+
+    Ok(Json(ApiResponse {
+        success: true,
+        data: FederatedImpactResponse {
+            repository: request.repository,
+            function_name: request.function_name,
+            affected_repositories,
+            impacted,
+        },
+        snapshot: None,
+    }))
+}
+
+/// Reject traversal requests deeper than the server allows.
+fn check_depth_limit(config: &ServerConfig, max_depth: usize) -> Result<(), ApiError> {
+    if max_depth > config.limits.max_depth {
+        return Err(ApiError::new(
+            StatusCode::UNPROCESSABLE_ENTITY,
+            format!(
+                "max_depth {} exceeds the server limit of {}; lower max_depth or query a single function by name",
+                max_depth, config.limits.max_depth
+            ),
+        ));
+    }
+    Ok(())
+}
+
+/// Node budget of one traversal; walks stop as soon as it runs out instead of building the whole result first.
+struct NodeBudget {
+    remaining: usize,
+    exhausted: bool,
+}
+
+impl NodeBudget {
+    fn new(config: &ServerConfig) -> Self {
+        Self { remaining: config.limits.max_nodes, exhausted: false }
+    }
+
+    /// Spend one node; `false` once the budget is gone
+    fn take(&mut self) -> bool {
+        if self.remaining == 0 {
+            self.exhausted = true;
+            return false;
+        }
+        self.remaining -= 1;
+        true
+    }
+
+    /// Reject the traversal if it was cut short, suggesting `hint` to the caller.
+    fn check(&self, config: &ServerConfig, hint: &str) -> Result<(), ApiError> {
+        if self.exhausted {
+            return Err(node_limit_error(config, hint));
+        }
+        Ok(())
+    }
+}
+
+fn node_limit_error(config: &ServerConfig, hint: &str) -> ApiError {
+    ApiError::new(
+        StatusCode::UNPROCESSABLE_ENTITY,
+        format!("query returns more than {} nodes, the server limit; {}", config.limits.max_nodes, hint),
+    )
+}
+
+fn count_tree_nodes(node: &super::models::HierarchicalNode) -> usize {
+    1 + node.children.iter().map(count_tree_nodes).sum::<usize>()
+}
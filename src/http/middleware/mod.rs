@@ -1,10 +1,145 @@
-use tower_http::cors::{CorsLayer, Any};
+use axum::{
+    body::Body,
+    http::{header::ETAG, HeaderName, HeaderValue, Method, Request},
+    response::Response,
+    Router,
+};
+use tower_http::cors::{AllowHeaders, AllowMethods, AllowOrigin, CorsLayer};
+use tower_http::set_header::SetResponseHeaderLayer;
+use tower_http::classify::ServerErrorsFailureClass;
+use tower_http::request_id::{MakeRequestUuid, PropagateRequestIdLayer, SetRequestIdLayer};
+use tower_http::trace::TraceLayer;
+use tracing::Span;
 use std::time::Duration;
 
-pub fn create_cors_layer() -> CorsLayer {
-    CorsLayer::new()
-        .allow_origin(Any)
-        .allow_methods(Any)
-        .allow_headers(Any)
-        .max_age(Duration::from_secs(3600))
-} 
\ No newline at end of file
+use super::config::{CorsConfig, SecurityHeadersConfig};
+
+/// Build the CORS layer from configuration; `"*"` entries allow anything.
+pub fn create_cors_layer(config: &CorsConfig) -> Result<CorsLayer, String> {
+    let is_any = |values: &[String]| values.iter().any(|v| v == "*");
+
+    let origins = if is_any(&config.allowed_origins) {
+        AllowOrigin::any()
+    } else {
+        let origins = config
+            .allowed_origins
+            .iter()
+            .map(|o| HeaderValue::from_str(o).map_err(|_| format!("Invalid CORS origin '{}'", o)))
+            .collect::<Result<Vec<_>, _>>()?;
+        AllowOrigin::list(origins)
+    };
+    let methods = if is_any(&config.allowed_methods) {
+        AllowMethods::any()
+    } else {
+        let methods = config
+            .allowed_methods
+            .iter()
+            .map(|m| Method::from_bytes(m.to_uppercase().as_bytes()).map_err(|_| format!("Invalid CORS method '{}'", m)))
+            .collect::<Result<Vec<_>, _>>()?;
+        AllowMethods::list(methods)
+    };
+    let headers = if is_any(&config.allowed_headers) {
+        AllowHeaders::any()
+    } else {
+        let headers = config
+            .allowed_headers
+            .iter()
+            .map(|h| HeaderName::from_bytes(h.as_bytes()).map_err(|_| format!("Invalid CORS header '{}'", h)))
+            .collect::<Result<Vec<_>, _>>()?;
+        AllowHeaders::list(headers)
+    };
+
+    Ok(CorsLayer::new()
+        .allow_origin(origins)
+        .allow_methods(methods)
+        .allow_headers(headers)
+        .expose_headers([ETAG, HeaderName::from_static("x-request-id")])
+        .allow_credentials(config.allow_credentials)
+        .max_age(Duration::from_secs(config.max_age_secs)))
+}
+
+/// Add standard security headers unless a handler already set them.
+pub fn with_security_headers<S>(router: Router<S>, config: &SecurityHeadersConfig) -> Result<Router<S>, String>
+where
+    S: Clone + Send + Sync + 'static,
+{
+    if !config.enabled {
+        return Ok(router);
+    }
+    let csp = HeaderValue::from_str(&config.content_security_policy)
+        .map_err(|_| "Invalid content_security_policy".to_string())?;
+
+    let mut router = router
+        .layer(SetResponseHeaderLayer::if_not_present(
+            HeaderName::from_static("x-content-type-options"),
+            HeaderValue::from_static("nosniff"),
+        ))
+        .layer(SetResponseHeaderLayer::if_not_present(
+            HeaderName::from_static("x-frame-options"),
+            HeaderValue::from_static("DENY"),
+        ))
+        .layer(SetResponseHeaderLayer::if_not_present(
+            HeaderName::from_static("referrer-policy"),
+            HeaderValue::from_static("no-referrer"),
+        ))
+        .layer(SetResponseHeaderLayer::if_not_present(
+            HeaderName::from_static("content-security-policy"),
+            csp,
+        ));
+    if let Some(max_age) = config.hsts_max_age_secs {
+        let hsts = HeaderValue::from_str(&format!("max-age={}; includeSubDomains", max_age))
+            .map_err(|_| "Invalid hsts_max_age_secs".to_string())?;
+        router = router.layer(SetResponseHeaderLayer::if_not_present(
+            HeaderName::from_static("strict-transport-security"),
+            hsts,
+        ));
+    }
+    Ok(router)
+}
+
+/// Adds request ids and per-request tracing to a router.
+///
+/// Every request runs inside a span carrying its method, URI and
+/// `x-request-id` (taken from the request or generated), so logs emitted by
+/// handlers and the analyzers they call can be correlated. The id is echoed
+/// back on the response, and completion is logged with the handler latency.
+pub fn with_request_tracing<S>(router: Router<S>) -> Router<S>
+where
+    S: Clone + Send + Sync + 'static,
+{
+    let trace = TraceLayer::new_for_http()
+        .make_span_with(|request: &Request<Body>| {
+            let request_id = request
+                .headers()
+                .get("x-request-id")
+                .and_then(|v| v.to_str().ok())
+                .unwrap_or("-");
+            tracing::info_span!(
+                "request",
+                method = %request.method(),
+                uri = %request.uri(),
+                request_id = %request_id,
+            )
+        })
+        .on_request(())
+        .on_response(|response: &Response, latency: Duration, _span: &Span| {
+            tracing::info!(
+                status = response.status().as_u16(),
+                latency_ms = latency.as_secs_f64() * 1000.0,
+                "request completed"
+            );
+        })
+        .on_failure(|error: ServerErrorsFailureClass, latency: Duration, _span: &Span| {
+            tracing::error!(
+                error = %error,
+                latency_ms = latency.as_secs_f64() * 1000.0,
+                "request failed"
+            );
+        });
+
+    // Layers added last run first: the id is set before the span is created
+    router
+        .layer(PropagateRequestIdLayer::x_request_id())
+        .layer(trace)
+        .layer(SetRequestIdLayer::x_request_id(MakeRequestUuid))
+}
@@ -2,5 +2,16 @@ pub mod server;
 pub mod handlers;
 pub mod models;
 pub mod middleware;
+pub mod config;
+pub mod state;
+pub mod scheduler;
+pub mod webhook;
+pub mod tenant;
+pub mod cache;
+pub mod range;
+pub mod reload;
+pub mod share;
+pub mod versioning;
 
-pub use server::CodeGraphServer; 
\ No newline at end of file
+pub use server::CodeGraphServer;
+pub use config::ServerConfig; 
\ No newline at end of file
@@ -0,0 +1,103 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::services::coverage::{CoverageFormat, FunctionCoverage};
+use crate::services::profile::{ProfileFormat, UnresolvedFrame};
+use crate::services::observed_calls::ObservedCallsReport;
+use crate::services::traces::TraceReplay;
+use crate::storage::annotations::HotPath;
+use crate::storage::MetricMap;
+
+#[derive(Debug, Deserialize)]
+pub struct AnnotateNodesRequest {
+    /// Qualified function name (e.g. `config::parse`, `com.example.Server.handle`) -> metrics
+    pub annotations: BTreeMap<String, MetricMap>,
+    /// Drop previously uploaded annotations instead of merging into them
+    pub replace: Option<bool>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ProjectAnnotationsResponse {
+    pub project_id: String,
+    /// All stored annotations after the update
+    pub annotations: BTreeMap<String, MetricMap>,
+    /// Functions in the graph that carry at least one metric
+    pub matched_functions: usize,
+    /// Names that did not resolve to any function in the current graph
+    pub unmatched: Vec<String>,
+    /// Heaviest call paths of the last imported profile
+    pub hot_paths: Vec<HotPath>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ImportProfileQuery {
+    /// `pprof`, `perf` or `collapsed`; detected from the body when omitted
+    pub format: Option<String>,
+    /// pprof sample type to use (default: the last one, like `go tool pprof`)
+    pub sample_index: Option<usize>,
+    /// Number of hot call paths to keep (default 10)
+    pub hot_paths: Option<usize>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ImportProfileResponse {
+    pub project_id: String,
+    pub format: ProfileFormat,
+    pub total_samples: u64,
+    /// Samples with at least one frame resolved to a function in the graph
+    pub resolved_samples: u64,
+    /// Functions that received `profile_self` / `profile_total` / `profile_total_pct` metrics
+    pub matched_functions: usize,
+    pub unresolved_frames: Vec<UnresolvedFrame>,
+    pub hot_paths: Vec<HotPath>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ImportCoverageQuery {
+    /// `lcov` or `cobertura`; detected from the body when omitted
+    pub format: Option<String>,
+    /// Minimum number of distinct callers for an uncovered function to count as a test gap (default 3)
+    pub min_fan_in: Option<usize>,
+    /// Maximum number of test gaps to return
+    pub limit: Option<usize>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ImportCoverageResponse {
+    pub project_id: String,
+    pub format: CoverageFormat,
+    pub covered: usize,
+    pub uncovered: usize,
+    /// Functions whose file or lines are missing from the report
+    pub unknown: usize,
+    /// Uncovered functions with at least `min_fan_in` callers, most called first
+    pub test_gaps: Vec<FunctionCoverage>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ReplayTracesQuery {
+    /// Only replay this trace; all traces in the export are replayed when omitted
+    pub trace_id: Option<String>,
+    /// Store `trace_self_ms` / `trace_spans` annotations for mapped functions (default true)
+    pub annotate: Option<bool>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ReplayTracesResponse {
+    pub project_id: String,
+    pub total_spans: usize,
+    /// Spans mapped to a function through `code.*` attributes or their name
+    pub mapped_spans: usize,
+    pub traces: Vec<TraceReplay>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ObservedCallsResponse {
+    pub project_id: String,
+    #[serde(flatten)]
+    pub report: ObservedCallsReport,
+    /// Resolved/unresolved call counts after merging
+    pub resolved_calls: usize,
+    pub unresolved_calls: usize,
+}
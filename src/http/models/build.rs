@@ -5,6 +5,22 @@ pub struct BuildGraphRequest {
     pub project_dir: String,
     pub force_rebuild: Option<bool>,
     pub exclude_patterns: Option<Vec<String>>,
+    /// Only analyze these languages (e.g. `["rust", "python"]`)
+    pub languages: Option<Vec<String>>,
+    /// Paths to skip, relative to `project_dir`; glob patterns are allowed
+    pub exclude_paths: Option<Vec<String>>,
+    /// Fail the build when the resulting graph is internally inconsistent
+    pub strict: Option<bool>,
+    /// Analyze C/C++ macro definitions as functions so macro invocations resolve
+    pub expand_macros: Option<bool>,
+    /// Store compressed function bodies with the graph so snippets come from the analyzed revision
+    pub embed_sources: Option<bool>,
+    /// Guess calls from function names in files that fail to parse (`main` calls every function of its file,
+    /// tests call the functions they are named after); off by default
+    pub heuristic_fallback: Option<bool>,
+    /// Add call edges from a framework node to functions registered by decorators such as `@app.route`,
+    /// `@Scheduled` or `@Get`, so they are no longer reported as having no callers
+    pub framework_entry_points: Option<bool>,
 }
 
 #[derive(Debug, Serialize)]
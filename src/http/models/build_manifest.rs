@@ -0,0 +1,17 @@
+use serde::Deserialize;
+
+use crate::storage::BuildManifest;
+
+/// A manifest stored with one of the server's projects, or one pasted from another build (e.g. a CI artifact)
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+pub enum ManifestSource {
+    Project { project_id: String },
+    Manifest(Box<BuildManifest>),
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CompareManifestsRequest {
+    pub base: ManifestSource,
+    pub head: ManifestSource,
+}
@@ -0,0 +1,32 @@
+use serde::{Deserialize, Serialize};
+
+use crate::codegraph::capabilities::LanguageCapabilities;
+
+#[derive(Debug, Serialize)]
+pub struct CapabilitiesResponse {
+    /// Newest grammar ABI version the bundled tree-sitter runtime can load
+    pub tree_sitter_abi_version: usize,
+    /// Bundled languages followed by registered external analyzers
+    pub languages: Vec<LanguageCapabilities>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct AdminAnalyzersRequest {
+    /// Languages to stop analyzing, named as in `FunctionInfo.language`
+    #[serde(default)]
+    pub disable: Vec<String>,
+    /// Disabled languages to analyze again
+    #[serde(default)]
+    pub enable: Vec<String>,
+    /// Load the external analyzers given with `--analyzer` again from their files
+    #[serde(default)]
+    pub reload: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub struct AdminAnalyzersResponse {
+    pub languages: Vec<LanguageCapabilities>,
+    /// External analyzers loaded again by `reload`
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub reloaded: Vec<String>,
+}
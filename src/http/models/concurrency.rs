@@ -0,0 +1,30 @@
+use serde::{Deserialize, Serialize};
+
+use crate::services::concurrency::{ConcurrencyKind, FunctionConcurrency, LockOrderInversion};
+
+#[derive(Debug, Deserialize)]
+pub struct QueryConcurrencyRequest {
+    pub project_id: Option<String>,
+    /// Only report functions using at least one of these kinds
+    pub kinds: Option<Vec<ConcurrencyKind>>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct QueryConcurrencyResponse {
+    pub project_id: String,
+    pub total_functions: usize,
+    pub functions: Vec<FunctionConcurrency>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct QueryLockOrderRequest {
+    pub project_id: Option<String>,
+    /// How deep to follow callees while a lock is held (default 3)
+    pub max_depth: Option<usize>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct QueryLockOrderResponse {
+    pub project_id: String,
+    pub inversions: Vec<LockOrderInversion>,
+}
@@ -0,0 +1,44 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Deserialize)]
+pub struct QueryEntryPointsRequest {
+    pub project_id: Option<String>,
+    /// Only return these kinds of entry points (`http`, `scheduled`, `task`, `message`)
+    pub kinds: Option<Vec<String>>,
+    /// Only return entry points of these frameworks, e.g. `axum`, `spring` or `express`
+    pub frameworks: Option<Vec<String>>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct EntryPointInfo {
+    /// Id of the function in the call graph
+    pub function_id: String,
+    pub function_name: String,
+    pub file_path: String,
+    pub line_start: usize,
+    pub line_end: usize,
+    /// How the framework invokes the function: `http`, `scheduled`, `task` or `message`
+    pub kind: String,
+    /// Decorator, annotation or call that registers the function, e.g. `app.route`, `GetMapping` or `router.get`
+    pub decorator: String,
+    /// Arguments of the decorator as written, e.g. the route path
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub arguments: Option<String>,
+    /// Framework that registers the function, e.g. `axum`, `actix`, `spring`, `flask`, `fastapi` or `express`
+    pub framework: String,
+    /// HTTP method; absent when the route accepts any method or the entry point is not a route
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub method: Option<String>,
+    /// Route path, when it is written as a string literal
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub route: Option<String>,
+    /// Line of the registration, which may be in another file than the function
+    pub registration_file: String,
+    pub registration_line: usize,
+}
+
+#[derive(Debug, Serialize)]
+pub struct QueryEntryPointsResponse {
+    pub project_id: String,
+    pub entry_points: Vec<EntryPointInfo>,
+}
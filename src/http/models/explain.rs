@@ -0,0 +1,24 @@
+use serde::{Deserialize, Serialize};
+
+use crate::services::FunctionExplanation;
+
+#[derive(Debug, Deserialize)]
+pub struct ExplainFunctionRequest {
+    pub project_id: Option<String>,
+    /// Graph id of the function, as returned by the other queries
+    pub function_id: Option<String>,
+    pub function_name: Option<String>,
+    /// File of the function; a path suffix is enough. Needed when the name is ambiguous
+    pub filepath: Option<String>,
+    /// Maximum snippet length in lines (default 80)
+    pub max_snippet_lines: Option<usize>,
+    /// Maximum callers and callees returned, each (default 20)
+    pub max_relations: Option<usize>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ExplainFunctionResponse {
+    pub project_id: String,
+    #[serde(flatten)]
+    pub function: FunctionExplanation,
+}
@@ -0,0 +1,9 @@
+use serde::Deserialize;
+
+#[derive(Debug, Deserialize)]
+pub struct ExportGraphQuery {
+    /// `json`, `dot`, `mermaid`, `gexf` (default), `gephi-stream`, `nodes-csv` or `edges-csv`
+    pub format: Option<String>,
+    /// Export only the functions matching this attribute predicate (see `POST /slice`)
+    pub slice: Option<String>,
+}
@@ -0,0 +1,33 @@
+use serde::{Deserialize, Serialize};
+
+use crate::services::feature_flags::{FlagUsage, GuardedFunction};
+
+#[derive(Debug, Deserialize)]
+pub struct QueryFeatureFlagsRequest {
+    pub project_id: Option<String>,
+    /// Flag to inspect; when omitted all detected flags are listed
+    pub flag: Option<String>,
+    /// Regex patterns whose first capture group is the flag name (replaces the defaults)
+    pub patterns: Option<Vec<String>>,
+    /// Additional patterns appended to the defaults
+    pub extra_patterns: Option<Vec<String>>,
+    /// How deep to follow callees of the functions reading the flag (default 3)
+    pub max_depth: Option<usize>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct FeatureFlagSummary {
+    pub flag: String,
+    pub usage_count: usize,
+    pub functions: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct QueryFeatureFlagsResponse {
+    pub project_id: String,
+    pub flags: Vec<FeatureFlagSummary>,
+    /// Usages of the requested flag
+    pub usages: Vec<FlagUsage>,
+    /// Functions on code paths guarded by the requested flag
+    pub guarded_functions: Vec<GuardedFunction>,
+}
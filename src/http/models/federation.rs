@@ -0,0 +1,85 @@
+use serde::{Deserialize, Serialize};
+
+use crate::codegraph::{
+    CrossRepoEdge, DuplicateGroup, FederatedHit, FederatedQueryKind, FederatedRepository, ImpactedFunction, PackageLink,
+};
+
+#[derive(Debug, Deserialize)]
+pub struct RegisterRepositoryRequest {
+    /// Name used for provenance in federated results; re-registering replaces the repository
+    pub name: String,
+    pub project_dir: String,
+    /// Re-analyze the project even if a stored graph exists (default false)
+    pub rebuild: Option<bool>,
+    pub languages: Option<Vec<String>>,
+    pub exclude_paths: Option<Vec<String>>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct FederatedRepositoriesResponse {
+    pub repositories: Vec<FederatedRepository>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct FederatedSearchRequest {
+    pub function_name: String,
+    /// definitions (default), callers or callees
+    pub kind: Option<FederatedQueryKind>,
+    /// Restrict the search to these repositories; empty or absent searches all
+    pub repositories: Option<Vec<String>>,
+    /// Maximum number of hits returned (default 500)
+    pub limit: Option<usize>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct FederatedSearchResponse {
+    pub function_name: String,
+    pub kind: FederatedQueryKind,
+    pub searched_repositories: Vec<String>,
+    pub total_hits: usize,
+    pub truncated: bool,
+    pub hits: Vec<FederatedHit>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct FederatedDependenciesResponse {
+    /// Repositories that depend on a package published by another registered repository
+    pub links: Vec<PackageLink>,
+    /// Calls from a dependent repository into the repository publishing the package
+    pub edges: Vec<CrossRepoEdge>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct FederatedImpactRequest {
+    pub repository: String,
+    pub function_name: String,
+    /// Levels of callers to follow, across repository boundaries (default 3)
+    pub max_depth: Option<usize>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct FederatedImpactResponse {
+    pub repository: String,
+    pub function_name: String,
+    pub affected_repositories: Vec<String>,
+    pub impacted: Vec<ImpactedFunction>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct FederatedDuplicatesRequest {
+    /// Restrict the search to these repositories; empty or absent searches all
+    pub repositories: Option<Vec<String>>,
+    /// Ignore functions shorter than this many lines (default 5)
+    pub min_lines: Option<usize>,
+    /// Maximum number of groups returned (default 100)
+    pub limit: Option<usize>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct FederatedDuplicatesResponse {
+    pub searched_repositories: Vec<String>,
+    pub total_groups: usize,
+    pub truncated: bool,
+    /// Identical functions found in at least two repositories, largest first
+    pub groups: Vec<DuplicateGroup>,
+}
@@ -0,0 +1,40 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Deserialize)]
+pub struct ProjectFilesQuery {
+    /// Only list files whose ERROR-node ratio marks their extraction as suspect
+    pub suspect_only: Option<bool>,
+    /// Only list files that failed to parse
+    pub failed_only: Option<bool>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ProjectFile {
+    pub path: String,
+    pub language: String,
+    /// Functions the graph holds for the file
+    pub functions: usize,
+    /// Parse time of the last build that parsed the file, in milliseconds
+    pub duration_ms: Option<f64>,
+    /// Syntax tree size; absent for languages parsed without a tree-sitter grammar
+    /// and for projects built before parse statistics were recorded
+    pub node_count: Option<usize>,
+    /// `ERROR` nodes tree-sitter inserted where the source did not match the grammar
+    pub error_nodes: Option<usize>,
+    pub error_ratio: Option<f64>,
+    /// The error ratio is high enough that functions and calls may be missing, or the file failed to parse
+    pub suspect: bool,
+    /// Why the last build could not parse the file; its functions and calls are missing from the graph
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub parse_error: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ProjectFilesResponse {
+    pub project_id: String,
+    pub total_files: usize,
+    pub suspect_files: usize,
+    /// Files that failed to parse
+    pub failed_files: usize,
+    pub files: Vec<ProjectFile>,
+}
@@ -0,0 +1,34 @@
+use serde::{Deserialize, Serialize};
+
+use crate::codegraph::types::ClassType;
+
+#[derive(Debug, Deserialize)]
+pub struct QueryImplementationsRequest {
+    pub project_id: Option<String>,
+    /// Interface (or base class) declaring the method
+    pub interface: String,
+    pub method: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ImplementationInfo {
+    /// Class that implements the method
+    pub class_name: String,
+    pub class_type: ClassType,
+    pub file_path: String,
+    /// Id of the function in the call graph
+    pub function_id: String,
+    pub function_name: String,
+    pub line_start: usize,
+    pub line_end: usize,
+}
+
+#[derive(Debug, Serialize)]
+pub struct QueryImplementationsResponse {
+    pub project_id: String,
+    pub interface: String,
+    pub method: String,
+    /// Files declaring a type with the interface's name
+    pub declared_in: Vec<String>,
+    pub implementations: Vec<ImplementationInfo>,
+}
@@ -0,0 +1,22 @@
+use serde::{Deserialize, Serialize};
+
+use crate::services::latency::{BudgetAnnotation, RolledUpBudget};
+
+#[derive(Debug, Deserialize)]
+pub struct QueryLatencyBudgetRequest {
+    pub project_id: Option<String>,
+    /// Budget annotation file (TOML); defaults to `codegraph-budgets.toml` in the project directory
+    pub annotations_path: Option<String>,
+    /// Inline annotations, merged with the ones loaded from file
+    pub annotations: Option<Vec<BudgetAnnotation>>,
+    /// Entry point function names; defaults to all functions without callers
+    pub entry_points: Option<Vec<String>>,
+    pub limit: Option<usize>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct QueryLatencyBudgetResponse {
+    pub project_id: String,
+    pub annotated_functions: usize,
+    pub budgets: Vec<RolledUpBudget>,
+}
@@ -0,0 +1,20 @@
+use serde::{Deserialize, Serialize};
+
+use crate::services::log_index::LogMatch;
+
+#[derive(Debug, Deserialize)]
+pub struct FindLogOriginRequest {
+    pub project_id: Option<String>,
+    /// A log line as it appears in production output
+    pub log_line: String,
+    /// How many levels of callers to include for each match (default 2)
+    pub caller_depth: Option<usize>,
+    pub limit: Option<usize>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct FindLogOriginResponse {
+    pub project_id: String,
+    pub indexed_statements: usize,
+    pub matches: Vec<LogMatch>,
+}
@@ -4,6 +4,30 @@ pub mod snippet;
 pub mod skeleton;
 pub mod init;
 pub mod investigate;
+pub mod refactor;
+pub mod risk;
+pub mod stacktrace;
+pub mod explain;
+pub mod log_origin;
+pub mod feature_flags;
+pub mod concurrency;
+pub mod latency;
+pub mod project;
+pub mod webhook;
+pub mod federation;
+pub mod export;
+pub mod slice;
+pub mod reviewers;
+pub mod annotations;
+pub mod rename;
+pub mod share;
+pub mod build_manifest;
+pub mod refresh;
+pub mod implementations;
+pub mod entry_points;
+pub mod files;
+pub mod unresolved;
+pub mod capabilities;
 
 pub use build::*;
 pub use query::*;
@@ -11,13 +35,46 @@ pub use snippet::*;
 pub use skeleton::*;
 pub use init::*;
 pub use investigate::*;
+pub use refactor::*;
+pub use risk::*;
+pub use stacktrace::*;
+pub use explain::*;
+pub use log_origin::*;
+pub use feature_flags::*;
+pub use concurrency::*;
+pub use latency::*;
+pub use project::*;
+pub use webhook::*;
+pub use federation::*;
+pub use export::*;
+pub use slice::*;
+pub use reviewers::*;
+pub use annotations::*;
+pub use rename::*;
+pub use share::*;
+pub use build_manifest::*;
+pub use refresh::*;
+pub use implementations::*;
+pub use entry_points::*;
+pub use files::*;
+pub use unresolved::*;
+pub use capabilities::*;
 
+use axum::{
+    http::StatusCode,
+    response::{IntoResponse, Json, Response},
+};
 use serde::{Deserialize, Serialize};
 
+use crate::storage::GraphSnapshot;
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct ApiResponse<T> {
     pub success: bool,
     pub data: T,
+    /// Commit, build time and analyzer version of the graph that answered a query
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub snapshot: Option<GraphSnapshot>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -25,4 +82,27 @@ pub struct ApiError {
     pub success: bool,
     pub error: String,
     pub code: u16,
+}
+
+impl ApiError {
+    pub fn new(status: StatusCode, error: impl Into<String>) -> Self {
+        Self {
+            success: false,
+            error: error.into(),
+            code: status.as_u16(),
+        }
+    }
+}
+
+impl From<StatusCode> for ApiError {
+    fn from(status: StatusCode) -> Self {
+        Self::new(status, status.canonical_reason().unwrap_or("Error"))
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        let status = StatusCode::from_u16(self.code).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR);
+        (status, Json(self)).into_response()
+    }
 } 
\ No newline at end of file
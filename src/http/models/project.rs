@@ -0,0 +1,14 @@
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+
+use crate::http::scheduler::ReindexStatus;
+
+#[derive(Debug, Serialize)]
+pub struct ProjectMetadataResponse {
+    pub project_id: String,
+    pub project_dir: String,
+    /// When the stored graph was last written; absent if the project was never built
+    pub parsed_at: Option<DateTime<Utc>>,
+    /// Background re-index status, present when the project has a schedule
+    pub reindex: Option<ReindexStatus>,
+}
@@ -1,10 +1,30 @@
 use serde::{Deserialize, Serialize};
 
+use crate::codegraph::types::CallKind;
+use crate::storage::MetricMap;
+
 #[derive(Debug, Deserialize)]
 pub struct QueryCallGraphRequest {
     pub filepath: String,
     pub function_name: Option<String>,
     pub max_depth: Option<usize>,
+    /// Only follow calls of this kind: `sync`, `await` (awaited async calls) or `spawn` (spawned tasks)
+    #[serde(default)]
+    pub call_kind: Option<CallKind>,
+    /// Only follow calls of these kinds; combined with `call_kind` when both are given
+    #[serde(default)]
+    pub call_kinds: Option<Vec<CallKind>>,
+    /// Follow calls to names the analysis could not resolve (default true)
+    #[serde(default)]
+    pub include_unresolved: Option<bool>,
+    /// Follow edges the analysis guessed: name-based fallback edges of files that failed to parse
+    /// and overload picks with a confidence below 1 (default true)
+    #[serde(default)]
+    pub include_heuristic: Option<bool>,
+    /// Report each caller and callee once, with the lines of all its calls in `call_sites`,
+    /// instead of once per call (default false)
+    #[serde(default)]
+    pub group_call_sites: Option<bool>,
 }
 
 #[derive(Debug, Serialize, Clone)]
@@ -13,14 +33,30 @@ pub struct FunctionInfo {
     pub name: String,
     pub line_start: usize,
     pub line_end: usize,
+    /// Notebook cell the lines are relative to, for `.ipynb` files
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cell: Option<usize>,
+    /// Class, struct or trait the function is a method of; absent for free functions
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub parent_class_id: Option<String>,
     pub callers: Vec<CallRelation>,
     pub callees: Vec<CallRelation>,
+    /// External metrics uploaded via `/projects/{id}/annotations`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub metrics: Option<MetricMap>,
 }
 
 #[derive(Debug, Serialize, Clone)]
 pub struct CallRelation {
     pub function_name: String,
     pub file_path: String,
+    pub call_kind: CallKind,
+    /// 从多个同名重载中选出该调用时的置信度，没有重载时省略
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub confidence: Option<f32>,
+    /// Lines of every call between the two functions, only set when `group_call_sites` is requested
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub call_sites: Option<Vec<usize>>,
 }
 
 #[derive(Debug, Serialize)]
@@ -36,6 +72,10 @@ pub struct QueryHierarchicalGraphRequest {
     pub root_function: Option<String>,
     pub max_depth: Option<usize>,
     pub include_file_info: Option<bool>,
+    /// Without `root_function`, list every function grouped by file instead of starting from the framework entry points
+    pub group_by_file: Option<bool>,
+    /// Index of the first file of the page when functions are grouped by file; see `next_offset`
+    pub offset: Option<usize>,
 }
 
 #[derive(Debug, Serialize)]
@@ -57,6 +97,9 @@ pub struct QueryHierarchicalGraphResponse {
     pub tree_structure: HierarchicalNode,
     pub total_functions: usize,
     pub total_relations: usize,
+    /// Set when functions are grouped by file and more files follow: the `offset` of the next page
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub next_offset: Option<usize>,
 } 
 
 #[derive(Debug, Deserialize)]
@@ -74,6 +117,8 @@ pub struct DrawCallGraphQuery {
     pub function_name: Option<String>,
     #[serde(default = "default_max_depth")]
     pub max_depth: Option<usize>,
+    /// Annotation metric used to color nodes, e.g. `cpu_samples`
+    pub color_by: Option<String>,
 }
 
 fn default_max_depth() -> Option<usize> {
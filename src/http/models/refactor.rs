@@ -0,0 +1,18 @@
+use serde::{Deserialize, Serialize};
+
+use crate::services::refactor::RefactorSuggestion;
+
+#[derive(Debug, Deserialize)]
+pub struct QueryRefactorSuggestionsRequest {
+    pub project_id: Option<String>,
+    pub min_calls: Option<usize>,
+    pub min_ratio: Option<f64>,
+    pub limit: Option<usize>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct QueryRefactorSuggestionsResponse {
+    pub project_id: String,
+    pub total_suggestions: usize,
+    pub suggestions: Vec<RefactorSuggestion>,
+}
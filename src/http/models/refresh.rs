@@ -0,0 +1,22 @@
+use serde::{Deserialize, Serialize};
+
+use crate::services::GraphDelta;
+
+#[derive(Debug, Deserialize)]
+pub struct RefreshFileRequest {
+    pub project_id: String,
+    /// File to re-analyze, absolute or relative to the project directory
+    pub file_path: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RefreshFileResponse {
+    pub project_id: String,
+    pub file_path: String,
+    /// Functions and call edges added and removed by the refresh
+    #[serde(flatten)]
+    pub delta: GraphDelta,
+    /// Resolved/unresolved call counts after the refresh
+    pub resolved_calls: usize,
+    pub unresolved_calls: usize,
+}
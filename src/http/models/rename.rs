@@ -0,0 +1,25 @@
+use serde::{Deserialize, Serialize};
+
+use crate::services::rename::{RenameKind, RenamePreview};
+
+#[derive(Debug, Deserialize)]
+pub struct PreviewRenameRequest {
+    pub project_id: Option<String>,
+    /// Current name of the function or class
+    pub name: String,
+    /// Proposed name; existing functions with this name are reported as conflicts
+    pub new_name: Option<String>,
+    /// Restrict declarations to functions or classes (both when omitted)
+    pub kind: Option<RenameKind>,
+    /// Also list occurrences inside string literals (default false)
+    pub include_strings: Option<bool>,
+    /// Also list occurrences inside comments and docstrings (default false)
+    pub include_comments: Option<bool>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct PreviewRenameResponse {
+    pub project_id: String,
+    #[serde(flatten)]
+    pub preview: RenamePreview,
+}
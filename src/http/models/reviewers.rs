@@ -0,0 +1,25 @@
+use serde::{Deserialize, Serialize};
+
+use crate::services::reviewers::ReviewerSuggestion;
+
+#[derive(Debug, Deserialize)]
+pub struct SuggestReviewersRequest {
+    pub project_id: Option<String>,
+    /// Changed files, relative to the project directory (suffix match like `/projects/:id/risk`)
+    pub changed_files: Vec<String>,
+    /// How many caller levels to follow from the changed functions (default 3)
+    pub max_depth: Option<usize>,
+    pub limit: Option<usize>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SuggestReviewersResponse {
+    pub project_id: String,
+    /// Functions defined in the changed files
+    pub changed_functions: usize,
+    /// Transitive callers of the changed functions within `max_depth`
+    pub impacted_functions: usize,
+    /// Affected functions with neither a CODEOWNERS rule nor git history
+    pub unowned_functions: usize,
+    pub reviewers: Vec<ReviewerSuggestion>,
+}
@@ -0,0 +1,26 @@
+use serde::{Deserialize, Serialize};
+
+use crate::services::risk::FunctionRisk;
+
+#[derive(Debug, Deserialize)]
+pub struct ProjectRiskQuery {
+    /// Only return functions scoring at least this much (0-100)
+    pub min_score: Option<f64>,
+    /// Only return functions not reached from any test
+    pub untested_only: Option<bool>,
+    /// Comma-separated list of touched files to restrict the report to, matched against the end of each path by whole components
+    pub files: Option<String>,
+    /// Score at or above which an untested function fails the gate (default 70)
+    pub gate_threshold: Option<f64>,
+    pub limit: Option<usize>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ProjectRiskResponse {
+    pub project_id: String,
+    pub total_functions: usize,
+    /// Untested functions at or above `gate_threshold`
+    pub high_risk_untested: usize,
+    pub gate_passed: bool,
+    pub functions: Vec<FunctionRisk>,
+}
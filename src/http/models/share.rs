@@ -0,0 +1,31 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Default, Deserialize)]
+pub struct CreateShareRequest {
+    /// Token lifetime such as `"2h"` or `"3d"`; `share.default_ttl` when omitted
+    pub expires_in: Option<String>,
+    /// File (and optionally function) the returned `view_url` opens on
+    pub filepath: Option<String>,
+    pub function_name: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ShareTokenResponse {
+    pub project_id: String,
+    pub token: String,
+    pub expires_at: DateTime<Utc>,
+    /// Interactive call-graph page readable with the token alone
+    pub view_url: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SharedProjectResponse {
+    pub project_id: String,
+    pub project_dir: String,
+    pub expires_at: DateTime<Utc>,
+    pub total_functions: usize,
+    pub total_files: usize,
+    /// Files with at least one function, usable as `filepath` in shared queries
+    pub files: Vec<String>,
+}
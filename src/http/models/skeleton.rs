@@ -1,8 +1,12 @@
 use serde::{Deserialize, Serialize};
 
+use crate::services::ContextBudget;
+
 #[derive(Debug, Deserialize)]
 pub struct QueryCodeSkeletonRequest {
     pub filepaths: Vec<String>,
+    /// Fit the skeletons into this many (estimated) tokens, keeping entry points and exported API first
+    pub max_tokens: Option<usize>,
 }
 
 #[derive(Debug, Serialize)]
@@ -10,9 +14,14 @@ pub struct CodeSkeletonResponse {
     pub filepath: String,
     pub language: String,
     pub skeleton_text: String,
+    /// Served from the skeleton cache (file content unchanged since it was last parsed)
+    pub cached: bool,
 }
 
 #[derive(Debug, Serialize)]
 pub struct CodeSkeletonBatchResponse {
     pub skeletons: Vec<CodeSkeletonResponse>,
+    /// How `max_tokens` was spent; present only when a budget was requested
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub budget: Option<ContextBudget>,
 } 
\ No newline at end of file
@@ -0,0 +1,23 @@
+use serde::{Deserialize, Serialize};
+
+use crate::codegraph::types::{CallRelation, FunctionInfo};
+
+#[derive(Debug, Deserialize)]
+pub struct SliceGraphRequest {
+    pub project_id: Option<String>,
+    /// Attribute predicate, e.g. `language == "java" && owner == "payments" && !generated`
+    pub expr: String,
+    /// Export format of the slice (see `GET /projects/:id/export`); JSON nodes and edges when omitted
+    pub format: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SliceGraphResponse {
+    pub project_id: String,
+    pub expr: String,
+    /// Functions in the whole graph
+    pub total_functions: usize,
+    pub functions: Vec<FunctionInfo>,
+    /// Calls whose caller and callee are both in the slice
+    pub calls: Vec<CallRelation>,
+}
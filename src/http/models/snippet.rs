@@ -16,4 +16,6 @@ pub struct CodeSnippetResponse {
     pub line_start: usize,
     pub line_end: usize,
     pub language: String,
+    /// The code was stored with the graph at build time rather than read from the working tree
+    pub from_snapshot: bool,
 } 
\ No newline at end of file
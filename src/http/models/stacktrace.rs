@@ -0,0 +1,23 @@
+use serde::{Deserialize, Serialize};
+
+use crate::services::stacktrace::ExplainedFrame;
+
+#[derive(Debug, Deserialize)]
+pub struct ExplainStacktraceRequest {
+    pub project_id: Option<String>,
+    /// Raw stack trace text as printed by the runtime
+    pub stacktrace: String,
+    /// Attach source snippets to resolved frames (default true)
+    pub include_snippets: Option<bool>,
+    /// Maximum snippet length in lines (default 40)
+    pub max_snippet_lines: Option<usize>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ExplainStacktraceResponse {
+    pub project_id: String,
+    pub frames: Vec<ExplainedFrame>,
+    pub resolved_frames: usize,
+    pub inferred_frames: usize,
+    pub unresolved_frames: usize,
+}
@@ -0,0 +1,22 @@
+use serde::{Deserialize, Serialize};
+
+use crate::services::unresolved::UnresolvedCallGroup;
+
+#[derive(Debug, Deserialize)]
+pub struct QueryUnresolvedCallsRequest {
+    pub project_id: Option<String>,
+    /// Only report callee names with at least this many unresolved calls
+    pub min_count: Option<usize>,
+    /// Maximum number of groups to return
+    pub limit: Option<usize>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct QueryUnresolvedCallsResponse {
+    pub project_id: String,
+    pub total_calls: usize,
+    pub unresolved_calls: usize,
+    /// Number of distinct callee names before `min_count` and `limit` are applied
+    pub total_groups: usize,
+    pub groups: Vec<UnresolvedCallGroup>,
+}
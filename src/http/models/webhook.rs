@@ -0,0 +1,25 @@
+use serde::Serialize;
+
+#[derive(Debug, Serialize)]
+pub struct GitWebhookResponse {
+    /// Whether a rebuild was queued; false for ignored events
+    pub queued: bool,
+    pub project_id: Option<String>,
+    pub project_dir: Option<String>,
+    #[serde(rename = "ref")]
+    pub git_ref: Option<String>,
+    /// Why the event did not queue a rebuild
+    pub reason: Option<String>,
+}
+
+impl GitWebhookResponse {
+    pub fn ignored(reason: impl Into<String>) -> Self {
+        Self {
+            queued: false,
+            project_id: None,
+            project_dir: None,
+            git_ref: None,
+            reason: Some(reason.into()),
+        }
+    }
+}
@@ -0,0 +1,111 @@
+use axum::http::{
+    header::{IF_RANGE, RANGE},
+    HeaderMap,
+};
+
+/// Inclusive byte range of a response body
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ByteRange {
+    pub start: u64,
+    pub end: u64,
+}
+
+impl ByteRange {
+    /// Value of the `Content-Range` header for a body of `total` bytes
+    pub fn content_range(&self, total: u64) -> String {
+        format!("bytes {}-{}/{}", self.start, self.end, total)
+    }
+}
+
+/// Outcome of evaluating the `Range` header against a body of known size
+#[derive(Debug, PartialEq, Eq)]
+pub enum RangeRequest {
+    /// No usable range: send the whole body with 200
+    Full,
+    /// Send the given slice with 206
+    Partial(ByteRange),
+    /// No byte of the body falls in the range: answer 416
+    Unsatisfiable,
+}
+
+/// Parse a single `bytes=` range. Multiple ranges, other units and
+/// malformed values fall back to the full body, as RFC 9110 allows.
+pub fn parse_range(value: &str, total: u64) -> RangeRequest {
+    let Some(spec) = value.trim().strip_prefix("bytes=") else {
+        return RangeRequest::Full;
+    };
+    if spec.contains(',') {
+        return RangeRequest::Full;
+    }
+    let Some((start, end)) = spec.trim().split_once('-') else {
+        return RangeRequest::Full;
+    };
+
+    let range = match (start.trim(), end.trim()) {
+        ("", "") => return RangeRequest::Full,
+        // suffix range: the last N bytes
+        ("", suffix) => match suffix.parse::<u64>() {
+            Ok(0) => return RangeRequest::Unsatisfiable,
+            Ok(n) if total > 0 => ByteRange { start: total.saturating_sub(n), end: total - 1 },
+            Ok(_) => return RangeRequest::Unsatisfiable,
+            Err(_) => return RangeRequest::Full,
+        },
+        (start, end) => {
+            let Ok(start) = start.parse::<u64>() else {
+                return RangeRequest::Full;
+            };
+            let end = match end {
+                "" => u64::MAX,
+                end => match end.parse::<u64>() {
+                    Ok(end) if end >= start => end,
+                    _ => return RangeRequest::Full,
+                },
+            };
+            if start >= total {
+                return RangeRequest::Unsatisfiable;
+            }
+            ByteRange { start, end: end.min(total - 1) }
+        }
+    };
+    RangeRequest::Partial(range)
+}
+
+/// Evaluate `Range` and `If-Range` for a body identified by `etag`.
+/// A stale `If-Range` validator means the client's partial copy is outdated,
+/// so the whole body is sent instead.
+pub fn requested_range(headers: &HeaderMap, etag: &str, total: u64) -> RangeRequest {
+    let Some(range) = headers.get(RANGE).and_then(|v| v.to_str().ok()) else {
+        return RangeRequest::Full;
+    };
+    if let Some(validator) = headers.get(IF_RANGE).and_then(|v| v.to_str().ok()) {
+        if validator.trim() != etag {
+            return RangeRequest::Full;
+        }
+    }
+    parse_range(range, total)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_range() {
+        let partial = |start, end| RangeRequest::Partial(ByteRange { start, end });
+        assert_eq!(parse_range("bytes=0-9", 100), partial(0, 9));
+        assert_eq!(parse_range("bytes=90-", 100), partial(90, 99));
+        assert_eq!(parse_range("bytes=90-500", 100), partial(90, 99));
+        assert_eq!(parse_range("bytes=-10", 100), partial(90, 99));
+        assert_eq!(parse_range("bytes=-500", 100), partial(0, 99));
+        assert_eq!(parse_range("bytes=100-", 100), RangeRequest::Unsatisfiable);
+        assert_eq!(parse_range("bytes=0-1,5-6", 100), RangeRequest::Full);
+        assert_eq!(parse_range("bytes=9-1", 100), RangeRequest::Full);
+        assert_eq!(parse_range("lines=1-2", 100), RangeRequest::Full);
+
+        let mut headers = HeaderMap::new();
+        headers.insert(RANGE, "bytes=10-".parse().unwrap());
+        assert_eq!(requested_range(&headers, "\"abc\"", 100), partial(10, 99));
+        headers.insert(IF_RANGE, "\"old\"".parse().unwrap());
+        assert_eq!(requested_range(&headers, "\"abc\"", 100), RangeRequest::Full);
+    }
+}
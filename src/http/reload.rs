@@ -0,0 +1,147 @@
+use serde::Serialize;
+
+use crate::cli::set_log_level;
+use crate::services::graph_builder::{build_and_store, project_id_for};
+use super::config::{PreloadProject, ServerConfig};
+use super::state::AppState;
+
+/// What a configuration reload changed
+#[derive(Debug, Clone, Serialize)]
+pub struct ReloadReport {
+    pub tenants: usize,
+    pub max_depth: usize,
+    pub max_nodes: usize,
+    pub log_level: Option<String>,
+    /// Preloaded projects, as `project_dir`
+    pub preloaded: Vec<String>,
+    /// Sections that changed but only take effect after a restart
+    pub restart_required: Vec<String>,
+}
+
+/// Re-read the configuration file and apply auth keys (tenants, admin and
+/// webhook secrets), limits, the preload list and the log level.
+/// Loaded graphs are kept; nothing is swapped unless the new file is valid.
+pub async fn reload_config(state: &AppState) -> Result<ReloadReport, String> {
+    let path = state
+        .config_path
+        .as_deref()
+        .ok_or_else(|| "Server was started without --config; nothing to reload".to_string())?;
+    let mut new_config = ServerConfig::load(path)?;
+    let old_config = state.config.load();
+
+    let tenants = state.tenants.load().reload(&new_config.tenants)?;
+    if let Some(level) = &new_config.log_level {
+        set_log_level(level)?;
+    }
+
+    let restart_required = restart_required(&old_config, &new_config);
+    if !restart_required.is_empty() {
+        tracing::warn!("Config sections changed but need a restart: {}", restart_required.join(", "));
+    }
+    // Keep reporting the settings that are actually in effect
+    new_config.cors = old_config.cors.clone();
+    new_config.security_headers = old_config.security_headers.clone();
+    new_config.cache = old_config.cache.clone();
+    new_config.schedules = old_config.schedules.clone();
+
+    let report = ReloadReport {
+        tenants: new_config.tenants.len(),
+        max_depth: new_config.limits.max_depth,
+        max_nodes: new_config.limits.max_nodes,
+        log_level: new_config.log_level.clone(),
+        preloaded: new_config.preload.iter().map(|p| p.project_dir.clone()).collect(),
+        restart_required,
+    };
+
+    let preload = new_config.preload.clone();
+    state.tenants.store(tenants);
+    state.config.store(new_config);
+    state.cache.clear();
+    preload_projects(state, &preload).await;
+
+    tracing::info!("Reloaded configuration from {}", path.display());
+    Ok(report)
+}
+
+/// Config sections that are baked into the router or background tasks at startup
+fn restart_required(old: &ServerConfig, new: &ServerConfig) -> Vec<String> {
+    let differs = |a: serde_json::Result<serde_json::Value>, b: serde_json::Result<serde_json::Value>| {
+        a.ok() != b.ok()
+    };
+    let mut sections = Vec::new();
+    if differs(serde_json::to_value(&old.cors), serde_json::to_value(&new.cors)) {
+        sections.push("cors".to_string());
+    }
+    if differs(serde_json::to_value(&old.security_headers), serde_json::to_value(&new.security_headers)) {
+        sections.push("security_headers".to_string());
+    }
+    if differs(serde_json::to_value(&old.cache), serde_json::to_value(&new.cache)) {
+        sections.push("cache".to_string());
+    }
+    if differs(serde_json::to_value(&old.schedules), serde_json::to_value(&new.schedules)) {
+        sections.push("schedule".to_string());
+    }
+    sections
+}
+
+/// Make sure each preloaded project has a stored graph (building it if needed)
+/// and load it into memory when its storage has no graph loaded yet.
+pub async fn preload_projects(state: &AppState, projects: &[PreloadProject]) {
+    let tenants = state.tenants.load();
+    for project in projects {
+        let Some(storage) = tenants.storage_for(project.tenant.as_deref()) else {
+            tracing::error!("Cannot preload {}: unknown tenant {:?}", project.project_dir, project.tenant);
+            continue;
+        };
+        let project_dir = project.project_dir.clone();
+        let result = tokio::task::spawn_blocking(move || -> Result<(), String> {
+            let project_id = project_id_for(&project_dir);
+            let stored = storage
+                .get_persistence()
+                .load_graph(&project_id)
+                .map_err(|e| format!("Failed to load graph: {}", e))?;
+            let (graph, snapshot) = match stored {
+                Some(graph) => (graph, storage.get_persistence().load_snapshot(&project_id).ok().flatten()),
+                None => {
+                    let outcome = build_and_store(&storage, &project_dir, Default::default()).map_err(|e| e.to_string())?;
+                    (outcome.graph, Some(outcome.snapshot))
+                }
+            };
+            if storage.get_graph().read().is_none() {
+                storage.set_graph_with_snapshot(graph, snapshot);
+                tracing::info!("Preloaded {} into memory", project_dir);
+            }
+            Ok(())
+        })
+        .await
+        .unwrap_or_else(|e| Err(format!("Preload task panicked: {}", e)));
+
+        if let Err(e) = result {
+            tracing::error!("Failed to preload {}: {}", project.project_dir, e);
+        }
+    }
+}
+
+/// Reload the configuration whenever the process receives SIGHUP
+pub fn spawn_sighup_listener(state: AppState) {
+    #[cfg(unix)]
+    tokio::spawn(async move {
+        use tokio::signal::unix::{signal, SignalKind};
+
+        let mut hangup = match signal(SignalKind::hangup()) {
+            Ok(hangup) => hangup,
+            Err(e) => {
+                tracing::warn!("Cannot listen for SIGHUP: {}", e);
+                return;
+            }
+        };
+        while hangup.recv().await.is_some() {
+            tracing::info!("SIGHUP received, reloading configuration");
+            if let Err(e) = reload_config(&state).await {
+                tracing::error!("Configuration reload failed: {}", e);
+            }
+        }
+    });
+    #[cfg(not(unix))]
+    let _ = state;
+}
@@ -0,0 +1,394 @@
+use std::collections::HashMap;
+use std::path::Path;
+use std::process::Command;
+use std::sync::Arc;
+use std::time::Instant;
+
+use chrono::{DateTime, Utc};
+use parking_lot::{Mutex, RwLock};
+use serde::Serialize;
+use tokio::time::MissedTickBehavior;
+
+use crate::codegraph::AnalyzeOptions;
+use crate::services::graph_builder::{build_and_store, project_id_for};
+use crate::storage::StorageManager;
+use super::config::ReindexSchedule;
+use super::tenant::TenantRegistry;
+
+/// A single re-index of one project
+#[derive(Debug, Clone)]
+pub struct ReindexJob {
+    pub project_dir: String,
+    pub full_rebuild: bool,
+    pub languages: Vec<String>,
+    pub exclude_paths: Vec<String>,
+    /// What caused the run, e.g. `"schedule"` or `"push refs/heads/main"`
+    pub trigger: String,
+    /// Fetch this ref and fast-forward the checkout before analyzing
+    pub sync_ref: Option<String>,
+    /// Tenant owning the project; `None` for the default storage
+    pub tenant: Option<String>,
+}
+
+impl ReindexJob {
+    fn key(&self) -> String {
+        status_key(self.tenant.as_deref(), &project_id_for(&self.project_dir))
+    }
+}
+
+impl From<&ReindexSchedule> for ReindexJob {
+    fn from(schedule: &ReindexSchedule) -> Self {
+        Self {
+            project_dir: schedule.project_dir.clone(),
+            full_rebuild: schedule.full_rebuild,
+            languages: schedule.languages.clone(),
+            exclude_paths: schedule.exclude_paths.clone(),
+            trigger: "schedule".to_string(),
+            sync_ref: None,
+            tenant: schedule.tenant.clone(),
+        }
+    }
+}
+
+/// Outcome of the background re-index runs for one project
+#[derive(Debug, Clone, Serialize)]
+pub struct ReindexStatus {
+    pub project_dir: String,
+    /// Schedule interval, absent for projects only rebuilt on demand
+    pub every: Option<String>,
+    pub runs: u64,
+    pub failures: u64,
+    pub running: bool,
+    /// Another run is waiting for the current one to finish
+    pub queued: bool,
+    pub last_trigger: Option<String>,
+    pub last_run_at: Option<DateTime<Utc>>,
+    pub last_success_at: Option<DateTime<Utc>>,
+    pub last_failure_at: Option<DateTime<Utc>>,
+    pub last_error: Option<String>,
+    pub last_duration_ms: Option<u64>,
+    pub last_total_functions: Option<usize>,
+}
+
+impl ReindexStatus {
+    fn new(project_dir: &str, every: Option<String>) -> Self {
+        Self {
+            project_dir: project_dir.to_string(),
+            every,
+            runs: 0,
+            failures: 0,
+            running: false,
+            queued: false,
+            last_trigger: None,
+            last_run_at: None,
+            last_success_at: None,
+            last_failure_at: None,
+            last_error: None,
+            last_duration_ms: None,
+            last_total_functions: None,
+        }
+    }
+}
+
+/// Rebuilds or refreshes project graphs in the background, either on a
+/// configured interval or when queued (e.g. by an SCM push webhook).
+///
+/// Runs for the same project are serialized, and jobs queued while one is
+/// running collapse into a single follow-up run. Runs save the graph and
+/// update the project registry. The in-memory graph used by `/query_call_graph`
+/// is replaced only when it holds the rebuilt project, so a background refresh
+/// never swaps out a different project an interactive user has loaded.
+pub struct ReindexScheduler {
+    schedules: Vec<ReindexSchedule>,
+    // key: status_key(tenant, project_id)
+    statuses: RwLock<HashMap<String, ReindexStatus>>,
+    // key: status_key(..); the latest job waiting to run
+    pending: Mutex<HashMap<String, ReindexJob>>,
+    // key: status_key(..); held while a job for the project runs
+    running: Mutex<HashMap<String, Arc<tokio::sync::Mutex<()>>>>,
+}
+
+impl ReindexScheduler {
+    pub fn new(schedules: Vec<ReindexSchedule>) -> Self {
+        let statuses = schedules
+            .iter()
+            .map(|schedule| {
+                let status = ReindexStatus::new(&schedule.project_dir, Some(schedule.every.clone()));
+                (ReindexJob::from(schedule).key(), status)
+            })
+            .collect();
+        Self {
+            schedules,
+            statuses: RwLock::new(statuses),
+            pending: Mutex::new(HashMap::new()),
+            running: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Background status for a tenant's project, if it was scheduled or queued
+    pub fn status(&self, tenant: Option<&str>, project_id: &str) -> Option<ReindexStatus> {
+        self.statuses.read().get(&status_key(tenant, project_id)).cloned()
+    }
+
+    /// Spawn one background task per schedule
+    pub fn start(self: &Arc<Self>, tenants: &TenantRegistry) {
+        for schedule in self.schedules.clone() {
+            let Some(storage) = tenants.storage_for(schedule.tenant.as_deref()) else {
+                tracing::error!(
+                    "Skipping schedule for {}: unknown tenant {:?}",
+                    schedule.project_dir,
+                    schedule.tenant
+                );
+                continue;
+            };
+            let interval = match schedule.interval() {
+                Ok(interval) => interval,
+                Err(e) => {
+                    tracing::error!("Skipping schedule for {}: {}", schedule.project_dir, e);
+                    continue;
+                }
+            };
+            tracing::info!(
+                "Scheduling re-index of {} every {} ({})",
+                schedule.project_dir,
+                schedule.every,
+                if schedule.full_rebuild { "full rebuild" } else { "incremental" }
+            );
+
+            let scheduler = Arc::clone(self);
+            tokio::spawn(async move {
+                let first = if schedule.run_on_start {
+                    tokio::time::Instant::now()
+                } else {
+                    tokio::time::Instant::now() + interval
+                };
+                let mut ticker = tokio::time::interval_at(first, interval);
+                ticker.set_missed_tick_behavior(MissedTickBehavior::Skip);
+                loop {
+                    ticker.tick().await;
+                    scheduler.run_once(ReindexJob::from(&schedule), Arc::clone(&storage)).await;
+                }
+            });
+        }
+    }
+
+    /// Queue a job without waiting for it; returns the project id.
+    /// A job already waiting for the same project is replaced by this one.
+    pub fn enqueue(self: &Arc<Self>, job: ReindexJob, storage: Arc<StorageManager>) -> String {
+        let key = job.key();
+        self.update(&job, |status| status.queued = true);
+        let project_id = project_id_for(&job.project_dir);
+        let already_waiting = self.pending.lock().insert(key.clone(), job).is_some();
+        if !already_waiting {
+            let scheduler = Arc::clone(self);
+            tokio::spawn(async move {
+                let lock = scheduler.project_lock(&key);
+                let _guard = lock.lock().await;
+                let job = scheduler.pending.lock().remove(&key);
+                if let Some(job) = job {
+                    scheduler.run_locked(job, storage).await;
+                }
+            });
+        }
+        project_id
+    }
+
+    /// Run a single job, waiting for any run of the same project to finish first
+    pub async fn run_once(&self, job: ReindexJob, storage: Arc<StorageManager>) {
+        let lock = self.project_lock(&job.key());
+        let _guard = lock.lock().await;
+        self.run_locked(job, storage).await;
+    }
+
+    /// Wait until no build, re-index or compaction of the project is running and
+    /// hold it off until the guard is dropped
+    pub async fn lock_project(&self, tenant: Option<&str>, project_id: &str) -> tokio::sync::OwnedMutexGuard<()> {
+        self.project_lock(&status_key(tenant, project_id)).lock_owned().await
+    }
+
+    /// Blocking variant of [`ReindexScheduler::lock_project`] for use on blocking threads
+    pub fn blocking_lock_project(&self, tenant: Option<&str>, project_id: &str) -> tokio::sync::OwnedMutexGuard<()> {
+        self.project_lock(&status_key(tenant, project_id)).blocking_lock_owned()
+    }
+
+    fn project_lock(&self, key: &str) -> Arc<tokio::sync::Mutex<()>> {
+        self.running.lock().entry(key.to_string()).or_default().clone()
+    }
+
+    async fn run_locked(&self, job: ReindexJob, storage: Arc<StorageManager>) {
+        let still_queued = self.pending.lock().contains_key(&job.key());
+        self.update(&job, |status| {
+            status.running = true;
+            status.queued = still_queued;
+            status.last_trigger = Some(job.trigger.clone());
+            status.last_run_at = Some(Utc::now());
+        });
+
+        let started = Instant::now();
+        let project_dir = job.project_dir.clone();
+        let sync_ref = job.sync_ref.clone();
+        let options = AnalyzeOptions {
+            languages: Vec::new(),
+            exclude_paths: job.exclude_paths.clone(),
+            force_rebuild: job.full_rebuild,
+            ..Default::default()
+        }
+        .with_languages(&job.languages);
+
+        let result = match options {
+            Ok(options) => tokio::task::spawn_blocking(move || {
+                if let Some(git_ref) = &sync_ref {
+                    sync_checkout(Path::new(&project_dir), git_ref)?;
+                }
+                let outcome = build_and_store(&storage, &project_dir, options).map_err(|e| e.to_string())?;
+                storage.replace_project_graph(Path::new(&project_dir), outcome.graph, Some(outcome.snapshot));
+                Ok(outcome.total_functions)
+            })
+            .await
+            .unwrap_or_else(|e| Err(format!("Re-index task panicked: {}", e))),
+            Err(e) => Err(e),
+        };
+        let elapsed_ms = started.elapsed().as_millis() as u64;
+
+        match &result {
+            Ok(total_functions) => tracing::info!(
+                "Re-indexed {} ({}) in {} ms ({} functions)",
+                job.project_dir,
+                job.trigger,
+                elapsed_ms,
+                total_functions
+            ),
+            Err(e) => tracing::error!("Re-index of {} ({}) failed: {}", job.project_dir, job.trigger, e),
+        }
+
+        self.update(&job, |status| {
+            status.running = false;
+            status.runs += 1;
+            status.last_duration_ms = Some(elapsed_ms);
+            match result {
+                Ok(total_functions) => {
+                    status.last_success_at = Some(Utc::now());
+                    status.last_total_functions = Some(total_functions);
+                }
+                Err(e) => {
+                    status.failures += 1;
+                    status.last_failure_at = Some(Utc::now());
+                    status.last_error = Some(e);
+                }
+            }
+        });
+    }
+
+    fn update(&self, job: &ReindexJob, f: impl FnOnce(&mut ReindexStatus)) {
+        let mut statuses = self.statuses.write();
+        let status = statuses
+            .entry(job.key())
+            .or_insert_with(|| ReindexStatus::new(&job.project_dir, None));
+        f(status);
+    }
+}
+
+fn status_key(tenant: Option<&str>, project_id: &str) -> String {
+    format!("{}/{}", tenant.unwrap_or(""), project_id)
+}
+
+/// Fetch `git_ref` from `origin` and fast-forward the checkout to it
+fn sync_checkout(project_dir: &Path, git_ref: &str) -> Result<(), String> {
+    let git = |args: &[&str]| -> Result<(), String> {
+        let output = Command::new("git")
+            .arg("-C")
+            .arg(project_dir)
+            .args(args)
+            .output()
+            .map_err(|e| format!("Failed to run git: {}", e))?;
+        if output.status.success() {
+            Ok(())
+        } else {
+            Err(format!(
+                "git {} failed: {}",
+                args.join(" "),
+                String::from_utf8_lossy(&output.stderr).trim()
+            ))
+        }
+    };
+    git(&["fetch", "--quiet", "origin", git_ref])?;
+    git(&["merge", "--ff-only", "--quiet", "FETCH_HEAD"])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cli::args::StorageMode;
+    use crate::codegraph::types::{FunctionInfo, PetCodeGraph};
+    use crate::http::config::parse_interval;
+    use std::time::Duration;
+
+    fn schedule(project_dir: &str) -> ReindexSchedule {
+        ReindexSchedule {
+            project_dir: project_dir.to_string(),
+            every: "15m".to_string(),
+            full_rebuild: false,
+            run_on_start: false,
+            tenant: None,
+            languages: Vec::new(),
+            exclude_paths: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_parse_interval() {
+        assert_eq!(parse_interval("90").unwrap(), Duration::from_secs(90));
+        assert_eq!(parse_interval("15m").unwrap(), Duration::from_secs(900));
+        assert_eq!(parse_interval("2h").unwrap(), Duration::from_secs(7200));
+        assert!(parse_interval("0s").is_err());
+        assert!(parse_interval("5w").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_failed_run_is_recorded() {
+        let missing = schedule("/nonexistent/codegraph/project");
+        let scheduler = ReindexScheduler::new(vec![missing.clone()]);
+        let project_id = project_id_for(&missing.project_dir);
+
+        scheduler
+            .run_once(ReindexJob::from(&missing), Arc::new(StorageManager::new()))
+            .await;
+
+        let status = scheduler.status(None, &project_id).unwrap();
+        assert_eq!((status.runs, status.failures), (1, 1));
+        assert!(!status.running);
+        assert!(status.last_success_at.is_none());
+        assert!(status.last_error.unwrap().contains("not found"));
+    }
+
+    #[tokio::test]
+    async fn test_run_replaces_in_memory_graph_of_same_project() {
+        let dir = tempfile::tempdir().unwrap();
+        let project = dir.path().join("project");
+        std::fs::create_dir_all(&project).unwrap();
+        std::fs::write(project.join("app.py"), "def main():\n    helper()\n\ndef helper():\n    pass\n").unwrap();
+        let storage = Arc::new(StorageManager::with_base_dir(dir.path().join("db"), StorageMode::Json));
+        let job = ReindexJob::from(&schedule(project.to_str().unwrap()));
+        let loaded = |file: &std::path::Path| {
+            let mut graph = PetCodeGraph::new();
+            graph.add_function(FunctionInfo { name: "stale".to_string(), file_path: file.to_path_buf(), ..Default::default() });
+            graph
+        };
+
+        // A graph of another project stays loaded
+        storage.set_graph(loaded(&dir.path().join("other/app.py")));
+        let scheduler = ReindexScheduler::new(Vec::new());
+        scheduler.run_once(job.clone(), storage.clone()).await;
+        let names = |storage: &StorageManager| {
+            let mut names: Vec<String> = storage.get_graph_clone().unwrap().get_all_functions().iter().map(|f| f.name.clone()).collect();
+            names.sort();
+            names
+        };
+        assert_eq!(names(&storage), vec!["stale"]);
+
+        storage.set_graph(loaded(&project.join("app.py")));
+        scheduler.run_once(job, storage.clone()).await;
+        assert_eq!(names(&storage), vec!["helper", "main"]);
+        assert!(storage.graph_snapshot().is_some());
+    }
+}
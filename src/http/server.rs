@@ -1,54 +1,154 @@
 use axum::{
-    routing::{post, get},
+    middleware,
+    routing::{delete, post, get},
     Router,
     response::Json,
 };
+use std::path::PathBuf;
 use std::sync::Arc;
 use tokio::net::TcpListener;
-use tower_http::cors::CorsLayer;
 use crate::storage::StorageManager;
+use super::config::ServerConfig;
+use super::state::{AppState, Swappable};
+use super::reload::{preload_projects, spawn_sighup_listener};
+use crate::cli::set_log_level;
+use super::scheduler::ReindexScheduler;
+use super::tenant::TenantRegistry;
+use super::cache::{cache_query_response, QueryCache};
+use super::middleware::{create_cors_layer, with_request_tracing, with_security_headers};
+use super::versioning::negotiate_api_version;
 
 use super::{
-    handlers::{build_graph, query_call_graph, query_code_snippet, query_code_skeleton, query_hierarchical_graph, draw_call_graph, draw_call_graph_home, init, investigate_repo},
+    handlers::{build_graph, query_call_graph, query_code_snippet, query_code_skeleton, query_hierarchical_graph, draw_call_graph, draw_call_graph_home, init, investigate_repo, query_refactor_suggestions, query_unresolved_calls, project_risk, export_project_graph, export_project_ndjson, slice_graph, explain_stacktrace, explain_function, query_implementations, query_entry_points, find_log_origin, query_feature_flags, query_concurrency, query_lock_order_inversions, preview_rename, query_latency_budget, suggest_reviewers, annotate_project_nodes, project_annotations, import_project_profile, import_project_coverage, replay_project_traces, merge_project_observed_calls, refresh_file, project_metadata, project_files, project_manifest, compare_manifests, create_project_share, shared_project, shared_query_call_graph, shared_query_hierarchical_graph, shared_draw_call_graph, git_webhook, admin_reload, admin_compact, admin_analyzers, update_admin_analyzers, capabilities, register_federated_repository, list_federated_repositories, unregister_federated_repository, federated_search, federated_duplicates, federated_dependencies, federated_impact},
     models::ApiResponse,
 };
 
 pub struct CodeGraphServer {
     storage: Arc<StorageManager>,
+    config: ServerConfig,
+    config_path: Option<PathBuf>,
 }
 
 impl CodeGraphServer {
     pub fn new(storage: Arc<StorageManager>) -> Self {
-        Self { storage }
+        Self::with_config(storage, ServerConfig::default())
+    }
+
+    pub fn with_config(storage: Arc<StorageManager>, config: ServerConfig) -> Self {
+        Self { storage, config, config_path: None }
+    }
+
+    /// Remember where the configuration came from so it can be reloaded
+    /// with SIGHUP or `POST /admin/reload`
+    pub fn with_config_path(mut self, path: impl Into<PathBuf>) -> Self {
+        self.config_path = Some(path.into());
+        self
     }
 
     pub async fn start(self, addr: &str) -> Result<(), Box<dyn std::error::Error>> {
-        let app = self.create_router();
+        if let Some(level) = &self.config.log_level {
+            set_log_level(level)?;
+        }
+        let tenants = TenantRegistry::new(self.storage.clone(), &self.config.tenants)?;
+        let scheduler = Arc::new(ReindexScheduler::new(self.config.schedules.clone()));
+        scheduler.start(&tenants);
+        let preload = self.config.preload.clone();
+
+        let (app, state) = self.create_router(tenants, scheduler)?;
+        spawn_sighup_listener(state.clone());
+        tokio::spawn(async move { preload_projects(&state, &preload).await });
         
         let listener = TcpListener::bind(addr).await?;
-        println!("🚀 CodeGraph HTTP server starting on {}", addr);
+        tracing::info!("CodeGraph HTTP server listening on {}", addr);
         
         axum::serve(listener, app).await?;
         Ok(())
     }
 
-    fn create_router(self) -> Router {
-        // CORS configuration
-        let cors = CorsLayer::permissive();
+    fn create_router(
+        self,
+        tenants: TenantRegistry,
+        scheduler: Arc<ReindexScheduler>,
+    ) -> Result<(Router, AppState), String> {
+        self.config.validate()?;
+        let cors = create_cors_layer(&self.config.cors)?;
+        let security_headers = self.config.security_headers.clone();
 
-        Router::new()
-            .route("/health", get(health_check))
-            .route("/init", post(init))
-            .route("/build_graph", post(build_graph))
+        let state = AppState {
+            tenants: Arc::new(Swappable::new(tenants)),
+            cache: Arc::new(QueryCache::new(self.config.cache.clone())),
+            config: Arc::new(Swappable::new(self.config)),
+            scheduler,
+            config_path: self.config_path.map(Arc::new),
+        };
+
+        // Pure queries: answered from the response cache and tagged with ETags
+        let cached = Router::new()
             .route("/query_call_graph", post(query_call_graph))
             .route("/query_code_snippet", post(query_code_snippet))
-            .route("/query_code_skeleton", post(query_code_skeleton))
             .route("/query_hierarchical_graph", post(query_hierarchical_graph))
+            .route("/query_refactor_suggestions", post(query_refactor_suggestions))
+            .route("/query_unresolved_calls", post(query_unresolved_calls))
+            .route("/query_feature_flags", post(query_feature_flags))
+            .route("/query_concurrency", post(query_concurrency))
+            .route("/query_lock_order_inversions", post(query_lock_order_inversions))
+            .route("/preview_rename", post(preview_rename))
+            .route_layer(middleware::from_fn_with_state(state.clone(), cache_query_response));
+
+        let router = Router::new()
+            .route("/health", get(health_check))
+            .route("/capabilities", get(capabilities))
+            .route("/init", post(init))
+            .route("/query_code_skeleton", post(query_code_skeleton))
+            .route("/build_graph", post(build_graph))
+            .route("/refresh_file", post(refresh_file))
             .route("/investigate_repo", post(investigate_repo))
+            .route("/projects/:id", get(project_metadata))
+            .route("/projects/:id/files", get(project_files))
+            .route("/hooks/git", post(git_webhook))
+            .route("/admin/reload", post(admin_reload))
+            .route("/admin/compact", post(admin_compact))
+            .route("/admin/analyzers", get(admin_analyzers).post(update_admin_analyzers))
+            .route(
+                "/federated/repositories",
+                get(list_federated_repositories).post(register_federated_repository),
+            )
+            .route("/federated/repositories/:name", delete(unregister_federated_repository))
+            .route("/federated/search", post(federated_search))
+            .route("/federated/duplicates", post(federated_duplicates))
+            .route("/federated/dependencies", get(federated_dependencies))
+            .route("/federated/impact", post(federated_impact))
+            .route("/projects/:id/risk", get(project_risk))
+            .route("/projects/:id/export", get(export_project_graph))
+            .route("/projects/:id/export.ndjson", get(export_project_ndjson))
+            .route("/slice", post(slice_graph))
+            .route("/explain_stacktrace", post(explain_stacktrace))
+            .route("/explain_function", post(explain_function))
+            .route("/query_implementations", post(query_implementations))
+            .route("/query_entry_points", post(query_entry_points))
+            .route("/find_log_origin", post(find_log_origin))
+            .route("/query_latency_budget", post(query_latency_budget))
+            .route("/suggest_reviewers", post(suggest_reviewers))
+            .route("/projects/:id/annotations", get(project_annotations).post(annotate_project_nodes))
+            .route("/projects/:id/profiles", post(import_project_profile))
+            .route("/projects/:id/coverage", post(import_project_coverage))
+            .route("/projects/:id/traces", post(replay_project_traces))
+            .route("/projects/:id/observed_calls", post(merge_project_observed_calls))
             .route("/", get(draw_call_graph_home))
             .route("/draw_call_graph", get(draw_call_graph))
-            .layer(cors)
-            .with_state(self.storage)
+            .route("/projects/:id/manifest", get(project_manifest))
+            .route("/compare_manifests", post(compare_manifests))
+            .route("/projects/:id/share", post(create_project_share))
+            .route("/shared/:token", get(shared_project))
+            .route("/shared/:token/query_call_graph", post(shared_query_call_graph))
+            .route("/shared/:token/query_hierarchical_graph", post(shared_query_hierarchical_graph))
+            .route("/shared/:token/draw_call_graph", get(shared_draw_call_graph))
+            .merge(cached)
+            .layer(middleware::from_fn_with_state(state.clone(), negotiate_api_version))
+            .layer(cors);
+
+        let router = with_security_headers(router, &security_headers)?;
+        Ok((with_request_tracing(router).with_state(state.clone()), state))
     }
 }
 
@@ -57,5 +157,6 @@ async fn health_check() -> Json<ApiResponse<&'static str>> {
     Json(ApiResponse {
         success: true,
         data: "CodeGraph HTTP service is running",
+        snapshot: None,
     })
 } 
\ No newline at end of file
@@ -0,0 +1,136 @@
+use std::sync::Arc;
+
+use axum::{
+    async_trait,
+    extract::{FromRequestParts, Path},
+    http::{request::Parts, StatusCode},
+};
+use chrono::{DateTime, Utc};
+use hmac::{Hmac, Mac};
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+
+use crate::codegraph::types::PetCodeGraph;
+use crate::storage::{GraphSnapshot, StorageManager};
+use super::models::ApiError;
+use super::state::AppState;
+
+/// What a share token grants: read access to one build of one project until it expires
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ShareClaims {
+    /// Tenant whose storage holds the project; the default storage when unset
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tenant: Option<String>,
+    pub project_id: String,
+    /// `built_at` of the snapshot the token was issued for; a rebuild invalidates the token
+    pub built_at: DateTime<Utc>,
+    pub expires_at: DateTime<Utc>,
+}
+
+impl ShareClaims {
+    /// Token of the form `<hex claims>.<hex hmac>`, safe to put in a URL path
+    pub fn sign(&self, secret: &str) -> String {
+        let payload = serde_json::to_vec(self).unwrap_or_default();
+        format!("{}.{}", hex::encode(&payload), hex::encode(mac(secret, &payload)))
+    }
+
+    /// Claims of a token signed with `secret` that has not expired at `now`
+    pub fn verify(token: &str, secret: &str, now: DateTime<Utc>) -> Result<Self, &'static str> {
+        let (payload, signature) = token.split_once('.').ok_or("malformed share token")?;
+        let payload = hex::decode(payload).map_err(|_| "malformed share token")?;
+        let signature = hex::decode(signature).map_err(|_| "malformed share token")?;
+        let mut verifier = Hmac::<Sha256>::new_from_slice(secret.as_bytes()).map_err(|_| "invalid share secret")?;
+        verifier.update(&payload);
+        verifier.verify_slice(&signature).map_err(|_| "invalid share token")?;
+        let claims: Self = serde_json::from_slice(&payload).map_err(|_| "malformed share token")?;
+        if claims.expires_at <= now {
+            return Err("share token has expired");
+        }
+        Ok(claims)
+    }
+}
+
+fn mac(secret: &str, payload: &[u8]) -> Vec<u8> {
+    let Ok(mut mac) = Hmac::<Sha256>::new_from_slice(secret.as_bytes()) else {
+        return Vec::new();
+    };
+    mac.update(payload);
+    mac.finalize().into_bytes().to_vec()
+}
+
+/// The project snapshot named by the share token in a `/shared/:token/...` path.
+///
+/// Share tokens replace the tenant token on these routes only, so a token
+/// holder can query that one graph and nothing else on the server.
+pub struct SharedSnapshot {
+    pub claims: ShareClaims,
+    pub storage: Arc<StorageManager>,
+    pub graph: PetCodeGraph,
+    pub snapshot: GraphSnapshot,
+}
+
+#[async_trait]
+impl FromRequestParts<AppState> for SharedSnapshot {
+    type Rejection = ApiError;
+
+    async fn from_request_parts(parts: &mut Parts, state: &AppState) -> Result<Self, Self::Rejection> {
+        let Path(token) = Path::<String>::from_request_parts(parts, state)
+            .await
+            .map_err(|_| ApiError::new(StatusCode::BAD_REQUEST, "missing share token"))?;
+        let config = state.config.load();
+        let Some(secret) = config.share.secret.as_deref() else {
+            return Err(ApiError::new(StatusCode::FORBIDDEN, "sharing is not configured"));
+        };
+        let claims = ShareClaims::verify(&token, secret, Utc::now())
+            .map_err(|reason| ApiError::new(StatusCode::UNAUTHORIZED, reason))?;
+        let storage = state
+            .tenants
+            .load()
+            .storage_for(claims.tenant.as_deref())
+            .ok_or_else(|| ApiError::new(StatusCode::UNAUTHORIZED, "share token refers to an unknown tenant"))?;
+
+        // Only the build the token was issued for may be read
+        let replaced = || ApiError::new(StatusCode::GONE, "the shared snapshot has been replaced by a newer build");
+        let persistence = storage.get_persistence();
+        let snapshot = persistence
+            .load_snapshot(&claims.project_id)
+            .map_err(|_| ApiError::from(StatusCode::INTERNAL_SERVER_ERROR))?
+            .filter(|snapshot| snapshot.built_at == claims.built_at)
+            .ok_or_else(replaced)?;
+        let graph = persistence
+            .load_graph(&claims.project_id)
+            .map_err(|_| ApiError::from(StatusCode::INTERNAL_SERVER_ERROR))?
+            .ok_or_else(replaced)?;
+        Ok(Self { claims, storage, graph, snapshot })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    #[test]
+    fn test_share_token_round_trip() {
+        let now = Utc::now();
+        let claims = ShareClaims {
+            tenant: Some("team-a".to_string()),
+            project_id: "0cc175b9".to_string(),
+            built_at: now - Duration::hours(1),
+            expires_at: now + Duration::hours(2),
+        };
+        let token = claims.sign("s3cret");
+        assert!(token.chars().all(|c| c.is_ascii_hexdigit() || c == '.'));
+        assert_eq!(ShareClaims::verify(&token, "s3cret", now), Ok(claims.clone()));
+
+        assert_eq!(ShareClaims::verify(&token, "other", now), Err("invalid share token"));
+        assert_eq!(ShareClaims::verify(&token, "s3cret", now + Duration::hours(3)), Err("share token has expired"));
+
+        // Claims cannot be edited without the secret
+        let (_, signature) = token.split_once('.').unwrap();
+        let widened = ShareClaims { project_id: "92eb5ffe".to_string(), ..claims };
+        let forged = format!("{}.{}", hex::encode(serde_json::to_vec(&widened).unwrap()), signature);
+        assert_eq!(ShareClaims::verify(&forged, "s3cret", now), Err("invalid share token"));
+        assert_eq!(ShareClaims::verify("not-a-token", "s3cret", now), Err("malformed share token"));
+    }
+}
@@ -0,0 +1,58 @@
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use axum::extract::FromRef;
+use parking_lot::RwLock;
+
+use super::config::ServerConfig;
+use super::scheduler::ReindexScheduler;
+use super::tenant::TenantRegistry;
+use super::cache::QueryCache;
+
+/// A value that can be replaced while the server runs (config reload);
+/// readers take a cheap snapshot and are unaffected by later swaps.
+pub struct Swappable<T>(RwLock<Arc<T>>);
+
+impl<T> Swappable<T> {
+    pub fn new(value: T) -> Self {
+        Self(RwLock::new(Arc::new(value)))
+    }
+
+    pub fn load(&self) -> Arc<T> {
+        self.0.read().clone()
+    }
+
+    pub fn store(&self, value: T) {
+        *self.0.write() = Arc::new(value);
+    }
+}
+
+/// Shared state for all handlers; handlers extract only the parts they need.
+/// Storage is per tenant and is extracted with `TenantStorage`.
+#[derive(Clone)]
+pub struct AppState {
+    pub tenants: Arc<Swappable<TenantRegistry>>,
+    pub config: Arc<Swappable<ServerConfig>>,
+    pub scheduler: Arc<ReindexScheduler>,
+    pub cache: Arc<QueryCache>,
+    /// File the configuration was loaded from, re-read on reload
+    pub config_path: Option<Arc<PathBuf>>,
+}
+
+impl FromRef<AppState> for Arc<TenantRegistry> {
+    fn from_ref(state: &AppState) -> Self {
+        state.tenants.load()
+    }
+}
+
+impl FromRef<AppState> for Arc<ServerConfig> {
+    fn from_ref(state: &AppState) -> Self {
+        state.config.load()
+    }
+}
+
+impl FromRef<AppState> for Arc<ReindexScheduler> {
+    fn from_ref(state: &AppState) -> Self {
+        state.scheduler.clone()
+    }
+}
@@ -0,0 +1,159 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use axum::{
+    async_trait,
+    extract::FromRequestParts,
+    http::{header::AUTHORIZATION, request::Parts, HeaderMap, StatusCode, Uri},
+};
+
+use crate::storage::StorageManager;
+use super::config::TenantConfig;
+use super::models::ApiError;
+use super::state::AppState;
+
+/// Maps tenant tokens to their isolated storage.
+///
+/// Without configured tenants the server runs single-tenant and every
+/// request uses the default storage.
+pub struct TenantRegistry {
+    default: Arc<StorageManager>,
+    by_token: HashMap<String, Arc<StorageManager>>,
+    by_id: HashMap<String, Arc<StorageManager>>,
+}
+
+impl TenantRegistry {
+    pub fn new(default: Arc<StorageManager>, tenants: &[TenantConfig]) -> Result<Self, String> {
+        Self::build(default, tenants, &HashMap::new())
+    }
+
+    /// Registry for a reloaded tenant list. Tenants that still exist keep
+    /// their storage (and loaded graphs) and pick up the new quota.
+    pub fn reload(&self, tenants: &[TenantConfig]) -> Result<Self, String> {
+        Self::build(self.default.clone(), tenants, &self.by_id)
+    }
+
+    fn build(
+        default: Arc<StorageManager>,
+        tenants: &[TenantConfig],
+        existing: &HashMap<String, Arc<StorageManager>>,
+    ) -> Result<Self, String> {
+        let mut by_token = HashMap::new();
+        let mut by_id = HashMap::new();
+        for tenant in tenants {
+            if tenant.token.is_empty() {
+                return Err(format!("Tenant '{}' has an empty token", tenant.id));
+            }
+            let storage = match existing.get(&tenant.id) {
+                Some(storage) => storage.clone(),
+                None => Arc::new(StorageManager::for_tenant(
+                    &tenant.id,
+                    default.get_storage_mode().clone(),
+                    tenant.quota.clone(),
+                )?),
+            };
+            if by_id.insert(tenant.id.clone(), storage.clone()).is_some() {
+                return Err(format!("Duplicate tenant id '{}'", tenant.id));
+            }
+            if by_token.insert(tenant.token.clone(), storage).is_some() {
+                return Err(format!("Tenant '{}' reuses another tenant's token", tenant.id));
+            }
+        }
+        // Apply quotas only once the whole list is valid
+        for tenant in tenants {
+            by_id[&tenant.id].set_quota(tenant.quota.clone());
+        }
+        Ok(Self { default, by_token, by_id })
+    }
+
+    pub fn is_multi_tenant(&self) -> bool {
+        !self.by_id.is_empty()
+    }
+
+    /// Storage for a tenant id; `None` selects the default storage
+    pub fn storage_for(&self, tenant_id: Option<&str>) -> Option<Arc<StorageManager>> {
+        match tenant_id {
+            Some(id) => self.by_id.get(id).cloned(),
+            None => Some(self.default.clone()),
+        }
+    }
+
+    /// Storage selected by a request's token
+    pub fn storage_for_token(&self, token: Option<&str>) -> Result<Arc<StorageManager>, ApiError> {
+        if !self.is_multi_tenant() {
+            return Ok(self.default.clone());
+        }
+        let token = token.ok_or_else(|| ApiError::new(StatusCode::UNAUTHORIZED, "missing tenant token"))?;
+        self.by_token
+            .get(token)
+            .cloned()
+            .ok_or_else(|| ApiError::new(StatusCode::UNAUTHORIZED, "unknown tenant token"))
+    }
+}
+
+/// The calling tenant's storage, resolved from `Authorization: Bearer <token>`
+/// or an `access_token` query parameter (for browser pages).
+pub struct TenantStorage(pub Arc<StorageManager>);
+
+#[async_trait]
+impl FromRequestParts<AppState> for TenantStorage {
+    type Rejection = ApiError;
+
+    async fn from_request_parts(parts: &mut Parts, state: &AppState) -> Result<Self, Self::Rejection> {
+        let token = request_token(&parts.headers, &parts.uri);
+        state.tenants.load().storage_for_token(token.as_deref()).map(TenantStorage)
+    }
+}
+
+/// Token from `Authorization: Bearer <token>`
+pub fn bearer_token(headers: &HeaderMap) -> Option<String> {
+    headers
+        .get(AUTHORIZATION)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.strip_prefix("Bearer "))
+        .map(|t| t.trim().to_string())
+}
+
+/// Token from `Authorization: Bearer <token>` or the `access_token` query parameter
+pub fn request_token(headers: &HeaderMap, uri: &Uri) -> Option<String> {
+    bearer_token(headers).or_else(|| {
+        uri.query().and_then(|query| {
+            query
+                .split('&')
+                .find_map(|pair| pair.strip_prefix("access_token="))
+                .map(str::to_string)
+        })
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::StorageQuota;
+
+    fn tenant(id: &str, token: &str) -> TenantConfig {
+        TenantConfig {
+            id: id.to_string(),
+            token: token.to_string(),
+            quota: StorageQuota::default(),
+        }
+    }
+
+    #[test]
+    fn test_token_selects_tenant_storage() {
+        let default = Arc::new(StorageManager::new());
+
+        let single = TenantRegistry::new(default.clone(), &[]).unwrap();
+        assert!(single.storage_for_token(None).unwrap().tenant_id().is_none());
+
+        let registry = TenantRegistry::new(default, &[tenant("unit-test-a", "tok-a")]).unwrap();
+        let storage = registry.storage_for_token(Some("tok-a")).unwrap();
+        assert_eq!(storage.tenant_id(), Some("unit-test-a"));
+        assert!(storage.get_persistence().base_dir().ends_with("tenants/unit-test-a"));
+        assert!(registry.storage_for_token(None).is_err());
+        assert!(registry.storage_for_token(Some("tok-b")).is_err());
+
+        let invalid = TenantRegistry::new(Arc::new(StorageManager::new()), &[tenant("../escape", "tok")]);
+        assert!(invalid.is_err());
+    }
+}
@@ -0,0 +1,143 @@
+use axum::{
+    extract::{Request, State},
+    http::{
+        header::{ACCEPT, CONTENT_TYPE, VARY},
+        HeaderMap, HeaderValue, StatusCode,
+    },
+    middleware::Next,
+    response::{IntoResponse, Json, Response},
+};
+use serde::{Deserialize, Serialize};
+
+use super::models::ApiError;
+use super::state::AppState;
+
+/// Media type prefix of versioned responses: `application/vnd.codegraph.v2+json`
+const VENDOR_PREFIX: &str = "application/vnd.codegraph.v";
+
+/// Shape of JSON responses, negotiated with the `Accept` header.
+///
+/// Handlers serialize one shape for every version: fields are only ever
+/// added, so a body valid for v1 stays valid. A version names the oldest
+/// shape that has the fields a client relies on.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ApiVersion {
+    #[default]
+    V1,
+    /// Functions carry a `kind`: `function`, `unresolved`, `macro`, `generated`, `reexport` or `abstract`
+    /// (serialized from [`FunctionInfo::kind`](crate::codegraph::types::FunctionInfo::kind))
+    V2,
+}
+
+impl ApiVersion {
+    pub const SUPPORTED: [ApiVersion; 2] = [ApiVersion::V1, ApiVersion::V2];
+
+    pub fn number(self) -> u32 {
+        match self {
+            ApiVersion::V1 => 1,
+            ApiVersion::V2 => 2,
+        }
+    }
+
+    fn from_number(number: u32) -> Option<Self> {
+        Self::SUPPORTED.into_iter().find(|version| version.number() == number)
+    }
+
+    /// `application/vnd.codegraph.v<N>+json`
+    pub fn media_type(self) -> String {
+        format!("{}{}+json", VENDOR_PREFIX, self.number())
+    }
+}
+
+/// Version requested by the `Accept` header.
+///
+/// `Ok(None)` when no vendor media type is listed, so the configured default
+/// applies; `Err` carries the text of an unknown or unsupported version. When
+/// several vendor types are listed, the newest supported one wins.
+pub fn requested_version(headers: &HeaderMap) -> Result<Option<ApiVersion>, String> {
+    let mut requested = Vec::new();
+    let media_types = headers
+        .get_all(ACCEPT)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .map(|item| item.split(';').next().unwrap_or_default().trim().to_ascii_lowercase());
+    for media_type in media_types {
+        let Some(rest) = media_type.strip_prefix(VENDOR_PREFIX) else {
+            continue;
+        };
+        let number = rest.strip_suffix("+json").unwrap_or(rest);
+        requested.push(number.parse().ok().and_then(ApiVersion::from_number).ok_or(media_type.clone()));
+    }
+    if requested.is_empty() {
+        return Ok(None);
+    }
+    let supported = requested.iter().filter_map(|version| version.as_ref().ok()).max_by_key(|version| version.number());
+    match supported {
+        Some(version) => Ok(Some(*version)),
+        None => Err(requested.into_iter().find_map(Result::err).unwrap_or_default()),
+    }
+}
+
+fn is_json(response: &Response) -> bool {
+    response
+        .headers()
+        .get(CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|value| value.starts_with("application/json"))
+}
+
+/// Middleware that negotiates the response version.
+///
+/// Unsupported versions are rejected with 406. The negotiated version is put
+/// in the request extensions (the response cache keys on it). Clients that
+/// asked for a vendor media type get it back as the `Content-Type` of JSON
+/// responses.
+pub async fn negotiate_api_version(State(state): State<AppState>, mut request: Request, next: Next) -> Response {
+    let requested = match requested_version(request.headers()) {
+        Ok(requested) => requested,
+        Err(media_type) => {
+            let supported: Vec<String> = ApiVersion::SUPPORTED.iter().map(|version| version.media_type()).collect();
+            let message = format!("Unsupported media type '{}', supported: {}", media_type, supported.join(", "));
+            return (StatusCode::NOT_ACCEPTABLE, Json(ApiError::new(StatusCode::NOT_ACCEPTABLE, message))).into_response();
+        }
+    };
+    let version = requested.unwrap_or(state.config.load().api.default_version);
+    request.extensions_mut().insert(version);
+
+    let mut response = next.run(request).await;
+    response.headers_mut().append(VARY, HeaderValue::from_static("accept"));
+    if requested.is_some() && is_json(&response) {
+        if let Ok(media_type) = HeaderValue::from_str(&version.media_type()) {
+            response.headers_mut().insert(CONTENT_TYPE, media_type);
+        }
+    }
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn accept(value: &str) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert(ACCEPT, HeaderValue::from_str(value).unwrap());
+        headers
+    }
+
+    #[test]
+    fn test_requested_version() {
+        assert_eq!(requested_version(&HeaderMap::new()), Ok(None));
+        assert_eq!(requested_version(&accept("application/json, */*")), Ok(None));
+        assert_eq!(requested_version(&accept("application/vnd.codegraph.v1+json")), Ok(Some(ApiVersion::V1)));
+        assert_eq!(
+            requested_version(&accept("application/vnd.codegraph.v9+json, application/vnd.codegraph.v2+json;q=0.5")),
+            Ok(Some(ApiVersion::V2))
+        );
+        assert_eq!(
+            requested_version(&accept("application/vnd.codegraph.v9+json")),
+            Err("application/vnd.codegraph.v9+json".to_string())
+        );
+    }
+}
@@ -0,0 +1,166 @@
+use axum::http::HeaderMap;
+use hmac::{Hmac, Mac};
+use serde_json::Value;
+use sha2::Sha256;
+
+use super::config::{WebhookConfig, WebhookRepository};
+
+/// The parts of a GitHub or GitLab push payload needed to pick a project
+#[derive(Debug, Clone, PartialEq)]
+pub struct PushEvent {
+    /// `owner/name` of the pushed repository
+    pub repository: String,
+    /// Clone URLs advertised by the payload, used as alternative match keys
+    pub urls: Vec<String>,
+    /// Full ref, e.g. `refs/heads/main`
+    pub git_ref: String,
+    /// Commit the ref points to after the push; all zeros when the ref was deleted
+    pub after: Option<String>,
+}
+
+impl PushEvent {
+    pub fn is_deletion(&self) -> bool {
+        self.after.as_deref().is_some_and(|sha| !sha.is_empty() && sha.chars().all(|c| c == '0'))
+    }
+
+    pub fn branch(&self) -> Option<&str> {
+        self.git_ref.strip_prefix("refs/heads/")
+    }
+}
+
+/// Check the request against the shared secret.
+///
+/// GitHub sends `X-Hub-Signature-256: sha256=<hmac of the body>`; GitLab
+/// sends the secret itself in `X-Gitlab-Token`.
+pub fn verify_signature(secret: &str, headers: &HeaderMap, body: &[u8]) -> bool {
+    if let Some(signature) = headers.get("x-hub-signature-256").and_then(|v| v.to_str().ok()) {
+        let Some(Ok(expected)) = signature.strip_prefix("sha256=").map(hex::decode) else {
+            return false;
+        };
+        let Ok(mut mac) = Hmac::<Sha256>::new_from_slice(secret.as_bytes()) else {
+            return false;
+        };
+        mac.update(body);
+        return mac.verify_slice(&expected).is_ok();
+    }
+    if let Some(token) = headers.get("x-gitlab-token").and_then(|v| v.to_str().ok()) {
+        return constant_time_eq(token.as_bytes(), secret.as_bytes());
+    }
+    false
+}
+
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+/// Whether the headers announce a push event (as opposed to ping, PR, ...)
+pub fn is_push_event(headers: &HeaderMap) -> bool {
+    let header = |name: &str| headers.get(name).and_then(|v| v.to_str().ok());
+    match (header("x-github-event"), header("x-gitlab-event")) {
+        (Some(event), _) => event == "push",
+        (None, Some(event)) => event == "Push Hook",
+        (None, None) => true,
+    }
+}
+
+/// Extract the push details from a GitHub or GitLab payload
+pub fn parse_push_event(payload: &Value) -> Option<PushEvent> {
+    let git_ref = payload.get("ref")?.as_str()?.to_string();
+    let str_at = |value: &Value, key: &str| value.get(key).and_then(Value::as_str).map(str::to_string);
+
+    let (repository, urls) = if let Some(project) = payload.get("project") {
+        // GitLab
+        let urls = ["git_http_url", "git_ssh_url", "web_url"]
+            .iter()
+            .filter_map(|key| str_at(project, key))
+            .collect();
+        (str_at(project, "path_with_namespace")?, urls)
+    } else {
+        // GitHub
+        let repository = payload.get("repository")?;
+        let urls = ["clone_url", "ssh_url", "html_url"]
+            .iter()
+            .filter_map(|key| str_at(repository, key))
+            .collect();
+        (str_at(repository, "full_name")?, urls)
+    };
+
+    Some(PushEvent {
+        repository,
+        urls,
+        git_ref,
+        after: str_at(payload, "after").or_else(|| str_at(payload, "checkout_sha")),
+    })
+}
+
+/// Find the configured repository a push belongs to, honouring its branch filter
+pub fn match_repository<'a>(config: &'a WebhookConfig, event: &PushEvent) -> Option<&'a WebhookRepository> {
+    let normalize = |s: &str| s.trim_end_matches('/').trim_end_matches(".git").to_lowercase();
+    config.repositories.iter().find(|repo| {
+        let name = normalize(&repo.name);
+        let same_repo = normalize(&event.repository) == name || event.urls.iter().any(|url| normalize(url) == name);
+        let branch_ok = repo.branches.is_empty()
+            || event.branch().is_some_and(|branch| repo.branches.iter().any(|b| b == branch));
+        same_repo && branch_ok
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_verify_signature() {
+        let body = br#"{"ref":"refs/heads/main"}"#;
+        let mut mac = Hmac::<Sha256>::new_from_slice(b"s3cret").unwrap();
+        mac.update(body);
+        let signature = format!("sha256={}", hex::encode(mac.finalize().into_bytes()));
+
+        let mut headers = HeaderMap::new();
+        headers.insert("x-hub-signature-256", signature.parse().unwrap());
+        assert!(verify_signature("s3cret", &headers, body));
+        assert!(!verify_signature("other", &headers, body));
+
+        let mut headers = HeaderMap::new();
+        headers.insert("x-gitlab-token", "s3cret".parse().unwrap());
+        assert!(verify_signature("s3cret", &headers, body));
+        assert!(!verify_signature("s3cret", &HeaderMap::new(), body));
+    }
+
+    #[test]
+    fn test_parse_and_match_push() {
+        let github = json!({
+            "ref": "refs/heads/main",
+            "after": "9f1c",
+            "repository": {"full_name": "Acme/Backend", "clone_url": "https://github.com/Acme/Backend.git"}
+        });
+        let gitlab = json!({
+            "object_kind": "push",
+            "ref": "refs/heads/dev",
+            "checkout_sha": "77aa",
+            "project": {"path_with_namespace": "acme/backend", "git_http_url": "https://gitlab.com/acme/backend.git"}
+        });
+
+        let config = WebhookConfig {
+            secret: Some("s3cret".to_string()),
+            repositories: vec![WebhookRepository {
+                name: "acme/backend".to_string(),
+                project_dir: "/srv/backend".to_string(),
+                branches: vec!["main".to_string()],
+                sync: false,
+                tenant: None,
+                languages: Vec::new(),
+                exclude_paths: Vec::new(),
+            }],
+        };
+
+        let event = parse_push_event(&github).unwrap();
+        assert_eq!(event.branch(), Some("main"));
+        assert_eq!(match_repository(&config, &event).unwrap().project_dir, "/srv/backend");
+
+        let event = parse_push_event(&gitlab).unwrap();
+        assert_eq!(event.after.as_deref(), Some("77aa"));
+        assert!(match_repository(&config, &event).is_none());
+    }
+}
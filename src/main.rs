@@ -1,29 +1,51 @@
 use clap::Parser;
-use codegraph_cli::cli::{Cli, CodeGraphRunner};
+use codegraph_cli::cli::{init_logging, Cli, CodeGraphRunner};
 use codegraph_cli::cli::args::Commands;
-use codegraph_cli::http::CodeGraphServer;
+use codegraph_cli::codegraph::AnalyzerRegistry;
+use codegraph_cli::services::PostAnalysisHooks;
+use codegraph_cli::http::{CodeGraphServer, ServerConfig};
 use codegraph_cli::storage::StorageManager;
 use std::sync::Arc;
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
     let cli = Cli::parse();
+    init_logging(cli.log_format, cli.verbose)?;
+    for path in &cli.analyzers {
+        let manifest = AnalyzerRegistry::register_dynamic(path)?;
+        tracing::info!("Loaded analyzer '{}' from {}", manifest.name, path);
+    }
 
     match &cli.command {
-        Commands::Server { address, storage_mode } => {
+        Commands::Server { address, storage_mode, config, cors_origins, hooks } => {
             let server_addr = address.as_deref().unwrap_or("127.0.0.1:8080");
-            println!("Starting CodeGraph HTTP server on {}", server_addr);
+            tracing::info!("Starting CodeGraph HTTP server on {}", server_addr);
 
             // Determine storage mode
             let storage_mode = storage_mode.as_ref().unwrap_or(&cli.storage_mode).clone();
-            println!("Using storage mode: {:?}", storage_mode);
+            tracing::info!("Using storage mode: {:?}", storage_mode);
+
+            let mut server_config = match config {
+                Some(path) => ServerConfig::load(std::path::Path::new(path))?,
+                None => ServerConfig::default(),
+            };
+            if !cors_origins.is_empty() {
+                server_config.cors.allowed_origins = cors_origins.clone();
+            }
+
+            for command in hooks {
+                PostAnalysisHooks::register_command(command);
+            }
 
             let storage = Arc::new(StorageManager::with_storage_mode(storage_mode));
-            let server = CodeGraphServer::new(storage);
+            let mut server = CodeGraphServer::with_config(storage, server_config);
+            if let Some(path) = config {
+                server = server.with_config_path(path);
+            }
             server.start(server_addr).await?;
         }
-        Commands::Vectorize { .. } => {
-            // 使用CodeGraphRunner处理vectorize命令
+        _ => {
+            // 其余命令交由CodeGraphRunner处理
             CodeGraphRunner::run(cli).await?;
         }
     }
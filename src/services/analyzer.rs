@@ -1,11 +1,12 @@
 use std::collections::{HashMap, HashSet};
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use uuid::Uuid;
 use tracing::info;
 
 use crate::codegraph::graph::CodeGraph;
-use crate::codegraph::types::{FunctionInfo, CodeGraphStats};
+use crate::codegraph::types::{FunctionInfo, CodeGraphStats, FileParseStats};
 use crate::codegraph::parser::CodeParser;
+use crate::codegraph::options::AnalyzeOptions;
 
 /// 代码图分析器，提供高级分析功能
 pub struct CodeAnalyzer {
@@ -21,6 +22,14 @@ impl CodeAnalyzer {
         }
     }
 
+    /// 使用指定的分析选项创建分析器
+    pub fn with_options(options: AnalyzeOptions) -> Self {
+        Self {
+            parser: CodeParser::with_options(options),
+            code_graph: None,
+        }
+    }
+
     /// 分析目录并构建代码图
     pub fn analyze_directory(&mut self, dir: &Path) -> Result<&CodeGraph, String> {
         info!("Starting code graph analysis for directory: {}", dir.display());
@@ -37,6 +46,11 @@ impl CodeAnalyzer {
         self.code_graph.as_ref()
     }
 
+    /// 本次分析中解析过的文件的解析统计
+    pub fn parse_stats(&self) -> &HashMap<PathBuf, FileParseStats> {
+        self.parser.parse_stats()
+    }
+
     /// 获取统计信息
     pub fn get_stats(&self) -> Option<&CodeGraphStats> {
         self.code_graph.as_ref().map(|cg| cg.get_stats())
@@ -291,6 +305,21 @@ impl CodeAnalyzer {
         self.code_graph.as_ref().map(|cg| cg.to_dot())
     }
 
+    /// 导出为Mermaid格式
+    pub fn export_mermaid(&self) -> Option<String> {
+        self.code_graph.as_ref().map(|cg| cg.to_mermaid())
+    }
+
+    /// 导出为D2格式
+    pub fn export_d2(&self) -> Option<String> {
+        self.code_graph.as_ref().map(|cg| cg.to_d2())
+    }
+
+    /// 导出为PlantUML格式
+    pub fn export_plantuml(&self) -> Option<String> {
+        self.code_graph.as_ref().map(|cg| cg.to_plantuml())
+    }
+
     /// 导出为JSON格式
     pub fn export_json(&self) -> Option<Result<String, serde_json::Error>> {
         self.code_graph.as_ref().map(|cg| cg.to_json())
@@ -0,0 +1,289 @@
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::codegraph::types::PetCodeGraph;
+use crate::services::source_scan::for_each_source_line;
+
+/// 并发原语类别
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConcurrencyKind {
+    /// 获取锁
+    Lock,
+    /// 创建线程或任务
+    Spawn,
+    /// 使用通道或队列
+    Channel,
+}
+
+/// 模式目录中的一项：锁模式的第一个捕获组为锁名
+struct PrimitivePattern {
+    kind: ConcurrencyKind,
+    regex: Regex,
+}
+
+/// 按语言组织的并发原语模式目录
+pub struct PatternCatalog {
+    patterns: HashMap<String, Vec<PrimitivePattern>>,
+}
+
+impl PatternCatalog {
+    pub fn new() -> Self {
+        let table: &[(&str, ConcurrencyKind, &str)] = &[
+            ("rust", ConcurrencyKind::Lock, r"([\w.]+)\s*\.\s*(?:lock|read|write|try_lock|try_read|try_write)\(\)"),
+            ("rust", ConcurrencyKind::Spawn, r"\b(?:thread::spawn|tokio::spawn|task::spawn|spawn_blocking|rayon::spawn|thread::scope)\b"),
+            ("rust", ConcurrencyKind::Channel, r"\b(?:mpsc::|crossbeam_channel::|broadcast::channel|oneshot::channel|watch::channel|flume::)"),
+            ("python", ConcurrencyKind::Lock, r"\bwith\s+([\w.]*(?i:lock|mutex|sem)[\w.]*)\s*(?:\(\))?\s*:"),
+            ("python", ConcurrencyKind::Lock, r"([\w.]+)\.acquire\("),
+            ("python", ConcurrencyKind::Spawn, r"\b(?:threading\.Thread|Thread\(|ThreadPoolExecutor|ProcessPoolExecutor|multiprocessing\.Process|asyncio\.create_task|asyncio\.gather|loop\.run_in_executor)"),
+            ("python", ConcurrencyKind::Channel, r"\b(?:queue\.Queue|asyncio\.Queue|multiprocessing\.Queue|Queue\(\))"),
+            ("java", ConcurrencyKind::Lock, r"\bsynchronized\s*\(\s*([\w.]+)\s*\)"),
+            ("java", ConcurrencyKind::Lock, r"([\w.]+)\.(?:lock|lockInterruptibly|tryLock)\(\)"),
+            ("java", ConcurrencyKind::Spawn, r"\b(?:new\s+Thread\(|Executors\.|\.submit\(|CompletableFuture\.(?:runAsync|supplyAsync)|\.execute\()"),
+            ("java", ConcurrencyKind::Channel, r"\b(?:BlockingQueue|LinkedBlockingQueue|ArrayBlockingQueue|SynchronousQueue|Exchanger)\b"),
+            ("go", ConcurrencyKind::Lock, r"([\w.]+)\.(?:Lock|RLock)\(\)"),
+            ("go", ConcurrencyKind::Spawn, r"\bgo\s+(?:func\b|[\w.]+\()"),
+            ("go", ConcurrencyKind::Channel, r"\bmake\(\s*chan\b|<-\s*[\w.]+|[\w.]+\s*<-"),
+            ("cpp", ConcurrencyKind::Lock, r"\b(?:lock_guard|unique_lock|scoped_lock|shared_lock)\s*(?:<[^>]*>)?\s*\w+\s*[({]\s*([\w.>-]+)"),
+            ("cpp", ConcurrencyKind::Lock, r"([\w.>-]+?)(?:\.|->)lock\(\)"),
+            ("cpp", ConcurrencyKind::Spawn, r"\b(?:std::thread|std::jthread|std::async|pthread_create)\b"),
+            ("javascript", ConcurrencyKind::Spawn, r"\bnew\s+Worker\("),
+            ("javascript", ConcurrencyKind::Channel, r"\bnew\s+MessageChannel\(|\.postMessage\("),
+            ("typescript", ConcurrencyKind::Spawn, r"\bnew\s+Worker\("),
+            ("typescript", ConcurrencyKind::Channel, r"\bnew\s+MessageChannel\(|\.postMessage\("),
+        ];
+
+        let mut patterns: HashMap<String, Vec<PrimitivePattern>> = HashMap::new();
+        for (language, kind, pattern) in table {
+            patterns.entry(language.to_string()).or_default().push(PrimitivePattern {
+                kind: *kind,
+                regex: Regex::new(pattern).expect("valid concurrency pattern"),
+            });
+        }
+        Self { patterns }
+    }
+
+    /// 匹配一行代码，返回 (类别, 锁名)
+    fn match_line(&self, language: &str, line: &str) -> Vec<(ConcurrencyKind, Option<String>)> {
+        let Some(patterns) = self.patterns.get(language) else {
+            return Vec::new();
+        };
+        let mut matches = Vec::new();
+        for pattern in patterns {
+            for captures in pattern.regex.captures_iter(line) {
+                let lock = match pattern.kind {
+                    ConcurrencyKind::Lock => captures.get(1).map(|m| normalize_lock_name(m.as_str())),
+                    _ => None,
+                };
+                matches.push((pattern.kind, lock));
+            }
+        }
+        matches
+    }
+}
+
+impl Default for PatternCatalog {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// 去掉 `self.` / `this.` 等接收者前缀，使不同函数中的同一把锁名称一致
+fn normalize_lock_name(name: &str) -> String {
+    let name = name.trim_start_matches('&').trim_start_matches('*');
+    for prefix in ["self.", "this.", "this->", "self->"] {
+        if let Some(stripped) = name.strip_prefix(prefix) {
+            return stripped.to_string();
+        }
+    }
+    name.to_string()
+}
+
+/// 函数中的一次并发原语使用
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PrimitiveUsage {
+    pub kind: ConcurrencyKind,
+    pub lock: Option<String>,
+    pub line: usize,
+    pub source: String,
+}
+
+/// 单个函数的并发标签
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FunctionConcurrency {
+    pub function_id: Uuid,
+    pub function_name: String,
+    pub file_path: String,
+    pub kinds: BTreeSet<ConcurrencyKind>,
+    pub usages: Vec<PrimitiveUsage>,
+}
+
+/// 锁顺序的一个证据：在 `function` 中持有 `first` 后（直接或经由调用）获取 `second`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LockOrderWitness {
+    pub first: String,
+    pub second: String,
+    pub function_name: String,
+    pub file_path: String,
+    pub line: usize,
+    /// 从该函数经直接被调用者到实际获取 `second` 的函数（更深的中间层省略）
+    pub path: Vec<String>,
+}
+
+/// 两把锁以相反顺序获取的潜在死锁
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LockOrderInversion {
+    pub locks: (String, String),
+    pub forward: LockOrderWitness,
+    pub backward: LockOrderWitness,
+}
+
+/// 并发原语使用报告
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ConcurrencyReport {
+    pub functions: Vec<FunctionConcurrency>,
+}
+
+impl ConcurrencyReport {
+    /// 扫描图中所有函数的源码，按语言模式目录打标签
+    pub fn build(graph: &PetCodeGraph, catalog: &PatternCatalog) -> Self {
+        let mut by_function: BTreeMap<Uuid, FunctionConcurrency> = BTreeMap::new();
+
+        for_each_source_line(graph, |_, line_number, line, function| {
+            let Some(function) = function else {
+                return;
+            };
+            for (kind, lock) in catalog.match_line(&function.language, line) {
+                let entry = by_function.entry(function.id).or_insert_with(|| FunctionConcurrency {
+                    function_id: function.id,
+                    function_name: function.name.clone(),
+                    file_path: function.file_path.display().to_string(),
+                    kinds: BTreeSet::new(),
+                    usages: Vec::new(),
+                });
+                entry.kinds.insert(kind);
+                entry.usages.push(PrimitiveUsage {
+                    kind,
+                    lock,
+                    line: line_number,
+                    source: line.trim().to_string(),
+                });
+            }
+        });
+
+        Self { functions: by_function.into_values().collect() }
+    }
+
+    fn direct_locks(&self) -> HashMap<Uuid, Vec<(String, usize)>> {
+        self.functions
+            .iter()
+            .map(|f| {
+                let locks = f
+                    .usages
+                    .iter()
+                    .filter_map(|u| u.lock.clone().map(|lock| (lock, u.line)))
+                    .collect();
+                (f.function_id, locks)
+            })
+            .collect()
+    }
+
+    /// 查找锁顺序反转：在某处先A后B、在另一处先B后A获取
+    pub fn find_lock_order_inversions(&self, graph: &PetCodeGraph, max_depth: usize) -> Vec<LockOrderInversion> {
+        let direct = self.direct_locks();
+
+        // 每个函数在 max_depth 内（含自身）可能获取的锁，以及到达获取点的路径
+        let reachable_locks = |function_id: &Uuid| -> Vec<(String, Vec<String>)> {
+            let mut result = Vec::new();
+            if let Some(function) = graph.get_function_by_id(function_id) {
+                for (lock, _) in direct.get(function_id).into_iter().flatten() {
+                    result.push((lock.clone(), vec![function.name.clone()]));
+                }
+            }
+            for (callee, _) in graph.get_callees_within(function_id, max_depth) {
+                for (lock, _) in direct.get(&callee.id).into_iter().flatten() {
+                    result.push((lock.clone(), vec![callee.name.clone()]));
+                }
+            }
+            result
+        };
+
+        let mut orders: BTreeMap<(String, String), LockOrderWitness> = BTreeMap::new();
+        for (function_id, locks) in &direct {
+            let Some(function) = graph.get_function_by_id(function_id) else {
+                continue;
+            };
+            for (first, first_line) in locks {
+                let mut record = |second: &str, line: usize, path: Vec<String>| {
+                    if second != first {
+                        orders.entry((first.clone(), second.to_string())).or_insert_with(|| LockOrderWitness {
+                            first: first.clone(),
+                            second: second.to_string(),
+                            function_name: function.name.clone(),
+                            file_path: function.file_path.display().to_string(),
+                            line,
+                            path,
+                        });
+                    }
+                };
+
+                for (second, second_line) in locks {
+                    if second_line > first_line {
+                        record(second, *second_line, vec![function.name.clone()]);
+                    }
+                }
+                for (callee, relation) in graph.get_callees(function_id) {
+                    if relation.line_number <= *first_line {
+                        continue;
+                    }
+                    for (second, tail) in reachable_locks(&callee.id) {
+                        let mut path = vec![function.name.clone(), callee.name.clone()];
+                        if tail.last() != path.last() {
+                            path.extend(tail);
+                        }
+                        record(&second, relation.line_number, path);
+                    }
+                }
+            }
+        }
+
+        let mut inversions = Vec::new();
+        for ((first, second), forward) in &orders {
+            if first >= second {
+                continue;
+            }
+            if let Some(backward) = orders.get(&(second.clone(), first.clone())) {
+                inversions.push(LockOrderInversion {
+                    locks: (first.clone(), second.clone()),
+                    forward: forward.clone(),
+                    backward: backward.clone(),
+                });
+            }
+        }
+        inversions
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pattern_catalog() {
+        let catalog = PatternCatalog::new();
+
+        let matches = catalog.match_line("rust", "let guard = self.accounts.lock().unwrap();");
+        assert_eq!(matches, vec![(ConcurrencyKind::Lock, Some("accounts".to_string()))]);
+
+        let matches = catalog.match_line("go", "go worker(jobs)");
+        assert_eq!(matches[0].0, ConcurrencyKind::Spawn);
+
+        let matches = catalog.match_line("java", "synchronized (this.ledger) {");
+        assert_eq!(matches, vec![(ConcurrencyKind::Lock, Some("ledger".to_string()))]);
+
+        assert!(catalog.match_line("python", "x = compute()").is_empty());
+    }
+}
@@ -0,0 +1,235 @@
+use std::path::Path;
+
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+/// 粗略估算文本的 token 数（约4个字符一个 token）
+pub fn estimate_tokens(text: &str) -> usize {
+    text.chars().count().div_ceil(4)
+}
+
+/// 参与预算分配的一个文件骨架
+#[derive(Debug, Clone, Copy)]
+pub struct ContextFile<'a> {
+    pub filepath: &'a str,
+    pub language: &'a str,
+    pub skeleton_text: &'a str,
+}
+
+/// 单个文件的预算使用情况
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FileBudget {
+    pub filepath: String,
+    pub tokens: usize,
+    pub included_symbols: usize,
+    pub total_symbols: usize,
+}
+
+/// 按 token 预算组装上下文后的分配明细
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ContextBudget {
+    pub max_tokens: usize,
+    pub used_tokens: usize,
+    /// 保留下来的文件（可能被截断）
+    pub files: Vec<FileBudget>,
+    /// 整个被丢弃的文件
+    pub omitted_files: Vec<String>,
+    /// 被丢弃的符号总数
+    pub omitted_symbols: usize,
+}
+
+/// 组装结果：`texts[i]` 为第 i 个输入文件保留的骨架，`None` 表示整个文件被丢弃
+#[derive(Debug, Clone)]
+pub struct ContextAssembly {
+    pub texts: Vec<Option<String>>,
+    pub budget: ContextBudget,
+}
+
+/// 符号优先级：入口点 > 导出API > 内部实现
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum SymbolPriority {
+    Internal,
+    Exported,
+    EntryPoint,
+}
+
+/// 常见的入口文件名（不含扩展名）
+const ENTRY_FILE_STEMS: &[&str] = &["main", "lib", "index", "__init__", "__main__", "mod", "app", "server"];
+
+struct Block {
+    file: usize,
+    order: usize,
+    priority: SymbolPriority,
+    entry_file: bool,
+    tokens: usize,
+}
+
+/// 按优先级在 `max_tokens` 内挑选符号，低优先级的符号和文件最先被截掉
+pub fn assemble_context(files: &[ContextFile], max_tokens: usize) -> ContextAssembly {
+    let classifier = SymbolClassifier::new();
+    let split: Vec<Vec<String>> = files.iter().map(|f| split_symbols(f.skeleton_text)).collect();
+
+    let mut blocks = Vec::new();
+    for (file_index, (file, symbols)) in files.iter().zip(&split).enumerate() {
+        let entry_file = Path::new(file.filepath)
+            .file_stem()
+            .is_some_and(|stem| ENTRY_FILE_STEMS.contains(&stem.to_string_lossy().as_ref()));
+        for (order, symbol) in symbols.iter().enumerate() {
+            blocks.push(Block {
+                file: file_index,
+                order,
+                priority: classifier.classify(file.language, symbol),
+                entry_file,
+                tokens: estimate_tokens(symbol),
+            });
+        }
+    }
+    // 稳定排序：同优先级时保持请求中的文件顺序和文件内的符号顺序
+    blocks.sort_by(|a, b| b.priority.cmp(&a.priority).then(b.entry_file.cmp(&a.entry_file)));
+
+    let mut selected: Vec<Vec<usize>> = vec![Vec::new(); files.len()];
+    let mut remaining = max_tokens;
+    for block in &blocks {
+        if block.tokens <= remaining {
+            remaining -= block.tokens;
+            selected[block.file].push(block.order);
+        }
+    }
+
+    let mut texts = Vec::with_capacity(files.len());
+    let mut budget = ContextBudget {
+        max_tokens,
+        used_tokens: 0,
+        files: Vec::new(),
+        omitted_files: Vec::new(),
+        omitted_symbols: 0,
+    };
+    for ((file, symbols), mut keep) in files.iter().zip(&split).zip(selected) {
+        budget.omitted_symbols += symbols.len() - keep.len();
+        if keep.is_empty() && !symbols.is_empty() {
+            budget.omitted_files.push(file.filepath.to_string());
+            texts.push(None);
+            continue;
+        }
+        keep.sort_unstable();
+        let text = keep.iter().map(|&i| symbols[i].as_str()).collect::<Vec<_>>().join("\n\n");
+        let tokens = keep.iter().map(|&i| estimate_tokens(&symbols[i])).sum();
+        budget.used_tokens += tokens;
+        budget.files.push(FileBudget {
+            filepath: file.filepath.to_string(),
+            tokens,
+            included_symbols: keep.len(),
+            total_symbols: symbols.len(),
+        });
+        texts.push(Some(text));
+    }
+
+    ContextAssembly { texts, budget }
+}
+
+/// 骨架中的顶层符号以空行分隔；缩进开头的片段属于上一个符号（如类成员）
+fn split_symbols(skeleton: &str) -> Vec<String> {
+    let mut symbols: Vec<String> = Vec::new();
+    for chunk in skeleton.split("\n\n").filter(|c| !c.trim().is_empty()) {
+        match symbols.last_mut() {
+            Some(last) if chunk.starts_with([' ', '\t']) => {
+                last.push_str("\n\n");
+                last.push_str(chunk);
+            }
+            _ => symbols.push(chunk.to_string()),
+        }
+    }
+    symbols
+}
+
+pub(crate) struct SymbolClassifier {
+    entry_point: Regex,
+    declared_name: Regex,
+}
+
+impl SymbolClassifier {
+    pub(crate) fn new() -> Self {
+        Self {
+            entry_point: Regex::new(
+                r"^(?:pub\s+)?(?:export\s+)?(?:async\s+)?(?:fn|def|func|function)\s+main\b|\bstatic\s+void\s+main\s*\(|^int\s+main\s*\(",
+            )
+            .expect("valid entry point pattern"),
+            declared_name: Regex::new(
+                r"^(?:async\s+)?(?:def|class|func|type)\s+(?:\([^)]*\)\s*)?([A-Za-z_]\w*)",
+            )
+            .expect("valid declaration pattern"),
+        }
+    }
+
+    fn classify(&self, language: &str, symbol: &str) -> SymbolPriority {
+        // 跳过属性、装饰器和注释，取声明所在行
+        let header = symbol
+            .lines()
+            .map(str::trim)
+            .find(|line| {
+                !line.is_empty()
+                    && !line.starts_with("#[")
+                    && !line.starts_with('@')
+                    && !line.starts_with("//")
+                    && !line.starts_with("/*")
+                    && !line.starts_with('*')
+            })
+            .unwrap_or("");
+
+        if self.entry_point.is_match(header) {
+            return SymbolPriority::EntryPoint;
+        }
+
+        let exported = match language {
+            "rust" => header.starts_with("pub"),
+            "javascript" | "typescript" => header.starts_with("export"),
+            "java" => header.split_whitespace().any(|w| w == "public"),
+            "python" => self
+                .declared_name
+                .captures(header)
+                .is_some_and(|c| !c[1].starts_with('_')),
+            "go" => self
+                .declared_name
+                .captures(header)
+                .is_some_and(|c| c[1].starts_with(|ch: char| ch.is_ascii_uppercase())),
+            // 其他语言没有可靠的可见性标记，按导出处理
+            _ => true,
+        };
+        if exported {
+            SymbolPriority::Exported
+        } else {
+            SymbolPriority::Internal
+        }
+    }
+
+    /// 符号是否为入口点或导出API
+    pub(crate) fn is_public(&self, language: &str, symbol: &str) -> bool {
+        self.classify(language, symbol) > SymbolPriority::Internal
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_budget_drops_internal_symbols_first() {
+        let main_rs = "fn main() {\n    ...\n}\n\nfn parse_args() -> Args {\n    ...\n}";
+        let util_rs = "pub fn render(page: &Page) -> String {\n    ...\n}\n\nfn escape(s: &str) -> String {\n    ...\n}";
+        let files = [
+            ContextFile { filepath: "src/util.rs", language: "rust", skeleton_text: util_rs },
+            ContextFile { filepath: "src/main.rs", language: "rust", skeleton_text: main_rs },
+        ];
+        let budget = estimate_tokens("fn main() {\n    ...\n}") + estimate_tokens("pub fn render(page: &Page) -> String {\n    ...\n}");
+
+        let assembly = assemble_context(&files, budget);
+        assert_eq!(assembly.texts[0].as_deref(), Some("pub fn render(page: &Page) -> String {\n    ...\n}"));
+        assert_eq!(assembly.texts[1].as_deref(), Some("fn main() {\n    ...\n}"));
+        assert_eq!(assembly.budget.omitted_symbols, 2);
+        assert!(assembly.budget.used_tokens <= budget);
+
+        let assembly = assemble_context(&files, estimate_tokens("fn main() {\n    ...\n}"));
+        assert!(assembly.texts[0].is_none());
+        assert_eq!(assembly.budget.omitted_files, vec!["src/util.rs"]);
+    }
+}
@@ -0,0 +1,395 @@
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::codegraph::types::{FunctionInfo, PetCodeGraph};
+use crate::services::graph_export::node_metrics;
+use crate::storage::annotations::path_qualified_name;
+use crate::storage::MetricMap;
+
+/// 写入注解的指标名：函数是否被覆盖（1或0）
+pub const COVERED_METRIC: &str = "covered";
+/// 写入注解的指标名：函数内被执行的可执行行占比（0-100）
+pub const COVERAGE_PERCENT_METRIC: &str = "coverage_pct";
+
+/// 覆盖率报告格式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CoverageFormat {
+    /// lcov tracefile（`lcov.info`）
+    Lcov,
+    /// Cobertura XML（`coverage.xml`）
+    Cobertura,
+}
+
+impl CoverageFormat {
+    pub fn parse(name: &str) -> Result<Self, String> {
+        match name {
+            "lcov" => Ok(Self::Lcov),
+            "cobertura" | "xml" => Ok(Self::Cobertura),
+            other => Err(format!("Unknown coverage format '{}', expected lcov or cobertura", other)),
+        }
+    }
+
+    /// 以 `<` 开头的视为Cobertura XML，否则为lcov
+    pub fn detect(content: &str) -> Self {
+        if content.trim_start().starts_with('<') {
+            Self::Cobertura
+        } else {
+            Self::Lcov
+        }
+    }
+}
+
+/// 单个源文件的覆盖数据
+#[derive(Debug, Clone, Default)]
+struct FileCoverage {
+    /// 行号 -> 执行次数
+    lines: BTreeMap<usize, u64>,
+    /// 函数起始行 -> 执行次数（仅lcov提供）
+    functions: BTreeMap<usize, u64>,
+}
+
+/// 解析后的覆盖率报告，路径保持报告中的原样（可能是相对路径）
+#[derive(Debug, Clone)]
+pub struct CoverageData {
+    pub format: CoverageFormat,
+    files: HashMap<PathBuf, FileCoverage>,
+}
+
+impl CoverageData {
+    pub fn parse(content: &str, format: Option<CoverageFormat>) -> Result<Self, String> {
+        let format = format.unwrap_or_else(|| CoverageFormat::detect(content));
+        let files = match format {
+            CoverageFormat::Lcov => parse_lcov(content)?,
+            CoverageFormat::Cobertura => parse_cobertura(content)?,
+        };
+        if files.is_empty() {
+            return Err(format!("No source files found in {:?} coverage report", format));
+        }
+        Ok(Self { format, files })
+    }
+
+    /// 报告中与图中文件对应的条目：完整路径相同，或图中路径以报告路径结尾（取最长的）
+    fn file_for(&self, file_path: &Path) -> Option<&FileCoverage> {
+        self.files
+            .iter()
+            .filter(|(path, _)| file_path.ends_with(path))
+            .max_by_key(|(path, _)| path.components().count())
+            .map(|(_, coverage)| coverage)
+    }
+}
+
+fn parse_lcov(content: &str) -> Result<HashMap<PathBuf, FileCoverage>, String> {
+    let mut files: HashMap<PathBuf, FileCoverage> = HashMap::new();
+    let mut current: Option<(PathBuf, FileCoverage)> = None;
+    // 函数名 -> 起始行，FNDA 通过函数名引用 FN
+    let mut function_lines: HashMap<String, usize> = HashMap::new();
+
+    for (index, line) in content.lines().enumerate() {
+        let line = line.trim();
+        let invalid = || format!("Invalid lcov record on line {}: {}", index + 1, line);
+        if let Some(path) = line.strip_prefix("SF:") {
+            current = Some((PathBuf::from(path), FileCoverage::default()));
+            function_lines.clear();
+        } else if line == "end_of_record" {
+            if let Some((path, coverage)) = current.take() {
+                let entry = files.entry(path).or_default();
+                entry.lines.extend(coverage.lines);
+                entry.functions.extend(coverage.functions);
+            }
+        } else if let Some((_, coverage)) = current.as_mut() {
+            if let Some(record) = line.strip_prefix("DA:") {
+                let mut parts = record.split(',');
+                let number = parts.next().and_then(|n| n.parse().ok()).ok_or_else(invalid)?;
+                let hits = parts.next().and_then(|n| n.parse().ok()).ok_or_else(invalid)?;
+                coverage.lines.insert(number, hits);
+            } else if let Some(record) = line.strip_prefix("FN:") {
+                // `FN:<起始行>,<函数名>`，新版lcov为 `FN:<起始行>,<结束行>,<函数名>`
+                let (start, name) = record.split_once(',').ok_or_else(invalid)?;
+                let name = match name.split_once(',') {
+                    Some((end, rest)) if end.parse::<usize>().is_ok() => rest,
+                    _ => name,
+                };
+                function_lines.insert(name.to_string(), start.parse().map_err(|_| invalid())?);
+            } else if let Some(record) = line.strip_prefix("FNDA:") {
+                let (hits, name) = record.split_once(',').ok_or_else(invalid)?;
+                if let Some(start) = function_lines.get(name) {
+                    *coverage.functions.entry(*start).or_insert(0) += hits.parse::<u64>().map_err(|_| invalid())?;
+                }
+            }
+        }
+    }
+    Ok(files)
+}
+
+fn parse_cobertura(content: &str) -> Result<HashMap<PathBuf, FileCoverage>, String> {
+    let tag = Regex::new(r"<(class|line)\b([^>]*)>").expect("valid regex");
+    let attribute = Regex::new(r#"([\w-]+)\s*=\s*"([^"]*)""#).expect("valid regex");
+
+    let mut files: HashMap<PathBuf, FileCoverage> = HashMap::new();
+    let mut current: Option<PathBuf> = None;
+    for captures in tag.captures_iter(content) {
+        let attributes: HashMap<&str, &str> = attribute
+            .captures_iter(&captures[2])
+            .map(|a| (a.get(1).unwrap().as_str(), a.get(2).unwrap().as_str()))
+            .collect();
+        match &captures[1] {
+            "class" => current = attributes.get("filename").map(|f| PathBuf::from(unescape_xml(f))),
+            _ => {
+                let (Some(path), Some(number), Some(hits)) = (&current, attributes.get("number"), attributes.get("hits")) else {
+                    continue;
+                };
+                let number: usize = number.parse().map_err(|_| format!("Invalid line number '{}'", number))?;
+                let hits: u64 = hits.parse().map_err(|_| format!("Invalid hit count '{}'", hits))?;
+                // 方法下的行与类下的行重复出现，取较大值
+                let entry = files.entry(path.clone()).or_default().lines.entry(number).or_insert(0);
+                *entry = (*entry).max(hits);
+            }
+        }
+    }
+    Ok(files)
+}
+
+fn unescape_xml(value: &str) -> String {
+    value
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&amp;", "&")
+}
+
+/// 函数的覆盖状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CoverageStatus {
+    Covered,
+    Uncovered,
+    /// 报告中没有该函数所在文件或行的数据
+    Unknown,
+}
+
+/// 单个函数的覆盖情况
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FunctionCoverage {
+    pub function_id: Uuid,
+    pub function_name: String,
+    pub file_path: String,
+    pub line_start: usize,
+    pub line_end: usize,
+    pub status: CoverageStatus,
+    /// 函数范围内被执行的可执行行数
+    pub covered_lines: usize,
+    /// 函数范围内的可执行行数
+    pub total_lines: usize,
+    /// 不同调用方的数量
+    pub fan_in: usize,
+}
+
+impl FunctionCoverage {
+    pub fn percent(&self) -> f64 {
+        if self.total_lines == 0 {
+            if self.status == CoverageStatus::Covered { 100.0 } else { 0.0 }
+        } else {
+            (self.covered_lines as f64 * 10000.0 / self.total_lines as f64).round() / 100.0
+        }
+    }
+}
+
+/// 覆盖率与调用图结合后的报告
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CoverageReport {
+    pub covered: usize,
+    pub uncovered: usize,
+    pub unknown: usize,
+    /// 所有函数的覆盖情况
+    pub functions: Vec<FunctionCoverage>,
+    /// 调用方至少 `min_fan_in` 个却完全未被覆盖的函数，按调用方数量降序
+    pub test_gaps: Vec<FunctionCoverage>,
+}
+
+impl CoverageReport {
+    /// 转换为注解：`文件路径::函数名` -> 是否覆盖及覆盖百分比；状态未知的函数不写入
+    pub fn to_annotations(&self, root: &Path, graph: &PetCodeGraph) -> BTreeMap<String, MetricMap> {
+        let mut annotations = BTreeMap::new();
+        for coverage in self.functions.iter().filter(|f| f.status != CoverageStatus::Unknown) {
+            let Some(function) = graph.get_function_by_id(&coverage.function_id) else {
+                continue;
+            };
+            let metrics: &mut MetricMap = annotations.entry(path_qualified_name(function, root)).or_default();
+            let covered = if coverage.status == CoverageStatus::Covered { 1.0 } else { 0.0 };
+            metrics.insert(COVERED_METRIC.to_string(), covered);
+            metrics.insert(COVERAGE_PERCENT_METRIC.to_string(), coverage.percent());
+        }
+        annotations
+    }
+
+    pub fn to_text(&self, location: impl Fn(&Path, usize) -> String) -> String {
+        let mut out = String::new();
+        for gap in &self.test_gaps {
+            out.push_str(&format!(
+                "{}: {} has {} caller(s) and no coverage\n",
+                location(Path::new(&gap.file_path), gap.line_start),
+                gap.function_name,
+                gap.fan_in
+            ));
+        }
+        out.push_str(&format!(
+            "\n{} covered, {} uncovered, {} without data; {} test gap(s)\n",
+            self.covered,
+            self.uncovered,
+            self.unknown,
+            self.test_gaps.len()
+        ));
+        out
+    }
+}
+
+/// 按覆盖数据标记图中每个函数：lcov的函数记录优先，否则看函数行范围内的可执行行
+pub fn analyze_coverage(graph: &PetCodeGraph, data: &CoverageData, min_fan_in: usize) -> CoverageReport {
+    let metrics = node_metrics(graph);
+    let mut functions: Vec<FunctionCoverage> = graph
+        .get_all_functions()
+        .into_iter()
+        .filter(|f| !f.is_unresolved())
+        .map(|function| {
+            let fan_in = metrics.get(&function.id).map(|m| m.fan_in).unwrap_or(0);
+            function_coverage(function, data.file_for(&function.file_path), fan_in)
+        })
+        .collect();
+    functions.sort_by(|a, b| (&a.file_path, a.line_start).cmp(&(&b.file_path, b.line_start)));
+
+    let count = |status| functions.iter().filter(|f| f.status == status).count();
+    let mut test_gaps: Vec<FunctionCoverage> = functions
+        .iter()
+        .filter(|f| f.status == CoverageStatus::Uncovered && f.fan_in >= min_fan_in)
+        .cloned()
+        .collect();
+    test_gaps.sort_by(|a, b| b.fan_in.cmp(&a.fan_in).then_with(|| a.function_name.cmp(&b.function_name)));
+
+    CoverageReport {
+        covered: count(CoverageStatus::Covered),
+        uncovered: count(CoverageStatus::Uncovered),
+        unknown: count(CoverageStatus::Unknown),
+        functions,
+        test_gaps,
+    }
+}
+
+fn function_coverage(function: &FunctionInfo, file: Option<&FileCoverage>, fan_in: usize) -> FunctionCoverage {
+    let range = function.line_start..=function.line_end;
+    let (covered_lines, total_lines, function_hits) = match file {
+        Some(file) => {
+            let lines: Vec<u64> = file.lines.range(range.clone()).map(|(_, hits)| *hits).collect();
+            // 嵌套函数也落在范围内，取离起始行最近的函数记录
+            let function_hits = file.functions.range(range).next().map(|(_, hits)| *hits);
+            (lines.iter().filter(|hits| **hits > 0).count(), lines.len(), function_hits)
+        }
+        None => (0, 0, None),
+    };
+    let status = match function_hits {
+        Some(hits) if hits > 0 => CoverageStatus::Covered,
+        Some(_) => CoverageStatus::Uncovered,
+        None if total_lines == 0 => CoverageStatus::Unknown,
+        None if covered_lines > 0 => CoverageStatus::Covered,
+        None => CoverageStatus::Uncovered,
+    };
+    FunctionCoverage {
+        function_id: function.id,
+        function_name: function.name.clone(),
+        file_path: function.file_path.display().to_string(),
+        line_start: function.line_start,
+        line_end: function.line_end,
+        status,
+        covered_lines,
+        total_lines,
+        fan_in,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::codegraph::types::CallRelation;
+
+    fn function(name: &str, file: &str, lines: (usize, usize)) -> FunctionInfo {
+        FunctionInfo {
+            id: Uuid::new_v4(),
+            name: name.to_string(),
+            file_path: PathBuf::from(file),
+            line_start: lines.0,
+            line_end: lines.1,
+            namespace: "crate".to_string(),
+            language: "rust".to_string(),
+            ..Default::default()
+        }
+    }
+
+    fn graph() -> PetCodeGraph {
+        let mut graph = PetCodeGraph::new();
+        let parse = function("parse", "/repo/src/config.rs", (1, 5));
+        let load = function("load", "/repo/src/config.rs", (7, 12));
+        let callers: Vec<FunctionInfo> =
+            (0..3).map(|i| function(&format!("caller{}", i), "/repo/src/main.rs", (i * 3 + 1, i * 3 + 2))).collect();
+        let relations: Vec<CallRelation> = callers
+            .iter()
+            .flat_map(|caller| [&parse, &load].map(|callee| CallRelation {
+                line_number: caller.line_start,
+                ..CallRelation::between(caller, callee)
+            }))
+            .collect();
+        for f in callers.into_iter().chain([parse, load]) {
+            graph.add_function(f);
+        }
+        for r in relations {
+            graph.add_call_relation(r).unwrap();
+        }
+        graph
+    }
+
+    fn status_of(report: &CoverageReport, name: &str) -> CoverageStatus {
+        report.functions.iter().find(|f| f.function_name == name).unwrap().status
+    }
+
+    #[test]
+    fn test_lcov_test_gaps() {
+        let lcov = "TN:\nSF:src/config.rs\nFN:1,parse\nFN:7,12,load\nFNDA:4,parse\nFNDA:0,load\nDA:2,4\nDA:8,0\nDA:9,0\nend_of_record\n";
+        let data = CoverageData::parse(lcov, None).unwrap();
+        assert_eq!(data.format, CoverageFormat::Lcov);
+        let report = analyze_coverage(&graph(), &data, 3);
+        assert_eq!(status_of(&report, "parse"), CoverageStatus::Covered);
+        assert_eq!(status_of(&report, "load"), CoverageStatus::Uncovered);
+        assert_eq!(status_of(&report, "caller0"), CoverageStatus::Unknown);
+        assert_eq!(report.test_gaps.len(), 1);
+        assert_eq!(report.test_gaps[0].function_name, "load");
+        assert_eq!(report.test_gaps[0].fan_in, 3);
+        assert!(analyze_coverage(&graph(), &data, 4).test_gaps.is_empty());
+    }
+
+    #[test]
+    fn test_cobertura_line_coverage() {
+        let xml = r#"<?xml version="1.0" ?>
+<coverage line-rate="0.5"><packages><package name="src"><classes>
+  <class name="config" filename="src/config.rs" line-rate="0.5">
+    <methods><method name="parse"><lines><line number="2" hits="3"/></lines></method></methods>
+    <lines><line number="2" hits="3"/><line number="3" hits="0"/><line hits="0" number="8"/></lines>
+  </class>
+</classes></package></packages></coverage>"#;
+        let data = CoverageData::parse(xml, None).unwrap();
+        assert_eq!(data.format, CoverageFormat::Cobertura);
+        let graph = graph();
+        let report = analyze_coverage(&graph, &data, 1);
+        let parse = report.functions.iter().find(|f| f.function_name == "parse").unwrap();
+        assert_eq!((parse.status, parse.covered_lines, parse.total_lines), (CoverageStatus::Covered, 1, 2));
+        assert_eq!(status_of(&report, "load"), CoverageStatus::Uncovered);
+
+        let annotations = report.to_annotations(Path::new("/repo"), &graph);
+        assert_eq!(annotations["src/config.rs::parse"][COVERAGE_PERCENT_METRIC], 50.0);
+        assert_eq!(annotations["src/config.rs::load"][COVERED_METRIC], 0.0);
+        assert!(!annotations.contains_key("src/main.rs::caller0"));
+    }
+}
@@ -0,0 +1,416 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::codegraph::treesitter::structs::SymbolType;
+use crate::codegraph::treesitter::TreeSitterParser;
+use crate::codegraph::types::{CallRelation, FunctionInfo, PetCodeGraph};
+use crate::codegraph::NotebookCells;
+
+/// 单行上下文的最大字符数
+const MAX_CONTEXT_CHARS: usize = 160;
+
+/// 函数说明的大小限制
+#[derive(Debug, Clone)]
+pub struct ExplainOptions {
+    /// 代码片段最多行数
+    pub max_snippet_lines: usize,
+    /// 调用者、被调用者各自最多条数
+    pub max_relations: usize,
+}
+
+impl Default for ExplainOptions {
+    fn default() -> Self {
+        Self { max_snippet_lines: 80, max_relations: 20 }
+    }
+}
+
+/// 函数所属的类
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EnclosingClass {
+    pub name: String,
+    pub line_start: usize,
+    pub line_end: usize,
+    /// 类声明的第一行
+    pub declaration: Option<String>,
+    /// 图中位于该类范围内的其他函数
+    pub members: Vec<String>,
+}
+
+/// 一条直接调用关系及其单行上下文
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CallContext {
+    pub function_id: Uuid,
+    pub function_name: String,
+    pub file_path: String,
+    pub line_start: usize,
+    /// 调用所在行（位于调用者中）
+    pub call_line: usize,
+    pub resolved: bool,
+    /// 调用者为调用所在行，被调用者为其声明行（未解析时为调用所在行）
+    pub context: Option<String>,
+}
+
+/// 单个函数的说明：代码、文档注释、所属类和直接调用关系
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FunctionExplanation {
+    pub function_id: Uuid,
+    pub name: String,
+    pub file_path: String,
+    pub language: String,
+    pub namespace: String,
+    pub line_start: usize,
+    pub line_end: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cell: Option<usize>,
+    pub signature: Option<String>,
+    pub doc_comment: Option<String>,
+    pub snippet: Option<String>,
+    pub snippet_truncated: bool,
+    pub class: Option<EnclosingClass>,
+    pub callers: Vec<CallContext>,
+    pub callees: Vec<CallContext>,
+    pub total_callers: usize,
+    pub total_callees: usize,
+}
+
+/// 汇总函数的说明；源文件不可读时代码、注释和类为空
+pub fn explain_function(graph: &PetCodeGraph, function: &FunctionInfo, options: &ExplainOptions) -> FunctionExplanation {
+    let mut sources = SourceCache::default();
+    let lines = sources.lines(function);
+    let (snippet, snippet_truncated) = match lines {
+        Some(lines) => {
+            let start = function.line_start.saturating_sub(1).min(lines.len());
+            let end = function.line_end.clamp(start, lines.len());
+            let take = (end - start).min(options.max_snippet_lines);
+            (Some(lines[start..start + take].join("\n")), take < end - start)
+        }
+        None => (None, false),
+    };
+    let doc_comment = lines.and_then(|lines| doc_comment(lines, function));
+    let class = enclosing_class(graph, function);
+
+    // 经门面模块再导出的函数列出门面的使用者
+    let callers = graph.get_users(&function.id);
+    let callees = graph.get_callees(&function.id);
+    let total_callers = callers.len();
+    let total_callees = callees.len();
+    let mut callers: Vec<CallContext> = callers
+        .into_iter()
+        .take(options.max_relations)
+        .map(|(caller, relation)| {
+            let context = sources.line(caller, relation.line_number);
+            call_context(caller, relation, context)
+        })
+        .collect();
+    let mut callees: Vec<CallContext> = callees
+        .into_iter()
+        .take(options.max_relations)
+        .map(|(callee, relation)| {
+            let context = if relation.is_resolved {
+                sources.line(callee, callee.line_start)
+            } else {
+                sources.line(function, relation.line_number)
+            };
+            call_context(callee, relation, context)
+        })
+        .collect();
+    callers.sort_by(|a, b| (&a.file_path, a.call_line).cmp(&(&b.file_path, b.call_line)));
+    callees.sort_by_key(|c| c.call_line);
+
+    FunctionExplanation {
+        function_id: function.id,
+        name: function.name.clone(),
+        file_path: function.file_path.display().to_string(),
+        language: function.language.clone(),
+        namespace: function.namespace.clone(),
+        line_start: function.line_start,
+        line_end: function.line_end,
+        cell: function.cell,
+        signature: function.signature.clone(),
+        doc_comment,
+        snippet,
+        snippet_truncated,
+        class,
+        callers,
+        callees,
+        total_callers,
+        total_callees,
+    }
+}
+
+fn call_context(function: &FunctionInfo, relation: &CallRelation, context: Option<String>) -> CallContext {
+    CallContext {
+        function_id: function.id,
+        function_name: function.name.clone(),
+        file_path: function.file_path.display().to_string(),
+        line_start: function.line_start,
+        call_line: relation.line_number,
+        resolved: relation.is_resolved,
+        context,
+    }
+}
+
+/// 按文件缓存源码行；notebook 函数取所在代码单元的源码，与其行号一致
+#[derive(Default)]
+struct SourceCache {
+    files: HashMap<(PathBuf, Option<usize>), Option<Vec<String>>>,
+}
+
+impl SourceCache {
+    fn lines(&mut self, function: &FunctionInfo) -> Option<&[String]> {
+        self.files
+            .entry((function.file_path.clone(), function.cell))
+            .or_insert_with(|| {
+                let content = fs::read_to_string(&function.file_path).ok()?;
+                let content = match function.cell {
+                    Some(cell) => NotebookCells::parse(&content).ok()?.cell_source(cell)?.to_string(),
+                    None => content,
+                };
+                Some(content.lines().map(str::to_string).collect())
+            })
+            .as_deref()
+    }
+
+    /// 第 `line` 行（从1开始）去掉缩进后的内容，过长时截断
+    fn line(&mut self, function: &FunctionInfo, line: usize) -> Option<String> {
+        let text = self.lines(function)?.get(line.checked_sub(1)?)?.trim();
+        if text.is_empty() {
+            return None;
+        }
+        Some(match text.char_indices().nth(MAX_CONTEXT_CHARS) {
+            Some((end, _)) => format!("{}…", &text[..end]),
+            None => text.to_string(),
+        })
+    }
+}
+
+/// 函数的文档注释：Python 优先取 docstring，其余取紧邻声明之上的注释（跳过属性和装饰器）
+fn doc_comment(lines: &[String], function: &FunctionInfo) -> Option<String> {
+    let start = function.line_start.checked_sub(1)?;
+    if function.language == "python" {
+        if let Some(docstring) = python_docstring(lines, start, function.line_end) {
+            return Some(docstring);
+        }
+    }
+    let hash_comments = matches!(function.language.as_str(), "python" | "bash" | "ruby");
+    let dash_comments = matches!(function.language.as_str(), "lua" | "sql");
+
+    let mut comment = vec![];
+    let mut in_block = false;
+    for line in lines[..start.min(lines.len())].iter().rev() {
+        let trimmed = line.trim();
+        if in_block {
+            comment.push(trimmed);
+            if trimmed.starts_with("/*") {
+                in_block = false;
+            }
+            continue;
+        }
+        let is_comment = trimmed.starts_with("//")
+            || (hash_comments && trimmed.starts_with('#'))
+            || (dash_comments && trimmed.starts_with("--"));
+        if trimmed.ends_with("*/") {
+            comment.push(trimmed);
+            in_block = !trimmed.starts_with("/*");
+        } else if is_comment {
+            comment.push(trimmed);
+        } else if comment.is_empty() && (trimmed.starts_with("#[") || trimmed.starts_with('@')) {
+            // 属性和装饰器位于注释和声明之间
+            continue;
+        } else {
+            break;
+        }
+    }
+    comment.reverse();
+    let text: Vec<&str> = comment.into_iter().map(strip_comment_marker).collect();
+    join_trimmed(&text)
+}
+
+fn strip_comment_marker(line: &str) -> &str {
+    let line = line.trim_end_matches("*/").trim_end();
+    for marker in ["///", "//!", "//", "/**", "/*", "--[[", "---", "--", "##", "#", "*"] {
+        if let Some(rest) = line.strip_prefix(marker) {
+            return rest.trim();
+        }
+    }
+    line.trim()
+}
+
+/// 函数体的第一条语句是字符串时即为 docstring
+fn python_docstring(lines: &[String], start: usize, line_end: usize) -> Option<String> {
+    let end = line_end.min(lines.len());
+    let header_end = (start..end).find(|&i| lines[i].trim_end().ends_with(':'))?;
+    let first = (header_end + 1..end).find(|&i| !lines[i].trim().is_empty())?;
+    let text = lines[first].trim().trim_start_matches(['r', 'R', 'u', 'U']);
+    let quote = ["\"\"\"", "'''", "\"", "'"].into_iter().find(|q| text.starts_with(q))?;
+    let body = &text[quote.len()..];
+    if let Some(close) = body.find(quote) {
+        return join_trimmed(&[&body[..close]]);
+    }
+    let mut docstring = vec![body];
+    for line in &lines[first + 1..end] {
+        match line.find(quote) {
+            Some(close) => {
+                docstring.push(&line[..close]);
+                return join_trimmed(&docstring.iter().map(|l| l.trim()).collect::<Vec<_>>());
+            }
+            None => docstring.push(line),
+        }
+    }
+    None
+}
+
+/// 去掉首尾空行后按行拼接，全空时为 None
+fn join_trimmed(lines: &[&str]) -> Option<String> {
+    let first = lines.iter().position(|l| !l.trim().is_empty())?;
+    let last = lines.iter().rposition(|l| !l.trim().is_empty())?;
+    Some(lines[first..=last].join("\n"))
+}
+
+/// 包含该函数的最内层类/结构体，从源文件重新解析
+fn enclosing_class(graph: &PetCodeGraph, function: &FunctionInfo) -> Option<EnclosingClass> {
+    let symbols = TreeSitterParser::new().parse_file(&function.file_path).ok()?;
+    // notebook 的符号范围指向拼接脚本，换算为代码单元内的行号
+    let cells = match function.cell {
+        Some(_) => Some(NotebookCells::parse(&fs::read_to_string(&function.file_path).ok()?).ok()?),
+        None => None,
+    };
+    let to_cell_line = |line: usize| match &cells {
+        Some(cells) => cells.locate(line).filter(|(cell, _)| Some(*cell) == function.cell).map(|(_, line)| line),
+        None => Some(line),
+    };
+
+    let (name, line_start, line_end) = symbols
+        .iter()
+        .filter_map(|symbol| {
+            let guard = symbol.read();
+            let symbol = guard.as_ref();
+            if symbol.symbol_type() != SymbolType::StructDeclaration {
+                return None;
+            }
+            let start = to_cell_line(symbol.full_range().start_point.row + 1)?;
+            let end = to_cell_line(symbol.full_range().end_point.row + 1)?;
+            (start <= function.line_start && function.line_end <= end && (start, end) != (function.line_start, function.line_end))
+                .then(|| (symbol.name().to_string(), start, end))
+        })
+        .min_by_key(|(_, start, end)| end - start)?;
+
+    let mut sources = SourceCache::default();
+    let declaration = sources.line(function, line_start);
+    let mut members: Vec<String> = graph
+        .find_functions_by_file(&function.file_path)
+        .into_iter()
+        .filter(|f| f.id != function.id && f.cell == function.cell && line_start <= f.line_start && f.line_end <= line_end)
+        .map(|f| f.name.clone())
+        .collect();
+    members.sort();
+    members.dedup();
+    Some(EnclosingClass { name, line_start, line_end, declaration, members })
+}
+
+/// 按 ID、名称和文件路径后缀查找要说明的函数；同名函数有多个且无法区分时返回全部候选
+pub fn find_function<'a>(
+    graph: &'a PetCodeGraph,
+    function_id: Option<&Uuid>,
+    name: Option<&str>,
+    file: Option<&str>,
+) -> Result<&'a FunctionInfo, Vec<&'a FunctionInfo>> {
+    if let Some(function) = function_id.and_then(|id| graph.get_function_by_id(id)) {
+        return Ok(function);
+    }
+    let in_file = |f: &&FunctionInfo| {
+        let file = match file {
+            Some(file) => file.trim_start_matches("./").replace('\\', "/"),
+            None => return true,
+        };
+        let path = f.file_path.to_string_lossy().replace('\\', "/");
+        path.ends_with(&file) || file.ends_with(path.as_str())
+    };
+    let mut candidates: Vec<&FunctionInfo> = match name {
+        Some(name) => graph.find_functions_by_name(name),
+        None => match file {
+            Some(file) => graph.find_functions_by_file(&Path::new(file).to_path_buf()),
+            None => vec![],
+        },
+    };
+    candidates.retain(|f| !f.is_unresolved());
+    candidates.retain(in_file);
+    match candidates.len() {
+        1 => Ok(candidates[0]),
+        _ => Err(candidates),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::codegraph::parser::CodeParser;
+    use crate::codegraph::AnalyzeOptions;
+    use tempfile::TempDir;
+
+    fn function(name: &str, language: &str, line_start: usize, line_end: usize) -> FunctionInfo {
+        FunctionInfo {
+            id: Uuid::new_v4(),
+            name: name.to_string(),
+            file_path: PathBuf::from("f"),
+            line_start,
+            line_end,
+            namespace: "global".to_string(),
+            language: language.to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_doc_comments() {
+        let lines = |code: &str| code.lines().map(str::to_string).collect::<Vec<_>>();
+
+        let rust = lines("use a;\n\n/// Loads the file.\n///\n/// Fails when missing.\n#[inline]\npub fn load() {}\n");
+        assert_eq!(doc_comment(&rust, &function("load", "rust", 7, 7)).as_deref(), Some("Loads the file.\n\nFails when missing."));
+
+        let java = lines("/**\n * Saves the user.\n */\n@Override\npublic void save() {}\n");
+        assert_eq!(doc_comment(&java, &function("save", "java", 5, 5)).as_deref(), Some("Saves the user."));
+
+        let python = lines("# helper\ndef clean(df):\n    \"\"\"Drop empty rows.\n\n    Keeps the index.\n    \"\"\"\n    return df.dropna()\n");
+        assert_eq!(doc_comment(&python, &function("clean", "python", 2, 7)).as_deref(), Some("Drop empty rows.\n\nKeeps the index."));
+        let python = lines("# helper\ndef clean(df):\n    return df\n");
+        assert_eq!(doc_comment(&python, &function("clean", "python", 2, 3)).as_deref(), Some("helper"));
+
+        let go = lines("x := 1\n\nfunc run() {}\n");
+        assert_eq!(doc_comment(&go, &function("run", "go", 3, 3)), None);
+    }
+
+    #[test]
+    fn test_explain_method_with_callers_and_class() {
+        let temp_dir = TempDir::new().unwrap();
+        fs::write(
+            temp_dir.path().join("store.py"),
+            "class Store:\n    def save(self, item):\n        \"\"\"Persist one item.\"\"\"\n        return write_row(item)\n\n    def flush(self):\n        pass\n\n\ndef write_row(item):\n    return item\n",
+        )
+        .unwrap();
+        fs::write(temp_dir.path().join("app.py"), "from store import Store\n\ndef main():\n    Store().save({\"id\": 1})\n").unwrap();
+        let options = AnalyzeOptions {
+            storage_dir: Some(temp_dir.path().join(".codegraph_db")),
+            force_rebuild: true,
+            ..Default::default()
+        };
+        let graph = CodeParser::with_options(options).build_petgraph_code_graph(temp_dir.path()).unwrap();
+
+        let save = find_function(&graph, None, Some("save"), Some("store.py")).unwrap();
+        let explanation = explain_function(&graph, save, &ExplainOptions::default());
+        assert_eq!(explanation.doc_comment.as_deref(), Some("Persist one item."));
+        assert!(explanation.snippet.as_deref().unwrap().starts_with("    def save(self, item):"));
+        let class = explanation.class.unwrap();
+        assert_eq!((class.name.as_str(), class.line_start, class.declaration.as_deref()), ("Store", 1, Some("class Store:")));
+        assert_eq!(class.members, vec!["flush".to_string()]);
+
+        let caller = explanation.callers.iter().find(|c| c.function_name == "main").unwrap();
+        assert_eq!((caller.call_line, caller.context.as_deref()), (4, Some("Store().save({\"id\": 1})")));
+        let callee = explanation.callees.iter().find(|c| c.function_name == "write_row").unwrap();
+        assert_eq!(callee.context.as_deref(), Some("def write_row(item):"));
+    }
+}
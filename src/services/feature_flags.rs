@@ -0,0 +1,124 @@
+use std::collections::BTreeMap;
+
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::codegraph::types::PetCodeGraph;
+use crate::services::source_scan::for_each_source_line;
+
+/// 默认的特性开关读取模式，每个模式的第一个捕获组为开关名
+pub const DEFAULT_FLAG_PATTERNS: &[&str] = &[
+    // flags.is_enabled("x") / isEnabled('x') / feature_enabled("x")
+    r#"(?i)\b(?:is_?enabled|feature_?enabled|is_?feature_?enabled|is_?on)\(\s*["']([\w.:\-]+)["']"#,
+    // Rust 条件编译
+    r#"#\[cfg\(.*?feature\s*=\s*"([\w\-]+)""#,
+    r#"cfg!\(.*?feature\s*=\s*"([\w\-]+)""#,
+    // LaunchDarkly SDK
+    r#"\b(?:variation|variationDetail|boolVariation|stringVariation|numberVariation|intVariation|jsonVariation|BoolVariation|StringVariation|IntVariation|JSONVariation|bool_variation|string_variation|variation_detail)\(\s*["']([\w.\-]+)["']"#,
+];
+
+/// 属性/注解与其修饰的函数之间允许间隔的最大行数
+const ATTRIBUTE_LOOKAHEAD: usize = 3;
+
+/// 单处开关读取
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FlagUsage {
+    pub flag: String,
+    pub file_path: String,
+    pub line: usize,
+    pub function_id: Option<Uuid>,
+    pub function_name: Option<String>,
+    pub source: String,
+}
+
+/// 受开关保护的函数
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GuardedFunction {
+    pub function_id: Uuid,
+    pub function_name: String,
+    pub file_path: String,
+    /// 距离读取开关的函数的调用层数，0表示直接读取开关
+    pub depth: usize,
+}
+
+/// 开关名 -> 使用位置 的索引
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct FeatureFlagIndex {
+    pub flags: BTreeMap<String, Vec<FlagUsage>>,
+}
+
+impl FeatureFlagIndex {
+    /// 使用给定模式扫描图中所有源文件；模式无效时返回错误
+    pub fn build(graph: &PetCodeGraph, patterns: &[String]) -> Result<Self, String> {
+        let regexes = patterns
+            .iter()
+            .map(|p| Regex::new(p).map_err(|e| format!("Invalid flag pattern '{}': {}", p, e)))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let mut index = FeatureFlagIndex::default();
+        for_each_source_line(graph, |file_path, line_number, line, function| {
+            for regex in &regexes {
+                for captures in regex.captures_iter(line) {
+                    let Some(flag) = captures.get(1) else {
+                        continue;
+                    };
+                    // 属性或注解位于函数之前，归属到紧随其后的函数
+                    let function = function.or_else(|| {
+                        graph
+                            .find_functions_by_file(&file_path.to_path_buf())
+                            .into_iter()
+                            .filter(|f| f.line_start > line_number && f.line_start <= line_number + ATTRIBUTE_LOOKAHEAD)
+                            .min_by_key(|f| f.line_start)
+                    });
+                    index.flags.entry(flag.as_str().to_string()).or_default().push(FlagUsage {
+                        flag: flag.as_str().to_string(),
+                        file_path: file_path.display().to_string(),
+                        line: line_number,
+                        function_id: function.map(|f| f.id),
+                        function_name: function.map(|f| f.name.clone()),
+                        source: line.trim().to_string(),
+                    });
+                }
+            }
+        });
+        Ok(index)
+    }
+
+    /// 使用默认模式构建索引
+    pub fn build_default(graph: &PetCodeGraph) -> Self {
+        let patterns: Vec<String> = DEFAULT_FLAG_PATTERNS.iter().map(|p| p.to_string()).collect();
+        Self::build(graph, &patterns).expect("default flag patterns are valid")
+    }
+
+    /// 开关保护的代码路径：读取开关的函数及其下游 `max_depth` 层内的被调用函数
+    pub fn guarded_functions(&self, graph: &PetCodeGraph, flag: &str, max_depth: usize) -> Vec<GuardedFunction> {
+        let mut guarded: BTreeMap<Uuid, GuardedFunction> = BTreeMap::new();
+        let Some(usages) = self.flags.get(flag) else {
+            return Vec::new();
+        };
+
+        for function_id in usages.iter().filter_map(|u| u.function_id) {
+            let Some(function) = graph.get_function_by_id(&function_id) else {
+                continue;
+            };
+            let reached = std::iter::once((function, 0)).chain(graph.get_callees_within(&function_id, max_depth));
+            for (callee, depth) in reached {
+                if callee.is_unresolved() {
+                    continue;
+                }
+                let entry = guarded.entry(callee.id).or_insert_with(|| GuardedFunction {
+                    function_id: callee.id,
+                    function_name: callee.name.clone(),
+                    file_path: callee.file_path.display().to_string(),
+                    depth,
+                });
+                entry.depth = entry.depth.min(depth);
+            }
+        }
+
+        let mut result: Vec<GuardedFunction> = guarded.into_values().collect();
+        result.sort_by(|a, b| a.depth.cmp(&b.depth).then(a.function_name.cmp(&b.function_name)));
+        result
+    }
+}
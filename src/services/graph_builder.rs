@@ -0,0 +1,184 @@
+use std::collections::HashMap;
+use std::path::Path;
+
+use crate::codegraph::entrypoints::detect_entry_points;
+use crate::codegraph::types::PetCodeGraph;
+use crate::codegraph::AnalyzeOptions;
+use crate::services::CodeAnalyzer;
+use crate::services::hooks::{AnalyzeResult, PostAnalysisHooks};
+use crate::storage::{BuildManifest, FunctionBodies, GraphSnapshot, IncrementalManager, StorageManager};
+
+/// 项目ID：项目目录的MD5
+pub fn project_id_for(project_dir: &str) -> String {
+    format!("{:x}", md5::compute(project_dir.as_bytes()))
+}
+
+/// 构建失败的原因
+#[derive(Debug, Clone, PartialEq)]
+pub enum BuildError {
+    /// 项目目录不存在
+    NotFound(String),
+    /// 超出租户配额
+    QuotaExceeded(String),
+    /// 分析或保存失败
+    Failed(String),
+}
+
+impl std::fmt::Display for BuildError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BuildError::NotFound(msg) | BuildError::QuotaExceeded(msg) | BuildError::Failed(msg) => {
+                write!(f, "{}", msg)
+            }
+        }
+    }
+}
+
+/// 一次构建的结果
+pub struct BuildOutcome {
+    pub project_id: String,
+    pub total_files: usize,
+    pub total_functions: usize,
+    pub graph: PetCodeGraph,
+    pub snapshot: GraphSnapshot,
+}
+
+/// 分析项目目录，保存图并登记到项目注册表；不修改内存中缓存的图
+pub fn build_and_store(
+    storage: &StorageManager,
+    project_dir: &str,
+    options: AnalyzeOptions,
+) -> Result<BuildOutcome, BuildError> {
+    let dir = Path::new(project_dir);
+    if !dir.is_dir() {
+        return Err(BuildError::NotFound(format!("Project directory not found: {}", project_dir)));
+    }
+    let project_id = project_id_for(project_dir);
+    storage.check_quota(&project_id, None).map_err(BuildError::QuotaExceeded)?;
+    // 在分析前记录提交，分析期间的新提交不会被误认为已包含
+    let snapshot = GraphSnapshot::capture(dir);
+    let hashes_before = storage.get_persistence().load_file_hashes(&project_id).unwrap_or_default();
+    let incremental = !options.force_rebuild && !hashes_before.is_empty();
+    let manifest = BuildManifest::capture(dir, &options, &snapshot);
+    let embed_sources = options.embed_sources;
+
+    // 增量构建读写与目标存储相同的目录（租户之间互相隔离）
+    let options = AnalyzeOptions {
+        storage_dir: Some(storage.get_persistence().base_dir().to_path_buf()),
+        ..options
+    };
+    let mut analyzer = CodeAnalyzer::with_options(options);
+    analyzer
+        .analyze_directory(dir)
+        .map_err(|e| BuildError::Failed(format!("Failed to analyze directory: {}", e)))?;
+
+    // 内容变化的文件的骨架缓存失效
+    let hashes_after = storage.get_persistence().load_file_hashes(&project_id).unwrap_or_default();
+    let skeletons = storage.get_skeleton_cache();
+    let mut changed_files = IncrementalManager::changed_files(&hashes_before, &hashes_after);
+    changed_files.sort();
+    for path in &changed_files {
+        skeletons.invalidate(path);
+    }
+
+    // 跳过的文件沿用上次的解析统计，已删除的文件不再保留
+    let mut parse_stats = if incremental {
+        storage.get_persistence().load_parse_stats(&project_id).unwrap_or_default()
+    } else {
+        HashMap::new()
+    };
+    parse_stats.retain(|path, _| hashes_after.contains_key(path));
+    for (path, stats) in analyzer.parse_stats() {
+        parse_stats.insert(path.to_string_lossy().to_string(), stats.clone());
+    }
+
+    let (total_files, total_functions) = analyzer
+        .get_stats()
+        .map(|stats| (stats.total_files, stats.total_functions))
+        .unwrap_or((0, 0));
+    let cg = analyzer
+        .get_code_graph()
+        .ok_or_else(|| BuildError::Failed("Analyzer produced no code graph".to_string()))?;
+
+    // 转换为PetCodeGraph以便保存
+    let mut graph = PetCodeGraph::new();
+    for function in cg.functions.values() {
+        graph.add_function(function.clone());
+    }
+    tracing::info!("Added {} functions to PetCodeGraph", cg.functions.len());
+
+    let mut successful_relations = 0;
+    for relation in &cg.call_relations {
+        if let Err(e) = graph.add_call_relation(relation.clone()) {
+            tracing::warn!("Failed to add call relation: {}", e);
+        } else {
+            successful_relations += 1;
+        }
+    }
+    tracing::info!(
+        "Successfully added {}/{} call relations to PetCodeGraph",
+        successful_relations,
+        cg.call_relations.len()
+    );
+    graph.update_stats();
+
+    if let Err(e) = storage.check_quota(&project_id, Some(graph.get_all_functions().len())) {
+        // 分析时已写入新的文件哈希，但图未保存；清除哈希避免下次增量构建跳过这些文件
+        if let Err(err) = storage.get_persistence().clear_file_hashes(&project_id) {
+            tracing::warn!("Failed to clear file hashes for {}: {}", project_id, err);
+        }
+        return Err(BuildError::QuotaExceeded(e));
+    }
+    storage
+        .get_persistence()
+        .save_graph(&project_id, &graph)
+        .map_err(|e| BuildError::Failed(format!("Failed to save graph: {}", e)))?;
+    if let Err(e) = storage.get_persistence().save_snapshot(&project_id, &snapshot) {
+        tracing::warn!("Failed to save snapshot info for {}: {}", project_id, e);
+    }
+    if let Err(e) = storage.get_persistence().save_manifest(&project_id, &manifest) {
+        tracing::warn!("Failed to save build manifest for {}: {}", project_id, e);
+    }
+    if let Err(e) = storage.get_persistence().save_parse_stats(&project_id, &parse_stats) {
+        tracing::warn!("Failed to save parse statistics for {}: {}", project_id, e);
+    }
+    // 入口注册从源文件读取，与函数源码一样在分析后立即检测
+    if let Err(e) = storage.get_persistence().save_entry_points(&project_id, &detect_entry_points(&graph)) {
+        tracing::warn!("Failed to save entry points for {}: {}", project_id, e);
+    }
+    // 在分析后立即读取源码，尽量与被分析的版本一致
+    let bodies = if embed_sources {
+        storage.get_persistence().save_function_bodies(&project_id, &FunctionBodies::capture(&graph))
+    } else {
+        storage.get_persistence().clear_function_bodies(&project_id)
+    };
+    if let Err(e) = bodies {
+        tracing::warn!("Failed to store function bodies for {}: {}", project_id, e);
+    }
+    storage.mark_updated();
+    if let Err(e) = storage.get_persistence().register_project(&project_id, project_dir) {
+        tracing::warn!("Failed to register project in registry: {}", e);
+    }
+
+    let stats = graph.get_stats();
+    PostAnalysisHooks::run(&AnalyzeResult {
+        project_id: project_id.clone(),
+        project_dir: project_dir.to_string(),
+        tenant: storage.tenant_id().map(str::to_string),
+        incremental,
+        total_files,
+        total_functions,
+        resolved_calls: stats.resolved_calls,
+        unresolved_calls: stats.unresolved_calls,
+        changed_files: changed_files.iter().map(|path| path.display().to_string()).collect(),
+        snapshot: snapshot.clone(),
+    });
+
+    Ok(BuildOutcome {
+        project_id,
+        total_files,
+        total_functions,
+        graph,
+        snapshot,
+    })
+}
@@ -0,0 +1,590 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::path::PathBuf;
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use tracing::debug;
+use uuid::Uuid;
+
+use crate::codegraph::diagram::language_color;
+use crate::codegraph::proto;
+use crate::codegraph::treesitter::structs::SymbolType;
+use crate::codegraph::treesitter::TreeSitterParser;
+use crate::codegraph::types::{ClassInfo, ClassType, FunctionInfo, PetCodeGraph};
+use crate::services::lint::function_complexity;
+
+/// 图导出格式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "kebab-case")]
+pub enum ExportFormat {
+    /// 完整的图JSON
+    Json,
+    /// Graphviz DOT
+    Dot,
+    /// Mermaid流程图
+    Mermaid,
+    /// D2图表
+    D2,
+    /// PlantUML
+    Plantuml,
+    /// Gephi使用的GEXF 1.3
+    Gexf,
+    /// Gephi Graph Streaming插件的JSON事件流（每行一个事件）
+    GephiStream,
+    /// 节点表和边表两个CSV文件（仅命令行，写入输出目录）
+    Csv,
+    /// 节点表CSV
+    NodesCsv,
+    /// 边表CSV
+    EdgesCsv,
+    /// JSON Lines：先输出函数记录再输出调用边记录，每行一个JSON对象
+    Ndjson,
+    /// `proto/codegraph.proto` 中的 `CodeGraph` 消息（二进制）
+    Protobuf,
+    /// Obsidian知识库：每个模块/类一篇Markdown笔记（仅命令行，写入输出目录）
+    Obsidian,
+    /// 函数、类、调用边和度量四张Parquet表（仅命令行，需要 `parquet` feature）
+    Parquet,
+}
+
+impl ExportFormat {
+    /// 响应的 Content-Type
+    pub fn content_type(&self) -> &'static str {
+        match self {
+            ExportFormat::Json => "application/json",
+            ExportFormat::Dot => "text/vnd.graphviz; charset=utf-8",
+            ExportFormat::Mermaid | ExportFormat::D2 | ExportFormat::Plantuml => "text/plain; charset=utf-8",
+            ExportFormat::Gexf => "application/gexf+xml",
+            ExportFormat::GephiStream | ExportFormat::Ndjson => "application/x-ndjson",
+            ExportFormat::Csv | ExportFormat::NodesCsv | ExportFormat::EdgesCsv => "text/csv; charset=utf-8",
+            ExportFormat::Protobuf => "application/x-protobuf",
+            ExportFormat::Obsidian => "text/markdown; charset=utf-8",
+            ExportFormat::Parquet => "application/vnd.apache.parquet",
+        }
+    }
+}
+
+impl FromStr for ExportFormat {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.trim().to_lowercase().as_str() {
+            "json" => Ok(ExportFormat::Json),
+            "dot" | "graphviz" => Ok(ExportFormat::Dot),
+            "mermaid" => Ok(ExportFormat::Mermaid),
+            "d2" => Ok(ExportFormat::D2),
+            "plantuml" | "puml" => Ok(ExportFormat::Plantuml),
+            "gexf" => Ok(ExportFormat::Gexf),
+            "gephi-stream" | "gephi_stream" => Ok(ExportFormat::GephiStream),
+            "csv" => Ok(ExportFormat::Csv),
+            "nodes-csv" | "nodes_csv" => Ok(ExportFormat::NodesCsv),
+            "edges-csv" | "edges_csv" => Ok(ExportFormat::EdgesCsv),
+            "ndjson" | "jsonl" => Ok(ExportFormat::Ndjson),
+            "protobuf" | "proto" => Ok(ExportFormat::Protobuf),
+            "obsidian" | "markdown" => Ok(ExportFormat::Obsidian),
+            "parquet" => Ok(ExportFormat::Parquet),
+            other => Err(format!(
+                "Unsupported export format '{}', expected one of: json, dot, mermaid, d2, plantuml, gexf, gephi-stream, csv, nodes-csv, edges-csv, ndjson, protobuf, obsidian, parquet",
+                other
+            )),
+        }
+    }
+}
+
+/// 导出时附加在节点上的度量
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct NodeMetrics {
+    /// 不同调用方的数量（仅统计已解析的调用）
+    pub fan_in: usize,
+    /// 不同被调用方的数量（仅统计已解析的调用）
+    pub fan_out: usize,
+    /// 圈复杂度，源文件不可读或语言不支持时为空
+    pub complexity: Option<usize>,
+}
+
+/// 按指定格式导出图
+pub fn export_graph(graph: &PetCodeGraph, format: ExportFormat) -> Result<String, String> {
+    match format {
+        ExportFormat::Json => graph.to_json().map_err(|e| format!("Failed to serialize graph: {}", e)),
+        ExportFormat::Dot => Ok(graph.to_dot()),
+        ExportFormat::Mermaid => Ok(graph.to_mermaid()),
+        ExportFormat::D2 => Ok(graph.to_d2()),
+        ExportFormat::Plantuml => Ok(graph.to_plantuml()),
+        ExportFormat::Gexf => Ok(to_gexf(graph, &node_metrics(graph))),
+        ExportFormat::GephiStream => Ok(to_gephi_stream(graph, &node_metrics(graph))),
+        ExportFormat::NodesCsv => Ok(to_nodes_csv(graph, &node_metrics(graph))),
+        ExportFormat::EdgesCsv => Ok(to_edges_csv(graph)),
+        ExportFormat::Ndjson => Ok(ndjson_lines(graph).collect()),
+        ExportFormat::Csv => Err("CSV export writes two files; use nodes-csv or edges-csv for a single table".to_string()),
+        ExportFormat::Protobuf => Err("Protobuf export is binary; use export_graph_bytes".to_string()),
+        ExportFormat::Obsidian => Err("Obsidian export writes a directory of notes; use the CLI with an output directory".to_string()),
+        ExportFormat::Parquet => Err("Parquet export writes binary table files; use the CLI with an output directory".to_string()),
+    }
+}
+
+/// 按指定格式导出为字节，支持二进制格式
+pub fn export_graph_bytes(graph: &PetCodeGraph, format: ExportFormat) -> Result<Vec<u8>, String> {
+    match format {
+        ExportFormat::Protobuf => Ok(to_protobuf(graph)),
+        format => export_graph(graph, format).map(String::into_bytes),
+    }
+}
+
+/// 编码为 `codegraph.v1.CodeGraph`，类从源文件重新提取
+pub fn to_protobuf(graph: &PetCodeGraph) -> Vec<u8> {
+    use prost::Message;
+    proto::CodeGraph::from_graph(graph, &extract_classes(graph)).encode_to_vec()
+}
+
+/// 图中不保存类，从图中涉及的源文件重新提取类/结构体，按文件和行号排序
+pub fn extract_classes(graph: &PetCodeGraph) -> Vec<ClassInfo> {
+    let mut files: BTreeMap<&PathBuf, &FunctionInfo> = BTreeMap::new();
+    for function in graph.graph.node_weights().filter(|f| !f.is_unresolved()) {
+        files.entry(&function.file_path).or_insert(function);
+    }
+
+    let parser = TreeSitterParser::new();
+    let mut classes = Vec::new();
+    for (file_path, sample) in files {
+        let symbols = match parser.parse_file(file_path) {
+            Ok(symbols) => symbols,
+            Err(e) => {
+                debug!("No classes for {}: {:?}", file_path.display(), e);
+                continue;
+            }
+        };
+        let mut in_file: Vec<ClassInfo> = symbols
+            .iter()
+            .filter_map(|symbol| {
+                let guard = symbol.read();
+                let symbol = guard.as_ref();
+                (symbol.symbol_type() == SymbolType::StructDeclaration).then(|| ClassInfo {
+                    id: ClassInfo::stable_id(&sample.language, file_path, &sample.namespace, symbol.name()),
+                    name: symbol.name().to_string(),
+                    file_path: file_path.clone(),
+                    line_start: symbol.full_range().start_point.row + 1,
+                    line_end: symbol.full_range().end_point.row + 1,
+                    namespace: sample.namespace.clone(),
+                    language: sample.language.clone(),
+                    class_type: ClassType::Struct,
+                    parent_class: None,
+                    implemented_interfaces: vec![],
+                    member_functions: vec![],
+                    member_variables: vec![],
+                })
+            })
+            .collect();
+        for class in &mut in_file {
+            class.member_functions = graph
+                .find_functions_by_file(file_path)
+                .into_iter()
+                .filter(|f| f.parent_class_id == Some(class.id))
+                .map(|f| f.id)
+                .collect();
+        }
+        in_file.sort_by(|a, b| (a.line_start, &a.name).cmp(&(b.line_start, &b.name)));
+        classes.extend(in_file);
+    }
+    classes
+}
+
+/// 计算每个函数的扇入、扇出和圈复杂度（复杂度需要读取源文件）
+pub fn node_metrics(graph: &PetCodeGraph) -> HashMap<Uuid, NodeMetrics> {
+    let mut complexity_by_file: HashMap<&PathBuf, HashMap<(String, usize), usize>> = HashMap::new();
+    let mut metrics = HashMap::new();
+    for function in graph.get_all_functions() {
+        let distinct = |relations: Vec<(Uuid, bool)>| {
+            relations.into_iter().filter(|(_, resolved)| *resolved).map(|(id, _)| id).collect::<HashSet<_>>().len()
+        };
+        let fan_in = distinct(graph.get_callers(&function.id).into_iter().map(|(f, r)| (f.id, r.is_resolved)).collect());
+        let fan_out = distinct(graph.get_callees(&function.id).into_iter().map(|(f, r)| (f.id, r.is_resolved)).collect());
+
+        let complexity = complexity_by_file
+            .entry(&function.file_path)
+            .or_insert_with(|| {
+                function_complexity(&function.file_path).unwrap_or_else(|e| {
+                    debug!("No complexity for {}: {}", function.file_path.display(), e);
+                    HashMap::new()
+                })
+            })
+            .get(&(function.name.clone(), function.line_start))
+            .copied();
+
+        metrics.insert(function.id, NodeMetrics { fan_in, fan_out, complexity });
+    }
+    metrics
+}
+
+fn xml_escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for ch in text.chars() {
+        match ch {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            _ => escaped.push(ch),
+        }
+    }
+    escaped
+}
+
+/// `#rrggbb` -> (r, g, b)
+fn rgb(color: &str) -> (u8, u8, u8) {
+    let channel = |range| u8::from_str_radix(color.get(range).unwrap_or("e0"), 16).unwrap_or(224);
+    (channel(1..3), channel(3..5), channel(5..7))
+}
+
+/// 节点大小随扇入增长，便于在Gephi中直接看出热点
+fn node_size(metrics: &NodeMetrics) -> f64 {
+    10.0 + 4.0 * (metrics.fan_in as f64).sqrt()
+}
+
+/// 生成GEXF 1.3：节点带语言、命名空间、扇入/扇出、复杂度等属性，并按语言着色、按扇入设置大小
+pub fn to_gexf(graph: &PetCodeGraph, metrics: &HashMap<Uuid, NodeMetrics>) -> String {
+    let mut gexf = String::new();
+    gexf.push_str("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    gexf.push_str("<gexf xmlns=\"http://gexf.net/1.3\" xmlns:viz=\"http://gexf.net/1.3/viz\" version=\"1.3\">\n");
+    gexf.push_str("  <meta>\n");
+    gexf.push_str("    <creator>CodeGraph Exporter</creator>\n");
+    gexf.push_str("    <description>Code dependency graph</description>\n");
+    gexf.push_str("  </meta>\n");
+    gexf.push_str("  <graph mode=\"static\" defaultedgetype=\"directed\">\n");
+
+    gexf.push_str("    <attributes class=\"node\">\n");
+    for (id, title, kind) in [
+        ("file", "file", "string"),
+        ("language", "language", "string"),
+        ("namespace", "namespace", "string"),
+        ("line_start", "line_start", "integer"),
+        ("line_end", "line_end", "integer"),
+        ("fan_in", "fan_in", "integer"),
+        ("fan_out", "fan_out", "integer"),
+        ("complexity", "complexity", "integer"),
+    ] {
+        gexf.push_str(&format!("      <attribute id=\"{}\" title=\"{}\" type=\"{}\"/>\n", id, title, kind));
+    }
+    gexf.push_str("    </attributes>\n");
+    gexf.push_str("    <attributes class=\"edge\">\n");
+    gexf.push_str("      <attribute id=\"line_number\" title=\"line_number\" type=\"integer\"/>\n");
+    gexf.push_str("      <attribute id=\"is_resolved\" title=\"is_resolved\" type=\"boolean\"/>\n");
+    gexf.push_str("    </attributes>\n");
+
+    gexf.push_str("    <nodes>\n");
+    for node_index in graph.graph.node_indices() {
+        let function = &graph.graph[node_index];
+        let node_metrics = metrics.get(&function.id).cloned().unwrap_or_default();
+        gexf.push_str(&format!("      <node id=\"{}\" label=\"{}\">\n", function.id, xml_escape(&function.name)));
+        gexf.push_str("        <attvalues>\n");
+        let mut values = vec![
+            ("file", xml_escape(&function.file_path.display().to_string())),
+            ("language", xml_escape(&function.language)),
+            ("namespace", xml_escape(&function.namespace)),
+            ("line_start", function.line_start.to_string()),
+            ("line_end", function.line_end.to_string()),
+            ("fan_in", node_metrics.fan_in.to_string()),
+            ("fan_out", node_metrics.fan_out.to_string()),
+        ];
+        if let Some(complexity) = node_metrics.complexity {
+            values.push(("complexity", complexity.to_string()));
+        }
+        for (attribute, value) in values {
+            gexf.push_str(&format!("          <attvalue for=\"{}\" value=\"{}\"/>\n", attribute, value));
+        }
+        gexf.push_str("        </attvalues>\n");
+        let (r, g, b) = rgb(language_color(&function.language));
+        gexf.push_str(&format!("        <viz:color r=\"{}\" g=\"{}\" b=\"{}\"/>\n", r, g, b));
+        gexf.push_str(&format!("        <viz:size value=\"{:.1}\"/>\n", node_size(&node_metrics)));
+        gexf.push_str("      </node>\n");
+    }
+    gexf.push_str("    </nodes>\n");
+
+    gexf.push_str("    <edges>\n");
+    for edge_index in graph.graph.edge_indices() {
+        let Some((source, target)) = graph.graph.edge_endpoints(edge_index) else {
+            continue;
+        };
+        let relation = &graph.graph[edge_index];
+        gexf.push_str(&format!(
+            "      <edge id=\"{}\" source=\"{}\" target=\"{}\">\n",
+            edge_index.index(),
+            graph.graph[source].id,
+            graph.graph[target].id
+        ));
+        gexf.push_str("        <attvalues>\n");
+        gexf.push_str(&format!("          <attvalue for=\"line_number\" value=\"{}\"/>\n", relation.line_number));
+        gexf.push_str(&format!("          <attvalue for=\"is_resolved\" value=\"{}\"/>\n", relation.is_resolved));
+        gexf.push_str("        </attvalues>\n");
+        gexf.push_str("      </edge>\n");
+    }
+    gexf.push_str("    </edges>\n");
+    gexf.push_str("  </graph>\n");
+    gexf.push_str("</gexf>\n");
+    gexf
+}
+
+/// 生成Gephi Graph Streaming事件：先添加所有节点（`an`），再添加所有边（`ae`），每行一个JSON对象
+pub fn to_gephi_stream(graph: &PetCodeGraph, metrics: &HashMap<Uuid, NodeMetrics>) -> String {
+    let mut events = String::new();
+    for function in graph.get_all_functions() {
+        let node_metrics = metrics.get(&function.id).cloned().unwrap_or_default();
+        let (r, g, b) = rgb(language_color(&function.language));
+        let event = json!({
+            "an": {
+                function.id.to_string(): {
+                    "label": function.name,
+                    "file": function.file_path.display().to_string(),
+                    "language": function.language,
+                    "namespace": function.namespace,
+                    "line_start": function.line_start,
+                    "line_end": function.line_end,
+                    "fan_in": node_metrics.fan_in,
+                    "fan_out": node_metrics.fan_out,
+                    "complexity": node_metrics.complexity,
+                    "size": node_size(&node_metrics),
+                    "r": r as f64 / 255.0,
+                    "g": g as f64 / 255.0,
+                    "b": b as f64 / 255.0,
+                }
+            }
+        });
+        events.push_str(&event.to_string());
+        events.push('\n');
+    }
+    for edge_index in graph.graph.edge_indices() {
+        let Some((source, target)) = graph.graph.edge_endpoints(edge_index) else {
+            continue;
+        };
+        let relation = &graph.graph[edge_index];
+        let event = json!({
+            "ae": {
+                format!("e{}", edge_index.index()): {
+                    "source": graph.graph[source].id.to_string(),
+                    "target": graph.graph[target].id.to_string(),
+                    "directed": true,
+                    "line_number": relation.line_number,
+                    "is_resolved": relation.is_resolved,
+                }
+            }
+        });
+        events.push_str(&event.to_string());
+        events.push('\n');
+    }
+    events
+}
+
+/// 跨构建稳定的节点ID：由文件、命名空间和函数名哈希得到，同名函数按行号追加序号
+pub fn stable_ids(graph: &PetCodeGraph) -> HashMap<Uuid, String> {
+    let mut functions = graph.get_all_functions();
+    functions.sort_by(|a, b| {
+        (&a.file_path, &a.namespace, &a.name, a.line_start).cmp(&(&b.file_path, &b.namespace, &b.name, b.line_start))
+    });
+
+    let mut seen: HashMap<String, usize> = HashMap::new();
+    let mut ids = HashMap::new();
+    for function in functions {
+        let key = format!("{}::{}::{}", function.file_path.display(), function.namespace, function.name);
+        let hash = format!("{:x}", md5::compute(key.as_bytes()));
+        let occurrence = seen.entry(hash.clone()).or_default();
+        let id = match *occurrence {
+            0 => hash[..16].to_string(),
+            n => format!("{}-{}", &hash[..16], n),
+        };
+        *occurrence += 1;
+        ids.insert(function.id, id);
+    }
+    ids
+}
+
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+fn csv_row(fields: &[String]) -> String {
+    let mut row = fields.iter().map(|f| csv_field(f)).collect::<Vec<_>>().join(",");
+    row.push('\n');
+    row
+}
+
+/// 节点表：每个函数一行，按文件和行号排序
+pub fn to_nodes_csv(graph: &PetCodeGraph, metrics: &HashMap<Uuid, NodeMetrics>) -> String {
+    let ids = stable_ids(graph);
+    let mut functions = graph.get_all_functions();
+    functions.sort_by(|a, b| (&a.file_path, a.line_start, &a.name).cmp(&(&b.file_path, b.line_start, &b.name)));
+
+    let mut csv = String::from("id,name,namespace,language,file,line_start,line_end,fan_in,fan_out,complexity\n");
+    for function in functions {
+        let node_metrics = metrics.get(&function.id).cloned().unwrap_or_default();
+        csv.push_str(&csv_row(&[
+            ids[&function.id].clone(),
+            function.name.clone(),
+            function.namespace.clone(),
+            function.language.clone(),
+            function.file_path.display().to_string(),
+            function.line_start.to_string(),
+            function.line_end.to_string(),
+            node_metrics.fan_in.to_string(),
+            node_metrics.fan_out.to_string(),
+            node_metrics.complexity.map(|c| c.to_string()).unwrap_or_default(),
+        ]));
+    }
+    csv
+}
+
+/// 导出的一条调用边，节点使用稳定ID；按字段顺序排序
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(tag = "type", rename = "edge")]
+struct ExportedEdge {
+    source: String,
+    target: String,
+    source_name: String,
+    target_name: String,
+    file: String,
+    line: usize,
+    resolved: bool,
+}
+
+/// 按 (调用方, 被调用方, 行号) 排序的调用边
+fn sorted_edges(graph: &PetCodeGraph, ids: &HashMap<Uuid, String>) -> Vec<ExportedEdge> {
+    let mut edges = Vec::new();
+    for edge_index in graph.graph.edge_indices() {
+        let Some((source, target)) = graph.graph.edge_endpoints(edge_index) else {
+            continue;
+        };
+        let (caller, callee, relation) = (&graph.graph[source], &graph.graph[target], &graph.graph[edge_index]);
+        edges.push(ExportedEdge {
+            source: ids[&caller.id].clone(),
+            target: ids[&callee.id].clone(),
+            source_name: caller.name.clone(),
+            target_name: callee.name.clone(),
+            file: relation.caller_file.display().to_string(),
+            line: relation.line_number,
+            resolved: relation.is_resolved,
+        });
+    }
+    edges.sort();
+    edges
+}
+
+/// 边表：每次调用一行，`source`/`target` 对应节点表的 `id`
+pub fn to_edges_csv(graph: &PetCodeGraph) -> String {
+    let mut csv = String::from("source,target,source_name,target_name,file,line,resolved\n");
+    for edge in sorted_edges(graph, &stable_ids(graph)) {
+        csv.push_str(&csv_row(&[
+            edge.source,
+            edge.target,
+            edge.source_name,
+            edge.target_name,
+            edge.file,
+            edge.line.to_string(),
+            edge.resolved.to_string(),
+        ]));
+    }
+    csv
+}
+
+/// JSON Lines导出：函数记录在前、调用边记录在后，顺序稳定，同一个图每次输出的字节完全相同（便于按字节范围续传）
+pub fn ndjson_lines(graph: &PetCodeGraph) -> impl Iterator<Item = String> + '_ {
+    let ids = stable_ids(graph);
+    let edges = sorted_edges(graph, &ids);
+    let mut functions = graph.get_all_functions();
+    functions.sort_by(|a, b| (&a.file_path, a.line_start, &a.name).cmp(&(&b.file_path, b.line_start, &b.name)));
+
+    let functions = functions.into_iter().map(move |function| {
+        json!({
+            "type": "function",
+            "id": ids[&function.id],
+            "name": function.name,
+            "namespace": function.namespace,
+            "language": function.language,
+            "file": function.file_path,
+            "line_start": function.line_start,
+            "line_end": function.line_end,
+        })
+    });
+    let edges = edges
+        .into_iter()
+        .map(|edge| serde_json::to_string(&edge).expect("edge records serialize to JSON"));
+    functions.map(|record| record.to_string()).chain(edges).map(|record| format!("{}\n", record))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::codegraph::parser::CodeParser;
+    use crate::codegraph::AnalyzeOptions;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_gexf_carries_metrics() {
+        let temp_dir = TempDir::new().unwrap();
+        fs::write(
+            temp_dir.path().join("lib.rs"),
+            "pub fn check(a: i32) -> bool {\n    if a > 0 && a < 10 {\n        return helper();\n    }\n    helper()\n}\n\npub fn helper() -> bool {\n    true\n}\n\npub fn other() -> bool {\n    helper()\n}\n",
+        )
+        .unwrap();
+        let options = AnalyzeOptions {
+            storage_dir: Some(temp_dir.path().join(".codegraph_db")),
+            force_rebuild: true,
+            ..Default::default()
+        };
+        let graph = CodeParser::with_options(options).build_petgraph_code_graph(temp_dir.path()).unwrap();
+        let metrics = node_metrics(&graph);
+
+        let helper = graph.find_functions_by_name("helper")[0];
+        let check = graph.find_functions_by_name("check")[0];
+        assert_eq!(metrics[&helper.id].fan_in, 2);
+        assert_eq!(metrics[&check.id].fan_out, 1);
+        assert_eq!(metrics[&check.id].complexity, Some(3));
+
+        let gexf = export_graph(&graph, ExportFormat::Gexf).unwrap();
+        assert!(gexf.contains(&format!("<node id=\"{}\" label=\"helper\">", helper.id)));
+        assert!(gexf.contains("<attvalue for=\"fan_in\" value=\"2\"/>"));
+        assert!(gexf.contains("<attvalue for=\"complexity\" value=\"3\"/>"));
+        assert!(gexf.contains("<viz:color r=\"222\" g=\"165\" b=\"132\"/>"));
+
+        let stream = export_graph(&graph, ExportFormat::GephiStream).unwrap();
+        let events: Vec<serde_json::Value> = stream.lines().map(|l| serde_json::from_str(l).unwrap()).collect();
+        assert_eq!(events.iter().filter(|e| e.get("an").is_some()).count(), graph.graph.node_count());
+        assert_eq!(events.iter().filter(|e| e.get("ae").is_some()).count(), graph.graph.edge_count());
+        assert_eq!(events[0]["an"].as_object().unwrap().len(), 1);
+
+        let nodes = export_graph(&graph, ExportFormat::NodesCsv).unwrap();
+        let edges = export_graph(&graph, ExportFormat::EdgesCsv).unwrap();
+        assert_eq!(nodes.lines().count(), graph.graph.node_count() + 1);
+        assert_eq!(edges.lines().count(), graph.graph.edge_count() + 1);
+        let helper_row = nodes.lines().find(|l| l.split(',').nth(1) == Some("helper")).unwrap();
+        assert!(helper_row.ends_with(",2,0,1"), "{}", helper_row);
+        let helper_id = helper_row.split(',').next().unwrap();
+        assert_eq!(edges.lines().filter(|l| l.split(',').nth(1) == Some(helper_id)).count(), 3);
+        // 重新构建后 UUID 会变化，CSV 中的 ID 保持不变
+        let rebuilt = CodeParser::with_options(AnalyzeOptions {
+            storage_dir: Some(temp_dir.path().join(".codegraph_db")),
+            force_rebuild: true,
+            ..Default::default()
+        })
+        .build_petgraph_code_graph(temp_dir.path())
+        .unwrap();
+        assert_eq!(to_edges_csv(&rebuilt), edges);
+        // JSON Lines 在重建后逐字节相同，可以按字节范围续传
+        let ndjson = export_graph(&graph, ExportFormat::Ndjson).unwrap();
+        assert_eq!(ndjson_lines(&rebuilt).collect::<String>(), ndjson);
+        let records: Vec<serde_json::Value> = ndjson.lines().map(|l| serde_json::from_str(l).unwrap()).collect();
+        assert_eq!(records.iter().filter(|r| r["type"] == "function").count(), graph.graph.node_count());
+        assert_eq!(records.last().unwrap()["type"], "edge");
+        assert!(records.iter().any(|r| r["target"] == helper_id && r["resolved"] == true));
+        assert_eq!(csv_field("a,\"b\""), "\"a,\"\"b\"\"\"");
+        assert!(export_graph(&graph, ExportFormat::Csv).is_err());
+
+        assert_eq!("gephi-stream".parse::<ExportFormat>(), Ok(ExportFormat::GephiStream));
+        assert!("png".parse::<ExportFormat>().is_err());
+        assert_eq!(xml_escape("a<b & \"c\""), "a&lt;b &amp; &quot;c&quot;");
+    }
+}
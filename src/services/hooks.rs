@@ -0,0 +1,163 @@
+use std::io::Write;
+use std::process::{Command, Stdio};
+use std::sync::Arc;
+
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+
+use crate::storage::GraphSnapshot;
+
+/// 一次成功构建（全量或增量）的摘要，传给构建后钩子
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AnalyzeResult {
+    pub project_id: String,
+    pub project_dir: String,
+    /// 所属租户，默认存储时为空
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tenant: Option<String>,
+    /// 是否复用了上次构建的文件哈希，只重新分析变化的文件
+    pub incremental: bool,
+    pub total_files: usize,
+    pub total_functions: usize,
+    pub resolved_calls: usize,
+    pub unresolved_calls: usize,
+    /// 内容相对上次构建发生变化的文件；首次构建时为全部文件
+    pub changed_files: Vec<String>,
+    pub snapshot: GraphSnapshot,
+}
+
+/// 构建完成并保存后调用的钩子，用于通知、自定义导出或策略检查。
+///
+/// 钩子在构建线程上同步执行；返回的错误只记录日志，不影响构建结果。
+pub trait PostAnalysisHook: Send + Sync {
+    fn name(&self) -> &str;
+    fn on_analyzed(&self, result: &AnalyzeResult) -> Result<(), String>;
+}
+
+static HOOKS: RwLock<Vec<Arc<dyn PostAnalysisHook>>> = parking_lot::const_rwlock(Vec::new());
+
+/// 进程内注册的构建后钩子，按注册顺序执行
+pub struct PostAnalysisHooks;
+
+impl PostAnalysisHooks {
+    pub fn register(hook: Arc<dyn PostAnalysisHook>) {
+        tracing::info!("Registered post-analysis hook '{}'", hook.name());
+        HOOKS.write().push(hook);
+    }
+
+    /// 注册一个外部命令：经 shell 执行，结果以 JSON 写入其标准输入
+    pub fn register_command(command: &str) {
+        Self::register(Arc::new(CommandHook { command: command.to_string() }));
+    }
+
+    /// 移除指定名称的钩子（命令钩子的名称即命令本身）
+    pub fn unregister(name: &str) -> bool {
+        let mut hooks = HOOKS.write();
+        let before = hooks.len();
+        hooks.retain(|hook| hook.name() != name);
+        hooks.len() != before
+    }
+
+    /// 依次执行全部钩子，返回失败的钩子数
+    pub fn run(result: &AnalyzeResult) -> usize {
+        // 先复制再执行，钩子内部注册/注销不会死锁
+        let hooks: Vec<_> = HOOKS.read().clone();
+        let mut failed = 0;
+        for hook in hooks {
+            if let Err(e) = hook.on_analyzed(result) {
+                tracing::warn!("Post-analysis hook '{}' failed for {}: {}", hook.name(), result.project_dir, e);
+                failed += 1;
+            }
+        }
+        failed
+    }
+}
+
+/// `--hook <cmd>`：以 JSON 形式在标准输入上接收 [`AnalyzeResult`]
+struct CommandHook {
+    command: String,
+}
+
+impl PostAnalysisHook for CommandHook {
+    fn name(&self) -> &str {
+        &self.command
+    }
+
+    fn on_analyzed(&self, result: &AnalyzeResult) -> Result<(), String> {
+        let payload = serde_json::to_vec(result).map_err(|e| e.to_string())?;
+        let mut command = if cfg!(windows) {
+            let mut command = Command::new("cmd");
+            command.arg("/C").arg(&self.command);
+            command
+        } else {
+            let mut command = Command::new("sh");
+            command.arg("-c").arg(&self.command);
+            command
+        };
+        let mut child = command
+            .env("CODEGRAPH_PROJECT_ID", &result.project_id)
+            .env("CODEGRAPH_PROJECT_DIR", &result.project_dir)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|e| format!("failed to start: {}", e))?;
+        // 不读取输入的命令会提前关闭管道，此时写入失败不算错误
+        if let Some(mut stdin) = child.stdin.take() {
+            let _ = stdin.write_all(&payload);
+        }
+        let output = child.wait_with_output().map_err(|e| e.to_string())?;
+        if output.status.success() {
+            Ok(())
+        } else {
+            Err(format!("exited with {}: {}", output.status, String::from_utf8_lossy(&output.stderr).trim()))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn result(project_dir: &str) -> AnalyzeResult {
+        AnalyzeResult {
+            project_id: "0cc175b9".to_string(),
+            project_dir: project_dir.to_string(),
+            tenant: None,
+            incremental: true,
+            total_files: 2,
+            total_functions: 5,
+            resolved_calls: 4,
+            unresolved_calls: 1,
+            changed_files: vec![format!("{}/src/main.rs", project_dir)],
+            snapshot: GraphSnapshot {
+                commit: None,
+                dirty: false,
+                built_at: chrono::Utc::now(),
+                analyzer_version: "test".to_string(),
+                pull_request: None,
+                base_commit: None,
+            },
+        }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_command_hook_receives_result_on_stdin() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let out = temp_dir.path().join("result.json");
+        let project_dir = temp_dir.path().join("hook-project").display().to_string();
+        let command = format!("cat > '{}' && test \"$CODEGRAPH_PROJECT_ID\" = 0cc175b9", out.display());
+        PostAnalysisHooks::register_command(&command);
+        PostAnalysisHooks::register_command("echo policy violated >&2; exit 3");
+
+        let expected = result(&project_dir);
+        let failed = PostAnalysisHooks::run(&expected);
+        assert!(PostAnalysisHooks::unregister(&command));
+        assert!(PostAnalysisHooks::unregister("echo policy violated >&2; exit 3"));
+
+        assert_eq!(failed, 1);
+        let received: AnalyzeResult = serde_json::from_slice(&std::fs::read(&out).unwrap()).unwrap();
+        assert_eq!(received, expected);
+    }
+}
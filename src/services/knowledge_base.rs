@@ -0,0 +1,299 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use tracing::debug;
+use uuid::Uuid;
+
+use crate::codegraph::types::{FunctionInfo, PetCodeGraph};
+use crate::services::graph_export::{extract_classes, node_metrics, NodeMetrics};
+use crate::services::skeleton::build_skeleton;
+
+/// 类笔记所在的子目录
+const CLASS_DIR: &str = "classes";
+
+/// 知识库导出结果
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct KnowledgeBaseSummary {
+    pub modules: usize,
+    pub classes: usize,
+}
+
+/// 类笔记：同一文件中同名的声明（如Rust的struct和它的impl块）合并为一篇
+struct ClassNote<'a> {
+    name: &'a str,
+    language: &'a str,
+    file_path: &'a PathBuf,
+    /// 各个声明的行范围
+    ranges: Vec<(usize, usize)>,
+    /// 笔记路径（相对知识库根目录，带 .md）
+    note: String,
+}
+
+impl ClassNote<'_> {
+    fn lines(&self) -> String {
+        self.ranges.iter().map(|(start, end)| format!("{}-{}", start, end)).collect::<Vec<_>>().join(", ")
+    }
+
+    fn contains(&self, function: &FunctionInfo) -> bool {
+        self.ranges.iter().any(|(start, end)| function.line_start >= *start && function.line_end <= *end)
+    }
+}
+
+struct Notes<'a> {
+    /// 源文件 -> 模块笔记路径（相对知识库根目录，带 .md）
+    modules: BTreeMap<&'a PathBuf, String>,
+    classes: Vec<ClassNote<'a>>,
+    metrics: HashMap<Uuid, NodeMetrics>,
+}
+
+impl Notes<'_> {
+    /// 指向函数所在模块笔记中对应标题的链接，未解析的函数只显示名称
+    fn function_link(&self, function: &FunctionInfo) -> String {
+        match self.modules.get(&function.file_path) {
+            Some(note) if !function.is_unresolved() => {
+                format!("[[{}#{}|{}]]", note, function.name, function.name)
+            }
+            _ => format!("`{}`", function.name),
+        }
+    }
+
+    fn metrics_line(&self, function: &FunctionInfo) -> String {
+        let metrics = self.metrics.get(&function.id).cloned().unwrap_or_default();
+        let complexity = metrics.complexity.map(|c| c.to_string()).unwrap_or_else(|| "-".to_string());
+        format!(
+            "lines {}-{} · fan-in {} · fan-out {} · complexity {}",
+            function.line_start, function.line_end, metrics.fan_in, metrics.fan_out, complexity
+        )
+    }
+}
+
+fn file_title(path: &str) -> &str {
+    path.rsplit('/').next().unwrap_or(path)
+}
+
+/// 以 `/` 分隔的相对路径，不在根目录下的文件使用去掉开头 `/` 的完整路径
+fn relative_note_path(root: &Path, file: &Path) -> String {
+    let relative = file.strip_prefix(root).unwrap_or(file);
+    let parts: Vec<String> = relative
+        .components()
+        .filter_map(|c| match c {
+            std::path::Component::Normal(part) => Some(part.to_string_lossy().into_owned()),
+            _ => None,
+        })
+        .collect();
+    format!("{}.md", parts.join("/"))
+}
+
+fn links(notes: &Notes, functions: Vec<&FunctionInfo>) -> String {
+    let mut seen = HashSet::new();
+    let mut rendered: Vec<String> = functions
+        .into_iter()
+        .filter(|f| seen.insert(f.id))
+        .map(|f| notes.function_link(f))
+        .collect();
+    rendered.sort();
+    if rendered.is_empty() {
+        "—".to_string()
+    } else {
+        rendered.join(", ")
+    }
+}
+
+fn render_module(notes: &Notes, graph: &PetCodeGraph, file: &Path, note: &str, functions: &[&FunctionInfo]) -> String {
+    let language = functions.first().map(|f| f.language.as_str()).unwrap_or_default();
+    let mut md = format!(
+        "---\ntags: [codegraph/module, lang/{}]\npath: \"{}\"\n---\n# {}\n\n",
+        language,
+        file.display(),
+        file_title(note.trim_end_matches(".md"))
+    );
+    let classes: Vec<_> = notes.classes.iter().filter(|c| c.file_path == file).collect();
+    md.push_str(&format!("Language: {} · Functions: {} · Classes: {}\n", language, functions.len(), classes.len()));
+
+    if !classes.is_empty() {
+        md.push_str("\n## Classes\n\n");
+        for class in classes {
+            md.push_str(&format!("- [[{}|{}]] (lines {})\n", class.note, class.name, class.lines()));
+        }
+    }
+
+    match fs::read_to_string(file).map_err(|e| e.to_string()).and_then(|code| build_skeleton(file, &code)) {
+        Ok((_, skeleton)) if !skeleton.trim().is_empty() => {
+            md.push_str(&format!("\n## Skeleton\n\n```{}\n{}\n```\n", language, skeleton.trim_end()));
+        }
+        Ok(_) => {}
+        Err(e) => debug!("No skeleton for {}: {}", file.display(), e),
+    }
+
+    md.push_str("\n## Functions\n");
+    for function in functions {
+        md.push_str(&format!("\n### {}\n\n", function.name));
+        md.push_str(&format!("- {}\n", notes.metrics_line(function)));
+        let callees = graph.get_callees(&function.id).into_iter().map(|(f, _)| f).collect();
+        let callers = graph.get_callers(&function.id).into_iter().map(|(f, _)| f).collect();
+        md.push_str(&format!("- Calls: {}\n", links(notes, callees)));
+        md.push_str(&format!("- Called by: {}\n", links(notes, callers)));
+    }
+    md
+}
+
+fn render_class(notes: &Notes, graph: &PetCodeGraph, class: &ClassNote, functions: &[&FunctionInfo]) -> String {
+    let module = &notes.modules[class.file_path];
+    let mut md = format!(
+        "---\ntags: [codegraph/class, lang/{}]\n---\n# {}\n\nDefined in [[{}|{}]], lines {}.\n",
+        class.language,
+        class.name,
+        module,
+        file_title(module.trim_end_matches(".md")),
+        class.lines()
+    );
+
+    // 类的行范围内定义的函数视为方法
+    let methods: Vec<&FunctionInfo> = functions.iter().filter(|f| class.contains(f)).copied().collect();
+    if methods.is_empty() {
+        return md;
+    }
+    let method_ids: HashSet<Uuid> = methods.iter().map(|f| f.id).collect();
+
+    md.push_str("\n## Methods\n\n");
+    for method in &methods {
+        md.push_str(&format!("- {} · {}\n", notes.function_link(method), notes.metrics_line(method)));
+    }
+    // 类内方法之间的调用不列出
+    let callees = methods
+        .iter()
+        .flat_map(|m| graph.get_callees(&m.id))
+        .map(|(f, _)| f)
+        .filter(|f| !method_ids.contains(&f.id))
+        .collect();
+    let callers = methods
+        .iter()
+        .flat_map(|m| graph.get_callers(&m.id))
+        .map(|(f, _)| f)
+        .filter(|f| !method_ids.contains(&f.id))
+        .collect();
+    md.push_str(&format!("\n## Calls\n\n{}\n", links(notes, callees)));
+    md.push_str(&format!("\n## Called by\n\n{}\n", links(notes, callers)));
+    md
+}
+
+fn write_note(out: &Path, note: &str, content: &str) -> Result<(), String> {
+    let path = out.join(note);
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+    }
+    fs::write(&path, content).map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+}
+
+/// 生成Obsidian知识库：每个源文件一篇模块笔记（骨架、度量、调用方/被调用方的wiki链接），
+/// 每个类一篇类笔记，另有 `index.md` 列出全部模块
+pub fn write_knowledge_base(graph: &PetCodeGraph, root: &Path, out: &Path) -> Result<KnowledgeBaseSummary, String> {
+    let mut by_file: BTreeMap<&PathBuf, Vec<&FunctionInfo>> = BTreeMap::new();
+    for function in graph.graph.node_weights().filter(|f| !f.is_unresolved()) {
+        by_file.entry(&function.file_path).or_default().push(function);
+    }
+    for functions in by_file.values_mut() {
+        functions.sort_by(|a, b| (a.line_start, &a.name).cmp(&(b.line_start, &b.name)));
+    }
+
+    let all_classes = extract_classes(graph);
+    let mut classes: Vec<ClassNote> = Vec::new();
+    let mut used_names: HashMap<&str, usize> = HashMap::new();
+    for class in all_classes.iter().filter(|c| by_file.contains_key(&c.file_path)) {
+        let range = (class.line_start, class.line_end);
+        if let Some(existing) = classes.iter_mut().find(|c| c.name == class.name && c.file_path == &class.file_path) {
+            existing.ranges.push(range);
+            continue;
+        }
+        // 不同文件中的同名类追加序号，避免笔记互相覆盖
+        let count = used_names.entry(&class.name).or_default();
+        *count += 1;
+        let note = match *count {
+            1 => format!("{}/{}.md", CLASS_DIR, class.name),
+            n => format!("{}/{}-{}.md", CLASS_DIR, class.name, n),
+        };
+        classes.push(ClassNote {
+            name: &class.name,
+            language: &class.language,
+            file_path: &class.file_path,
+            ranges: vec![range],
+            note,
+        });
+    }
+
+    let notes = Notes {
+        modules: by_file.keys().map(|file| (*file, relative_note_path(root, file))).collect(),
+        classes,
+        metrics: node_metrics(graph),
+    };
+
+    let mut index = String::from("---\ntags: [codegraph/index]\n---\n# Code graph\n\n## Modules\n\n");
+    for (file, functions) in &by_file {
+        let note = &notes.modules[file];
+        write_note(out, note, &render_module(&notes, graph, file, note, functions))?;
+        index.push_str(&format!("- [[{}|{}]] ({} functions)\n", note, note.trim_end_matches(".md"), functions.len()));
+    }
+    for class in &notes.classes {
+        write_note(out, &class.note, &render_class(&notes, graph, class, &by_file[class.file_path]))?;
+    }
+    if !notes.classes.is_empty() {
+        index.push_str("\n## Classes\n\n");
+        for class in &notes.classes {
+            index.push_str(&format!("- [[{}|{}]]\n", class.note, class.name));
+        }
+    }
+    write_note(out, "index.md", &index)?;
+
+    Ok(KnowledgeBaseSummary {
+        modules: by_file.len(),
+        classes: notes.classes.len(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::codegraph::parser::CodeParser;
+    use crate::codegraph::AnalyzeOptions;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_knowledge_base_links_callers_and_callees() {
+        let temp_dir = TempDir::new().unwrap();
+        let src = temp_dir.path().join("src");
+        fs::create_dir_all(&src).unwrap();
+        fs::write(
+            src.join("shapes.rs"),
+            "pub struct Circle {\n    r: f64,\n}\n\npub fn area(c: &Circle) -> f64 {\n    square(c.r).max(cube(c.r))\n}\n\nimpl Circle {\n    pub fn diameter(&self) -> f64 {\n        self.r * 2.0\n    }\n}\n",
+        )
+        .unwrap();
+        fs::write(src.join("math.rs"), "pub fn square(x: f64) -> f64 {\n    x * x\n}\n").unwrap();
+        let options = AnalyzeOptions {
+            storage_dir: Some(temp_dir.path().join(".codegraph_db")),
+            force_rebuild: true,
+            ..Default::default()
+        };
+        let graph = CodeParser::with_options(options).build_petgraph_code_graph(temp_dir.path()).unwrap();
+
+        let out = temp_dir.path().join("vault");
+        let summary = write_knowledge_base(&graph, temp_dir.path(), &out).unwrap();
+        assert_eq!((summary.modules, summary.classes), (2, 1));
+
+        let shapes = fs::read_to_string(out.join("src/shapes.rs.md")).unwrap();
+        assert!(shapes.starts_with("---\ntags: [codegraph/module, lang/rust]\n"));
+        assert!(shapes.contains("- [[classes/Circle.md|Circle]] (lines 1-3, 9-13)\n"));
+        assert!(shapes.contains("## Skeleton\n\n```rust\n"));
+        assert!(shapes.contains("### area\n\n- lines 5-7 · fan-in 0 · fan-out 1 · complexity 1\n"));
+        assert!(shapes.contains("- Calls: [[src/math.rs.md#square|square]], `cube`, `max`\n"));
+
+        let math = fs::read_to_string(out.join("src/math.rs.md")).unwrap();
+        assert!(math.contains("- Called by: [[src/shapes.rs.md#area|area]]\n"));
+        let class = fs::read_to_string(out.join("classes/Circle.md")).unwrap();
+        assert!(class.contains("Defined in [[src/shapes.rs.md|shapes.rs]], lines 1-3, 9-13.\n"));
+        assert!(class.contains("## Methods\n\n- [[src/shapes.rs.md#diameter|diameter]] · lines 10-12"));
+        let index = fs::read_to_string(out.join("index.md")).unwrap();
+        assert!(index.contains("- [[src/math.rs.md|src/math.rs]] (1 functions)\n"));
+    }
+}
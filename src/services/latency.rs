@@ -0,0 +1,258 @@
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::codegraph::types::{FunctionInfo, PetCodeGraph};
+
+/// 项目目录下默认的预算注解文件名
+pub const DEFAULT_BUDGET_FILE: &str = "codegraph-budgets.toml";
+
+/// 单个函数的延迟/成本注解
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BudgetAnnotation {
+    /// 函数名
+    pub function: String,
+    /// 可选的文件路径后缀，用于区分同名函数
+    pub file: Option<String>,
+    /// 单次调用的延迟（毫秒）
+    #[serde(default)]
+    pub latency_ms: f64,
+    /// 单次调用的成本（任意单位）
+    #[serde(default)]
+    pub cost: f64,
+}
+
+/// 注解配置文件
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct BudgetConfig {
+    #[serde(default, rename = "annotation")]
+    pub annotations: Vec<BudgetAnnotation>,
+}
+
+impl BudgetConfig {
+    /// 从TOML文件加载
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let content = fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read budget file {}: {}", path.display(), e))?;
+        toml::from_str(&content).map_err(|e| format!("Invalid budget file {}: {}", path.display(), e))
+    }
+
+    fn matches(annotation: &BudgetAnnotation, function: &FunctionInfo) -> bool {
+        annotation.function == function.name
+            && annotation
+                .file
+                .as_ref()
+                .is_none_or(|file| function.file_path.to_string_lossy().ends_with(file.as_str()))
+    }
+}
+
+/// 汇总后的预算
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RolledUpBudget {
+    pub function_id: Uuid,
+    pub function_name: String,
+    pub file_path: String,
+    /// 沿最慢调用路径累计的延迟
+    pub worst_path_latency_ms: f64,
+    /// 假设每个下游函数都被调用一次时的总延迟
+    pub total_latency_ms: f64,
+    /// 假设每个下游函数都被调用一次时的总成本
+    pub total_cost: f64,
+    /// 最慢调用路径（函数名）
+    pub critical_path: Vec<String>,
+}
+
+#[derive(Debug, Clone, Default)]
+struct Rollup {
+    worst_path: f64,
+    total_latency: f64,
+    total_cost: f64,
+    next_on_path: Option<Uuid>,
+}
+
+/// 延迟预算汇总分析
+pub struct LatencyBudgetAnalyzer<'a> {
+    graph: &'a PetCodeGraph,
+    own: HashMap<Uuid, (f64, f64)>,
+    memo: HashMap<Uuid, Rollup>,
+}
+
+impl<'a> LatencyBudgetAnalyzer<'a> {
+    pub fn new(graph: &'a PetCodeGraph, config: &BudgetConfig) -> Self {
+        let mut own = HashMap::new();
+        for function in graph.get_all_functions() {
+            for annotation in &config.annotations {
+                if BudgetConfig::matches(annotation, function) {
+                    let entry = own.entry(function.id).or_insert((0.0, 0.0));
+                    entry.0 += annotation.latency_ms;
+                    entry.1 += annotation.cost;
+                }
+            }
+        }
+        Self { graph, own, memo: HashMap::new() }
+    }
+
+    /// 被注解匹配到的函数数量
+    pub fn annotated_functions(&self) -> usize {
+        self.own.len()
+    }
+
+    /// 汇总指定入口的预算；未指定入口时使用所有没有调用者的函数
+    pub fn rollup(&mut self, entry_points: Option<&[String]>) -> Vec<RolledUpBudget> {
+        let entries: Vec<Uuid> = match entry_points {
+            Some(names) => names
+                .iter()
+                .flat_map(|name| self.graph.find_functions_by_name(name))
+                .map(|f| f.id)
+                .collect(),
+            None => self
+                .graph
+                .get_all_functions()
+                .into_iter()
+                .filter(|f| !f.is_unresolved() && self.graph.get_callers(&f.id).is_empty())
+                .map(|f| f.id)
+                .collect(),
+        };
+
+        let mut results: Vec<RolledUpBudget> = entries
+            .into_iter()
+            .filter_map(|id| {
+                let rollup = self.compute(id, &mut HashSet::new());
+                let function = self.graph.get_function_by_id(&id)?;
+                Some(RolledUpBudget {
+                    function_id: id,
+                    function_name: function.name.clone(),
+                    file_path: function.file_path.display().to_string(),
+                    worst_path_latency_ms: rollup.worst_path,
+                    total_latency_ms: rollup.total_latency,
+                    total_cost: rollup.total_cost,
+                    critical_path: self.critical_path(id),
+                })
+            })
+            .collect();
+
+        results.sort_by(|a, b| {
+            b.worst_path_latency_ms
+                .partial_cmp(&a.worst_path_latency_ms)
+                .unwrap_or(std::cmp::Ordering::Equal)
+        });
+        results
+    }
+
+    /// 深度优先汇总，递归栈上的函数（环）按零贡献处理
+    fn compute(&mut self, id: Uuid, on_stack: &mut HashSet<Uuid>) -> Rollup {
+        if let Some(done) = self.memo.get(&id) {
+            return done.clone();
+        }
+        if !on_stack.insert(id) {
+            return Rollup::default();
+        }
+
+        let (own_latency, own_cost) = self.own.get(&id).copied().unwrap_or((0.0, 0.0));
+        let mut rollup = Rollup {
+            worst_path: own_latency,
+            total_latency: own_latency,
+            total_cost: own_cost,
+            next_on_path: None,
+        };
+
+        let callees: HashSet<Uuid> = self.graph.get_callees(&id).into_iter().map(|(f, _)| f.id).collect();
+        let mut best_child: Option<(f64, Uuid)> = None;
+        for callee in callees {
+            let child = self.compute(callee, on_stack);
+            rollup.total_latency += child.total_latency;
+            rollup.total_cost += child.total_cost;
+            if best_child.is_none_or(|(worst, _)| child.worst_path > worst) {
+                best_child = Some((child.worst_path, callee));
+            }
+        }
+        if let Some((worst, callee)) = best_child {
+            rollup.worst_path += worst;
+            if worst > 0.0 {
+                rollup.next_on_path = Some(callee);
+            }
+        }
+
+        on_stack.remove(&id);
+        self.memo.insert(id, rollup.clone());
+        rollup
+    }
+
+    fn critical_path(&self, start: Uuid) -> Vec<String> {
+        let mut path = Vec::new();
+        let mut seen = HashSet::new();
+        let mut current = Some(start);
+        while let Some(id) = current {
+            if !seen.insert(id) {
+                break;
+            }
+            if let Some(function) = self.graph.get_function_by_id(&id) {
+                path.push(function.name.clone());
+            }
+            current = self.memo.get(&id).and_then(|r| r.next_on_path);
+        }
+        path
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn function(name: &str) -> FunctionInfo {
+        FunctionInfo {
+            id: Uuid::new_v4(),
+            name: name.to_string(),
+            file_path: PathBuf::from("src/service.rs"),
+            line_start: 1,
+            line_end: 2,
+            namespace: "crate".to_string(),
+            language: "rust".to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_rollup_to_entry_point() {
+        let mut graph = PetCodeGraph::new();
+        let handler = function("handle_order");
+        let load = function("load_user");
+        let query = function("db_query");
+        let charge = function("charge_card");
+        for f in [&handler, &load, &query, &charge] {
+            graph.add_function(f.clone());
+        }
+        graph.add_call(&handler, &load);
+        graph.add_call(&handler, &charge);
+        graph.add_call(&load, &query);
+
+        let config: BudgetConfig = toml::from_str(
+            r#"
+            [[annotation]]
+            function = "db_query"
+            latency_ms = 12.0
+            cost = 0.5
+
+            [[annotation]]
+            function = "charge_card"
+            file = "service.rs"
+            latency_ms = 80.0
+            "#,
+        )
+        .unwrap();
+
+        let mut analyzer = LatencyBudgetAnalyzer::new(&graph, &config);
+        assert_eq!(analyzer.annotated_functions(), 2);
+        let budgets = analyzer.rollup(None);
+        assert_eq!(budgets.len(), 1);
+        assert_eq!(budgets[0].function_name, "handle_order");
+        assert_eq!(budgets[0].worst_path_latency_ms, 80.0);
+        assert_eq!(budgets[0].total_latency_ms, 92.0);
+        assert_eq!(budgets[0].total_cost, 0.5);
+        assert_eq!(budgets[0].critical_path, vec!["handle_order", "charge_card"]);
+    }
+}
@@ -0,0 +1,648 @@
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use tracing::{debug, warn};
+use uuid::Uuid;
+
+use crate::codegraph::entrypoints::detect_entry_points;
+use crate::codegraph::parser::CodeParser;
+use crate::codegraph::AnalyzeOptions;
+use crate::codegraph::types::PetCodeGraph;
+use crate::codegraph::treesitter::parsers::{get_ast_parser_by_filename, get_tree_sitter_language};
+use crate::codegraph::treesitter::{FunctionDeclaration, SymbolType};
+use crate::services::context_budget::SymbolClassifier;
+use crate::services::risk::is_test_function;
+use crate::services::policy::Severity;
+use crate::services::suppression::apply_inline_suppressions;
+
+/// 会增加嵌套层级的控制流节点类型（覆盖所有已支持语言的语法）
+const NESTING_NODE_KINDS: &[&str] = &[
+    "if_statement",
+    "if_expression",
+    "if_let_expression",
+    "for_statement",
+    "for_expression",
+    "for_in_statement",
+    "for_range_loop",
+    "enhanced_for_statement",
+    "while_statement",
+    "while_expression",
+    "do_statement",
+    "loop_expression",
+    "match_expression",
+    "switch_statement",
+    "switch_expression",
+    "expression_switch_statement",
+    "type_switch_statement",
+    "select_statement",
+    "try_statement",
+    "with_statement",
+];
+
+/// 每出现一次就增加一条独立路径的分支节点类型（用于圈复杂度）
+const DECISION_NODE_KINDS: &[&str] = &[
+    "if_statement",
+    "if_expression",
+    "if_let_expression",
+    "elif_clause",
+    "for_statement",
+    "for_expression",
+    "for_in_statement",
+    "for_range_loop",
+    "enhanced_for_statement",
+    "while_statement",
+    "while_expression",
+    "do_statement",
+    "loop_expression",
+    "match_arm",
+    "switch_case",
+    "switch_label",
+    "case_statement",
+    "expression_case",
+    "type_case",
+    "communication_case",
+    "catch_clause",
+    "except_clause",
+    "conditional_expression",
+    "ternary_expression",
+];
+
+/// Lint规则
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum LintRule {
+    /// 函数过长
+    FunctionLength,
+    /// 参数过多
+    ParameterCount,
+    /// 嵌套过深
+    NestingDepth,
+    /// 没有调用者且未导出的函数
+    DeadCode,
+    /// 函数间的循环调用
+    Cycle,
+}
+
+impl LintRule {
+    pub fn id(&self) -> &'static str {
+        match self {
+            LintRule::FunctionLength => "function-length",
+            LintRule::ParameterCount => "parameter-count",
+            LintRule::NestingDepth => "nesting-depth",
+            LintRule::DeadCode => "dead-code",
+            LintRule::Cycle => "cycle",
+        }
+    }
+
+    pub fn description(&self) -> &'static str {
+        match self {
+            LintRule::FunctionLength => "Function body spans too many lines",
+            LintRule::ParameterCount => "Function declares too many parameters",
+            LintRule::NestingDepth => "Control flow is nested too deeply",
+            LintRule::DeadCode => "Function is never called and is not exported or an entry point",
+            LintRule::Cycle => "Function is part of a call cycle spanning several functions",
+        }
+    }
+
+    pub fn all() -> [LintRule; 5] {
+        [
+            LintRule::FunctionLength,
+            LintRule::ParameterCount,
+            LintRule::NestingDepth,
+            LintRule::DeadCode,
+            LintRule::Cycle,
+        ]
+    }
+}
+
+/// Lint阈值配置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LintConfig {
+    /// 函数最大行数
+    pub max_lines: usize,
+    /// 最大参数个数
+    pub max_params: usize,
+    /// 最大嵌套深度
+    pub max_nesting: usize,
+    /// 是否构建调用图并检查 dead-code 与 cycle 规则
+    #[serde(default)]
+    pub graph_rules: bool,
+}
+
+impl Default for LintConfig {
+    fn default() -> Self {
+        Self {
+            max_lines: 80,
+            max_params: 5,
+            max_nesting: 4,
+            graph_rules: false,
+        }
+    }
+}
+
+/// 单条Lint结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LintFinding {
+    pub rule: LintRule,
+    pub function_name: String,
+    pub file_path: PathBuf,
+    pub line_start: usize,
+    pub line_end: usize,
+    pub value: usize,
+    pub threshold: usize,
+    pub message: String,
+    /// 策略文件确定的级别，未使用策略时为 warn
+    #[serde(default)]
+    pub severity: Severity,
+}
+
+/// Lint报告
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct LintReport {
+    pub files_scanned: usize,
+    pub functions_checked: usize,
+    pub findings: Vec<LintFinding>,
+    /// 被行内 `codegraph:ignore` 注释抑制的结果数
+    #[serde(default)]
+    pub suppressed: usize,
+    /// 被基线文件过滤的结果数
+    #[serde(default)]
+    pub baselined: usize,
+    /// 被策略文件设为 ignore 的结果数
+    #[serde(default)]
+    pub ignored: usize,
+}
+
+/// 单个函数的结构度量
+#[derive(Debug, Clone)]
+struct FunctionMetrics {
+    name: String,
+    line_start: usize,
+    line_end: usize,
+    params: usize,
+    nesting: usize,
+    complexity: usize,
+}
+
+/// 基于AST的结构异味检查服务
+pub struct LintService {
+    config: LintConfig,
+}
+
+impl LintService {
+    pub fn new(config: LintConfig) -> Self {
+        Self { config }
+    }
+
+    /// 检查目录下的所有支持文件
+    pub fn lint_directory(&self, dir: &Path) -> Result<LintReport, String> {
+        if !dir.exists() {
+            return Err(format!("Path does not exist: {}", dir.display()));
+        }
+
+        let files = if dir.is_file() {
+            vec![dir.to_path_buf()]
+        } else {
+            let mut parser = CodeParser::new();
+            parser.scan_directory(dir)
+        };
+
+        let mut report = LintReport::default();
+        for file in files {
+            match self.lint_file(&file) {
+                Ok((checked, mut findings)) => {
+                    report.files_scanned += 1;
+                    report.functions_checked += checked;
+                    report.findings.append(&mut findings);
+                }
+                Err(e) => warn!("Skipping {}: {}", file.display(), e),
+            }
+        }
+
+        if self.config.graph_rules {
+            if dir.is_dir() {
+                // 调用边只在本次构建中生成，不复用增量缓存
+                let mut parser = CodeParser::with_options(AnalyzeOptions { force_rebuild: true, ..Default::default() });
+                let graph = parser.build_petgraph_code_graph(dir)?;
+                report.findings.extend(graph_findings(&graph));
+            } else {
+                warn!("Graph rules need a directory, skipping them for {}", dir.display());
+            }
+        }
+
+        let (findings, suppressed) = apply_inline_suppressions(std::mem::take(&mut report.findings));
+        report.findings = findings;
+        report.suppressed = suppressed;
+
+        report.findings.sort_by(|a, b| {
+            a.file_path
+                .cmp(&b.file_path)
+                .then(a.line_start.cmp(&b.line_start))
+                .then(a.rule.id().cmp(b.rule.id()))
+        });
+        Ok(report)
+    }
+
+    /// 检查单个文件，返回检查的函数数量和结果
+    pub fn lint_file(&self, file_path: &PathBuf) -> Result<(usize, Vec<LintFinding>), String> {
+        let code = fs::read_to_string(file_path)
+            .map_err(|e| format!("Failed to read file {}: {}", file_path.display(), e))?;
+        let metrics = collect_function_metrics(&code, file_path)?;
+        debug!("Collected {} functions from {}", metrics.len(), file_path.display());
+
+        let findings = metrics
+            .iter()
+            .flat_map(|m| self.check(m, file_path))
+            .collect();
+        Ok((metrics.len(), findings))
+    }
+
+    fn check(&self, metrics: &FunctionMetrics, file_path: &Path) -> Vec<LintFinding> {
+        let length = metrics.line_end - metrics.line_start + 1;
+        let checks = [
+            (LintRule::FunctionLength, length, self.config.max_lines, "lines"),
+            (LintRule::ParameterCount, metrics.params, self.config.max_params, "parameters"),
+            (LintRule::NestingDepth, metrics.nesting, self.config.max_nesting, "levels of nesting"),
+        ];
+
+        checks
+            .into_iter()
+            .filter(|(_, value, threshold, _)| value > threshold)
+            .map(|(rule, value, threshold, unit)| LintFinding {
+                rule,
+                function_name: metrics.name.clone(),
+                file_path: file_path.to_path_buf(),
+                line_start: metrics.line_start,
+                line_end: metrics.line_end,
+                value,
+                threshold,
+                message: format!(
+                    "Function '{}' has {} {} (threshold {})",
+                    metrics.name, value, unit, threshold
+                ),
+                severity: Severity::Warn,
+            })
+            .collect()
+    }
+}
+
+/// 基于调用图的规则：未被调用的内部函数（dead-code）和多函数循环调用（cycle）。
+/// 框架注册的路由、定时任务等入口由框架调用，不算未被调用
+pub fn graph_findings(graph: &PetCodeGraph) -> Vec<LintFinding> {
+    let mut findings = Vec::new();
+    let entry_points: HashSet<Uuid> = detect_entry_points(graph).into_iter().map(|entry| entry.function_id).collect();
+
+    let classifier = SymbolClassifier::new();
+    let mut sources: HashMap<PathBuf, Vec<String>> = HashMap::new();
+    for function in graph.graph.node_weights() {
+        if function.is_unresolved()
+            || is_test_function(function)
+            || !graph.get_callers(&function.id).is_empty()
+            || entry_points.contains(&function.id)
+        {
+            continue;
+        }
+        let lines = sources.entry(function.file_path.clone()).or_insert_with(|| {
+            fs::read_to_string(&function.file_path)
+                .map(|code| code.lines().map(str::to_string).collect())
+                .unwrap_or_default()
+        });
+        // 读不到源码时无法判断可见性，保守地不报告
+        let Some(declaration) = lines.get(function.line_start.saturating_sub(1)..function.line_end.min(lines.len()))
+        else {
+            continue;
+        };
+        if declaration.is_empty() || classifier.is_public(&function.language, &declaration.join("\n")) {
+            continue;
+        }
+        findings.push(LintFinding {
+            rule: LintRule::DeadCode,
+            function_name: function.name.clone(),
+            file_path: function.file_path.clone(),
+            line_start: function.line_start,
+            line_end: function.line_end,
+            value: 0,
+            threshold: 0,
+            message: format!("Function '{}' is never called and is not exported", function.name),
+            severity: Severity::Warn,
+        });
+    }
+
+    for component in graph.strongly_connected_components() {
+        if component.len() < 2 {
+            continue;
+        }
+        let mut members: Vec<_> = component.iter().filter_map(|&index| graph.get_function(index)).collect();
+        members.sort_by(|a, b| a.name.cmp(&b.name));
+        for function in &members {
+            let others: Vec<&str> = members
+                .iter()
+                .filter(|other| other.id != function.id)
+                .map(|other| other.name.as_str())
+                .collect();
+            findings.push(LintFinding {
+                rule: LintRule::Cycle,
+                function_name: function.name.clone(),
+                file_path: function.file_path.clone(),
+                line_start: function.line_start,
+                line_end: function.line_end,
+                value: members.len(),
+                threshold: 1,
+                message: format!(
+                    "Function '{}' is part of a call cycle with {}",
+                    function.name,
+                    others.join(", ")
+                ),
+                severity: Severity::Warn,
+            });
+        }
+    }
+    findings
+}
+
+/// 解析文件并收集每个函数的长度、参数个数与嵌套深度
+fn collect_function_metrics(code: &str, file_path: &PathBuf) -> Result<Vec<FunctionMetrics>, String> {
+    let (mut ast_parser, language_id) =
+        get_ast_parser_by_filename(file_path).map_err(|e| e.message)?;
+    let symbols = ast_parser.parse(code, file_path);
+
+    let tree = get_tree_sitter_language(language_id).and_then(|language| {
+        let mut ts_parser = tree_sitter::Parser::new();
+        ts_parser.set_language(&language).ok()?;
+        ts_parser.parse(code, None)
+    });
+
+    let mut metrics = Vec::new();
+    for symbol in symbols {
+        let mut guard = symbol.write();
+        if guard.symbol_type() != SymbolType::FunctionDeclaration {
+            continue;
+        }
+        let range = *guard.full_range();
+        let name = guard.name().to_string();
+        let params = guard
+            .as_any_mut()
+            .downcast_ref::<FunctionDeclaration>()
+            .map(|decl| decl.args.len())
+            .unwrap_or(0);
+        let node = tree
+            .as_ref()
+            .and_then(|t| t.root_node().descendant_for_byte_range(range.start_byte, range.end_byte));
+        let nesting = node.map(|node| max_nesting_depth(node, 0)).unwrap_or(0);
+        let complexity = 1 + node.map(|node| count_decisions(node, code)).unwrap_or(0);
+
+        metrics.push(FunctionMetrics {
+            name,
+            line_start: range.start_point.row + 1,
+            line_end: range.end_point.row + 1,
+            params,
+            nesting,
+            complexity,
+        });
+    }
+    Ok(metrics)
+}
+
+/// 文件中每个函数的圈复杂度，键为 (函数名, 起始行)
+pub fn function_complexity(file_path: &PathBuf) -> Result<HashMap<(String, usize), usize>, String> {
+    let code = fs::read_to_string(file_path)
+        .map_err(|e| format!("Failed to read file {}: {}", file_path.display(), e))?;
+    Ok(collect_function_metrics(&code, file_path)?
+        .into_iter()
+        .map(|m| ((m.name, m.line_start), m.complexity))
+        .collect())
+}
+
+/// 统计节点下的分支数：分支语句、case 分支以及短路逻辑运算符
+fn count_decisions(node: tree_sitter::Node, code: &str) -> usize {
+    let mut cursor = node.walk();
+    let mut count = 0;
+    for child in node.children(&mut cursor) {
+        let kind = child.kind();
+        if DECISION_NODE_KINDS.contains(&kind) {
+            count += 1;
+        } else if matches!(kind, "binary_expression" | "boolean_operator") {
+            let operator = child
+                .child_by_field_name("operator")
+                .and_then(|op| code.get(op.byte_range()));
+            if matches!(operator, Some("&&" | "||" | "and" | "or")) {
+                count += 1;
+            }
+        }
+        count += count_decisions(child, code);
+    }
+    count
+}
+
+/// 计算节点下控制流的最大嵌套深度，`else if` 链不额外计数
+fn max_nesting_depth(node: tree_sitter::Node, depth: usize) -> usize {
+    let mut cursor = node.walk();
+    let mut max_depth = depth;
+    for child in node.children(&mut cursor) {
+        let child_depth = if is_nesting_node(&child) { depth + 1 } else { depth };
+        max_depth = max_depth.max(max_nesting_depth(child, child_depth));
+    }
+    max_depth
+}
+
+fn is_nesting_node(node: &tree_sitter::Node) -> bool {
+    if !NESTING_NODE_KINDS.contains(&node.kind()) {
+        return false;
+    }
+    if node.kind().starts_with("if_") {
+        if let Some(parent) = node.parent() {
+            if parent.kind() == "else_clause" || parent.kind().starts_with("if_") {
+                return false;
+            }
+        }
+    }
+    true
+}
+
+impl LintReport {
+    /// 按规则统计结果数量
+    pub fn counts_by_rule(&self) -> HashMap<LintRule, usize> {
+        let mut counts = HashMap::new();
+        for finding in &self.findings {
+            *counts.entry(finding.rule).or_insert(0) += 1;
+        }
+        counts
+    }
+
+    /// 级别为 error 的结果数
+    pub fn error_count(&self) -> usize {
+        self.findings.iter().filter(|f| f.severity == Severity::Error).count()
+    }
+
+    /// 生成可读文本报告
+    pub fn to_text(&self) -> String {
+        self.to_text_with(|file, line| format!("{}:{}", file.display(), line))
+    }
+
+    /// 生成可读文本报告，由 `location` 格式化每条结果的文件位置
+    pub fn to_text_with(&self, location: impl Fn(&Path, usize) -> String) -> String {
+        let mut out = String::new();
+        for finding in &self.findings {
+            out.push_str(&format!(
+                "{}: {} [{}] {}\n",
+                location(&finding.file_path, finding.line_start),
+                finding.severity.as_str(),
+                finding.rule.id(),
+                finding.message
+            ));
+        }
+        out.push_str(&format!(
+            "\n{} finding(s) in {} function(s) across {} file(s)\n",
+            self.findings.len(),
+            self.functions_checked,
+            self.files_scanned
+        ));
+        let errors = self.error_count();
+        if errors > 0 {
+            out.push_str(&format!("{} error(s)\n", errors));
+        }
+        if self.suppressed > 0 || self.baselined > 0 || self.ignored > 0 {
+            out.push_str(&format!(
+                "{} suppressed inline, {} matched the baseline, {} ignored by policy\n",
+                self.suppressed, self.baselined, self.ignored
+            ));
+        }
+        out
+    }
+
+    /// 生成SARIF 2.1.0格式报告
+    pub fn to_sarif(&self) -> serde_json::Value {
+        let rules: Vec<serde_json::Value> = LintRule::all()
+            .iter()
+            .map(|rule| {
+                json!({
+                    "id": rule.id(),
+                    "shortDescription": { "text": rule.description() },
+                })
+            })
+            .collect();
+
+        let results: Vec<serde_json::Value> = self
+            .findings
+            .iter()
+            .map(|finding| {
+                json!({
+                    "ruleId": finding.rule.id(),
+                    "level": finding.severity.sarif_level(),
+                    "message": { "text": finding.message },
+                    "locations": [{
+                        "physicalLocation": {
+                            "artifactLocation": { "uri": finding.file_path.display().to_string() },
+                            "region": {
+                                "startLine": finding.line_start,
+                                "endLine": finding.line_end,
+                            }
+                        }
+                    }]
+                })
+            })
+            .collect();
+
+        json!({
+            "$schema": "https://json.schemastore.org/sarif-2.1.0.json",
+            "version": "2.1.0",
+            "runs": [{
+                "tool": {
+                    "driver": {
+                        "name": "codegraph",
+                        "version": env!("CARGO_PKG_VERSION"),
+                        "rules": rules,
+                    }
+                },
+                "results": results,
+            }]
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    const SAMPLE: &str = r#"
+fn small(a: i32) -> i32 {
+    a + 1
+}
+
+fn wide(a: i32, b: i32, c: i32, d: i32) -> i32 {
+    if a > 0 {
+        for _ in 0..b {
+            if c > 0 {
+                return d;
+            } else if d > 0 {
+                return c;
+            }
+        }
+    }
+    0
+}
+"#;
+
+    #[test]
+    fn test_lint_thresholds() {
+        let temp_dir = TempDir::new().unwrap();
+        let file = temp_dir.path().join("sample.rs");
+        fs::write(&file, SAMPLE).unwrap();
+
+        let service = LintService::new(LintConfig { max_lines: 10, max_params: 3, max_nesting: 2, ..Default::default() });
+        let report = service.lint_directory(temp_dir.path()).unwrap();
+
+        assert_eq!(report.files_scanned, 1);
+        assert_eq!(report.functions_checked, 2);
+        let counts = report.counts_by_rule();
+        assert_eq!(counts.get(&LintRule::FunctionLength), Some(&1));
+        assert_eq!(counts.get(&LintRule::ParameterCount), Some(&1));
+        assert_eq!(counts.get(&LintRule::NestingDepth), Some(&1));
+
+        let nesting = report.findings.iter().find(|f| f.rule == LintRule::NestingDepth).unwrap();
+        assert_eq!(nesting.function_name, "wide");
+        assert_eq!(nesting.value, 3);
+
+        let sarif = report.to_sarif();
+        assert_eq!(sarif["runs"][0]["results"].as_array().unwrap().len(), 3);
+
+        // wide: 两个 if、一个 for、一个 else if
+        let complexity = function_complexity(&file).unwrap();
+        assert_eq!(complexity.get(&("small".to_string(), 2)), Some(&1));
+        assert_eq!(complexity.get(&("wide".to_string(), 6)), Some(&5));
+    }
+
+    #[test]
+    fn test_graph_rules_and_inline_suppression() {
+        let temp_dir = TempDir::new().unwrap();
+        let file = temp_dir.path().join("lib.rs");
+        fs::write(
+            &file,
+            "pub fn run() {\n    ping(3);\n}\n\nfn ping(n: u32) {\n    pong(n);\n}\n\nfn pong(n: u32) {\n    ping(n);\n}\n\nfn unused() {}\n\n#[get(\"/health\")]\nasync fn health() {}\n\n// codegraph:ignore dead-code kept for the plugin loader\nfn loaded_dynamically() {}\n",
+        )
+        .unwrap();
+        let options = AnalyzeOptions {
+            storage_dir: Some(temp_dir.path().join(".codegraph_db")),
+            force_rebuild: true,
+            ..Default::default()
+        };
+        let graph = CodeParser::with_options(options).build_petgraph_code_graph(temp_dir.path()).unwrap();
+
+        let findings = graph_findings(&graph);
+        let names = |rule: LintRule| -> Vec<&str> {
+            findings.iter().filter(|f| f.rule == rule).map(|f| f.function_name.as_str()).collect()
+        };
+        let mut dead = names(LintRule::DeadCode);
+        dead.sort();
+        assert_eq!(dead, vec!["loaded_dynamically", "unused"]);
+        assert_eq!(names(LintRule::Cycle), vec!["ping", "pong"]);
+
+        let (kept, suppressed) = apply_inline_suppressions(findings);
+        assert_eq!(suppressed, 1);
+        assert!(kept.iter().all(|f| f.function_name != "loaded_dynamically"));
+    }
+}
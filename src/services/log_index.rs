@@ -0,0 +1,183 @@
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::codegraph::types::PetCodeGraph;
+use crate::services::source_scan::for_each_source_line;
+
+/// 模板中至少需要的字面字符数，过短的模板会匹配任意日志
+const MIN_LITERAL_CHARS: usize = 4;
+
+/// 源码中的一条日志语句
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LogStatement {
+    /// 原始字符串字面量（去掉引号）
+    pub template: String,
+    /// 日志级别，无法判断时为 "print"
+    pub level: String,
+    pub file_path: String,
+    pub line: usize,
+    pub function_id: Option<Uuid>,
+    pub function_name: Option<String>,
+    /// 模板中的字面字符数，用于匹配打分
+    pub literal_chars: usize,
+    #[serde(skip)]
+    matcher: Option<Regex>,
+}
+
+/// 日志行匹配结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LogMatch {
+    pub statement: LogStatement,
+    pub score: f64,
+    /// 发出日志的函数的上游调用者 (函数名, 文件, 距离)
+    pub callers: Vec<(String, String, usize)>,
+}
+
+/// 日志语句索引
+#[derive(Debug, Clone, Default)]
+pub struct LogIndex {
+    pub statements: Vec<LogStatement>,
+}
+
+fn log_call_regex() -> Regex {
+    Regex::new(
+        r#"(?x)
+        (?:\b(?:log|logger|logging|LOG|LOGGER|Log|tracing|console|slog|zap|System\.out|System\.err|fmt|self\.logger|self\.log)\s*(?:\.|::)\s*)?
+        \b(trace|debug|info|warn|warning|error|fatal|critical|exception|log|print|println|eprintln|eprint|printf|Printf|Println|Print|Errorf|Infof|Warnf|Debugf|Fatalf|Info|Warn|Error|Debug|Fatal|puts)
+        !?\s*\(\s*
+        (?:[A-Za-z_][\w.]*\s*=\s*[^,]+,\s*)*
+        [fr]?(?:"((?:[^"\\]|\\.)*)"|'((?:[^'\\]|\\.)*)'|`([^`]*)`)
+        "#,
+    )
+    .unwrap()
+}
+
+fn placeholder_regex() -> Regex {
+    Regex::new(r"\$\{[^}]*\}|\{[^{}]*\}|%[-+ #0]*\d*(?:\.\d+)?[sdifvqxXoeEgGpTr]").unwrap()
+}
+
+fn level_of(method: &str) -> String {
+    match method.to_lowercase().trim_end_matches('f') {
+        "trace" => "trace",
+        "debug" => "debug",
+        "info" | "log" => "info",
+        "warn" | "warning" => "warn",
+        "error" | "exception" | "eprintln" | "eprint" => "error",
+        "fatal" | "critical" => "fatal",
+        _ => "print",
+    }
+    .to_string()
+}
+
+/// 将日志模板编译为匹配正则，占位符匹配任意文本
+fn compile_template(template: &str, placeholders: &Regex) -> (Option<Regex>, usize) {
+    let mut pattern = String::new();
+    let mut literal_chars = 0;
+    let mut last = 0;
+    for m in placeholders.find_iter(template) {
+        let literal = &template[last..m.start()];
+        literal_chars += literal.trim().len();
+        pattern.push_str(&regex::escape(literal));
+        pattern.push_str(".*?");
+        last = m.end();
+    }
+    let tail = &template[last..];
+    literal_chars += tail.trim().len();
+    pattern.push_str(&regex::escape(tail));
+
+    if literal_chars < MIN_LITERAL_CHARS {
+        return (None, literal_chars);
+    }
+    (Regex::new(&pattern).ok(), literal_chars)
+}
+
+impl LogIndex {
+    /// 扫描图中所有源文件，收集带字符串字面量的日志调用
+    pub fn build(graph: &PetCodeGraph) -> Self {
+        let call_regex = log_call_regex();
+        let placeholders = placeholder_regex();
+        let mut statements = Vec::new();
+
+        for_each_source_line(graph, |file_path, line_number, line, function| {
+            for captures in call_regex.captures_iter(line) {
+                let template = captures
+                    .get(2)
+                    .or_else(|| captures.get(3))
+                    .or_else(|| captures.get(4))
+                    .map(|m| m.as_str().to_string())
+                    .unwrap_or_default();
+                let (matcher, literal_chars) = compile_template(&template, &placeholders);
+                statements.push(LogStatement {
+                    template,
+                    level: level_of(&captures[1]),
+                    file_path: file_path.display().to_string(),
+                    line: line_number,
+                    function_id: function.map(|f| f.id),
+                    function_name: function.map(|f| f.name.clone()),
+                    literal_chars,
+                    matcher,
+                });
+            }
+        });
+
+        Self { statements }
+    }
+
+    /// 将生产环境中的一行日志映射回发出它的日志语句，按匹配程度降序
+    pub fn find_origin(&self, graph: &PetCodeGraph, log_line: &str, caller_depth: usize, limit: usize) -> Vec<LogMatch> {
+        let line_len = log_line.trim().len().max(1) as f64;
+        let mut matches: Vec<LogMatch> = self
+            .statements
+            .iter()
+            .filter(|s| s.matcher.as_ref().is_some_and(|re| re.is_match(log_line)))
+            .map(|statement| {
+                let callers = statement
+                    .function_id
+                    .map(|id| {
+                        graph
+                            .get_callers_within(&id, caller_depth)
+                            .into_iter()
+                            .map(|(f, depth)| (f.name.clone(), f.file_path.display().to_string(), depth))
+                            .collect()
+                    })
+                    .unwrap_or_default();
+                LogMatch {
+                    statement: statement.clone(),
+                    score: (statement.literal_chars as f64 / line_len).min(1.0),
+                    callers,
+                }
+            })
+            .collect();
+
+        matches.sort_by(|a, b| b.score.partial_cmp(&a.score).unwrap_or(std::cmp::Ordering::Equal));
+        matches.truncate(limit);
+        matches
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_template_matching() {
+        let call_regex = log_call_regex();
+        let placeholders = placeholder_regex();
+
+        let rust = r#"    tracing::warn!("Failed to add call relation: {}", e);"#;
+        let caps = call_regex.captures(rust).unwrap();
+        assert_eq!(&caps[1], "warn");
+        let (matcher, _) = compile_template(&caps[2], &placeholders);
+        assert!(matcher.unwrap().is_match("2024-01-01T00:00:00Z WARN Failed to add call relation: missing node"));
+
+        let python = r#"        logger.error("user %s not found in %d ms", user, took)"#;
+        let caps = call_regex.captures(python).unwrap();
+        assert_eq!(level_of(&caps[1]), "error");
+        let (matcher, _) = compile_template(&caps[2], &placeholders);
+        assert!(matcher.unwrap().is_match("ERROR user alice not found in 12 ms"));
+
+        let (matcher, _) = compile_template("{}", &placeholders);
+        assert!(matcher.is_none());
+    }
+}
@@ -1,5 +1,68 @@
 pub mod snippet_service;
 pub mod analyzer;
+pub mod lint;
+pub mod suppression;
+pub mod policy;
+pub mod refactor;
+pub mod risk;
+pub mod coverage;
+pub mod reviewers;
+pub mod stacktrace;
+pub mod explain;
+pub mod source_scan;
+pub mod log_index;
+pub mod feature_flags;
+pub mod concurrency;
+pub mod latency;
+pub mod profile;
+pub mod traces;
+pub mod observed_calls;
+pub mod rename;
+pub mod graph_builder;
+pub mod hooks;
+pub mod skeleton;
+pub mod context_budget;
+pub mod synthetic;
+pub mod graph_export;
+pub mod knowledge_base;
+pub mod report;
+pub mod slice;
+pub mod pr_index;
+pub mod refresh;
+pub mod unresolved;
+#[cfg(feature = "parquet")]
+pub mod parquet_export;
 
 pub use snippet_service::SnippetService;
-pub use analyzer::CodeAnalyzer; 
\ No newline at end of file
+pub use analyzer::CodeAnalyzer;
+pub use lint::{LintService, LintConfig, LintReport};
+pub use suppression::{Baseline, InlineSuppressions};
+pub use policy::{Policy, Severity};
+pub use refactor::{RefactorAdvisor, RefactorConfig, RefactorSuggestion};
+pub use risk::{RiskScorer, RiskWeights, FunctionRisk};
+pub use coverage::{analyze_coverage, CoverageData, CoverageFormat, CoverageReport};
+pub use reviewers::{suggest_reviewers, OwnershipData, ReviewerReport};
+pub use stacktrace::explain_stack_trace;
+pub use explain::{explain_function, ExplainOptions, FunctionExplanation};
+pub use log_index::LogIndex;
+pub use feature_flags::FeatureFlagIndex;
+pub use concurrency::{ConcurrencyReport, PatternCatalog};
+pub use latency::{BudgetConfig, LatencyBudgetAnalyzer};
+pub use profile::{import_profile, ProfileFormat, ProfileImport};
+pub use traces::{parse_trace_export, replay_traces, trace_annotations, TraceReplay};
+pub use observed_calls::{merge_observed_calls, parse_observed_calls, ObservedCallsReport};
+pub use rename::{preview_rename, RenameKind, RenameOptions, RenamePreview};
+pub use graph_builder::{build_and_store, BuildError, BuildOutcome};
+pub use hooks::{AnalyzeResult, PostAnalysisHook, PostAnalysisHooks};
+pub use skeleton::{build_skeleton, cached_skeleton, cached_skeletons};
+pub use context_budget::{assemble_context, ContextBudget, ContextFile};
+pub use synthetic::{generate_repository, SyntheticRepoConfig, SyntheticRepoSummary};
+pub use graph_export::{export_graph, ExportFormat, NodeMetrics};
+pub use knowledge_base::{write_knowledge_base, KnowledgeBaseSummary};
+pub use report::{write_report, ReportSummary};
+pub use slice::{slice_graph, SliceAttributes, SliceExpr};
+pub use pr_index::{index_pull_request, pr_project_id, pull_request_from_env, PrIndexOutcome};
+pub use refresh::{refresh_stored_file, GraphDelta, RefreshOutcome};
+pub use unresolved::{triage_unresolved_calls, UnresolvedCallsReport};
+#[cfg(feature = "parquet")]
+pub use parquet_export::write_parquet_tables;
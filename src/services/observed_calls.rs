@@ -0,0 +1,299 @@
+use std::collections::{BTreeSet, HashMap};
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use uuid::Uuid;
+
+use crate::codegraph::types::{FunctionInfo, ObservedCallMerge, PetCodeGraph};
+use crate::services::stacktrace::{resolve_frame, StackFrame};
+use crate::storage::annotations::FunctionIndex;
+
+/// 插桩记录中调用的一端：函数名/限定名，或者文件+行号
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct CallEndpoint {
+    pub symbol: Option<String>,
+    pub file: Option<String>,
+    pub line: Option<usize>,
+}
+
+impl CallEndpoint {
+    /// `src/app.py:42` 形式按位置解析，其余按函数名/限定名解析
+    fn parse(text: &str) -> Option<Self> {
+        let text = text.trim();
+        if text.is_empty() {
+            return None;
+        }
+        if let Some((file, line)) = text.rsplit_once(':').filter(|(file, _)| file.contains(['.', '/', '\\'])) {
+            if let Ok(line) = line.parse() {
+                return Some(Self { symbol: None, file: Some(file.to_string()), line: Some(line) });
+            }
+        }
+        Some(Self { symbol: Some(text.to_string()), file: None, line: None })
+    }
+
+    fn from_json(value: &Value) -> Option<Self> {
+        match value {
+            Value::String(text) => Self::parse(text),
+            Value::Object(map) => {
+                let text = |keys: &[&str]| keys.iter().find_map(|key| map.get(*key)?.as_str()).map(str::to_string);
+                let endpoint = Self {
+                    symbol: text(&["function", "symbol", "name"]),
+                    file: text(&["file", "filepath", "path"]),
+                    line: ["line", "lineno"].iter().find_map(|key| map.get(*key)?.as_u64()).map(|l| l as usize),
+                };
+                (endpoint.symbol.is_some() || endpoint.file.is_some()).then_some(endpoint)
+            }
+            _ => None,
+        }
+    }
+
+    fn describe(&self) -> String {
+        match (&self.symbol, &self.file, self.line) {
+            (Some(symbol), _, _) => symbol.clone(),
+            (None, Some(file), Some(line)) => format!("{}:{}", file, line),
+            (None, Some(file), None) => file.clone(),
+            (None, None, _) => String::new(),
+        }
+    }
+}
+
+/// 插桩观测到的一对调用者/被调用者
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ObservedCall {
+    pub caller: CallEndpoint,
+    pub callee: CallEndpoint,
+    pub count: u64,
+}
+
+/// 合并到图中的一条观测边
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ObservedEdge {
+    pub caller_id: Uuid,
+    pub caller: String,
+    pub caller_file: String,
+    pub callee_id: Uuid,
+    pub callee: String,
+    pub callee_file: String,
+    pub count: u64,
+    pub merge: ObservedCallMerge,
+}
+
+/// 合并结果
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ObservedCallsReport {
+    /// 记录中的调用对数（合并重复项之前）
+    pub total_pairs: usize,
+    /// 静态分析已发现的调用
+    pub confirmed: usize,
+    /// 由未解析升级为已解析的调用
+    pub upgraded: usize,
+    /// 静态分析遗漏、新增的调用
+    pub added: usize,
+    /// 按观测次数降序
+    pub edges: Vec<ObservedEdge>,
+    /// 无法唯一映射到函数的调用端（去重）
+    pub unmapped: Vec<String>,
+}
+
+fn json_count(item: &Value) -> u64 {
+    ["count", "calls", "hits"].iter().find_map(|key| item.get(*key)?.as_u64()).unwrap_or(1)
+}
+
+fn parse_json_calls(root: &Value) -> Result<Vec<ObservedCall>, String> {
+    let items = root
+        .as_array()
+        .or_else(|| root.get("calls").and_then(Value::as_array))
+        .ok_or("Observed calls must be an array or an object with a `calls` array")?;
+    Ok(items
+        .iter()
+        .filter_map(|item| {
+            Some(ObservedCall {
+                caller: CallEndpoint::from_json(item.get("caller")?)?,
+                callee: CallEndpoint::from_json(item.get("callee")?)?,
+                count: json_count(item),
+            })
+        })
+        .collect())
+}
+
+/// 文本行：`caller -> callee [count]`，或以空白/逗号分隔的 `caller callee [count]`
+fn parse_text_line(line: &str) -> Option<ObservedCall> {
+    let (caller, rest) = match line.split_once("->") {
+        Some((caller, rest)) => (caller.trim(), rest.trim()),
+        None => line.split_once([',', '\t', ' ']).map(|(caller, rest)| (caller.trim(), rest.trim()))?,
+    };
+    let mut fields: Vec<&str> = rest.split([',', '\t', ' ']).filter(|s| !s.is_empty()).collect();
+    let count = match fields.last().and_then(|last| last.parse().ok()) {
+        Some(count) if fields.len() > 1 => {
+            fields.pop();
+            count
+        }
+        _ => 1,
+    };
+    Some(ObservedCall {
+        caller: CallEndpoint::parse(caller)?,
+        callee: CallEndpoint::parse(fields.first()?)?,
+        count,
+    })
+}
+
+/// 解析插桩输出的调用记录：JSON（数组或 `{"calls": [...]}`，两端可以是字符串或
+/// `{function, file, line}` 对象）或每行一对的文本，`#` 开头的行为注释
+pub fn parse_observed_calls(content: &str) -> Result<Vec<ObservedCall>, String> {
+    let trimmed = content.trim_start();
+    let calls = if trimmed.starts_with('[') || trimmed.starts_with('{') {
+        let root: Value = serde_json::from_str(trimmed).map_err(|e| format!("Invalid observed calls: {}", e))?;
+        parse_json_calls(&root)?
+    } else {
+        content
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .filter_map(parse_text_line)
+            .collect()
+    };
+    if calls.is_empty() {
+        return Err("No observed calls found".to_string());
+    }
+    Ok(calls)
+}
+
+/// 调用端映射到函数：有文件时按位置解析，否则要求函数名/限定名唯一匹配
+fn resolve_endpoint<'a>(graph: &'a PetCodeGraph, index: &FunctionIndex<'a>, endpoint: &CallEndpoint) -> Option<&'a FunctionInfo> {
+    if endpoint.file.is_some() {
+        let frame = StackFrame {
+            raw: endpoint.describe(),
+            symbol: endpoint.symbol.clone(),
+            file: endpoint.file.clone(),
+            line: endpoint.line,
+        };
+        return resolve_frame(graph, &frame);
+    }
+    let matched = index.lookup(endpoint.symbol.as_deref()?);
+    (matched.len() == 1).then(|| matched[0])
+}
+
+/// 把观测到的调用合并为图中的observed边，同一对函数的多条记录累加次数
+pub fn merge_observed_calls(graph: &mut PetCodeGraph, calls: &[ObservedCall]) -> ObservedCallsReport {
+    let mut counts: HashMap<(Uuid, Uuid), u64> = HashMap::new();
+    let mut unmapped = BTreeSet::new();
+    {
+        let index = FunctionIndex::new(graph);
+        for call in calls {
+            let caller = resolve_endpoint(graph, &index, &call.caller);
+            let callee = resolve_endpoint(graph, &index, &call.callee);
+            match (caller, callee) {
+                (Some(caller), Some(callee)) => *counts.entry((caller.id, callee.id)).or_default() += call.count,
+                (caller, callee) => {
+                    if caller.is_none() {
+                        unmapped.insert(call.caller.describe());
+                    }
+                    if callee.is_none() {
+                        unmapped.insert(call.callee.describe());
+                    }
+                }
+            }
+        }
+    }
+
+    let mut report = ObservedCallsReport { total_pairs: calls.len(), ..Default::default() };
+    for ((caller_id, callee_id), count) in counts {
+        let merge = match graph.record_observed_call(&caller_id, &callee_id) {
+            Ok(merge) => merge,
+            Err(e) => {
+                tracing::warn!("Failed to record observed call: {}", e);
+                continue;
+            }
+        };
+        match merge {
+            ObservedCallMerge::Confirmed => report.confirmed += 1,
+            ObservedCallMerge::Upgraded => report.upgraded += 1,
+            ObservedCallMerge::Added => report.added += 1,
+        }
+        let (Some(caller), Some(callee)) = (graph.get_function_by_id(&caller_id), graph.get_function_by_id(&callee_id)) else {
+            continue;
+        };
+        report.edges.push(ObservedEdge {
+            caller_id,
+            caller: caller.name.clone(),
+            caller_file: caller.file_path.display().to_string(),
+            callee_id,
+            callee: callee.name.clone(),
+            callee_file: callee.file_path.display().to_string(),
+            count,
+            merge,
+        });
+    }
+    graph.update_stats();
+    report.edges.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| (&a.caller, &a.callee).cmp(&(&b.caller, &b.callee))));
+    report.unmapped = unmapped.into_iter().collect();
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::codegraph::types::{CallRelation, FunctionKind};
+    use std::path::PathBuf;
+
+    fn function(name: &str, file: &str, line_start: usize, namespace: &str) -> FunctionInfo {
+        FunctionInfo {
+            id: Uuid::new_v4(),
+            name: name.to_string(),
+            file_path: PathBuf::from(file),
+            line_start,
+            line_end: line_start + 10,
+            namespace: namespace.to_string(),
+            language: "python".to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_parse_observed_calls() {
+        let text = "# caller -> callee count\napp.main -> handlers.on_click 3\nsrc/app.py:12,render\n";
+        let calls = parse_observed_calls(text).unwrap();
+        assert_eq!(calls.len(), 2);
+        assert_eq!(calls[0].caller.symbol.as_deref(), Some("app.main"));
+        assert_eq!(calls[0].count, 3);
+        assert_eq!((calls[1].caller.file.as_deref(), calls[1].caller.line), (Some("src/app.py"), Some(12)));
+        assert_eq!(calls[1].callee.symbol.as_deref(), Some("render"));
+
+        let json = r#"{"calls": [{"caller": {"function": "main", "file": "app.py", "line": 3}, "callee": "render", "count": 7}]}"#;
+        let calls = parse_observed_calls(json).unwrap();
+        assert_eq!(calls[0].caller.symbol.as_deref(), Some("main"));
+        assert_eq!(calls[0].count, 7);
+        assert!(parse_observed_calls("# nothing\n").is_err());
+    }
+
+    #[test]
+    fn test_merge_upgrades_dynamic_call() {
+        let main = function("main", "/srv/app.py", 1, "app");
+        let on_click = function("on_click", "/srv/handlers.py", 1, "handlers");
+        let render = function("render", "/srv/view.py", 1, "view");
+        let mut placeholder = function("on_click", "/srv/app.py", 5, "unresolved");
+        placeholder.kind = FunctionKind::Unresolved;
+        placeholder.line_end = 5;
+        let mut graph = PetCodeGraph::new();
+        for f in [&main, &on_click, &render, &placeholder] {
+            graph.add_function(f.clone());
+        }
+        graph
+            .add_call_relation(CallRelation {
+                callee_name: "self.handler.on_click".to_string(),
+                line_number: 5,
+                ..CallRelation::between(&main, &placeholder)
+            })
+            .unwrap();
+
+        let calls = parse_observed_calls("main -> handlers.on_click 2\n/srv/app.py:5 -> on_click 1\non_click -> render\nmain -> missing\n").unwrap();
+        let report = merge_observed_calls(&mut graph, &calls);
+        assert_eq!((report.total_pairs, report.upgraded, report.added, report.confirmed), (4, 1, 1, 0));
+        assert_eq!(report.unmapped, vec!["missing".to_string()]);
+        assert_eq!((report.edges[0].callee.as_str(), report.edges[0].count), ("on_click", 3));
+        assert_eq!(report.edges[0].merge, ObservedCallMerge::Upgraded);
+        assert!(graph.get_function_by_id(&placeholder.id).is_none());
+        assert!(graph.get_callees(&on_click.id).iter().all(|(_, relation)| relation.observed));
+        assert_eq!((graph.stats.resolved_calls, graph.stats.unresolved_calls), (2, 0));
+    }
+}
@@ -0,0 +1,199 @@
+use std::fs::{self, File};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use arrow_array::{ArrayRef, BooleanArray, RecordBatch, StringArray, UInt64Array};
+use arrow_schema::{DataType, Field, Schema};
+use parquet::arrow::ArrowWriter;
+use parquet::basic::Compression;
+use parquet::file::properties::WriterProperties;
+
+use crate::codegraph::types::PetCodeGraph;
+use crate::services::graph_export::{extract_classes, node_metrics, stable_ids};
+
+/// 导出的表文件名，顺序即写入顺序
+pub const PARQUET_TABLES: &[&str] = &["functions.parquet", "classes.parquet", "edges.parquet", "metrics.parquet"];
+
+fn strings<S: AsRef<str>>(values: impl IntoIterator<Item = S>) -> ArrayRef {
+    Arc::new(StringArray::from_iter_values(values))
+}
+
+fn numbers(values: impl IntoIterator<Item = usize>) -> ArrayRef {
+    Arc::new(UInt64Array::from_iter_values(values.into_iter().map(|v| v as u64)))
+}
+
+fn field(name: &str, data_type: DataType) -> Field {
+    Field::new(name, data_type, false)
+}
+
+fn write_table(path: &Path, fields: Vec<Field>, columns: Vec<ArrayRef>) -> Result<(), String> {
+    let schema = Arc::new(Schema::new(fields));
+    let batch = RecordBatch::try_new(schema.clone(), columns)
+        .map_err(|e| format!("Failed to build table {}: {}", path.display(), e))?;
+    let file = File::create(path).map_err(|e| format!("Failed to create {}: {}", path.display(), e))?;
+    let properties = WriterProperties::builder().set_compression(Compression::SNAPPY).build();
+    let mut writer = ArrowWriter::try_new(file, schema, Some(properties))
+        .map_err(|e| format!("Failed to open parquet writer for {}: {}", path.display(), e))?;
+    writer
+        .write(&batch)
+        .map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+    writer.close().map_err(|e| format!("Failed to finish {}: {}", path.display(), e))?;
+    Ok(())
+}
+
+/// 将函数、类、调用边和函数度量分别写成列式Parquet表，返回写入的文件
+pub fn write_parquet_tables(graph: &PetCodeGraph, dir: &Path) -> Result<Vec<PathBuf>, String> {
+    fs::create_dir_all(dir).map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+    let ids = stable_ids(graph);
+    let metrics = node_metrics(graph);
+    let mut functions = graph.get_all_functions();
+    functions.sort_by(|a, b| (&a.file_path, a.line_start, &a.name).cmp(&(&b.file_path, b.line_start, &b.name)));
+    let paths: Vec<PathBuf> = PARQUET_TABLES.iter().map(|name| dir.join(name)).collect();
+
+    write_table(
+        &paths[0],
+        vec![
+            field("id", DataType::Utf8),
+            field("name", DataType::Utf8),
+            field("namespace", DataType::Utf8),
+            field("language", DataType::Utf8),
+            field("file", DataType::Utf8),
+            field("line_start", DataType::UInt64),
+            field("line_end", DataType::UInt64),
+        ],
+        vec![
+            strings(functions.iter().map(|f| ids[&f.id].as_str())),
+            strings(functions.iter().map(|f| f.name.as_str())),
+            strings(functions.iter().map(|f| f.namespace.as_str())),
+            strings(functions.iter().map(|f| f.language.as_str())),
+            strings(functions.iter().map(|f| f.file_path.display().to_string())),
+            numbers(functions.iter().map(|f| f.line_start)),
+            numbers(functions.iter().map(|f| f.line_end)),
+        ],
+    )?;
+
+    let classes = extract_classes(graph);
+    write_table(
+        &paths[1],
+        vec![
+            field("name", DataType::Utf8),
+            field("namespace", DataType::Utf8),
+            field("language", DataType::Utf8),
+            field("file", DataType::Utf8),
+            field("line_start", DataType::UInt64),
+            field("line_end", DataType::UInt64),
+        ],
+        vec![
+            strings(classes.iter().map(|c| c.name.as_str())),
+            strings(classes.iter().map(|c| c.namespace.as_str())),
+            strings(classes.iter().map(|c| c.language.as_str())),
+            strings(classes.iter().map(|c| c.file_path.display().to_string())),
+            numbers(classes.iter().map(|c| c.line_start)),
+            numbers(classes.iter().map(|c| c.line_end)),
+        ],
+    )?;
+
+    let mut edges = Vec::new();
+    for edge_index in graph.graph.edge_indices() {
+        let Some((source, target)) = graph.graph.edge_endpoints(edge_index) else {
+            continue;
+        };
+        let (caller, callee, relation) = (&graph.graph[source], &graph.graph[target], &graph.graph[edge_index]);
+        edges.push((
+            ids[&caller.id].clone(),
+            ids[&callee.id].clone(),
+            relation.caller_file.display().to_string(),
+            relation.line_number,
+            relation.is_resolved,
+        ));
+    }
+    edges.sort();
+    write_table(
+        &paths[2],
+        vec![
+            field("source", DataType::Utf8),
+            field("target", DataType::Utf8),
+            field("file", DataType::Utf8),
+            field("line", DataType::UInt64),
+            field("resolved", DataType::Boolean),
+        ],
+        vec![
+            strings(edges.iter().map(|e| e.0.as_str())),
+            strings(edges.iter().map(|e| e.1.as_str())),
+            strings(edges.iter().map(|e| e.2.as_str())),
+            numbers(edges.iter().map(|e| e.3)),
+            Arc::new(BooleanArray::from(edges.iter().map(|e| e.4).collect::<Vec<_>>())),
+        ],
+    )?;
+
+    let metrics: Vec<_> = functions
+        .iter()
+        .map(|f| (ids[&f.id].as_str(), metrics.get(&f.id).cloned().unwrap_or_default()))
+        .collect();
+    write_table(
+        &paths[3],
+        vec![
+            field("id", DataType::Utf8),
+            field("fan_in", DataType::UInt64),
+            field("fan_out", DataType::UInt64),
+            Field::new("complexity", DataType::UInt64, true),
+        ],
+        vec![
+            strings(metrics.iter().map(|(id, _)| *id)),
+            numbers(metrics.iter().map(|(_, m)| m.fan_in)),
+            numbers(metrics.iter().map(|(_, m)| m.fan_out)),
+            Arc::new(UInt64Array::from(metrics.iter().map(|(_, m)| m.complexity.map(|c| c as u64)).collect::<Vec<_>>())),
+        ],
+    )?;
+
+    Ok(paths)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::codegraph::parser::CodeParser;
+    use crate::codegraph::AnalyzeOptions;
+    use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+    use tempfile::TempDir;
+
+    fn read_table(path: &Path) -> RecordBatch {
+        let reader = ParquetRecordBatchReaderBuilder::try_new(File::open(path).unwrap()).unwrap().build().unwrap();
+        let mut batches: Vec<RecordBatch> = reader.map(|b| b.unwrap()).collect();
+        assert_eq!(batches.len(), 1);
+        batches.remove(0)
+    }
+
+    #[test]
+    fn test_parquet_tables_round_trip() {
+        let dir = TempDir::new().unwrap();
+        fs::write(
+            dir.path().join("shapes.rs"),
+            "pub struct Circle {\n    r: f64,\n}\n\npub fn area(c: &Circle) -> f64 {\n    if c.r > 0.0 {\n        return square(c.r);\n    }\n    0.0\n}\n\npub fn square(x: f64) -> f64 {\n    x * x\n}\n",
+        )
+        .unwrap();
+        let options = AnalyzeOptions {
+            storage_dir: Some(dir.path().join(".codegraph_db")),
+            force_rebuild: true,
+            ..Default::default()
+        };
+        let graph = CodeParser::with_options(options).build_petgraph_code_graph(dir.path()).unwrap();
+
+        let out = dir.path().join("tables");
+        let paths = write_parquet_tables(&graph, &out).unwrap();
+        assert_eq!(paths.len(), PARQUET_TABLES.len());
+
+        let functions = read_table(&paths[0]);
+        assert_eq!(functions.num_rows(), graph.get_all_functions().len());
+        let classes = read_table(&paths[1]);
+        let names = classes.column_by_name("name").unwrap().as_any().downcast_ref::<StringArray>().unwrap();
+        assert!(names.iter().any(|n| n == Some("Circle")));
+
+        let edges = read_table(&paths[2]);
+        assert_eq!(edges.num_rows(), graph.graph.edge_count());
+        let metrics = read_table(&paths[3]);
+        assert_eq!(metrics.num_rows(), functions.num_rows());
+        let complexity = metrics.column_by_name("complexity").unwrap().as_any().downcast_ref::<UInt64Array>().unwrap();
+        assert!(complexity.iter().any(|c| c == Some(2)));
+    }
+}
@@ -0,0 +1,163 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::codegraph::options::path_matches;
+use crate::services::lint::{LintFinding, LintRule};
+
+/// 检查目录下默认的策略文件名
+pub const DEFAULT_POLICY_FILE: &str = "codegraph-policy.yaml";
+
+/// 结果的处理级别
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    /// 不报告
+    Ignore,
+    /// 报告但不影响退出码
+    #[default]
+    Warn,
+    /// 报告并使检查失败
+    Error,
+}
+
+impl Severity {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Severity::Ignore => "ignore",
+            Severity::Warn => "warning",
+            Severity::Error => "error",
+        }
+    }
+
+    /// SARIF 中对应的 `level`
+    pub fn sarif_level(&self) -> &'static str {
+        match self {
+            Severity::Ignore => "none",
+            Severity::Warn => "warning",
+            Severity::Error => "error",
+        }
+    }
+}
+
+/// 作用于部分路径的覆盖规则
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PolicyScope {
+    /// glob模式或目录前缀，相对于检查根目录
+    pub paths: Vec<String>,
+    /// 该作用域内未单独配置的规则使用的级别，缺省时沿用外层结果
+    #[serde(default)]
+    pub default: Option<Severity>,
+    #[serde(default)]
+    pub rules: BTreeMap<String, Severity>,
+}
+
+/// 策略文件：规则ID到级别的映射，以及按路径覆盖的作用域
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Policy {
+    #[serde(default)]
+    pub default: Severity,
+    #[serde(default)]
+    pub rules: BTreeMap<String, Severity>,
+    #[serde(default)]
+    pub scopes: Vec<PolicyScope>,
+}
+
+impl Policy {
+    /// 从YAML文件加载并校验
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let content = fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read policy file {}: {}", path.display(), e))?;
+        Self::from_yaml(&content).map_err(|e| format!("Invalid policy file {}: {}", path.display(), e))
+    }
+
+    pub fn from_yaml(content: &str) -> Result<Self, String> {
+        let policy: Self = serde_yaml::from_str(content).map_err(|e| e.to_string())?;
+        policy.validate()?;
+        Ok(policy)
+    }
+
+    /// 拒绝未知的规则ID和没有路径的作用域，避免拼写错误被静默忽略
+    fn validate(&self) -> Result<(), String> {
+        let known: Vec<&str> = LintRule::all().iter().map(|rule| rule.id()).collect();
+        let rule_ids = self.rules.keys().chain(self.scopes.iter().flat_map(|scope| scope.rules.keys()));
+        for rule_id in rule_ids {
+            if !known.contains(&rule_id.as_str()) {
+                return Err(format!("Unknown rule '{}', expected one of: {}", rule_id, known.join(", ")));
+            }
+        }
+        if let Some(index) = self.scopes.iter().position(|scope| scope.paths.is_empty()) {
+            return Err(format!("Scope #{} has no paths", index + 1));
+        }
+        Ok(())
+    }
+
+    /// 规则在给定相对路径上的级别：先取全局规则或默认级别，再依次应用匹配的作用域，靠后的覆盖靠前的
+    pub fn severity(&self, rule_id: &str, relative: &Path) -> Severity {
+        let mut severity = self.rules.get(rule_id).copied().unwrap_or(self.default);
+        for scope in &self.scopes {
+            if scope.paths.iter().any(|pattern| path_matches(pattern, relative)) {
+                if let Some(level) = scope.rules.get(rule_id).copied().or(scope.default) {
+                    severity = level;
+                }
+            }
+        }
+        severity
+    }
+
+    /// 为每条结果设置级别并丢弃 ignore 的结果，返回保留的结果和被忽略的数量
+    pub fn apply(&self, findings: Vec<LintFinding>, root: &Path) -> (Vec<LintFinding>, usize) {
+        let mut kept = Vec::with_capacity(findings.len());
+        let mut ignored = 0;
+        for mut finding in findings {
+            let relative = finding.file_path.strip_prefix(root).unwrap_or(&finding.file_path);
+            finding.severity = self.severity(finding.rule.id(), relative);
+            if finding.severity == Severity::Ignore {
+                ignored += 1;
+            } else {
+                kept.push(finding);
+            }
+        }
+        (kept, ignored)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const POLICY: &str = r#"
+default: warn
+rules:
+  dead-code: error
+  cycle: error
+scopes:
+  - paths: ["legacy/**", "vendor"]
+    default: ignore
+  - paths: ["src/core/**"]
+    rules:
+      function-length: error
+  - paths: ["legacy/billing/**"]
+    rules:
+      cycle: error
+"#;
+
+    #[test]
+    fn test_policy_severity_by_scope() {
+        let policy = Policy::from_yaml(POLICY).unwrap();
+        assert_eq!(policy.severity("dead-code", Path::new("src/app.rs")), Severity::Error);
+        assert_eq!(policy.severity("nesting-depth", Path::new("src/app.rs")), Severity::Warn);
+        assert_eq!(policy.severity("function-length", Path::new("src/core/db.rs")), Severity::Error);
+        assert_eq!(policy.severity("dead-code", Path::new("legacy/old.rs")), Severity::Ignore);
+        assert_eq!(policy.severity("dead-code", Path::new("third_party/vendor/x.rs")), Severity::Ignore);
+        // 后面的作用域覆盖前面的
+        assert_eq!(policy.severity("cycle", Path::new("legacy/billing/invoice.rs")), Severity::Error);
+        assert_eq!(policy.severity("dead-code", Path::new("legacy/billing/invoice.rs")), Severity::Ignore);
+
+        assert!(Policy::from_yaml("rules:\n  dead-cod: error\n").unwrap_err().contains("dead-cod"));
+        assert!(Policy::from_yaml("scopes:\n  - paths: []\n").is_err());
+        assert!(Policy::from_yaml("default: fatal\n").is_err());
+    }
+}
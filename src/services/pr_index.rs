@@ -0,0 +1,238 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use tracing::{info, warn};
+use uuid::Uuid;
+
+use crate::codegraph::entrypoints::detect_entry_points;
+use crate::codegraph::parser::CodeParser;
+use crate::codegraph::types::PetCodeGraph;
+use crate::codegraph::AnalyzeOptions;
+use crate::services::graph_builder::{project_id_for, BuildError};
+use crate::storage::snapshot::git;
+use crate::storage::{GraphSnapshot, StorageManager};
+
+/// PR 图的项目ID：基线项目ID加 PR 编号
+pub fn pr_project_id(base_project_id: &str, pull_request: u64) -> String {
+    format!("{}-pr-{}", base_project_id, pull_request)
+}
+
+/// CI 环境变量中的 PR 编号：GitHub Actions 的 `GITHUB_REF`（`refs/pull/<n>/merge`）或 GitLab 的 `CI_MERGE_REQUEST_IID`
+pub fn pull_request_from_env() -> Option<u64> {
+    pull_request_from_vars(|name| std::env::var(name).ok())
+}
+
+fn pull_request_from_vars(var: impl Fn(&str) -> Option<String>) -> Option<u64> {
+    var("GITHUB_REF")
+        .and_then(|git_ref| git_ref.strip_prefix("refs/pull/")?.split('/').next()?.parse().ok())
+        .or_else(|| var("CI_MERGE_REQUEST_IID")?.parse().ok())
+}
+
+/// 一次 PR 索引的结果
+pub struct PrIndexOutcome {
+    pub project_id: String,
+    pub base_project_id: String,
+    /// 是否复用了基线图；没有基线图时对 head 做了全量构建
+    pub warm: bool,
+    /// 相对项目目录的变化文件
+    pub changed_files: Vec<PathBuf>,
+    pub graph: PetCodeGraph,
+    pub snapshot: GraphSnapshot,
+}
+
+/// 在基线分支已保存的图上应用 `base...head` 的差异，得到 PR 的图并以 PR 编号保存为单独的项目。
+///
+/// 变化的文件从工作区读取，因此 `head` 必须是当前检出的提交。基线图不会被修改；
+/// 没有基线图时对工作区做一次全量构建。
+pub fn index_pull_request(
+    storage: &StorageManager,
+    project_dir: &str,
+    base: &str,
+    head: &str,
+    pull_request: u64,
+) -> Result<PrIndexOutcome, BuildError> {
+    let dir = Path::new(project_dir);
+    if !dir.is_dir() {
+        return Err(BuildError::NotFound(format!("Project directory not found: {}", project_dir)));
+    }
+    let commit = |rev: &str| {
+        git(dir, &["rev-parse", "--verify", &format!("{}^{{commit}}", rev)])
+            .ok_or_else(|| BuildError::NotFound(format!("Unknown revision '{}' in {}", rev, project_dir)))
+    };
+    if commit(head)? != commit("HEAD")? {
+        return Err(BuildError::Failed(format!(
+            "{} is not checked out in {}; pull request indexing reads changed files from the working tree",
+            head, project_dir
+        )));
+    }
+    let merge_base = git(dir, &["merge-base", base, head])
+        .ok_or_else(|| BuildError::NotFound(format!("'{}' and '{}' have no common ancestor", base, head)))?;
+
+    let base_project_id = project_id_for(project_dir);
+    let project_id = pr_project_id(&base_project_id, pull_request);
+    storage.check_quota(&project_id, None).map_err(BuildError::QuotaExceeded)?;
+    let persistence = storage.get_persistence();
+    let mut snapshot = GraphSnapshot::capture(dir);
+    snapshot.pull_request = Some(pull_request);
+
+    // 解析器只写入文件哈希，放在临时目录中，避免影响基线项目的增量构建
+    let scratch = std::env::temp_dir().join(format!("codegraph-pr-{}", Uuid::new_v4().simple()));
+    let mut parser = CodeParser::with_options(AnalyzeOptions {
+        storage_dir: Some(scratch.clone()),
+        force_rebuild: true,
+        ..Default::default()
+    });
+    let base_graph = persistence.load_graph(&base_project_id).unwrap_or_else(|e| {
+        warn!("Failed to load the base graph of {}: {}", project_dir, e);
+        None
+    });
+    let result = match base_graph {
+        Some(mut graph) => {
+            // 基线图构建时的提交才是图实际反映的版本，与 merge-base 不同时以它为准
+            let from = match persistence.load_snapshot(&base_project_id).ok().flatten().and_then(|s| s.commit) {
+                Some(built) if built != merge_base => {
+                    warn!("Base graph was built at {}, not at the merge base {}; diffing from {}", built, merge_base, built);
+                    built
+                }
+                _ => merge_base,
+            };
+            changed_files(dir, &from).and_then(|changed| {
+                let paths: Vec<PathBuf> = changed.iter().map(|path| dir.join(path)).collect();
+                parser.apply_changed_files(&mut graph, dir, &paths).map_err(BuildError::Failed)?;
+                Ok((graph, changed, true, from))
+            })
+        }
+        None => {
+            warn!("No stored graph for {}; building the pull request graph from scratch", project_dir);
+            changed_files(dir, &merge_base).and_then(|changed| {
+                let graph = parser.build_petgraph_code_graph(dir).map_err(BuildError::Failed)?;
+                Ok((graph, changed, false, merge_base))
+            })
+        }
+    };
+    if scratch.exists() {
+        if let Err(e) = fs::remove_dir_all(&scratch) {
+            warn!("Failed to remove {}: {}", scratch.display(), e);
+        }
+    }
+    let (graph, changed_files, warm, base_commit) = result?;
+    snapshot.base_commit = Some(base_commit);
+
+    storage
+        .check_quota(&project_id, Some(graph.get_all_functions().len()))
+        .map_err(BuildError::QuotaExceeded)?;
+    persistence
+        .save_graph(&project_id, &graph)
+        .map_err(|e| BuildError::Failed(format!("Failed to save graph: {}", e)))?;
+    if let Err(e) = persistence.save_snapshot(&project_id, &snapshot) {
+        warn!("Failed to save snapshot info for {}: {}", project_id, e);
+    }
+    if let Err(e) = persistence.save_entry_points(&project_id, &detect_entry_points(&graph)) {
+        warn!("Failed to save entry points for {}: {}", project_id, e);
+    }
+    storage.mark_updated();
+    info!(
+        "Indexed pull request #{} of {} as {} ({} changed files, {})",
+        pull_request,
+        project_dir,
+        project_id,
+        changed_files.len(),
+        if warm { "from the base graph" } else { "full build" }
+    );
+
+    Ok(PrIndexOutcome {
+        project_id,
+        base_project_id,
+        warm,
+        changed_files,
+        graph,
+        snapshot,
+    })
+}
+
+/// `from` 与工作区之间内容有变化的文件（含删除），相对项目目录
+fn changed_files(dir: &Path, from: &str) -> Result<Vec<PathBuf>, BuildError> {
+    let output = git(dir, &["diff", "--name-only", "--no-renames", "--relative", from])
+        .ok_or_else(|| BuildError::Failed(format!("Failed to diff {} against {}", dir.display(), from)))?;
+    Ok(output.lines().filter(|line| !line.is_empty()).map(PathBuf::from).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::process::Command;
+
+    fn run_git(dir: &Path, args: &[&str]) {
+        let status = Command::new("git")
+            .arg("-C")
+            .arg(dir)
+            .args(["-c", "user.name=test", "-c", "user.email=test@example.com"])
+            .args(args)
+            .status()
+            .unwrap();
+        assert!(status.success(), "git {:?} failed", args);
+    }
+
+    #[test]
+    fn test_pull_request_from_vars() {
+        let vars = |pairs: &'static [(&'static str, &'static str)]| {
+            move |name: &str| pairs.iter().find(|(key, _)| *key == name).map(|(_, value)| value.to_string())
+        };
+        assert_eq!(pull_request_from_vars(vars(&[("GITHUB_REF", "refs/pull/42/merge")])), Some(42));
+        assert_eq!(pull_request_from_vars(vars(&[("GITHUB_REF", "refs/heads/main")])), None);
+        assert_eq!(pull_request_from_vars(vars(&[("CI_MERGE_REQUEST_IID", "7")])), Some(7));
+    }
+
+    #[test]
+    fn test_index_pull_request_applies_diff_to_base_graph() {
+        let repo = tempfile::tempdir().unwrap();
+        let data = tempfile::tempdir().unwrap();
+        let root = repo.path();
+        let project_dir = root.display().to_string();
+        fs::create_dir_all(root.join("src")).unwrap();
+        fs::write(root.join("src/lib.rs"), "pub fn entry() {\n    helper();\n    old_path();\n}\n").unwrap();
+        fs::write(root.join("src/helper.rs"), "pub fn helper() {}\n").unwrap();
+        fs::write(root.join("src/old.rs"), "pub fn old_path() {}\n").unwrap();
+        run_git(root, &["init", "-q", "-b", "main"]);
+        run_git(root, &["add", "-A"]);
+        run_git(root, &["commit", "-q", "-m", "base"]);
+
+        let storage = StorageManager::with_base_dir(data.path().to_path_buf(), crate::cli::args::StorageMode::Json);
+        crate::services::build_and_store(&storage, &project_dir, AnalyzeOptions::default()).unwrap();
+        let base_functions = storage.get_persistence().load_graph(&project_id_for(&project_dir)).unwrap().unwrap().get_all_functions().len();
+
+        run_git(root, &["checkout", "-q", "-b", "feature"]);
+        fs::write(root.join("src/helper.rs"), "pub fn helper() {\n    added();\n}\n\npub fn added() {}\n").unwrap();
+        fs::remove_file(root.join("src/old.rs")).unwrap();
+        run_git(root, &["add", "-A"]);
+        run_git(root, &["commit", "-q", "-m", "change"]);
+
+        let outcome = index_pull_request(&storage, &project_dir, "main", "HEAD", 12).unwrap();
+        assert!(outcome.warm);
+        assert_eq!(outcome.project_id, format!("{}-pr-12", outcome.base_project_id));
+        let mut changed = outcome.changed_files.clone();
+        changed.sort();
+        assert_eq!(changed, vec![PathBuf::from("src/helper.rs"), PathBuf::from("src/old.rs")]);
+        assert_eq!(outcome.snapshot.pull_request, Some(12));
+        assert!(outcome.snapshot.base_commit.is_some());
+
+        let graph = storage.get_persistence().load_graph(&outcome.project_id).unwrap().unwrap();
+        let callees = |name: &str| {
+            let function = graph.find_functions_by_name(name).into_iter().find(|f| !f.is_unresolved()).unwrap();
+            let mut callees: Vec<(String, bool)> =
+                graph.get_callees(&function.id).into_iter().map(|(f, r)| (f.name.clone(), r.is_resolved)).collect();
+            callees.sort();
+            callees
+        };
+        // 未变化文件调入变化文件的调用重新连到新的函数，被删除的函数变为未解析调用
+        assert_eq!(callees("entry"), vec![("helper".to_string(), true), ("old_path".to_string(), false)]);
+        assert_eq!(callees("helper"), vec![("added".to_string(), true)]);
+        assert_eq!(graph.validate(), vec![]);
+
+        // 基线图保持不变，PR 快照单独保存
+        let base = storage.get_persistence().load_graph(&outcome.base_project_id).unwrap().unwrap();
+        assert_eq!(base.get_all_functions().len(), base_functions);
+        let saved = storage.get_persistence().load_snapshot(&outcome.project_id).unwrap().unwrap();
+        assert_eq!(saved.pull_request, Some(12));
+    }
+}
@@ -0,0 +1,567 @@
+use std::collections::{BTreeMap, HashMap};
+use std::io::Read;
+use std::path::Path;
+
+use prost::Message;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::codegraph::types::{FunctionInfo, PetCodeGraph};
+use crate::storage::annotations::{path_qualified_name, FunctionIndex, HotPath};
+use crate::storage::MetricMap;
+
+/// 写入注解的指标名：函数作为叶子帧的样本数
+pub const SELF_METRIC: &str = "profile_self";
+/// 写入注解的指标名：函数出现在调用栈中的样本数
+pub const TOTAL_METRIC: &str = "profile_total";
+/// 写入注解的指标名：`profile_total` 占全部样本的百分比
+pub const TOTAL_PERCENT_METRIC: &str = "profile_total_pct";
+
+/// 报告中保留的未解析帧数量
+const MAX_UNRESOLVED_FRAMES: usize = 20;
+
+/// 剖析数据格式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ProfileFormat {
+    /// pprof protobuf，可以是gzip压缩的
+    Pprof,
+    /// `perf script` 的文本输出
+    PerfScript,
+    /// 折叠栈：每行 `root;caller;leaf 样本数`
+    Collapsed,
+}
+
+impl ProfileFormat {
+    pub fn parse(name: &str) -> Result<Self, String> {
+        match name {
+            "pprof" => Ok(Self::Pprof),
+            "perf" | "perf-script" => Ok(Self::PerfScript),
+            "collapsed" | "folded" => Ok(Self::Collapsed),
+            other => Err(format!("Unknown profile format '{}', expected pprof, perf or collapsed", other)),
+        }
+    }
+
+    /// 根据内容猜测格式：gzip或非文本视为pprof，每行以样本数结尾视为折叠栈，否则为perf script
+    pub fn detect(data: &[u8]) -> Self {
+        if data.starts_with(&[0x1f, 0x8b]) {
+            return Self::Pprof;
+        }
+        let Ok(text) = std::str::from_utf8(data) else {
+            return Self::Pprof;
+        };
+        let mut lines = text.lines().filter(|l| !l.trim().is_empty()).peekable();
+        if lines.peek().is_some() && lines.all(|l| !l.starts_with(char::is_whitespace) && parse_collapsed_line(l).is_some()) {
+            Self::Collapsed
+        } else {
+            Self::PerfScript
+        }
+    }
+}
+
+/// 剖析中的一帧
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct Frame {
+    name: String,
+    /// pprof 中记录的源文件，用于区分同名函数
+    file: Option<String>,
+}
+
+/// 一个样本：调用栈（叶子在前）和权重
+#[derive(Debug, Clone)]
+struct StackSample {
+    frames: Vec<Frame>,
+    value: u64,
+}
+
+/// 单个函数的样本统计
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FunctionSamples {
+    /// 作为叶子帧的样本
+    pub self_samples: u64,
+    /// 出现在调用栈中的样本（递归只计一次）
+    pub total_samples: u64,
+}
+
+/// 未能解析到图中函数的帧
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UnresolvedFrame {
+    pub name: String,
+    pub samples: u64,
+}
+
+/// 导入结果
+#[derive(Debug, Clone)]
+pub struct ProfileImport {
+    pub format: ProfileFormat,
+    pub total_samples: u64,
+    /// 至少有一帧解析到图中函数的样本
+    pub resolved_samples: u64,
+    pub functions: HashMap<Uuid, FunctionSamples>,
+    /// 按样本数降序
+    pub unresolved_frames: Vec<UnresolvedFrame>,
+    pub hot_paths: Vec<HotPath>,
+}
+
+impl ProfileImport {
+    /// 转换为注解：`文件路径::函数名` -> 自身/累计样本及累计百分比
+    pub fn to_annotations(&self, graph: &PetCodeGraph, root: &Path) -> BTreeMap<String, MetricMap> {
+        let mut annotations = BTreeMap::new();
+        for (id, samples) in &self.functions {
+            let Some(function) = graph.get_function_by_id(id) else {
+                continue;
+            };
+            let metrics: &mut MetricMap = annotations.entry(path_qualified_name(function, root)).or_default();
+            metrics.insert(SELF_METRIC.to_string(), samples.self_samples as f64);
+            metrics.insert(TOTAL_METRIC.to_string(), samples.total_samples as f64);
+            metrics.insert(TOTAL_PERCENT_METRIC.to_string(), percent(samples.total_samples, self.total_samples));
+        }
+        annotations
+    }
+}
+
+fn percent(part: u64, total: u64) -> f64 {
+    if total == 0 {
+        0.0
+    } else {
+        (part as f64 * 10000.0 / total as f64).round() / 100.0
+    }
+}
+
+/// 解析剖析数据并把帧解析到图中的函数，统计各函数的样本并找出最热的 `max_hot_paths` 条调用路径。
+/// `sample_index` 选择pprof中的样本类型，默认使用最后一种（与 `go tool pprof` 一致）
+pub fn import_profile(
+    data: &[u8],
+    format: Option<ProfileFormat>,
+    sample_index: Option<usize>,
+    graph: &PetCodeGraph,
+    root: &Path,
+    max_hot_paths: usize,
+) -> Result<ProfileImport, String> {
+    let format = format.unwrap_or_else(|| ProfileFormat::detect(data));
+    let samples = match format {
+        ProfileFormat::Pprof => parse_pprof(data, sample_index)?,
+        ProfileFormat::PerfScript => parse_perf_script(&text(data)?),
+        ProfileFormat::Collapsed => parse_collapsed(&text(data)?),
+    };
+
+    let index = FunctionIndex::new(graph);
+    let mut resolved_frames: HashMap<&Frame, Option<&FunctionInfo>> = HashMap::new();
+    let mut functions: HashMap<Uuid, FunctionSamples> = HashMap::new();
+    let mut unresolved: HashMap<&str, u64> = HashMap::new();
+    let mut paths: HashMap<Vec<Uuid>, u64> = HashMap::new();
+    let mut total_samples = 0;
+    let mut resolved_samples = 0;
+
+    for sample in &samples {
+        total_samples += sample.value;
+        // 叶子在前的已解析栈
+        let mut stack: Vec<&FunctionInfo> = Vec::new();
+        for frame in &sample.frames {
+            let function = *resolved_frames.entry(frame).or_insert_with(|| resolve_frame(&index, frame));
+            match function {
+                Some(function) => stack.push(function),
+                None => *unresolved.entry(frame.name.as_str()).or_insert(0) += sample.value,
+            }
+        }
+        let Some(leaf) = stack.first() else {
+            continue;
+        };
+        resolved_samples += sample.value;
+        functions.entry(leaf.id).or_default().self_samples += sample.value;
+
+        let mut path: Vec<Uuid> = stack.iter().rev().map(|f| f.id).collect();
+        // 递归调用在路径中只保留一次
+        path.dedup();
+        let mut counted: Vec<Uuid> = path.clone();
+        counted.sort();
+        counted.dedup();
+        for id in counted {
+            functions.entry(id).or_default().total_samples += sample.value;
+        }
+        *paths.entry(path).or_insert(0) += sample.value;
+    }
+
+    let mut hot: Vec<(Vec<Uuid>, u64)> = paths.into_iter().collect();
+    hot.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    let hot_paths = hot
+        .into_iter()
+        .take(max_hot_paths)
+        .map(|(ids, samples)| HotPath {
+            functions: ids
+                .iter()
+                .filter_map(|id| graph.get_function_by_id(id))
+                .map(|f| path_qualified_name(f, root))
+                .collect(),
+            samples,
+            percent: percent(samples, total_samples),
+        })
+        .collect();
+
+    let mut unresolved_frames: Vec<UnresolvedFrame> = unresolved
+        .into_iter()
+        .map(|(name, samples)| UnresolvedFrame { name: name.to_string(), samples })
+        .collect();
+    unresolved_frames.sort_by(|a, b| b.samples.cmp(&a.samples).then_with(|| a.name.cmp(&b.name)));
+    unresolved_frames.truncate(MAX_UNRESOLVED_FRAMES);
+
+    Ok(ProfileImport { format, total_samples, resolved_samples, functions, unresolved_frames, hot_paths })
+}
+
+fn text(data: &[u8]) -> Result<String, String> {
+    String::from_utf8(data.to_vec()).map_err(|e| format!("Profile is not valid UTF-8 text: {}", e))
+}
+
+/// 同名函数有多个时用帧中的源文件名区分，仍有歧义的帧不计入
+fn resolve_frame<'a>(index: &FunctionIndex<'a>, frame: &Frame) -> Option<&'a FunctionInfo> {
+    let name = normalize_symbol(&frame.name)?;
+    let mut candidates = index.lookup(&name);
+    if candidates.len() > 1 {
+        if let Some(file_name) = frame.file.as_deref().and_then(|f| Path::new(f).file_name()) {
+            candidates.retain(|f| f.file_path.file_name() == Some(file_name));
+        }
+    }
+    match candidates.as_slice() {
+        [function] => Some(function),
+        _ => None,
+    }
+}
+
+/// 去掉符号中的地址偏移、参数列表、泛型参数、Rust符号哈希和闭包标记，
+/// 并把Go的 `pkg.(*Type).Method` 写成 `pkg.Type.Method`
+fn normalize_symbol(symbol: &str) -> Option<String> {
+    let mut name = symbol.trim();
+    if name.is_empty() || name.starts_with('[') {
+        return None;
+    }
+    if let Some(pos) = name.rfind("+0x") {
+        name = &name[..pos];
+    }
+    let mut name = name.trim_end_matches(" const").to_string();
+    if name.contains(".(") {
+        name = name.replace("(*", "").replace(['(', ')'], "");
+    }
+    if name.ends_with(')') {
+        if let Some(open) = matching_open(&name, '(', ')') {
+            name.truncate(open);
+        }
+    }
+    let name = strip_nested(&name, '<', '>').replace("::{{closure}}", "");
+    let name = match name.rsplit_once("::h") {
+        Some((head, hash)) if hash.len() == 16 && hash.chars().all(|c| c.is_ascii_hexdigit()) => head.to_string(),
+        _ => name,
+    };
+    let name = name.trim().trim_start_matches("::").to_string();
+    (!name.is_empty()).then_some(name)
+}
+
+/// 最后一个闭括号对应的开括号位置
+fn matching_open(s: &str, open: char, close: char) -> Option<usize> {
+    let mut depth = 0usize;
+    for (i, c) in s.char_indices().rev() {
+        if c == close {
+            depth += 1;
+        } else if c == open {
+            depth = depth.checked_sub(1)?;
+            if depth == 0 {
+                return Some(i);
+            }
+        }
+    }
+    None
+}
+
+/// 删除所有成对括号及其内容
+fn strip_nested(s: &str, open: char, close: char) -> String {
+    let mut depth = 0usize;
+    let mut out = String::with_capacity(s.len());
+    for c in s.chars() {
+        if c == open {
+            depth += 1;
+        } else if c == close && depth > 0 {
+            depth -= 1;
+        } else if depth == 0 {
+            out.push(c);
+        }
+    }
+    out
+}
+
+fn parse_collapsed_line(line: &str) -> Option<(Vec<&str>, u64)> {
+    let (stack, count) = line.trim_end().rsplit_once(' ')?;
+    let count = count.parse().ok()?;
+    Some((stack.split(';').filter(|f| !f.is_empty()).collect(), count))
+}
+
+fn parse_collapsed(text: &str) -> Vec<StackSample> {
+    text.lines()
+        .filter_map(parse_collapsed_line)
+        .map(|(stack, value)| StackSample {
+            // 折叠栈从根开始，转换为叶子在前
+            frames: stack.into_iter().rev().map(|name| Frame { name: name.to_string(), file: None }).collect(),
+            value,
+        })
+        .collect()
+}
+
+/// 每个样本以不缩进的头部行开始，随后每行一帧：`地址 符号+偏移 (模块)`，样本之间以空行分隔
+fn parse_perf_script(text: &str) -> Vec<StackSample> {
+    let mut samples = Vec::new();
+    let mut current: Option<Vec<Frame>> = None;
+    for line in text.lines() {
+        if line.trim().is_empty() {
+            if let Some(frames) = current.take() {
+                samples.push(StackSample { frames, value: 1 });
+            }
+        } else if line.starts_with(char::is_whitespace) {
+            let trimmed = line.trim();
+            let symbol = trimmed.split_once(char::is_whitespace).map(|(_, rest)| rest).unwrap_or(trimmed);
+            let symbol = match symbol.rfind(" (") {
+                Some(pos) if symbol.ends_with(')') => &symbol[..pos],
+                _ => symbol,
+            };
+            current.get_or_insert_with(Vec::new).push(Frame { name: symbol.to_string(), file: None });
+        } else {
+            if let Some(frames) = current.take() {
+                samples.push(StackSample { frames, value: 1 });
+            }
+            current = Some(Vec::new());
+        }
+    }
+    if let Some(frames) = current {
+        samples.push(StackSample { frames, value: 1 });
+    }
+    samples.retain(|s| !s.frames.is_empty());
+    samples
+}
+
+/// pprof `profile.proto` 中用到的字段
+#[derive(Clone, PartialEq, Message)]
+struct PprofProfile {
+    #[prost(message, repeated, tag = "1")]
+    sample_type: Vec<PprofValueType>,
+    #[prost(message, repeated, tag = "2")]
+    sample: Vec<PprofSample>,
+    #[prost(message, repeated, tag = "4")]
+    location: Vec<PprofLocation>,
+    #[prost(message, repeated, tag = "5")]
+    function: Vec<PprofFunction>,
+    #[prost(string, repeated, tag = "6")]
+    string_table: Vec<String>,
+}
+
+#[derive(Clone, PartialEq, Message)]
+struct PprofValueType {
+    #[prost(int64, tag = "1")]
+    r#type: i64,
+    #[prost(int64, tag = "2")]
+    unit: i64,
+}
+
+#[derive(Clone, PartialEq, Message)]
+struct PprofSample {
+    #[prost(uint64, repeated, tag = "1")]
+    location_id: Vec<u64>,
+    #[prost(int64, repeated, tag = "2")]
+    value: Vec<i64>,
+}
+
+#[derive(Clone, PartialEq, Message)]
+struct PprofLocation {
+    #[prost(uint64, tag = "1")]
+    id: u64,
+    #[prost(message, repeated, tag = "4")]
+    line: Vec<PprofLine>,
+}
+
+#[derive(Clone, PartialEq, Message)]
+struct PprofLine {
+    #[prost(uint64, tag = "1")]
+    function_id: u64,
+    #[prost(int64, tag = "2")]
+    line: i64,
+}
+
+#[derive(Clone, PartialEq, Message)]
+struct PprofFunction {
+    #[prost(uint64, tag = "1")]
+    id: u64,
+    #[prost(int64, tag = "2")]
+    name: i64,
+    #[prost(int64, tag = "3")]
+    system_name: i64,
+    #[prost(int64, tag = "4")]
+    filename: i64,
+}
+
+fn parse_pprof(data: &[u8], sample_index: Option<usize>) -> Result<Vec<StackSample>, String> {
+    let mut decompressed = Vec::new();
+    let data = if data.starts_with(&[0x1f, 0x8b]) {
+        flate2::read::GzDecoder::new(data)
+            .read_to_end(&mut decompressed)
+            .map_err(|e| format!("Failed to decompress pprof profile: {}", e))?;
+        &decompressed[..]
+    } else {
+        data
+    };
+    let profile = PprofProfile::decode(data).map_err(|e| format!("Invalid pprof profile: {}", e))?;
+
+    let value_index = match sample_index {
+        Some(index) if index >= profile.sample_type.len() => {
+            return Err(format!(
+                "sample_index {} out of range, profile has {} sample types",
+                index,
+                profile.sample_type.len()
+            ))
+        }
+        Some(index) => index,
+        None => profile.sample_type.len().saturating_sub(1),
+    };
+    let string = |index: i64| profile.string_table.get(index as usize).cloned().unwrap_or_default();
+    let functions: HashMap<u64, Frame> = profile
+        .function
+        .iter()
+        .map(|f| {
+            let file = Some(string(f.filename)).filter(|s| !s.is_empty());
+            (f.id, Frame { name: string(f.name), file })
+        })
+        .collect();
+    // 一个位置可能包含多个内联帧，排在前面的是被内联的（更靠近叶子）
+    let locations: HashMap<u64, Vec<Frame>> = profile
+        .location
+        .iter()
+        .map(|l| (l.id, l.line.iter().filter_map(|line| functions.get(&line.function_id).cloned()).collect()))
+        .collect();
+
+    Ok(profile
+        .sample
+        .iter()
+        .filter_map(|sample| {
+            let value = *sample.value.get(value_index)?;
+            if value <= 0 {
+                return None;
+            }
+            let frames = sample
+                .location_id
+                .iter()
+                .filter_map(|id| locations.get(id))
+                .flatten()
+                .cloned()
+                .collect();
+            Some(StackSample { frames, value: value as u64 })
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::codegraph::types::CallRelation;
+    use std::path::PathBuf;
+
+    fn function(name: &str, namespace: &str, file: &str) -> FunctionInfo {
+        FunctionInfo {
+            id: Uuid::new_v4(),
+            name: name.to_string(),
+            file_path: PathBuf::from(file),
+            line_start: 1,
+            line_end: 10,
+            namespace: namespace.to_string(),
+            language: "go".to_string(),
+            ..Default::default()
+        }
+    }
+
+    fn graph() -> (PetCodeGraph, [Uuid; 3]) {
+        let mut graph = PetCodeGraph::new();
+        let main = function("main", "main", "/repo/main.go");
+        let handle = function("handle", "server", "/repo/server/server.go");
+        let parse = function("parse", "server", "/repo/server/parse.go");
+        let ids = [main.id, handle.id, parse.id];
+        let relations = [CallRelation::between(&main, &handle), CallRelation::between(&handle, &parse)];
+        for f in [main, handle, parse] {
+            graph.add_function(f);
+        }
+        for r in relations {
+            graph.add_call_relation(r).unwrap();
+        }
+        (graph, ids)
+    }
+
+    #[test]
+    fn test_normalize_symbol() {
+        assert_eq!(normalize_symbol("server.(*Server).handle").as_deref(), Some("server.Server.handle"));
+        assert_eq!(normalize_symbol("ns::Foo::bar(int, std::vector<int> const&) const").as_deref(), Some("ns::Foo::bar"));
+        assert_eq!(normalize_symbol("app::run::h0123456789abcdef+0x1f").as_deref(), Some("app::run"));
+        assert_eq!(
+            normalize_symbol("<alloc::vec::Vec<T> as core::ops::Drop>::drop").as_deref(),
+            Some("drop")
+        );
+        assert_eq!(normalize_symbol("[unknown]"), None);
+    }
+
+    #[test]
+    fn test_import_collapsed_and_perf() {
+        let (graph, [main, handle, parse]) = graph();
+        let collapsed = "main.main;server.handle;server.parse 30\nmain.main;server.handle 10\nruntime.goexit;main.main 5\n";
+        assert_eq!(ProfileFormat::detect(collapsed.as_bytes()), ProfileFormat::Collapsed);
+        let result = import_profile(collapsed.as_bytes(), None, None, &graph, Path::new("/repo"), 5).unwrap();
+        assert_eq!(result.total_samples, 45);
+        assert_eq!(result.resolved_samples, 45);
+        assert_eq!(result.functions[&parse], FunctionSamples { self_samples: 30, total_samples: 30 });
+        assert_eq!(result.functions[&handle], FunctionSamples { self_samples: 10, total_samples: 40 });
+        assert_eq!(result.functions[&main].total_samples, 45);
+        assert_eq!(result.unresolved_frames[0].name, "runtime.goexit");
+        assert_eq!(
+            result.hot_paths[0].functions,
+            vec!["main.go::main", "server/server.go::handle", "server/parse.go::parse"]
+        );
+        assert_eq!(result.hot_paths[0].percent, 66.67);
+
+        let annotations = result.to_annotations(&graph, Path::new("/repo"));
+        assert_eq!(annotations["server/server.go::handle"][TOTAL_METRIC], 40.0);
+
+        let perf = "app 123 [000] 1.0: 1 cycles:\n\tffff01 server.parse+0x10 (/repo/app)\n\tffff02 server.(*Server).handle+0x8 (/repo/app)\n\tffff03 [unknown] ([kernel])\n\napp 123 [000] 1.1: 1 cycles:\n\tffff02 server.handle (/repo/app)\n";
+        assert_eq!(ProfileFormat::detect(perf.as_bytes()), ProfileFormat::PerfScript);
+        let result = import_profile(perf.as_bytes(), None, None, &graph, Path::new("/repo"), 5).unwrap();
+        assert_eq!(result.total_samples, 2);
+        assert_eq!(result.functions[&handle], FunctionSamples { self_samples: 1, total_samples: 2 });
+        assert_eq!(result.unresolved_frames[0].name, "[unknown]");
+    }
+
+    #[test]
+    fn test_import_pprof() {
+        let (graph, [_, handle, parse]) = graph();
+        let strings = ["", "samples", "count", "cpu", "nanoseconds", "server.parse", "/build/server/parse.go", "server.(*Server).handle"];
+        let profile = PprofProfile {
+            sample_type: vec![PprofValueType { r#type: 1, unit: 2 }, PprofValueType { r#type: 3, unit: 4 }],
+            sample: vec![
+                PprofSample { location_id: vec![1, 2], value: vec![3, 3_000_000] },
+                PprofSample { location_id: vec![2], value: vec![1, 1_000_000] },
+            ],
+            location: vec![
+                PprofLocation { id: 1, line: vec![PprofLine { function_id: 1, line: 12 }] },
+                PprofLocation { id: 2, line: vec![PprofLine { function_id: 2, line: 40 }] },
+            ],
+            function: vec![
+                PprofFunction { id: 1, name: 5, system_name: 5, filename: 6 },
+                PprofFunction { id: 2, name: 7, system_name: 7, filename: 0 },
+            ],
+            string_table: strings.iter().map(|s| s.to_string()).collect(),
+        };
+        let mut encoded = Vec::new();
+        let mut encoder = flate2::write::GzEncoder::new(&mut encoded, flate2::Compression::default());
+        std::io::Write::write_all(&mut encoder, &profile.encode_to_vec()).unwrap();
+        encoder.finish().unwrap();
+
+        let result = import_profile(&encoded, None, None, &graph, Path::new("/repo"), 5).unwrap();
+        assert_eq!(result.format, ProfileFormat::Pprof);
+        assert_eq!(result.total_samples, 4_000_000);
+        assert_eq!(result.functions[&parse].self_samples, 3_000_000);
+        assert_eq!(result.functions[&handle].total_samples, 4_000_000);
+
+        let counts = import_profile(&encoded, None, Some(0), &graph, Path::new("/repo"), 5).unwrap();
+        assert_eq!(counts.total_samples, 4);
+        assert!(import_profile(&encoded, None, Some(2), &graph, Path::new("/repo"), 5).is_err());
+    }
+}
@@ -0,0 +1,278 @@
+use std::collections::{BTreeMap, HashMap};
+
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::codegraph::types::{FunctionInfo, PetCodeGraph};
+
+/// 不具备模块区分度的默认命名空间，遇到时回退为文件路径
+const GENERIC_NAMESPACES: &[&str] = &["", "global", "crate", "default"];
+
+/// 重构建议的阈值配置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RefactorConfig {
+    /// 与目标模块之间的最少调用次数
+    pub min_calls: usize,
+    /// 目标模块耦合占总耦合的最小比例
+    pub min_ratio: f64,
+}
+
+impl Default for RefactorConfig {
+    fn default() -> Self {
+        Self {
+            min_calls: 2,
+            min_ratio: 0.6,
+        }
+    }
+}
+
+/// 建议类型
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SuggestionKind {
+    /// 将函数移动到另一个模块
+    MoveFunction,
+    /// 将整个模块合并到另一个模块
+    MergeModule,
+}
+
+/// 单条重构建议
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RefactorSuggestion {
+    pub kind: SuggestionKind,
+    /// 函数名（模块级建议时为空）
+    pub function_name: Option<String>,
+    pub function_id: Option<Uuid>,
+    pub file_path: Option<String>,
+    pub line_start: Option<usize>,
+    pub from_module: String,
+    pub to_module: String,
+    /// 模块内部耦合（调用次数）
+    pub internal_calls: usize,
+    /// 与目标模块的耦合（调用次数）
+    pub external_calls: usize,
+    /// 目标模块耦合占比
+    pub score: f64,
+    pub reason: String,
+}
+
+/// 按模块聚合后的调用图
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ModuleGraph {
+    /// 模块 -> 函数ID列表
+    pub modules: BTreeMap<String, Vec<Uuid>>,
+    /// (调用方模块, 被调方模块) -> 调用次数
+    pub edges: BTreeMap<(String, String), usize>,
+}
+
+impl ModuleGraph {
+    /// 从函数级调用图聚合出模块级调用图（仅统计已解析的调用）
+    pub fn from_graph(graph: &PetCodeGraph) -> Self {
+        let mut module_graph = ModuleGraph::default();
+        for function in graph.get_all_functions() {
+            if function.is_unresolved() {
+                continue;
+            }
+            module_graph
+                .modules
+                .entry(module_of(function))
+                .or_default()
+                .push(function.id);
+        }
+
+        for relation in graph.get_all_call_relations() {
+            if !relation.is_resolved {
+                continue;
+            }
+            let (Some(caller), Some(callee)) = (
+                graph.get_function_by_id(&relation.caller_id),
+                graph.get_function_by_id(&relation.callee_id),
+            ) else {
+                continue;
+            };
+            *module_graph
+                .edges
+                .entry((module_of(caller), module_of(callee)))
+                .or_insert(0) += 1;
+        }
+        module_graph
+    }
+
+    /// 模块与其他各模块之间的耦合（双向调用次数之和），包含自身即内部耦合
+    pub fn coupling_of(&self, module: &str) -> HashMap<String, usize> {
+        let mut coupling = HashMap::new();
+        for ((from, to), count) in &self.edges {
+            if from == module {
+                *coupling.entry(to.clone()).or_insert(0) += count;
+            } else if to == module {
+                *coupling.entry(from.clone()).or_insert(0) += count;
+            }
+        }
+        coupling
+    }
+}
+
+/// 函数所属模块：有意义的命名空间优先，否则使用文件路径
+pub fn module_of(function: &FunctionInfo) -> String {
+    if function.is_unresolved() || GENERIC_NAMESPACES.contains(&function.namespace.as_str()) {
+        function.file_path.display().to_string()
+    } else {
+        function.namespace.clone()
+    }
+}
+
+/// 基于调用图的移动/合并建议分析器
+pub struct RefactorAdvisor {
+    config: RefactorConfig,
+}
+
+impl RefactorAdvisor {
+    pub fn new(config: RefactorConfig) -> Self {
+        Self { config }
+    }
+
+    /// 生成全部建议，按得分降序排列
+    pub fn suggest(&self, graph: &PetCodeGraph) -> Vec<RefactorSuggestion> {
+        let module_graph = ModuleGraph::from_graph(graph);
+        let mut suggestions = self.suggest_function_moves(graph);
+        suggestions.extend(self.suggest_module_merges(&module_graph));
+        suggestions.sort_by(|a, b| {
+            b.score
+                .partial_cmp(&a.score)
+                .unwrap_or(std::cmp::Ordering::Equal)
+                .then(b.external_calls.cmp(&a.external_calls))
+        });
+        suggestions
+    }
+
+    /// 找出主要被其他模块调用、且与本模块联系很少的函数
+    pub fn suggest_function_moves(&self, graph: &PetCodeGraph) -> Vec<RefactorSuggestion> {
+        let mut suggestions = Vec::new();
+
+        for function in graph.get_all_functions() {
+            if function.is_unresolved() {
+                continue;
+            }
+            let home = module_of(function);
+            let mut coupling: HashMap<String, usize> = HashMap::new();
+
+            let neighbours = graph
+                .get_callers(&function.id)
+                .into_iter()
+                .chain(graph.get_callees(&function.id));
+            for (other, relation) in neighbours {
+                if !relation.is_resolved || other.id == function.id || other.is_unresolved() {
+                    continue;
+                }
+                *coupling.entry(module_of(other)).or_insert(0) += 1;
+            }
+
+            if let Some(suggestion) = self.evaluate(&home, &coupling) {
+                suggestions.push(RefactorSuggestion {
+                    kind: SuggestionKind::MoveFunction,
+                    function_name: Some(function.name.clone()),
+                    function_id: Some(function.id),
+                    file_path: Some(function.file_path.display().to_string()),
+                    line_start: Some(function.line_start),
+                    reason: format!(
+                        "'{}' has {} call(s) with {} but only {} within {}",
+                        function.name, suggestion.external_calls, suggestion.to_module,
+                        suggestion.internal_calls, home
+                    ),
+                    ..suggestion
+                });
+            }
+        }
+        suggestions
+    }
+
+    /// 找出几乎只与另一个模块交互的模块
+    pub fn suggest_module_merges(&self, module_graph: &ModuleGraph) -> Vec<RefactorSuggestion> {
+        let mut suggestions = Vec::new();
+        for module in module_graph.modules.keys() {
+            let coupling = module_graph.coupling_of(module);
+            if let Some(suggestion) = self.evaluate(module, &coupling) {
+                suggestions.push(RefactorSuggestion {
+                    reason: format!(
+                        "module {} has {} call(s) with {} but only {} internal call(s)",
+                        module, suggestion.external_calls, suggestion.to_module, suggestion.internal_calls
+                    ),
+                    ..suggestion
+                });
+            }
+        }
+        suggestions
+    }
+
+    /// 判断耦合分布是否满足移动条件，返回不含具体函数信息的建议
+    fn evaluate(&self, home: &str, coupling: &HashMap<String, usize>) -> Option<RefactorSuggestion> {
+        let internal = coupling.get(home).copied().unwrap_or(0);
+        let total: usize = coupling.values().sum();
+        let (target, external) = coupling
+            .iter()
+            .filter(|(module, _)| module.as_str() != home)
+            .max_by(|a, b| a.1.cmp(b.1).then(b.0.cmp(a.0)))?;
+
+        let score = *external as f64 / total as f64;
+        if *external < self.config.min_calls || *external <= internal || score < self.config.min_ratio {
+            return None;
+        }
+
+        Some(RefactorSuggestion {
+            kind: SuggestionKind::MergeModule,
+            function_name: None,
+            function_id: None,
+            file_path: None,
+            line_start: None,
+            from_module: home.to_string(),
+            to_module: target.clone(),
+            internal_calls: internal,
+            external_calls: *external,
+            score,
+            reason: String::new(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn function(name: &str, file: &str) -> FunctionInfo {
+        FunctionInfo {
+            id: Uuid::new_v4(),
+            name: name.to_string(),
+            file_path: PathBuf::from(file),
+            line_start: 1,
+            line_end: 5,
+            namespace: "global".to_string(),
+            language: "python".to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_suggests_moving_misplaced_function() {
+        let mut graph = PetCodeGraph::new();
+        let helper = function("format_invoice", "utils.py");
+        let util_peer = function("slugify", "utils.py");
+        let a = function("render", "billing.py");
+        let b = function("send", "billing.py");
+        let c = function("archive", "billing.py");
+        for f in [&helper, &util_peer, &a, &b, &c] {
+            graph.add_function(f.clone());
+        }
+        graph.add_call(&a, &helper);
+        graph.add_call(&b, &helper);
+        graph.add_call(&c, &helper);
+        graph.add_call(&a, &b);
+
+        let suggestions = RefactorAdvisor::new(RefactorConfig::default()).suggest_function_moves(&graph);
+        assert_eq!(suggestions.len(), 1);
+        assert_eq!(suggestions[0].function_name.as_deref(), Some("format_invoice"));
+        assert_eq!(suggestions[0].from_module, "utils.py");
+        assert_eq!(suggestions[0].to_module, "billing.py");
+        assert_eq!(suggestions[0].external_calls, 3);
+    }
+}
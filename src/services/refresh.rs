@@ -0,0 +1,257 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use serde::Serialize;
+
+use crate::codegraph::entrypoints::detect_entry_points;
+use crate::codegraph::parser::CodeParser;
+use crate::codegraph::types::{CallKind, CallRelation, EntityGraph, FunctionInfo, PetCodeGraph};
+use crate::services::graph_builder::BuildError;
+use crate::storage::StorageManager;
+
+/// 增加或删除的函数
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct FunctionChange {
+    pub id: String,
+    pub name: String,
+    pub namespace: String,
+    pub file_path: String,
+    pub line_start: usize,
+    pub line_end: usize,
+}
+
+/// 增加或删除的调用边
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct CallChange {
+    pub caller: String,
+    pub caller_file: String,
+    pub callee: String,
+    pub callee_file: String,
+    pub line_number: usize,
+    pub is_resolved: bool,
+    pub call_kind: CallKind,
+}
+
+/// 两个图之间的差异。
+///
+/// 函数按文件、命名空间、名称和签名比较，调用边按两端的函数、是否解析和调用方式比较，都不含行号，
+/// 只移动了位置的函数和调用不算变化。未解析调用的占位函数不列出，只体现在调用边中
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct GraphDelta {
+    pub functions_added: Vec<FunctionChange>,
+    pub functions_removed: Vec<FunctionChange>,
+    pub edges_added: Vec<CallChange>,
+    pub edges_removed: Vec<CallChange>,
+}
+
+type FunctionKey<'a> = (&'a Path, &'a str, &'a str, Option<&'a str>);
+type CallKey<'a> = (&'a Path, &'a str, &'a Path, &'a str, bool, CallKind);
+
+impl GraphDelta {
+    pub fn between(before: &PetCodeGraph, after: &PetCodeGraph) -> Self {
+        let functions = |graph| {
+            let graph: &PetCodeGraph = graph;
+            graph.get_all_functions().into_iter().filter(|f| !f.is_unresolved()).collect::<Vec<_>>()
+        };
+        let (functions_before, functions_after) = (functions(before), functions(after));
+        let (calls_before, calls_after) = (before.get_all_call_relations(), after.get_all_call_relations());
+
+        let mut delta = Self {
+            functions_added: only_in(&functions_after, &functions_before, function_key).into_iter().map(function_change).collect(),
+            functions_removed: only_in(&functions_before, &functions_after, function_key).into_iter().map(function_change).collect(),
+            edges_added: only_in(&calls_after, &calls_before, call_key).into_iter().map(call_change).collect(),
+            edges_removed: only_in(&calls_before, &calls_after, call_key).into_iter().map(call_change).collect(),
+        };
+        delta.functions_added.sort_by(|a, b| (&a.file_path, a.line_start).cmp(&(&b.file_path, b.line_start)));
+        delta.functions_removed.sort_by(|a, b| (&a.file_path, a.line_start).cmp(&(&b.file_path, b.line_start)));
+        delta.edges_added.sort_by(|a, b| (&a.caller_file, a.line_number).cmp(&(&b.caller_file, b.line_number)));
+        delta.edges_removed.sort_by(|a, b| (&a.caller_file, a.line_number).cmp(&(&b.caller_file, b.line_number)));
+        delta
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.functions_added.is_empty()
+            && self.functions_removed.is_empty()
+            && self.edges_added.is_empty()
+            && self.edges_removed.is_empty()
+    }
+}
+
+fn function_key(f: &FunctionInfo) -> FunctionKey<'_> {
+    (f.file_path.as_path(), f.namespace.as_str(), f.name.as_str(), f.signature.as_deref())
+}
+
+fn call_key(r: &CallRelation) -> CallKey<'_> {
+    (r.caller_file.as_path(), r.caller_name.as_str(), r.callee_file.as_path(), r.callee_name.as_str(), r.is_resolved, r.call_kind)
+}
+
+/// `items` 中比 `others` 多出的元素，相同键出现多次时按次数比较
+fn only_in<'a, T, K: std::hash::Hash + Eq>(items: &[&'a T], others: &[&'a T], key: impl Fn(&'a T) -> K) -> Vec<&'a T> {
+    let mut remaining: HashMap<K, usize> = HashMap::new();
+    for item in others {
+        *remaining.entry(key(item)).or_default() += 1;
+    }
+    items
+        .iter()
+        .filter(|item| match remaining.get_mut(&key(item)) {
+            Some(count) if *count > 0 => {
+                *count -= 1;
+                false
+            }
+            _ => true,
+        })
+        .copied()
+        .collect()
+}
+
+fn function_change(function: &FunctionInfo) -> FunctionChange {
+    FunctionChange {
+        id: function.id.to_string(),
+        name: function.name.clone(),
+        namespace: function.namespace.clone(),
+        file_path: function.file_path.display().to_string(),
+        line_start: function.line_start,
+        line_end: function.line_end,
+    }
+}
+
+fn call_change(relation: &CallRelation) -> CallChange {
+    CallChange {
+        caller: relation.caller_name.clone(),
+        caller_file: relation.caller_file.display().to_string(),
+        callee: relation.callee_name.clone(),
+        callee_file: relation.callee_file.display().to_string(),
+        line_number: relation.line_number,
+        is_resolved: relation.is_resolved,
+        call_kind: relation.call_kind,
+    }
+}
+
+/// 一次单文件刷新的结果
+pub struct RefreshOutcome {
+    pub project_id: String,
+    pub project_dir: PathBuf,
+    pub file_path: PathBuf,
+    pub delta: GraphDelta,
+    pub graph: PetCodeGraph,
+}
+
+/// 用 [`CodeParser::refresh_file`] 把单个文件的当前内容应用到已保存的项目图上并保存，返回图的变化。
+///
+/// `file_path` 可以是绝对路径或相对项目目录的路径，文件已删除时删除其中的函数
+pub fn refresh_stored_file(storage: &StorageManager, project_id: &str, file_path: &str) -> Result<RefreshOutcome, BuildError> {
+    let persistence = storage.get_persistence();
+    let record = persistence
+        .get_project_record(project_id)
+        .map_err(|e| BuildError::Failed(format!("Failed to read the project registry: {}", e)))?
+        .ok_or_else(|| BuildError::NotFound(format!("Unknown project: {}", project_id)))?;
+    let project_dir = PathBuf::from(&record.project_dir);
+    let path = project_dir.join(file_path);
+    if !path.starts_with(&project_dir) || path.components().any(|c| c == std::path::Component::ParentDir) {
+        return Err(BuildError::NotFound(format!("{} is not inside {}", file_path, record.project_dir)));
+    }
+    let mut graph = persistence
+        .load_graph(project_id)
+        .map_err(|e| BuildError::Failed(format!("Failed to load graph: {}", e)))?
+        .ok_or_else(|| BuildError::NotFound(format!("No stored graph for project {}", project_id)))?;
+
+    let before = graph.clone();
+    CodeParser::new()
+        .refresh_file(&path, &mut EntityGraph::new(), &mut graph)
+        .map_err(BuildError::Failed)?;
+    let delta = GraphDelta::between(&before, &graph);
+
+    storage
+        .check_quota(project_id, Some(graph.get_all_functions().len()))
+        .map_err(BuildError::QuotaExceeded)?;
+    persistence
+        .save_graph(project_id, &graph)
+        .map_err(|e| BuildError::Failed(format!("Failed to save graph: {}", e)))?;
+    if let Err(e) = persistence.save_entry_points(project_id, &detect_entry_points(&graph)) {
+        tracing::warn!("Failed to save entry points for {}: {}", project_id, e);
+    }
+    storage.mark_updated();
+
+    Ok(RefreshOutcome {
+        project_id: project_id.to_string(),
+        project_dir,
+        file_path: path,
+        delta,
+        graph,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::codegraph::AnalyzeOptions;
+    use crate::services::graph_builder::project_id_for;
+    use std::fs;
+
+    #[test]
+    fn test_refresh_stored_file_returns_delta() {
+        let repo = tempfile::tempdir().unwrap();
+        let data = tempfile::tempdir().unwrap();
+        let root = repo.path();
+        let project_dir = root.display().to_string();
+        fs::write(root.join("main.py"), "from util import helper\n\ndef main():\n    helper()\n").unwrap();
+        fs::write(root.join("util.py"), "def helper():\n    old()\n\ndef old():\n    pass\n").unwrap();
+        let storage = StorageManager::with_base_dir(data.path().to_path_buf(), crate::cli::args::StorageMode::Json);
+        crate::services::build_and_store(&storage, &project_dir, AnalyzeOptions::default()).unwrap();
+        let project_id = project_id_for(&project_dir);
+
+        // 编辑器保存：删除 old，新增 fresh，helper 向下移动一行
+        fs::write(root.join("util.py"), "\ndef helper():\n    fresh()\n\ndef fresh():\n    pass\n").unwrap();
+        let outcome = refresh_stored_file(&storage, &project_id, "util.py").unwrap();
+        let names = |changes: &[FunctionChange]| changes.iter().map(|c| c.name.clone()).collect::<Vec<_>>();
+        let calls = |changes: &[CallChange]| changes.iter().map(|c| (c.caller.clone(), c.callee.clone())).collect::<Vec<_>>();
+        assert_eq!(names(&outcome.delta.functions_added), vec!["fresh"]);
+        assert_eq!(names(&outcome.delta.functions_removed), vec!["old"]);
+        assert_eq!(calls(&outcome.delta.edges_added), vec![("helper".to_string(), "fresh".to_string())]);
+        assert_eq!(calls(&outcome.delta.edges_removed), vec![("helper".to_string(), "old".to_string())]);
+
+        // 其余文件调入该文件的调用仍然连着，并已保存
+        let graph = storage.get_persistence().load_graph(&project_id).unwrap().unwrap();
+        let main = graph.find_functions_by_name("main")[0];
+        let callees: Vec<(String, usize)> = graph.get_callees(&main.id).into_iter().map(|(f, _)| (f.name.clone(), f.line_start)).collect();
+        assert_eq!(callees, vec![("helper".to_string(), 2)]);
+        assert_eq!(graph.validate(), vec![]);
+
+        // 没有变化时差异为空；项目外的路径被拒绝
+        assert!(refresh_stored_file(&storage, &project_id, "util.py").unwrap().delta.is_empty());
+        assert!(matches!(refresh_stored_file(&storage, &project_id, "../elsewhere.py"), Err(BuildError::NotFound(_))));
+    }
+
+    #[test]
+    fn test_entry_points_stored_with_graph() {
+        let repo = tempfile::tempdir().unwrap();
+        let data = tempfile::tempdir().unwrap();
+        let root = repo.path();
+        let project_dir = root.display().to_string();
+        fs::write(root.join("app.py"), "@app.route('/users')\ndef users():\n    pass\n").unwrap();
+        let storage = StorageManager::with_base_dir(data.path().to_path_buf(), crate::cli::args::StorageMode::Json);
+        crate::services::build_and_store(&storage, &project_dir, AnalyzeOptions::default()).unwrap();
+        let project_id = project_id_for(&project_dir);
+
+        // 构建时检测入口并随图保存；刷新文件后重新检测，函数 id 与新图一致
+        let stored = |storage: &StorageManager| {
+            let graph = storage.get_persistence().load_graph(&project_id).unwrap().unwrap();
+            let entry_points = storage.get_persistence().load_entry_points(&project_id).unwrap().unwrap();
+            entry_points
+                .iter()
+                .map(|entry| (graph.get_function_by_id(&entry.function_id).unwrap().name.clone(), entry.decorator.clone(), entry.route.clone()))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(stored(&storage), vec![("users".to_string(), "app.route".to_string(), Some("/users".to_string()))]);
+
+        fs::write(root.join("app.py"), "@app.route('/users')\ndef users():\n    pass\n\n@app.route('/items')\ndef items():\n    pass\n").unwrap();
+        refresh_stored_file(&storage, &project_id, "app.py").unwrap();
+        assert_eq!(
+            stored(&storage),
+            vec![
+                ("users".to_string(), "app.route".to_string(), Some("/users".to_string())),
+                ("items".to_string(), "app.route".to_string(), Some("/items".to_string())),
+            ]
+        );
+    }
+}
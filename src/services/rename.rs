@@ -0,0 +1,365 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::codegraph::parser::CodeParser;
+use crate::codegraph::treesitter::parsers::{get_ast_parser_by_filename, get_tree_sitter_language};
+use crate::codegraph::treesitter::SymbolType;
+use crate::codegraph::types::PetCodeGraph;
+use crate::services::source_scan::enclosing_function;
+
+/// 要重命名的符号种类，未指定时函数和类的声明都算
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RenameKind {
+    Function,
+    Class,
+}
+
+/// 需要修改的位置类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RenameLocationKind {
+    /// 函数/类的声明
+    Declaration,
+    /// 调用点
+    Call,
+    /// import/use/include 语句
+    Import,
+    /// 其他代码中的引用（类型标注、作为值传递、成员访问等）
+    Reference,
+    /// 字符串字面量（反射、路由表、序列化名称等）
+    String,
+    /// 注释和文档
+    Comment,
+}
+
+/// 重命名预览的参数
+#[derive(Debug, Clone)]
+pub struct RenameOptions {
+    pub name: String,
+    pub new_name: Option<String>,
+    pub kind: Option<RenameKind>,
+    pub include_strings: bool,
+    pub include_comments: bool,
+}
+
+/// 一处需要修改的位置，行号和列号从1开始（列按字符计）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RenameLocation {
+    pub line: usize,
+    pub column: usize,
+    pub kind: RenameLocationKind,
+    /// 所在行（去掉首尾空白）
+    pub text: String,
+    /// 所在函数
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub function: Option<String>,
+}
+
+/// 按文件分组的修改位置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RenameFile {
+    pub file_path: String,
+    pub language: String,
+    pub locations: Vec<RenameLocation>,
+}
+
+/// 重命名预览结果
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RenamePreview {
+    pub name: String,
+    pub new_name: Option<String>,
+    pub total_locations: usize,
+    pub counts: BTreeMap<RenameLocationKind, usize>,
+    pub files: Vec<RenameFile>,
+    /// 已存在的同名函数（`new_name` 冲突），`文件:行号` 形式
+    pub conflicts: Vec<String>,
+    /// 未请求而没有列出的字符串/注释中的出现次数
+    pub skipped_strings: usize,
+    pub skipped_comments: usize,
+}
+
+/// 是否为合法标识符（支持的语言里共同的子集，另外允许 JS 的 `$`）
+pub fn is_identifier(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars.next().is_some_and(|c| c.is_alphabetic() || c == '_' || c == '$')
+        && chars.all(|c| c.is_alphanumeric() || c == '_' || c == '$')
+}
+
+fn is_identifier_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_' || c == '$'
+}
+
+/// 文本中作为完整标识符出现的字节偏移
+fn word_occurrences(content: &str, name: &str) -> Vec<usize> {
+    content
+        .match_indices(name)
+        .map(|(start, _)| start)
+        .filter(|&start| {
+            let before = content[..start].chars().next_back();
+            let after = content[start + name.len()..].chars().next();
+            !before.is_some_and(is_identifier_char) && !after.is_some_and(is_identifier_char)
+        })
+        .collect()
+}
+
+/// 词法上下文
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Lexical {
+    Code,
+    String,
+    Comment,
+}
+
+/// 从语法树叶子向上查找：注释、字符串（模板字符串的插值部分仍是代码）
+fn lexical_from_tree(tree: &tree_sitter::Tree, start: usize, end: usize) -> Lexical {
+    let mut node = tree.root_node().descendant_for_byte_range(start, end);
+    while let Some(current) = node {
+        let kind = current.kind();
+        if kind.contains("comment") {
+            return Lexical::Comment;
+        }
+        if kind == "template_substitution" || kind == "interpolation" {
+            return Lexical::Code;
+        }
+        if kind.contains("string") || kind == "char_literal" {
+            return Lexical::String;
+        }
+        node = current.parent();
+    }
+    Lexical::Code
+}
+
+/// 没有语法树的语言：位于注释符号内视为注释，行内之前的引号数为奇数视为字符串
+fn lexical_from_text(line_prefix: &str, in_comment: bool) -> Lexical {
+    if in_comment {
+        return Lexical::Comment;
+    }
+    let quotes = line_prefix.chars().filter(|&c| c == '"' || c == '\'').count();
+    if quotes % 2 == 1 {
+        Lexical::String
+    } else {
+        Lexical::Code
+    }
+}
+
+/// 代码中出现位置的分类依据：AST符号的类型、名称和字节范围
+struct SymbolSpan {
+    symbol_type: SymbolType,
+    name: String,
+    full: (usize, usize),
+    declaration: (usize, usize),
+}
+
+fn contains(range: (usize, usize), offset: usize) -> bool {
+    range.0 <= offset && offset < range.1
+}
+
+fn declaration_kind(symbol_type: &SymbolType) -> Option<RenameKind> {
+    match symbol_type {
+        SymbolType::FunctionDeclaration => Some(RenameKind::Function),
+        SymbolType::StructDeclaration => Some(RenameKind::Class),
+        _ => None,
+    }
+}
+
+/// 代码中的出现位置分类，返回None表示是另一种符号的同名声明
+fn classify_code(spans: &[SymbolSpan], options: &RenameOptions, content: &str, offset: usize) -> Option<RenameLocationKind> {
+    let named = |span: &&SymbolSpan| span.name == options.name;
+    if let Some(span) = spans.iter().filter(named).find(|s| declaration_kind(&s.symbol_type).is_some() && contains(s.declaration, offset)) {
+        let kind = declaration_kind(&span.symbol_type);
+        return (options.kind.is_none() || options.kind == kind).then_some(RenameLocationKind::Declaration);
+    }
+    if spans.iter().any(|s| s.symbol_type == SymbolType::ImportDeclaration && contains(s.full, offset)) {
+        return Some(RenameLocationKind::Import);
+    }
+    let followed_by_paren = content[offset + options.name.len()..].trim_start().starts_with('(');
+    if followed_by_paren && spans.iter().filter(named).any(|s| s.symbol_type == SymbolType::FunctionCall && contains(s.full, offset)) {
+        return Some(RenameLocationKind::Call);
+    }
+    Some(RenameLocationKind::Reference)
+}
+
+/// 预览单个文件，文件不含该名称或语言不受支持时返回None
+fn preview_file(graph: &PetCodeGraph, file_path: &Path, options: &RenameOptions, preview: &mut RenamePreview) -> Option<RenameFile> {
+    let content = fs::read_to_string(file_path).ok()?;
+    let occurrences = word_occurrences(&content, &options.name);
+    if occurrences.is_empty() {
+        return None;
+    }
+    let path = file_path.to_path_buf();
+    let (mut parser, language_id) = get_ast_parser_by_filename(&path).ok()?;
+    let spans: Vec<SymbolSpan> = parser
+        .parse(&content, &path)
+        .iter()
+        .map(|symbol| {
+            let symbol = symbol.read();
+            let (full, declaration) = (symbol.full_range(), symbol.declaration_range());
+            SymbolSpan {
+                symbol_type: symbol.symbol_type(),
+                name: symbol.name().to_string(),
+                full: (full.start_byte, full.end_byte),
+                declaration: (declaration.start_byte, declaration.end_byte),
+            }
+        })
+        .collect();
+    let tree = get_tree_sitter_language(language_id).and_then(|language| {
+        let mut ts_parser = tree_sitter::Parser::new();
+        ts_parser.set_language(&language).ok()?;
+        ts_parser.parse(&content, None)
+    });
+
+    let mut locations = Vec::new();
+    for offset in occurrences {
+        let line_start = content[..offset].rfind('\n').map_or(0, |i| i + 1);
+        let lexical = match &tree {
+            Some(tree) => lexical_from_tree(tree, offset, offset + options.name.len()),
+            None => {
+                let in_comment = spans
+                    .iter()
+                    .any(|s| s.symbol_type == SymbolType::CommentDefinition && contains(s.full, offset));
+                lexical_from_text(&content[line_start..offset], in_comment)
+            }
+        };
+        let kind = match lexical {
+            Lexical::String if !options.include_strings => {
+                preview.skipped_strings += 1;
+                continue;
+            }
+            Lexical::Comment if !options.include_comments => {
+                preview.skipped_comments += 1;
+                continue;
+            }
+            Lexical::String => RenameLocationKind::String,
+            Lexical::Comment => RenameLocationKind::Comment,
+            Lexical::Code => match classify_code(&spans, options, &content, offset) {
+                Some(kind) => kind,
+                None => continue,
+            },
+        };
+
+        let line = content[..offset].matches('\n').count() + 1;
+        let line_end = content[offset..].find('\n').map_or(content.len(), |i| offset + i);
+        locations.push(RenameLocation {
+            line,
+            column: content[line_start..offset].chars().count() + 1,
+            kind,
+            text: content[line_start..line_end].trim().to_string(),
+            function: enclosing_function(graph, file_path, line)
+                .filter(|f| !f.is_unresolved())
+                .map(|f| f.name.clone()),
+        });
+    }
+
+    (!locations.is_empty()).then(|| RenameFile {
+        file_path: file_path.display().to_string(),
+        language: language_id.to_string(),
+        locations,
+    })
+}
+
+/// 列出重命名函数/类需要修改的所有位置，按文件分组。扫描图中的文件以及项目目录下
+/// 的源文件（没有函数的文件也可能 import 或引用该符号）
+pub fn preview_rename(graph: &PetCodeGraph, project_dir: Option<&Path>, options: &RenameOptions) -> RenamePreview {
+    let mut files: BTreeSet<PathBuf> = graph.file_functions.keys().cloned().collect();
+    if let Some(dir) = project_dir.filter(|dir| dir.is_dir()) {
+        files.extend(CodeParser::new().scan_directory(dir));
+    }
+
+    let mut preview = RenamePreview {
+        name: options.name.clone(),
+        new_name: options.new_name.clone(),
+        ..Default::default()
+    };
+    for file_path in &files {
+        if let Some(file) = preview_file(graph, file_path, options, &mut preview) {
+            preview.files.push(file);
+        }
+    }
+    for location in preview.files.iter().flat_map(|f| &f.locations) {
+        *preview.counts.entry(location.kind).or_default() += 1;
+    }
+    preview.total_locations = preview.counts.values().sum();
+
+    if let Some(new_name) = &options.new_name {
+        preview.conflicts = graph
+            .find_functions_by_name(new_name)
+            .into_iter()
+            .filter(|f| !f.is_unresolved())
+            .map(|f| format!("{}:{}", f.file_path.display(), f.line_start))
+            .collect();
+        preview.conflicts.sort();
+    }
+    preview
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_word_occurrences() {
+        assert_eq!(word_occurrences("load(x); loader(); reload; load", "load"), vec![0, 27]);
+        assert!(is_identifier("$el_2") && !is_identifier("2x") && !is_identifier("a.b"));
+    }
+
+    #[test]
+    fn test_preview_rename_python() {
+        let dir = std::env::temp_dir().join(format!("rename_preview_{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(
+            dir.join("util.py"),
+            "def load_config(path):\n    \"\"\"Used by load_config callers.\"\"\"\n    return path\n",
+        )
+        .unwrap();
+        fs::write(
+            dir.join("app.py"),
+            "from util import load_config\n\n# load_config is called once\ndef main():\n    cfg = load_config('a.toml')\n    hooks = {'load': load_config}\n    log('load_config done')\n",
+        )
+        .unwrap();
+
+        let options = RenameOptions {
+            name: "load_config".to_string(),
+            new_name: Some("main".to_string()),
+            kind: Some(RenameKind::Function),
+            include_strings: false,
+            include_comments: true,
+        };
+        let mut graph = PetCodeGraph::new();
+        graph.add_function(crate::codegraph::types::FunctionInfo {
+            id: uuid::Uuid::new_v4(),
+            name: "main".to_string(),
+            file_path: dir.join("app.py"),
+            line_start: 4,
+            line_end: 7,
+            namespace: "app".to_string(),
+            language: "python".to_string(),
+            ..Default::default()
+        });
+        let preview = preview_rename(&graph, Some(&dir), &options);
+        let _ = fs::remove_dir_all(&dir);
+
+        let kinds: Vec<(String, usize, RenameLocationKind)> = preview
+            .files
+            .iter()
+            .flat_map(|f| f.locations.iter().map(move |l| (f.file_path.rsplit('/').next().unwrap().to_string(), l.line, l.kind)))
+            .collect();
+        assert_eq!(
+            kinds,
+            vec![
+                ("app.py".to_string(), 1, RenameLocationKind::Import),
+                ("app.py".to_string(), 3, RenameLocationKind::Comment),
+                ("app.py".to_string(), 5, RenameLocationKind::Call),
+                ("app.py".to_string(), 6, RenameLocationKind::Reference),
+                ("util.py".to_string(), 1, RenameLocationKind::Declaration),
+            ]
+        );
+        assert_eq!(preview.skipped_strings, 2);
+        assert_eq!(preview.files[0].locations[2].function.as_deref(), Some("main"));
+        assert_eq!(preview.files[0].locations[2].column, 11);
+        assert_eq!(preview.conflicts.len(), 1);
+    }
+}
@@ -0,0 +1,448 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use uuid::Uuid;
+
+use crate::codegraph::diagram::language_color;
+use crate::codegraph::types::{FunctionInfo, PetCodeGraph};
+use crate::services::graph_export::{node_metrics, stable_ids, NodeMetrics};
+
+/// 仪表盘中排行榜的条数
+const TOP_N: usize = 15;
+
+/// 函数页面中代码片段的最大行数
+const MAX_SNIPPET_LINES: usize = 200;
+
+/// 报告生成结果
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ReportSummary {
+    pub modules: usize,
+    pub functions: usize,
+    /// 写入的HTML页面数（含首页）
+    pub pages: usize,
+}
+
+fn html_escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&#39;")
+}
+
+/// 套用页面模板；图表配置嵌入 `<script>`，需要避免提前闭合标签
+fn render_page(title: &str, nav: &str, content: &str, charts: &[Value]) -> String {
+    let charts = serde_json::to_string(charts).unwrap_or_else(|_| "[]".to_string()).replace("</", "<\\/");
+    include_str!("templates/report_page.html")
+        .replace("__TITLE__", &html_escape(title))
+        .replace("__NAV__", nav)
+        .replace("__CHARTS__", &charts)
+        .replace("__CONTENT__", content)
+}
+
+struct Site<'a> {
+    root: &'a Path,
+    /// 源文件 -> 模块页面文件名（位于 `modules/`）
+    modules: BTreeMap<&'a PathBuf, String>,
+    function_pages: HashMap<Uuid, String>,
+    metrics: HashMap<Uuid, NodeMetrics>,
+}
+
+impl Site<'_> {
+    fn display_path(&self, file: &Path) -> String {
+        file.strip_prefix(self.root).unwrap_or(file).display().to_string()
+    }
+
+    /// 从子目录页面（`modules/`、`functions/`）指向函数页面的链接，未解析的函数只显示名称
+    fn function_link(&self, function: &FunctionInfo) -> String {
+        match self.function_pages.get(&function.id) {
+            Some(page) => format!("<a href=\"../functions/{}\">{}</a>", page, html_escape(&function.name)),
+            None => format!("<code>{}</code>", html_escape(&function.name)),
+        }
+    }
+
+    fn metrics(&self, function: &FunctionInfo) -> NodeMetrics {
+        self.metrics.get(&function.id).cloned().unwrap_or_default()
+    }
+}
+
+fn complexity_cell(metrics: &NodeMetrics) -> String {
+    metrics.complexity.map(|c| c.to_string()).unwrap_or_else(|| "-".to_string())
+}
+
+/// 将路径转换为可作文件名的形式
+fn page_name(relative: &str, used: &mut HashSet<String>) -> String {
+    let base: String = relative
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() || c == '.' || c == '-' { c } else { '_' })
+        .collect();
+    let mut name = format!("{}.html", base);
+    let mut n = 2;
+    while !used.insert(name.clone()) {
+        name = format!("{}-{}.html", base, n);
+        n += 1;
+    }
+    name
+}
+
+fn card(value: impl std::fmt::Display, label: &str) -> String {
+    format!("<div class=\"card\"><div class=\"value\">{}</div><div class=\"label\">{}</div></div>", value, label)
+}
+
+fn bar_chart(id: &str, title: &str, labels: Vec<String>, values: Vec<Value>) -> Value {
+    json!({
+        "id": id,
+        "option": {
+            "title": { "text": title, "left": "center", "textStyle": { "fontSize": 14 } },
+            "tooltip": {},
+            "grid": { "left": 160, "right": 24, "top": 40, "bottom": 24 },
+            "xAxis": { "type": "value", "minInterval": 1 },
+            "yAxis": { "type": "category", "inverse": true, "data": labels },
+            "series": [{ "type": "bar", "data": values, "itemStyle": { "color": "#667eea" } }],
+        }
+    })
+}
+
+/// 以函数为节点的力导向图，节点可点击跳转到函数页面
+fn graph_chart(id: &str, site: &Site, graph: &PetCodeGraph, focus: &HashSet<Uuid>) -> Value {
+    let mut node_ids: HashSet<Uuid> = focus.clone();
+    let mut links = Vec::new();
+    for function_id in focus {
+        for (callee, _) in graph.get_callees(function_id) {
+            node_ids.insert(callee.id);
+            links.push(json!({ "source": function_id.to_string(), "target": callee.id.to_string() }));
+        }
+        for (caller, _) in graph.get_callers(function_id) {
+            if !focus.contains(&caller.id) {
+                node_ids.insert(caller.id);
+                links.push(json!({ "source": caller.id.to_string(), "target": function_id.to_string() }));
+            }
+        }
+    }
+
+    let mut nodes: Vec<&FunctionInfo> = node_ids.iter().filter_map(|id| graph.get_function_by_id(id)).collect();
+    nodes.sort_by(|a, b| (&a.file_path, a.line_start, &a.name).cmp(&(&b.file_path, b.line_start, &b.name)));
+    let nodes: Vec<Value> = nodes
+        .into_iter()
+        .map(|function| {
+            let in_focus = focus.contains(&function.id);
+            let color = if function.is_unresolved() { "#cccccc" } else { language_color(&function.language) };
+            json!({
+                "id": function.id.to_string(),
+                "name": function.name,
+                "symbolSize": if in_focus { 22 } else { 12 },
+                "itemStyle": { "color": color, "opacity": if in_focus { 1.0 } else { 0.6 } },
+                "label": { "show": true },
+                "url": site.function_pages.get(&function.id).map(|page| format!("../functions/{}", page)),
+            })
+        })
+        .collect();
+
+    json!({
+        "id": id,
+        "option": {
+            "tooltip": {},
+            "series": [{
+                "type": "graph",
+                "layout": "force",
+                "roam": true,
+                "draggable": true,
+                "force": { "repulsion": 180, "edgeLength": 80 },
+                "edgeSymbol": ["none", "arrow"],
+                "data": nodes,
+                "links": links,
+                "lineStyle": { "opacity": 0.6, "curveness": 0.1 },
+            }],
+        }
+    })
+}
+
+fn render_index(site: &Site, graph: &PetCodeGraph, by_file: &BTreeMap<&PathBuf, Vec<&FunctionInfo>>) -> String {
+    let functions: Vec<&FunctionInfo> = by_file.values().flatten().copied().collect();
+    let relations = graph.get_all_call_relations();
+    let resolved = relations.iter().filter(|r| r.is_resolved).count();
+    let mut languages: BTreeMap<&str, usize> = BTreeMap::new();
+    for function in &functions {
+        *languages.entry(function.language.as_str()).or_default() += 1;
+    }
+
+    let mut content = String::from("<div class=\"cards\">");
+    content.push_str(&card(by_file.len(), "modules"));
+    content.push_str(&card(functions.len(), "functions"));
+    content.push_str(&card(relations.len(), "calls"));
+    let share = if relations.is_empty() { 100.0 } else { resolved as f64 * 100.0 / relations.len() as f64 };
+    content.push_str(&card(format!("{:.0}%", share), "calls resolved"));
+    content.push_str(&card(languages.len(), "languages"));
+    content.push_str("</div>\n");
+
+    let top = |key: &dyn Fn(&NodeMetrics) -> usize| -> (Vec<String>, Vec<Value>) {
+        let mut ranked: Vec<(&FunctionInfo, usize)> = functions.iter().map(|f| (*f, key(&site.metrics(f)))).collect();
+        ranked.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.name.cmp(&b.0.name)));
+        ranked
+            .into_iter()
+            .filter(|(_, value)| *value > 0)
+            .take(TOP_N)
+            .map(|(function, value)| {
+                let url = site.function_pages.get(&function.id).map(|page| format!("functions/{}", page));
+                (function.name.clone(), json!({ "value": value, "url": url }))
+            })
+            .unzip()
+    };
+    let (fan_in_labels, fan_in_values) = top(&|m| m.fan_in);
+    let (complexity_labels, complexity_values) = top(&|m| m.complexity.unwrap_or(0));
+    let (fan_out_labels, fan_out_values) = top(&|m| m.fan_out);
+    let language_data: Vec<Value> = languages
+        .iter()
+        .map(|(language, count)| json!({ "name": language, "value": count, "itemStyle": { "color": language_color(language) } }))
+        .collect();
+    let charts = vec![
+        bar_chart("chart-fan-in", "Most called (fan-in)", fan_in_labels, fan_in_values),
+        bar_chart("chart-complexity", "Most complex (cyclomatic)", complexity_labels, complexity_values),
+        bar_chart("chart-fan-out", "Most calls made (fan-out)", fan_out_labels, fan_out_values),
+        json!({
+            "id": "chart-languages",
+            "option": {
+                "title": { "text": "Functions by language", "left": "center", "textStyle": { "fontSize": 14 } },
+                "tooltip": {},
+                "series": [{ "type": "pie", "radius": ["35%", "65%"], "data": language_data }],
+            }
+        }),
+    ];
+    content.push_str("<h2>Dashboard</h2>\n<div class=\"charts\">");
+    for chart in &charts {
+        content.push_str(&format!("<div class=\"chart\" id=\"{}\"></div>", chart["id"].as_str().unwrap_or_default()));
+    }
+    content.push_str("</div>\n");
+
+    // 按调用者的语言统计调用解析情况
+    let mut resolution: BTreeMap<&str, (usize, usize)> = BTreeMap::new();
+    for relation in &relations {
+        let Some(caller) = graph.get_function_by_id(&relation.caller_id) else { continue };
+        let entry = resolution.entry(caller.language.as_str()).or_default();
+        entry.0 += 1;
+        if relation.is_resolved {
+            entry.1 += 1;
+        }
+    }
+    content.push_str("<h2>Call resolution</h2>\n<table><tr><th>Language</th><th class=\"num\">Calls</th><th class=\"num\">Resolved</th><th class=\"num\">Unresolved</th><th class=\"num\">Resolved %</th></tr>\n");
+    for (language, (calls, resolved)) in &resolution {
+        content.push_str(&format!(
+            "<tr><td>{}</td><td class=\"num\">{}</td><td class=\"num\">{}</td><td class=\"num\">{}</td><td class=\"num\">{:.0}%</td></tr>\n",
+            html_escape(language),
+            calls,
+            resolved,
+            calls - resolved,
+            *resolved as f64 * 100.0 / *calls as f64
+        ));
+    }
+    content.push_str("</table>\n");
+
+    content.push_str("<h2>Modules</h2>\n<table><tr><th>Module</th><th>Language</th><th class=\"num\">Functions</th><th class=\"num\">Fan-in</th><th class=\"num\">Max complexity</th></tr>\n");
+    for (file, functions) in by_file {
+        let fan_in: usize = functions.iter().map(|f| site.metrics(f).fan_in).sum();
+        let max_complexity = functions.iter().filter_map(|f| site.metrics(f).complexity).max();
+        content.push_str(&format!(
+            "<tr><td><a href=\"modules/{}\">{}</a></td><td>{}</td><td class=\"num\">{}</td><td class=\"num\">{}</td><td class=\"num\">{}</td></tr>\n",
+            site.modules[file],
+            html_escape(&site.display_path(file)),
+            html_escape(&functions[0].language),
+            functions.len(),
+            fan_in,
+            max_complexity.map(|c| c.to_string()).unwrap_or_else(|| "-".to_string())
+        ));
+    }
+    content.push_str("</table>\n");
+
+    render_page("Code Graph Report", &html_escape(&site.root.display().to_string()), &content, &charts)
+}
+
+fn render_module(site: &Site, graph: &PetCodeGraph, file: &Path, functions: &[&FunctionInfo]) -> String {
+    let title = site.display_path(file);
+    let nav = "<a href=\"../index.html\">Index</a>".to_string();
+    let mut content = format!(
+        "<p class=\"muted\">{} · {} functions</p>\n<h2>Call graph</h2>\n<div class=\"chart graph\" id=\"chart-graph\"></div>\n",
+        html_escape(&functions[0].language),
+        functions.len()
+    );
+
+    content.push_str("<h2>Functions</h2>\n<table><tr><th>Function</th><th class=\"num\">Lines</th><th class=\"num\">Fan-in</th><th class=\"num\">Fan-out</th><th class=\"num\">Complexity</th></tr>\n");
+    for function in functions {
+        let metrics = site.metrics(function);
+        content.push_str(&format!(
+            "<tr><td>{}</td><td class=\"num\">{}-{}</td><td class=\"num\">{}</td><td class=\"num\">{}</td><td class=\"num\">{}</td></tr>\n",
+            site.function_link(function),
+            function.line_start,
+            function.line_end,
+            metrics.fan_in,
+            metrics.fan_out,
+            complexity_cell(&metrics)
+        ));
+    }
+    content.push_str("</table>\n");
+
+    let focus: HashSet<Uuid> = functions.iter().map(|f| f.id).collect();
+    let charts = vec![graph_chart("chart-graph", site, graph, &focus)];
+    render_page(&title, &nav, &content, &charts)
+}
+
+/// 读取函数源码，带行号；超过上限时截断
+fn snippet(function: &FunctionInfo) -> Option<String> {
+    let source = fs::read_to_string(&function.file_path).ok()?;
+    let start = function.line_start.max(1);
+    let count = (function.line_end.saturating_sub(start) + 1).min(MAX_SNIPPET_LINES);
+    let mut html = String::new();
+    for (offset, line) in source.lines().skip(start - 1).take(count).enumerate() {
+        html.push_str(&format!("<span class=\"ln\">{}</span>{}\n", start + offset, html_escape(line)));
+    }
+    if function.line_end + 1 - start > count {
+        html.push_str("<span class=\"ln\"></span>…\n");
+    }
+    Some(html)
+}
+
+fn render_function(site: &Site, graph: &PetCodeGraph, function: &FunctionInfo) -> String {
+    let module = &site.modules[&function.file_path];
+    let nav = format!(
+        "<a href=\"../index.html\">Index</a> / <a href=\"../modules/{}\">{}</a>",
+        module,
+        html_escape(&site.display_path(&function.file_path))
+    );
+    let metrics = site.metrics(function);
+    let mut content = String::from("<div class=\"cards\">");
+    content.push_str(&card(format!("{}-{}", function.line_start, function.line_end), "lines"));
+    content.push_str(&card(metrics.fan_in, "fan-in"));
+    content.push_str(&card(metrics.fan_out, "fan-out"));
+    content.push_str(&card(complexity_cell(&metrics), "complexity"));
+    content.push_str("</div>\n");
+    if !function.namespace.is_empty() {
+        content.push_str(&format!("<p class=\"muted\">namespace <code>{}</code></p>\n", html_escape(&function.namespace)));
+    }
+
+    let list = |related: Vec<&FunctionInfo>| -> String {
+        let mut seen = HashSet::new();
+        let items: Vec<String> = related
+            .into_iter()
+            .filter(|f| seen.insert(f.id))
+            .map(|f| format!("<li>{}</li>", site.function_link(f)))
+            .collect();
+        if items.is_empty() {
+            "<p class=\"muted\">None</p>\n".to_string()
+        } else {
+            format!("<ul class=\"links\">{}</ul>\n", items.join(""))
+        }
+    };
+    content.push_str("<h2>Calls</h2>\n");
+    content.push_str(&list(graph.get_callees(&function.id).into_iter().map(|(f, _)| f).collect()));
+    content.push_str("<h2>Called by</h2>\n");
+    content.push_str(&list(graph.get_callers(&function.id).into_iter().map(|(f, _)| f).collect()));
+
+    if let Some(code) = snippet(function) {
+        content.push_str(&format!("<h2>Source</h2>\n<pre><code>{}</code></pre>\n", code));
+    }
+    render_page(&function.name, &nav, &content, &[])
+}
+
+fn write_page(out: &Path, relative: &str, html: &str) -> Result<(), String> {
+    let path = out.join(relative);
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+    }
+    fs::write(&path, html).map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+}
+
+/// 生成静态HTML报告：首页（概览、度量仪表盘、模块列表）、每个模块一页（调用图和函数表）、
+/// 每个函数一页（度量、调用关系和源码片段）。`root` 用于显示相对路径
+pub fn write_report(graph: &PetCodeGraph, root: &Path, out: &Path) -> Result<ReportSummary, String> {
+    let mut by_file: BTreeMap<&PathBuf, Vec<&FunctionInfo>> = BTreeMap::new();
+    for function in graph.graph.node_weights().filter(|f| !f.is_unresolved()) {
+        by_file.entry(&function.file_path).or_default().push(function);
+    }
+    for functions in by_file.values_mut() {
+        functions.sort_by(|a, b| (a.line_start, &a.name).cmp(&(b.line_start, &b.name)));
+    }
+
+    let mut used = HashSet::new();
+    let modules = by_file
+        .keys()
+        .map(|file| {
+            let relative = file.strip_prefix(root).unwrap_or(file).display().to_string();
+            (*file, page_name(&relative, &mut used))
+        })
+        .collect();
+    let ids = stable_ids(graph);
+    let function_pages = by_file
+        .values()
+        .flatten()
+        .map(|f| (f.id, format!("{}.html", ids[&f.id])))
+        .collect();
+    let site = Site {
+        root,
+        modules,
+        function_pages,
+        metrics: node_metrics(graph),
+    };
+
+    let mut summary = ReportSummary::default();
+    write_page(out, "index.html", &render_index(&site, graph, &by_file))?;
+    summary.pages += 1;
+    for (file, functions) in &by_file {
+        write_page(out, &format!("modules/{}", site.modules[file]), &render_module(&site, graph, file, functions))?;
+        summary.modules += 1;
+        summary.pages += 1;
+        for function in functions {
+            let page = &site.function_pages[&function.id];
+            write_page(out, &format!("functions/{}", page), &render_function(&site, graph, function))?;
+            summary.functions += 1;
+            summary.pages += 1;
+        }
+    }
+    Ok(summary)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::codegraph::parser::CodeParser;
+    use crate::codegraph::AnalyzeOptions;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_report_links_modules_and_functions() {
+        let temp_dir = TempDir::new().unwrap();
+        fs::write(
+            temp_dir.path().join("lib.rs"),
+            "pub fn check(a: i32) -> bool {\n    if a < 10 {\n        return helper();\n    }\n    a > 0 && helper()\n}\n\npub fn helper() -> bool {\n    true\n}\n",
+        )
+        .unwrap();
+        let options = AnalyzeOptions {
+            storage_dir: Some(temp_dir.path().join(".codegraph_db")),
+            force_rebuild: true,
+            ..Default::default()
+        };
+        let graph = CodeParser::with_options(options).build_petgraph_code_graph(temp_dir.path()).unwrap();
+
+        let out = temp_dir.path().join("report");
+        let summary = write_report(&graph, temp_dir.path(), &out).unwrap();
+        assert_eq!((summary.modules, summary.functions, summary.pages), (1, 2, 4));
+
+        let index = fs::read_to_string(out.join("index.html")).unwrap();
+        assert!(index.contains("<a href=\"modules/lib.rs.html\">lib.rs</a>"));
+        assert!(index.contains("\"id\":\"chart-fan-in\""));
+        assert!(index.contains("<tr><td>rust</td><td class=\"num\">2</td><td class=\"num\">2</td><td class=\"num\">0</td><td class=\"num\">100%</td></tr>"));
+
+        let module = fs::read_to_string(out.join("modules/lib.rs.html")).unwrap();
+        let ids = stable_ids(&graph);
+        let helper = graph.find_functions_by_name("helper")[0];
+        assert!(module.contains(&format!("<a href=\"../functions/{}.html\">helper</a>", ids[&helper.id])));
+        assert!(module.contains("\"type\":\"graph\""));
+
+        let check = graph.find_functions_by_name("check")[0];
+        let page = fs::read_to_string(out.join(format!("functions/{}.html", ids[&check.id]))).unwrap();
+        assert!(page.contains("<span class=\"ln\">2</span>    if a &lt; 10 {"));
+        assert!(page.contains(&format!("<li><a href=\"../functions/{}.html\">helper</a></li>", ids[&helper.id])));
+        assert!(page.contains("<div class=\"value\">3</div><div class=\"label\">complexity</div>"));
+    }
+}
@@ -0,0 +1,373 @@
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use glob::{MatchOptions, Pattern};
+use serde::{Deserialize, Serialize};
+use tracing::debug;
+use uuid::Uuid;
+
+use crate::codegraph::types::{FunctionInfo, PetCodeGraph};
+use crate::services::risk::file_churn;
+
+/// CODEOWNERS 的常见位置，按GitHub的查找顺序排列
+const CODEOWNERS_LOCATIONS: &[&str] = &[".github/CODEOWNERS", "CODEOWNERS", "docs/CODEOWNERS", ".gitlab/CODEOWNERS"];
+
+/// 每位评审者返回的受影响文件数上限
+const MAX_FILES_PER_REVIEWER: usize = 10;
+
+/// 一条CODEOWNERS规则
+#[derive(Debug, Clone)]
+struct OwnerRule {
+    pattern: String,
+    owners: Vec<String>,
+}
+
+/// CODEOWNERS文件，后出现的规则优先
+#[derive(Debug, Clone, Default)]
+pub struct CodeOwners {
+    rules: Vec<OwnerRule>,
+}
+
+impl CodeOwners {
+    pub fn parse(content: &str) -> Self {
+        let rules = content
+            .lines()
+            .map(|line| line.split(" #").next().unwrap_or("").trim())
+            // 跳过注释、空行和GitLab的 `[Section]` 标题
+            .filter(|line| !line.is_empty() && !line.starts_with('#') && !line.starts_with('['))
+            .filter_map(|line| {
+                let mut parts = line.split_whitespace();
+                let pattern = parts.next()?.to_string();
+                Some(OwnerRule { pattern, owners: parts.map(str::to_string).collect() })
+            })
+            .collect();
+        Self { rules }
+    }
+
+    /// 在项目目录的常见位置查找CODEOWNERS
+    pub fn discover(project_dir: &Path) -> Option<Self> {
+        CODEOWNERS_LOCATIONS
+            .iter()
+            .map(|location| project_dir.join(location))
+            .find_map(|path| fs::read_to_string(path).ok())
+            .map(|content| Self::parse(&content))
+    }
+
+    /// 相对路径的所有者；最后一条匹配的规则生效，没有所有者的规则表示显式取消归属
+    pub fn owners_of(&self, relative: &Path) -> Option<&[String]> {
+        let relative = relative.to_string_lossy().replace('\\', "/");
+        self.rules
+            .iter()
+            .rev()
+            .find(|rule| pattern_matches(&rule.pattern, &relative))
+            .map(|rule| rule.owners.as_slice())
+            .filter(|owners| !owners.is_empty())
+    }
+}
+
+/// 按CODEOWNERS（gitignore风格）语义匹配：以 `/` 开头或中间含 `/` 的模式相对仓库根目录，
+/// 否则匹配任意层级的文件或目录名；以 `/` 结尾的模式只匹配目录
+fn pattern_matches(pattern: &str, relative: &str) -> bool {
+    let dir_only = pattern.ends_with('/');
+    let trimmed = pattern.trim_start_matches('/').trim_end_matches('/');
+    if trimmed.is_empty() || trimmed == "*" || trimmed == "**" {
+        return true;
+    }
+    let anchored = pattern.starts_with('/') || trimmed.contains('/');
+    let Ok(glob) = Pattern::new(trimmed) else {
+        return false;
+    };
+    let options = MatchOptions { require_literal_separator: true, ..Default::default() };
+
+    let components: Vec<&str> = relative.split('/').filter(|c| !c.is_empty()).collect();
+    let count = components.len();
+    // 匹配到某个上级目录时，目录下的所有文件都归属该规则
+    (1..=count).any(|end| {
+        if dir_only && end == count {
+            return false;
+        }
+        if anchored {
+            glob.matches_with(&components[..end].join("/"), options)
+        } else {
+            glob.matches_with(components[end - 1], options)
+        }
+    })
+}
+
+/// 通过git历史统计每个文件提交次数最多的作者邮箱，键为规范化后的绝对路径
+pub fn file_top_authors(project_dir: &Path) -> HashMap<PathBuf, String> {
+    let mut authors = HashMap::new();
+    let toplevel = match Command::new("git")
+        .arg("-C")
+        .arg(project_dir)
+        .args(["rev-parse", "--show-toplevel"])
+        .output()
+    {
+        Ok(output) if output.status.success() => PathBuf::from(String::from_utf8_lossy(&output.stdout).trim()),
+        _ => {
+            debug!("{} is not a git repository, authors are unavailable", project_dir.display());
+            return authors;
+        }
+    };
+    let output = match Command::new("git")
+        .arg("-C")
+        .arg(project_dir)
+        .args(["log", "--format=%x00%ae", "--name-only", "--no-renames"])
+        .output()
+    {
+        Ok(output) if output.status.success() => output,
+        _ => return authors,
+    };
+
+    let mut counts: HashMap<PathBuf, BTreeMap<String, usize>> = HashMap::new();
+    let mut author = String::new();
+    for line in String::from_utf8_lossy(&output.stdout).lines() {
+        if let Some(email) = line.strip_prefix('\0') {
+            author = email.trim().to_string();
+            continue;
+        }
+        let line = line.trim();
+        if line.is_empty() || author.is_empty() {
+            continue;
+        }
+        let path = toplevel.join(line);
+        let path = path.canonicalize().unwrap_or(path);
+        *counts.entry(path).or_default().entry(author.clone()).or_insert(0) += 1;
+    }
+    for (path, by_author) in counts {
+        // BTreeMap 保证次数相同时按邮箱排序，结果稳定
+        if let Some((email, _)) = by_author.into_iter().rev().max_by_key(|(_, count)| *count) {
+            authors.insert(path, email);
+        }
+    }
+    authors
+}
+
+/// 所有者信息的来源
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum OwnerSource {
+    /// CODEOWNERS 规则
+    Codeowners,
+    /// 文件提交次数最多的作者
+    GitHistory,
+}
+
+/// 一位建议的评审者（个人或团队）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReviewerSuggestion {
+    pub owner: String,
+    pub source: OwnerSource,
+    /// 受影响函数的加权和：距离越远权重越低，变更频繁的文件权重更高
+    pub score: f64,
+    /// 改动文件中归属该评审者的函数数
+    pub changed_functions: usize,
+    /// 通过调用链间接受影响、归属该评审者的函数数
+    pub impacted_functions: usize,
+    /// 受影响最大的文件（相对项目目录）
+    pub files: Vec<String>,
+}
+
+/// 评审者建议结果
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ReviewerReport {
+    pub changed_functions: usize,
+    pub impacted_functions: usize,
+    /// 找不到所有者的受影响函数数
+    pub unowned_functions: usize,
+    pub reviewers: Vec<ReviewerSuggestion>,
+}
+
+/// 计算评审者建议所需的项目信息
+#[derive(Debug, Clone, Default)]
+pub struct OwnershipData {
+    pub codeowners: CodeOwners,
+    /// 没有CODEOWNERS规则时回退使用的文件作者，键为规范化后的绝对路径
+    pub authors: HashMap<PathBuf, String>,
+    /// 文件提交次数，键为规范化后的绝对路径
+    pub churn: HashMap<PathBuf, usize>,
+}
+
+impl OwnershipData {
+    /// 从项目目录读取CODEOWNERS和git历史
+    pub fn from_project(project_dir: &Path) -> Self {
+        Self {
+            codeowners: CodeOwners::discover(project_dir).unwrap_or_default(),
+            authors: file_top_authors(project_dir),
+            churn: file_churn(project_dir),
+        }
+    }
+}
+
+#[derive(Default)]
+struct ReviewerAccumulator {
+    source: Option<OwnerSource>,
+    score: f64,
+    changed: usize,
+    impacted: usize,
+    files: HashMap<String, f64>,
+}
+
+/// 根据改动文件的传递调用影响，按所有者汇总建议的评审者
+pub fn suggest_reviewers(
+    graph: &PetCodeGraph,
+    project_dir: &Path,
+    ownership: &OwnershipData,
+    changed_files: &[String],
+    max_depth: usize,
+) -> ReviewerReport {
+    let changed: Vec<&Path> = changed_files
+        .iter()
+        .map(|f| Path::new(f.trim().trim_start_matches("./")))
+        .filter(|p| !p.as_os_str().is_empty())
+        .collect();
+
+    // 改动文件中的函数距离为0，其上游调用者按调用距离记录最短距离
+    let mut distances: HashMap<Uuid, (&FunctionInfo, usize)> = HashMap::new();
+    for function in graph.graph.node_weights() {
+        if function.is_unresolved() || !changed.iter().any(|c| function.file_path.ends_with(c)) {
+            continue;
+        }
+        distances.insert(function.id, (function, 0));
+    }
+    let seeds: Vec<Uuid> = distances.keys().copied().collect();
+    for seed in seeds {
+        for (caller, depth) in graph.get_callers_within(&seed, max_depth) {
+            let entry = distances.entry(caller.id).or_insert((caller, depth));
+            entry.1 = entry.1.min(depth);
+        }
+    }
+
+    let root = project_dir.canonicalize().unwrap_or_else(|_| project_dir.to_path_buf());
+    let mut canonical: HashMap<&Path, PathBuf> = HashMap::new();
+    for (function, _) in distances.values() {
+        canonical
+            .entry(function.file_path.as_path())
+            .or_insert_with(|| function.file_path.canonicalize().unwrap_or_else(|_| function.file_path.clone()));
+    }
+    let max_churn = canonical
+        .values()
+        .map(|path| ownership.churn.get(path).copied().unwrap_or(0))
+        .max()
+        .unwrap_or(0)
+        .max(1) as f64;
+
+    let mut report = ReviewerReport::default();
+    let mut reviewers: BTreeMap<String, ReviewerAccumulator> = BTreeMap::new();
+    for (function, depth) in distances.values() {
+        if *depth == 0 {
+            report.changed_functions += 1;
+        } else {
+            report.impacted_functions += 1;
+        }
+
+        let path = &canonical[function.file_path.as_path()];
+        let relative = path
+            .strip_prefix(&root)
+            .or_else(|_| function.file_path.strip_prefix(project_dir))
+            .unwrap_or(&function.file_path);
+        let (owners, source): (Vec<String>, _) = match ownership.codeowners.owners_of(relative) {
+            Some(owners) => (owners.to_vec(), OwnerSource::Codeowners),
+            None => (ownership.authors.get(path).cloned().into_iter().collect(), OwnerSource::GitHistory),
+        };
+        if owners.is_empty() {
+            report.unowned_functions += 1;
+            continue;
+        }
+
+        let churn = ownership.churn.get(path).copied().unwrap_or(0) as f64;
+        let weight = (1.0 + churn / max_churn) / (*depth as f64 + 1.0);
+        let file = relative.to_string_lossy().replace('\\', "/");
+        for owner in owners {
+            let reviewer = reviewers.entry(owner).or_default();
+            reviewer.source.get_or_insert(source);
+            reviewer.score += weight;
+            if *depth == 0 {
+                reviewer.changed += 1;
+            } else {
+                reviewer.impacted += 1;
+            }
+            *reviewer.files.entry(file.clone()).or_insert(0.0) += weight;
+        }
+    }
+
+    report.reviewers = reviewers
+        .into_iter()
+        .map(|(owner, reviewer)| {
+            let mut files: Vec<(String, f64)> = reviewer.files.into_iter().collect();
+            files.sort_by(|a, b| b.1.total_cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+            ReviewerSuggestion {
+                owner,
+                source: reviewer.source.unwrap_or(OwnerSource::Codeowners),
+                score: (reviewer.score * 100.0).round() / 100.0,
+                changed_functions: reviewer.changed,
+                impacted_functions: reviewer.impacted,
+                files: files.into_iter().take(MAX_FILES_PER_REVIEWER).map(|(file, _)| file).collect(),
+            }
+        })
+        .collect();
+    report
+        .reviewers
+        .sort_by(|a, b| b.score.total_cmp(&a.score).then_with(|| a.owner.cmp(&b.owner)));
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::codegraph::parser::CodeParser;
+    use crate::codegraph::AnalyzeOptions;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_codeowners_matching() {
+        let owners = CodeOwners::parse(
+            "# comment\n*       @org/everyone\n*.md    @org/docs\n/src/api/ @org/api # inline\nbuild/  @org/infra\n/src/api/generated/\n",
+        );
+        let of = |path: &str| owners.owners_of(Path::new(path)).map(|o| o.join(","));
+        assert_eq!(of("main.rs").as_deref(), Some("@org/everyone"));
+        assert_eq!(of("guide/intro.md").as_deref(), Some("@org/docs"));
+        assert_eq!(of("src/api/routes.rs").as_deref(), Some("@org/api"));
+        assert_eq!(of("tools/build/run.sh").as_deref(), Some("@org/infra"));
+        // 以 `/` 结尾的模式不匹配同名文件
+        assert_eq!(of("build").as_deref(), Some("@org/everyone"));
+        // 没有所有者的规则取消归属
+        assert_eq!(of("src/api/generated/client.rs"), None);
+    }
+
+    #[test]
+    fn test_suggest_reviewers_follows_callers() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        fs::create_dir_all(root.join("core")).unwrap();
+        fs::create_dir_all(root.join("web")).unwrap();
+        fs::write(root.join("core/store.rs"), "pub fn save() {\n    encode();\n}\n\npub fn encode() {}\n").unwrap();
+        fs::write(root.join("web/handlers.rs"), "pub fn create() {\n    save();\n}\n\npub fn index() {\n    create();\n}\n").unwrap();
+        fs::write(root.join("main.rs"), "fn main() {\n    index();\n}\n").unwrap();
+        let options = AnalyzeOptions {
+            storage_dir: Some(root.join(".codegraph_db")),
+            force_rebuild: true,
+            ..Default::default()
+        };
+        let graph = CodeParser::with_options(options).build_petgraph_code_graph(root).unwrap();
+
+        let ownership = OwnershipData {
+            codeowners: CodeOwners::parse("/core/ @org/storage\n/web/ @org/web\n"),
+            ..Default::default()
+        };
+        let report = suggest_reviewers(&graph, root, &ownership, &["core/store.rs".to_string()], 2);
+
+        assert_eq!(report.changed_functions, 2);
+        // create 距离1，index 距离2，main 超出深度
+        assert_eq!(report.impacted_functions, 2);
+        assert_eq!(report.unowned_functions, 0);
+        let owners: Vec<&str> = report.reviewers.iter().map(|r| r.owner.as_str()).collect();
+        assert_eq!(owners, vec!["@org/storage", "@org/web"]);
+        let web = &report.reviewers[1];
+        assert_eq!(web.impacted_functions, 2);
+        assert_eq!(web.files, vec!["web/handlers.rs".to_string()]);
+        assert_eq!(web.source, OwnerSource::Codeowners);
+    }
+}
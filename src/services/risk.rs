@@ -0,0 +1,292 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use serde::{Deserialize, Serialize};
+use tracing::debug;
+use uuid::Uuid;
+
+use crate::codegraph::types::{FunctionInfo, PetCodeGraph};
+
+/// PageRank阻尼系数
+const DAMPING: f64 = 0.85;
+/// PageRank迭代次数
+const PAGERANK_ITERATIONS: usize = 20;
+
+/// 风险得分各项权重
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RiskWeights {
+    pub fan_in: f64,
+    pub centrality: f64,
+    pub churn: f64,
+    pub untested: f64,
+}
+
+impl Default for RiskWeights {
+    fn default() -> Self {
+        Self {
+            fan_in: 0.35,
+            centrality: 0.25,
+            churn: 0.25,
+            untested: 0.15,
+        }
+    }
+}
+
+/// 单个函数的风险评估
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FunctionRisk {
+    pub function_id: Uuid,
+    pub function_name: String,
+    pub file_path: String,
+    pub line_start: usize,
+    pub fan_in: usize,
+    pub centrality: f64,
+    pub churn: usize,
+    pub tested: bool,
+    /// 0-100 的综合风险得分
+    pub score: f64,
+}
+
+/// 基于调用图、变更历史和测试覆盖的风险评分器
+pub struct RiskScorer {
+    weights: RiskWeights,
+}
+
+impl RiskScorer {
+    pub fn new(weights: RiskWeights) -> Self {
+        Self { weights }
+    }
+
+    /// 计算所有函数的风险得分，按得分降序返回
+    pub fn score(&self, graph: &PetCodeGraph, churn: &HashMap<PathBuf, usize>) -> Vec<FunctionRisk> {
+        let centrality = pagerank(graph);
+        let tested = functions_reached_by_tests(graph);
+
+        let functions: Vec<&FunctionInfo> = graph
+            .get_all_functions()
+            .into_iter()
+            .filter(|f| !f.is_unresolved() && !f.is_reexport() && !is_test_function(f))
+            .collect();
+
+        let fan_ins: HashMap<Uuid, usize> = functions
+            .iter()
+            .map(|f| {
+                let callers: HashSet<Uuid> = graph
+                    .get_users(&f.id)
+                    .into_iter()
+                    .filter(|(_, rel)| rel.is_resolved)
+                    .map(|(caller, _)| caller.id)
+                    .collect();
+                (f.id, callers.len())
+            })
+            .collect();
+
+        let mut canonical_cache: HashMap<PathBuf, PathBuf> = HashMap::new();
+        let churns: HashMap<Uuid, usize> = functions
+            .iter()
+            .map(|f| {
+                let canonical = canonical_cache
+                    .entry(f.file_path.clone())
+                    .or_insert_with(|| f.file_path.canonicalize().unwrap_or_else(|_| f.file_path.clone()));
+                (f.id, churn.get(canonical.as_path()).copied().unwrap_or(0))
+            })
+            .collect();
+
+        let max_fan_in = fan_ins.values().copied().max().unwrap_or(0).max(1) as f64;
+        let max_churn = churns.values().copied().max().unwrap_or(0).max(1) as f64;
+        let max_centrality = centrality.values().copied().fold(0.0, f64::max).max(f64::EPSILON);
+
+        let mut risks: Vec<FunctionRisk> = functions
+            .iter()
+            .map(|f| {
+                let fan_in = fan_ins[&f.id];
+                let churn = churns[&f.id];
+                let node_centrality = centrality.get(&f.id).copied().unwrap_or(0.0);
+                let is_tested = tested.contains(&f.id);
+
+                let score = self.weights.fan_in * (fan_in as f64 / max_fan_in)
+                    + self.weights.centrality * (node_centrality / max_centrality)
+                    + self.weights.churn * (churn as f64 / max_churn)
+                    + if is_tested { 0.0 } else { self.weights.untested };
+                let total_weight = self.weights.fan_in + self.weights.centrality + self.weights.churn + self.weights.untested;
+
+                FunctionRisk {
+                    function_id: f.id,
+                    function_name: f.name.clone(),
+                    file_path: f.file_path.display().to_string(),
+                    line_start: f.line_start,
+                    fan_in,
+                    centrality: node_centrality,
+                    churn,
+                    tested: is_tested,
+                    score: (score / total_weight.max(f64::EPSILON) * 100.0 * 100.0).round() / 100.0,
+                }
+            })
+            .collect();
+
+        risks.sort_by(|a, b| b.score.partial_cmp(&a.score).unwrap_or(std::cmp::Ordering::Equal));
+        risks
+    }
+}
+
+impl Default for RiskScorer {
+    fn default() -> Self {
+        Self::new(RiskWeights::default())
+    }
+}
+
+/// 判断函数是否为测试函数（按命名约定和文件路径）
+pub fn is_test_function(function: &FunctionInfo) -> bool {
+    let name = function.name.to_lowercase();
+    let path = function.file_path.to_string_lossy().to_lowercase();
+    name.starts_with("test_")
+        || (name.starts_with("test") && function.name.chars().nth(4).is_some_and(|c| c.is_uppercase()))
+        || path.contains("/tests/")
+        || path.contains("/test/")
+        || path.contains("_test.")
+        || path.contains(".test.")
+        || path.contains(".spec.")
+}
+
+/// 从所有测试函数出发，沿调用边可达的函数集合
+pub fn functions_reached_by_tests(graph: &PetCodeGraph) -> HashSet<Uuid> {
+    let mut reached = HashSet::new();
+    let mut queue: VecDeque<Uuid> = graph
+        .get_all_functions()
+        .into_iter()
+        .filter(|f| is_test_function(f))
+        .map(|f| f.id)
+        .collect();
+
+    while let Some(id) = queue.pop_front() {
+        for (callee, relation) in graph.get_callees(&id) {
+            if relation.is_resolved && reached.insert(callee.id) {
+                queue.push_back(callee.id);
+            }
+        }
+    }
+    reached
+}
+
+/// 计算调用图上的PageRank中心度
+pub fn pagerank(graph: &PetCodeGraph) -> HashMap<Uuid, f64> {
+    let node_count = graph.graph.node_count();
+    if node_count == 0 {
+        return HashMap::new();
+    }
+
+    let n = node_count as f64;
+    let mut ranks = vec![1.0 / n; node_count];
+    for _ in 0..PAGERANK_ITERATIONS {
+        let mut next = vec![(1.0 - DAMPING) / n; node_count];
+        let mut dangling = 0.0;
+        for node in graph.graph.node_indices() {
+            let out_degree = graph.graph.neighbors(node).count();
+            if out_degree == 0 {
+                dangling += ranks[node.index()];
+                continue;
+            }
+            let share = ranks[node.index()] / out_degree as f64;
+            for target in graph.graph.neighbors(node) {
+                next[target.index()] += DAMPING * share;
+            }
+        }
+        for rank in next.iter_mut() {
+            *rank += DAMPING * dangling / n;
+        }
+        ranks = next;
+    }
+
+    graph
+        .graph
+        .node_indices()
+        .map(|node| (graph.graph[node].id, ranks[node.index()]))
+        .collect()
+}
+
+/// 通过git历史统计每个文件的提交次数，键为规范化后的绝对路径
+pub fn file_churn(project_dir: &Path) -> HashMap<PathBuf, usize> {
+    let mut churn = HashMap::new();
+
+    let toplevel = match Command::new("git")
+        .arg("-C")
+        .arg(project_dir)
+        .args(["rev-parse", "--show-toplevel"])
+        .output()
+    {
+        Ok(output) if output.status.success() => {
+            PathBuf::from(String::from_utf8_lossy(&output.stdout).trim())
+        }
+        _ => {
+            debug!("{} is not a git repository, churn is unavailable", project_dir.display());
+            return churn;
+        }
+    };
+
+    let output = match Command::new("git")
+        .arg("-C")
+        .arg(project_dir)
+        .args(["log", "--format=", "--name-only", "--no-renames"])
+        .output()
+    {
+        Ok(output) if output.status.success() => output,
+        _ => return churn,
+    };
+
+    for line in String::from_utf8_lossy(&output.stdout).lines() {
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+        let path = toplevel.join(line);
+        let path = path.canonicalize().unwrap_or(path);
+        *churn.entry(path).or_insert(0) += 1;
+    }
+    churn
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn function(name: &str, file: &str) -> FunctionInfo {
+        FunctionInfo {
+            id: Uuid::new_v4(),
+            name: name.to_string(),
+            file_path: PathBuf::from(file),
+            line_start: 1,
+            line_end: 3,
+            namespace: "global".to_string(),
+            language: "python".to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_untested_hub_scores_highest() {
+        let mut graph = PetCodeGraph::new();
+        let hub = function("parse", "src/core.py");
+        let leaf = function("helper", "src/core.py");
+        let a = function("handle_a", "src/api.py");
+        let b = function("handle_b", "src/api.py");
+        let test = function("test_helper", "tests/test_core.py");
+        for f in [&hub, &leaf, &a, &b, &test] {
+            graph.add_function(f.clone());
+        }
+        graph.add_call(&a, &hub);
+        graph.add_call(&b, &hub);
+        graph.add_call(&test, &leaf);
+
+        let churn = HashMap::from([(PathBuf::from("src/core.py"), 5)]);
+        let risks = RiskScorer::default().score(&graph, &churn);
+
+        assert_eq!(risks.len(), 4);
+        assert_eq!(risks[0].function_name, "parse");
+        assert_eq!(risks[0].fan_in, 2);
+        assert!(!risks[0].tested);
+        let helper = risks.iter().find(|r| r.function_name == "helper").unwrap();
+        assert!(helper.tested);
+        assert_eq!(helper.churn, 5);
+    }
+}
@@ -0,0 +1,129 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use tokio::sync::Semaphore;
+use tokio::task::JoinSet;
+
+use uuid::Uuid;
+
+use crate::codegraph::treesitter::ast_instance_structs::SymbolInformation;
+use crate::codegraph::treesitter::file_ast_markup::FileASTMarkup;
+use crate::codegraph::treesitter::parsers::get_ast_parser_by_filename;
+use crate::codegraph::treesitter::skeletonizer::make_formatter;
+use crate::codegraph::treesitter::structs::SymbolType;
+use crate::storage::skeleton_cache::{content_hash, CachedSkeleton, SkeletonCache};
+
+/// 生成文件骨架：顶层类/结构体和函数的声明，返回 (语言, 骨架文本)
+pub fn build_skeleton(path: &Path, code: &str) -> Result<(String, String), String> {
+    let (mut parser, language_id) = get_ast_parser_by_filename(&path.to_path_buf())
+        .map_err(|e| format!("No parser for {}: {:?}", path.display(), e))?;
+
+    let symbols = parser.parse(code, &path.to_path_buf());
+    let guid_to_children: HashMap<Uuid, Vec<Uuid>> = symbols
+        .iter()
+        .map(|s| (*s.read().guid(), s.read().childs_guid().clone()))
+        .collect();
+    let ast_markup = FileASTMarkup {
+        symbols_sorted_by_path_len: symbols.iter().map(|s| s.read().symbol_info_struct()).collect(),
+    };
+    let guid_to_info: HashMap<Uuid, &SymbolInformation> = ast_markup
+        .symbols_sorted_by_path_len
+        .iter()
+        .map(|s| (s.guid, s))
+        .collect();
+
+    let formatter = make_formatter(&language_id);
+    let lines: Vec<String> = ast_markup
+        .symbols_sorted_by_path_len
+        .iter()
+        .filter(|x| x.symbol_type == SymbolType::StructDeclaration || x.symbol_type == SymbolType::FunctionDeclaration)
+        .map(|symbol| formatter.make_skeleton(symbol, &code.to_string(), &guid_to_children, &guid_to_info))
+        .collect();
+
+    Ok((language_id.to_string(), lines.join("\n\n")))
+}
+
+/// 读取文件并生成骨架，内容未变时直接使用缓存；返回 (骨架, 是否命中缓存)
+pub fn cached_skeleton(cache: &SkeletonCache, path: &Path) -> Result<(CachedSkeleton, bool), String> {
+    let content = std::fs::read(path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    let hash = content_hash(&content);
+    if let Some(hit) = cache.get(path, &hash) {
+        return Ok((hit, true));
+    }
+
+    let code = String::from_utf8_lossy(&content);
+    let (language, skeleton_text) = build_skeleton(path, &code)?;
+    let entry = CachedSkeleton {
+        path: path.to_path_buf(),
+        content_hash: hash,
+        language,
+        skeleton_text,
+    };
+    cache.insert(entry.clone());
+    Ok((entry, false))
+}
+
+/// 并发生成多个文件的骨架，最多 `parallelism` 个文件同时解析；结果顺序与输入一致
+pub async fn cached_skeletons(
+    cache: Arc<SkeletonCache>,
+    paths: Vec<PathBuf>,
+    parallelism: usize,
+) -> Vec<(PathBuf, Result<(CachedSkeleton, bool), String>)> {
+    let permits = Arc::new(Semaphore::new(parallelism.max(1)));
+    let mut tasks = JoinSet::new();
+    for (index, path) in paths.iter().cloned().enumerate() {
+        let cache = cache.clone();
+        let permits = permits.clone();
+        tasks.spawn(async move {
+            let _permit = permits.acquire_owned().await;
+            let result = tokio::task::spawn_blocking(move || cached_skeleton(&cache, &path))
+                .await
+                .unwrap_or_else(|e| Err(format!("Skeleton task failed: {}", e)));
+            (index, result)
+        });
+    }
+
+    let mut results: Vec<Option<Result<(CachedSkeleton, bool), String>>> = paths.iter().map(|_| None).collect();
+    while let Some(joined) = tasks.join_next().await {
+        if let Ok((index, result)) = joined {
+            results[index] = Some(result);
+        }
+    }
+    paths
+        .into_iter()
+        .zip(results)
+        .map(|(path, result)| {
+            let result = result.unwrap_or_else(|| Err(format!("Skeleton task for {} was cancelled", path.display())));
+            (path, result)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_batch_keeps_input_order() {
+        let dir = std::env::temp_dir().join(format!("codegraph-skeleton-batch-{}", Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let mut paths = Vec::new();
+        for i in 0..6 {
+            let path = dir.join(format!("m{}.rs", i));
+            std::fs::write(&path, format!("fn f{}() {{}}\n", i)).unwrap();
+            paths.push(path);
+        }
+        paths.insert(3, dir.join("missing.rs"));
+
+        let cache = Arc::new(SkeletonCache::new(dir.join("cache")));
+        let results = cached_skeletons(cache, paths.clone(), 2).await;
+        assert_eq!(results.iter().map(|(p, _)| p.clone()).collect::<Vec<_>>(), paths);
+        assert!(results[3].1.is_err());
+        let (skeleton, cached) = results[6].1.as_ref().unwrap();
+        assert!(skeleton.skeleton_text.contains("f5"));
+        assert!(!cached);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
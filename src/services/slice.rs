@@ -0,0 +1,530 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::fs;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use regex::Regex;
+use uuid::Uuid;
+
+use crate::codegraph::types::{FunctionInfo, PetCodeGraph};
+use crate::services::reviewers::CodeOwners;
+use crate::storage::MetricMap;
+
+/// 判断生成代码时读取的文件头部字节数
+const GENERATED_HEADER_BYTES: u64 = 2048;
+
+/// 文件头部出现即视为生成代码的标记
+const GENERATED_MARKERS: &[&str] = &["@generated", "DO NOT EDIT", "do not edit", "auto-generated", "autogenerated", "Autogenerated", "Auto-generated"];
+
+/// 生成代码的常见文件名后缀
+const GENERATED_SUFFIXES: &[&str] = &[".pb.go", "_pb2.py", "_pb2_grpc.py", ".pb.cc", ".pb.h", ".g.dart", ".freezed.dart", ".designer.cs", ".g.cs"];
+
+/// 括号和 `!` 的最大嵌套层数，表达式来自请求参数，过深的嵌套会让递归下降耗尽栈
+const MAX_NESTING: usize = 64;
+
+/// 比较运算符
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CompareOp {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    /// `=~`，右侧为正则表达式
+    Matches,
+}
+
+/// 表达式中的字面量
+#[derive(Debug, Clone)]
+pub enum Literal {
+    String(String),
+    Number(f64),
+    Bool(bool),
+    Pattern(Regex),
+}
+
+/// 节点属性谓词，例如 `language == "java" && owner == "payments" && !generated`
+#[derive(Debug, Clone)]
+pub enum SliceExpr {
+    And(Box<SliceExpr>, Box<SliceExpr>),
+    Or(Box<SliceExpr>, Box<SliceExpr>),
+    Not(Box<SliceExpr>),
+    Compare { attribute: String, op: CompareOp, value: Literal },
+    /// 单独的属性名：布尔值为真、数值非零、字符串非空时成立
+    Attribute(String),
+}
+
+/// 表达式解析错误，`position` 为字节偏移
+#[derive(Debug, Clone, PartialEq)]
+pub struct SliceParseError {
+    pub position: usize,
+    pub message: String,
+}
+
+impl fmt::Display for SliceParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} at offset {}", self.message, self.position)
+    }
+}
+
+impl std::error::Error for SliceParseError {}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Ident(String),
+    String(String),
+    Number(f64),
+    And,
+    Or,
+    Not,
+    Open,
+    Close,
+    Op(CompareOp),
+}
+
+fn tokenize(input: &str) -> Result<Vec<(usize, Token)>, SliceParseError> {
+    let error = |position: usize, message: &str| SliceParseError { position, message: message.to_string() };
+    let bytes = input.as_bytes();
+    let mut tokens = vec![];
+    let mut i = 0;
+    while i < bytes.len() {
+        let c = bytes[i];
+        let start = i;
+        let two = input.get(i..i + 2).unwrap_or("");
+        let token = match c {
+            _ if c.is_ascii_whitespace() => {
+                i += 1;
+                continue;
+            }
+            b'(' => Token::Open,
+            b')' => Token::Close,
+            _ if two == "&&" => Token::And,
+            _ if two == "||" => Token::Or,
+            _ if two == "==" => Token::Op(CompareOp::Eq),
+            _ if two == "!=" => Token::Op(CompareOp::Ne),
+            _ if two == "<=" => Token::Op(CompareOp::Le),
+            _ if two == ">=" => Token::Op(CompareOp::Ge),
+            _ if two == "=~" => Token::Op(CompareOp::Matches),
+            b'!' => Token::Not,
+            b'<' => Token::Op(CompareOp::Lt),
+            b'>' => Token::Op(CompareOp::Gt),
+            b'"' | b'\'' => {
+                let mut value = String::new();
+                let mut chars = input[i + 1..].char_indices();
+                loop {
+                    match chars.next() {
+                        Some((offset, ch)) if ch as u32 == c as u32 => {
+                            i += 1 + offset + 1;
+                            break;
+                        }
+                        Some((_, '\\')) => match chars.next() {
+                            Some((_, escaped)) => value.push(escaped),
+                            None => return Err(error(start, "unterminated string")),
+                        },
+                        Some((_, ch)) => value.push(ch),
+                        None => return Err(error(start, "unterminated string")),
+                    }
+                }
+                tokens.push((start, Token::String(value)));
+                continue;
+            }
+            _ if c.is_ascii_digit() || (c == b'-' && bytes.get(i + 1).is_some_and(u8::is_ascii_digit)) => {
+                i += 1;
+                while i < bytes.len() && (bytes[i].is_ascii_digit() || bytes[i] == b'.') {
+                    i += 1;
+                }
+                let number = input[start..i].parse().map_err(|_| error(start, "invalid number"))?;
+                tokens.push((start, Token::Number(number)));
+                continue;
+            }
+            _ if c.is_ascii_alphabetic() || c == b'_' => {
+                while i < bytes.len() && (bytes[i].is_ascii_alphanumeric() || bytes[i] == b'_' || bytes[i] == b'.') {
+                    i += 1;
+                }
+                tokens.push((start, Token::Ident(input[start..i].to_string())));
+                continue;
+            }
+            _ => return Err(error(start, "unexpected character")),
+        };
+        i += match token {
+            Token::Open | Token::Close | Token::Not | Token::Op(CompareOp::Lt) | Token::Op(CompareOp::Gt) => 1,
+            _ => 2,
+        };
+        tokens.push((start, token));
+    }
+    Ok(tokens)
+}
+
+/// 递归下降解析：`||` 优先级最低，其次 `&&`，`!` 最高
+struct ExprParser {
+    tokens: Vec<(usize, Token)>,
+    pos: usize,
+    end: usize,
+    /// 当前所在的括号和 `!` 层数
+    depth: usize,
+}
+
+impl ExprParser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos).map(|(_, token)| token)
+    }
+
+    fn offset(&self) -> usize {
+        self.tokens.get(self.pos).map_or(self.end, |(offset, _)| *offset)
+    }
+
+    fn error(&self, message: impl Into<String>) -> SliceParseError {
+        SliceParseError { position: self.offset(), message: message.into() }
+    }
+
+    /// 跳过当前的 `(` 或 `!` 进入一层后用 `parse` 解析，超过 [`MAX_NESTING`] 层时报错
+    fn nested(&mut self, parse: fn(&mut Self) -> Result<SliceExpr, SliceParseError>) -> Result<SliceExpr, SliceParseError> {
+        if self.depth >= MAX_NESTING {
+            return Err(self.error(format!("expression nested deeper than {} levels", MAX_NESTING)));
+        }
+        self.pos += 1;
+        self.depth += 1;
+        let expr = parse(self);
+        self.depth -= 1;
+        expr
+    }
+
+    fn or(&mut self) -> Result<SliceExpr, SliceParseError> {
+        let mut expr = self.and()?;
+        while self.peek() == Some(&Token::Or) {
+            self.pos += 1;
+            expr = SliceExpr::Or(Box::new(expr), Box::new(self.and()?));
+        }
+        Ok(expr)
+    }
+
+    fn and(&mut self) -> Result<SliceExpr, SliceParseError> {
+        let mut expr = self.unary()?;
+        while self.peek() == Some(&Token::And) {
+            self.pos += 1;
+            expr = SliceExpr::And(Box::new(expr), Box::new(self.unary()?));
+        }
+        Ok(expr)
+    }
+
+    fn unary(&mut self) -> Result<SliceExpr, SliceParseError> {
+        match self.peek() {
+            Some(Token::Not) => Ok(SliceExpr::Not(Box::new(self.nested(Self::unary)?))),
+            Some(Token::Open) => {
+                let expr = self.nested(Self::or)?;
+                if self.peek() != Some(&Token::Close) {
+                    return Err(self.error("expected `)`"));
+                }
+                self.pos += 1;
+                Ok(expr)
+            }
+            Some(Token::Ident(attribute)) => {
+                let attribute = attribute.clone();
+                self.pos += 1;
+                let Some(Token::Op(op)) = self.peek().cloned() else {
+                    return Ok(SliceExpr::Attribute(attribute));
+                };
+                self.pos += 1;
+                let value = match (self.peek().cloned(), op) {
+                    (Some(Token::String(pattern)), CompareOp::Matches) => Literal::Pattern(
+                        Regex::new(&pattern).map_err(|e| self.error(format!("invalid regex: {}", e)))?,
+                    ),
+                    (_, CompareOp::Matches) => return Err(self.error("`=~` expects a quoted regex")),
+                    (Some(Token::String(value)), _) => Literal::String(value),
+                    (Some(Token::Number(value)), _) => Literal::Number(value),
+                    (Some(Token::Ident(value)), _) if value == "true" || value == "false" => Literal::Bool(value == "true"),
+                    _ => return Err(self.error("expected a string, number or boolean")),
+                };
+                self.pos += 1;
+                Ok(SliceExpr::Compare { attribute, op, value })
+            }
+            Some(_) => Err(self.error("expected an attribute, `!` or `(`")),
+            None => Err(self.error("unexpected end of expression")),
+        }
+    }
+}
+
+impl FromStr for SliceExpr {
+    type Err = SliceParseError;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        let mut parser = ExprParser { tokens: tokenize(input)?, pos: 0, end: input.len(), depth: 0 };
+        let expr = parser.or()?;
+        if parser.pos < parser.tokens.len() {
+            return Err(parser.error("unexpected token"));
+        }
+        Ok(expr)
+    }
+}
+
+/// 属性值
+#[derive(Debug, Clone, PartialEq)]
+enum Value {
+    String(String),
+    Number(f64),
+    Bool(bool),
+}
+
+impl Value {
+    fn truthy(&self) -> bool {
+        match self {
+            Value::String(value) => !value.is_empty(),
+            Value::Number(value) => *value != 0.0,
+            Value::Bool(value) => *value,
+        }
+    }
+
+    fn compare(&self, op: CompareOp, literal: &Literal) -> bool {
+        use std::cmp::Ordering;
+        let ordering = match (self, literal) {
+            (Value::String(value), Literal::Pattern(pattern)) => return pattern.is_match(value),
+            (_, Literal::Pattern(_)) => return false,
+            (Value::String(value), Literal::String(other)) => value.as_str().cmp(other.as_str()),
+            (Value::Number(value), Literal::Number(other)) => match value.partial_cmp(other) {
+                Some(ordering) => ordering,
+                None => return false,
+            },
+            (Value::Bool(value), Literal::Bool(other)) => value.cmp(other),
+            // 类型不同时只有 `!=` 成立
+            _ => return op == CompareOp::Ne,
+        };
+        match op {
+            CompareOp::Eq => ordering == Ordering::Equal,
+            CompareOp::Ne => ordering != Ordering::Equal,
+            CompareOp::Lt => ordering == Ordering::Less,
+            CompareOp::Le => ordering != Ordering::Greater,
+            CompareOp::Gt => ordering == Ordering::Greater,
+            CompareOp::Ge => ordering != Ordering::Less,
+            CompareOp::Matches => false,
+        }
+    }
+}
+
+/// 切片谓词可用的节点属性。
+///
+/// 内置属性：`name`、`namespace`、`language`、`signature`、`file`（相对项目目录）、
+/// `line_start`、`line_end`、`lines`、`callers`、`callees`、`owner`（CODEOWNERS，
+/// 可写完整的 `@org/team`、`org/team` 或只写 `team`）和 `generated`；
+/// 其他属性名按节点标注的指标（`/projects/:id/annotations`）取值。
+#[derive(Debug, Clone, Default)]
+pub struct SliceAttributes {
+    project_dir: PathBuf,
+    codeowners: CodeOwners,
+    metrics: HashMap<Uuid, MetricMap>,
+    generated: HashMap<PathBuf, bool>,
+}
+
+impl SliceAttributes {
+    /// 读取项目的CODEOWNERS并检测图中每个文件是否为生成代码
+    pub fn new(graph: &PetCodeGraph, project_dir: &Path, metrics: HashMap<Uuid, MetricMap>) -> Self {
+        let mut generated = HashMap::new();
+        for function in graph.get_all_functions() {
+            if !generated.contains_key(&function.file_path) {
+                generated.insert(function.file_path.clone(), is_generated_file(&function.file_path));
+            }
+        }
+        Self {
+            project_dir: project_dir.to_path_buf(),
+            codeowners: CodeOwners::discover(project_dir).unwrap_or_default(),
+            metrics,
+            generated,
+        }
+    }
+
+    fn relative<'a>(&self, path: &'a Path) -> &'a Path {
+        path.strip_prefix(&self.project_dir).unwrap_or(path)
+    }
+
+    /// 属性的取值；`owner` 可以有多个值，缺失的属性没有值
+    fn values(&self, graph: &PetCodeGraph, function: &FunctionInfo, attribute: &str) -> Vec<Value> {
+        let string = |value: &str| vec![Value::String(value.to_string())];
+        let number = |value: usize| vec![Value::Number(value as f64)];
+        match attribute {
+            "name" => string(&function.name),
+            "namespace" => string(&function.namespace),
+            "language" => string(&function.language),
+            "signature" => function.signature.as_deref().map(string).unwrap_or_default(),
+            "file" => string(&self.relative(&function.file_path).to_string_lossy().replace('\\', "/")),
+            "line_start" => number(function.line_start),
+            "line_end" => number(function.line_end),
+            "lines" => number(function.line_end.saturating_sub(function.line_start) + 1),
+            "callers" => number(graph.get_callers(&function.id).len()),
+            "callees" => number(graph.get_callees(&function.id).len()),
+            "generated" => vec![Value::Bool(self.generated.get(&function.file_path).copied().unwrap_or(false))],
+            "owner" => self
+                .codeowners
+                .owners_of(self.relative(&function.file_path))
+                .unwrap_or_default()
+                .iter()
+                .flat_map(|owner| owner_aliases(owner))
+                .map(Value::String)
+                .collect(),
+            metric => self
+                .metrics
+                .get(&function.id)
+                .and_then(|metrics| metrics.get(metric))
+                .map(|value| vec![Value::Number(*value)])
+                .unwrap_or_default(),
+        }
+    }
+
+    /// 对一个函数求值谓词；多值属性任一值满足即成立，`!=` 要求没有值相等
+    pub fn matches(&self, graph: &PetCodeGraph, function: &FunctionInfo, expr: &SliceExpr) -> bool {
+        match expr {
+            SliceExpr::And(left, right) => self.matches(graph, function, left) && self.matches(graph, function, right),
+            SliceExpr::Or(left, right) => self.matches(graph, function, left) || self.matches(graph, function, right),
+            SliceExpr::Not(inner) => !self.matches(graph, function, inner),
+            SliceExpr::Attribute(attribute) => self.values(graph, function, attribute).iter().any(Value::truthy),
+            SliceExpr::Compare { attribute, op: CompareOp::Ne, value } => !self
+                .values(graph, function, attribute)
+                .iter()
+                .any(|v| v.compare(CompareOp::Eq, value)),
+            SliceExpr::Compare { attribute, op, value } => {
+                self.values(graph, function, attribute).iter().any(|v| v.compare(*op, value))
+            }
+        }
+    }
+}
+
+/// `@org/payments` 同时可用 `org/payments` 和 `payments` 匹配
+fn owner_aliases(owner: &str) -> Vec<String> {
+    let mut aliases = vec![owner.to_string()];
+    let bare = owner.trim_start_matches('@');
+    if bare != owner {
+        aliases.push(bare.to_string());
+    }
+    if let Some((_, team)) = bare.rsplit_once('/') {
+        aliases.push(team.to_string());
+    }
+    aliases
+}
+
+/// 按文件名后缀、`generated` 目录或文件头部的生成标记判断是否为生成代码
+pub fn is_generated_file(path: &Path) -> bool {
+    let name = path.file_name().map(|name| name.to_string_lossy()).unwrap_or_default();
+    if GENERATED_SUFFIXES.iter().any(|suffix| name.ends_with(suffix)) || name.contains(".generated.") {
+        return true;
+    }
+    if path.components().any(|c| matches!(c.as_os_str().to_str(), Some("generated" | "__generated__"))) {
+        return true;
+    }
+    let mut header = Vec::new();
+    if let Ok(file) = fs::File::open(path) {
+        let _ = file.take(GENERATED_HEADER_BYTES).read_to_end(&mut header);
+    }
+    let header = String::from_utf8_lossy(&header);
+    GENERATED_MARKERS.iter().any(|marker| header.contains(marker))
+}
+
+/// 谓词选中的函数及它们之间的调用构成的导出子图
+pub fn slice_graph(graph: &PetCodeGraph, expr: &SliceExpr, attributes: &SliceAttributes) -> PetCodeGraph {
+    let mut slice = PetCodeGraph::new();
+    for function in graph.get_all_functions() {
+        if attributes.matches(graph, function, expr) {
+            slice.add_function(function.clone());
+        }
+    }
+    for relation in graph.get_all_call_relations() {
+        if slice.get_node_index(&relation.caller_id).is_some() && slice.get_node_index(&relation.callee_id).is_some() {
+            let _ = slice.add_call_relation(relation.clone());
+        }
+    }
+    slice.update_stats();
+    slice
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn function(name: &str, file: &Path, language: &str) -> FunctionInfo {
+        FunctionInfo {
+            id: Uuid::new_v4(),
+            name: name.to_string(),
+            file_path: file.to_path_buf(),
+            line_start: 1,
+            line_end: 3,
+            namespace: String::new(),
+            language: language.to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_parse_expressions() {
+        assert!(matches!("a && b || c".parse::<SliceExpr>().unwrap(), SliceExpr::Or(..)));
+        assert!(matches!("a && (b || c)".parse::<SliceExpr>().unwrap(), SliceExpr::And(..)));
+        assert!(matches!("!generated".parse::<SliceExpr>().unwrap(), SliceExpr::Not(..)));
+        assert!(matches!(
+            "lines >= 10".parse::<SliceExpr>().unwrap(),
+            SliceExpr::Compare { op: CompareOp::Ge, value: Literal::Number(n), .. } if n == 10.0
+        ));
+        assert!(matches!(
+            "name == 'it\\'s'".parse::<SliceExpr>().unwrap(),
+            SliceExpr::Compare { value: Literal::String(s), .. } if s == "it's"
+        ));
+
+        let error = "language == ".parse::<SliceExpr>().unwrap_err();
+        assert_eq!(error.position, 12);
+        assert!("(a && b".parse::<SliceExpr>().is_err());
+        assert!("a b".parse::<SliceExpr>().is_err());
+        assert!("file =~ '('".parse::<SliceExpr>().is_err());
+        assert!("name == \"open".parse::<SliceExpr>().is_err());
+
+        // 嵌套层数有上限，超出时报错而不是耗尽栈
+        let nested = |depth: usize| format!("{}a{}", "(".repeat(depth), ")".repeat(depth));
+        assert!(nested(MAX_NESTING).parse::<SliceExpr>().is_ok());
+        let error = nested(100_000).parse::<SliceExpr>().unwrap_err();
+        assert_eq!(error.position, MAX_NESTING);
+        assert!(error.message.contains("nested deeper"));
+        assert!(format!("{}a", "!".repeat(100_000)).parse::<SliceExpr>().is_err());
+    }
+
+    #[test]
+    fn test_slice_induced_subgraph() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        fs::create_dir_all(root.join(".github")).unwrap();
+        fs::write(root.join(".github/CODEOWNERS"), "/payments/ @acme/payments\n/web/ @acme/web\n").unwrap();
+        fs::create_dir_all(root.join("payments")).unwrap();
+        fs::create_dir_all(root.join("web")).unwrap();
+        fs::write(root.join("payments/Charge.java"), "class Charge {}\n").unwrap();
+        fs::write(root.join("payments/Stub.java"), "// Code generated by protoc. DO NOT EDIT.\n").unwrap();
+        fs::write(root.join("web/app.ts"), "export {}\n").unwrap();
+
+        let charge = function("charge", &root.join("payments/Charge.java"), "java");
+        let refund = function("refund", &root.join("payments/Charge.java"), "java");
+        let stub = function("stub", &root.join("payments/Stub.java"), "java");
+        let render = function("render", &root.join("web/app.ts"), "typescript");
+        let mut graph = PetCodeGraph::new();
+        for f in [&charge, &refund, &stub, &render] {
+            graph.add_function(f.clone());
+        }
+        graph.add_call(&charge, &refund);
+        graph.add_call(&charge, &stub);
+        graph.add_call(&render, &charge);
+
+        let metrics = HashMap::from([(refund.id, MetricMap::from([("coverage".to_string(), 0.2)]))]);
+        let attributes = SliceAttributes::new(&graph, root, metrics);
+        let slice = |expr: &str| {
+            let sliced = slice_graph(&graph, &expr.parse().unwrap(), &attributes);
+            let mut names: Vec<String> = sliced.get_all_functions().iter().map(|f| f.name.clone()).collect();
+            names.sort();
+            (names, sliced.get_all_call_relations().len())
+        };
+
+        assert_eq!(
+            slice(r#"language == "java" && owner == "payments" && !generated"#),
+            (vec!["charge".to_string(), "refund".to_string()], 1)
+        );
+        assert_eq!(slice("owner == '@acme/web' || generated").0, vec!["render", "stub"]);
+        assert_eq!(slice("coverage < 0.5").0, vec!["refund"]);
+        assert_eq!(slice("coverage != 0.2").0.len(), 3);
+        assert_eq!(slice("file =~ '^payments/' && callers == 0").0, Vec::<String>::new());
+        assert_eq!(slice("file =~ '^web/' || callers > 1").0, vec!["render"]);
+        assert_eq!(slice("owner == 'nobody'").1, 0);
+    }
+}
@@ -0,0 +1,39 @@
+use std::fs;
+use std::path::Path;
+
+use crate::codegraph::types::{FunctionInfo, PetCodeGraph};
+
+/// 查找包含指定行的最内层函数
+pub fn enclosing_function<'a>(graph: &'a PetCodeGraph, file_path: &Path, line: usize) -> Option<&'a FunctionInfo> {
+    graph
+        .find_functions_by_file(&file_path.to_path_buf())
+        .into_iter()
+        .filter(|f| f.line_start <= line && line <= f.line_end)
+        .min_by_key(|f| f.line_end - f.line_start)
+}
+
+/// 逐行遍历图中所有源文件，回调参数为文件路径、行号（从1开始）、行内容和所在函数
+pub fn for_each_source_line<F>(graph: &PetCodeGraph, mut callback: F)
+where
+    F: FnMut(&Path, usize, &str, Option<&FunctionInfo>),
+{
+    let mut files: Vec<&std::path::PathBuf> = graph.file_functions.keys().collect();
+    files.sort();
+
+    for file_path in files {
+        let Ok(content) = fs::read_to_string(file_path) else {
+            continue;
+        };
+        let functions = graph.find_functions_by_file(file_path);
+
+        for (index, line) in content.lines().enumerate() {
+            let line_number = index + 1;
+            let function = functions
+                .iter()
+                .filter(|f| f.line_start <= line_number && line_number <= f.line_end)
+                .min_by_key(|f| f.line_end - f.line_start)
+                .copied();
+            callback(file_path, line_number, line, function);
+        }
+    }
+}
@@ -0,0 +1,393 @@
+use std::collections::{HashMap, VecDeque};
+use std::fs;
+use std::path::Path;
+use std::sync::LazyLock;
+
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::codegraph::types::{FunctionInfo, PetCodeGraph};
+
+/// 调用链补全时允许的最大中间跳数
+const MAX_GAP_DEPTH: usize = 6;
+
+/// 从原始堆栈中解析出的单帧
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StackFrame {
+    pub raw: String,
+    pub symbol: Option<String>,
+    pub file: Option<String>,
+    pub line: Option<usize>,
+}
+
+/// 帧在解释结果中的来源
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FrameOrigin {
+    /// 直接来自堆栈
+    Trace,
+    /// 根据调用图补全的中间调用
+    Inferred,
+}
+
+/// 解释后的单帧
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExplainedFrame {
+    pub origin: FrameOrigin,
+    /// 原始帧（补全帧为空）
+    pub frame: Option<StackFrame>,
+    pub function_id: Option<Uuid>,
+    pub function_name: Option<String>,
+    pub file_path: Option<String>,
+    pub line_start: Option<usize>,
+    pub line_end: Option<usize>,
+    pub snippet: Option<String>,
+}
+
+/// 堆栈解释结果，帧按调用顺序（外层在前）排列
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExplainedStackTrace {
+    pub frames: Vec<ExplainedFrame>,
+    pub resolved_frames: usize,
+    pub inferred_frames: usize,
+    pub unresolved_frames: usize,
+}
+
+// 各语言堆栈帧的格式，编译一次后复用
+static PYTHON_FRAME: LazyLock<Regex> = LazyLock::new(|| Regex::new(r#"File "([^"]+)", line (\d+)(?:, in (\S+))?"#).unwrap());
+static JAVA_FRAME: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"at ([\w$.<>]+)\(([^:()]+):(\d+)\)").unwrap());
+static JS_NAMED_FRAME: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"at (?:async )?([\w$.<>\[\] ]+?) \((.+?):(\d+)(?::\d+)?\)").unwrap());
+static FILE_LINE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?:at )?((?:[A-Za-z]:)?[\w./\\-]+\.\w+):(\d+)(?::\d+)?").unwrap());
+static RUST_SYMBOL: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"^\s*\d+:\s+(\S+)").unwrap());
+static GO_SYMBOL: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"^([\w./*()-]+)\(.*\)$").unwrap());
+
+/// 解析原始堆栈文本，支持Python、Java、JavaScript、Rust和Go的常见格式
+pub fn parse_stack_trace(trace: &str) -> Vec<StackFrame> {
+    let mut frames: Vec<StackFrame> = Vec::new();
+    // Rust/Go 将符号和位置分两行输出，先记下符号等待下一行的位置
+    let mut pending_symbol: Option<String> = None;
+
+    for line in trace.lines() {
+        let trimmed = line.trim();
+        if trimmed.is_empty() {
+            continue;
+        }
+
+        let frame = if let Some(c) = PYTHON_FRAME.captures(trimmed) {
+            Some(StackFrame {
+                raw: trimmed.to_string(),
+                symbol: c.get(3).map(|m| m.as_str().to_string()),
+                file: Some(c[1].to_string()),
+                line: c[2].parse().ok(),
+            })
+        } else if let Some(c) = JAVA_FRAME.captures(trimmed) {
+            Some(StackFrame {
+                raw: trimmed.to_string(),
+                symbol: Some(c[1].to_string()),
+                file: Some(c[2].to_string()),
+                line: c[3].parse().ok(),
+            })
+        } else if let Some(c) = JS_NAMED_FRAME.captures(trimmed) {
+            Some(StackFrame {
+                raw: trimmed.to_string(),
+                symbol: Some(c[1].trim().to_string()),
+                file: Some(c[2].to_string()),
+                line: c[3].parse().ok(),
+            })
+        } else if let Some(c) = FILE_LINE.captures(trimmed) {
+            Some(StackFrame {
+                raw: trimmed.to_string(),
+                symbol: pending_symbol.take(),
+                file: Some(c[1].to_string()),
+                line: c[2].parse().ok(),
+            })
+        } else {
+            if let Some(c) = RUST_SYMBOL.captures(trimmed) {
+                pending_symbol = Some(c[1].to_string());
+            } else if let Some(c) = GO_SYMBOL.captures(trimmed) {
+                pending_symbol = Some(c[1].to_string());
+            }
+            None
+        };
+
+        if let Some(frame) = frame {
+            pending_symbol = None;
+            frames.push(frame);
+        }
+    }
+
+    frames
+}
+
+/// 判断堆栈是否为外层在前（Python的traceback）
+fn is_outermost_first(trace: &str) -> bool {
+    trace.contains("Traceback (most recent call last)")
+}
+
+/// 取符号的最后一段作为函数名，如 `pkg.Class.method` -> `method`
+fn short_symbol_name(symbol: &str) -> &str {
+    let symbol = symbol.trim_end_matches("()");
+    let symbol = symbol.rsplit("::").next().unwrap_or(symbol);
+    let symbol = symbol.rsplit('.').next().unwrap_or(symbol);
+    symbol.trim_start_matches('*').trim_matches(|c| c == '(' || c == ')')
+}
+
+/// 帧中的文件与图中的文件是否相同：按路径分量比较，一方是另一方的后缀即可，
+/// 因此 `a.py` 不会匹配 `data.py`
+fn same_file(path: &Path, frame_file: &str) -> bool {
+    let frame_file = frame_file.replace('\\', "/");
+    let frame_path = Path::new(frame_file.trim_start_matches("./"));
+    let path = path.to_string_lossy().replace('\\', "/");
+    let path = Path::new(&path);
+    path.ends_with(frame_path) || frame_path.ends_with(path)
+}
+
+/// 将单帧解析到调用图中的函数：优先使用文件+行号，其次使用符号名
+pub fn resolve_frame<'a>(graph: &'a PetCodeGraph, frame: &StackFrame) -> Option<&'a FunctionInfo> {
+    if let (Some(file), Some(line)) = (&frame.file, frame.line) {
+        let by_location = graph
+            .get_all_functions()
+            .into_iter()
+            // 未解析调用的占位节点位于调用行上，不能作为帧所在的函数
+            .filter(|f| !f.is_unresolved())
+            .filter(|f| same_file(&f.file_path, file) && f.line_start <= line && line <= f.line_end)
+            // 嵌套函数取范围最小者
+            .min_by_key(|f| f.line_end - f.line_start);
+        if by_location.is_some() {
+            return by_location;
+        }
+    }
+
+    let symbol = frame.symbol.as_deref()?;
+    let name = short_symbol_name(symbol);
+    let candidates = graph.find_functions_by_name(name);
+    match &frame.file {
+        Some(file) => candidates
+            .iter()
+            .find(|f| same_file(&f.file_path, file))
+            .or(candidates.first())
+            .copied(),
+        None => candidates.first().copied(),
+    }
+}
+
+/// 调用图上从 `from` 到 `to` 的最短路径（不含两端），找不到返回None
+pub fn shortest_call_path(graph: &PetCodeGraph, from: &Uuid, to: &Uuid, max_depth: usize) -> Option<Vec<Uuid>> {
+    if from == to {
+        return Some(Vec::new());
+    }
+
+    let mut parents: HashMap<Uuid, Uuid> = HashMap::new();
+    let mut queue = VecDeque::from([(*from, 0usize)]);
+    while let Some((current, depth)) = queue.pop_front() {
+        if depth >= max_depth {
+            continue;
+        }
+        for (callee, _) in graph.get_callees(&current) {
+            if callee.id == *from || parents.contains_key(&callee.id) {
+                continue;
+            }
+            parents.insert(callee.id, current);
+            if callee.id == *to {
+                let mut path = Vec::new();
+                let mut node = parents[to];
+                while node != *from {
+                    path.push(node);
+                    node = parents[&node];
+                }
+                path.reverse();
+                return Some(path);
+            }
+            queue.push_back((callee.id, depth + 1));
+        }
+    }
+    None
+}
+
+/// 读取函数源码片段，超过 `max_lines` 行时截断
+pub fn read_function_snippet(function: &FunctionInfo, max_lines: usize) -> Option<String> {
+    let content = fs::read_to_string(&function.file_path).ok()?;
+    let start = function.line_start.saturating_sub(1);
+    let len = (function.line_end + 1).saturating_sub(function.line_start).min(max_lines);
+    let lines: Vec<&str> = content.lines().skip(start).take(len).collect();
+    if lines.is_empty() {
+        None
+    } else {
+        Some(lines.join("\n"))
+    }
+}
+
+/// 解释堆栈：解析每一帧、补全缺失的中间调用并附加代码片段
+pub fn explain_stack_trace(graph: &PetCodeGraph, trace: &str, snippet_lines: Option<usize>) -> ExplainedStackTrace {
+    let mut frames = parse_stack_trace(trace);
+    if !is_outermost_first(trace) {
+        frames.reverse();
+    }
+
+    let to_explained = |origin: FrameOrigin, frame: Option<StackFrame>, function: Option<&FunctionInfo>| ExplainedFrame {
+        origin,
+        frame,
+        function_id: function.map(|f| f.id),
+        function_name: function.map(|f| f.name.clone()),
+        file_path: function.map(|f| f.file_path.display().to_string()),
+        line_start: function.map(|f| f.line_start),
+        line_end: function.map(|f| f.line_end),
+        snippet: function.and_then(|f| snippet_lines.and_then(|n| read_function_snippet(f, n))),
+    };
+
+    let mut explained = Vec::new();
+    let mut previous: Option<Uuid> = None;
+    for frame in frames {
+        let function = resolve_frame(graph, &frame);
+        if let (Some(prev), Some(current)) = (previous, function) {
+            if let Some(path) = shortest_call_path(graph, &prev, &current.id, MAX_GAP_DEPTH) {
+                for id in path {
+                    explained.push(to_explained(FrameOrigin::Inferred, None, graph.get_function_by_id(&id)));
+                }
+            }
+        }
+        previous = function.map(|f| f.id);
+        explained.push(to_explained(FrameOrigin::Trace, Some(frame), function));
+    }
+
+    let resolved_frames = explained
+        .iter()
+        .filter(|f| f.origin == FrameOrigin::Trace && f.function_id.is_some())
+        .count();
+    let inferred_frames = explained.iter().filter(|f| f.origin == FrameOrigin::Inferred).count();
+    let unresolved_frames = explained.iter().filter(|f| f.function_id.is_none()).count();
+
+    ExplainedStackTrace {
+        frames: explained,
+        resolved_frames,
+        inferred_frames,
+        unresolved_frames,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::codegraph::types::FunctionKind;
+
+    fn function(name: &str, file: &Path, line_start: usize, line_end: usize) -> FunctionInfo {
+        FunctionInfo {
+            id: Uuid::new_v4(),
+            name: name.to_string(),
+            file_path: file.to_path_buf(),
+            line_start,
+            line_end,
+            namespace: "app".to_string(),
+            language: "python".to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_parse_common_formats() {
+        let python = "Traceback (most recent call last):\n  File \"app/main.py\", line 10, in run\n    handle()\n  File \"app/handlers.py\", line 42, in handle\n    raise ValueError()\nValueError";
+        let frames = parse_stack_trace(python);
+        assert_eq!(frames.len(), 2);
+        assert_eq!(frames[1].symbol.as_deref(), Some("handle"));
+        assert_eq!(frames[1].file.as_deref(), Some("app/handlers.py"));
+        assert_eq!(frames[1].line, Some(42));
+
+        let java = "java.lang.IllegalStateException: boom\n\tat com.acme.Service.process(Service.java:27)\n\tat com.acme.Main.main(Main.java:5)";
+        let frames = parse_stack_trace(java);
+        assert_eq!(frames.len(), 2);
+        assert_eq!(frames[0].symbol.as_deref(), Some("com.acme.Service.process"));
+        assert_eq!(short_symbol_name(frames[0].symbol.as_ref().unwrap()), "process");
+
+        let js = "Error: nope\n    at validate (/srv/app/lib/check.js:12:7)\n    at /srv/app/index.js:3:1";
+        let frames = parse_stack_trace(js);
+        assert_eq!(frames.len(), 2);
+        assert_eq!(frames[0].symbol.as_deref(), Some("validate"));
+        assert_eq!(frames[1].file.as_deref(), Some("/srv/app/index.js"));
+
+        let rust = "   0: demo::parse_config\n             at ./src/config.rs:18:9\n   1: demo::main\n             at ./src/main.rs:4:5";
+        let frames = parse_stack_trace(rust);
+        assert_eq!(frames.len(), 2);
+        assert_eq!(frames[0].symbol.as_deref(), Some("demo::parse_config"));
+        assert_eq!(frames[0].line, Some(18));
+    }
+
+    #[test]
+    fn test_frame_files_match_by_components() {
+        assert!(same_file(Path::new("/srv/app/handlers.py"), "app/handlers.py"));
+        assert!(same_file(Path::new("/work/demo/src/main.rs"), "./src/main.rs"));
+        assert!(same_file(Path::new("/srv/app/lib/check.js"), "/srv/app/lib/check.js"));
+        assert!(same_file(Path::new("C:\\work\\app\\main.py"), "app/main.py"));
+        // 文件名的后缀不算同一文件
+        assert!(!same_file(Path::new("/srv/app/data.py"), "a.py"));
+        assert!(!same_file(Path::new("/srv/app/handlers.py"), "dlers.py"));
+    }
+
+    #[test]
+    fn test_explain_stack_trace_against_graph() {
+        let dir = tempfile::tempdir().unwrap();
+        let app = dir.path().join("app");
+        fs::create_dir_all(&app).unwrap();
+        let (main_py, handlers_py, db_py) = (app.join("main.py"), app.join("handlers.py"), app.join("db.py"));
+        fs::write(&main_py, "def run():\n    handle()\n").unwrap();
+        fs::write(&handlers_py, "def handle():\n    validate()\n\ndef validate():\n    save()\n").unwrap();
+        fs::write(&db_py, "def save():\n    conn.execute()\n    raise IOError()\n").unwrap();
+
+        let run = function("run", &main_py, 1, 2);
+        let handle = function("handle", &handlers_py, 1, 2);
+        let validate = function("validate", &handlers_py, 4, 5);
+        let save = function("save", &db_py, 1, 3);
+        // 未解析调用的占位节点位于调用行上，范围比 `save` 小也不能作为帧所在的函数
+        let execute = FunctionInfo { kind: FunctionKind::Unresolved, ..function("conn.execute", &db_py, 2, 2) };
+        let mut graph = PetCodeGraph::new();
+        for f in [&run, &handle, &validate, &save, &execute] {
+            graph.add_function(f.clone());
+        }
+        graph.add_call(&run, &handle);
+        graph.add_call(&handle, &validate);
+        graph.add_call(&validate, &save);
+        graph.add_call(&save, &execute);
+
+        let frame = |file: Option<&str>, line: Option<usize>, symbol: Option<&str>| StackFrame {
+            raw: String::new(),
+            symbol: symbol.map(str::to_string),
+            file: file.map(str::to_string),
+            line,
+        };
+        assert_eq!(resolve_frame(&graph, &frame(Some("app/db.py"), Some(2), None)).map(|f| f.id), Some(save.id));
+        // 行号不在任何函数内时按符号名解析
+        assert_eq!(resolve_frame(&graph, &frame(Some("app/handlers.py"), Some(3), Some("app.handlers.validate"))).map(|f| f.id), Some(validate.id));
+        assert!(resolve_frame(&graph, &frame(Some("lib/vendor.py"), Some(5), Some("helper"))).is_none());
+
+        assert_eq!(shortest_call_path(&graph, &run.id, &save.id, MAX_GAP_DEPTH), Some(vec![handle.id, validate.id]));
+        assert_eq!(shortest_call_path(&graph, &run.id, &save.id, 2), None);
+        assert_eq!(shortest_call_path(&graph, &save.id, &run.id, MAX_GAP_DEPTH), None);
+
+        let trace = "Traceback (most recent call last):\n  File \"app/main.py\", line 2, in run\n    handle()\n  File \"app/db.py\", line 3, in save\n    raise IOError()\n  File \"lib/vendor.py\", line 5, in helper\nOSError";
+        let explained = explain_stack_trace(&graph, trace, Some(2));
+        let frames: Vec<(FrameOrigin, Option<&str>)> =
+            explained.frames.iter().map(|f| (f.origin.clone(), f.function_name.as_deref())).collect();
+        assert_eq!(
+            frames,
+            vec![
+                (FrameOrigin::Trace, Some("run")),
+                (FrameOrigin::Inferred, Some("handle")),
+                (FrameOrigin::Inferred, Some("validate")),
+                (FrameOrigin::Trace, Some("save")),
+                (FrameOrigin::Trace, None),
+            ]
+        );
+        assert_eq!((explained.resolved_frames, explained.inferred_frames, explained.unresolved_frames), (2, 2, 1));
+        assert!(explained.frames[1].frame.is_none());
+        assert_eq!(explained.frames[4].frame.as_ref().and_then(|f| f.symbol.as_deref()), Some("helper"));
+        // 片段按 `snippet_lines` 截断，未解析的帧没有片段
+        assert_eq!(explained.frames[3].snippet.as_deref(), Some("def save():\n    conn.execute()"));
+        assert_eq!(explained.frames[0].snippet.as_deref(), Some("def run():\n    handle()"));
+        assert!(explained.frames[4].snippet.is_none());
+
+        let without_snippets = explain_stack_trace(&graph, trace, None);
+        assert!(without_snippets.frames.iter().all(|f| f.snippet.is_none()));
+    }
+}
@@ -0,0 +1,259 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::services::lint::LintFinding;
+
+/// 行内抑制标记：`// codegraph:ignore dead-code` 作用于所在行或紧随其后的声明
+pub const IGNORE_MARKER: &str = "codegraph:ignore";
+/// 文件级抑制标记：`// codegraph:ignore-file cycle` 作用于整个文件
+pub const IGNORE_FILE_MARKER: &str = "codegraph:ignore-file";
+
+/// 基线文件格式版本
+const BASELINE_VERSION: u32 = 1;
+
+/// 一条抑制声明；`rules` 为空表示抑制所有规则
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+struct Suppression {
+    rules: Vec<String>,
+}
+
+impl Suppression {
+    fn covers(&self, rule_id: &str) -> bool {
+        self.rules.is_empty() || self.rules.iter().any(|r| r == rule_id)
+    }
+}
+
+/// 单个文件中的抑制注释
+#[derive(Debug, Clone, Default)]
+pub struct InlineSuppressions {
+    file: Vec<Suppression>,
+    lines: HashMap<usize, Suppression>,
+    /// 每行是否只含注释、属性或装饰器（声明前的抑制注释可以隔着这些行生效）
+    annotation_lines: Vec<bool>,
+}
+
+impl InlineSuppressions {
+    /// 从源码中解析抑制注释，行号从1开始
+    pub fn parse(source: &str) -> Self {
+        let mut result = Self::default();
+        for (index, line) in source.lines().enumerate() {
+            let trimmed = line.trim();
+            result.annotation_lines.push(is_annotation_line(trimmed));
+            if let Some(rest) = find_marker(line, IGNORE_FILE_MARKER) {
+                result.file.push(parse_rules(rest));
+            } else if let Some(rest) = find_marker(line, IGNORE_MARKER) {
+                result.lines.insert(index + 1, parse_rules(rest));
+            }
+        }
+        result
+    }
+
+    /// 起始于 `line` 的结果是否被抑制：检查文件级标记、同一行，以及声明上方连续的注释/属性行
+    pub fn suppresses(&self, rule_id: &str, line: usize) -> bool {
+        if self.file.iter().any(|s| s.covers(rule_id)) {
+            return true;
+        }
+        if self.lines.get(&line).is_some_and(|s| s.covers(rule_id)) {
+            return true;
+        }
+        let mut current = line;
+        while current > 1 && self.annotation_lines.get(current - 2).copied().unwrap_or(false) {
+            current -= 1;
+            if self.lines.get(&current).is_some_and(|s| s.covers(rule_id)) {
+                return true;
+            }
+        }
+        false
+    }
+}
+
+fn is_annotation_line(trimmed: &str) -> bool {
+    ["//", "#", "@", "/*", "*", "--"].iter().any(|prefix| trimmed.starts_with(prefix))
+}
+
+fn find_marker<'a>(line: &'a str, marker: &str) -> Option<&'a str> {
+    let index = line.find(marker)?;
+    let rest = &line[index + marker.len()..];
+    // `codegraph:ignore-file` 以 `codegraph:ignore` 开头，要求标记后紧跟空白或行尾
+    match rest.chars().next() {
+        None => Some(rest),
+        Some(c) if c.is_whitespace() => Some(rest),
+        _ => None,
+    }
+}
+
+/// 解析标记后的规则列表，遇到第一个不像规则ID的词为止（之后视为说明文字）
+fn parse_rules(rest: &str) -> Suppression {
+    let rules = rest
+        .split(|c: char| c.is_whitespace() || c == ',')
+        .filter(|word| !word.is_empty())
+        .take_while(|word| {
+            word.starts_with(|c: char| c.is_ascii_lowercase())
+                && word.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-')
+        })
+        .map(str::to_string)
+        .collect();
+    Suppression { rules }
+}
+
+/// 按行内注释过滤结果，返回保留的结果和被抑制的数量
+pub fn apply_inline_suppressions(findings: Vec<LintFinding>) -> (Vec<LintFinding>, usize) {
+    let mut cache: HashMap<std::path::PathBuf, InlineSuppressions> = HashMap::new();
+    let mut kept = Vec::with_capacity(findings.len());
+    let mut suppressed = 0;
+    for finding in findings {
+        let suppressions = cache.entry(finding.file_path.clone()).or_insert_with(|| {
+            fs::read_to_string(&finding.file_path)
+                .map(|source| InlineSuppressions::parse(&source))
+                .unwrap_or_default()
+        });
+        if suppressions.suppresses(finding.rule.id(), finding.line_start) {
+            suppressed += 1;
+        } else {
+            kept.push(finding);
+        }
+    }
+    (kept, suppressed)
+}
+
+/// 基线中的一条已知结果；不记录行号，代码移动后仍能匹配
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct BaselineEntry {
+    pub rule: String,
+    /// 相对于检查根目录的路径，使用 `/` 分隔
+    pub file: String,
+    pub function: String,
+}
+
+/// 已知结果的基线文件，提交到仓库后只有新增的结果会被报告
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Baseline {
+    pub version: u32,
+    pub findings: Vec<BaselineEntry>,
+}
+
+impl Baseline {
+    /// 由当前结果生成基线
+    pub fn from_findings(findings: &[LintFinding], root: &Path) -> Self {
+        let mut entries: Vec<BaselineEntry> = findings.iter().map(|f| entry_for(f, root)).collect();
+        entries.sort();
+        Self { version: BASELINE_VERSION, findings: entries }
+    }
+
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let content = fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read baseline {}: {}", path.display(), e))?;
+        let baseline: Self = serde_json::from_str(&content)
+            .map_err(|e| format!("Invalid baseline {}: {}", path.display(), e))?;
+        if baseline.version != BASELINE_VERSION {
+            return Err(format!(
+                "Unsupported baseline version {} in {} (expected {})",
+                baseline.version,
+                path.display(),
+                BASELINE_VERSION
+            ));
+        }
+        Ok(baseline)
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        let json = serde_json::to_string_pretty(self).map_err(|e| e.to_string())?;
+        fs::write(path, json + "\n").map_err(|e| format!("Failed to write baseline {}: {}", path.display(), e))
+    }
+
+    /// 过滤掉基线中已有的结果，返回新增结果和被过滤的数量。
+    /// 同一 (规则, 文件, 函数) 出现的次数超过基线记录时，多出的部分视为新增
+    pub fn filter(&self, findings: Vec<LintFinding>, root: &Path) -> (Vec<LintFinding>, usize) {
+        let mut remaining: HashMap<&BaselineEntry, usize> = HashMap::new();
+        for entry in &self.findings {
+            *remaining.entry(entry).or_insert(0) += 1;
+        }
+        let mut kept = Vec::new();
+        let mut baselined = 0;
+        for finding in findings {
+            let entry = entry_for(&finding, root);
+            match remaining.get_mut(&entry) {
+                Some(count) if *count > 0 => {
+                    *count -= 1;
+                    baselined += 1;
+                }
+                _ => kept.push(finding),
+            }
+        }
+        (kept, baselined)
+    }
+}
+
+fn entry_for(finding: &LintFinding, root: &Path) -> BaselineEntry {
+    let relative = finding.file_path.strip_prefix(root).unwrap_or(&finding.file_path);
+    let file = relative
+        .components()
+        .map(|c| c.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/");
+    BaselineEntry {
+        rule: finding.rule.id().to_string(),
+        file,
+        function: finding.function_name.clone(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::lint::LintRule;
+    use std::path::PathBuf;
+
+    fn finding(rule: LintRule, file: &str, function: &str, line: usize) -> LintFinding {
+        LintFinding {
+            rule,
+            function_name: function.to_string(),
+            file_path: PathBuf::from(file),
+            line_start: line,
+            line_end: line + 3,
+            value: 0,
+            threshold: 0,
+            message: String::new(),
+            severity: Default::default(),
+        }
+    }
+
+    #[test]
+    fn test_inline_suppressions() {
+        let source = "// codegraph:ignore dead-code, cycle legacy entry point\n#[inline]\nfn old() {}\n\nfn wide(a: i32) {} // codegraph:ignore\n\n/* codegraph:ignore-file nesting-depth */\n";
+        let suppressions = InlineSuppressions::parse(source);
+        assert!(suppressions.suppresses("dead-code", 3));
+        assert!(suppressions.suppresses("cycle", 3));
+        assert!(!suppressions.suppresses("function-length", 3));
+        assert!(suppressions.suppresses("parameter-count", 5));
+        assert!(suppressions.suppresses("nesting-depth", 1));
+        assert!(!suppressions.suppresses("dead-code", 6));
+    }
+
+    #[test]
+    fn test_baseline_filters_known_findings() {
+        let root = Path::new("/repo");
+        let known = vec![
+            finding(LintRule::DeadCode, "/repo/src/a.rs", "old", 10),
+            finding(LintRule::FunctionLength, "/repo/src/a.rs", "big", 20),
+        ];
+        let baseline = Baseline::from_findings(&known, root);
+        assert_eq!(baseline.findings[0].file, "src/a.rs");
+
+        // 代码移动后行号变化仍能匹配；同名的第二个结果超出基线数量，视为新增
+        let current = vec![
+            finding(LintRule::DeadCode, "/repo/src/a.rs", "old", 42),
+            finding(LintRule::FunctionLength, "/repo/src/a.rs", "big", 60),
+            finding(LintRule::FunctionLength, "/repo/src/a.rs", "big", 90),
+            finding(LintRule::Cycle, "/repo/src/b.rs", "ping", 1),
+        ];
+        let (kept, baselined) = baseline.filter(current, root);
+        assert_eq!(baselined, 2);
+        assert_eq!(kept.len(), 2);
+        assert_eq!(kept[0].line_start, 90);
+        assert_eq!(kept[1].rule, LintRule::Cycle);
+    }
+}
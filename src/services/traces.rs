@@ -0,0 +1,493 @@
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use uuid::Uuid;
+
+use crate::codegraph::types::{FunctionInfo, PetCodeGraph};
+use crate::services::stacktrace::{resolve_frame, shortest_call_path, StackFrame};
+use crate::storage::annotations::{path_qualified_name, FunctionIndex, MetricMap};
+
+/// 父子 span 之间在静态调用图上查找中间调用的最大深度
+const MAX_LINK_DEPTH: usize = 8;
+
+/// 从导出数据中读取的单个 span
+#[derive(Debug, Clone, PartialEq)]
+pub struct TraceSpan {
+    pub trace_id: String,
+    pub span_id: String,
+    pub parent_span_id: Option<String>,
+    pub name: String,
+    pub service: Option<String>,
+    pub start_ns: u64,
+    pub end_ns: u64,
+    pub attributes: HashMap<String, String>,
+}
+
+/// span 映射到函数所依据的信息
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SpanMatch {
+    /// `code.function`/`code.filepath`/`code.lineno` 等属性
+    CodeAttributes,
+    /// span 名本身是函数名或限定名
+    SpanName,
+}
+
+/// span 与最近一个已映射祖先 span 的关系
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SpanLink {
+    /// 没有已映射的祖先
+    Root,
+    /// 与祖先映射到同一函数
+    SameFunction,
+    /// 静态调用图上祖先函数可以到达本函数
+    StaticPath,
+    /// 祖先位于其他服务，跨服务调用
+    CrossService,
+    /// 同一服务内，但静态调用图上没有路径（动态分发、回调等）
+    Unlinked,
+    /// span 未映射到函数
+    Unmapped,
+}
+
+/// 回放后的 span，按调用树深度优先、同层按开始时间排列
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReplayedSpan {
+    pub span_id: String,
+    pub parent_span_id: Option<String>,
+    pub name: String,
+    pub service: Option<String>,
+    pub depth: usize,
+    /// 相对 trace 开始时间的偏移
+    pub start_offset_ms: f64,
+    pub duration_ms: f64,
+    /// 扣除子 span 覆盖时间后的耗时
+    pub self_ms: f64,
+    pub function_id: Option<Uuid>,
+    pub function_name: Option<String>,
+    pub file_path: Option<String>,
+    pub line_start: Option<usize>,
+    pub matched_by: Option<SpanMatch>,
+    pub link: SpanLink,
+    /// 从祖先函数到本函数之间的中间调用（`link` 为 `static_path` 时）
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub static_path: Vec<String>,
+    /// 是否位于关键路径（从根开始每层选择最晚结束的子 span）
+    pub critical: bool,
+}
+
+/// 一条 trace 在静态调用图上的回放结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TraceReplay {
+    pub trace_id: String,
+    pub duration_ms: f64,
+    pub services: Vec<String>,
+    pub spans: Vec<ReplayedSpan>,
+    pub mapped_spans: usize,
+    /// 未映射到函数的 span 名（去重）
+    pub unmapped_span_names: Vec<String>,
+    /// 各服务的自身耗时合计
+    pub service_self_ms: BTreeMap<String, f64>,
+}
+
+fn attribute_value(value: &Value) -> Option<String> {
+    match value {
+        Value::String(s) => Some(s.clone()),
+        Value::Number(n) => Some(n.to_string()),
+        Value::Bool(b) => Some(b.to_string()),
+        // OTLP AnyValue: {"stringValue": ...} / {"intValue": "42"} / ...
+        Value::Object(map) => ["stringValue", "intValue", "doubleValue", "boolValue"]
+            .iter()
+            .find_map(|key| map.get(*key).and_then(attribute_value)),
+        _ => None,
+    }
+}
+
+/// OTLP 的 `[{key, value}]` 列表或普通对象
+fn parse_attributes(value: Option<&Value>) -> HashMap<String, String> {
+    match value {
+        Some(Value::Array(items)) => items
+            .iter()
+            .filter_map(|item| Some((item.get("key")?.as_str()?.to_string(), attribute_value(item.get("value")?)?)))
+            .collect(),
+        Some(Value::Object(map)) => map.iter().filter_map(|(k, v)| Some((k.clone(), attribute_value(v)?))).collect(),
+        _ => HashMap::new(),
+    }
+}
+
+fn as_u64(value: Option<&Value>) -> u64 {
+    match value {
+        Some(Value::Number(n)) => n.as_u64().unwrap_or(0),
+        Some(Value::String(s)) => s.parse().unwrap_or(0),
+        _ => 0,
+    }
+}
+
+fn non_empty(value: Option<&Value>) -> Option<String> {
+    value.and_then(Value::as_str).filter(|s| !s.is_empty()).map(str::to_string)
+}
+
+fn otlp_span(span: &Value, service: Option<&str>) -> Option<TraceSpan> {
+    let attributes = parse_attributes(span.get("attributes"));
+    Some(TraceSpan {
+        trace_id: non_empty(span.get("traceId"))?,
+        span_id: non_empty(span.get("spanId"))?,
+        parent_span_id: non_empty(span.get("parentSpanId")),
+        name: span.get("name").and_then(Value::as_str).unwrap_or_default().to_string(),
+        service: attributes
+            .get("service.name")
+            .cloned()
+            .or_else(|| service.map(str::to_string))
+            .or_else(|| non_empty(span.get("service"))),
+        start_ns: as_u64(span.get("startTimeUnixNano")),
+        end_ns: as_u64(span.get("endTimeUnixNano")),
+        attributes,
+    })
+}
+
+/// Zipkin v2：时间单位为微秒，属性在 `tags` 中
+fn zipkin_span(span: &Value) -> Option<TraceSpan> {
+    let start = as_u64(span.get("timestamp")) * 1000;
+    Some(TraceSpan {
+        trace_id: non_empty(span.get("traceId"))?,
+        span_id: non_empty(span.get("id"))?,
+        parent_span_id: non_empty(span.get("parentId")),
+        name: span.get("name").and_then(Value::as_str).unwrap_or_default().to_string(),
+        service: span.get("localEndpoint").and_then(|e| non_empty(e.get("serviceName"))),
+        start_ns: start,
+        end_ns: start + as_u64(span.get("duration")) * 1000,
+        attributes: parse_attributes(span.get("tags")),
+    })
+}
+
+/// 解析导出的 span：OTLP JSON（`resourceSpans`）、Zipkin v2 数组或 OTLP 风格的 span 数组
+pub fn parse_trace_export(content: &str) -> Result<Vec<TraceSpan>, String> {
+    let root: Value = serde_json::from_str(content).map_err(|e| format!("Invalid trace export: {}", e))?;
+    let mut spans = Vec::new();
+    if let Some(resource_spans) = root.get("resourceSpans").and_then(Value::as_array) {
+        for resource in resource_spans {
+            let resource_attributes = parse_attributes(resource.get("resource").and_then(|r| r.get("attributes")));
+            let service = resource_attributes.get("service.name").map(String::as_str);
+            let scopes = resource
+                .get("scopeSpans")
+                .or_else(|| resource.get("instrumentationLibrarySpans"))
+                .and_then(Value::as_array);
+            for scope in scopes.into_iter().flatten() {
+                let scope_spans = scope.get("spans").and_then(Value::as_array);
+                spans.extend(scope_spans.into_iter().flatten().filter_map(|span| otlp_span(span, service)));
+            }
+        }
+    } else if let Some(items) = root.as_array().or_else(|| root.get("spans").and_then(Value::as_array)) {
+        for item in items {
+            let span = if item.get("spanId").is_some() { otlp_span(item, None) } else { zipkin_span(item) };
+            spans.extend(span);
+        }
+    } else {
+        return Err("Trace export must contain `resourceSpans` or an array of spans".to_string());
+    }
+    if spans.is_empty() {
+        return Err("Trace export contains no spans".to_string());
+    }
+    Ok(spans)
+}
+
+fn first_attribute<'s>(span: &'s TraceSpan, keys: &[&str]) -> Option<&'s str> {
+    keys.iter().find_map(|key| span.attributes.get(*key)).map(String::as_str).filter(|s| !s.is_empty())
+}
+
+/// span 名中像函数名的部分：去掉参数列表，含空格（如 `GET /users`）时视为不可映射
+fn span_name_symbol(name: &str) -> Option<&str> {
+    let name = name.trim();
+    let name = name.split_once('(').map_or(name, |(head, _)| head);
+    (!name.is_empty() && !name.contains(char::is_whitespace)).then_some(name)
+}
+
+/// 将 span 映射到函数：优先使用 OpenTelemetry 代码属性，其次使用 span 名
+pub(crate) fn resolve_span<'a>(graph: &'a PetCodeGraph, index: &FunctionIndex<'a>, span: &TraceSpan) -> Option<(&'a FunctionInfo, SpanMatch)> {
+    let function = first_attribute(span, &["code.function.name", "code.function"]);
+    let namespace = first_attribute(span, &["code.namespace"]);
+    let file = first_attribute(span, &["code.file.path", "code.filepath"]);
+    let line = first_attribute(span, &["code.line.number", "code.lineno"]).and_then(|l| l.parse().ok());
+
+    if file.is_some() {
+        let frame = StackFrame {
+            raw: span.name.clone(),
+            symbol: function.map(str::to_string),
+            file: file.map(str::to_string),
+            line,
+        };
+        if let Some(found) = resolve_frame(graph, &frame) {
+            return Some((found, SpanMatch::CodeAttributes));
+        }
+    }
+    if let Some(function) = function {
+        let key = match namespace {
+            Some(namespace) => format!("{}.{}", namespace, function),
+            None => function.to_string(),
+        };
+        if let Some(found) = index.lookup(&key).first() {
+            return Some((found, SpanMatch::CodeAttributes));
+        }
+    }
+    let symbol = span_name_symbol(&span.name)?;
+    let matched = index.lookup(symbol);
+    // 只有函数名且有多个同名函数时无法确定
+    (matched.len() == 1).then(|| (matched[0], SpanMatch::SpanName))
+}
+
+/// 把 `[start, end)` 区间合并后计算总长度
+fn covered_length(mut intervals: Vec<(u64, u64)>) -> u64 {
+    intervals.sort();
+    let mut total = 0;
+    let mut current: Option<(u64, u64)> = None;
+    for (start, end) in intervals {
+        match current {
+            Some((s, e)) if start <= e => current = Some((s, e.max(end))),
+            Some((s, e)) => {
+                total += e - s;
+                current = Some((start, end));
+            }
+            None => current = Some((start, end)),
+        }
+    }
+    total + current.map_or(0, |(s, e)| e - s)
+}
+
+fn ms(ns: u64) -> f64 {
+    ns as f64 / 1_000_000.0
+}
+
+/// 回放 trace：每个 span 映射到函数，并与最近的已映射祖先在静态调用图上连接。
+/// 指定 `trace_id` 时只回放该 trace，结果按 trace 开始时间排列
+pub fn replay_traces(graph: &PetCodeGraph, spans: &[TraceSpan], trace_id: Option<&str>) -> Vec<TraceReplay> {
+    let index = FunctionIndex::new(graph);
+    let mut traces: BTreeMap<&str, Vec<&TraceSpan>> = BTreeMap::new();
+    for span in spans.iter().filter(|s| trace_id.is_none_or(|id| s.trace_id == id)) {
+        traces.entry(span.trace_id.as_str()).or_default().push(span);
+    }
+    let mut grouped: Vec<(&str, Vec<&TraceSpan>)> = traces.into_iter().collect();
+    grouped.sort_by_key(|(id, spans)| (spans.iter().map(|s| s.start_ns).min().unwrap_or(0), *id));
+    grouped
+        .into_iter()
+        .map(|(trace_id, spans)| replay_trace(graph, &index, trace_id, spans))
+        .collect()
+}
+
+/// 最近一个已映射祖先 span 的函数及其所属服务
+type MappedAncestor<'g, 's> = (&'g FunctionInfo, Option<&'s str>);
+
+fn replay_trace<'a>(graph: &'a PetCodeGraph, index: &FunctionIndex<'a>, trace_id: &str, mut spans: Vec<&TraceSpan>) -> TraceReplay {
+    spans.sort_by_key(|s| (s.start_ns, s.span_id.clone()));
+    let trace_start = spans.iter().map(|s| s.start_ns).min().unwrap_or(0);
+    let trace_end = spans.iter().map(|s| s.end_ns).max().unwrap_or(trace_start);
+    let ids: BTreeSet<&str> = spans.iter().map(|s| s.span_id.as_str()).collect();
+
+    // 父 span 缺失时当作根
+    let mut children: HashMap<Option<&str>, Vec<&TraceSpan>> = HashMap::new();
+    for span in &spans {
+        let parent = span.parent_span_id.as_deref().filter(|p| ids.contains(p));
+        children.entry(parent).or_default().push(span);
+    }
+
+    // 关键路径：从每个根开始选择最晚结束的子 span
+    let mut critical: BTreeSet<&str> = BTreeSet::new();
+    if let Some(root) = children.get(&None).and_then(|roots| roots.iter().max_by_key(|s| s.end_ns)) {
+        let mut current = *root;
+        loop {
+            critical.insert(current.span_id.as_str());
+            match children.get(&Some(current.span_id.as_str())).and_then(|c| c.iter().max_by_key(|s| s.end_ns)) {
+                Some(next) => current = next,
+                None => break,
+            }
+        }
+    }
+
+    let mut replayed = Vec::with_capacity(spans.len());
+    let mut stack: Vec<(&TraceSpan, usize, Option<MappedAncestor>)> = children
+        .get(&None)
+        .map(|roots| roots.iter().rev().map(|s| (*s, 0, None)).collect())
+        .unwrap_or_default();
+    while let Some((span, depth, ancestor)) = stack.pop() {
+        let resolved = resolve_span(graph, index, span);
+        let function = resolved.map(|(f, _)| f);
+        let mut static_path = Vec::new();
+        let link = match (function, ancestor) {
+            (None, _) => SpanLink::Unmapped,
+            (Some(_), None) => SpanLink::Root,
+            (Some(current), Some((previous, previous_service))) => {
+                if current.id == previous.id {
+                    SpanLink::SameFunction
+                } else if previous_service.is_some() && span.service.is_some() && previous_service != span.service.as_deref() {
+                    SpanLink::CrossService
+                } else if let Some(path) = shortest_call_path(graph, &previous.id, &current.id, MAX_LINK_DEPTH) {
+                    static_path = path
+                        .iter()
+                        .filter_map(|id| graph.get_function_by_id(id))
+                        .map(|f| f.name.clone())
+                        .collect();
+                    SpanLink::StaticPath
+                } else {
+                    SpanLink::Unlinked
+                }
+            }
+        };
+
+        let child_spans = children.get(&Some(span.span_id.as_str())).cloned().unwrap_or_default();
+        let covered = covered_length(
+            child_spans
+                .iter()
+                .map(|c| (c.start_ns.max(span.start_ns), c.end_ns.min(span.end_ns)))
+                .filter(|(s, e)| s < e)
+                .collect(),
+        );
+        let duration = span.end_ns.saturating_sub(span.start_ns);
+        replayed.push(ReplayedSpan {
+            span_id: span.span_id.clone(),
+            parent_span_id: span.parent_span_id.clone(),
+            name: span.name.clone(),
+            service: span.service.clone(),
+            depth,
+            start_offset_ms: ms(span.start_ns.saturating_sub(trace_start)),
+            duration_ms: ms(duration),
+            self_ms: ms(duration.saturating_sub(covered)),
+            function_id: function.map(|f| f.id),
+            function_name: function.map(|f| f.name.clone()),
+            file_path: function.map(|f| f.file_path.display().to_string()),
+            line_start: function.map(|f| f.line_start),
+            matched_by: resolved.map(|(_, m)| m),
+            link,
+            static_path,
+            critical: critical.contains(span.span_id.as_str()),
+        });
+
+        let next_ancestor = function.map(|f| (f, span.service.as_deref())).or(ancestor);
+        for child in child_spans.iter().rev() {
+            stack.push((child, depth + 1, next_ancestor));
+        }
+    }
+
+    let services: BTreeSet<String> = replayed.iter().filter_map(|s| s.service.clone()).collect();
+    let mut service_self_ms: BTreeMap<String, f64> = BTreeMap::new();
+    for span in &replayed {
+        if let Some(service) = &span.service {
+            *service_self_ms.entry(service.clone()).or_default() += span.self_ms;
+        }
+    }
+    let unmapped: BTreeSet<String> = replayed.iter().filter(|s| s.function_id.is_none()).map(|s| s.name.clone()).collect();
+
+    TraceReplay {
+        trace_id: trace_id.to_string(),
+        duration_ms: ms(trace_end.saturating_sub(trace_start)),
+        services: services.into_iter().collect(),
+        mapped_spans: replayed.iter().filter(|s| s.function_id.is_some()).count(),
+        unmapped_span_names: unmapped.into_iter().collect(),
+        service_self_ms,
+        spans: replayed,
+    }
+}
+
+/// 转换为注解：`trace_self_ms` 为映射到该函数的 span 自身耗时合计，`trace_spans` 为 span 数
+pub fn trace_annotations(replays: &[TraceReplay], graph: &PetCodeGraph, root: &Path) -> BTreeMap<String, MetricMap> {
+    let mut totals: HashMap<Uuid, (f64, f64)> = HashMap::new();
+    for span in replays.iter().flat_map(|r| &r.spans) {
+        if let Some(id) = span.function_id {
+            let entry = totals.entry(id).or_default();
+            entry.0 += span.self_ms;
+            entry.1 += 1.0;
+        }
+    }
+    totals
+        .into_iter()
+        .filter_map(|(id, (self_ms, count))| {
+            let function = graph.get_function_by_id(&id)?;
+            let metrics = MetricMap::from([("trace_self_ms".to_string(), self_ms), ("trace_spans".to_string(), count)]);
+            Some((path_qualified_name(function, root), metrics))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn function(name: &str, file: &str, line_start: usize, line_end: usize) -> FunctionInfo {
+        FunctionInfo {
+            id: Uuid::new_v4(),
+            name: name.to_string(),
+            file_path: PathBuf::from(file),
+            line_start,
+            line_end,
+            namespace: "global".to_string(),
+            language: "python".to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_replay_otlp_trace() {
+        let mut graph = PetCodeGraph::new();
+        let handle = function("handle_order", "/srv/api/orders.py", 10, 30);
+        let validate = function("validate", "/srv/api/checks.py", 1, 8);
+        let save = function("save_order", "/srv/api/db.py", 5, 20);
+        let charge = function("charge", "/srv/billing/pay.py", 3, 12);
+        for f in [&handle, &validate, &save, &charge] {
+            graph.add_function(f.clone());
+        }
+        graph.add_call(&handle, &validate);
+        graph.add_call(&validate, &save);
+
+        let export = r#"{"resourceSpans": [
+          {"resource": {"attributes": [{"key": "service.name", "value": {"stringValue": "api"}}]},
+           "scopeSpans": [{"spans": [
+             {"traceId": "t1", "spanId": "a", "name": "POST /orders", "startTimeUnixNano": "0", "endTimeUnixNano": "100000000",
+              "attributes": [{"key": "code.filepath", "value": {"stringValue": "api/orders.py"}}, {"key": "code.lineno", "value": {"intValue": "12"}}]},
+             {"traceId": "t1", "spanId": "b", "parentSpanId": "a", "name": "save_order", "startTimeUnixNano": "10000000", "endTimeUnixNano": "40000000"},
+             {"traceId": "t1", "spanId": "c", "parentSpanId": "a", "name": "GET /health", "startTimeUnixNano": "45000000", "endTimeUnixNano": "50000000"}
+           ]}]},
+          {"resource": {"attributes": [{"key": "service.name", "value": {"stringValue": "billing"}}]},
+           "scopeSpans": [{"spans": [
+             {"traceId": "t1", "spanId": "d", "parentSpanId": "a", "name": "charge", "startTimeUnixNano": "50000000", "endTimeUnixNano": "95000000"}
+           ]}]}
+        ]}"#;
+        let spans = parse_trace_export(export).unwrap();
+        assert_eq!(spans.len(), 4);
+        assert_eq!(spans[3].service.as_deref(), Some("billing"));
+
+        let replays = replay_traces(&graph, &spans, None);
+        assert_eq!(replays.len(), 1);
+        let trace = &replays[0];
+        assert_eq!(trace.services, vec!["api".to_string(), "billing".to_string()]);
+        assert_eq!(trace.mapped_spans, 3);
+        assert_eq!(trace.unmapped_span_names, vec!["GET /health".to_string()]);
+
+        let names: Vec<&str> = trace.spans.iter().map(|s| s.span_id.as_str()).collect();
+        assert_eq!(names, vec!["a", "b", "c", "d"]);
+        let root = &trace.spans[0];
+        assert_eq!(root.function_name.as_deref(), Some("handle_order"));
+        assert_eq!(root.matched_by, Some(SpanMatch::CodeAttributes));
+        assert!((root.self_ms - 20.0).abs() < 1e-9);
+        let save_span = &trace.spans[1];
+        assert_eq!(save_span.link, SpanLink::StaticPath);
+        assert_eq!(save_span.static_path, vec!["validate".to_string()]);
+        assert_eq!(trace.spans[2].link, SpanLink::Unmapped);
+        let charge_span = &trace.spans[3];
+        assert_eq!(charge_span.link, SpanLink::CrossService);
+        assert!(charge_span.critical && root.critical && !save_span.critical);
+        assert!((trace.service_self_ms["billing"] - 45.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_parse_zipkin_spans() {
+        let export = r#"[{"traceId": "t2", "id": "x", "name": "charge", "timestamp": 1000, "duration": 2500,
+                          "localEndpoint": {"serviceName": "billing"}, "tags": {"code.function": "charge"}}]"#;
+        let spans = parse_trace_export(export).unwrap();
+        assert_eq!(spans[0].span_id, "x");
+        assert_eq!(spans[0].end_ns - spans[0].start_ns, 2_500_000);
+        assert_eq!(spans[0].attributes["code.function"], "charge");
+        assert!(parse_trace_export("{}").is_err());
+    }
+}
@@ -0,0 +1,163 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::codegraph::types::{FunctionInfo, PetCodeGraph};
+
+/// 每组最多列出的候选函数
+const MAX_CANDIDATES: usize = 10;
+
+/// 一处未解析的调用
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct UnresolvedCallSite {
+    pub caller_id: Uuid,
+    pub caller_name: String,
+    pub file_path: String,
+    pub line_number: usize,
+}
+
+/// 与被调用名的最后一段同名的已知函数，可能是调用的真正目标
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ResolutionCandidate {
+    pub function_id: Uuid,
+    pub name: String,
+    pub namespace: String,
+    pub file_path: String,
+    pub line_start: usize,
+}
+
+/// 同一被调用名的所有未解析调用
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct UnresolvedCallGroup {
+    /// 调用处写出的名称，例如 `router.handle`
+    pub callee_name: String,
+    pub count: usize,
+    pub sites: Vec<UnresolvedCallSite>,
+    pub candidates: Vec<ResolutionCandidate>,
+}
+
+/// 图中未解析调用的汇总，用于评估图的质量
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct UnresolvedCallsReport {
+    pub total_calls: usize,
+    pub unresolved_calls: usize,
+    /// 按调用次数降序，次数相同时按名称排序
+    pub groups: Vec<UnresolvedCallGroup>,
+}
+
+/// 按被调用名分组列出所有未解析的调用，并为每组找出同名的候选函数
+pub fn triage_unresolved_calls(graph: &PetCodeGraph) -> UnresolvedCallsReport {
+    let relations = graph.get_all_call_relations();
+    let mut groups: BTreeMap<&str, Vec<UnresolvedCallSite>> = BTreeMap::new();
+    for relation in relations.iter().filter(|relation| !relation.is_resolved) {
+        groups.entry(relation.callee_name.as_str()).or_default().push(UnresolvedCallSite {
+            caller_id: relation.caller_id,
+            caller_name: relation.caller_name.clone(),
+            file_path: relation.caller_file.display().to_string(),
+            line_number: relation.line_number,
+        });
+    }
+
+    let mut by_name: BTreeMap<&str, Vec<&FunctionInfo>> = BTreeMap::new();
+    for function in graph.get_all_functions() {
+        if !function.is_unresolved() && !function.is_reexport() {
+            by_name.entry(function.name.as_str()).or_default().push(function);
+        }
+    }
+
+    let mut groups: Vec<UnresolvedCallGroup> = groups
+        .into_iter()
+        .map(|(callee_name, mut sites)| {
+            sites.sort_by(|a, b| (&a.file_path, a.line_number).cmp(&(&b.file_path, b.line_number)));
+            let mut candidates: Vec<ResolutionCandidate> = by_name
+                .get(short_name(callee_name))
+                .into_iter()
+                .flatten()
+                .map(|function| ResolutionCandidate {
+                    function_id: function.id,
+                    name: function.name.clone(),
+                    namespace: function.namespace.clone(),
+                    file_path: function.file_path.display().to_string(),
+                    line_start: function.line_start,
+                })
+                .collect();
+            candidates.sort_by(|a, b| (&a.file_path, a.line_start).cmp(&(&b.file_path, b.line_start)));
+            candidates.truncate(MAX_CANDIDATES);
+            UnresolvedCallGroup { callee_name: callee_name.to_string(), count: sites.len(), sites, candidates }
+        })
+        .collect();
+    groups.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.callee_name.cmp(&b.callee_name)));
+
+    UnresolvedCallsReport {
+        total_calls: relations.len(),
+        unresolved_calls: groups.iter().map(|group| group.count).sum(),
+        groups,
+    }
+}
+
+/// 限定名的最后一段：`self.repo.save` -> `save`，`fmt::format` -> `format`
+fn short_name(callee_name: &str) -> &str {
+    let name = callee_name.trim_end_matches("()");
+    name.rsplit(['.', ':', '>']).next().unwrap_or(name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::codegraph::types::{CallRelation, FunctionKind};
+    use std::path::PathBuf;
+
+    fn function(name: &str, namespace: &str, file: &str, line: usize) -> FunctionInfo {
+        FunctionInfo {
+            id: Uuid::new_v4(),
+            name: name.to_string(),
+            file_path: PathBuf::from(file),
+            line_start: line,
+            line_end: line + 5,
+            namespace: namespace.to_string(),
+            language: "python".to_string(),
+            ..Default::default()
+        }
+    }
+
+    fn call(caller: &FunctionInfo, callee: &FunctionInfo, callee_name: &str, line: usize, resolved: bool) -> CallRelation {
+        CallRelation {
+            callee_name: callee_name.to_string(),
+            line_number: line,
+            is_resolved: resolved,
+            ..CallRelation::between(caller, callee)
+        }
+    }
+
+    #[test]
+    fn test_groups_unresolved_calls_with_candidates() {
+        let mut graph = PetCodeGraph::new();
+        let main = function("main", "app", "app.py", 1);
+        let helper = function("helper", "app", "app.py", 10);
+        let save = function("save", "repo", "repo.py", 3);
+        let save_placeholder = FunctionInfo { kind: FunctionKind::Unresolved, ..function("repo.save", "unresolved", "", 0) };
+        let log_placeholder = FunctionInfo { kind: FunctionKind::Unresolved, ..function("log", "unresolved", "", 0) };
+        for f in [&main, &helper, &save, &save_placeholder, &log_placeholder] {
+            graph.add_function(f.clone());
+        }
+        graph.add_call_relation(call(&main, &helper, "helper", 2, true)).unwrap();
+        graph.add_call_relation(call(&main, &save_placeholder, "repo.save", 3, false)).unwrap();
+        graph.add_call_relation(call(&helper, &save_placeholder, "repo.save", 11, false)).unwrap();
+        graph.add_call_relation(call(&helper, &log_placeholder, "log", 12, false)).unwrap();
+
+        let report = triage_unresolved_calls(&graph);
+        assert_eq!((report.total_calls, report.unresolved_calls), (4, 3));
+        assert_eq!(report.groups.len(), 2);
+
+        let first = &report.groups[0];
+        assert_eq!((first.callee_name.as_str(), first.count), ("repo.save", 2));
+        assert_eq!(first.sites.iter().map(|s| s.line_number).collect::<Vec<_>>(), vec![3, 11]);
+        assert_eq!(first.candidates.len(), 1);
+        assert_eq!(first.candidates[0].function_id, save.id);
+
+        let second = &report.groups[1];
+        assert_eq!((second.callee_name.as_str(), second.count), ("log", 1));
+        assert!(second.candidates.is_empty());
+    }
+}
@@ -0,0 +1,184 @@
+use std::collections::{BTreeMap, HashMap};
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::codegraph::types::{FunctionInfo, PetCodeGraph};
+
+/// 单个节点上的指标，例如 `cpu_samples`、`coverage`
+pub type MetricMap = BTreeMap<String, f64>;
+
+/// 外部数据（性能剖析、覆盖率等）按限定名附加到图节点上的注解
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct NodeAnnotations {
+    /// 限定名 -> 指标
+    #[serde(default)]
+    pub nodes: BTreeMap<String, MetricMap>,
+    /// 最近一次导入的性能剖析中最热的调用路径
+    #[serde(default)]
+    pub hot_paths: Vec<HotPath>,
+}
+
+/// 剖析样本中出现的一条调用路径（从入口到叶子）
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HotPath {
+    /// 路径上的函数，使用 `文件路径::函数名` 形式的限定名
+    pub functions: Vec<String>,
+    pub samples: u64,
+    /// 占全部样本的百分比
+    pub percent: f64,
+}
+
+impl NodeAnnotations {
+    /// 合并新的指标，同名指标覆盖旧值
+    pub fn merge(&mut self, incoming: BTreeMap<String, MetricMap>) {
+        for (name, metrics) in incoming {
+            self.nodes.entry(name).or_default().extend(metrics);
+        }
+    }
+
+    /// 将限定名解析为图中的函数，返回每个函数的指标和未匹配到任何函数的限定名。
+    /// 多个限定名指向同一函数时指标合并
+    pub fn resolve(&self, graph: &PetCodeGraph) -> (HashMap<Uuid, MetricMap>, Vec<String>) {
+        let index = FunctionIndex::new(graph);
+        let mut resolved: HashMap<Uuid, MetricMap> = HashMap::new();
+        let mut unmatched = Vec::new();
+        for (key, metrics) in &self.nodes {
+            let matched = index.lookup(key);
+            if matched.is_empty() {
+                unmatched.push(key.clone());
+            }
+            for function in matched {
+                resolved.entry(function.id).or_default().extend(metrics.iter().map(|(k, v)| (k.clone(), *v)));
+            }
+        }
+        (resolved, unmatched)
+    }
+}
+
+/// 按函数名索引图中的函数，用于把外部数据中的限定名解析为节点
+pub(crate) struct FunctionIndex<'a> {
+    by_name: HashMap<&'a str, Vec<&'a FunctionInfo>>,
+}
+
+impl<'a> FunctionIndex<'a> {
+    pub(crate) fn new(graph: &'a PetCodeGraph) -> Self {
+        Self::from_functions(graph.graph.node_weights())
+    }
+
+    pub(crate) fn from_functions(functions: impl IntoIterator<Item = &'a FunctionInfo>) -> Self {
+        let mut by_name: HashMap<&str, Vec<&FunctionInfo>> = HashMap::new();
+        for function in functions.into_iter().filter(|f| !f.is_unresolved()) {
+            by_name.entry(function.name.as_str()).or_default().push(function);
+        }
+        Self { by_name }
+    }
+
+    /// `src/config.rs::parse` 形式按文件路径后缀匹配；其余形式的限定部分最后一段需与
+    /// 命名空间的最后一段或文件名（去掉扩展名）一致。只有函数名时匹配所有同名函数。
+    /// 限定部分不匹配但同名函数唯一时仍使用它，以兼容剖析器输出的
+    /// `crate::module::Type::method` 之类更长的路径
+    pub(crate) fn lookup(&self, key: &str) -> Vec<&'a FunctionInfo> {
+        if let Some((path, name)) = key.rsplit_once("::").filter(|(path, _)| path.contains(['/', '\\'])) {
+            return self
+                .by_name
+                .get(name)
+                .map(|candidates| candidates.iter().copied().filter(|f| f.file_path.ends_with(path)).collect())
+                .unwrap_or_default();
+        }
+
+        let (qualifier, name) = split_key(key);
+        let candidates = self.by_name.get(name).cloned().unwrap_or_default();
+        let Some(last) = qualifier.last() else {
+            return candidates;
+        };
+        let matched: Vec<&FunctionInfo> = candidates
+            .iter()
+            .copied()
+            .filter(|function| {
+                let namespace_tail = function.namespace.rsplit(['.', ':']).next().unwrap_or_default();
+                let file_stem = function.file_path.file_stem().and_then(|s| s.to_str());
+                namespace_tail == *last || file_stem == Some(*last)
+            })
+            .collect();
+        if matched.is_empty() && candidates.len() == 1 {
+            candidates
+        } else {
+            matched
+        }
+    }
+}
+
+/// 函数在项目内的限定名：`相对路径::函数名`，路径使用 `/` 分隔
+pub fn path_qualified_name(function: &FunctionInfo, root: &Path) -> String {
+    let relative = function.file_path.strip_prefix(root).unwrap_or(&function.file_path);
+    let path = relative
+        .components()
+        .map(|c| c.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/");
+    format!("{}::{}", path, function.name)
+}
+
+/// 按 `::`、`.`、`/`、`#` 拆分限定名，返回限定部分和函数名
+fn split_key(key: &str) -> (Vec<&str>, &str) {
+    let mut segments: Vec<&str> = key
+        .split("::")
+        .flat_map(|part| part.split(['.', '/', '\\', '#']))
+        .filter(|s| !s.is_empty())
+        .collect();
+    let name = segments.pop().unwrap_or(key);
+    (segments, name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn function(name: &str, namespace: &str, file: &str) -> FunctionInfo {
+        FunctionInfo {
+            id: Uuid::new_v4(),
+            name: name.to_string(),
+            file_path: PathBuf::from(file),
+            line_start: 1,
+            line_end: 5,
+            namespace: namespace.to_string(),
+            language: "rust".to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_resolve_qualified_names() {
+        let mut graph = PetCodeGraph::new();
+        let parse_a = function("parse", "crate", "/repo/src/config.rs");
+        let parse_b = function("parse", "crate", "/repo/src/query.rs");
+        let handle = function("handle", "com.example", "/repo/src/Server.java");
+        let (id_a, id_b, id_handle) = (parse_a.id, parse_b.id, handle.id);
+        for f in [parse_a, parse_b, handle] {
+            graph.add_function(f);
+        }
+
+        let mut annotations = NodeAnnotations::default();
+        annotations.merge(BTreeMap::from([
+            ("config::parse".to_string(), MetricMap::from([("cpu_samples".to_string(), 120.0)])),
+            ("com.example.Server.handle".to_string(), MetricMap::from([("coverage".to_string(), 0.4)])),
+            ("src/query.rs::parse".to_string(), MetricMap::from([("cpu_samples".to_string(), 7.0)])),
+            ("missing".to_string(), MetricMap::from([("cpu_samples".to_string(), 1.0)])),
+        ]));
+        annotations.merge(BTreeMap::from([(
+            "config::parse".to_string(),
+            MetricMap::from([("coverage".to_string(), 0.9)]),
+        )]));
+
+        let (resolved, unmatched) = annotations.resolve(&graph);
+        assert_eq!(resolved[&id_a].get("cpu_samples"), Some(&120.0));
+        assert_eq!(resolved[&id_a].get("coverage"), Some(&0.9));
+        assert_eq!(resolved[&id_b].get("cpu_samples"), Some(&7.0));
+        assert!(!resolved[&id_b].contains_key("coverage"));
+        assert_eq!(resolved[&id_handle].get("coverage"), Some(&0.4));
+        assert_eq!(unmatched, vec!["missing".to_string()]);
+    }
+}
@@ -0,0 +1,254 @@
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+
+use crate::cli::args::StorageMode;
+use crate::storage::persistence::{PersistenceManager, TENANTS_DIR};
+use crate::storage::skeleton_cache::SKELETONS_DIR;
+
+/// 项目保留规则，均未设置时不清理任何项目
+#[derive(Debug, Clone, Default)]
+pub struct RetentionRules {
+    /// 最后一次构建早于该时长的项目
+    pub max_age: Option<Duration>,
+    /// 每个存储根目录（默认存储及各租户）只保留最近构建的若干项目
+    pub keep_last: Option<usize>,
+    /// 登记的源码目录已不存在的项目
+    pub missing_source: bool,
+}
+
+/// 单个项目占用的存储
+#[derive(Debug, Clone, Serialize)]
+pub struct ProjectUsage {
+    pub project_id: String,
+    /// 服务端登记的源码目录；CLI 构建的项目没有登记
+    pub project_dir: Option<String>,
+    pub source_exists: Option<bool>,
+    /// 快照中的构建时间，否则为登记时间或文件修改时间
+    pub last_built: Option<DateTime<Utc>>,
+    pub commit: Option<String>,
+    /// 是否保存了快照（snapshot.json）
+    pub has_snapshot: bool,
+    /// 保存的图文件数（JSON 和/或二进制）
+    pub graph_files: usize,
+    pub files: usize,
+    pub bytes: u64,
+    /// 命中保留规则的原因；为空表示保留
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub prune_reason: Option<String>,
+}
+
+/// 一个存储根目录（默认存储或某个租户）
+#[derive(Debug, Clone, Serialize)]
+pub struct StorageRootUsage {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tenant: Option<String>,
+    pub path: PathBuf,
+    pub projects: Vec<ProjectUsage>,
+    pub skeleton_entries: usize,
+    pub skeleton_bytes: u64,
+    /// 项目目录和骨架缓存以外的文件（如项目登记表）
+    pub other_bytes: u64,
+    pub bytes: u64,
+}
+
+/// 存储目录的整体使用情况
+#[derive(Debug, Clone, Serialize)]
+pub struct StorageAudit {
+    pub path: PathBuf,
+    pub roots: Vec<StorageRootUsage>,
+    pub total_projects: usize,
+    pub total_snapshots: usize,
+    pub total_bytes: u64,
+    pub prunable_projects: usize,
+    pub prunable_bytes: u64,
+}
+
+impl StorageAudit {
+    /// 统计 `base_dir` 及其下各租户目录，并按 `rules` 标记可清理的项目
+    pub fn collect(base_dir: &Path, rules: &RetentionRules, now: DateTime<Utc>) -> io::Result<Self> {
+        let mut roots = vec![audit_root(base_dir, None, rules, now)?];
+        let tenants_dir = base_dir.join(TENANTS_DIR);
+        if tenants_dir.is_dir() {
+            let mut tenants: Vec<_> = fs::read_dir(&tenants_dir)?
+                .filter_map(|entry| entry.ok())
+                .filter(|entry| entry.path().is_dir())
+                .filter_map(|entry| entry.file_name().to_str().map(str::to_string))
+                .collect();
+            tenants.sort();
+            for tenant in tenants {
+                roots.push(audit_root(&tenants_dir.join(&tenant), Some(tenant), rules, now)?);
+            }
+        }
+
+        let projects = || roots.iter().flat_map(|root| root.projects.iter());
+        Ok(Self {
+            path: base_dir.to_path_buf(),
+            total_projects: projects().count(),
+            total_snapshots: projects().filter(|p| p.has_snapshot).count(),
+            total_bytes: roots.iter().map(|root| root.bytes).sum(),
+            prunable_projects: projects().filter(|p| p.prune_reason.is_some()).count(),
+            prunable_bytes: projects().filter(|p| p.prune_reason.is_some()).map(|p| p.bytes).sum(),
+            roots,
+        })
+    }
+
+    /// 删除标记为可清理的项目（目录和登记记录），返回删除的项目数
+    pub fn prune(&self) -> io::Result<usize> {
+        let mut pruned = 0;
+        for root in &self.roots {
+            let persistence = PersistenceManager::with_base_dir(root.path.clone(), StorageMode::Json);
+            for project in root.projects.iter().filter(|p| p.prune_reason.is_some()) {
+                persistence.delete_project(&project.project_id)?;
+                pruned += 1;
+            }
+        }
+        Ok(pruned)
+    }
+}
+
+fn audit_root(dir: &Path, tenant: Option<String>, rules: &RetentionRules, now: DateTime<Utc>) -> io::Result<StorageRootUsage> {
+    let persistence = PersistenceManager::with_base_dir(dir.to_path_buf(), StorageMode::Json);
+    let mut project_ids = persistence.list_projects()?;
+    project_ids.sort();
+
+    let mut projects = vec![];
+    for project_id in project_ids {
+        let project_path = dir.join(&project_id);
+        let (files, bytes, modified) = dir_usage(&project_path)?;
+        let record = persistence.get_project_record(&project_id).ok().flatten();
+        let snapshot = persistence.load_snapshot(&project_id).ok().flatten();
+        let last_built = snapshot
+            .as_ref()
+            .map(|s| s.built_at)
+            .or(record.as_ref().map(|r| r.parsed_at))
+            .or(modified);
+        let project_dir = record.map(|r| r.project_dir);
+        projects.push(ProjectUsage {
+            source_exists: project_dir.as_ref().map(|d| Path::new(d).is_dir()),
+            project_dir,
+            last_built,
+            commit: snapshot.as_ref().and_then(|s| s.commit.clone()),
+            has_snapshot: snapshot.is_some(),
+            graph_files: ["graph.json", "graph.bin"].iter().filter(|f| project_path.join(f).is_file()).count(),
+            files,
+            bytes,
+            prune_reason: None,
+            project_id,
+        });
+    }
+    apply_rules(&mut projects, rules, now);
+
+    let skeletons = dir_usage(&dir.join(SKELETONS_DIR))?;
+    let (_, total_bytes, _) = dir_usage(dir)?;
+    let tenants_bytes = if tenant.is_none() { dir_usage(&dir.join(TENANTS_DIR))?.1 } else { 0 };
+    let bytes = total_bytes - tenants_bytes;
+    let project_bytes: u64 = projects.iter().map(|p| p.bytes).sum();
+    Ok(StorageRootUsage {
+        tenant,
+        path: dir.to_path_buf(),
+        skeleton_entries: skeletons.0,
+        skeleton_bytes: skeletons.1,
+        other_bytes: bytes.saturating_sub(project_bytes + skeletons.1),
+        bytes,
+        projects,
+    })
+}
+
+/// 按规则标记项目，每个项目只记录第一条命中的规则
+fn apply_rules(projects: &mut [ProjectUsage], rules: &RetentionRules, now: DateTime<Utc>) {
+    if rules.missing_source {
+        for project in projects.iter_mut().filter(|p| p.source_exists == Some(false)) {
+            project.prune_reason = Some("source directory no longer exists".to_string());
+        }
+    }
+    if let Some(max_age) = rules.max_age.and_then(|age| chrono::Duration::from_std(age).ok()) {
+        for project in projects.iter_mut().filter(|p| p.prune_reason.is_none()) {
+            if let Some(age) = project.last_built.map(|built| now - built).filter(|age| *age > max_age) {
+                project.prune_reason = Some(format!("last built {} days ago", age.num_days()));
+            }
+        }
+    }
+    if let Some(keep) = rules.keep_last {
+        let mut newest: Vec<usize> = (0..projects.len()).collect();
+        newest.sort_by_key(|&i| std::cmp::Reverse(projects[i].last_built));
+        for &i in newest.iter().skip(keep) {
+            if projects[i].prune_reason.is_none() {
+                projects[i].prune_reason = Some(format!("not among the {} most recent builds", keep));
+            }
+        }
+    }
+}
+
+/// 目录下的文件数、总字节数和最新修改时间；目录不存在时为零
+fn dir_usage(dir: &Path) -> io::Result<(usize, u64, Option<DateTime<Utc>>)> {
+    let (mut files, mut bytes, mut modified) = (0, 0, None::<DateTime<Utc>>);
+    if !dir.is_dir() {
+        return Ok((files, bytes, modified));
+    }
+    let mut pending = vec![dir.to_path_buf()];
+    while let Some(dir) = pending.pop() {
+        for entry in fs::read_dir(&dir)? {
+            let entry = entry?;
+            let metadata = entry.metadata()?;
+            if metadata.is_dir() {
+                pending.push(entry.path());
+            } else {
+                files += 1;
+                bytes += metadata.len();
+                if let Ok(time) = metadata.modified() {
+                    let time = DateTime::<Utc>::from(time);
+                    modified = Some(modified.map_or(time, |m| m.max(time)));
+                }
+            }
+        }
+    }
+    Ok((files, bytes, modified))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::GraphSnapshot;
+
+    #[test]
+    fn test_retention_rules_mark_and_prune_projects() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let base = temp_dir.path();
+        let persistence = PersistenceManager::with_base_dir(base.to_path_buf(), StorageMode::Json);
+        let now = Utc::now();
+        let source = tempfile::tempdir().unwrap();
+        for (project_id, age_days) in [("fresh", 1), ("stale", 90), ("older", 40)] {
+            let snapshot = GraphSnapshot {
+                commit: Some(format!("{}-sha", project_id)),
+                dirty: false,
+                built_at: now - chrono::Duration::days(age_days),
+                analyzer_version: "test".to_string(),
+                pull_request: None,
+                base_commit: None,
+            };
+            persistence.save_snapshot(project_id, &snapshot).unwrap();
+        }
+        persistence.register_project("fresh", &source.path().display().to_string()).unwrap();
+        persistence.register_project("older", &base.join("deleted").display().to_string()).unwrap();
+        let tenant = PersistenceManager::with_base_dir(base.join(TENANTS_DIR).join("acme"), StorageMode::Json);
+        tenant.save_file_hash("tenant-project", "a.rs", "0cc175b9").unwrap();
+
+        let rules = RetentionRules { max_age: Some(Duration::from_secs(60 * 86400)), keep_last: Some(2), missing_source: true };
+        let audit = StorageAudit::collect(base, &rules, now).unwrap();
+        assert_eq!((audit.total_projects, audit.total_snapshots), (4, 3));
+        assert_eq!(audit.roots[1].tenant.as_deref(), Some("acme"));
+        let reasons: Vec<_> = audit.roots[0].projects.iter().map(|p| (p.project_id.as_str(), p.prune_reason.is_some())).collect();
+        assert_eq!(reasons, vec![("fresh", false), ("older", true), ("stale", true)]);
+        assert_eq!(audit.roots[0].projects[1].prune_reason.as_deref(), Some("source directory no longer exists"));
+        assert!(audit.total_bytes >= audit.prunable_bytes && audit.prunable_bytes > 0);
+
+        assert_eq!(audit.prune().unwrap(), 2);
+        assert_eq!(persistence.list_projects().unwrap(), vec!["fresh".to_string()]);
+        assert!(!persistence.is_project_parsed("older").unwrap());
+    }
+}
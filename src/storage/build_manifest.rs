@@ -0,0 +1,285 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::Path;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::codegraph::options::{AnalyzeOptions, IGNORED_DIRECTORIES};
+use crate::codegraph::parser::CodeParser;
+use crate::codegraph::treesitter::parsers::get_tree_sitter_language;
+use crate::codegraph::treesitter::{AnalyzerManifest, AnalyzerRegistry, LanguageId};
+use crate::storage::snapshot::GraphSnapshot;
+
+/// 带 tree-sitter 语法的语言，其余语言由无语法的扫描器分析，随分析器版本变化
+const GRAMMAR_LANGUAGES: [LanguageId; 12] = [
+    LanguageId::Rust,
+    LanguageId::Python,
+    LanguageId::Java,
+    LanguageId::Cpp,
+    LanguageId::TypeScript,
+    LanguageId::TypeScriptReact,
+    LanguageId::JavaScript,
+    LanguageId::Go,
+    LanguageId::Kotlin,
+    LanguageId::Ruby,
+    LanguageId::Swift,
+    LanguageId::Scala,
+];
+
+/// 语法的标识。语法 crate 的版本在运行时不可得，节点和字段数量随语法更新而变化，用作指纹
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GrammarInfo {
+    pub abi_version: usize,
+    /// 语法生成时写入的版本号，旧语法没有
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+    pub node_kinds: usize,
+    pub fields: usize,
+}
+
+impl GrammarInfo {
+    pub fn of(grammar: &tree_sitter::Language) -> Self {
+        Self {
+            abi_version: grammar.abi_version(),
+            version: grammar
+                .metadata()
+                .map(|m| format!("{}.{}.{}", m.major_version, m.minor_version, m.patch_version)),
+            node_kinds: grammar.node_kind_count(),
+            fields: grammar.field_count(),
+        }
+    }
+}
+
+/// 影响分析结果的构建选项
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ManifestConfig {
+    pub languages: Vec<String>,
+    pub exclude_paths: Vec<String>,
+    pub strict: bool,
+    /// 未开启时不写入，开启前保存的清单哈希保持不变
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub expand_macros: bool,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub heuristic_fallback: bool,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub framework_entry_points: bool,
+}
+
+/// 一次构建的全部输入：分析器和语法版本、选项、忽略规则以及每个输入文件的哈希。
+/// 同一提交的两次构建结果不同时，比较两者的清单即可找到原因。
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BuildManifest {
+    pub analyzer_version: String,
+    pub commit: Option<String>,
+    #[serde(default)]
+    pub dirty: bool,
+    pub built_at: DateTime<Utc>,
+    /// 链接的 tree-sitter 库支持的最高 ABI 版本
+    pub tree_sitter_abi: usize,
+    /// 语言名 -> 语法信息
+    pub grammars: BTreeMap<String, GrammarInfo>,
+    /// 运行时注册的外部分析器
+    #[serde(default)]
+    pub external_analyzers: Vec<AnalyzerManifest>,
+    pub config: ManifestConfig,
+    /// `config` 的 SHA-256，便于快速比较
+    pub config_hash: String,
+    pub ignore_rules: Vec<String>,
+    /// 相对项目目录的路径 -> 内容 MD5（与增量构建使用的哈希一致）
+    pub files: BTreeMap<String, String>,
+}
+
+impl BuildManifest {
+    /// 记录以 `options` 分析 `project_dir` 时的输入
+    pub fn capture(project_dir: &Path, options: &AnalyzeOptions, snapshot: &GraphSnapshot) -> Self {
+        let mut config = ManifestConfig {
+            languages: options.languages.clone(),
+            exclude_paths: options.exclude_paths.clone(),
+            strict: options.strict,
+            expand_macros: options.expand_macros,
+            heuristic_fallback: options.heuristic_fallback,
+            framework_entry_points: options.framework_entry_points,
+        };
+        config.languages.sort();
+        config.languages.dedup();
+        config.exclude_paths.sort();
+        config.exclude_paths.dedup();
+        let config_hash = hex::encode(Sha256::digest(serde_json::to_vec(&config).unwrap_or_default()));
+
+        let mut ignore_rules = vec![".*/".to_string()];
+        ignore_rules.extend(IGNORED_DIRECTORIES.iter().map(|dir| format!("{}/", dir)));
+        ignore_rules.extend(config.exclude_paths.iter().cloned());
+
+        let grammars = GRAMMAR_LANGUAGES
+            .iter()
+            .filter_map(|&language| Some((language.to_string(), GrammarInfo::of(&get_tree_sitter_language(language)?))))
+            .collect();
+
+        // 按与构建相同的规则重新扫描，已删除的文件不会残留在清单中
+        let scan_options = AnalyzeOptions { storage_dir: None, ..options.clone() };
+        let files = CodeParser::with_options(scan_options)
+            .scan_directory(project_dir)
+            .into_iter()
+            .filter_map(|path| {
+                let content = std::fs::read(&path).ok()?;
+                let relative = path.strip_prefix(project_dir).unwrap_or(&path);
+                Some((relative.to_string_lossy().replace('\\', "/"), format!("{:x}", md5::compute(&content))))
+            })
+            .collect();
+
+        Self {
+            analyzer_version: snapshot.analyzer_version.clone(),
+            commit: snapshot.commit.clone(),
+            dirty: snapshot.dirty,
+            built_at: snapshot.built_at,
+            tree_sitter_abi: tree_sitter::LANGUAGE_VERSION,
+            grammars,
+            external_analyzers: AnalyzerRegistry::registered(),
+            config,
+            config_hash,
+            ignore_rules,
+            files,
+        }
+    }
+
+    /// `self` 为基准，`head` 为对比的构建；构建时间不参与比较
+    pub fn diff(&self, head: &BuildManifest) -> ManifestDiff {
+        let mut grammars = vec![];
+        let languages: BTreeSet<&String> = self.grammars.keys().chain(head.grammars.keys()).collect();
+        for language in languages {
+            let (base, head) = (self.grammars.get(language), head.grammars.get(language));
+            if base != head {
+                grammars.push(GrammarChange { language: language.clone(), base: base.cloned(), head: head.cloned() });
+            }
+        }
+
+        let base_rules: BTreeSet<&String> = self.ignore_rules.iter().collect();
+        let head_rules: BTreeSet<&String> = head.ignore_rules.iter().collect();
+        let mut files_added = vec![];
+        let mut files_changed = vec![];
+        for (path, hash) in &head.files {
+            match self.files.get(path) {
+                None => files_added.push(path.clone()),
+                Some(base) if base != hash => files_changed.push(path.clone()),
+                Some(_) => {}
+            }
+        }
+
+        let mut diff = ManifestDiff {
+            identical: false,
+            analyzer_version: Change::of(&self.analyzer_version, &head.analyzer_version),
+            commit: Change::of(&self.commit, &head.commit),
+            dirty: Change::of(&self.dirty, &head.dirty),
+            tree_sitter_abi: Change::of(&self.tree_sitter_abi, &head.tree_sitter_abi),
+            grammars,
+            external_analyzers: Change::of(&self.external_analyzers, &head.external_analyzers),
+            config: Change::of(&self.config, &head.config),
+            ignore_rules_added: head_rules.difference(&base_rules).map(|r| r.to_string()).collect(),
+            ignore_rules_removed: base_rules.difference(&head_rules).map(|r| r.to_string()).collect(),
+            files_added,
+            files_removed: self.files.keys().filter(|path| !head.files.contains_key(*path)).cloned().collect(),
+            files_changed,
+        };
+        diff.identical = diff.analyzer_version.is_none()
+            && diff.commit.is_none()
+            && diff.dirty.is_none()
+            && diff.tree_sitter_abi.is_none()
+            && diff.grammars.is_empty()
+            && diff.external_analyzers.is_none()
+            && diff.config.is_none()
+            && diff.ignore_rules_added.is_empty()
+            && diff.ignore_rules_removed.is_empty()
+            && diff.files_added.is_empty()
+            && diff.files_removed.is_empty()
+            && diff.files_changed.is_empty();
+        diff
+    }
+}
+
+/// 一项在两份清单中的不同取值
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Change<T> {
+    pub base: T,
+    pub head: T,
+}
+
+impl<T: Clone + PartialEq> Change<T> {
+    fn of(base: &T, head: &T) -> Option<Self> {
+        (base != head).then(|| Self { base: base.clone(), head: head.clone() })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct GrammarChange {
+    pub language: String,
+    pub base: Option<GrammarInfo>,
+    pub head: Option<GrammarInfo>,
+}
+
+/// 两份构建清单的差异；只列出不同的部分
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ManifestDiff {
+    pub identical: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub analyzer_version: Option<Change<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub commit: Option<Change<Option<String>>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dirty: Option<Change<bool>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tree_sitter_abi: Option<Change<usize>>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub grammars: Vec<GrammarChange>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub external_analyzers: Option<Change<Vec<AnalyzerManifest>>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub config: Option<Change<ManifestConfig>>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub ignore_rules_added: Vec<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub ignore_rules_removed: Vec<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub files_added: Vec<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub files_removed: Vec<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub files_changed: Vec<String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn test_manifest_diff_explains_differing_builds() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let root = temp_dir.path();
+        fs::create_dir_all(root.join("src")).unwrap();
+        fs::create_dir_all(root.join("node_modules/dep")).unwrap();
+        fs::write(root.join("src/main.rs"), "fn main() { helper(); }\n").unwrap();
+        fs::write(root.join("src/helper.rs"), "pub fn helper() {}\n").unwrap();
+        fs::write(root.join("node_modules/dep/index.js"), "function dep() {}\n").unwrap();
+        let snapshot = GraphSnapshot::capture(root);
+
+        let base = BuildManifest::capture(root, &AnalyzeOptions::default(), &snapshot);
+        assert_eq!(base.files.keys().collect::<Vec<_>>(), vec!["src/helper.rs", "src/main.rs"]);
+        assert!(base.grammars.contains_key("rust"));
+        assert!(base.diff(&base.clone()).identical);
+
+        // 同一提交、不同选项和工作区内容
+        fs::write(root.join("src/helper.rs"), "pub fn helper() { log(); }\n").unwrap();
+        fs::write(root.join("src/extra.rs"), "fn extra() {}\n").unwrap();
+        let options = AnalyzeOptions { exclude_paths: vec!["src/main.rs".to_string()], ..Default::default() };
+        let head = BuildManifest::capture(root, &options, &GraphSnapshot { built_at: Utc::now(), ..snapshot });
+        let diff = base.diff(&head);
+        assert!(!diff.identical);
+        assert_eq!(diff.files_added, vec!["src/extra.rs"]);
+        assert_eq!(diff.files_removed, vec!["src/main.rs"]);
+        assert_eq!(diff.files_changed, vec!["src/helper.rs"]);
+        assert_eq!(diff.ignore_rules_added, vec!["src/main.rs"]);
+        assert_ne!(base.config_hash, head.config_hash);
+        assert!(diff.config.is_some() && diff.grammars.is_empty() && diff.analyzer_version.is_none());
+    }
+}
@@ -0,0 +1,347 @@
+use std::collections::HashSet;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use serde::Serialize;
+
+use crate::cli::args::StorageMode;
+use crate::codegraph::types::PetCodeGraph;
+use crate::storage::persistence::{PersistenceManager, TENANTS_DIR};
+use crate::storage::petgraph_storage::PetGraphStorageManager;
+use crate::storage::skeleton_cache::{CachedSkeleton, SKELETONS_DIR};
+
+/// 单个项目的压缩结果
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ProjectCompaction {
+    pub project_id: String,
+    /// 重写的图文件数（JSON 和/或二进制）
+    pub graph_files: usize,
+    /// 名称/文件索引中指向已删除函数的条目
+    pub stale_index_entries: usize,
+    /// 已删除函数的嵌入源码
+    pub orphaned_bodies: usize,
+    /// 不再匹配任何函数的注解
+    pub orphaned_annotations: usize,
+    /// 已不在构建中的文件的解析统计
+    pub orphaned_parse_stats: usize,
+    pub bytes_before: u64,
+    pub bytes_after: u64,
+}
+
+/// 一个存储根目录（默认存储或某个租户）的压缩结果
+#[derive(Debug, Clone, Serialize)]
+pub struct RootCompaction {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tenant: Option<String>,
+    pub path: PathBuf,
+    pub projects: Vec<ProjectCompaction>,
+    /// 源文件已不存在或无法读取的骨架缓存项
+    pub orphaned_skeletons: usize,
+    pub skeleton_bytes_reclaimed: u64,
+}
+
+/// 整个存储目录的压缩结果
+#[derive(Debug, Clone, Serialize)]
+pub struct CompactionReport {
+    pub path: PathBuf,
+    pub roots: Vec<RootCompaction>,
+    /// 项目目录压缩前后的总大小
+    pub project_bytes_before: u64,
+    pub project_bytes_after: u64,
+    /// 项目目录和骨架缓存共释放的字节数
+    pub reclaimed_bytes: u64,
+}
+
+/// 重写 `base_dir` 及其下各租户目录中的项目：按图中现存的函数重建图的索引，
+/// 删除已删除函数和文件留下的源码、注解、解析统计和骨架缓存项
+pub fn compact_storage(base_dir: &Path) -> io::Result<CompactionReport> {
+    compact_storage_locked(base_dir, |_, _| ())
+}
+
+/// 同 [`compact_storage`]，压缩每个项目期间持有 `lock_project(租户, 项目ID)` 返回的锁，
+/// 避免与同一项目的构建同时写入
+pub fn compact_storage_locked<G>(
+    base_dir: &Path,
+    lock_project: impl Fn(Option<&str>, &str) -> G,
+) -> io::Result<CompactionReport> {
+    let mut roots = vec![compact_root(base_dir, None, &lock_project)?];
+    let tenants_dir = base_dir.join(TENANTS_DIR);
+    if tenants_dir.is_dir() {
+        let mut tenants: Vec<_> = fs::read_dir(&tenants_dir)?
+            .filter_map(|entry| entry.ok())
+            .filter(|entry| entry.path().is_dir())
+            .filter_map(|entry| entry.file_name().to_str().map(str::to_string))
+            .collect();
+        tenants.sort();
+        for tenant in tenants {
+            roots.push(compact_root(&tenants_dir.join(&tenant), Some(tenant), &lock_project)?);
+        }
+    }
+
+    let projects = || roots.iter().flat_map(|root| root.projects.iter());
+    let project_bytes_before: u64 = projects().map(|p| p.bytes_before).sum();
+    let project_bytes_after: u64 = projects().map(|p| p.bytes_after).sum();
+    let skeleton_bytes: u64 = roots.iter().map(|root| root.skeleton_bytes_reclaimed).sum();
+    Ok(CompactionReport {
+        path: base_dir.to_path_buf(),
+        reclaimed_bytes: project_bytes_before.saturating_sub(project_bytes_after) + skeleton_bytes,
+        project_bytes_before,
+        project_bytes_after,
+        roots,
+    })
+}
+
+fn compact_root<G>(
+    dir: &Path,
+    tenant: Option<String>,
+    lock_project: &impl Fn(Option<&str>, &str) -> G,
+) -> io::Result<RootCompaction> {
+    let persistence = PersistenceManager::with_base_dir(dir.to_path_buf(), StorageMode::Json);
+    let mut project_ids = persistence.list_projects()?;
+    project_ids.sort();
+    let mut projects = vec![];
+    for project_id in project_ids {
+        let _guard = lock_project(tenant.as_deref(), &project_id);
+        projects.push(compact_project(dir, &project_id)?);
+    }
+    let (orphaned_skeletons, skeleton_bytes_reclaimed) = compact_skeletons(&dir.join(SKELETONS_DIR))?;
+    Ok(RootCompaction { tenant, path: dir.to_path_buf(), projects, orphaned_skeletons, skeleton_bytes_reclaimed })
+}
+
+fn compact_project(dir: &Path, project_id: &str) -> io::Result<ProjectCompaction> {
+    let project_dir = dir.join(project_id);
+    let mut result = ProjectCompaction {
+        project_id: project_id.to_string(),
+        bytes_before: dir_bytes(&project_dir)?,
+        ..Default::default()
+    };
+
+    // 两种格式都保存时以二进制为准，与 `StorageMode::Both` 的加载顺序一致
+    let json_file = project_dir.join("graph.json");
+    let binary_file = project_dir.join("graph.bin");
+    let stored = if binary_file.is_file() {
+        PetGraphStorageManager::load_from_binary(&binary_file)
+    } else if json_file.is_file() {
+        PetGraphStorageManager::load_from_file(&json_file)
+    } else {
+        Err(String::new())
+    };
+    let graph = match stored {
+        Ok(stored) => {
+            let (graph, stale) = rebuild(&stored);
+            result.stale_index_entries = stale;
+            for file in [&json_file, &binary_file].into_iter().filter(|file| file.is_file()) {
+                let written = if file == &binary_file {
+                    PetGraphStorageManager::save_to_binary(&graph, file)
+                } else {
+                    PetGraphStorageManager::save_to_file(&graph, file)
+                };
+                written.map_err(io::Error::other)?;
+                result.graph_files += 1;
+            }
+            Some(graph)
+        }
+        Err(e) => {
+            if !e.is_empty() {
+                tracing::warn!("Skipping graph of {} during compaction: {}", project_id, e);
+            }
+            None
+        }
+    };
+
+    let persistence = PersistenceManager::with_base_dir(dir.to_path_buf(), StorageMode::Json);
+    if let Some(graph) = &graph {
+        if let Some(mut bodies) = persistence.load_function_bodies(project_id)? {
+            let before = bodies.len();
+            bodies.bodies.retain(|id, _| graph.get_function_by_id(id).is_some());
+            result.orphaned_bodies = before - bodies.len();
+            persistence.save_function_bodies(project_id, &bodies)?;
+        }
+        if project_dir.join("annotations.json").is_file() {
+            let mut annotations = persistence.load_annotations(project_id)?;
+            let (_, unmatched) = annotations.resolve(graph);
+            if !unmatched.is_empty() {
+                result.orphaned_annotations = unmatched.len();
+                annotations.nodes.retain(|name, _| !unmatched.contains(name));
+                persistence.save_annotations(project_id, &annotations)?;
+            }
+        }
+    }
+
+    let hashes = persistence.load_file_hashes(project_id)?;
+    if !hashes.is_empty() && project_dir.join("parse_stats.json").is_file() {
+        let mut stats = persistence.load_parse_stats(project_id)?;
+        let before = stats.len();
+        stats.retain(|path, _| hashes.contains_key(path));
+        if stats.len() < before {
+            result.orphaned_parse_stats = before - stats.len();
+            persistence.save_parse_stats(project_id, &stats)?;
+        }
+    }
+
+    result.bytes_after = dir_bytes(&project_dir)?;
+    Ok(result)
+}
+
+/// 只保留图中现存的函数及它们之间的调用，并重新生成名称/文件索引；
+/// 返回新图和旧索引中失效的条目数
+fn rebuild(stored: &PetCodeGraph) -> (PetCodeGraph, usize) {
+    let mut graph = PetCodeGraph::new();
+    for function in stored.get_all_functions() {
+        graph.add_function(function.clone());
+    }
+    for relation in stored.get_all_call_relations() {
+        if let Err(e) = graph.add_call_relation(relation.clone()) {
+            tracing::warn!("Dropping call relation during compaction: {}", e);
+        }
+    }
+    graph.update_stats();
+
+    let live: HashSet<_> = stored.get_all_functions().iter().map(|function| function.id).collect();
+    let stale = stored
+        .function_names
+        .values()
+        .chain(stored.file_functions.values())
+        .flatten()
+        .filter(|id| !live.contains(id))
+        .count();
+    (graph, stale)
+}
+
+/// 删除源文件已不存在或内容无法解析的骨架缓存项，返回 (删除数, 释放的字节数)
+fn compact_skeletons(dir: &Path) -> io::Result<(usize, u64)> {
+    let (mut removed, mut bytes) = (0, 0);
+    if !dir.is_dir() {
+        return Ok((removed, bytes));
+    }
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        let path = entry.path();
+        if !path.is_file() {
+            continue;
+        }
+        let source_exists = fs::read_to_string(&path)
+            .ok()
+            .and_then(|content| serde_json::from_str::<CachedSkeleton>(&content).ok())
+            .is_some_and(|skeleton| skeleton.path.is_file());
+        if !source_exists {
+            bytes += entry.metadata()?.len();
+            fs::remove_file(&path)?;
+            removed += 1;
+        }
+    }
+    Ok((removed, bytes))
+}
+
+fn dir_bytes(dir: &Path) -> io::Result<u64> {
+    let mut bytes = 0;
+    if !dir.is_dir() {
+        return Ok(bytes);
+    }
+    for entry in fs::read_dir(dir)? {
+        let metadata = entry?.metadata()?;
+        if metadata.is_file() {
+            bytes += metadata.len();
+        }
+    }
+    Ok(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::codegraph::types::{CallRelation, FileParseStats, FunctionInfo};
+    use crate::storage::function_bodies::{EmbeddedBody, FunctionBodies};
+    use std::collections::{BTreeMap, HashMap};
+    use uuid::Uuid;
+
+    fn function(name: &str) -> FunctionInfo {
+        FunctionInfo {
+            id: Uuid::new_v4(),
+            name: name.to_string(),
+            file_path: PathBuf::from(format!("src/{}.rs", name)),
+            line_start: 1,
+            line_end: 3,
+            namespace: name.to_string(),
+            language: "rust".to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_compaction_drops_entries_of_deleted_functions() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let base = temp_dir.path();
+        let persistence = PersistenceManager::with_base_dir(base.to_path_buf(), StorageMode::Both);
+
+        let (main, helper, deleted) = (function("main"), function("helper"), function("deleted"));
+        let mut graph = PetCodeGraph::new();
+        for f in [&main, &helper] {
+            graph.add_function(f.clone());
+        }
+        graph
+            .add_call_relation(CallRelation { line_number: 2, ..CallRelation::between(&main, &helper) })
+            .unwrap();
+        // 增量更新后残留的索引条目
+        graph.function_names.entry(deleted.name.clone()).or_default().push(deleted.id);
+        graph.file_functions.entry(deleted.file_path.clone()).or_default().push(deleted.id);
+        persistence.save_graph("project", &graph).unwrap();
+
+        let body = |text: &str| EmbeddedBody { first_line: 0, text: text.to_string() };
+        let bodies = FunctionBodies {
+            bodies: HashMap::from([(main.id, body("fn main() {}")), (deleted.id, body("fn deleted() {}"))]),
+        };
+        persistence.save_function_bodies("project", &bodies).unwrap();
+        let mut annotations = crate::storage::NodeAnnotations::default();
+        annotations.merge(BTreeMap::from([
+            ("src/main.rs::main".to_string(), BTreeMap::from([("cpu_samples".to_string(), 3.0)])),
+            ("src/deleted.rs::deleted".to_string(), BTreeMap::from([("cpu_samples".to_string(), 1.0)])),
+        ]));
+        persistence.save_annotations("project", &annotations).unwrap();
+        persistence.save_file_hash("project", "src/main.rs", "0cc175b9").unwrap();
+        let stats = |path: &str| {
+            let stats = FileParseStats { language: "rust".to_string(), duration_ms: 1.0, node_count: Some(10), error_nodes: Some(0), parse_error: None };
+            (path.to_string(), stats)
+        };
+        persistence
+            .save_parse_stats("project", &HashMap::from([stats("src/main.rs"), stats("src/deleted.rs")]))
+            .unwrap();
+
+        let skeletons = base.join(SKELETONS_DIR);
+        fs::create_dir_all(&skeletons).unwrap();
+        let missing = CachedSkeleton {
+            path: base.join("gone.rs"),
+            content_hash: "x".to_string(),
+            language: "rust".to_string(),
+            skeleton_text: "fn gone()".to_string(),
+        };
+        fs::write(skeletons.join("gone.json"), serde_json::to_string(&missing).unwrap()).unwrap();
+
+        let report = compact_storage(base).unwrap();
+        let project = &report.roots[0].projects[0];
+        assert_eq!(project.graph_files, 2);
+        assert_eq!(project.stale_index_entries, 2);
+        assert_eq!((project.orphaned_bodies, project.orphaned_annotations, project.orphaned_parse_stats), (1, 1, 1));
+        assert_eq!(report.roots[0].orphaned_skeletons, 1);
+        assert!(report.reclaimed_bytes > 0);
+
+        let compacted = persistence.load_graph("project").unwrap().unwrap();
+        assert!(compacted.validate().is_empty());
+        assert_eq!(compacted.get_callees(&main.id).len(), 1);
+        assert!(!compacted.function_names.contains_key("deleted"));
+        assert_eq!(persistence.load_function_bodies("project").unwrap().unwrap().len(), 1);
+        assert_eq!(persistence.load_parse_stats("project").unwrap().len(), 1);
+        assert!(!skeletons.join("gone.json").exists());
+
+        // 再次压缩不再有可清理的内容；每个项目压缩时都持有该项目的锁
+        let locked = std::cell::RefCell::new(Vec::new());
+        let again = compact_storage_locked(base, |tenant, project_id| {
+            locked.borrow_mut().push((tenant.map(str::to_string), project_id.to_string()));
+        })
+        .unwrap();
+        assert_eq!(locked.into_inner(), vec![(None, "project".to_string())]);
+        assert_eq!(again.roots[0].projects[0].stale_index_entries, 0);
+        assert_eq!(again.roots[0].projects[0].orphaned_bodies, 0);
+    }
+}
@@ -0,0 +1,129 @@
+use std::collections::HashMap;
+use std::io::{Read, Write};
+use std::path::PathBuf;
+
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::codegraph::types::PetCodeGraph;
+use crate::codegraph::NotebookCells;
+
+/// 函数体前后额外保存的行数，片段查询的上下文不会超过这个范围
+pub const EMBEDDED_CONTEXT_LINES: usize = 10;
+
+/// 构建时保存的一段源码，`first_line` 为其第一行在文件（或笔记本单元）中的下标
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EmbeddedBody {
+    pub first_line: usize,
+    pub text: String,
+}
+
+impl EmbeddedBody {
+    /// 按文件行号排列的行，保存范围之前的行为空，便于与读取磁盘文件时使用相同的下标
+    pub fn lines(&self) -> Vec<&str> {
+        let mut lines = vec![""; self.first_line];
+        lines.extend(self.text.lines());
+        lines
+    }
+}
+
+/// 与图一起保存的函数源码（函数ID -> 函数体及其上下文），
+/// 使片段查询返回的是被分析的那个版本，而不是工作区中已改动的文件
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct FunctionBodies {
+    #[serde(default)]
+    pub bodies: HashMap<Uuid, EmbeddedBody>,
+}
+
+impl FunctionBodies {
+    /// 从磁盘读取图中每个函数的源码，应在分析后立即调用；读取失败的文件跳过
+    pub fn capture(graph: &PetCodeGraph) -> Self {
+        let mut files: HashMap<PathBuf, Option<String>> = HashMap::new();
+        let mut bodies = HashMap::new();
+        for function in graph.get_all_functions() {
+            let Some(contents) = files
+                .entry(function.file_path.clone())
+                .or_insert_with(|| std::fs::read_to_string(&function.file_path).ok())
+            else {
+                continue;
+            };
+            // 笔记本中的函数按单元编号
+            let source = match function.cell {
+                Some(cell) => match NotebookCells::parse(contents).ok().and_then(|cells| cells.cell_source(cell).map(str::to_string)) {
+                    Some(source) => source,
+                    None => continue,
+                },
+                None => contents.clone(),
+            };
+            let lines: Vec<&str> = source.lines().collect();
+            let first_line = function.line_start.saturating_sub(EMBEDDED_CONTEXT_LINES).min(lines.len());
+            let last_line = (function.line_end + EMBEDDED_CONTEXT_LINES).min(lines.len()).max(first_line);
+            bodies.insert(function.id, EmbeddedBody { first_line, text: lines[first_line..last_line].join("\n") });
+        }
+        Self { bodies }
+    }
+
+    pub fn get(&self, function_id: &Uuid) -> Option<&EmbeddedBody> {
+        self.bodies.get(function_id)
+    }
+
+    pub fn len(&self) -> usize {
+        self.bodies.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.bodies.is_empty()
+    }
+
+    /// gzip 压缩的 JSON
+    pub fn to_compressed(&self) -> std::io::Result<Vec<u8>> {
+        let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+        encoder.write_all(&serde_json::to_vec(self)?)?;
+        encoder.finish()
+    }
+
+    pub fn from_compressed(data: &[u8]) -> std::io::Result<Self> {
+        let mut json = Vec::new();
+        flate2::read::GzDecoder::new(data).read_to_end(&mut json)?;
+        serde_json::from_slice(&json).map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::codegraph::types::FunctionInfo;
+
+    #[test]
+    fn test_capture_keeps_analyzed_revision() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("lib.rs");
+        let source: Vec<String> = (0..30).map(|i| format!("// line {}", i)).collect();
+        std::fs::write(&file, source.join("\n")).unwrap();
+
+        let mut graph = PetCodeGraph::new();
+        let function = FunctionInfo {
+            id: Uuid::new_v4(),
+            name: "run".to_string(),
+            file_path: file.clone(),
+            line_start: 12,
+            line_end: 15,
+            namespace: "lib".to_string(),
+            language: "rust".to_string(),
+            ..Default::default()
+        };
+        graph.add_function(function.clone());
+
+        let bodies = FunctionBodies::capture(&graph);
+        std::fs::write(&file, "fn changed() {}").unwrap();
+
+        let restored = FunctionBodies::from_compressed(&bodies.to_compressed().unwrap()).unwrap();
+        assert_eq!(restored, bodies);
+        let body = restored.get(&function.id).unwrap();
+        assert_eq!(body.first_line, 2);
+        let lines = body.lines();
+        assert_eq!(lines.len(), 25);
+        assert_eq!(lines[12..15].join("\n"), "// line 12\n// line 13\n// line 14");
+        assert_eq!(lines[0], "");
+    }
+}
@@ -9,7 +9,7 @@ use chrono::Utc;
 
 use crate::codegraph::types::{
     FileMetadata, FileIndex, SnippetIndex, EntityGraph, PetCodeGraph,
-    FunctionInfo, ClassInfo, CallRelation
+    FunctionInfo, FunctionKind, ClassInfo, CallRelation, CallKind
 };
 use crate::codegraph::treesitter::TreeSitterParser;
 
@@ -23,6 +23,8 @@ pub struct IncrementalManager {
     snippet_index: SnippetIndex,
     /// TreeSitter解析器
     ts_parser: TreeSitterParser,
+    /// 更新后图不一致时返回错误
+    strict: bool,
 }
 
 impl IncrementalManager {
@@ -32,9 +34,16 @@ impl IncrementalManager {
             file_index: FileIndex::default(),
             snippet_index: SnippetIndex::default(),
             ts_parser: TreeSitterParser::new(),
+            strict: false,
         }
     }
 
+    /// 启用严格模式：每次更新后校验图的一致性，发现问题时返回错误
+    pub fn with_strict(mut self, strict: bool) -> Self {
+        self.strict = strict;
+        self
+    }
+
     /// 计算文件的MD5哈希值
     pub fn compute_file_md5(&self, file_path: &Path) -> Result<String, io::Error> {
         let content = fs::read(file_path)?;
@@ -54,6 +63,15 @@ impl IncrementalManager {
         }
     }
 
+    /// 比较两次构建保存的文件哈希，返回内容发生变化的文件
+    pub fn changed_files(before: &HashMap<String, String>, after: &HashMap<String, String>) -> Vec<PathBuf> {
+        after
+            .iter()
+            .filter(|(path, hash)| before.get(*path) != Some(*hash))
+            .map(|(path, _)| PathBuf::from(path))
+            .collect()
+    }
+
     /// 获取文件元数据
     pub fn get_file_metadata(&self, file_path: &Path) -> Option<&FileMetadata> {
         self.file_metadata.get(file_path)
@@ -72,7 +90,7 @@ impl IncrementalManager {
         if !file_path.exists() {
             // 文件被删除，清理相关索引
             self._remove_file_entities(file_path, entity_graph, call_graph);
-            return Ok(());
+            return call_graph.check_invariants(self.strict, "removing a file");
         }
 
         // 计算当前MD5
@@ -90,7 +108,8 @@ impl IncrementalManager {
         // 文件需要更新，执行增量更新
         self._update_file(file_path, &current_md5, entity_graph, call_graph)?;
 
-        Ok(())
+        call_graph.update_stats();
+        call_graph.check_invariants(self.strict, "refreshing a file")
     }
 
     /// 更新文件元数据
@@ -155,6 +174,9 @@ impl IncrementalManager {
 
         let language = self._detect_language(file_path);
         let namespace = self._extract_namespace(file_path);
+        let file_content = fs::read_to_string(file_path).unwrap_or_default();
+        let owners = crate::codegraph::rust_methods::impl_owners(&symbols);
+        let closures = crate::codegraph::closures::Closures::new(&symbols, file_path);
 
         for symbol in symbols {
             let symbol_guard = symbol.read();
@@ -162,21 +184,30 @@ impl IncrementalManager {
 
             match symbol_ref.symbol_type() {
                 crate::codegraph::treesitter::structs::SymbolType::FunctionDeclaration => {
+                    let name = closures.name(symbol_ref.guid()).unwrap_or(symbol_ref.name()).to_string();
                     let function = FunctionInfo {
                         id: Uuid::new_v4(),
-                        name: symbol_ref.name().to_string(),
+                        name: name.clone(),
                         file_path: file_path.clone(),
                         line_start: symbol_ref.full_range().start_point.row + 1,
                         line_end: symbol_ref.full_range().end_point.row + 1,
                         namespace: namespace.clone(),
                         language: language.clone(),
-                        signature: Some(symbol_ref.name().to_string()),
+                        signature: Some(name),
+                        cell: None,
+                        body_hash: None,
+                        partial: false,
+                        return_type: None,
+                        parameters: Vec::new(),
+                        parent_class_id: crate::codegraph::parser::method_owner(&owners, symbol_ref, &language, &file_content)
+                            .map(|owner| ClassInfo::stable_id(&language, file_path, &namespace, &owner)),
+                        kind: FunctionKind::Function,
                     };
                     functions.push(function);
                 },
                 crate::codegraph::treesitter::structs::SymbolType::StructDeclaration => {
                     let class = ClassInfo {
-                        id: Uuid::new_v4(),
+                        id: ClassInfo::stable_id(&language, file_path, &namespace, symbol_ref.name()),
                         name: symbol_ref.name().to_string(),
                         file_path: file_path.clone(),
                         line_start: symbol_ref.full_range().start_point.row + 1,
@@ -195,6 +226,10 @@ impl IncrementalManager {
             }
         }
 
+        for class in &mut classes {
+            class.member_functions = functions.iter().filter(|f| f.parent_class_id == Some(class.id)).map(|f| f.id).collect();
+        }
+
         Ok((classes, functions))
     }
 
@@ -229,6 +264,10 @@ impl IncrementalManager {
                             callee_file: file_path.clone(),
                             line_number: call_line,
                             is_resolved: true,
+                            observed: false,
+                            template_args: None,
+                            call_kind: CallKind::Sync,
+                            confidence: None,
                         };
                         if let Err(e) = call_graph.add_call_relation(relation) {
                             warn!("Failed to add call relation: {}", e);
@@ -294,6 +333,10 @@ impl IncrementalManager {
             callee_file: file_path.clone(),
             line_number: call_line,
             is_resolved: false,
+            observed: false,
+            template_args: None,
+            call_kind: CallKind::Sync,
+            confidence: None,
         };
 
         if let Err(e) = call_graph.add_call_relation(relation) {
@@ -318,13 +361,11 @@ impl IncrementalManager {
             entity_graph.remove_entity(&entity_id);
         }
 
+        // file_index 之外的函数（如全量构建得到的图）由图自身的文件索引删除
         for function_id in function_ids {
-            if let Some(node_index) = call_graph.get_node_index(&function_id) {
-                call_graph.graph.remove_node(node_index);
-                call_graph.function_to_node.remove(&function_id);
-                call_graph.node_to_function.remove(&node_index);
-            }
+            call_graph.remove_function(&function_id);
         }
+        call_graph.remove_file(file_path);
 
         // 清理索引
         self.file_index.remove_file(file_path);
@@ -378,6 +419,12 @@ impl IncrementalManager {
 
     /// 检测文件语言
     fn _detect_language(&self, file_path: &Path) -> String {
+        if let Some(language) = crate::codegraph::treesitter::AnalyzerRegistry::language_for_path(file_path) {
+            return language;
+        }
+        if matches!(file_path.file_name().and_then(|n| n.to_str()), Some("Gemfile" | "Rakefile")) {
+            return "ruby".to_string();
+        }
         if let Some(ext) = file_path.extension().and_then(|e| e.to_str()) {
             match ext.to_lowercase().as_str() {
                 "rs" => "rust".to_string(),
@@ -387,6 +434,18 @@ impl IncrementalManager {
                 "java" => "java".to_string(),
                 "cpp" | "cc" | "cxx" | "c++" | "c" | "h" | "hpp" | "hxx" | "hh" => "cpp".to_string(),
                 "go" => "go".to_string(),
+                "kt" | "kts" => "kotlin".to_string(),
+                "rb" | "rake" => "ruby".to_string(),
+                "swift" => "swift".to_string(),
+                "scala" | "sc" => "scala".to_string(),
+                "m" | "mm" => "objc".to_string(),
+                "lua" => "lua".to_string(),
+                "sh" | "bash" => "bash".to_string(),
+                "sql" => "sql".to_string(),
+                "proto" => "proto".to_string(),
+                "vue" => "vue".to_string(),
+                "svelte" => "svelte".to_string(),
+                "html" | "htm" => "html".to_string(),
                 _ => "unknown".to_string(),
             }
         } else {
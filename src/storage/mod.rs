@@ -3,22 +3,60 @@ pub mod incremental;
 pub mod petgraph_storage;
 pub mod traits;
 pub mod prelude;
+pub mod snapshot;
+pub mod build_manifest;
+pub mod skeleton_cache;
+pub mod annotations;
+pub mod audit;
+pub mod function_bodies;
+pub mod compaction;
 
 pub use persistence::PersistenceManager;
 pub use incremental::IncrementalManager;
 pub use petgraph_storage::{PetGraphStorage, PetGraphStorageManager};
 pub use traits::{GraphPersistence, IncrementalUpdater, GraphSerializer};
+pub use snapshot::GraphSnapshot;
+pub use build_manifest::{BuildManifest, ManifestDiff};
+pub use skeleton_cache::SkeletonCache;
+pub use annotations::{MetricMap, NodeAnnotations};
+pub use audit::{RetentionRules, StorageAudit};
+pub use function_bodies::FunctionBodies;
+pub use compaction::{compact_storage, compact_storage_locked, CompactionReport};
 
+use std::path::Path;
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
 use parking_lot::RwLock;
 use crate::codegraph::types::PetCodeGraph;
+use crate::codegraph::federation::RepositoryFederation;
 use crate::cli::args::StorageMode;
+use serde::{Deserialize, Serialize};
+
+/// 租户配额，`None` 表示不限制
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct StorageQuota {
+    /// 最多可登记的项目数
+    pub max_projects: Option<usize>,
+    /// 单个项目图中最多的函数数
+    pub max_functions: Option<usize>,
+}
 
 pub struct StorageManager {
     persistence: Arc<PersistenceManager>,
     incremental: Arc<IncrementalManager>,
     graph: Arc<RwLock<Option<PetCodeGraph>>>,
+    // 内存中图的来源信息
+    graph_snapshot: RwLock<Option<GraphSnapshot>>,
+    // 按文件内容哈希缓存的代码骨架
+    skeletons: Arc<SkeletonCache>,
+    // 为联邦查询登记的仓库
+    federation: Arc<RepositoryFederation>,
     storage_mode: StorageMode,
+    tenant: Option<String>,
+    quota: RwLock<StorageQuota>,
+    // 快照标识：进程内随机前缀 + 每次图变更递增的计数
+    epoch: uuid::Uuid,
+    generation: AtomicU64,
 }
 
 impl StorageManager {
@@ -27,14 +65,95 @@ impl StorageManager {
     }
 
     pub fn with_storage_mode(storage_mode: StorageMode) -> Self {
+        let persistence = PersistenceManager::with_storage_mode(storage_mode.clone());
         Self {
-            persistence: Arc::new(PersistenceManager::with_storage_mode(storage_mode.clone())),
+            skeletons: Arc::new(SkeletonCache::new(persistence.base_dir().join(skeleton_cache::SKELETONS_DIR))),
+            persistence: Arc::new(persistence),
             incremental: Arc::new(IncrementalManager::new()),
             graph: Arc::new(RwLock::new(None)),
+            graph_snapshot: RwLock::new(None),
+            federation: Arc::new(RepositoryFederation::new()),
             storage_mode,
+            tenant: None,
+            quota: RwLock::new(StorageQuota::default()),
+            epoch: uuid::Uuid::new_v4(),
+            generation: AtomicU64::new(0),
+        }
+    }
+
+    /// 使用指定的存储目录（而不是当前目录下的 `.codegraph_db`）
+    pub fn with_base_dir(base_dir: std::path::PathBuf, storage_mode: StorageMode) -> Self {
+        Self {
+            skeletons: Arc::new(SkeletonCache::new(base_dir.join(skeleton_cache::SKELETONS_DIR))),
+            persistence: Arc::new(PersistenceManager::with_base_dir(base_dir, storage_mode.clone())),
+            ..Self::with_storage_mode(storage_mode)
         }
     }
 
+    /// 租户隔离的存储：数据位于 `.codegraph_db/tenants/<tenant_id>`，并受配额限制
+    pub fn for_tenant(tenant_id: &str, storage_mode: StorageMode, quota: StorageQuota) -> Result<Self, String> {
+        if tenant_id.is_empty()
+            || !tenant_id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+        {
+            return Err(format!("Invalid tenant id '{}': use letters, digits, '-' or '_'", tenant_id));
+        }
+        let base_dir = PersistenceManager::default_base_dir()
+            .join(persistence::TENANTS_DIR)
+            .join(tenant_id);
+        Ok(Self {
+            skeletons: Arc::new(SkeletonCache::new(base_dir.join(skeleton_cache::SKELETONS_DIR))),
+            persistence: Arc::new(PersistenceManager::with_base_dir(base_dir, storage_mode.clone())),
+            incremental: Arc::new(IncrementalManager::new()),
+            graph: Arc::new(RwLock::new(None)),
+            graph_snapshot: RwLock::new(None),
+            federation: Arc::new(RepositoryFederation::new()),
+            storage_mode,
+            tenant: Some(tenant_id.to_string()),
+            quota: RwLock::new(quota),
+            epoch: uuid::Uuid::new_v4(),
+            generation: AtomicU64::new(0),
+        })
+    }
+
+    /// 所属租户，单租户模式下为 `None`
+    pub fn tenant_id(&self) -> Option<&str> {
+        self.tenant.as_deref()
+    }
+
+    pub fn quota(&self) -> StorageQuota {
+        self.quota.read().clone()
+    }
+
+    /// 更新配额（配置热加载时使用），不影响已加载的图
+    pub fn set_quota(&self, quota: StorageQuota) {
+        *self.quota.write() = quota;
+    }
+
+    /// 检查保存项目是否超出配额；`function_count` 为 `None` 时只检查项目数
+    pub fn check_quota(&self, project_id: &str, function_count: Option<usize>) -> Result<(), String> {
+        let quota = self.quota();
+        if let Some(max_projects) = quota.max_projects {
+            let persistence = self.get_persistence();
+            let is_new = !persistence.is_project_parsed(project_id).unwrap_or(false);
+            let registered = persistence.count_parsed_projects().unwrap_or(0);
+            if is_new && registered >= max_projects {
+                return Err(format!(
+                    "Project quota exceeded: {} of {} projects already stored",
+                    registered, max_projects
+                ));
+            }
+        }
+        if let (Some(max_functions), Some(count)) = (quota.max_functions, function_count) {
+            if count > max_functions {
+                return Err(format!(
+                    "Graph size quota exceeded: {} functions, limit is {}",
+                    count, max_functions
+                ));
+            }
+        }
+        Ok(())
+    }
+
     pub fn set_storage_mode(&mut self, storage_mode: StorageMode) {
         self.storage_mode = storage_mode.clone();
         // Update persistence manager's storage mode
@@ -59,8 +178,55 @@ impl StorageManager {
         self.graph.clone()
     }
 
+    pub fn get_skeleton_cache(&self) -> Arc<SkeletonCache> {
+        self.skeletons.clone()
+    }
+
+    pub fn get_federation(&self) -> Arc<RepositoryFederation> {
+        self.federation.clone()
+    }
+
     pub fn set_graph(&self, graph: PetCodeGraph) {
+        self.set_graph_with_snapshot(graph, None);
+    }
+
+    /// 缓存图及其来源信息
+    pub fn set_graph_with_snapshot(&self, graph: PetCodeGraph, snapshot: Option<GraphSnapshot>) {
         *self.graph.write() = Some(graph);
+        *self.graph_snapshot.write() = snapshot;
+        self.mark_updated();
+    }
+
+    /// 内存中的图属于 `project_dir` 下的项目时换成重新构建的图，返回是否替换；
+    /// 内存中是其他项目的图时保持不变，后台构建不会替换用户当前载入的项目
+    pub fn replace_project_graph(&self, project_dir: &Path, graph: PetCodeGraph, snapshot: Option<GraphSnapshot>) -> bool {
+        let mut cached = self.graph.write();
+        let holds_project = cached
+            .as_ref()
+            .is_some_and(|cached| cached.get_all_functions().iter().any(|f| f.file_path.starts_with(project_dir)));
+        if !holds_project {
+            return false;
+        }
+        *cached = Some(graph);
+        *self.graph_snapshot.write() = snapshot;
+        drop(cached);
+        self.mark_updated();
+        true
+    }
+
+    /// 内存中图的来源信息
+    pub fn graph_snapshot(&self) -> Option<GraphSnapshot> {
+        self.graph_snapshot.read().clone()
+    }
+
+    /// 记录已保存或缓存的图发生了变化
+    pub fn mark_updated(&self) {
+        self.generation.fetch_add(1, Ordering::SeqCst);
+    }
+
+    /// 当前数据快照的标识，任何图变更后都会改变（用于查询缓存和ETag）
+    pub fn snapshot_id(&self) -> String {
+        format!("{}-{}", self.epoch.simple(), self.generation.load(Ordering::SeqCst))
     }
 
     pub fn get_graph_clone(&self) -> Option<PetCodeGraph> {
@@ -2,12 +2,20 @@ use std::path::PathBuf;
 use std::fs;
 use std::io;
 use std::collections::HashMap;
-use crate::codegraph::types::PetCodeGraph;
+use crate::codegraph::entrypoints::EntryPoint;
+use crate::codegraph::types::{FileParseStats, PetCodeGraph};
 use crate::storage::petgraph_storage::PetGraphStorageManager;
 use crate::cli::args::StorageMode;
+use crate::storage::snapshot::GraphSnapshot;
+use crate::storage::build_manifest::BuildManifest;
+use crate::storage::annotations::NodeAnnotations;
+use crate::storage::function_bodies::FunctionBodies;
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 
+/// 存储目录下存放各租户数据的子目录
+pub const TENANTS_DIR: &str = "tenants";
+
 pub struct PersistenceManager {
     base_dir: PathBuf,
     storage_mode: StorageMode,
@@ -32,10 +40,11 @@ impl PersistenceManager {
     }
 
     pub fn with_storage_mode(storage_mode: StorageMode) -> Self {
-        let base_dir = std::env::current_dir()
-            .unwrap_or_else(|_| PathBuf::from("."))
-            .join(".codegraph_db");
-        
+        Self::with_base_dir(Self::default_base_dir(), storage_mode)
+    }
+
+    /// 使用指定的存储目录（例如租户目录）
+    pub fn with_base_dir(base_dir: PathBuf, storage_mode: StorageMode) -> Self {
         // Create base directory if it doesn't exist
         if !base_dir.exists() {
             fs::create_dir_all(&base_dir).ok();
@@ -44,6 +53,17 @@ impl PersistenceManager {
         Self { base_dir, storage_mode }
     }
 
+    /// 默认存储目录：当前目录下的 `.codegraph_db`
+    pub fn default_base_dir() -> PathBuf {
+        std::env::current_dir()
+            .unwrap_or_else(|_| PathBuf::from("."))
+            .join(".codegraph_db")
+    }
+
+    pub fn base_dir(&self) -> &std::path::Path {
+        &self.base_dir
+    }
+
     pub fn set_storage_mode(&mut self, storage_mode: StorageMode) {
         self.storage_mode = storage_mode;
     }
@@ -151,6 +171,134 @@ impl PersistenceManager {
         Ok(())
     }
 
+    /// 保存图的来源信息（提交、构建时间、分析器版本）
+    pub fn save_snapshot(&self, project_id: &str, snapshot: &GraphSnapshot) -> io::Result<()> {
+        let project_dir = self.base_dir.join(project_id);
+        fs::create_dir_all(&project_dir)?;
+        let json = serde_json::to_string_pretty(snapshot)?;
+        fs::write(project_dir.join("snapshot.json"), json)
+    }
+
+    /// 加载图的来源信息，旧版本构建的项目没有该文件
+    pub fn load_snapshot(&self, project_id: &str) -> io::Result<Option<GraphSnapshot>> {
+        let snapshot_file = self.base_dir.join(project_id).join("snapshot.json");
+        if !snapshot_file.exists() {
+            return Ok(None);
+        }
+        let content = fs::read_to_string(snapshot_file)?;
+        serde_json::from_str(&content)
+            .map(Some)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+    }
+
+    /// 保存构建清单，用于比较两次构建的输入
+    pub fn save_manifest(&self, project_id: &str, manifest: &BuildManifest) -> io::Result<()> {
+        let project_dir = self.base_dir.join(project_id);
+        fs::create_dir_all(&project_dir)?;
+        let json = serde_json::to_string_pretty(manifest)?;
+        fs::write(project_dir.join("manifest.json"), json)
+    }
+
+    /// 加载构建清单，旧版本构建的项目没有该文件
+    pub fn load_manifest(&self, project_id: &str) -> io::Result<Option<BuildManifest>> {
+        let manifest_file = self.base_dir.join(project_id).join("manifest.json");
+        if !manifest_file.exists() {
+            return Ok(None);
+        }
+        let content = fs::read_to_string(manifest_file)?;
+        serde_json::from_str(&content)
+            .map(Some)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+    }
+
+    /// 保存附加在节点上的外部指标
+    pub fn save_annotations(&self, project_id: &str, annotations: &NodeAnnotations) -> io::Result<()> {
+        let project_dir = self.base_dir.join(project_id);
+        fs::create_dir_all(&project_dir)?;
+        let json = serde_json::to_string_pretty(annotations)?;
+        fs::write(project_dir.join("annotations.json"), json)
+    }
+
+    /// 加载附加在节点上的外部指标，未上传过时为空
+    pub fn load_annotations(&self, project_id: &str) -> io::Result<NodeAnnotations> {
+        let annotations_file = self.base_dir.join(project_id).join("annotations.json");
+        if !annotations_file.exists() {
+            return Ok(NodeAnnotations::default());
+        }
+        let content = fs::read_to_string(annotations_file)?;
+        serde_json::from_str(&content).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+    }
+
+    /// 保存各文件的解析统计（文件路径 -> 统计）
+    pub fn save_parse_stats(&self, project_id: &str, stats: &HashMap<String, FileParseStats>) -> io::Result<()> {
+        let project_dir = self.base_dir.join(project_id);
+        fs::create_dir_all(&project_dir)?;
+        let json = serde_json::to_string_pretty(stats)?;
+        fs::write(project_dir.join("parse_stats.json"), json)
+    }
+
+    /// 加载各文件的解析统计，旧版本构建的项目没有该文件
+    pub fn load_parse_stats(&self, project_id: &str) -> io::Result<HashMap<String, FileParseStats>> {
+        let stats_file = self.base_dir.join(project_id).join("parse_stats.json");
+        if !stats_file.exists() {
+            return Ok(HashMap::new());
+        }
+        let content = fs::read_to_string(stats_file)?;
+        serde_json::from_str(&content).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+    }
+
+    /// 保存构建时检测到的框架入口
+    pub fn save_entry_points(&self, project_id: &str, entry_points: &[EntryPoint]) -> io::Result<()> {
+        let project_dir = self.base_dir.join(project_id);
+        fs::create_dir_all(&project_dir)?;
+        let json = serde_json::to_string_pretty(entry_points)?;
+        fs::write(project_dir.join("entry_points.json"), json)
+    }
+
+    /// 加载构建时检测到的框架入口，旧版本构建的项目没有该文件时为 `None`
+    pub fn load_entry_points(&self, project_id: &str) -> io::Result<Option<Vec<EntryPoint>>> {
+        let entry_points_file = self.base_dir.join(project_id).join("entry_points.json");
+        if !entry_points_file.exists() {
+            return Ok(None);
+        }
+        let content = fs::read_to_string(entry_points_file)?;
+        serde_json::from_str(&content).map(Some).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+    }
+
+    /// 保存构建时嵌入的函数源码（gzip 压缩）
+    pub fn save_function_bodies(&self, project_id: &str, bodies: &FunctionBodies) -> io::Result<()> {
+        let project_dir = self.base_dir.join(project_id);
+        fs::create_dir_all(&project_dir)?;
+        fs::write(project_dir.join("bodies.json.gz"), bodies.to_compressed()?)
+    }
+
+    /// 加载嵌入的函数源码，构建时未开启 `embed_sources` 时为 `None`
+    pub fn load_function_bodies(&self, project_id: &str) -> io::Result<Option<FunctionBodies>> {
+        let bodies_file = self.base_dir.join(project_id).join("bodies.json.gz");
+        if !bodies_file.exists() {
+            return Ok(None);
+        }
+        FunctionBodies::from_compressed(&fs::read(bodies_file)?).map(Some)
+    }
+
+    /// 删除嵌入的函数源码，避免未开启 `embed_sources` 的构建返回旧版本的代码
+    pub fn clear_function_bodies(&self, project_id: &str) -> io::Result<()> {
+        let bodies_file = self.base_dir.join(project_id).join("bodies.json.gz");
+        if bodies_file.exists() {
+            fs::remove_file(bodies_file)?;
+        }
+        Ok(())
+    }
+
+    /// 删除文件哈希，使下一次构建重新解析所有文件
+    pub fn clear_file_hashes(&self, project_id: &str) -> io::Result<()> {
+        let hash_file = self.base_dir.join(project_id).join("file_hashes.json");
+        if hash_file.exists() {
+            fs::remove_file(hash_file)?;
+        }
+        Ok(())
+    }
+
     pub fn load_file_hashes(&self, project_id: &str) -> io::Result<HashMap<String, String>> {
         let hash_file = self.base_dir.join(project_id).join("file_hashes.json");
         
@@ -185,6 +333,10 @@ impl PersistenceManager {
                 let entry = entry?;
                 if entry.file_type()?.is_dir() {
                     if let Some(name) = entry.file_name().to_str() {
+                        // 租户数据目录和骨架缓存目录不是项目
+                        if name == TENANTS_DIR || name == crate::storage::skeleton_cache::SKELETONS_DIR {
+                            continue;
+                        }
                         projects.push(name.to_string());
                     }
                 }
@@ -266,6 +418,15 @@ impl PersistenceManager {
         Ok(None)
     }
 
+    pub fn get_project_record(&self, project_id: &str) -> io::Result<Option<ProjectRecord>> {
+        let registry = self.load_registry()?;
+        Ok(registry.projects.get(project_id).cloned())
+    }
+
+    pub fn count_parsed_projects(&self) -> io::Result<usize> {
+        Ok(self.load_registry()?.projects.len())
+    }
+
     pub fn list_parsed_projects(&self) -> io::Result<Vec<ProjectRecord>> {
         let registry = self.load_registry()?;
         Ok(registry.projects.values().cloned().collect())
@@ -4,7 +4,7 @@ use std::fs;
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
-use crate::codegraph::types::{PetCodeGraph, FunctionInfo, CallRelation, CodeGraphStats};
+use crate::codegraph::types::{PetCodeGraph, FunctionInfo, FunctionKind, CallRelation, CodeGraphStats};
 
 /// petgraph代码图存储格式
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -40,9 +40,13 @@ impl PetGraphStorage {
     pub fn to_petgraph(&self) -> PetCodeGraph {
         let mut code_graph = PetCodeGraph::new();
         
-        // 添加所有函数
+        // 添加所有函数；较早版本保存的占位节点只有 `unresolved` 命名空间，没有记录种类
         for function in &self.functions {
-            code_graph.add_function(function.clone());
+            let mut function = function.clone();
+            if function.kind == FunctionKind::Function && function.namespace == "unresolved" {
+                function.kind = FunctionKind::Unresolved;
+            }
+            code_graph.add_function(function);
         }
         
         // 添加所有调用关系
@@ -176,67 +180,11 @@ impl PetGraphStorageManager {
         Ok(())
     }
 
-    /// 导出为GEXF格式（用于Gephi等工具）
+    /// 导出为GEXF格式（用于Gephi等工具），节点带扇入/扇出和圈复杂度
     pub fn export_to_gexf(code_graph: &PetCodeGraph, file_path: &Path) -> Result<(), String> {
-        let mut gexf = String::new();
-        gexf.push_str("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
-        gexf.push_str("<gexf xmlns=\"http://www.gexf.net/1.3\" version=\"1.3\">\n");
-        gexf.push_str("  <meta lastmodifieddate=\"2024-01-01\">\n");
-        gexf.push_str("    <creator>CodeGraph Exporter</creator>\n");
-        gexf.push_str("    <description>Code dependency graph</description>\n");
-        gexf.push_str("  </meta>\n");
-        gexf.push_str("  <graph mode=\"static\" defaultedgetype=\"directed\">\n");
-        
-        // 定义节点属性
-        gexf.push_str("    <attributes class=\"node\">\n");
-        gexf.push_str("      <attribute id=\"0\" title=\"name\" type=\"string\"/>\n");
-        gexf.push_str("      <attribute id=\"1\" title=\"file\" type=\"string\"/>\n");
-        gexf.push_str("      <attribute id=\"2\" title=\"language\" type=\"string\"/>\n");
-        gexf.push_str("      <attribute id=\"3\" title=\"line_start\" type=\"integer\"/>\n");
-        gexf.push_str("      <attribute id=\"4\" title=\"line_end\" type=\"integer\"/>\n");
-        gexf.push_str("    </attributes>\n");
-        
-        // 定义边属性
-        gexf.push_str("    <attributes class=\"edge\">\n");
-        gexf.push_str("      <attribute id=\"0\" title=\"line_number\" type=\"integer\"/>\n");
-        gexf.push_str("      <attribute id=\"1\" title=\"is_resolved\" type=\"boolean\"/>\n");
-        gexf.push_str("    </attributes>\n");
-        
-        gexf.push_str("    <nodes>\n");
-        
-        // 添加节点
-        for (node_index, function) in code_graph.graph.node_indices().zip(code_graph.graph.node_weights()) {
-            gexf.push_str(&format!("      <node id=\"{}\" label=\"{}\">\n", node_index.index(), function.name));
-            gexf.push_str("        <attvalues>\n");
-            gexf.push_str(&format!("          <attvalue for=\"0\" value=\"{}\"/>\n", function.name));
-            gexf.push_str(&format!("          <attvalue for=\"1\" value=\"{}\"/>\n", function.file_path.display()));
-            gexf.push_str(&format!("          <attvalue for=\"2\" value=\"{}\"/>\n", function.language));
-            gexf.push_str(&format!("          <attvalue for=\"3\" value=\"{}\"/>\n", function.line_start));
-            gexf.push_str(&format!("          <attvalue for=\"4\" value=\"{}\"/>\n", function.line_end));
-            gexf.push_str("        </attvalues>\n");
-            gexf.push_str("      </node>\n");
-        }
-        
-        gexf.push_str("    </nodes>\n");
-        gexf.push_str("    <edges>\n");
-        
-        // 添加边
-        for (edge_index, edge) in code_graph.graph.edge_indices().zip(code_graph.graph.edge_weights()) {
-            if let Some((source, target)) = code_graph.graph.edge_endpoints(edge_index) {
-                gexf.push_str(&format!("      <edge id=\"{}\" source=\"{}\" target=\"{}\">\n", 
-                    edge_index.index(), source.index(), target.index()));
-                gexf.push_str("        <attvalues>\n");
-                gexf.push_str(&format!("          <attvalue for=\"0\" value=\"{}\"/>\n", edge.line_number));
-                gexf.push_str(&format!("          <attvalue for=\"1\" value=\"{}\"/>\n", edge.is_resolved));
-                gexf.push_str("        </attvalues>\n");
-                gexf.push_str("      </edge>\n");
-            }
-        }
-        
-        gexf.push_str("    </edges>\n");
-        gexf.push_str("  </graph>\n");
-        gexf.push_str("</gexf>\n");
-        
+        use crate::services::graph_export::{node_metrics, to_gexf};
+
+        let gexf = to_gexf(code_graph, &node_metrics(code_graph));
         fs::write(file_path, gexf)
             .map_err(|e| format!("Failed to write GEXF file {}: {}", file_path.display(), e))?;
         
@@ -0,0 +1,138 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+
+/// 缓存目录（位于存储根目录下）
+pub const SKELETONS_DIR: &str = "skeletons";
+
+/// 与增量构建相同的文件内容哈希（MD5）
+pub fn content_hash(content: &[u8]) -> String {
+    format!("{:x}", md5::compute(content))
+}
+
+/// 一个文件的骨架缓存项
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CachedSkeleton {
+    pub path: PathBuf,
+    pub content_hash: String,
+    pub language: String,
+    pub skeleton_text: String,
+}
+
+/// 按 (文件路径, 内容哈希) 缓存的代码骨架，内存中保存并持久化到存储目录
+pub struct SkeletonCache {
+    dir: PathBuf,
+    entries: RwLock<HashMap<PathBuf, CachedSkeleton>>,
+    hits: AtomicU64,
+    misses: AtomicU64,
+}
+
+impl SkeletonCache {
+    pub fn new(dir: PathBuf) -> Self {
+        Self {
+            dir,
+            entries: RwLock::new(HashMap::new()),
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+        }
+    }
+
+    // 路径规范化后作为键，使相对路径和绝对路径命中同一项
+    fn key(path: &Path) -> PathBuf {
+        fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf())
+    }
+
+    fn entry_file(&self, key: &Path) -> PathBuf {
+        self.dir.join(format!("{:x}.json", md5::compute(key.to_string_lossy().as_bytes())))
+    }
+
+    /// 查找内容哈希一致的缓存项；内存未命中时从磁盘加载
+    pub fn get(&self, path: &Path, content_hash: &str) -> Option<CachedSkeleton> {
+        let key = Self::key(path);
+        let in_memory = self.entries.read().get(&key).cloned();
+        let cached = in_memory.or_else(|| {
+            let content = fs::read_to_string(self.entry_file(&key)).ok()?;
+            let entry: CachedSkeleton = serde_json::from_str(&content).ok()?;
+            self.entries.write().insert(key.clone(), entry.clone());
+            Some(entry)
+        });
+        match cached.filter(|entry| entry.content_hash == content_hash) {
+            Some(entry) => {
+                self.hits.fetch_add(1, Ordering::Relaxed);
+                Some(CachedSkeleton { path: path.to_path_buf(), ..entry })
+            }
+            None => {
+                self.misses.fetch_add(1, Ordering::Relaxed);
+                None
+            }
+        }
+    }
+
+    /// 写入缓存项（覆盖同一文件的旧内容）
+    pub fn insert(&self, entry: CachedSkeleton) {
+        let key = Self::key(&entry.path);
+        let file = self.entry_file(&key);
+        let stored = CachedSkeleton { path: key.clone(), ..entry };
+        if let Err(e) = fs::create_dir_all(&self.dir)
+            .and_then(|_| fs::write(&file, serde_json::to_string(&stored).unwrap_or_default()))
+        {
+            tracing::warn!("Failed to persist skeleton for {}: {}", key.display(), e);
+        }
+        self.entries.write().insert(key, stored);
+    }
+
+    /// 文件变更或删除后移除其缓存项
+    pub fn invalidate(&self, path: &Path) {
+        let key = Self::key(path);
+        self.entries.write().remove(&key);
+        let file = self.entry_file(&key);
+        if file.exists() {
+            if let Err(e) = fs::remove_file(&file) {
+                tracing::warn!("Failed to remove cached skeleton {}: {}", file.display(), e);
+            }
+        }
+    }
+
+    /// (命中次数, 未命中次数)
+    pub fn stats(&self) -> (u64, u64) {
+        (self.hits.load(Ordering::Relaxed), self.misses.load(Ordering::Relaxed))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hit_requires_same_content() {
+        let dir = std::env::temp_dir().join(format!("codegraph-skeletons-{}", uuid::Uuid::new_v4()));
+        let cache = SkeletonCache::new(dir.join(SKELETONS_DIR));
+        let source = dir.join("lib.rs");
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(&source, "fn a() {}").unwrap();
+
+        let hash = content_hash(b"fn a() {}");
+        assert!(cache.get(&source, &hash).is_none());
+        cache.insert(CachedSkeleton {
+            path: source.clone(),
+            content_hash: hash.clone(),
+            language: "rust".to_string(),
+            skeleton_text: "fn a()".to_string(),
+        });
+        assert_eq!(cache.get(&source, &hash).unwrap().skeleton_text, "fn a()");
+        assert!(cache.get(&source, &content_hash(b"fn b() {}")).is_none());
+
+        // 新实例从磁盘加载
+        let reopened = SkeletonCache::new(dir.join(SKELETONS_DIR));
+        assert!(reopened.get(&source, &hash).is_some());
+        reopened.invalidate(&source);
+        assert!(SkeletonCache::new(dir.join(SKELETONS_DIR)).get(&source, &hash).is_none());
+        assert_eq!(cache.stats(), (1, 2));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,49 @@
+use std::path::Path;
+use std::process::Command;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// 图快照的来源信息，在构建时记录并随查询结果返回，便于调用方判断数据是否过期
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GraphSnapshot {
+    /// 构建时项目的 git 提交，非 git 仓库时为空
+    pub commit: Option<String>,
+    /// 构建时工作区是否有未提交的修改
+    #[serde(default)]
+    pub dirty: bool,
+    pub built_at: DateTime<Utc>,
+    /// 生成该图的分析器版本
+    pub analyzer_version: String,
+    /// 在基线分支的图上增量构建的 PR 图：PR 编号
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pull_request: Option<u64>,
+    /// PR 图所基于的基线图的提交
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub base_commit: Option<String>,
+}
+
+impl GraphSnapshot {
+    /// 记录项目目录当前的提交和构建时间
+    pub fn capture(project_dir: &Path) -> Self {
+        let commit = git(project_dir, &["rev-parse", "HEAD"]);
+        let dirty = commit.is_some()
+            && git(project_dir, &["status", "--porcelain", "--untracked-files=no"]).is_some_and(|s| !s.is_empty());
+        Self {
+            commit,
+            dirty,
+            built_at: Utc::now(),
+            analyzer_version: env!("CARGO_PKG_VERSION").to_string(),
+            pull_request: None,
+            base_commit: None,
+        }
+    }
+}
+
+pub(crate) fn git(dir: &Path, args: &[&str]) -> Option<String> {
+    let output = Command::new("git").arg("-C").arg(dir).args(args).output().ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    Some(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
@@ -0,0 +1,18 @@
+#!/usr/bin/env bash
+source lib/mathx.sh
+
+square() {
+  echo $(( $1 * $1 ))
+}
+
+function distance {
+  local dx=$(square "$1")
+  local dy=$(square "$2")
+  root $(( dx + dy ))
+}
+
+main() {
+  distance 3 4
+}
+
+main "$@"
@@ -0,0 +1,27 @@
+#include <cmath>
+#include <iostream>
+
+class Point {
+public:
+    double x;
+    double y;
+};
+
+double square(double v) {
+    return v * v;
+}
+
+double square_root(double v) {
+    return std::sqrt(v);
+}
+
+double distance(const Point& a, const Point& b) {
+    return square_root(square(a.x - b.x) + square(a.y - b.y));
+}
+
+int main() {
+    Point a{0, 0};
+    Point b{3, 4};
+    std::cout << distance(a, b) << std::endl;
+    return 0;
+}
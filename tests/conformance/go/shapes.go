@@ -0,0 +1,29 @@
+package shapes
+
+import (
+	"fmt"
+	"math"
+)
+
+type Point struct {
+	X float64
+	Y float64
+}
+
+func square(v float64) float64 {
+	return v * v
+}
+
+func squareRoot(v float64) float64 {
+	return math.Sqrt(v)
+}
+
+func (p Point) Distance(other Point) float64 {
+	return squareRoot(square(p.X-other.X) + square(p.Y-other.Y))
+}
+
+func Main() {
+	a := Point{X: 0, Y: 0}
+	b := Point{X: 3, Y: 4}
+	fmt.Println(a.Distance(b))
+}
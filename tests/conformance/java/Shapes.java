@@ -0,0 +1,17 @@
+public class Shapes {
+    static double square(double v) {
+        return v * v;
+    }
+
+    static double squareRoot(double v) {
+        return Math.sqrt(v);
+    }
+
+    static double distance(double x1, double y1, double x2, double y2) {
+        return squareRoot(square(x1 - x2) + square(y1 - y2));
+    }
+
+    public static void main(String[] args) {
+        System.out.println(distance(0, 0, 3, 4));
+    }
+}
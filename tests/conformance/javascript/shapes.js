@@ -0,0 +1,24 @@
+class Point {
+    constructor(x, y) {
+        this.x = x;
+        this.y = y;
+    }
+
+    distance(other) {
+        return squareRoot(square(this.x - other.x) + square(this.y - other.y));
+    }
+}
+
+function square(v) {
+    return v * v;
+}
+
+function squareRoot(v) {
+    return Math.sqrt(v);
+}
+
+function main() {
+    const a = new Point(0, 0);
+    const b = new Point(3, 4);
+    console.log(a.distance(b));
+}
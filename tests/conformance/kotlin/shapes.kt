@@ -0,0 +1,23 @@
+package shapes
+
+import kotlin.math.sqrt
+
+class Point(val x: Double, val y: Double) {
+    fun distance(other: Point): Double {
+        return squareRoot(square(x - other.x) + square(y - other.y))
+    }
+}
+
+fun square(v: Double): Double {
+    return v * v
+}
+
+fun squareRoot(v: Double): Double {
+    return sqrt(v)
+}
+
+fun main() {
+    val a = Point(0.0, 0.0)
+    val b = Point(3.0, 4.0)
+    println(a.distance(b))
+}
@@ -0,0 +1,28 @@
+local mathx = require("shapes.mathx")
+
+local Point = {}
+Point.__index = Point
+
+function Point.new(x, y)
+  return setmetatable({ x = x, y = y }, Point)
+end
+
+function Point:distance(other)
+  return square_root(square(self.x - other.x) + square(self.y - other.y))
+end
+
+function square(v)
+  return v * v
+end
+
+function square_root(v)
+  return mathx.sqrt(v)
+end
+
+local function main()
+  local a = Point.new(0, 0)
+  local b = Point.new(3, 4)
+  print(a:distance(b))
+end
+
+main()
@@ -0,0 +1,28 @@
+#import <math.h>
+
+@interface Point : NSObject
+@property (nonatomic) double x;
+@property (nonatomic) double y;
+- (double)distanceTo:(Point *)other;
+@end
+
+@implementation Point
+- (double)distanceTo:(Point *)other {
+    return squareRoot(square(self.x - other.x) + square(self.y - other.y));
+}
+@end
+
+double square(double v) {
+    return v * v;
+}
+
+double squareRoot(double v) {
+    return sqrt(v);
+}
+
+int main(int argc, char *argv[]) {
+    Point *a = [[Point alloc] init];
+    Point *b = [[Point alloc] init];
+    NSLog(@"%f", [a distanceTo:b]);
+    return 0;
+}
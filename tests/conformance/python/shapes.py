@@ -0,0 +1,28 @@
+import math
+
+
+class Point:
+    def __init__(self, x, y):
+        self.x = x
+        self.y = y
+
+    def distance(self, other):
+        return square_root(square(self.x - other.x) + square(self.y - other.y))
+
+
+def square(v):
+    return v * v
+
+
+def square_root(v):
+    return math.sqrt(v)
+
+
+def main():
+    a = Point(0, 0)
+    b = Point(3, 4)
+    print(a.distance(b))
+
+
+def scale(p: Point, factor: float = 1.0) -> Point:
+    return Point(p.x * factor, p.y * factor)
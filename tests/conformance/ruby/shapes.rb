@@ -0,0 +1,26 @@
+require 'json'
+
+class Point
+  def initialize(x, y)
+    @x = x
+    @y = y
+  end
+
+  def distance(other)
+    square_root(square(@x - other.x) + square(@y - other.y))
+  end
+end
+
+def square(v)
+  v * v
+end
+
+def square_root(v)
+  Math.sqrt(v)
+end
+
+def main
+  a = Point.new(0, 0)
+  b = Point.new(3, 4)
+  puts a.distance(b)
+end
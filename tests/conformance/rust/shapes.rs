@@ -0,0 +1,28 @@
+pub struct Point {
+    pub x: f64,
+    pub y: f64,
+}
+
+impl Point {
+    pub fn new(x: f64, y: f64) -> Self {
+        Point { x, y }
+    }
+
+    pub fn distance(&self, other: &Point) -> f64 {
+        square_root(square(self.x - other.x) + square(self.y - other.y))
+    }
+}
+
+fn square(v: f64) -> f64 {
+    v * v
+}
+
+fn square_root(v: f64) -> f64 {
+    v.sqrt()
+}
+
+pub fn main() {
+    let a = Point::new(0.0, 0.0);
+    let b = Point::new(3.0, 4.0);
+    println!("{}", a.distance(&b));
+}
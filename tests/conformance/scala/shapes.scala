@@ -0,0 +1,21 @@
+package shapes
+
+import scala.math.sqrt
+
+class Point(val x: Double, val y: Double) {
+  def distance(other: Point): Double = {
+    Shapes.squareRoot(Shapes.square(x - other.x) + Shapes.square(y - other.y))
+  }
+}
+
+object Shapes {
+  def square(v: Double): Double = v * v
+
+  def squareRoot(v: Double): Double = sqrt(v)
+
+  def main(args: Array[String]): Unit = {
+    val a = new Point(0, 0)
+    val b = new Point(3, 4)
+    println(a.distance(b))
+  }
+}
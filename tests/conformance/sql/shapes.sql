@@ -0,0 +1,23 @@
+-- Distances between stored points (PostgreSQL)
+CREATE FUNCTION shapes.square(v numeric) RETURNS numeric AS $$
+BEGIN
+  RETURN v * v;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE FUNCTION shapes.distance(a_id int, b_id int) RETURNS numeric AS $$
+DECLARE
+  dx numeric;
+  dy numeric;
+BEGIN
+  SELECT a.x - b.x, a.y - b.y INTO dx, dy FROM points a, points b WHERE a.id = a_id AND b.id = b_id;
+  RETURN sqrt(shapes.square(dx) + shapes.square(dy));
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE PROCEDURE shapes.report(p_id int)
+LANGUAGE plpgsql AS $$
+BEGIN
+  PERFORM shapes.distance(p_id, 0);
+END;
+$$;
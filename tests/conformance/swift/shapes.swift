@@ -0,0 +1,29 @@
+import Foundation
+
+class Point {
+    let x: Double
+    let y: Double
+
+    init(x: Double, y: Double) {
+        self.x = x
+        self.y = y
+    }
+
+    func distance(other: Point) -> Double {
+        return squareRoot(square(x - other.x) + square(y - other.y))
+    }
+}
+
+func square(_ v: Double) -> Double {
+    return v * v
+}
+
+func squareRoot(_ v: Double) -> Double {
+    return sqrt(v)
+}
+
+func main() {
+    let a = Point(x: 0, y: 0)
+    let b = Point(x: 3, y: 4)
+    print(a.distance(other: b))
+}
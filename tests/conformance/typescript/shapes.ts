@@ -0,0 +1,21 @@
+export class Point {
+    constructor(public x: number, public y: number) {}
+
+    distance(other: Point): number {
+        return squareRoot(square(this.x - other.x) + square(this.y - other.y));
+    }
+}
+
+function square(v: number): number {
+    return v * v;
+}
+
+function squareRoot(v: number): number {
+    return Math.sqrt(v);
+}
+
+export function main(): void {
+    const a = new Point(0, 0);
+    const b = new Point(3, 4);
+    console.log(a.distance(b));
+}
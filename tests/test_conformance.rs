@@ -0,0 +1,221 @@
+//! 各语言解析器的一致性测试
+//!
+//! `tests/conformance/<language>/` 下每个 `*.yaml` 描述一个样例源文件中应当提取出的函数、类、调用边和导入。
+//! 新增语言解析器时只需添加样例文件和对应的 YAML，不需要编写新的测试代码。
+//! 设置 `CONFORMANCE_DUMP=1` 可以打印实际提取结果，便于编写新的 fixture。
+
+use std::collections::BTreeSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::Deserialize;
+use tempfile::TempDir;
+
+use codegraph_cli::codegraph::parser::CodeParser;
+use codegraph_cli::codegraph::treesitter::parsers::get_ast_parser_by_filename;
+use codegraph_cli::codegraph::treesitter::{ImportDeclaration, SymbolType};
+use codegraph_cli::codegraph::{AnalyzeOptions, TreeSitterParser};
+
+const FIXTURES_DIR: &str = "tests/conformance";
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Fixture {
+    /// 样例源文件，相对 fixture 所在目录
+    source: String,
+    /// 期望的 `FunctionInfo.language`
+    language: String,
+    #[serde(default)]
+    functions: Vec<ExpectedFunction>,
+    #[serde(default)]
+    classes: Vec<String>,
+    #[serde(default)]
+    calls: Vec<ExpectedCall>,
+    /// 导入的模块或名字，各级以 `.` 连接，如 `kotlin.math.sqrt`
+    #[serde(default)]
+    imports: Vec<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ExpectedFunction {
+    name: String,
+    /// 起始行（从1开始），省略时不检查
+    line: Option<usize>,
+    /// 声明（空白压缩为单个空格），省略时不检查
+    signature: Option<String>,
+    /// 返回类型，省略时不检查
+    returns: Option<String>,
+    /// 形参，写作 `名字: 类型`，没有类型时只写名字；省略时不检查
+    params: Option<Vec<String>>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ExpectedCall {
+    caller: String,
+    callee: String,
+}
+
+/// (函数名, 声明, 返回类型, 形参)
+type ExtractedSignature = (String, Option<String>, Option<String>, Vec<String>);
+
+/// 实际提取结果
+#[derive(Debug, Default)]
+struct Extracted {
+    functions: BTreeSet<(String, usize, String)>,
+    signatures: BTreeSet<ExtractedSignature>,
+    classes: BTreeSet<String>,
+    calls: BTreeSet<(String, String)>,
+    imports: BTreeSet<String>,
+}
+
+fn fixture_paths() -> Vec<PathBuf> {
+    let mut paths: Vec<PathBuf> = walkdir::WalkDir::new(FIXTURES_DIR)
+        .into_iter()
+        .filter_map(Result::ok)
+        .map(|entry| entry.into_path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "yaml" || ext == "yml"))
+        .collect();
+    paths.sort();
+    paths
+}
+
+fn extract(source: &Path) -> Result<Extracted, String> {
+    // 单独复制到临时目录，使调用边只来自这个样例文件
+    let temp_dir = TempDir::new().map_err(|e| e.to_string())?;
+    let copied = temp_dir.path().join(source.file_name().unwrap());
+    fs::copy(source, &copied).map_err(|e| e.to_string())?;
+
+    let options = AnalyzeOptions {
+        storage_dir: Some(temp_dir.path().join(".codegraph_db")),
+        force_rebuild: true,
+        ..Default::default()
+    };
+    let graph = CodeParser::with_options(options).build_petgraph_code_graph(temp_dir.path())?;
+
+    let mut extracted = Extracted::default();
+    for function in graph.get_all_functions() {
+        if function.is_unresolved() {
+            continue;
+        }
+        extracted
+            .functions
+            .insert((function.name.clone(), function.line_start, function.language.clone()));
+        let params = function
+            .parameters
+            .iter()
+            .map(|p| match &p.type_ {
+                Some(type_) => format!("{}: {}", p.name, type_),
+                None => p.name.clone(),
+            })
+            .collect();
+        extracted
+            .signatures
+            .insert((function.name.clone(), function.signature.clone(), function.return_type.clone(), params));
+        for (callee, relation) in graph.get_callees(&function.id) {
+            if relation.is_resolved {
+                extracted.calls.insert((function.name.clone(), callee.name.clone()));
+            }
+        }
+    }
+
+    let symbols = TreeSitterParser::new()
+        .parse_file(&copied)
+        .map_err(|e| format!("{:?}", e))?;
+    for symbol in symbols {
+        let mut symbol = symbol.write();
+        if let Some(import) = symbol.as_any_mut().downcast_mut::<ImportDeclaration>() {
+            extracted.imports.insert(import.path_components.join("."));
+            continue;
+        }
+        if symbol.symbol_type() == SymbolType::StructDeclaration {
+            extracted.classes.insert(symbol.name().to_string());
+        }
+    }
+    Ok(extracted)
+}
+
+/// 检查一个 fixture，返回所有不满足的期望
+fn check(fixture_path: &Path) -> Vec<String> {
+    let fixture: Fixture = match fs::read_to_string(fixture_path)
+        .map_err(|e| e.to_string())
+        .and_then(|content| serde_yaml::from_str(&content).map_err(|e| e.to_string()))
+    {
+        Ok(fixture) => fixture,
+        Err(e) => return vec![format!("invalid fixture: {}", e)],
+    };
+    let source = fixture_path.parent().unwrap().join(&fixture.source);
+    if let Err(e) = get_ast_parser_by_filename(&source) {
+        return vec![format!("no parser registered for {}: {}", fixture.source, e.message)];
+    }
+    let extracted = match extract(&source) {
+        Ok(extracted) => extracted,
+        Err(e) => return vec![format!("analysis failed: {}", e)],
+    };
+    if std::env::var_os("CONFORMANCE_DUMP").is_some() {
+        println!("{}: {:#?}", fixture_path.display(), extracted);
+    }
+
+    let mut failures = Vec::new();
+    for expected in &fixture.functions {
+        let found: Vec<_> = extracted.functions.iter().filter(|(name, _, _)| *name == expected.name).collect();
+        if found.is_empty() {
+            failures.push(format!("missing function `{}`", expected.name));
+        } else if let Some(line) = expected.line {
+            if !found.iter().any(|(_, actual, _)| *actual == line) {
+                let lines: Vec<_> = found.iter().map(|(_, actual, _)| actual).collect();
+                failures.push(format!("function `{}` expected at line {}, found at {:?}", expected.name, line, lines));
+            }
+        }
+        if expected.signature.is_none() && expected.returns.is_none() && expected.params.is_none() {
+            continue;
+        }
+        let signatures: Vec<_> = extracted.signatures.iter().filter(|(name, ..)| *name == expected.name).collect();
+        let matches = signatures.iter().any(|(_, signature, returns, params)| {
+            expected.signature.as_ref().is_none_or(|expected| signature.as_ref() == Some(expected))
+                && expected.returns.as_ref().is_none_or(|expected| returns.as_ref() == Some(expected))
+                && expected.params.as_ref().is_none_or(|expected| params == expected)
+        });
+        if !matches && !signatures.is_empty() {
+            let found: Vec<_> = signatures.iter().map(|(_, signature, returns, params)| (signature, returns, params)).collect();
+            failures.push(format!("function `{}` has signature {:?}", expected.name, found));
+        }
+    }
+    for (name, _, language) in &extracted.functions {
+        if *language != fixture.language {
+            failures.push(format!("function `{}` has language `{}`, expected `{}`", name, language, fixture.language));
+        }
+    }
+    for class in &fixture.classes {
+        if !extracted.classes.contains(class) {
+            failures.push(format!("missing class `{}` (found {:?})", class, extracted.classes));
+        }
+    }
+    for call in &fixture.calls {
+        if !extracted.calls.contains(&(call.caller.clone(), call.callee.clone())) {
+            failures.push(format!("missing call edge `{}` -> `{}`", call.caller, call.callee));
+        }
+    }
+    for import in &fixture.imports {
+        if !extracted.imports.contains(import) {
+            failures.push(format!("missing import `{}` (found {:?})", import, extracted.imports));
+        }
+    }
+    failures
+}
+
+#[test]
+fn test_language_conformance() {
+    let fixtures = fixture_paths();
+    assert!(!fixtures.is_empty(), "no fixtures found under {}", FIXTURES_DIR);
+
+    let mut report = Vec::new();
+    for fixture in &fixtures {
+        let failures = check(fixture);
+        if !failures.is_empty() {
+            report.push(format!("{}:\n  - {}", fixture.display(), failures.join("\n  - ")));
+        }
+    }
+    assert!(report.is_empty(), "conformance failures:\n{}", report.join("\n"));
+}
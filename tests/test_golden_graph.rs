@@ -0,0 +1,150 @@
+//! 多语言样例项目的端到端测试
+//!
+//! `tests/test_repos/multi_language_project/` 混合了 Rust、Python、Java、TypeScript 和 Go 源文件，
+//! 每种语言的函数都跨文件调用。`expected.yaml` 列出必须解析出的跨文件调用边，
+//! `graph.golden.ndjson` 是整个项目导出的图（NDJSON 格式，路径相对项目目录，节点ID换成 `文件::函数名`）。
+//! 解析器改动使导出结果变化时测试失败；确认变化符合预期后，设置 `UPDATE_GOLDEN=1` 重新运行测试以更新 golden 文件。
+
+use std::collections::{BTreeSet, HashMap};
+use std::fs;
+use std::path::Path;
+
+use serde::Deserialize;
+use serde_json::Value;
+use tempfile::TempDir;
+
+use codegraph_cli::codegraph::parser::CodeParser;
+use codegraph_cli::codegraph::AnalyzeOptions;
+use codegraph_cli::services::graph_export::{export_graph, ExportFormat};
+
+const PROJECT_DIR: &str = "tests/test_repos/multi_language_project";
+const GOLDEN_FILE: &str = "graph.golden.ndjson";
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Expected {
+    edges: Vec<ExpectedEdge>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ExpectedEdge {
+    /// `文件::函数名`，文件相对项目目录
+    caller: String,
+    callee: String,
+}
+
+/// 把项目复制到临时目录再构建，避免在源码树中写入 `.codegraph_db`
+fn build_export() -> Result<(TempDir, String), String> {
+    let temp_dir = TempDir::new().map_err(|e| e.to_string())?;
+    for entry in walkdir::WalkDir::new(PROJECT_DIR).into_iter().filter_map(Result::ok) {
+        let relative = entry.path().strip_prefix(PROJECT_DIR).map_err(|e| e.to_string())?;
+        let target = temp_dir.path().join(relative);
+        if entry.file_type().is_dir() {
+            fs::create_dir_all(&target).map_err(|e| e.to_string())?;
+        } else if entry.file_name() != GOLDEN_FILE {
+            fs::copy(entry.path(), &target).map_err(|e| e.to_string())?;
+        }
+    }
+
+    let options = AnalyzeOptions {
+        storage_dir: Some(temp_dir.path().join(".codegraph_db")),
+        force_rebuild: true,
+        ..Default::default()
+    };
+    let graph = CodeParser::with_options(options).build_petgraph_code_graph(temp_dir.path())?;
+    let export = export_graph(&graph, ExportFormat::Ndjson)?;
+    Ok((temp_dir, export))
+}
+
+/// 去掉导出结果中与构建环境有关的部分：文件路径改为相对项目目录，稳定ID（由绝对路径计算）改为 `文件::函数名`，
+/// 函数记录在前、调用边记录在后，各自按内容排序
+fn normalize(export: &str, root: &Path) -> Vec<Value> {
+    let root = format!("{}/", root.display());
+    let relative = |value: &Value| value.as_str().unwrap_or_default().trim_start_matches(root.as_str()).to_string();
+
+    let mut records: Vec<Value> = export.lines().map(|line| serde_json::from_str(line).unwrap()).collect();
+    let mut names = HashMap::new();
+    for record in records.iter().filter(|record| record["type"] == "function") {
+        let file = relative(&record["file"]);
+        let name = record["name"].as_str().unwrap_or_default();
+        let label = if file.is_empty() { name.to_string() } else { format!("{}::{}", file, name) };
+        names.insert(record["id"].as_str().unwrap_or_default().to_string(), label);
+    }
+    for record in &mut records {
+        let object = record.as_object_mut().unwrap();
+        object.insert("file".into(), Value::String(relative(&object["file"])));
+        if let Some(id) = object.remove("id") {
+            object.insert("id".into(), Value::String(names[id.as_str().unwrap()].clone()));
+        }
+        for field in ["source", "target"] {
+            if let Some(id) = object.remove(field) {
+                object.insert(field.into(), Value::String(names[id.as_str().unwrap()].clone()));
+            }
+        }
+    }
+    // 导出时调用边按稳定ID排序，稳定ID随临时目录变化，这里改按替换后的内容排序
+    records.sort_by_key(|record| (record["type"] != "function", record.to_string()));
+    records
+}
+
+fn resolved_edges(records: &[Value]) -> BTreeSet<(String, String)> {
+    records
+        .iter()
+        .filter(|record| record["type"] == "edge" && record["resolved"] == true)
+        .map(|record| (record["source"].as_str().unwrap().to_string(), record["target"].as_str().unwrap().to_string()))
+        .collect()
+}
+
+#[test]
+fn test_multi_language_expected_edges() {
+    let (temp_dir, export) = build_export().expect("failed to build the multi-language project");
+    let records = normalize(&export, temp_dir.path());
+    let edges = resolved_edges(&records);
+
+    let expected: Expected =
+        serde_yaml::from_str(&fs::read_to_string(Path::new(PROJECT_DIR).join("expected.yaml")).unwrap()).unwrap();
+    let missing: Vec<_> = expected
+        .edges
+        .iter()
+        .filter(|edge| !edges.contains(&(edge.caller.clone(), edge.callee.clone())))
+        .map(|edge| format!("{} -> {}", edge.caller, edge.callee))
+        .collect();
+    assert!(missing.is_empty(), "missing cross-file edges:\n  {}\nresolved edges: {:#?}", missing.join("\n  "), edges);
+
+    let languages: BTreeSet<_> = records
+        .iter()
+        .filter(|record| record["type"] == "function" && record["namespace"] != "unresolved")
+        .filter_map(|record| record["language"].as_str())
+        .collect();
+    assert_eq!(languages, BTreeSet::from(["go", "java", "python", "rust", "typescript"]));
+}
+
+#[test]
+fn test_multi_language_golden_graph() {
+    let (temp_dir, export) = build_export().expect("failed to build the multi-language project");
+    let actual: String = normalize(&export, temp_dir.path())
+        .iter()
+        .map(|record| format!("{}\n", record))
+        .collect();
+
+    let golden_path = Path::new(PROJECT_DIR).join(GOLDEN_FILE);
+    if std::env::var_os("UPDATE_GOLDEN").is_some() {
+        fs::write(&golden_path, &actual).unwrap();
+        return;
+    }
+    let golden = fs::read_to_string(&golden_path)
+        .unwrap_or_else(|e| panic!("cannot read {}: {} (run with UPDATE_GOLDEN=1 to create it)", golden_path.display(), e));
+    if golden != actual {
+        let golden_lines: BTreeSet<_> = golden.lines().collect();
+        let actual_lines: BTreeSet<_> = actual.lines().collect();
+        let removed: Vec<_> = golden_lines.difference(&actual_lines).collect();
+        let added: Vec<_> = actual_lines.difference(&golden_lines).collect();
+        panic!(
+            "exported graph differs from {} (run with UPDATE_GOLDEN=1 if the change is intended)\nremoved: {:#?}\nadded: {:#?}",
+            golden_path.display(),
+            removed,
+            added
+        );
+    }
+}
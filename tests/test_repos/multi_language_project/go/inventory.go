@@ -0,0 +1,21 @@
+package main
+
+type Inventory struct {
+	items map[string]int
+}
+
+func NewInventory() *Inventory {
+	return &Inventory{items: map[string]int{}}
+}
+
+func (i *Inventory) Add(name string, count int) {
+	i.items[name] += clamp(count)
+}
+
+func (i *Inventory) Total() int {
+	total := 0
+	for _, count := range i.items {
+		total += count
+	}
+	return total
+}
@@ -0,0 +1,9 @@
+package main
+
+import "fmt"
+
+func main() {
+	inventory := NewInventory()
+	inventory.Add("apple", 3)
+	fmt.Println(describe(inventory))
+}
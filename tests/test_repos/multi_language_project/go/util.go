@@ -0,0 +1,14 @@
+package main
+
+import "fmt"
+
+func clamp(count int) int {
+	if count < 0 {
+		return 0
+	}
+	return count
+}
+
+func describe(inventory *Inventory) string {
+	return fmt.Sprintf("%d items", inventory.Total())
+}
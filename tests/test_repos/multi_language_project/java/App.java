@@ -0,0 +1,6 @@
+public class App {
+    public static void main(String[] args) {
+        UserService service = new UserService();
+        service.register("ada");
+    }
+}
@@ -0,0 +1,10 @@
+import java.util.ArrayList;
+import java.util.List;
+
+public class Repository {
+    private static final List<String> USERS = new ArrayList<>();
+
+    public static void save(String user) {
+        USERS.add(user);
+    }
+}
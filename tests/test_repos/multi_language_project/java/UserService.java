@@ -0,0 +1,10 @@
+public class UserService {
+    public void register(String name) {
+        String normalized = normalize(name);
+        Repository.save(normalized);
+    }
+
+    private String normalize(String name) {
+        return name.trim();
+    }
+}
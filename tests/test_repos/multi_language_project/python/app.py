@@ -0,0 +1,11 @@
+from services import create_user
+from storage import count_users
+
+
+def main():
+    create_user("ada")
+    print(count_users())
+
+
+if __name__ == "__main__":
+    main()
@@ -0,0 +1,11 @@
+from storage import save
+
+
+def normalize(name):
+    return name.strip().lower()
+
+
+def create_user(name):
+    user = {"name": normalize(name)}
+    save(user)
+    return user
@@ -0,0 +1,9 @@
+USERS = []
+
+
+def save(user):
+    USERS.append(user)
+
+
+def count_users():
+    return len(USERS)
@@ -0,0 +1,18 @@
+pub struct Rect {
+    pub width: f64,
+    pub height: f64,
+}
+
+impl Rect {
+    pub fn new(width: f64, height: f64) -> Self {
+        Rect { width, height }
+    }
+}
+
+pub fn area(rect: &Rect) -> f64 {
+    multiply(rect.width, rect.height)
+}
+
+fn multiply(a: f64, b: f64) -> f64 {
+    a * b
+}
@@ -0,0 +1,10 @@
+mod geometry;
+mod report;
+
+use geometry::{area, Rect};
+
+fn main() {
+    let rect = Rect::new(3.0, 4.0);
+    let total = area(&rect);
+    report::print_area("rect", total);
+}
@@ -0,0 +1,7 @@
+pub fn print_area(label: &str, value: f64) {
+    println!("{}", format_line(label, value));
+}
+
+fn format_line(label: &str, value: f64) -> String {
+    format!("{}: {:.2}", label, value)
+}
@@ -0,0 +1,9 @@
+import { roundPrice } from './format';
+
+export function checkout(prices: number[]): number {
+  return roundPrice(sum(prices));
+}
+
+function sum(prices: number[]): number {
+  return prices.reduce((a, b) => a + b, 0);
+}
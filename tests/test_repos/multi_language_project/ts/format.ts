@@ -0,0 +1,7 @@
+export function roundPrice(value: number): number {
+  return Math.round(value * 100) / 100;
+}
+
+export function formatPrice(value: number): string {
+  return '$' + roundPrice(value).toFixed(2);
+}
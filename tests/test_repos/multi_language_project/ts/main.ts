@@ -0,0 +1,9 @@
+import { checkout } from './cart';
+import { formatPrice } from './format';
+
+function main(): void {
+  const total = checkout([10, 20]);
+  console.log(formatPrice(total));
+}
+
+main();
@@ -0,0 +1,153 @@
+use std::fs;
+use std::path::Path;
+
+use proptest::prelude::*;
+use tempfile::TempDir;
+
+use codegraph_cli::codegraph::parser::CodeParser;
+use codegraph_cli::codegraph::types::PetCodeGraph;
+use codegraph_cli::codegraph::{AnalyzeOptions, TreeSitterParser};
+use codegraph_cli::services::build_skeleton;
+
+const BOM: char = '\u{feff}';
+
+/// 生成的源文件：每个函数的名称、起始行和结束行（从1开始）
+#[derive(Debug)]
+struct GeneratedSource {
+    text: String,
+    functions: Vec<(String, usize, usize)>,
+}
+
+/// 多字节文本：中文、西里尔字母、重音字符、emoji（含代理对和组合序列）
+fn multibyte_text() -> impl Strategy<Value = String> {
+    prop::collection::vec(
+        prop::sample::select(vec!["注释", "данные", "café", "🎉", "👩‍💻", "✓", "ß", "ascii", " ", "ñ", "𝔘"]),
+        0..6,
+    )
+    .prop_map(|parts| parts.concat())
+}
+
+/// 非 ASCII 的合法标识符（各语言都接受 Unicode 字母）
+fn identifier() -> impl Strategy<Value = String> {
+    (
+        prop::sample::select(vec!["fn", "数据", "größe", "значение", "café", "ñandú", "calc"]),
+        0u32..1000,
+    )
+        .prop_map(|(stem, n)| format!("{}_{}", stem, n))
+}
+
+fn source(
+    language: &'static str,
+) -> impl Strategy<Value = GeneratedSource> {
+    (
+        prop::collection::vec((identifier(), multibyte_text(), multibyte_text()), 1..5),
+        any::<bool>(),
+        any::<bool>(),
+    )
+        .prop_map(move |(functions, crlf, bom)| {
+            let mut lines: Vec<String> = Vec::new();
+            let mut declared = Vec::new();
+            for (index, (name, comment, literal)) in functions.into_iter().enumerate() {
+                // 同名函数会被合并，加序号保证唯一
+                let name = format!("{}_{}", name, index);
+                match language {
+                    "rust" => {
+                        lines.push(format!("// {}", comment));
+                        declared.push((name.clone(), lines.len() + 1, lines.len() + 4));
+                        lines.push(format!("pub fn {}() -> usize {{", name));
+                        lines.push(format!("    let s = \"{}\";", literal));
+                        lines.push("    s.len()".to_string());
+                        lines.push("}".to_string());
+                    }
+                    "python" => {
+                        lines.push(format!("# {}", comment));
+                        declared.push((name.clone(), lines.len() + 1, lines.len() + 3));
+                        lines.push(format!("def {}():", name));
+                        lines.push(format!("    s = \"{}\"", literal));
+                        lines.push("    return len(s)".to_string());
+                    }
+                    _ => {
+                        lines.push(format!("// {}", comment));
+                        declared.push((name.clone(), lines.len() + 1, lines.len() + 4));
+                        lines.push(format!("function {}() {{", name));
+                        lines.push(format!("    const s = \"{}\";", literal));
+                        lines.push("    return s.length;".to_string());
+                        lines.push("}".to_string());
+                    }
+                }
+                lines.push(String::new());
+            }
+            let newline = if crlf { "\r\n" } else { "\n" };
+            let mut text = lines.join(newline);
+            if bom {
+                text.insert(0, BOM);
+            }
+            GeneratedSource { text, functions: declared }
+        })
+}
+
+fn build(dir: &Path) -> PetCodeGraph {
+    let options = AnalyzeOptions {
+        storage_dir: Some(dir.join(".codegraph_db")),
+        force_rebuild: true,
+        ..Default::default()
+    };
+    CodeParser::with_options(options).build_petgraph_code_graph(dir).unwrap()
+}
+
+fn check_language(language: &'static str, file_name: &str, generated: &GeneratedSource) -> Result<(), TestCaseError> {
+    let temp_dir = TempDir::new().unwrap();
+    let path = temp_dir.path().join(file_name);
+    fs::write(&path, &generated.text).unwrap();
+
+    // 函数名和行号不受 CRLF、BOM 和多字节字符影响
+    let graph = build(temp_dir.path());
+    for (name, line_start, line_end) in &generated.functions {
+        let found = graph.find_functions_by_name(name);
+        prop_assert_eq!(found.len(), 1, "{} function {} not found", language, name);
+        prop_assert_eq!((found[0].line_start, found[0].line_end), (*line_start, *line_end), "{} function {}", language, name);
+    }
+
+    // 符号内容按行截取，不带 \r，且从声明开始
+    let code = fs::read_to_string(&path).unwrap();
+    let symbols = TreeSitterParser::new().parse_file(&path).unwrap();
+    for symbol in symbols {
+        let info = symbol.read().symbol_info_struct();
+        if !generated.functions.iter().any(|(name, _, _)| *name == info.name) {
+            continue;
+        }
+        let content = info.get_content(&code).unwrap();
+        prop_assert!(!content.contains('\r'), "{:?}", content);
+        prop_assert!(content.lines().next().unwrap_or_default().contains(&info.name), "{:?}", content);
+        let declaration = info.get_declaration_content(&code).unwrap();
+        prop_assert!(!declaration.contains('\r'), "{:?}", declaration);
+    }
+
+    // 骨架生成不会在多字节字符处越界
+    let (_, skeleton) = build_skeleton(&path, &code).map_err(TestCaseError::fail)?;
+    prop_assert!(!skeleton.contains('\r'));
+    prop_assert!(!skeleton.contains(BOM));
+    for (name, _, _) in &generated.functions {
+        prop_assert!(skeleton.contains(name.as_str()), "{} missing from skeleton:\n{}", name, skeleton);
+    }
+    Ok(())
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(48))]
+
+    #[test]
+    fn rust_sources_keep_accurate_positions(generated in source("rust")) {
+        check_language("rust", "lib.rs", &generated)?;
+    }
+
+    #[test]
+    fn python_sources_keep_accurate_positions(generated in source("python")) {
+        check_language("python", "module.py", &generated)?;
+    }
+
+    #[test]
+    fn javascript_sources_keep_accurate_positions(generated in source("javascript")) {
+        check_language("javascript", "index.js", &generated)?;
+    }
+}